@@ -10,13 +10,14 @@ pub use request::{
     AutoRevokeRequest, AutoRevokeScenario, BadgeQueryFilter, BatchGrantRequest, BatchRevokeRequest,
     BatchTaskFilter, CreateBadgeRequest, CreateCategoryRequest, CreateRuleRequest,
     CreateSeriesRequest, GrantLogFilter, ManualGrantRequest, ManualRevokeRequest,
-    OperationLogFilter, PaginationParams, RecipientType, TestRuleDefinitionRequest, TimeRangeParams,
+    OperationLogFilter, PaginationParams, RecipientType, RuleVersionDiffParams,
+    TestRuleDefinitionRequest, TimeRangeParams,
     UpdateBadgeRequest, UpdateCategoryRequest, UpdateRuleRequest, UpdateSeriesRequest,
 };
 
 pub use response::{
     ApiResponse, BadgeAdminDto, BadgeListItemDto, BadgeRankingDto, BadgeStatsDto, BatchTaskDto,
     CategoryDto, CreatedResponse, DeletedResponse, GrantLogDto, OperationLogDto, PageResponse,
-    RuleDto, SeriesDto, StatsOverview, TrendDataPoint, UserBadgeAdminDto, UserBadgeViewDto,
+    RuleDto, RuleFieldChangeDto, RuleVersionDiffDto, RuleVersionDto, SeriesDto, StatsOverview, TrendDataPoint, UserBadgeAdminDto, UserBadgeViewDto,
     UserLedgerDto, UserRedemptionDto, UserStatsDto,
 };
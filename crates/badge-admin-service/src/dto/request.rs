@@ -2,7 +2,9 @@
 //!
 //! 所有 REST API 的请求参数和请求体结构
 
-use badge_management::{BadgeAssets, BadgeStatus, BadgeType, SourceType, ValidityConfig};
+use badge_management::{
+    BadgeAssets, BadgeLevel, BadgeStatus, BadgeType, SourceType, ValidityConfig,
+};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use validator::Validate;
@@ -69,6 +71,12 @@ pub struct CreateBadgeRequest {
     pub assets: BadgeAssets,
     pub validity_config: ValidityConfig,
     pub max_supply: Option<i32>,
+    /// 等级定义，为空表示非分级徽章
+    #[serde(default)]
+    pub levels: Vec<BadgeLevel>,
+    /// 徽章积分，用于积分排行榜，默认 1 分
+    #[validate(range(min = 0, message = "徽章积分不能为负数"))]
+    pub score: Option<i32>,
 }
 
 /// 更新徽章请求
@@ -86,6 +94,11 @@ pub struct UpdateBadgeRequest {
     pub validity_config: Option<ValidityConfig>,
     pub max_supply: Option<i32>,
     pub status: Option<BadgeStatus>,
+    /// 等级定义，传空数组表示取消分级
+    pub levels: Option<Vec<BadgeLevel>>,
+    /// 徽章积分，已有用户持有时不可修改
+    #[validate(range(min = 0, message = "徽章积分不能为负数"))]
+    pub score: Option<i32>,
 }
 
 /// 创建规则请求
@@ -131,6 +144,16 @@ pub struct UpdateRuleRequest {
     pub enabled: Option<bool>,
 }
 
+/// 规则版本对比参数
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleVersionDiffParams {
+    /// 基准版本
+    pub from: i32,
+    /// 对比版本
+    pub to: i32,
+}
+
 /// 测试规则定义请求（无需持久化，仅做模拟评估）
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -427,9 +450,12 @@ mod tests {
                 image_url: None,
                 animation_url: None,
                 disabled_icon_url: None,
+                levels: vec![],
             },
             validity_config: ValidityConfig::default(),
             max_supply: None,
+            levels: vec![],
+            score: None,
         };
 
         assert!(request.validate().is_err());
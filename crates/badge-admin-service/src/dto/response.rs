@@ -3,7 +3,7 @@
 //! 所有 REST API 的响应体结构
 
 use badge_management::{
-    BadgeAssets, BadgeStatus, BadgeType, CategoryStatus, ValidityConfig,
+    BadgeAssets, BadgeLevel, BadgeStatus, BadgeType, CategoryStatus, ValidityConfig,
 };
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -156,6 +156,10 @@ pub struct BadgeAdminDto {
     pub max_supply: Option<i32>,
     pub issued_count: i32,
     pub status: BadgeStatus,
+    /// 等级定义，为空表示非分级徽章
+    pub levels: Vec<BadgeLevel>,
+    /// 徽章积分
+    pub score: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -194,10 +198,59 @@ pub struct RuleDto {
     pub global_quota: Option<i32>,
     pub global_granted: i32,
     pub enabled: bool,
+    /// 当前生效的版本号
+    pub current_version: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// 规则版本快照 DTO
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleVersionDto {
+    pub rule_id: i64,
+    pub version: i32,
+    pub event_type: Option<String>,
+    pub rule_code: Option<String>,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub rule_json: serde_json::Value,
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub max_count_per_user: Option<i32>,
+    pub global_quota: Option<i32>,
+    /// 变更类型：create / update / rollback
+    pub change_type: String,
+    /// 回滚来源版本号
+    pub source_version: Option<i32>,
+    pub created_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// 是否为规则当前生效的版本
+    pub is_live: bool,
+}
+
+/// 规则属性变更（时间窗口、配额等非规则树字段）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleFieldChangeDto {
+    pub field: String,
+    pub before: serde_json::Value,
+    pub after: serde_json::Value,
+}
+
+/// 规则版本差异 DTO
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleVersionDiffDto {
+    pub rule_id: i64,
+    pub from_version: i32,
+    pub to_version: i32,
+    /// 规则属性变更
+    pub field_changes: Vec<RuleFieldChangeDto>,
+    /// 规则树节点变更
+    pub node_changes: Vec<rule_engine::NodeChange>,
+}
+
 /// 发放记录响应 DTO
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -294,6 +347,10 @@ pub struct UserBadgeAdminDto {
     pub status: String,
     pub acquired_at: DateTime<Utc>,
     pub expires_at: Option<DateTime<Utc>>,
+    /// 是否置顶在用户徽章墙上
+    pub pinned: bool,
+    /// 置顶展示顺序（从 1 开始），未置顶为 null
+    pub pin_order: Option<i32>,
 }
 
 /// 用户兑换记录 DTO
@@ -50,6 +50,8 @@ pub enum AdminError {
     // 业务错误
     #[error("徽章已发布，无法删除")]
     BadgeAlreadyPublished,
+    #[error("徽章已有用户持有，无法修改积分")]
+    BadgeScoreLocked,
     #[error("规则 JSON 格式无效: {0}")]
     InvalidRuleJson(String),
     #[error("文件处理失败: {0}")]
@@ -58,6 +60,8 @@ pub enum AdminError {
     InsufficientStock,
     #[error("用户徽章数量不足")]
     InsufficientUserBadge,
+    #[error("兑换次数已达上限: rule_id={rule_id}, limit_type={limit_type}")]
+    RedemptionFrequencyLimitReached { rule_id: i64, limit_type: String },
 
     // 系统错误
     #[error("数据库错误: {0}")]
@@ -91,12 +95,15 @@ impl AdminError {
             | Self::BenefitNotFound(_)
             | Self::NotFound(_) => StatusCode::NOT_FOUND,
 
-            Self::BadgeAlreadyPublished | Self::InsufficientStock | Self::InsufficientUserBadge => {
-                StatusCode::CONFLICT
-            }
+            Self::BadgeAlreadyPublished
+            | Self::BadgeScoreLocked
+            | Self::InsufficientStock
+            | Self::InsufficientUserBadge => StatusCode::CONFLICT,
 
             Self::FileProcessingError(_) => StatusCode::UNPROCESSABLE_ENTITY,
 
+            Self::RedemptionFrequencyLimitReached { .. } => StatusCode::TOO_MANY_REQUESTS,
+
             Self::Database(_) | Self::Redis(_) | Self::Internal(_) => {
                 StatusCode::INTERNAL_SERVER_ERROR
             }
@@ -125,10 +132,12 @@ impl AdminError {
             Self::BenefitNotFound(_) => "BENEFIT_NOT_FOUND",
             Self::NotFound(_) => "NOT_FOUND",
             Self::BadgeAlreadyPublished => "BADGE_ALREADY_PUBLISHED",
+            Self::BadgeScoreLocked => "BADGE_SCORE_LOCKED",
             Self::InvalidRuleJson(_) => "INVALID_RULE_JSON",
             Self::FileProcessingError(_) => "FILE_PROCESSING_ERROR",
             Self::InsufficientStock => "INSUFFICIENT_STOCK",
             Self::InsufficientUserBadge => "INSUFFICIENT_USER_BADGE",
+            Self::RedemptionFrequencyLimitReached { .. } => "FREQUENCY_LIMIT_REACHED",
             Self::Database(_) => "DATABASE_ERROR",
             Self::Redis(_) => "REDIS_ERROR",
             Self::Internal(_) => "INTERNAL_ERROR",
@@ -194,13 +203,24 @@ impl From<badge_management::BadgeError> for AdminError {
             // 兑换相关业务错误：映射为适当的 HTTP 状态码而非 500
             badge_management::BadgeError::BenefitNotFound(id) => Self::BenefitNotFound(id),
             badge_management::BadgeError::RedemptionRuleNotFound(id) => Self::RuleNotFound(id),
+            badge_management::BadgeError::RedemptionOrderNotFound(order_no) => {
+                Self::NotFound(format!("兑换订单不存在: {}", order_no))
+            }
+            badge_management::BadgeError::RedemptionFrequencyLimitReached {
+                rule_id,
+                limit_type,
+            } => Self::RedemptionFrequencyLimitReached {
+                rule_id,
+                limit_type,
+            },
             badge_management::BadgeError::RedemptionRuleInactive(_)
             | badge_management::BadgeError::BenefitOutOfStock(_)
             | badge_management::BadgeError::InsufficientBadges { .. }
             | badge_management::BadgeError::UserBadgeNotFound { .. }
-            | badge_management::BadgeError::RedemptionFrequencyLimitReached { .. }
             | badge_management::BadgeError::DuplicateRedemption(_)
-            | badge_management::BadgeError::InvalidOrderStatus { .. } => {
+            | badge_management::BadgeError::InvalidOrderStatus { .. }
+            | badge_management::BadgeError::CancelWindowExpired { .. }
+            | badge_management::BadgeError::BenefitRevokeFailed { .. } => {
                 Self::Validation(err.to_string())
             }
             other => Self::Internal(other.to_string()),
@@ -243,12 +263,15 @@ mod tests {
             (AdminError::NotFound("some resource".into()), StatusCode::NOT_FOUND, "NOT_FOUND"),
             // 业务冲突类：409 表示请求合法但与当前状态冲突
             (AdminError::BadgeAlreadyPublished, StatusCode::CONFLICT, "BADGE_ALREADY_PUBLISHED"),
+            (AdminError::BadgeScoreLocked, StatusCode::CONFLICT, "BADGE_SCORE_LOCKED"),
             (AdminError::InsufficientStock, StatusCode::CONFLICT, "INSUFFICIENT_STOCK"),
             (AdminError::InsufficientUserBadge, StatusCode::CONFLICT, "INSUFFICIENT_USER_BADGE"),
             // 请求数据格式错误
             (AdminError::InvalidRuleJson("unexpected EOF".into()), StatusCode::BAD_REQUEST, "INVALID_RULE_JSON"),
             // 文件处理用 422，因为请求格式合法但内容无法处理
             (AdminError::FileProcessingError("corrupt image".into()), StatusCode::UNPROCESSABLE_ENTITY, "FILE_PROCESSING_ERROR"),
+            // 兑换频率超限用 429，客户端据错误码提示用户稍后再试
+            (AdminError::RedemptionFrequencyLimitReached { rule_id: 1, limit_type: "per_day".into() }, StatusCode::TOO_MANY_REQUESTS, "FREQUENCY_LIMIT_REACHED"),
             // 系统级错误：统一 500，防止内部实现细节泄露
             (AdminError::Redis("connection refused".into()), StatusCode::INTERNAL_SERVER_ERROR, "REDIS_ERROR"),
             (AdminError::Internal("unexpected state".into()), StatusCode::INTERNAL_SERVER_ERROR, "INTERNAL_ERROR"),
@@ -389,6 +412,7 @@ mod tests {
             (AdminError::InvalidRuleJson("eof".into()), StatusCode::BAD_REQUEST, "INVALID_RULE_JSON"),
             (AdminError::FileProcessingError("corrupt".into()), StatusCode::UNPROCESSABLE_ENTITY, "FILE_PROCESSING_ERROR"),
             (AdminError::BadgeAlreadyPublished, StatusCode::CONFLICT, "BADGE_ALREADY_PUBLISHED"),
+            (AdminError::BadgeScoreLocked, StatusCode::CONFLICT, "BADGE_SCORE_LOCKED"),
             (AdminError::InsufficientStock, StatusCode::CONFLICT, "INSUFFICIENT_STOCK"),
             (AdminError::InsufficientUserBadge, StatusCode::CONFLICT, "INSUFFICIENT_USER_BADGE"),
             (AdminError::Redis("down".into()), StatusCode::INTERNAL_SERVER_ERROR, "REDIS_ERROR"),
@@ -530,6 +554,16 @@ mod tests {
             AdminError::Validation(msg) => assert!(msg.contains("badge name too long")),
             other => panic!("期望 Validation，实际: {:?}", other),
         }
+
+        // RedemptionFrequencyLimitReached -> 独立变体（429），保留规则和限制类型
+        let err: AdminError = badge_management::BadgeError::RedemptionFrequencyLimitReached {
+            rule_id: 8,
+            limit_type: "per_week".into(),
+        }
+        .into();
+        assert_eq!(err.status_code(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(err.error_code(), "FREQUENCY_LIMIT_REACHED");
+        assert!(err.to_string().contains("per_week"));
     }
 
     /// 未在映射表中显式列出的 BadgeError 变体应回退到 AdminError::Internal，
@@ -586,14 +620,14 @@ mod tests {
 
     // ---- 变体完备性校验 ----
 
-    /// 确保测试用例覆盖了所有 22 个变体（不含 Database，因为它需要 sqlx::Error 无法简单构造）。
+    /// 确保测试用例覆盖了所有 25 个变体（不含 Database，因为它需要 sqlx::Error 无法简单构造）。
     /// 如果新增了变体但忘记加测试，这个计数断言会失败。
     #[test]
     fn test_all_variants_covered_in_table() {
-        // 共 24 个变体，Database 依赖 sqlx::Error 不易在表中构造，故排除 1 个 → 23
+        // 共 26 个变体，Database 依赖 sqlx::Error 不易在表中构造，故排除 1 个 → 25
         assert_eq!(
             all_error_variants().len(),
-            23,
+            25,
             "表驱动用例数量与变体总数不一致，可能新增了变体但未更新测试"
         );
     }
@@ -13,7 +13,7 @@ use tracing::info;
 use validator::Validate;
 
 use crate::{
-    BadgeAssets, BadgeStatus, BadgeType,
+    BadgeAssets, BadgeLevel, BadgeStatus, BadgeType,
     dto::{
         ApiResponse, BadgeAdminDto, BadgeQueryFilter, CreateBadgeRequest, PageResponse,
         PaginationParams, UpdateBadgeRequest,
@@ -40,6 +40,8 @@ struct BadgeFullRow {
     max_supply: Option<i64>,
     issued_count: i64,
     status: BadgeStatus,
+    levels: Value,
+    score: i32,
     created_at: DateTime<Utc>,
     updated_at: DateTime<Utc>,
 }
@@ -62,11 +64,14 @@ impl From<BadgeFullRow> for BadgeAdminDto {
                 image_url: None,
                 animation_url: None,
                 disabled_icon_url: None,
+                levels: vec![],
             }),
             validity_config: serde_json::from_value(row.validity_config).unwrap_or_default(),
             max_supply: row.max_supply.map(|v| v as i32),
             issued_count: row.issued_count as i32,
             status: row.status,
+            levels: serde_json::from_value(row.levels).unwrap_or_default(),
+            score: row.score,
             created_at: row.created_at,
             updated_at: row.updated_at,
         }
@@ -91,6 +96,8 @@ const BADGE_FULL_SQL: &str = r#"
         b.max_supply,
         b.issued_count,
         b.status,
+        b.levels,
+        b.score,
         b.created_at,
         b.updated_at
     FROM badges b
@@ -111,6 +118,23 @@ async fn fetch_badge_by_id(pool: &sqlx::PgPool, id: i64) -> Result<BadgeAdminDto
     Ok(row.into())
 }
 
+/// 校验分级配置：等级定义有序，等级资源只能引用已定义的等级
+fn validate_tiers(levels: &[BadgeLevel], assets: &BadgeAssets) -> Result<(), AdminError> {
+    badge_management::validate_levels(levels).map_err(AdminError::Validation)?;
+
+    if let Some(orphan) = assets
+        .levels
+        .iter()
+        .find(|a| !levels.iter().any(|l| l.level == a.level))
+    {
+        return Err(AdminError::Validation(format!(
+            "资源配置引用了未定义的等级 {}",
+            orphan.level
+        )));
+    }
+    Ok(())
+}
+
 /// 创建徽章
 ///
 /// POST /api/admin/badges
@@ -131,16 +155,19 @@ pub async fn create_badge(
         return Err(AdminError::SeriesNotFound(req.series_id));
     }
 
+    validate_tiers(&req.levels, &req.assets)?;
+
     let assets_json = serde_json::to_value(&req.assets)
         .map_err(|e| AdminError::Internal(format!("Failed to serialize assets: {}", e)))?;
+    let levels_json = serde_json::to_value(&req.levels)?;
     let validity_json = serde_json::to_value(&req.validity_config)
         .map_err(|e| AdminError::Internal(format!("Failed to serialize validity_config: {}", e)))?;
 
     // 新建徽章默认草稿状态
     let row: (i64,) = sqlx::query_as(
         r#"
-        INSERT INTO badges (series_id, badge_type, name, code, description, obtain_description, assets, validity_config, max_supply, status)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, 'draft')
+        INSERT INTO badges (series_id, badge_type, name, code, description, obtain_description, assets, validity_config, max_supply, levels, score, status)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, COALESCE($11, 1), 'draft')
         RETURNING id
         "#,
     )
@@ -153,6 +180,8 @@ pub async fn create_badge(
     .bind(&assets_json)
     .bind(&validity_json)
     .bind(req.max_supply.map(|v| v as i64))
+    .bind(&levels_json)
+    .bind(req.score)
     .fetch_one(&state.pool)
     .await?;
 
@@ -281,6 +310,15 @@ pub async fn update_badge(
         return Err(AdminError::BadgeNotFound(id));
     }
 
+    // 等级定义或资源变更时，结合当前配置校验分级的完整性
+    if req.levels.is_some() || req.assets.is_some() {
+        let current = fetch_badge_by_id(&state.pool, id).await?;
+        validate_tiers(
+            req.levels.as_deref().unwrap_or(&current.levels),
+            req.assets.as_ref().unwrap_or(&current.assets),
+        )?;
+    }
+
     // 序列化可选字段
     let assets_json = req
         .assets
@@ -296,6 +334,8 @@ pub async fn update_badge(
         .transpose()
         .map_err(|e| AdminError::Internal(format!("Failed to serialize validity_config: {}", e)))?;
 
+    let levels_json = req.levels.as_ref().map(serde_json::to_value).transpose()?;
+
     let status_str = req.status.map(|s| {
         serde_json::to_value(s)
             .ok()
@@ -306,8 +346,10 @@ pub async fn update_badge(
     // 审计快照：记录变更前状态
     audit_ctx.snapshot(&state.pool, "badges", id).await;
 
-    // 使用 COALESCE 实现部分更新，NULL 参数表示不更新该字段
-    sqlx::query(
+    // 使用 COALESCE 实现部分更新，NULL 参数表示不更新该字段。
+    // 排行榜按发放时的积分增量累积，周榜和月榜无法按新积分重算，
+    // 因此已有用户持有的徽章不允许修改积分，条件与更新在同一语句内判断
+    let result = sqlx::query(
         r#"
         UPDATE badges
         SET
@@ -319,8 +361,17 @@ pub async fn update_badge(
             validity_config = COALESCE($7, validity_config),
             max_supply = COALESCE($8, max_supply),
             status = COALESCE($9, status),
+            levels = COALESCE($10, levels),
+            score = COALESCE($11, score),
             updated_at = NOW()
         WHERE id = $1
+          AND (
+              $11::INT IS NULL
+              OR $11 = score
+              OR NOT EXISTS (
+                  SELECT 1 FROM user_badges WHERE badge_id = $1 AND quantity > 0
+              )
+          )
         "#,
     )
     .bind(id)
@@ -332,9 +383,15 @@ pub async fn update_badge(
     .bind(&validity_json)
     .bind(req.max_supply.map(|v| v as i64))
     .bind(&status_str)
+    .bind(&levels_json)
+    .bind(req.score)
     .execute(&state.pool)
     .await?;
 
+    if result.rows_affected() == 0 {
+        return Err(AdminError::BadgeScoreLocked);
+    }
+
     info!(badge_id = id, "Badge updated");
 
     let dto = fetch_badge_by_id(&state.pool, id).await?;
@@ -550,9 +607,12 @@ mod tests {
                 image_url: None,
                 animation_url: None,
                 disabled_icon_url: None,
+                levels: vec![],
             },
             validity_config: ValidityConfig::default(),
             max_supply: Some(100),
+            levels: vec![],
+            score: Some(5),
         };
         assert!(valid.validate().is_ok());
 
@@ -568,9 +628,12 @@ mod tests {
                 image_url: None,
                 animation_url: None,
                 disabled_icon_url: None,
+                levels: vec![],
             },
             validity_config: ValidityConfig::default(),
             max_supply: None,
+            levels: vec![],
+            score: None,
         };
         assert!(invalid.validate().is_err());
     }
@@ -593,6 +656,8 @@ mod tests {
             max_supply: Some(100),
             issued_count: 10,
             status: BadgeStatus::Draft,
+            levels: serde_json::json!([{"level": 1, "name": "铜", "threshold": 1}]),
+            score: 1,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         };
@@ -603,5 +668,38 @@ mod tests {
         assert_eq!(dto.max_supply, Some(100));
         assert_eq!(dto.issued_count, 10);
         assert_eq!(dto.status, BadgeStatus::Draft);
+        assert_eq!(dto.levels.len(), 1);
+        assert_eq!(dto.levels[0].name, "铜");
+    }
+
+    #[test]
+    fn test_validate_tiers() {
+        let levels = vec![
+            BadgeLevel {
+                level: 1,
+                name: "铜".to_string(),
+                threshold: 1,
+            },
+            BadgeLevel {
+                level: 2,
+                name: "银".to_string(),
+                threshold: 5,
+            },
+        ];
+        let mut assets: BadgeAssets = serde_json::from_value(serde_json::json!({
+            "iconUrl": "https://example.com/icon.png",
+            "levels": [{"level": 2, "iconUrl": "https://example.com/silver.png"}]
+        }))
+        .unwrap();
+        assert!(validate_tiers(&levels, &assets).is_ok());
+
+        // 资源引用了未定义的等级
+        assets.levels[0].level = 3;
+        assert!(validate_tiers(&levels, &assets).is_err());
+
+        // 等级门槛未递增
+        let mut invalid = levels.clone();
+        invalid[1].threshold = 1;
+        assert!(validate_tiers(&invalid, &BadgeAssets { levels: vec![], ..assets }).is_err());
     }
 }
@@ -8,6 +8,7 @@ use axum::{
     http::StatusCode,
 };
 use crate::middleware::AuditContext;
+use badge_shared::observability::middleware::grpc::traced_request;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use tracing::info;
@@ -35,12 +36,19 @@ pub struct CreateDependencyRequest {
     pub priority: i32,
     /// 依赖组 ID，同组内的条件是 AND 关系，不同组是 OR 关系
     pub dependency_group_id: String,
+    /// 依赖徽章被撤销或过期时的处理策略：keep（保留）、revoke（撤销）、suspend（暂停）
+    #[serde(default = "default_revoke_policy")]
+    pub revoke_policy: String,
 }
 
 fn default_quantity() -> i32 {
     1
 }
 
+fn default_revoke_policy() -> String {
+    "keep".to_string()
+}
+
 /// 依赖关系响应
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -54,6 +62,7 @@ pub struct DependencyResponse {
     pub auto_trigger: bool,
     pub priority: i32,
     pub dependency_group_id: String,
+    pub revoke_policy: String,
     pub enabled: bool,
     pub created_at: String,
     pub updated_at: String,
@@ -62,6 +71,27 @@ pub struct DependencyResponse {
 /// 有效的依赖类型
 const VALID_DEPENDENCY_TYPES: [&str; 3] = ["prerequisite", "consume", "exclusive"];
 
+/// 有效的撤销策略
+const VALID_REVOKE_POLICIES: [&str; 3] = ["keep", "revoke", "suspend"];
+
+/// 校验撤销策略：只有前置条件依赖才会在撤销时反向级联
+fn validate_revoke_policy(policy: &str, dependency_type: &str) -> Result<(), AdminError> {
+    if !VALID_REVOKE_POLICIES.contains(&policy) {
+        return Err(AdminError::Validation(format!(
+            "无效的撤销策略: {}，有效值为: keep, revoke, suspend",
+            policy
+        )));
+    }
+
+    if policy != "keep" && dependency_type != "prerequisite" {
+        return Err(AdminError::Validation(
+            "只有 prerequisite 类型的依赖可以设置 revoke 或 suspend 策略".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
 /// 创建依赖关系
 ///
 /// POST /api/admin/badges/{badge_id}/dependencies
@@ -90,6 +120,8 @@ pub async fn create_dependency(
         ));
     }
 
+    validate_revoke_policy(&req.revoke_policy, &req.dependency_type)?;
+
     // 验证必填数量
     if req.required_quantity < 1 {
         return Err(AdminError::Validation(
@@ -123,6 +155,7 @@ pub async fn create_dependency(
         auto_trigger: req.auto_trigger,
         priority: req.priority,
         dependency_group_id: req.dependency_group_id.clone(),
+        revoke_policy: req.revoke_policy.clone(),
     };
 
     let row = dependency_repo.create(&create_req).await?;
@@ -211,7 +244,7 @@ pub async fn refresh_dependency_cache(
         let cb = &state.badge_mgmt_circuit_breaker;
         let result = cb.call(|| {
             let mut c = client.clone();
-            async move { c.refresh_dependency_cache(RefreshDependencyCacheRequest {}).await }
+            async move { c.refresh_dependency_cache(traced_request(RefreshDependencyCacheRequest {})).await }
         }).await;
 
         match result {
@@ -253,7 +286,7 @@ pub async fn refresh_auto_benefit_cache(
         let cb = &state.badge_mgmt_circuit_breaker;
         let result = cb.call(|| {
             let mut c = client.clone();
-            async move { c.refresh_auto_benefit_cache(RefreshAutoBenefitCacheRequest {}).await }
+            async move { c.refresh_auto_benefit_cache(traced_request(RefreshAutoBenefitCacheRequest {})).await }
         }).await;
 
         match result {
@@ -305,6 +338,7 @@ impl From<badge_management::repository::BadgeDependencyRow> for DependencyRespon
             auto_trigger: row.auto_trigger,
             priority: row.priority,
             dependency_group_id: row.dependency_group_id,
+            revoke_policy: row.revoke_policy,
             enabled: row.enabled,
             created_at: row.created_at.to_rfc3339(),
             updated_at: row.updated_at.to_rfc3339(),
@@ -328,6 +362,8 @@ pub struct UpdateDependencyRequest {
     pub priority: Option<i32>,
     /// 依赖组 ID
     pub dependency_group_id: Option<String>,
+    /// 撤销策略
+    pub revoke_policy: Option<String>,
     /// 是否启用
     pub enabled: Option<bool>,
 }
@@ -371,6 +407,21 @@ pub async fn update_dependency(
 
     let dependency_repo = state.dependency_repo()?;
 
+    // 部分更新时按更新后的依赖类型和撤销策略组合校验，
+    // 避免只改类型把已有的 revoke/suspend 策略留在非前置条件依赖上
+    if req.dependency_type.is_some() || req.revoke_policy.is_some() {
+        let existing = dependency_repo
+            .get_by_id(id)
+            .await?
+            .ok_or(AdminError::DependencyNotFound(id))?;
+        validate_revoke_policy(
+            req.revoke_policy.as_deref().unwrap_or(&existing.revoke_policy),
+            req.dependency_type
+                .as_deref()
+                .unwrap_or(&existing.dependency_type),
+        )?;
+    }
+
     // 审计快照：记录变更前状态
     audit_ctx.snapshot(&state.pool, "badge_dependencies", id).await;
 
@@ -382,6 +433,7 @@ pub async fn update_dependency(
         auto_trigger: req.auto_trigger,
         priority: req.priority,
         dependency_group_id: req.dependency_group_id,
+        revoke_policy: req.revoke_policy,
         enabled: req.enabled,
     };
 
@@ -636,4 +688,15 @@ mod tests {
     fn test_default_quantity() {
         assert_eq!(default_quantity(), 1);
     }
+
+    #[test]
+    fn test_validate_revoke_policy() {
+        assert_eq!(default_revoke_policy(), "keep");
+        assert!(validate_revoke_policy("keep", "consume").is_ok());
+        assert!(validate_revoke_policy("suspend", "prerequisite").is_ok());
+        assert!(validate_revoke_policy("revoke", "prerequisite").is_ok());
+        assert!(validate_revoke_policy("revoke", "exclusive").is_err());
+        assert!(validate_revoke_policy("revoke", "consume").is_err());
+        assert!(validate_revoke_policy("delete", "prerequisite").is_err());
+    }
 }
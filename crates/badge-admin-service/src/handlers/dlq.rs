@@ -0,0 +1,471 @@
+//! 死信队列 API 处理器
+//!
+//! 查看、重放和清理持久化的死信消息。重放只是把死信置为立即到期，
+//! 实际发送由后台的死信重放调度器完成；可在单条重放时修改消息内容，
+//! 用于修正导致处理失败的脏数据。
+
+use axum::{
+    Extension, Json,
+    extract::{Path, Query, State},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use tracing::info;
+
+use crate::{
+    dto::{ApiResponse, PageResponse, PaginationParams},
+    error::AdminError,
+    middleware::AuditContext,
+    state::AppState,
+};
+
+/// 可按状态筛选的死信状态
+const DLQ_STATUSES: [&str; 4] = ["pending", "replaying", "replayed", "exhausted"];
+
+// ═══════════════════════════════════════════════════════════════════════════
+// DTO 定义
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// 死信消息 DTO
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadLetterDto {
+    pub id: i64,
+    pub message_id: String,
+    pub source_topic: String,
+    pub source_service: String,
+    /// 消息内容，仅详情接口返回；非 JSON 内容以字符串返回
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payload: Option<serde_json::Value>,
+    pub error: String,
+    pub retry_count: i32,
+    pub max_retries: i32,
+    /// pending / replaying / replayed / exhausted
+    pub status: String,
+    pub first_failed_at: DateTime<Utc>,
+    pub last_failed_at: DateTime<Utc>,
+    pub next_retry_at: Option<DateTime<Utc>>,
+    pub replayed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 死信查询过滤
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadLetterFilter {
+    pub source_service: Option<String>,
+    pub source_topic: Option<String>,
+    pub status: Option<String>,
+    /// 错误信息模糊匹配
+    pub error: Option<String>,
+    pub message_id: Option<String>,
+}
+
+/// 重放单条死信请求
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayDeadLetterRequest {
+    /// 修改后的消息内容，未提供时按原内容重放
+    pub payload: Option<serde_json::Value>,
+}
+
+/// 批量重放死信请求
+///
+/// 指定 `ids` 时只重放这些死信，否则重放匹配过滤条件的全部死信。
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkReplayDeadLetterRequest {
+    pub ids: Option<Vec<i64>>,
+    #[serde(flatten)]
+    pub filter: DeadLetterFilter,
+}
+
+/// 清理死信请求，至少需要一个条件
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PurgeDeadLetterRequest {
+    pub ids: Option<Vec<i64>>,
+    pub status: Option<String>,
+    pub source_service: Option<String>,
+    /// 只清理最近一次失败早于该时间的死信
+    pub before: Option<DateTime<Utc>>,
+}
+
+/// 批量操作结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadLetterBatchResult {
+    pub affected: u64,
+}
+
+/// 按来源服务和状态统计的死信数量
+#[derive(Debug, Clone, Serialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadLetterStatsDto {
+    pub source_service: String,
+    pub status: String,
+    pub count: i64,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// 数据库行映射
+// ═══════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, FromRow)]
+struct DeadLetterRow {
+    id: i64,
+    message_id: String,
+    source_topic: String,
+    source_service: String,
+    payload: String,
+    error: String,
+    retry_count: i32,
+    max_retries: i32,
+    status: String,
+    first_failed_at: DateTime<Utc>,
+    last_failed_at: DateTime<Utc>,
+    next_retry_at: Option<DateTime<Utc>>,
+    replayed_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl From<DeadLetterRow> for DeadLetterDto {
+    fn from(row: DeadLetterRow) -> Self {
+        Self {
+            id: row.id,
+            message_id: row.message_id,
+            source_topic: row.source_topic,
+            source_service: row.source_service,
+            payload: Some(parse_payload(&row.payload)),
+            error: row.error,
+            retry_count: row.retry_count,
+            max_retries: row.max_retries,
+            status: row.status,
+            first_failed_at: row.first_failed_at,
+            last_failed_at: row.last_failed_at,
+            next_retry_at: row.next_retry_at,
+            replayed_at: row.replayed_at,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+const DEAD_LETTER_COLUMNS: &str = r#"
+    id, message_id, source_topic, source_service, payload, error, retry_count, max_retries,
+    status, first_failed_at, last_failed_at, next_retry_at, replayed_at, created_at, updated_at
+"#;
+
+/// 与 `DeadLetterFilter` 对应的过滤条件，参数从 `$1` 开始依次为
+/// source_service、source_topic、status、error、message_id、ids
+const DEAD_LETTER_FILTER: &str = r#"
+    ($1::text IS NULL OR source_service = $1)
+    AND ($2::text IS NULL OR source_topic = $2)
+    AND ($3::text IS NULL OR status = $3)
+    AND ($4::text IS NULL OR error ILIKE '%' || $4 || '%')
+    AND ($5::text IS NULL OR message_id = $5)
+    AND ($6::bigint[] IS NULL OR id = ANY($6))
+"#;
+
+/// 将存储的消息内容转为 JSON，无法解析时原样作为字符串返回
+fn parse_payload(payload: &str) -> serde_json::Value {
+    serde_json::from_str(payload).unwrap_or_else(|_| serde_json::Value::String(payload.into()))
+}
+
+/// 将修改后的消息内容转回存储格式，字符串按原文保存
+fn serialize_payload(payload: &serde_json::Value) -> Result<String, AdminError> {
+    match payload {
+        serde_json::Value::String(raw) => Ok(raw.clone()),
+        other => Ok(serde_json::to_string(other)?),
+    }
+}
+
+fn validate_status(status: Option<&str>) -> Result<(), AdminError> {
+    match status {
+        Some(s) if !DLQ_STATUSES.contains(&s) => Err(AdminError::Validation(format!(
+            "无效的死信状态: {}，可选值: {}",
+            s,
+            DLQ_STATUSES.join("/")
+        ))),
+        _ => Ok(()),
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// 处理器
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// 获取死信列表
+///
+/// GET /api/admin/system/dlq/messages
+pub async fn list_dead_letters(
+    State(state): State<AppState>,
+    Query(pagination): Query<PaginationParams>,
+    Query(filter): Query<DeadLetterFilter>,
+) -> Result<Json<ApiResponse<PageResponse<DeadLetterDto>>>, AdminError> {
+    validate_status(filter.status.as_deref())?;
+
+    let total: (i64,) = sqlx::query_as(&format!(
+        "SELECT COUNT(*) FROM dead_letter_messages WHERE {}",
+        DEAD_LETTER_FILTER
+    ))
+    .bind(&filter.source_service)
+    .bind(&filter.source_topic)
+    .bind(&filter.status)
+    .bind(&filter.error)
+    .bind(&filter.message_id)
+    .bind(None::<Vec<i64>>)
+    .fetch_one(&state.pool)
+    .await?;
+
+    if total.0 == 0 {
+        return Ok(Json(ApiResponse::success(PageResponse::empty(
+            pagination.page,
+            pagination.page_size,
+        ))));
+    }
+
+    let rows = sqlx::query_as::<_, DeadLetterRow>(&format!(
+        r#"
+        SELECT {}
+        FROM dead_letter_messages
+        WHERE {}
+        ORDER BY last_failed_at DESC
+        LIMIT $7 OFFSET $8
+        "#,
+        DEAD_LETTER_COLUMNS, DEAD_LETTER_FILTER
+    ))
+    .bind(&filter.source_service)
+    .bind(&filter.source_topic)
+    .bind(&filter.status)
+    .bind(&filter.error)
+    .bind(&filter.message_id)
+    .bind(None::<Vec<i64>>)
+    .bind(pagination.limit())
+    .bind(pagination.offset())
+    .fetch_all(&state.pool)
+    .await?;
+
+    // 列表不返回消息内容，避免大消息拖慢分页
+    let items: Vec<DeadLetterDto> = rows
+        .into_iter()
+        .map(|row| DeadLetterDto {
+            payload: None,
+            ..row.into()
+        })
+        .collect();
+    let response = PageResponse::new(items, total.0, pagination.page, pagination.page_size);
+    Ok(Json(ApiResponse::success(response)))
+}
+
+/// 获取死信详情（含消息内容）
+///
+/// GET /api/admin/system/dlq/messages/:id
+pub async fn get_dead_letter(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Result<Json<ApiResponse<DeadLetterDto>>, AdminError> {
+    let row = fetch_dead_letter(&state, id).await?;
+    Ok(Json(ApiResponse::success(row.into())))
+}
+
+/// 重放单条死信
+///
+/// POST /api/admin/system/dlq/messages/:id/replay
+///
+/// 提供 `payload` 时先替换消息内容再重放
+pub async fn replay_dead_letter(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Extension(audit_ctx): Extension<AuditContext>,
+    Json(req): Json<ReplayDeadLetterRequest>,
+) -> Result<Json<ApiResponse<DeadLetterDto>>, AdminError> {
+    let current = fetch_dead_letter(&state, id).await?;
+    if current.status == "replaying" {
+        return Err(AdminError::Validation(format!("死信 {} 正在重放中", id)));
+    }
+    if current.source_topic.is_empty() {
+        return Err(AdminError::Validation(format!(
+            "死信 {} 缺少原始 topic，无法重放",
+            id
+        )));
+    }
+    let payload = req.payload.as_ref().map(serialize_payload).transpose()?;
+
+    // 审计快照：记录变更前状态
+    audit_ctx
+        .snapshot(&state.pool, "dead_letter_messages", id)
+        .await;
+
+    let row = sqlx::query_as::<_, DeadLetterRow>(&format!(
+        r#"
+        UPDATE dead_letter_messages
+        SET status = 'pending', next_retry_at = NOW(), payload = COALESCE($2, payload)
+        WHERE id = $1 AND status != 'replaying'
+        RETURNING {}
+        "#,
+        DEAD_LETTER_COLUMNS
+    ))
+    .bind(id)
+    .bind(&payload)
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or_else(|| AdminError::Validation(format!("死信 {} 正在重放中", id)))?;
+
+    info!(
+        dead_letter_id = id,
+        edited = payload.is_some(),
+        "死信已加入重放队列"
+    );
+    Ok(Json(ApiResponse::success(row.into())))
+}
+
+/// 批量重放死信
+///
+/// POST /api/admin/system/dlq/messages/replay
+///
+/// 跳过正在重放和缺少原始 topic 的死信
+pub async fn replay_dead_letters(
+    State(state): State<AppState>,
+    Json(req): Json<BulkReplayDeadLetterRequest>,
+) -> Result<Json<ApiResponse<DeadLetterBatchResult>>, AdminError> {
+    validate_status(req.filter.status.as_deref())?;
+
+    let result = sqlx::query(&format!(
+        r#"
+        UPDATE dead_letter_messages
+        SET status = 'pending', next_retry_at = NOW()
+        WHERE {}
+          AND status != 'replaying'
+          AND source_topic != ''
+        "#,
+        DEAD_LETTER_FILTER
+    ))
+    .bind(&req.filter.source_service)
+    .bind(&req.filter.source_topic)
+    .bind(&req.filter.status)
+    .bind(&req.filter.error)
+    .bind(&req.filter.message_id)
+    .bind(&req.ids)
+    .execute(&state.pool)
+    .await?;
+
+    info!(affected = result.rows_affected(), "死信已批量加入重放队列");
+    Ok(Json(ApiResponse::success(DeadLetterBatchResult {
+        affected: result.rows_affected(),
+    })))
+}
+
+/// 清理死信
+///
+/// POST /api/admin/system/dlq/messages/purge
+///
+/// 正在重放的死信不会被清理
+pub async fn purge_dead_letters(
+    State(state): State<AppState>,
+    Json(req): Json<PurgeDeadLetterRequest>,
+) -> Result<Json<ApiResponse<DeadLetterBatchResult>>, AdminError> {
+    if req.ids.is_none()
+        && req.status.is_none()
+        && req.source_service.is_none()
+        && req.before.is_none()
+    {
+        return Err(AdminError::Validation(
+            "清理死信至少需要指定 ids、status、sourceService 或 before 之一".to_string(),
+        ));
+    }
+    validate_status(req.status.as_deref())?;
+
+    let result = sqlx::query(
+        r#"
+        DELETE FROM dead_letter_messages
+        WHERE ($1::bigint[] IS NULL OR id = ANY($1))
+          AND ($2::text IS NULL OR status = $2)
+          AND ($3::text IS NULL OR source_service = $3)
+          AND ($4::timestamptz IS NULL OR last_failed_at < $4)
+          AND status != 'replaying'
+        "#,
+    )
+    .bind(&req.ids)
+    .bind(&req.status)
+    .bind(&req.source_service)
+    .bind(req.before)
+    .execute(&state.pool)
+    .await?;
+
+    info!(affected = result.rows_affected(), "死信已清理");
+    Ok(Json(ApiResponse::success(DeadLetterBatchResult {
+        affected: result.rows_affected(),
+    })))
+}
+
+/// 死信积压统计
+///
+/// GET /api/admin/system/dlq/stats
+pub async fn get_dead_letter_stats(
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<Vec<DeadLetterStatsDto>>>, AdminError> {
+    let stats = sqlx::query_as::<_, DeadLetterStatsDto>(
+        r#"
+        SELECT source_service, status, COUNT(*) AS count
+        FROM dead_letter_messages
+        GROUP BY source_service, status
+        ORDER BY source_service, status
+        "#,
+    )
+    .fetch_all(&state.pool)
+    .await?;
+
+    Ok(Json(ApiResponse::success(stats)))
+}
+
+async fn fetch_dead_letter(state: &AppState, id: i64) -> Result<DeadLetterRow, AdminError> {
+    sqlx::query_as::<_, DeadLetterRow>(&format!(
+        "SELECT {} FROM dead_letter_messages WHERE id = $1",
+        DEAD_LETTER_COLUMNS
+    ))
+    .bind(id)
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or_else(|| AdminError::NotFound(format!("死信 {} 不存在", id)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_round_trip() {
+        let json = parse_payload(r#"{"eventId":"evt-001"}"#);
+        assert_eq!(json["eventId"], "evt-001");
+        assert_eq!(serialize_payload(&json).unwrap(), r#"{"eventId":"evt-001"}"#);
+
+        // 非 JSON 内容按原文往返
+        let raw = parse_payload("not-json");
+        assert_eq!(raw, serde_json::Value::String("not-json".into()));
+        assert_eq!(serialize_payload(&raw).unwrap(), "not-json");
+    }
+
+    #[test]
+    fn test_validate_status() {
+        assert!(validate_status(None).is_ok());
+        assert!(validate_status(Some("exhausted")).is_ok());
+        assert!(validate_status(Some("failed")).is_err());
+    }
+
+    #[test]
+    fn test_bulk_replay_request_flattens_filter() {
+        let req: BulkReplayDeadLetterRequest =
+            serde_json::from_str(r#"{"sourceService":"notification-worker","error":"timeout"}"#)
+                .unwrap();
+        assert!(req.ids.is_none());
+        assert_eq!(
+            req.filter.source_service.as_deref(),
+            Some("notification-worker")
+        );
+        assert_eq!(req.filter.error.as_deref(), Some("timeout"));
+    }
+}
@@ -1,11 +1,24 @@
 //! 事件类型 API 处理器
 //!
-//! 提供事件类型列表查询，用于规则配置时选择事件类型
+//! 提供事件类型列表查询（用于规则配置时选择事件类型），以及自定义事件类型的注册和修改。
+//! 事件类型变更后通知所属服务组刷新，新类型无需发版即可被事件服务接收。
 
-use axum::{extract::State, Json};
-use serde::Serialize;
+use axum::{
+    extract::{Path, State},
+    Extension, Json,
+};
+use badge_shared::events::EventType;
+use badge_shared::rules::RuleReloadEvent;
+use badge_shared::rules::event_types::compile_payload_schema;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use validator::Validate;
 
-use crate::{dto::ApiResponse, error::AdminError, state::AppState};
+use crate::{dto::ApiResponse, error::AdminError, middleware::AuditContext, state::AppState};
+
+/// 可注册事件类型的服务组（即存在对应事件服务的分组）
+const SERVICE_GROUPS: [&str; 2] = ["engagement", "transaction"];
 
 /// 事件类型 DTO
 #[derive(Debug, Clone, Serialize)]
@@ -13,8 +26,43 @@ use crate::{dto::ApiResponse, error::AdminError, state::AppState};
 pub struct EventTypeDto {
     pub code: String,
     pub name: String,
+    pub service_group: String,
     pub description: Option<String>,
+    pub payload_schema: Option<serde_json::Value>,
     pub enabled: bool,
+    /// 是否为内置事件类型（内置类型的编码固定，由事件服务直接识别）
+    pub builtin: bool,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 注册事件类型请求
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateEventTypeRequest {
+    /// 事件类型编码（小写字母开头，仅包含小写字母、数字和下划线）
+    #[validate(length(min = 2, max = 50, message = "事件类型编码长度必须在2-50个字符之间"))]
+    pub code: String,
+    #[validate(length(min = 1, max = 100, message = "事件类型名称长度必须在1-100个字符之间"))]
+    pub name: String,
+    /// 所属服务组：engagement 或 transaction，决定由哪个事件服务处理
+    pub service_group: String,
+    pub description: Option<String>,
+    /// 事件负载的 JSON Schema
+    pub payload_schema: Option<serde_json::Value>,
+    pub enabled: Option<bool>,
+}
+
+/// 修改事件类型请求
+///
+/// 编码和服务组创建后不可修改；`payloadSchema` 传 `{}` 表示取消负载校验。
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateEventTypeRequest {
+    #[validate(length(min = 1, max = 100, message = "事件类型名称长度必须在1-100个字符之间"))]
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub payload_schema: Option<serde_json::Value>,
+    pub enabled: Option<bool>,
 }
 
 /// 事件类型数据库行
@@ -22,21 +70,84 @@ pub struct EventTypeDto {
 struct EventTypeRow {
     code: String,
     name: String,
+    service_group: String,
     description: Option<String>,
+    payload_schema: Option<serde_json::Value>,
     enabled: bool,
+    updated_at: DateTime<Utc>,
 }
 
 impl From<EventTypeRow> for EventTypeDto {
     fn from(row: EventTypeRow) -> Self {
+        let builtin = !EventType::from_code(&row.code).is_custom();
         Self {
             code: row.code,
             name: row.name,
+            service_group: row.service_group,
             description: row.description,
+            payload_schema: row.payload_schema,
             enabled: row.enabled,
+            builtin,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+/// 校验事件类型编码
+///
+/// 编码与内置事件类型的名称冲突时，事件服务会把它解析为内置类型，因此不允许注册。
+fn validate_code(code: &str) -> Result<(), AdminError> {
+    let mut chars = code.chars();
+    let well_formed = chars.next().is_some_and(|c| c.is_ascii_lowercase())
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if !well_formed {
+        return Err(AdminError::Validation(
+            "事件类型编码必须以小写字母开头，且只能包含小写字母、数字和下划线".to_string(),
+        ));
+    }
+
+    if !EventType::from_code(code).is_custom() {
+        return Err(AdminError::Validation(format!(
+            "事件类型编码 {} 与内置事件类型冲突",
+            code
+        )));
+    }
+
+    Ok(())
+}
+
+/// 校验负载 Schema，空对象视为不校验，返回需要保存的 Schema
+fn normalize_schema(
+    schema: Option<serde_json::Value>,
+) -> Result<Option<serde_json::Value>, AdminError> {
+    match schema {
+        Some(serde_json::Value::Object(ref map)) if map.is_empty() => Ok(None),
+        Some(schema) => {
+            compile_payload_schema(&schema)
+                .map_err(|e| AdminError::Validation(format!("payloadSchema 不是有效的 JSON Schema: {}", e)))?;
+            Ok(Some(schema))
         }
+        None => Ok(None),
+    }
+}
+
+/// 通知事件类型所属服务组刷新规则与事件类型
+async fn notify_event_type_reload(state: &AppState, row: &EventTypeRow, trigger: &str) {
+    let Some(ref publisher) = state.rule_reload_publisher else {
+        return;
+    };
+
+    let event = RuleReloadEvent::new(format!("admin:{}", trigger))
+        .with_service_group(row.service_group.as_str())
+        .with_event_type(row.code.as_str());
+    if let Err(e) = publisher.publish(&event).await {
+        warn!(code = %row.code, error = %e, "事件类型刷新事件发送失败，等待定时刷新生效");
     }
 }
 
+const EVENT_TYPE_COLUMNS: &str =
+    "code, name, service_group, description, payload_schema, enabled, updated_at";
+
 /// 获取事件类型列表
 ///
 /// GET /api/admin/event-types
@@ -45,14 +156,10 @@ impl From<EventTypeRow> for EventTypeDto {
 pub async fn list_event_types(
     State(state): State<AppState>,
 ) -> Result<Json<ApiResponse<Vec<EventTypeDto>>>, AdminError> {
-    let rows = sqlx::query_as::<_, EventTypeRow>(
-        r#"
-        SELECT code, name, description, enabled
-        FROM event_types
-        WHERE enabled = true
-        ORDER BY code
-        "#,
-    )
+    let rows = sqlx::query_as::<_, EventTypeRow>(&format!(
+        "SELECT {} FROM event_types WHERE enabled = true ORDER BY code",
+        EVENT_TYPE_COLUMNS
+    ))
     .fetch_all(&state.pool)
     .await?;
 
@@ -60,21 +167,166 @@ pub async fn list_event_types(
     Ok(Json(ApiResponse::success(items)))
 }
 
+/// 获取事件类型详情
+///
+/// GET /api/admin/event-types/:code
+pub async fn get_event_type(
+    State(state): State<AppState>,
+    Path(code): Path<String>,
+) -> Result<Json<ApiResponse<EventTypeDto>>, AdminError> {
+    let row = sqlx::query_as::<_, EventTypeRow>(&format!(
+        "SELECT {} FROM event_types WHERE code = $1",
+        EVENT_TYPE_COLUMNS
+    ))
+    .bind(&code)
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or_else(|| AdminError::NotFound(format!("事件类型不存在: {}", code)))?;
+
+    Ok(Json(ApiResponse::success(row.into())))
+}
+
+/// 注册自定义事件类型
+///
+/// POST /api/admin/event-types
+pub async fn create_event_type(
+    State(state): State<AppState>,
+    Json(req): Json<CreateEventTypeRequest>,
+) -> Result<Json<ApiResponse<EventTypeDto>>, AdminError> {
+    req.validate()?;
+    validate_code(&req.code)?;
+
+    if !SERVICE_GROUPS.contains(&req.service_group.as_str()) {
+        return Err(AdminError::Validation(format!(
+            "serviceGroup 必须是 {} 之一",
+            SERVICE_GROUPS.join("、")
+        )));
+    }
+
+    let payload_schema = normalize_schema(req.payload_schema)?;
+
+    let row = sqlx::query_as::<_, EventTypeRow>(&format!(
+        r#"
+        INSERT INTO event_types (code, name, service_group, description, payload_schema, enabled)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (code) DO NOTHING
+        RETURNING {}
+        "#,
+        EVENT_TYPE_COLUMNS
+    ))
+    .bind(&req.code)
+    .bind(&req.name)
+    .bind(&req.service_group)
+    .bind(&req.description)
+    .bind(&payload_schema)
+    .bind(req.enabled.unwrap_or(true))
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or_else(|| AdminError::Validation(format!("事件类型编码已存在: {}", req.code)))?;
+
+    info!(code = %row.code, service_group = %row.service_group, "Event type created");
+
+    notify_event_type_reload(&state, &row, "event_type_created").await;
+
+    Ok(Json(ApiResponse::success(row.into())))
+}
+
+/// 修改事件类型
+///
+/// PUT /api/admin/event-types/:code
+pub async fn update_event_type(
+    State(state): State<AppState>,
+    Path(code): Path<String>,
+    Extension(audit_ctx): Extension<AuditContext>,
+    Json(req): Json<UpdateEventTypeRequest>,
+) -> Result<Json<ApiResponse<EventTypeDto>>, AdminError> {
+    req.validate()?;
+
+    let id: i64 = sqlx::query_scalar("SELECT id FROM event_types WHERE code = $1")
+        .bind(&code)
+        .fetch_optional(&state.pool)
+        .await?
+        .ok_or_else(|| AdminError::NotFound(format!("事件类型不存在: {}", code)))?;
+
+    let schema_provided = req.payload_schema.is_some();
+    let payload_schema = normalize_schema(req.payload_schema)?;
+
+    audit_ctx.snapshot(&state.pool, "event_types", id).await;
+
+    let row = sqlx::query_as::<_, EventTypeRow>(&format!(
+        r#"
+        UPDATE event_types
+        SET name = COALESCE($2, name),
+            description = COALESCE($3, description),
+            payload_schema = CASE WHEN $4 THEN $5 ELSE payload_schema END,
+            enabled = COALESCE($6, enabled),
+            updated_at = NOW()
+        WHERE id = $1
+        RETURNING {}
+        "#,
+        EVENT_TYPE_COLUMNS
+    ))
+    .bind(id)
+    .bind(&req.name)
+    .bind(&req.description)
+    .bind(schema_provided)
+    .bind(&payload_schema)
+    .bind(req.enabled)
+    .fetch_one(&state.pool)
+    .await?;
+
+    info!(code = %row.code, "Event type updated");
+
+    notify_event_type_reload(&state, &row, "event_type_updated").await;
+
+    Ok(Json(ApiResponse::success(row.into())))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde_json::json;
 
     #[test]
     fn test_event_type_dto_serialization() {
         let dto = EventTypeDto {
             code: "purchase".to_string(),
             name: "购买事件".to_string(),
+            service_group: "transaction".to_string(),
             description: Some("用户完成购买时触发".to_string()),
+            payload_schema: None,
             enabled: true,
+            builtin: true,
+            updated_at: Utc::now(),
         };
 
         let json = serde_json::to_string(&dto).unwrap();
         assert!(json.contains("\"code\":\"purchase\""));
         assert!(json.contains("\"name\":\"购买事件\""));
+        assert!(json.contains("\"serviceGroup\":\"transaction\""));
+    }
+
+    #[test]
+    fn test_validate_code() {
+        assert!(validate_code("vip_login").is_ok());
+        assert!(validate_code("level2_reached").is_ok());
+
+        assert!(validate_code("VipLogin").is_err());
+        assert!(validate_code("2fa_enabled").is_err());
+        assert!(validate_code("vip-login").is_err());
+        // 与内置事件类型冲突
+        assert!(validate_code("purchase").is_err());
+        assert!(validate_code("check_in").is_err());
+    }
+
+    #[test]
+    fn test_normalize_schema() {
+        assert_eq!(normalize_schema(None).unwrap(), None);
+        assert_eq!(normalize_schema(Some(json!({}))).unwrap(), None);
+
+        let schema = json!({"type": "object", "required": ["level"]});
+        assert_eq!(normalize_schema(Some(schema.clone())).unwrap(), Some(schema));
+
+        assert!(normalize_schema(Some(json!({"type": 42}))).is_err());
     }
 }
@@ -9,6 +9,7 @@ use axum::{
     http::{HeaderMap, HeaderValue, StatusCode, header},
     response::IntoResponse,
 };
+use badge_shared::leaderboard::BadgeChange;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use tracing::info;
@@ -96,9 +97,22 @@ pub async fn manual_grant(
     // 事务保证三表一致性
     let mut tx = state.pool.begin().await?;
 
+    // 发放前的有效持有数量（已过期/已取消的记录会被重新激活），用于更新排行榜
+    let previous: Option<(i32, String)> = sqlx::query_as(
+        "SELECT quantity, status FROM user_badges WHERE user_id = $1 AND badge_id = $2 FOR UPDATE",
+    )
+    .bind(&req.user_id)
+    .bind(req.badge_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let active_before = match previous {
+        Some((quantity, status)) if status.eq_ignore_ascii_case("active") => quantity,
+        _ => 0,
+    };
+
     // 1. 插入或更新 user_badges
     // 数据库 DEFAULT 和 Worker 均使用小写 status，此处保持一致
-    sqlx::query(
+    let (new_quantity,): (i32,) = sqlx::query_as(
         r#"
         INSERT INTO user_badges (user_id, badge_id, quantity, status, first_acquired_at, source_type, created_at, updated_at)
         VALUES ($1, $2, $3, 'active', $4, 'MANUAL', $4, $4)
@@ -107,13 +121,14 @@ pub async fn manual_grant(
             quantity = user_badges.quantity + $3,
             status = 'active',
             updated_at = $4
+        RETURNING quantity
         "#,
     )
     .bind(&req.user_id)
     .bind(req.badge_id)
     .bind(req.quantity)
     .bind(now)
-    .execute(&mut *tx)
+    .fetch_one(&mut *tx)
     .await?;
 
     // 2. 写入 badge_ledger（需要计算 balance_after）
@@ -170,6 +185,15 @@ pub async fn manual_grant(
 
     tx.commit().await?;
 
+    state
+        .record_leaderboard(BadgeChange::new(
+            &req.user_id,
+            req.badge_id,
+            active_before,
+            new_quantity,
+        ))
+        .await;
+
     info!(
         user_id = %req.user_id,
         badge_id = req.badge_id,
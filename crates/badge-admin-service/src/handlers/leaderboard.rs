@@ -0,0 +1,241 @@
+//! 用户排行榜 API 处理器
+//!
+//! 排行榜由 badge-management-service 在 Redis 中增量维护，此处代理 gRPC 查询：
+//! 榜单前 N 名和指定用户的名次。
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use badge_proto::badge::{
+    GetLeaderboardRequest, GetLeaderboardResponse, LeaderboardEntry as ProtoLeaderboardEntry,
+};
+use badge_shared::error::BadgeError;
+use badge_shared::leaderboard::{LeaderboardKind, LeaderboardWindow};
+use badge_shared::observability::middleware::grpc::traced_request;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::{dto::ApiResponse, error::AdminError, state::AppState};
+
+/// 排行榜查询参数
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaderboardParams {
+    /// 系列 ID 或分类 ID（仅 series / category 榜）
+    pub scope_id: Option<i64>,
+    /// all_time / weekly / monthly，默认 all_time
+    pub window: Option<String>,
+    /// 返回条数，默认 10，最大 100
+    pub limit: Option<i32>,
+    /// 同时返回该用户的名次
+    pub user_id: Option<String>,
+}
+
+/// 排行榜条目 DTO
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaderboardEntryDto {
+    pub rank: i64,
+    pub user_id: String,
+    pub score: f64,
+    /// 系列完成率（0-100），仅系列完成榜有值
+    pub completion_rate: Option<f64>,
+}
+
+/// 排行榜 DTO
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaderboardDto {
+    pub board: String,
+    pub scope_id: Option<i64>,
+    pub window: String,
+    pub entries: Vec<LeaderboardEntryDto>,
+    /// 上榜用户总数
+    pub total: i64,
+    /// 指定用户的名次，未上榜时为空
+    pub my_entry: Option<LeaderboardEntryDto>,
+}
+
+impl LeaderboardEntryDto {
+    fn from_proto(entry: ProtoLeaderboardEntry, series_board: bool) -> Self {
+        Self {
+            rank: entry.rank,
+            user_id: entry.user_id,
+            score: entry.score,
+            completion_rate: series_board.then_some(entry.completion_rate),
+        }
+    }
+}
+
+impl From<GetLeaderboardResponse> for LeaderboardDto {
+    fn from(response: GetLeaderboardResponse) -> Self {
+        let series_board = response.board == "series";
+        Self {
+            board: response.board,
+            scope_id: (response.scope_id > 0).then_some(response.scope_id),
+            window: response.window,
+            entries: response
+                .entries
+                .into_iter()
+                .map(|entry| LeaderboardEntryDto::from_proto(entry, series_board))
+                .collect(),
+            total: response.total,
+            my_entry: response
+                .my_entry
+                .map(|entry| LeaderboardEntryDto::from_proto(entry, series_board)),
+        }
+    }
+}
+
+/// 在本地校验榜单和窗口，避免无效请求打到下游
+fn build_request(
+    board: &str,
+    params: LeaderboardParams,
+) -> Result<GetLeaderboardRequest, AdminError> {
+    let kind = LeaderboardKind::parse(board, params.scope_id).map_err(validation_error)?;
+    let window = LeaderboardWindow::parse(params.window.as_deref().unwrap_or_default())
+        .map_err(validation_error)?;
+
+    Ok(GetLeaderboardRequest {
+        board: kind.name().to_string(),
+        scope_id: kind.scope_id().unwrap_or_default(),
+        window: window.as_str().to_string(),
+        limit: params.limit.unwrap_or_default(),
+        user_id: params.user_id.unwrap_or_default(),
+    })
+}
+
+fn validation_error(err: BadgeError) -> AdminError {
+    match err {
+        BadgeError::Validation(msg) => AdminError::Validation(msg),
+        other => AdminError::Internal(other.to_string()),
+    }
+}
+
+async fn fetch_leaderboard(
+    state: &AppState,
+    request: GetLeaderboardRequest,
+) -> Result<LeaderboardDto, AdminError> {
+    let client = state
+        .badge_management_client
+        .read()
+        .await
+        .clone()
+        .ok_or_else(|| {
+            AdminError::Internal("Badge-management-service gRPC 客户端未配置".to_string())
+        })?;
+
+    let response = state
+        .badge_mgmt_circuit_breaker
+        .call(|| {
+            let mut c = client.clone();
+            let request = request.clone();
+            async move { c.get_leaderboard(traced_request(request)).await }
+        })
+        .await
+        .map_err(|e| AdminError::Internal(format!("查询排行榜失败: {}", e)))?;
+
+    Ok(response.into_inner().into())
+}
+
+/// 查询排行榜
+///
+/// GET /api/v1/leaderboards/:board?scopeId=&window=weekly&limit=20&userId=
+///
+/// board 取值：badges（徽章数）、score（积分）、series（系列完成度）、category（分类）。
+#[instrument(skip(state))]
+pub async fn get_leaderboard(
+    State(state): State<AppState>,
+    Path(board): Path<String>,
+    Query(params): Query<LeaderboardParams>,
+) -> Result<Json<ApiResponse<LeaderboardDto>>, AdminError> {
+    let request = build_request(&board, params)?;
+    let leaderboard = fetch_leaderboard(&state, request).await?;
+    Ok(Json(ApiResponse::success(leaderboard)))
+}
+
+/// 查询用户在排行榜中的名次
+///
+/// GET /api/v1/users/:user_id/leaderboards/:board?scopeId=&window=
+///
+/// 只返回用户自己的名次（`myEntry`），不返回榜单列表；未上榜时 `myEntry` 为空。
+#[instrument(skip(state))]
+pub async fn get_user_leaderboard_rank(
+    State(state): State<AppState>,
+    Path((user_id, board)): Path<(String, String)>,
+    Query(params): Query<LeaderboardParams>,
+) -> Result<Json<ApiResponse<LeaderboardDto>>, AdminError> {
+    let mut request = build_request(&board, params)?;
+    request.user_id = user_id;
+    // limit 为 1 时只取榜首，随后丢弃，仅保留用户名次和总人数
+    request.limit = 1;
+
+    let mut leaderboard = fetch_leaderboard(&state, request).await?;
+    leaderboard.entries.clear();
+    Ok(Json(ApiResponse::success(leaderboard)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_request() {
+        let request = build_request(
+            "series",
+            LeaderboardParams {
+                scope_id: Some(3),
+                window: Some("weekly".to_string()),
+                limit: Some(20),
+                user_id: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(request.board, "series");
+        assert_eq!(request.scope_id, 3);
+        assert_eq!(request.window, "weekly");
+        assert_eq!(request.limit, 20);
+
+        assert!(matches!(
+            build_request("category", LeaderboardParams::default()),
+            Err(AdminError::Validation(_))
+        ));
+        assert!(matches!(
+            build_request(
+                "badges",
+                LeaderboardParams {
+                    window: Some("daily".to_string()),
+                    ..Default::default()
+                }
+            ),
+            Err(AdminError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_leaderboard_from_proto() {
+        let response = GetLeaderboardResponse {
+            board: "series".to_string(),
+            scope_id: 3,
+            window: "all_time".to_string(),
+            entries: vec![ProtoLeaderboardEntry {
+                rank: 1,
+                user_id: "u1".to_string(),
+                score: 3.0,
+                completion_rate: 75.0,
+            }],
+            total: 1,
+            my_entry: None,
+        };
+
+        let dto = LeaderboardDto::from(response);
+        assert_eq!(dto.scope_id, Some(3));
+        assert_eq!(dto.entries[0].completion_rate, Some(75.0));
+        assert!(dto.my_entry.is_none());
+
+        let json = serde_json::to_value(&dto).unwrap();
+        assert_eq!(json["entries"][0]["userId"], "u1");
+        assert_eq!(json["entries"][0]["completionRate"], 75.0);
+    }
+}
@@ -13,8 +13,10 @@ pub mod batch_task;
 pub mod benefit;
 pub mod category;
 pub mod dependency;
+pub mod dlq;
 pub mod event_type;
 pub mod grant;
+pub mod leaderboard;
 pub mod operation_log;
 pub mod redemption;
 pub mod revoke;
@@ -22,5 +24,8 @@ pub mod rule;
 pub mod series;
 pub mod stats;
 pub mod notification;
+pub mod notification_contact;
+pub mod notification_preference;
+pub mod notification_template;
 pub mod template;
 pub mod user_view;
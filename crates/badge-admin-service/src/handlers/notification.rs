@@ -56,6 +56,9 @@ pub struct NotificationTaskDto {
     pub retry_count: i32,
     pub max_retries: i32,
     pub last_error: Option<String>,
+    /// 各渠道投递结果（渠道名 → status/attempts/error/sent_at）
+    pub channel_results: serde_json::Value,
+    pub next_retry_at: Option<DateTime<Utc>>,
     pub completed_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
 }
@@ -173,6 +176,8 @@ struct NotificationTaskRow {
     retry_count: i32,
     max_retries: i32,
     last_error: Option<String>,
+    channel_results: serde_json::Value,
+    next_retry_at: Option<DateTime<Utc>>,
     completed_at: Option<DateTime<Utc>>,
     created_at: DateTime<Utc>,
 }
@@ -190,6 +195,8 @@ impl From<NotificationTaskRow> for NotificationTaskDto {
             retry_count: row.retry_count,
             max_retries: row.max_retries,
             last_error: row.last_error,
+            channel_results: row.channel_results,
+            next_retry_at: row.next_retry_at,
             completed_at: row.completed_at,
             created_at: row.created_at,
         }
@@ -517,7 +524,8 @@ pub async fn list_notification_tasks(
     let data_sql = format!(
         r#"
         SELECT id, user_id, trigger_type, channels, template_id, status,
-               retry_count, max_retries, last_error, completed_at, created_at
+               retry_count, max_retries, last_error, channel_results, next_retry_at,
+               completed_at, created_at
         FROM notification_tasks
         {}
         ORDER BY created_at DESC
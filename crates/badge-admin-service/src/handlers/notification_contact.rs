@@ -0,0 +1,306 @@
+//! 用户通知联系方式 API 处理器
+//!
+//! 查看和写入 `user_notification_contacts`。注册事件只覆盖新注册的用户，
+//! 存量用户的联系方式通过这里的单个写入或批量导入回填；notification-worker 和
+//! badge-management-service 分发通知前按用户 ID 查询收件人。
+//!
+//! 写入只更新请求中提供的字段，未提供的字段保留已有值。
+
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use badge_shared::error::BadgeError;
+use badge_shared::notification_contacts::{ContactStore, PgContactStore, UserContacts};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::{dto::ApiResponse, error::AdminError, state::AppState};
+
+/// 用户 ID 最大长度，与 `user_notification_contacts.user_id` 一致
+const MAX_USER_ID_LEN: usize = 100;
+const MAX_EMAIL_LEN: usize = 255;
+const MAX_PHONE_LEN: usize = 32;
+const MAX_OPENID_LEN: usize = 128;
+const MAX_DEVICE_TOKEN_LEN: usize = 512;
+/// 单次批量导入的最大用户数
+const MAX_BATCH_SIZE: usize = 1000;
+
+// ═══════════════════════════════════════════════════════════════════════════
+// DTO 定义
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// 用户通知联系方式 DTO
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationContactsDto {
+    pub user_id: String,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub wechat_openid: Option<String>,
+    pub device_token: Option<String>,
+}
+
+impl From<UserContacts> for NotificationContactsDto {
+    fn from(contacts: UserContacts) -> Self {
+        Self {
+            user_id: contacts.user_id,
+            email: contacts.email,
+            phone: contacts.phone,
+            wechat_openid: contacts.wechat_openid,
+            device_token: contacts.device_token,
+        }
+    }
+}
+
+/// 写入联系方式请求，至少提供一项
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpsertNotificationContactsRequest {
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub wechat_openid: Option<String>,
+    pub device_token: Option<String>,
+}
+
+impl UpsertNotificationContactsRequest {
+    /// 校验请求并转换为联系方式，空白字段视为未提供
+    fn into_contacts(self, user_id: &str) -> Result<UserContacts, AdminError> {
+        if user_id.trim().is_empty() || user_id.len() > MAX_USER_ID_LEN {
+            return Err(AdminError::Validation(format!(
+                "用户 ID 长度必须在 1-{} 之间",
+                MAX_USER_ID_LEN
+            )));
+        }
+
+        let contacts = UserContacts {
+            user_id: user_id.to_string(),
+            email: normalize("邮箱", self.email, MAX_EMAIL_LEN)?,
+            phone: normalize("手机号", self.phone, MAX_PHONE_LEN)?,
+            wechat_openid: normalize("微信 openid", self.wechat_openid, MAX_OPENID_LEN)?,
+            device_token: normalize("推送设备 token", self.device_token, MAX_DEVICE_TOKEN_LEN)?,
+        };
+
+        if contacts.is_empty() {
+            return Err(AdminError::Validation(format!(
+                "用户 {} 至少需要提供一项联系方式",
+                user_id
+            )));
+        }
+        if contacts.email.as_deref().is_some_and(|e| !e.contains('@')) {
+            return Err(AdminError::Validation(format!(
+                "用户 {} 的邮箱格式不正确",
+                user_id
+            )));
+        }
+        Ok(contacts)
+    }
+}
+
+/// 批量导入中的单个用户
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationContactsItem {
+    pub user_id: String,
+    #[serde(flatten)]
+    pub contacts: UpsertNotificationContactsRequest,
+}
+
+/// 批量导入联系方式请求
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchUpsertNotificationContactsRequest {
+    pub items: Vec<NotificationContactsItem>,
+}
+
+/// 批量导入结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchUpsertNotificationContactsResult {
+    pub upserted: usize,
+}
+
+fn normalize(
+    field: &str,
+    value: Option<String>,
+    max_len: usize,
+) -> Result<Option<String>, AdminError> {
+    let Some(value) = value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+    else {
+        return Ok(None);
+    };
+    if value.len() > max_len {
+        return Err(AdminError::Validation(format!(
+            "{}长度不能超过 {}",
+            field, max_len
+        )));
+    }
+    Ok(Some(value))
+}
+
+fn contact_error(err: BadgeError) -> AdminError {
+    match err {
+        BadgeError::Validation(msg) => AdminError::Validation(msg),
+        BadgeError::Database(e) => AdminError::Database(e),
+        other => AdminError::Internal(other.to_string()),
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// 处理器
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// 获取用户通知联系方式
+///
+/// GET /api/admin/users/:user_id/notification-contacts
+///
+/// 未登记联系方式时返回所有字段为空
+pub async fn get_notification_contacts(
+    State(state): State<AppState>,
+    Path(user_id): Path<String>,
+) -> Result<Json<ApiResponse<NotificationContactsDto>>, AdminError> {
+    let store = PgContactStore::new(state.pool.clone());
+    let contacts = store
+        .get(&user_id)
+        .await
+        .map_err(contact_error)?
+        .unwrap_or_else(|| UserContacts::new(&user_id));
+
+    Ok(Json(ApiResponse::success(contacts.into())))
+}
+
+/// 写入用户通知联系方式
+///
+/// PUT /api/admin/users/:user_id/notification-contacts
+pub async fn upsert_notification_contacts(
+    State(state): State<AppState>,
+    Path(user_id): Path<String>,
+    Json(req): Json<UpsertNotificationContactsRequest>,
+) -> Result<Json<ApiResponse<NotificationContactsDto>>, AdminError> {
+    let contacts = req.into_contacts(&user_id)?;
+
+    let store = PgContactStore::new(state.pool.clone());
+    store.upsert(&contacts).await.map_err(contact_error)?;
+    let saved = store
+        .get(&user_id)
+        .await
+        .map_err(contact_error)?
+        .unwrap_or(contacts);
+
+    info!(user_id = %user_id, "用户通知联系方式已更新");
+    Ok(Json(ApiResponse::success(saved.into())))
+}
+
+/// 批量导入用户通知联系方式，用于回填存量用户
+///
+/// POST /api/admin/users/notification-contacts/batch
+///
+/// 先校验全部条目，任一条目不合法时整批拒绝；重复导入同一批数据是幂等的
+pub async fn batch_upsert_notification_contacts(
+    State(state): State<AppState>,
+    Json(req): Json<BatchUpsertNotificationContactsRequest>,
+) -> Result<Json<ApiResponse<BatchUpsertNotificationContactsResult>>, AdminError> {
+    let contacts = validate_batch(req)?;
+
+    let store = PgContactStore::new(state.pool.clone());
+    for item in &contacts {
+        store.upsert(item).await.map_err(contact_error)?;
+    }
+
+    info!(count = contacts.len(), "批量导入用户通知联系方式");
+    Ok(Json(ApiResponse::success(
+        BatchUpsertNotificationContactsResult {
+            upserted: contacts.len(),
+        },
+    )))
+}
+
+fn validate_batch(
+    req: BatchUpsertNotificationContactsRequest,
+) -> Result<Vec<UserContacts>, AdminError> {
+    if req.items.is_empty() || req.items.len() > MAX_BATCH_SIZE {
+        return Err(AdminError::Validation(format!(
+            "单次导入的用户数必须在 1-{} 之间",
+            MAX_BATCH_SIZE
+        )));
+    }
+    req.items
+        .into_iter()
+        .map(|item| item.contacts.into_contacts(&item.user_id))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upsert_request_into_contacts() {
+        let req: UpsertNotificationContactsRequest = serde_json::from_str(
+            r#"{"email": " alice@example.com ", "phone": "", "wechatOpenid": "o-123"}"#,
+        )
+        .unwrap();
+
+        let contacts = req.into_contacts("user-001").unwrap();
+        assert_eq!(contacts.user_id, "user-001");
+        assert_eq!(contacts.email.as_deref(), Some("alice@example.com"));
+        assert_eq!(contacts.phone, None);
+        assert_eq!(contacts.wechat_openid.as_deref(), Some("o-123"));
+        assert_eq!(contacts.device_token, None);
+    }
+
+    #[test]
+    fn test_upsert_request_validation() {
+        assert!(
+            UpsertNotificationContactsRequest::default()
+                .into_contacts("user-001")
+                .is_err()
+        );
+
+        let invalid_email = UpsertNotificationContactsRequest {
+            email: Some("alice".to_string()),
+            ..Default::default()
+        };
+        assert!(invalid_email.into_contacts("user-001").is_err());
+
+        let long_phone = UpsertNotificationContactsRequest {
+            phone: Some("1".repeat(MAX_PHONE_LEN + 1)),
+            ..Default::default()
+        };
+        assert!(long_phone.into_contacts("user-001").is_err());
+
+        let phone = UpsertNotificationContactsRequest {
+            phone: Some("13800000000".to_string()),
+            ..Default::default()
+        };
+        assert!(phone.into_contacts(" ").is_err());
+    }
+
+    #[test]
+    fn test_validate_batch() {
+        let req: BatchUpsertNotificationContactsRequest = serde_json::from_str(
+            r#"{"items": [
+                {"userId": "user-001", "email": "a@example.com"},
+                {"userId": "user-002", "deviceToken": "token-2"}
+            ]}"#,
+        )
+        .unwrap();
+        let contacts = validate_batch(req).unwrap();
+        assert_eq!(contacts.len(), 2);
+        assert_eq!(contacts[1].device_token.as_deref(), Some("token-2"));
+
+        // 任一条目不合法时整批拒绝
+        let req: BatchUpsertNotificationContactsRequest = serde_json::from_str(
+            r#"{"items": [
+                {"userId": "user-001", "email": "a@example.com"},
+                {"userId": "user-002"}
+            ]}"#,
+        )
+        .unwrap();
+        assert!(validate_batch(req).is_err());
+
+        let empty = BatchUpsertNotificationContactsRequest { items: vec![] };
+        assert!(validate_batch(empty).is_err());
+    }
+}
@@ -0,0 +1,248 @@
+//! 用户通知偏好 API 处理器
+//!
+//! 查看和修改用户的通知偏好：各通知类型启用的渠道、免打扰时段和通知语言。
+//! 偏好写入 `user_notification_preferences`，notification-worker 和
+//! badge-management-service 分发通知前读取；未保存过偏好的用户按默认偏好原样投递。
+
+use std::collections::HashMap;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use badge_shared::error::BadgeError;
+use badge_shared::events::{NotificationChannel, NotificationType};
+use badge_shared::notification_preferences::{
+    DEFAULT_TIMEZONE, NotificationPreferences, PgPreferenceStore, PreferenceStore, QuietHours,
+};
+use chrono::NaiveTime;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::{dto::ApiResponse, error::AdminError, state::AppState};
+
+/// 用户 ID 最大长度，与 `user_notification_preferences.user_id` 一致
+const MAX_USER_ID_LEN: usize = 100;
+/// 语言标识最大长度，与 `user_notification_preferences.locale` 一致
+const MAX_LOCALE_LEN: usize = 20;
+
+// ═══════════════════════════════════════════════════════════════════════════
+// DTO 定义
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// 用户通知偏好 DTO
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationPreferencesDto {
+    pub user_id: String,
+    /// 各通知类型启用的渠道，未配置的类型使用通知自带的渠道
+    pub channels: HashMap<NotificationType, Vec<NotificationChannel>>,
+    pub quiet_hours: Option<QuietHours>,
+    pub locale: String,
+    /// 是否保存过偏好，未保存时返回的是默认偏好
+    pub customized: bool,
+}
+
+impl NotificationPreferencesDto {
+    fn new(preferences: NotificationPreferences, customized: bool) -> Self {
+        Self {
+            user_id: preferences.user_id,
+            channels: preferences.channels,
+            quiet_hours: preferences.quiet_hours,
+            locale: preferences.locale,
+            customized,
+        }
+    }
+}
+
+/// 免打扰时段请求，起止时间为用户本地时间（如 `22:00`）
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuietHoursRequest {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    /// IANA 时区，默认 Asia/Shanghai
+    pub timezone: Option<String>,
+}
+
+/// 更新通知偏好请求，整体覆盖已有设置
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateNotificationPreferencesRequest {
+    /// 各通知类型启用的渠道，空数组表示关闭该类型的全部通知
+    #[serde(default)]
+    pub channels: HashMap<NotificationType, Vec<NotificationChannel>>,
+    /// 免打扰时段，为空表示不设置
+    pub quiet_hours: Option<QuietHoursRequest>,
+    /// 通知语言，默认 zh-CN
+    pub locale: Option<String>,
+}
+
+impl UpdateNotificationPreferencesRequest {
+    /// 校验请求并转换为偏好
+    fn into_preferences(self, user_id: &str) -> Result<NotificationPreferences, AdminError> {
+        validate_user_id(user_id)?;
+
+        let mut preferences = NotificationPreferences::new(user_id);
+        preferences.channels = self.channels;
+
+        if let Some(quiet) = self.quiet_hours {
+            let timezone = quiet
+                .timezone
+                .unwrap_or_else(|| DEFAULT_TIMEZONE.to_string());
+            let quiet_hours =
+                QuietHours::new(quiet.start, quiet.end, timezone).map_err(preference_error)?;
+            preferences = preferences.with_quiet_hours(quiet_hours);
+        }
+
+        if let Some(locale) = self.locale {
+            let locale = locale.trim();
+            if locale.is_empty() || locale.len() > MAX_LOCALE_LEN {
+                return Err(AdminError::Validation(format!(
+                    "通知语言长度必须在 1-{} 之间",
+                    MAX_LOCALE_LEN
+                )));
+            }
+            preferences = preferences.with_locale(locale);
+        }
+
+        Ok(preferences)
+    }
+}
+
+fn validate_user_id(user_id: &str) -> Result<(), AdminError> {
+    if user_id.trim().is_empty() || user_id.len() > MAX_USER_ID_LEN {
+        return Err(AdminError::Validation(format!(
+            "用户 ID 长度必须在 1-{} 之间",
+            MAX_USER_ID_LEN
+        )));
+    }
+    Ok(())
+}
+
+fn preference_error(err: BadgeError) -> AdminError {
+    match err {
+        BadgeError::Validation(msg) => AdminError::Validation(msg),
+        BadgeError::Database(e) => AdminError::Database(e),
+        other => AdminError::Internal(other.to_string()),
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// 处理器
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// 获取用户通知偏好
+///
+/// GET /api/admin/users/:user_id/notification-preferences
+/// GET /api/v1/users/:user_id/notification-preferences
+///
+/// 未保存过偏好时返回默认偏好，`customized` 为 false
+pub async fn get_notification_preferences(
+    State(state): State<AppState>,
+    Path(user_id): Path<String>,
+) -> Result<Json<ApiResponse<NotificationPreferencesDto>>, AdminError> {
+    validate_user_id(&user_id)?;
+
+    let store = PgPreferenceStore::new(state.pool.clone());
+    let dto = match store.get(&user_id).await.map_err(preference_error)? {
+        Some(preferences) => NotificationPreferencesDto::new(preferences, true),
+        None => NotificationPreferencesDto::new(NotificationPreferences::new(&user_id), false),
+    };
+
+    Ok(Json(ApiResponse::success(dto)))
+}
+
+/// 更新用户通知偏好
+///
+/// PUT /api/admin/users/:user_id/notification-preferences
+/// PUT /api/v1/users/:user_id/notification-preferences
+pub async fn update_notification_preferences(
+    State(state): State<AppState>,
+    Path(user_id): Path<String>,
+    Json(req): Json<UpdateNotificationPreferencesRequest>,
+) -> Result<Json<ApiResponse<NotificationPreferencesDto>>, AdminError> {
+    let preferences = req.into_preferences(&user_id)?;
+
+    let store = PgPreferenceStore::new(state.pool.clone());
+    store.save(&preferences).await.map_err(preference_error)?;
+
+    info!(
+        user_id = %user_id,
+        quiet_hours = preferences.quiet_hours.is_some(),
+        locale = %preferences.locale,
+        "用户通知偏好已更新"
+    );
+    Ok(Json(ApiResponse::success(NotificationPreferencesDto::new(
+        preferences,
+        true,
+    ))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_request_into_preferences() {
+        let req: UpdateNotificationPreferencesRequest = serde_json::from_str(
+            r#"{
+                "channels": {"BADGE_GRANTED": ["APP_PUSH"], "BADGE_EXPIRING": []},
+                "quietHours": {"start": "22:00", "end": "08:00", "timezone": "Europe/London"},
+                "locale": "en-US"
+            }"#,
+        )
+        .unwrap();
+
+        let preferences = req.into_preferences("user-001").unwrap();
+        assert_eq!(preferences.user_id, "user-001");
+        assert_eq!(
+            preferences.channels[&NotificationType::BadgeGranted],
+            [NotificationChannel::AppPush]
+        );
+        assert!(preferences.channels[&NotificationType::BadgeExpiring].is_empty());
+        let quiet = preferences.quiet_hours.unwrap();
+        assert_eq!(quiet.start, NaiveTime::from_hms_opt(22, 0, 0).unwrap());
+        assert_eq!(quiet.timezone, "Europe/London");
+        assert_eq!(preferences.locale, "en-US");
+    }
+
+    #[test]
+    fn test_update_request_defaults() {
+        let req: UpdateNotificationPreferencesRequest =
+            serde_json::from_str(r#"{"quietHours": {"start": "23:00", "end": "07:00"}}"#).unwrap();
+
+        let preferences = req.into_preferences("user-001").unwrap();
+        assert!(preferences.channels.is_empty());
+        assert_eq!(preferences.quiet_hours.unwrap().timezone, DEFAULT_TIMEZONE);
+        assert_eq!(preferences.locale, "zh-CN");
+    }
+
+    #[test]
+    fn test_update_request_validation() {
+        let invalid_timezone = UpdateNotificationPreferencesRequest {
+            quiet_hours: Some(QuietHoursRequest {
+                start: NaiveTime::from_hms_opt(22, 0, 0).unwrap(),
+                end: NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
+                timezone: Some("Mars/Olympus".to_string()),
+            }),
+            ..Default::default()
+        };
+        assert!(matches!(
+            invalid_timezone.into_preferences("user-001"),
+            Err(AdminError::Validation(_))
+        ));
+
+        let empty_locale = UpdateNotificationPreferencesRequest {
+            locale: Some("  ".to_string()),
+            ..Default::default()
+        };
+        assert!(empty_locale.into_preferences("user-001").is_err());
+
+        assert!(
+            UpdateNotificationPreferencesRequest::default()
+                .into_preferences(&"u".repeat(101))
+                .is_err()
+        );
+    }
+}
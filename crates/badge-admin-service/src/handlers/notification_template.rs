@@ -0,0 +1,556 @@
+//! 通知模板 API 处理器
+//!
+//! 管理本地化通知模板。模板按通知类型、语言和渠道区分，保存后由
+//! badge-management-service 和 notification-worker 定期加载，覆盖内置默认模板。
+//! 预览接口使用示例数据渲染模板，便于运营在发布前检查效果。
+
+use axum::{
+    Extension, Json,
+    extract::{Path, Query, State},
+};
+use badge_shared::notification_templates::{
+    NotificationTemplate, RenderedNotification, parse_channel, parse_notification_type,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use tracing::info;
+use validator::Validate;
+
+use crate::{
+    dto::{ApiResponse, PageResponse, PaginationParams},
+    error::AdminError,
+    middleware::AuditContext,
+    state::AppState,
+};
+
+/// 未指定语言时使用的模板语言
+const DEFAULT_TEMPLATE_LOCALE: &str = "zh-CN";
+
+// ═══════════════════════════════════════════════════════════════════════════
+// DTO 定义
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// 通知模板 DTO
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationTemplateDto {
+    pub id: i64,
+    /// 通知类型，如 BADGE_GRANTED
+    pub notification_type: String,
+    pub locale: String,
+    /// 渠道，为空表示该语言的通用模板
+    pub channel: Option<String>,
+    pub title_template: String,
+    pub body_template: String,
+    /// HTML 正文模板，仅邮件渠道使用
+    pub html_template: Option<String>,
+    /// 预览使用的示例数据
+    pub sample_data: serde_json::Value,
+    pub description: Option<String>,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 创建通知模板请求
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateNotificationTemplateRequest {
+    pub notification_type: String,
+    #[validate(length(min = 2, max = 20, message = "语言标识长度必须在2-20个字符之间"))]
+    pub locale: Option<String>,
+    pub channel: Option<String>,
+    #[validate(length(min = 1, max = 200, message = "标题模板长度必须在1-200个字符之间"))]
+    pub title_template: String,
+    #[validate(length(min = 1, message = "正文模板不能为空"))]
+    pub body_template: String,
+    pub html_template: Option<String>,
+    pub sample_data: Option<serde_json::Value>,
+    pub description: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+/// 更新通知模板请求
+///
+/// 通知类型、语言和渠道创建后不可修改；`htmlTemplate` 传空字符串表示删除 HTML 模板。
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateNotificationTemplateRequest {
+    #[validate(length(min = 1, max = 200, message = "标题模板长度必须在1-200个字符之间"))]
+    pub title_template: Option<String>,
+    #[validate(length(min = 1, message = "正文模板不能为空"))]
+    pub body_template: Option<String>,
+    pub html_template: Option<String>,
+    pub sample_data: Option<serde_json::Value>,
+    pub description: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+/// 通知模板查询过滤
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationTemplateFilter {
+    pub notification_type: Option<String>,
+    pub locale: Option<String>,
+    pub channel: Option<String>,
+}
+
+/// 预览通知模板请求
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewNotificationTemplateRequest {
+    /// 渲染数据，未提供时使用模板保存的示例数据
+    pub data: Option<serde_json::Value>,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// 数据库行映射
+// ═══════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, FromRow)]
+struct NotificationTemplateRow {
+    id: i64,
+    notification_type: String,
+    locale: String,
+    channel: Option<String>,
+    title_template: String,
+    body_template: String,
+    html_template: Option<String>,
+    sample_data: serde_json::Value,
+    description: Option<String>,
+    enabled: bool,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl From<NotificationTemplateRow> for NotificationTemplateDto {
+    fn from(row: NotificationTemplateRow) -> Self {
+        Self {
+            id: row.id,
+            notification_type: row.notification_type,
+            locale: row.locale,
+            channel: row.channel,
+            title_template: row.title_template,
+            body_template: row.body_template,
+            html_template: row.html_template,
+            sample_data: row.sample_data,
+            description: row.description,
+            enabled: row.enabled,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+/// 校验并构建通知模板
+///
+/// 通知类型和渠道使用与通知事件一致的编码（如 BADGE_GRANTED、APP_PUSH），
+/// 模板语法错误时返回校验错误，避免保存后两个服务加载失败。
+fn build_template(
+    notification_type: &str,
+    locale: &str,
+    channel: Option<&str>,
+    title_template: &str,
+    body_template: &str,
+    html_template: Option<&str>,
+) -> Result<NotificationTemplate, AdminError> {
+    let notification_type = parse_notification_type(notification_type)
+        .ok_or_else(|| AdminError::Validation(format!("无效的通知类型: {}", notification_type)))?;
+
+    let mut template =
+        NotificationTemplate::new(notification_type, locale, title_template, body_template);
+    if let Some(channel) = channel {
+        let channel = parse_channel(channel)
+            .ok_or_else(|| AdminError::Validation(format!("无效的通知渠道: {}", channel)))?;
+        template = template.with_channel(channel);
+    }
+    if let Some(html) = html_template {
+        template = template.with_html_body(html);
+    }
+
+    template
+        .compile()
+        .map_err(|e| AdminError::Validation(e.to_string()))?;
+    Ok(template)
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// API 处理器
+// ═══════════════════════════════════════════════════════════════════════════
+
+const TEMPLATE_COLUMNS: &str = "id, notification_type, locale, channel, title_template, \
+    body_template, html_template, sample_data, description, enabled, created_at, updated_at";
+
+async fn fetch_template(state: &AppState, id: i64) -> Result<NotificationTemplateRow, AdminError> {
+    sqlx::query_as::<_, NotificationTemplateRow>(&format!(
+        "SELECT {} FROM notification_templates WHERE id = $1",
+        TEMPLATE_COLUMNS
+    ))
+    .bind(id)
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or_else(|| AdminError::NotFound(format!("通知模板 {} 不存在", id)))
+}
+
+/// 获取通知模板列表
+///
+/// GET /api/admin/notification-templates
+pub async fn list_notification_templates(
+    State(state): State<AppState>,
+    Query(pagination): Query<PaginationParams>,
+    Query(filter): Query<NotificationTemplateFilter>,
+) -> Result<Json<ApiResponse<PageResponse<NotificationTemplateDto>>>, AdminError> {
+    let offset = pagination.offset();
+    let limit = pagination.limit();
+
+    // 构建动态 WHERE 子句
+    let mut conditions = Vec::new();
+    let mut param_idx = 1;
+
+    if filter.notification_type.is_some() {
+        conditions.push(format!("notification_type = ${}", param_idx));
+        param_idx += 1;
+    }
+    if filter.locale.is_some() {
+        conditions.push(format!("locale = ${}", param_idx));
+        param_idx += 1;
+    }
+    if filter.channel.is_some() {
+        conditions.push(format!("channel = ${}", param_idx));
+        param_idx += 1;
+    }
+
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+
+    // 统计总数
+    let count_sql = format!(
+        "SELECT COUNT(*) FROM notification_templates {}",
+        where_clause
+    );
+    let mut count_query = sqlx::query_as::<_, (i64,)>(&count_sql);
+
+    if let Some(ref notification_type) = filter.notification_type {
+        count_query = count_query.bind(notification_type);
+    }
+    if let Some(ref locale) = filter.locale {
+        count_query = count_query.bind(locale);
+    }
+    if let Some(ref channel) = filter.channel {
+        count_query = count_query.bind(channel);
+    }
+
+    let total = count_query.fetch_one(&state.pool).await?;
+
+    if total.0 == 0 {
+        return Ok(Json(ApiResponse::success(PageResponse::empty(
+            pagination.page,
+            pagination.page_size,
+        ))));
+    }
+
+    // 查询数据
+    let data_sql = format!(
+        "SELECT {} FROM notification_templates {} \
+         ORDER BY notification_type, locale, channel NULLS FIRST LIMIT ${} OFFSET ${}",
+        TEMPLATE_COLUMNS,
+        where_clause,
+        param_idx,
+        param_idx + 1
+    );
+
+    let mut data_query = sqlx::query_as::<_, NotificationTemplateRow>(&data_sql);
+
+    if let Some(ref notification_type) = filter.notification_type {
+        data_query = data_query.bind(notification_type);
+    }
+    if let Some(ref locale) = filter.locale {
+        data_query = data_query.bind(locale);
+    }
+    if let Some(ref channel) = filter.channel {
+        data_query = data_query.bind(channel);
+    }
+
+    let rows = data_query
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&state.pool)
+        .await?;
+
+    let items: Vec<NotificationTemplateDto> = rows.into_iter().map(Into::into).collect();
+    let response = PageResponse::new(items, total.0, pagination.page, pagination.page_size);
+    Ok(Json(ApiResponse::success(response)))
+}
+
+/// 获取通知模板详情
+///
+/// GET /api/admin/notification-templates/:id
+pub async fn get_notification_template(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Result<Json<ApiResponse<NotificationTemplateDto>>, AdminError> {
+    let row = fetch_template(&state, id).await?;
+    Ok(Json(ApiResponse::success(row.into())))
+}
+
+/// 创建通知模板
+///
+/// POST /api/admin/notification-templates
+pub async fn create_notification_template(
+    State(state): State<AppState>,
+    Json(req): Json<CreateNotificationTemplateRequest>,
+) -> Result<Json<ApiResponse<NotificationTemplateDto>>, AdminError> {
+    req.validate()?;
+
+    let locale = req.locale.as_deref().unwrap_or(DEFAULT_TEMPLATE_LOCALE);
+    build_template(
+        &req.notification_type,
+        locale,
+        req.channel.as_deref(),
+        &req.title_template,
+        &req.body_template,
+        req.html_template.as_deref(),
+    )?;
+
+    // 同一类型、语言和渠道只能有一个模板
+    let exists: (bool,) = sqlx::query_as(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM notification_templates
+            WHERE notification_type = $1 AND locale = $2
+              AND COALESCE(channel, '') = COALESCE($3, '')
+        )
+        "#,
+    )
+    .bind(&req.notification_type)
+    .bind(locale)
+    .bind(&req.channel)
+    .fetch_one(&state.pool)
+    .await?;
+
+    if exists.0 {
+        return Err(AdminError::Validation(format!(
+            "通知模板已存在: {} / {} / {}",
+            req.notification_type,
+            locale,
+            req.channel.as_deref().unwrap_or("默认渠道")
+        )));
+    }
+
+    let row = sqlx::query_as::<_, NotificationTemplateRow>(&format!(
+        r#"
+        INSERT INTO notification_templates
+            (notification_type, locale, channel, title_template, body_template,
+             html_template, sample_data, description, enabled)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING {}
+        "#,
+        TEMPLATE_COLUMNS
+    ))
+    .bind(&req.notification_type)
+    .bind(locale)
+    .bind(&req.channel)
+    .bind(&req.title_template)
+    .bind(&req.body_template)
+    .bind(&req.html_template)
+    .bind(req.sample_data.unwrap_or_else(|| serde_json::json!({})))
+    .bind(&req.description)
+    .bind(req.enabled.unwrap_or(true))
+    .fetch_one(&state.pool)
+    .await?;
+
+    info!(
+        template_id = row.id,
+        notification_type = %row.notification_type,
+        locale = %row.locale,
+        "通知模板已创建"
+    );
+    Ok(Json(ApiResponse::success(row.into())))
+}
+
+/// 更新通知模板
+///
+/// PUT /api/admin/notification-templates/:id
+pub async fn update_notification_template(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Extension(audit_ctx): Extension<AuditContext>,
+    Json(req): Json<UpdateNotificationTemplateRequest>,
+) -> Result<Json<ApiResponse<NotificationTemplateDto>>, AdminError> {
+    req.validate()?;
+
+    let current = fetch_template(&state, id).await?;
+    let html_template = match req.html_template.as_deref() {
+        Some("") => None,
+        Some(html) => Some(html),
+        None => current.html_template.as_deref(),
+    };
+    build_template(
+        &current.notification_type,
+        &current.locale,
+        current.channel.as_deref(),
+        req.title_template
+            .as_deref()
+            .unwrap_or(&current.title_template),
+        req.body_template
+            .as_deref()
+            .unwrap_or(&current.body_template),
+        html_template,
+    )?;
+
+    // 审计快照：记录变更前状态
+    audit_ctx
+        .snapshot(&state.pool, "notification_templates", id)
+        .await;
+
+    let row = sqlx::query_as::<_, NotificationTemplateRow>(&format!(
+        r#"
+        UPDATE notification_templates
+        SET
+            title_template = COALESCE($2, title_template),
+            body_template = COALESCE($3, body_template),
+            html_template = CASE WHEN $4::text IS NULL THEN html_template ELSE NULLIF($4, '') END,
+            sample_data = COALESCE($5, sample_data),
+            description = COALESCE($6, description),
+            enabled = COALESCE($7, enabled)
+        WHERE id = $1
+        RETURNING {}
+        "#,
+        TEMPLATE_COLUMNS
+    ))
+    .bind(id)
+    .bind(&req.title_template)
+    .bind(&req.body_template)
+    .bind(&req.html_template)
+    .bind(&req.sample_data)
+    .bind(&req.description)
+    .bind(req.enabled)
+    .fetch_one(&state.pool)
+    .await?;
+
+    info!(template_id = id, "通知模板已更新");
+    Ok(Json(ApiResponse::success(row.into())))
+}
+
+/// 删除通知模板
+///
+/// DELETE /api/admin/notification-templates/:id
+///
+/// 删除后该类型、语言和渠道回落到内置默认模板
+pub async fn delete_notification_template(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Extension(audit_ctx): Extension<AuditContext>,
+) -> Result<Json<ApiResponse<()>>, AdminError> {
+    // 审计快照：记录变更前状态
+    audit_ctx
+        .snapshot(&state.pool, "notification_templates", id)
+        .await;
+
+    let result = sqlx::query("DELETE FROM notification_templates WHERE id = $1")
+        .bind(id)
+        .execute(&state.pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AdminError::NotFound(format!("通知模板 {} 不存在", id)));
+    }
+
+    info!(template_id = id, "通知模板已删除");
+    Ok(Json(ApiResponse::<()>::success_empty()))
+}
+
+/// 预览通知模板
+///
+/// POST /api/admin/notification-templates/:id/preview
+///
+/// 使用请求数据或模板保存的示例数据渲染，返回标题、正文、HTML 正文和缺失的变量
+pub async fn preview_notification_template(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(req): Json<PreviewNotificationTemplateRequest>,
+) -> Result<Json<ApiResponse<RenderedNotification>>, AdminError> {
+    let row = fetch_template(&state, id).await?;
+    let template = build_template(
+        &row.notification_type,
+        &row.locale,
+        row.channel.as_deref(),
+        &row.title_template,
+        &row.body_template,
+        row.html_template.as_deref(),
+    )?;
+
+    let data = req.data.unwrap_or(row.sample_data);
+    let compiled = template
+        .compile()
+        .map_err(|e| AdminError::Validation(e.to_string()))?;
+    Ok(Json(ApiResponse::success(compiled.render(&data))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use badge_shared::events::{NotificationChannel, NotificationType};
+
+    #[test]
+    fn test_build_template() {
+        let template = build_template(
+            "BADGE_GRANTED",
+            "en-US",
+            Some("EMAIL"),
+            "New badge",
+            "You earned {{badge_name}}",
+            Some("<p>{{badge_name}}</p>"),
+        )
+        .unwrap();
+
+        assert_eq!(template.notification_type, NotificationType::BadgeGranted);
+        assert_eq!(template.channel, Some(NotificationChannel::Email));
+
+        let rendered = template
+            .compile()
+            .unwrap()
+            .render(&serde_json::json!({"badge_name": "<First>"}));
+        assert_eq!(rendered.body, "You earned <First>");
+        assert_eq!(rendered.html_body.as_deref(), Some("<p>&lt;First&gt;</p>"));
+    }
+
+    #[test]
+    fn test_build_template_rejects_invalid_input() {
+        let invalid_type = build_template("UNKNOWN", "zh-CN", None, "标题", "正文", None);
+        assert!(matches!(invalid_type, Err(AdminError::Validation(_))));
+
+        let invalid_channel =
+            build_template("BADGE_GRANTED", "zh-CN", Some("fax"), "标题", "正文", None);
+        assert!(matches!(invalid_channel, Err(AdminError::Validation(_))));
+
+        let invalid_syntax = build_template(
+            "BADGE_GRANTED",
+            "zh-CN",
+            None,
+            "标题",
+            "{{#if a}}未闭合",
+            None,
+        );
+        assert!(matches!(invalid_syntax, Err(AdminError::Validation(_))));
+    }
+
+    #[test]
+    fn test_create_request_deserialization() {
+        let req: CreateNotificationTemplateRequest = serde_json::from_value(serde_json::json!({
+            "notificationType": "BADGE_EXPIRING",
+            "titleTemplate": "徽章即将过期",
+            "bodyTemplate": "「{{badge_name}}」将在 {{days_left}} 天后过期",
+            "sampleData": {"badge_name": "首单", "days_left": 3}
+        }))
+        .unwrap();
+
+        assert!(req.validate().is_ok());
+        assert!(req.locale.is_none());
+        assert!(req.channel.is_none());
+    }
+}
@@ -15,9 +15,16 @@ use tracing::info;
 use uuid::Uuid;
 use validator::Validate;
 
-use badge_management::service::dto::RedeemBadgeRequest;
+use badge_management::models::{RedemptionRecipe, RequiredBadge};
+use badge_management::service::dto::{RedeemBadgeRequest, RedeemableBenefitDto};
+use badge_proto::badge::{
+    CancelRedemptionRequest as ProtoCancelRedemptionRequest,
+    CancelRedemptionResponse as ProtoCancelRedemptionResponse,
+};
+use badge_shared::observability::middleware::grpc::traced_request;
 
 use crate::{
+    auth::Claims,
     dto::{ApiResponse, PageResponse, PaginationParams},
     error::AdminError,
     state::AppState,
@@ -47,6 +54,8 @@ pub struct RedemptionRuleDto {
     pub benefit_id: i64,
     pub benefit_name: String,
     pub required_badges: Vec<RequiredBadgeDto>,
+    /// 兑换配方，配置后取代 required_badges
+    pub recipe: Option<RedemptionRecipe>,
     pub frequency_config: FrequencyConfigDto,
     /// 有效期类型：FIXED-固定时间段，RELATIVE-相对徽章获取时间
     pub validity_type: ValidityType,
@@ -87,8 +96,11 @@ pub struct CreateRedemptionRuleRequest {
     pub name: String,
     pub description: Option<String>,
     pub benefit_id: i64,
-    #[validate(length(min = 1, message = "至少需要一个徽章"))]
+    /// 固定徽章列表（AND 关系），配置 recipe 时可为空
+    #[serde(default)]
     pub required_badges: Vec<RequiredBadgeInput>,
+    /// 兑换配方：备选、系列/分类通配及选择策略
+    pub recipe: Option<RedemptionRecipe>,
     pub frequency_config: Option<FrequencyConfigDto>,
     /// 有效期类型：FIXED-固定时间段，RELATIVE-相对徽章获取时间
     #[serde(default)]
@@ -119,6 +131,7 @@ pub struct UpdateRedemptionRuleRequest {
     pub name: Option<String>,
     pub description: Option<String>,
     pub required_badges: Option<Vec<RequiredBadgeInput>>,
+    pub recipe: Option<RedemptionRecipe>,
     pub frequency_config: Option<FrequencyConfigDto>,
     /// 有效期类型：FIXED-固定时间段，RELATIVE-相对徽章获取时间
     pub validity_type: Option<ValidityType>,
@@ -138,6 +151,9 @@ pub struct RedeemRequest {
     pub rule_id: i64,
     /// 幂等键（可选，不提供时自动生成）
     pub idempotency_key: Option<String>,
+    /// 指定消耗的徽章（可选，不提供时按兑换配方的选择策略挑选）
+    #[serde(default)]
+    pub selected_badges: Vec<RequiredBadgeInput>,
 }
 
 /// 兑换响应 DTO
@@ -175,6 +191,8 @@ pub struct ConsumedBadgeDto {
     pub badge_id: i64,
     pub badge_name: String,
     pub quantity: i32,
+    /// 满足的兑换配方条目序号（从 0 开始）
+    pub recipe_term: Option<i32>,
 }
 
 /// 订单查询过滤
@@ -186,6 +204,56 @@ pub struct OrderQueryFilter {
     pub rule_id: Option<i64>,
 }
 
+/// 可兑换目录查询过滤
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedeemableQueryFilter {
+    /// 只返回当前可兑换的规则
+    #[serde(default)]
+    pub eligible_only: bool,
+}
+
+/// 管理员取消兑换订单请求
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelOrderRequest {
+    /// 取消原因
+    pub reason: Option<String>,
+}
+
+/// 用户取消兑换订单请求（外部 API）
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UserCancelOrderRequest {
+    #[validate(length(min = 1, message = "用户ID不能为空"))]
+    pub user_id: String,
+    /// 取消原因
+    pub reason: Option<String>,
+}
+
+/// 取消兑换订单结果 DTO
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelOrderResultDto {
+    pub order_no: String,
+    /// 订单此前已取消，本次未做变更
+    pub already_cancelled: bool,
+    /// 本次是否撤销了已发放的权益
+    pub benefit_revoked: bool,
+    /// 本次退回的徽章
+    pub refunded_badges: Vec<RefundedBadgeDto>,
+    pub cancelled_at: Option<DateTime<Utc>>,
+}
+
+/// 退回的徽章 DTO
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefundedBadgeDto {
+    pub badge_id: i64,
+    pub badge_name: String,
+    pub quantity: i32,
+}
+
 // ==================== 数据库查询结构 ====================
 
 #[derive(sqlx::FromRow)]
@@ -196,6 +264,7 @@ struct RedemptionRuleRow {
     benefit_id: i64,
     benefit_name: String,
     required_badges: Value,
+    recipe: Option<Value>,
     frequency_config: Value,
     validity_type: Option<String>,
     relative_days: Option<i32>,
@@ -225,6 +294,7 @@ struct RedemptionDetailRow {
     badge_id: i64,
     badge_name: String,
     quantity: i32,
+    recipe_term: Option<i32>,
 }
 
 // ==================== API 处理器 ====================
@@ -249,8 +319,15 @@ pub async fn create_redemption_rule(
         return Err(AdminError::BenefitNotFound(req.benefit_id));
     }
 
+    if req.required_badges.is_empty() && req.recipe.is_none() {
+        return Err(AdminError::Validation(
+            "至少需要一个徽章或配置兑换配方".to_string(),
+        ));
+    }
+
     // 检查所需徽章是否都存在
     for badge in &req.required_badges {
+        badge.validate()?;
         let badge_exists: (bool,) =
             sqlx::query_as("SELECT EXISTS(SELECT 1 FROM badges WHERE id = $1)")
                 .bind(badge.badge_id)
@@ -262,9 +339,19 @@ pub async fn create_redemption_rule(
         }
     }
 
-    // 序列化所需徽章和频率配置
+    if let Some(ref recipe) = req.recipe {
+        validate_recipe(&state.pool, recipe).await?;
+    }
+
+    // 序列化所需徽章、兑换配方和频率配置
     let required_badges_json = serde_json::to_value(&req.required_badges)
         .map_err(|e| AdminError::Internal(e.to_string()))?;
+    let recipe_json = req
+        .recipe
+        .as_ref()
+        .map(serde_json::to_value)
+        .transpose()
+        .map_err(|e| AdminError::Internal(e.to_string()))?;
     let frequency_config_json =
         serde_json::to_value(req.frequency_config.unwrap_or_default())
             .map_err(|e| AdminError::Internal(e.to_string()))?;
@@ -279,8 +366,8 @@ pub async fn create_redemption_rule(
         r#"
         INSERT INTO badge_redemption_rules (name, description, benefit_id, required_badges,
                                            frequency_config, validity_type, relative_days,
-                                           start_time, end_time, enabled, auto_redeem)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, true, $10)
+                                           start_time, end_time, enabled, auto_redeem, recipe)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, true, $10, $11)
         RETURNING id
         "#,
     )
@@ -294,6 +381,7 @@ pub async fn create_redemption_rule(
     .bind(req.start_time)
     .bind(req.end_time)
     .bind(req.auto_redeem)
+    .bind(&recipe_json)
     .fetch_one(&state.pool)
     .await?;
 
@@ -331,7 +419,7 @@ pub async fn list_redemption_rules(
         SELECT
             r.id, r.name, r.description, r.benefit_id,
             b.name as benefit_name,
-            r.required_badges, r.frequency_config,
+            r.required_badges, r.recipe, r.frequency_config,
             r.validity_type, r.relative_days,
             r.start_time, r.end_time, r.enabled,
             r.created_at, r.updated_at
@@ -403,10 +491,18 @@ pub async fn update_redemption_rule(
         }
     }
 
+    if let Some(ref recipe) = req.recipe {
+        validate_recipe(&state.pool, recipe).await?;
+    }
+
     let required_badges_json = req
         .required_badges
         .as_ref()
         .and_then(|b| serde_json::to_value(b).ok());
+    let recipe_json = req
+        .recipe
+        .as_ref()
+        .and_then(|r| serde_json::to_value(r).ok());
     let frequency_config_json = req
         .frequency_config
         .as_ref()
@@ -432,6 +528,7 @@ pub async fn update_redemption_rule(
             start_time = COALESCE($8, start_time),
             end_time = COALESCE($9, end_time),
             enabled = COALESCE($10, enabled),
+            recipe = COALESCE($11, recipe),
             updated_at = NOW()
         WHERE id = $1
         "#,
@@ -446,6 +543,7 @@ pub async fn update_redemption_rule(
     .bind(req.start_time)
     .bind(req.end_time)
     .bind(req.enabled)
+    .bind(&recipe_json)
     .execute(&state.pool)
     .await?;
 
@@ -544,7 +642,16 @@ pub async fn redeem(
 
     // 通过 RedemptionService 执行兑换
     if let Some(ref redemption_service) = state.redemption_service {
-        let request = RedeemBadgeRequest::new(&req.user_id, req.rule_id, &idempotency_key);
+        let selected_badges = req
+            .selected_badges
+            .iter()
+            .map(|b| RequiredBadge {
+                badge_id: b.badge_id,
+                quantity: b.quantity,
+            })
+            .collect();
+        let request = RedeemBadgeRequest::new(&req.user_id, req.rule_id, &idempotency_key)
+            .with_selected_badges(selected_badges);
 
         let response = redemption_service.redeem_badge(request).await?;
 
@@ -562,6 +669,52 @@ pub async fn redeem(
     }
 }
 
+/// 管理员取消兑换订单
+///
+/// POST /api/admin/redemption/orders/:order_no/cancel
+///
+/// 撤销已发放的权益并退回消耗的徽章，不受用户自助取消的时限限制。
+/// 权益撤销需要徽章管理服务中的权益处理器，因此通过 gRPC 执行
+pub async fn cancel_redemption_order(
+    State(state): State<AppState>,
+    Path(order_no): Path<String>,
+    Extension(claims): Extension<Claims>,
+    Json(req): Json<CancelOrderRequest>,
+) -> Result<Json<ApiResponse<CancelOrderResultDto>>, AdminError> {
+    let request = ProtoCancelRedemptionRequest {
+        order_no,
+        user_id: String::new(),
+        operator: claims.username,
+        reason: req.reason.unwrap_or_default(),
+    };
+
+    let result = cancel_via_grpc(&state, request).await?;
+    Ok(Json(ApiResponse::success(result)))
+}
+
+/// 用户取消兑换订单
+///
+/// POST /api/v1/redemption/orders/:order_no/cancel
+///
+/// 仅能取消本人订单，且须在下单后的可取消时限内
+pub async fn cancel_user_redemption_order(
+    State(state): State<AppState>,
+    Path(order_no): Path<String>,
+    Json(req): Json<UserCancelOrderRequest>,
+) -> Result<Json<ApiResponse<CancelOrderResultDto>>, AdminError> {
+    req.validate()?;
+
+    let request = ProtoCancelRedemptionRequest {
+        order_no,
+        user_id: req.user_id,
+        operator: String::new(),
+        reason: req.reason.unwrap_or_default(),
+    };
+
+    let result = cancel_via_grpc(&state, request).await?;
+    Ok(Json(ApiResponse::success(result)))
+}
+
 /// 获取单个兑换订单详情
 ///
 /// GET /api/admin/redemption/orders/:order_no
@@ -772,6 +925,36 @@ pub async fn get_user_redemption_history(
     Ok(Json(ApiResponse::success(response)))
 }
 
+/// 查询用户可兑换权益目录
+///
+/// GET /api/v1/users/:user_id/redeemable-benefits?eligibleOnly=true
+///
+/// 逐条比对有效兑换规则与用户持有徽章、兑换频率和权益库存，
+/// 返回每条规则的可兑换状态及原因，可兑换的排在前面
+pub async fn list_redeemable_benefits(
+    State(state): State<AppState>,
+    Path(user_id): Path<String>,
+    Query(pagination): Query<PaginationParams>,
+    Query(filter): Query<RedeemableQueryFilter>,
+) -> Result<Json<ApiResponse<PageResponse<RedeemableBenefitDto>>>, AdminError> {
+    let redemption_service = state
+        .redemption_service
+        .as_ref()
+        .ok_or_else(|| AdminError::Internal("RedemptionService 未配置".to_string()))?;
+
+    let page = redemption_service
+        .list_redeemable_benefits(
+            &user_id,
+            filter.eligible_only,
+            pagination.page,
+            pagination.limit(),
+        )
+        .await?;
+
+    let response = PageResponse::new(page.items, page.total, page.page, page.page_size);
+    Ok(Json(ApiResponse::success(response)))
+}
+
 // ==================== 辅助函数 ====================
 
 async fn fetch_rule_by_id(pool: &sqlx::PgPool, id: i64) -> Result<RedemptionRuleDto, AdminError> {
@@ -780,7 +963,7 @@ async fn fetch_rule_by_id(pool: &sqlx::PgPool, id: i64) -> Result<RedemptionRule
         SELECT
             r.id, r.name, r.description, r.benefit_id,
             b.name as benefit_name,
-            r.required_badges, r.frequency_config,
+            r.required_badges, r.recipe, r.frequency_config,
             r.validity_type, r.relative_days,
             r.start_time, r.end_time, r.enabled,
             r.created_at, r.updated_at
@@ -820,6 +1003,10 @@ async fn row_to_dto(
         });
     }
 
+    let recipe = row
+        .recipe
+        .filter(|r| !r.is_null())
+        .and_then(|r| serde_json::from_value(r).ok());
     let frequency_config: FrequencyConfigDto =
         serde_json::from_value(row.frequency_config).unwrap_or_default();
 
@@ -836,6 +1023,7 @@ async fn row_to_dto(
         benefit_id: row.benefit_id,
         benefit_name: row.benefit_name,
         required_badges,
+        recipe,
         frequency_config,
         validity_type,
         relative_days: row.relative_days,
@@ -847,6 +1035,102 @@ async fn row_to_dto(
     })
 }
 
+/// 校验兑换配方结构，以及引用的徽章、系列、分类是否存在
+async fn validate_recipe(
+    pool: &sqlx::PgPool,
+    recipe: &RedemptionRecipe,
+) -> Result<(), AdminError> {
+    recipe.validate().map_err(AdminError::Validation)?;
+
+    if let Some(id) = find_missing_id(pool, "badges", recipe.badge_ids()).await? {
+        return Err(AdminError::BadgeNotFound(id));
+    }
+    if let Some(id) = find_missing_id(pool, "badge_series", recipe.series_ids()).await? {
+        return Err(AdminError::SeriesNotFound(id));
+    }
+    if let Some(id) = find_missing_id(pool, "badge_categories", recipe.category_ids()).await? {
+        return Err(AdminError::CategoryNotFound(id));
+    }
+
+    Ok(())
+}
+
+/// 返回 ids 中第一个在指定表中不存在的 ID
+async fn find_missing_id(
+    pool: &sqlx::PgPool,
+    table: &str,
+    ids: Vec<i64>,
+) -> Result<Option<i64>, AdminError> {
+    if ids.is_empty() {
+        return Ok(None);
+    }
+    let existing: Vec<i64> =
+        sqlx::query_scalar(&format!("SELECT id FROM {} WHERE id = ANY($1)", table))
+            .bind(&ids)
+            .fetch_all(pool)
+            .await?;
+    Ok(ids.into_iter().find(|id| !existing.contains(id)))
+}
+
+/// 调用徽章管理服务取消兑换订单
+///
+/// 业务失败（success=false）按错误码转换：订单不存在为 404，其余为参数错误
+async fn cancel_via_grpc(
+    state: &AppState,
+    request: ProtoCancelRedemptionRequest,
+) -> Result<CancelOrderResultDto, AdminError> {
+    let client = state
+        .badge_management_client
+        .read()
+        .await
+        .clone()
+        .ok_or_else(|| {
+            AdminError::Internal("Badge-management-service gRPC 客户端未配置".to_string())
+        })?;
+
+    let response = state
+        .badge_mgmt_circuit_breaker
+        .call(|| {
+            let mut c = client.clone();
+            let request = request.clone();
+            async move { c.cancel_redemption(traced_request(request)).await }
+        })
+        .await
+        .map_err(|e| AdminError::Internal(format!("取消兑换订单失败: {}", e)))?
+        .into_inner();
+
+    cancel_response_to_dto(response)
+}
+
+fn cancel_response_to_dto(
+    response: ProtoCancelRedemptionResponse,
+) -> Result<CancelOrderResultDto, AdminError> {
+    if !response.success {
+        return Err(match response.error_code.as_str() {
+            "ORDER_NOT_FOUND" => AdminError::NotFound(response.message),
+            _ => AdminError::Validation(response.message),
+        });
+    }
+
+    Ok(CancelOrderResultDto {
+        order_no: response.order_no,
+        already_cancelled: response.already_cancelled,
+        benefit_revoked: response.benefit_revoked,
+        refunded_badges: response
+            .refunded_badges
+            .into_iter()
+            .map(|b| RefundedBadgeDto {
+                badge_id: b.badge_id,
+                badge_name: b.badge_name,
+                quantity: b.quantity,
+            })
+            .collect(),
+        cancelled_at: response
+            .cancelled_at
+            .and_then(|ts| DateTime::from_timestamp(ts.seconds, ts.nanos.max(0) as u32)),
+    })
+}
+
 async fn fetch_order_details(
     pool: &sqlx::PgPool,
     order_id: i64,
@@ -856,7 +1140,8 @@ async fn fetch_order_details(
         SELECT
             d.badge_id,
             b.name as badge_name,
-            d.quantity
+            d.quantity,
+            d.recipe_term
         FROM redemption_details d
         JOIN badges b ON b.id = d.badge_id
         WHERE d.order_id = $1
@@ -872,6 +1157,7 @@ async fn fetch_order_details(
             badge_id: r.badge_id,
             badge_name: r.badge_name,
             quantity: r.quantity,
+            recipe_term: r.recipe_term,
         })
         .collect())
 }
@@ -901,4 +1187,63 @@ mod tests {
         };
         assert!(invalid.validate().is_err());
     }
+
+    #[test]
+    fn test_create_rule_request_with_recipe() {
+        let req: CreateRedemptionRuleRequest = serde_json::from_value(serde_json::json!({
+            "name": "任选系列 3 枚",
+            "benefitId": 1,
+            "recipe": {
+                "terms": [{"type": "series", "seriesId": 2, "count": 3, "distinct": true}],
+                "selection": "soonest_expiring"
+            }
+        }))
+        .unwrap();
+
+        assert!(req.required_badges.is_empty());
+        assert!(req.validate().is_ok());
+        let recipe = req.recipe.unwrap();
+        assert!(recipe.validate().is_ok());
+        assert_eq!(recipe.series_ids(), vec![2]);
+    }
+
+    #[test]
+    fn test_cancel_response_to_dto() {
+        let dto = cancel_response_to_dto(ProtoCancelRedemptionResponse {
+            success: true,
+            order_no: "RD1".to_string(),
+            benefit_revoked: true,
+            refunded_badges: vec![badge_proto::badge::RefundedBadge {
+                badge_id: 5,
+                badge_name: "签到达人".to_string(),
+                quantity: 2,
+            }],
+            cancelled_at: Some(prost_types::Timestamp {
+                seconds: 1_700_000_000,
+                nanos: 0,
+            }),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(dto.benefit_revoked);
+        assert_eq!(dto.refunded_badges[0].quantity, 2);
+        assert_eq!(dto.cancelled_at.unwrap().timestamp(), 1_700_000_000);
+
+        // 业务失败按错误码转换
+        let err = cancel_response_to_dto(ProtoCancelRedemptionResponse {
+            error_code: "ORDER_NOT_FOUND".to_string(),
+            message: "兑换订单不存在".to_string(),
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert!(matches!(err, AdminError::NotFound(_)));
+
+        let err = cancel_response_to_dto(ProtoCancelRedemptionResponse {
+            error_code: "CANCEL_WINDOW_EXPIRED".to_string(),
+            message: "已超过可取消时限".to_string(),
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert!(matches!(err, AdminError::Validation(_)));
+    }
 }
@@ -2,6 +2,8 @@
 //!
 //! 实现徽章的手动取消、批量取消和取消记录查询。
 //! 取消操作涉及多表事务：user_badges 扣减 + badge_ledger + user_badge_logs。
+//! 依赖被撤销徽章的其他徽章通过反向级联处理：待处理记录与撤销同事务登记，
+//! 提交后立即执行，失败的记录由过期 Worker 按退避时间重试。
 
 use axum::{
     Extension, Json,
@@ -9,8 +11,10 @@ use axum::{
     http::{HeaderMap, HeaderValue, StatusCode, header},
     response::IntoResponse,
 };
+use badge_shared::leaderboard::BadgeChange;
 use chrono::{DateTime, Utc};
-use tracing::info;
+use sqlx::PgConnection;
+use tracing::{info, warn};
 use uuid::Uuid;
 use validator::Validate;
 
@@ -66,6 +70,9 @@ impl From<RevokeLogRow> for GrantLogDto {
 /// 2. 扣减 user_badges 数量（归零时标记为 Revoked）
 /// 3. 写入 badge_ledger（CANCEL + MANUAL）
 /// 4. 写入 user_badge_logs（REVOKE）
+/// 5. 登记反向级联（依赖可能要求最低持有数量，部分扣减也需检查）
+///
+/// 提交后执行反向级联。
 pub async fn manual_revoke(
     State(state): State<AppState>,
     Json(req): Json<ManualRevokeRequest>,
@@ -73,14 +80,14 @@ pub async fn manual_revoke(
     req.validate()?;
 
     // 通过 user_badge_id 反查用户和徽章信息
-    let ub_row: Option<(String, i64, i32)> = sqlx::query_as(
-        "SELECT user_id, badge_id, quantity FROM user_badges WHERE id = $1",
+    let ub_row: Option<(String, i64, i32, String)> = sqlx::query_as(
+        "SELECT user_id, badge_id, quantity, status FROM user_badges WHERE id = $1",
     )
     .bind(req.user_badge_id)
     .fetch_optional(&state.pool)
     .await?;
 
-    let (user_id, badge_id, current_qty) = ub_row.ok_or_else(|| {
+    let (user_id, badge_id, current_qty, status) = ub_row.ok_or_else(|| {
         AdminError::NotFound(format!("用户徽章记录不存在: {}", req.user_badge_id))
     })?;
 
@@ -176,8 +183,22 @@ pub async fn manual_revoke(
     .execute(&mut *tx)
     .await?;
 
+    // 5. 登记反向级联
+    let pending_cascade = enqueue_revoke_cascade(&state, &mut tx, &user_id, badge_id).await?;
+
     tx.commit().await?;
 
+    // 非有效状态的记录不计入排行榜，扣减后无需同步
+    if status.eq_ignore_ascii_case("active") {
+        state
+            .record_leaderboard(BadgeChange::removed(&user_id, badge_id, quantity, remaining))
+            .await;
+    }
+
+    if let Some(pending_id) = pending_cascade {
+        run_revoke_cascade(&state, pending_id, &user_id, badge_id).await;
+    }
+
     info!(
         user_badge_id = req.user_badge_id,
         user_id = %user_id,
@@ -477,7 +498,8 @@ pub struct RevokedBadgeInfo {
 /// POST /api/admin/revokes/auto
 ///
 /// 用于账号注销、身份变更、条件不满足等自动触发的撤销场景。
-/// 支持撤销指定用户的单个徽章或所有徽章。
+/// 支持撤销指定用户的单个徽章或所有徽章，每个被撤销的徽章与撤销同事务登记反向级联，
+/// 提交后执行。
 pub async fn auto_revoke(
     State(state): State<AppState>,
     Json(req): Json<AutoRevokeRequest>,
@@ -538,6 +560,7 @@ pub async fn auto_revoke(
 
     let mut tx = state.pool.begin().await?;
     let mut revoked_badges = Vec::new();
+    let mut pending_cascades = Vec::new();
     let mut total_revoked = 0;
 
     for (user_badge_id, badge_id, badge_name, quantity) in badges_to_revoke {
@@ -603,6 +626,13 @@ pub async fn auto_revoke(
         .execute(&mut *tx)
         .await?;
 
+        // 5. 登记反向级联
+        if let Some(pending_id) =
+            enqueue_revoke_cascade(&state, &mut tx, &req.user_id, badge_id).await?
+        {
+            pending_cascades.push((pending_id, badge_id));
+        }
+
         revoked_badges.push(RevokedBadgeInfo {
             badge_id,
             badge_name,
@@ -621,6 +651,21 @@ pub async fn auto_revoke(
 
     tx.commit().await?;
 
+    for badge in &revoked_badges {
+        state
+            .record_leaderboard(BadgeChange::removed(
+                &req.user_id,
+                badge.badge_id,
+                badge.quantity,
+                0,
+            ))
+            .await;
+    }
+
+    for (pending_id, badge_id) in pending_cascades {
+        run_revoke_cascade(&state, pending_id, &req.user_id, badge_id).await;
+    }
+
     info!(
         user_id = %req.user_id,
         scenario = %scenario_str,
@@ -637,6 +682,55 @@ pub async fn auto_revoke(
     })))
 }
 
+/// 在撤销事务内登记反向级联，未配置级联评估器时跳过
+async fn enqueue_revoke_cascade(
+    state: &AppState,
+    tx: &mut PgConnection,
+    user_id: &str,
+    badge_id: i64,
+) -> Result<Option<i64>, AdminError> {
+    let Some(evaluator) = &state.cascade_evaluator else {
+        return Ok(None);
+    };
+    Ok(evaluator
+        .enqueue_revoke_in_tx(tx, user_id, badge_id)
+        .await?)
+}
+
+/// 执行撤销事务登记的反向级联，受影响的徽章同步扣减排行榜
+///
+/// 级联失败只记录日志：待处理记录已随撤销事务提交，由过期 Worker 重试
+async fn run_revoke_cascade(state: &AppState, pending_id: i64, user_id: &str, badge_id: i64) {
+    let Some(evaluator) = &state.cascade_evaluator else {
+        return;
+    };
+
+    match evaluator
+        .evaluate_pending_revoke(pending_id, user_id, badge_id)
+        .await
+    {
+        Ok(result) => {
+            for dependent in &result.revoked_badges {
+                state
+                    .record_leaderboard(BadgeChange::removed(
+                        user_id,
+                        dependent.badge_id,
+                        dependent.quantity,
+                        0,
+                    ))
+                    .await;
+            }
+        }
+        Err(e) => warn!(
+            user_id = %user_id,
+            badge_id,
+            pending_id,
+            error = %e,
+            "撤销徽章反向级联失败，已登记待重试"
+        ),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::dto::ManualRevokeRequest;
@@ -2,15 +2,20 @@
 //!
 //! 实现徽章获取规则的 CRUD、发布和测试操作。
 //! 规则与规则引擎配合，定义用户获取徽章的自动触发条件。
+//! 每次编辑规则都会在 badge_rule_versions 中生成不可变的版本快照，支持历史查询、差异对比和回滚。
 
 use axum::{
     Extension,
     Json,
     extract::{Path, Query, State},
 };
-use crate::middleware::AuditContext;
+use crate::{auth::Claims, middleware::AuditContext};
+use badge_shared::observability::middleware::grpc::traced_request;
+use badge_shared::rules::RuleReloadEvent;
 use badge_proto::rule_engine::{
-    self, ConditionNode, GroupNode, Operator as ProtoOperator,
+    self, AggregateFunction as ProtoAggregateFunction, AggregateNode, ConditionNode, GroupNode,
+    Operator as ProtoOperator, TimeUnit as ProtoTimeUnit, TimeWindow as ProtoTimeWindow,
+    WindowType as ProtoWindowType,
     LogicalOperator as ProtoLogicalOperator, Rule as ProtoRule, RuleNode as ProtoRuleNode,
     TestRuleRequest as ProtoTestRuleRequest,
 };
@@ -22,6 +27,7 @@ use validator::Validate;
 use crate::{
     dto::{
         ApiResponse, CreateRuleRequest, PageResponse, PaginationParams, RuleDto,
+        RuleFieldChangeDto, RuleVersionDiffDto, RuleVersionDiffParams, RuleVersionDto,
         TestRuleDefinitionRequest, UpdateRuleRequest,
     },
     error::AdminError,
@@ -45,6 +51,7 @@ struct RuleFullRow {
     global_quota: Option<i32>,
     global_granted: i32,
     enabled: bool,
+    current_version: i32,
     created_at: DateTime<Utc>,
     updated_at: DateTime<Utc>,
 }
@@ -66,6 +73,7 @@ impl From<RuleFullRow> for RuleDto {
             global_quota: row.global_quota,
             global_granted: row.global_granted,
             enabled: row.enabled,
+            current_version: row.current_version,
             created_at: row.created_at,
             updated_at: row.updated_at,
         }
@@ -89,6 +97,7 @@ const RULE_FULL_SQL: &str = r#"
         r.global_quota,
         COALESCE(r.global_granted, 0) as global_granted,
         r.enabled,
+        r.current_version,
         r.created_at,
         r.updated_at
     FROM badge_rules r
@@ -113,6 +122,7 @@ async fn fetch_rule_by_id(pool: &sqlx::PgPool, id: i64) -> Result<RuleDto, Admin
 /// POST /api/admin/rules
 pub async fn create_rule(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Json(req): Json<CreateRuleRequest>,
 ) -> Result<Json<ApiResponse<RuleDto>>, AdminError> {
     req.validate()?;
@@ -146,6 +156,8 @@ pub async fn create_rule(
         )));
     }
 
+    let mut tx = state.pool.begin().await?;
+
     // 新建规则默认禁用，需要单独发布
     let row: (i64,) = sqlx::query_as(
         r#"
@@ -164,9 +176,12 @@ pub async fn create_rule(
     .bind(req.end_time)
     .bind(req.max_count_per_user)
     .bind(req.global_quota)
-    .fetch_one(&state.pool)
+    .fetch_one(&mut *tx)
     .await?;
 
+    record_rule_version(&mut tx, row.0, "create", None, &claims.username).await?;
+    tx.commit().await?;
+
     info!(rule_id = row.0, badge_id = req.badge_id, "Rule created");
 
     let dto = fetch_rule_by_id(&state.pool, row.0).await?;
@@ -229,19 +244,11 @@ pub async fn update_rule(
     State(state): State<AppState>,
     Path(id): Path<i64>,
     Extension(audit_ctx): Extension<AuditContext>,
+    Extension(claims): Extension<Claims>,
     Json(req): Json<UpdateRuleRequest>,
 ) -> Result<Json<ApiResponse<RuleDto>>, AdminError> {
     req.validate()?;
 
-    let exists: (bool,) = sqlx::query_as("SELECT EXISTS(SELECT 1 FROM badge_rules WHERE id = $1)")
-        .bind(id)
-        .fetch_one(&state.pool)
-        .await?;
-
-    if !exists.0 {
-        return Err(AdminError::RuleNotFound(id));
-    }
-
     // 校验 rule_json（如提供）
     if let Some(ref rule_json) = req.rule_json
         && rule_json.is_null()
@@ -252,6 +259,28 @@ pub async fn update_rule(
     // 审计快照：记录变更前状态
     audit_ctx.snapshot(&state.pool, "badge_rules", id).await;
 
+    let mut tx = state.pool.begin().await?;
+
+    // 行锁保证并发编辑时版本号严格递增
+    let current: Option<(Option<String>, bool)> =
+        sqlx::query_as("SELECT event_type, enabled FROM badge_rules WHERE id = $1 FOR UPDATE")
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+    let (old_event_type, was_enabled) = current.ok_or(AdminError::RuleNotFound(id))?;
+
+    // 仅切换启用状态不产生新版本，版本只记录规则定义本身的变化
+    let creates_version = req.event_type.is_some()
+        || req.rule_code.is_some()
+        || req.name.is_some()
+        || req.description.is_some()
+        || req.global_quota.is_some()
+        || req.rule_json.is_some()
+        || req.start_time.is_some()
+        || req.end_time.is_some()
+        || req.max_count_per_user.is_some();
+
     sqlx::query(
         r#"
         UPDATE badge_rules
@@ -266,6 +295,7 @@ pub async fn update_rule(
             end_time = COALESCE($9, end_time),
             max_count_per_user = COALESCE($10, max_count_per_user),
             enabled = COALESCE($11, enabled),
+            current_version = current_version + CASE WHEN $12 THEN 1 ELSE 0 END,
             updated_at = NOW()
         WHERE id = $1
         "#,
@@ -281,12 +311,30 @@ pub async fn update_rule(
     .bind(req.end_time)
     .bind(req.max_count_per_user)
     .bind(req.enabled)
-    .execute(&state.pool)
+    .bind(creates_version)
+    .execute(&mut *tx)
     .await?;
 
+    if creates_version {
+        record_rule_version(&mut tx, id, "update", None, &claims.username).await?;
+    }
+    tx.commit().await?;
+
     info!(rule_id = id, "Rule updated");
 
     let dto = fetch_rule_by_id(&state.pool, id).await?;
+
+    // 草稿状态的规则修改不影响线上，仅在变更前后任一时刻处于启用状态时通知刷新；
+    // 事件类型变更时新旧两个服务组都需要刷新
+    if was_enabled || dto.enabled {
+        let event_types: Vec<&str> = old_event_type
+            .as_deref()
+            .into_iter()
+            .chain([dto.event_type.as_str()])
+            .collect();
+        notify_rule_reload(&state, id, &event_types, "rule_updated").await;
+    }
+
     Ok(Json(ApiResponse::success(dto)))
 }
 
@@ -300,10 +348,11 @@ pub async fn delete_rule(
     Path(id): Path<i64>,
     Extension(audit_ctx): Extension<AuditContext>,
 ) -> Result<Json<ApiResponse<()>>, AdminError> {
-    let rule: Option<(bool,)> = sqlx::query_as("SELECT enabled FROM badge_rules WHERE id = $1")
-        .bind(id)
-        .fetch_optional(&state.pool)
-        .await?;
+    let rule: Option<(bool, Option<String>)> =
+        sqlx::query_as("SELECT enabled, event_type FROM badge_rules WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&state.pool)
+            .await?;
 
     let rule = rule.ok_or(AdminError::RuleNotFound(id))?;
 
@@ -324,6 +373,10 @@ pub async fn delete_rule(
 
     info!(rule_id = id, "Rule deleted");
 
+    // 规则删除前已禁用，这里再通知一次，避免之前的禁用事件丢失导致残留
+    let event_types: Vec<&str> = rule.1.as_deref().into_iter().collect();
+    notify_rule_reload(&state, id, &event_types, "rule_deleted").await;
+
     Ok(Json(ApiResponse::<()>::success_empty()))
 }
 
@@ -355,6 +408,397 @@ pub async fn publish_rule(
     info!(rule_id = id, "Rule published (enabled)");
 
     let dto = fetch_rule_by_id(&state.pool, id).await?;
+    notify_rule_reload(&state, id, &[&dto.event_type], "rule_published").await;
+
+    Ok(Json(ApiResponse::success(dto)))
+}
+
+/// 通知规则引擎和事件处理服务刷新规则
+///
+/// 按规则事件类型所属的服务组分别发送刷新事件。发送失败只记录告警而不影响接口结果，
+/// 各服务仍会在下一次定时刷新时加载到最新规则。
+pub(crate) async fn notify_rule_reload(
+    state: &AppState,
+    rule_id: i64,
+    event_types: &[&str],
+    trigger: &str,
+) {
+    let Some(ref publisher) = state.rule_reload_publisher else {
+        return;
+    };
+
+    let mut event_types: Vec<String> = event_types.iter().map(|s| s.to_string()).collect();
+    event_types.sort();
+    event_types.dedup();
+
+    let groups: Vec<(String,)> = match sqlx::query_as(
+        "SELECT DISTINCT service_group FROM event_types WHERE code = ANY($1) AND service_group IS NOT NULL",
+    )
+    .bind(&event_types)
+    .fetch_all(&state.pool)
+    .await
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            warn!(rule_id, error = %e, "查询事件类型服务组失败，将通知所有服务刷新");
+            Vec::new()
+        }
+    };
+
+    let base = RuleReloadEvent::new(format!("admin:{}", trigger)).with_rule_id(rule_id);
+    let base = match event_types.as_slice() {
+        [event_type] => base.with_event_type(event_type.as_str()),
+        _ => base,
+    };
+
+    let events: Vec<RuleReloadEvent> = if groups.is_empty() {
+        vec![base]
+    } else {
+        groups
+            .into_iter()
+            .map(|(group,)| base.clone().with_service_group(group))
+            .collect()
+    };
+
+    for event in &events {
+        if let Err(e) = publisher.publish(event).await {
+            warn!(rule_id, error = %e, "规则刷新事件发送失败，等待定时刷新生效");
+        }
+    }
+}
+
+// ─── 规则版本 ─────────────────────────────────────────────────────────
+
+/// 规则版本查询结果
+#[derive(sqlx::FromRow)]
+struct RuleVersionRow {
+    rule_id: i64,
+    version: i32,
+    event_type: Option<String>,
+    rule_code: Option<String>,
+    name: Option<String>,
+    description: Option<String>,
+    rule_json: Value,
+    start_time: Option<DateTime<Utc>>,
+    end_time: Option<DateTime<Utc>>,
+    max_count_per_user: Option<i32>,
+    global_quota: Option<i32>,
+    change_type: String,
+    source_version: Option<i32>,
+    created_by: Option<String>,
+    created_at: DateTime<Utc>,
+}
+
+impl RuleVersionRow {
+    fn into_dto(self, live_version: i32) -> RuleVersionDto {
+        RuleVersionDto {
+            rule_id: self.rule_id,
+            version: self.version,
+            event_type: self.event_type,
+            rule_code: self.rule_code,
+            name: self.name,
+            description: self.description,
+            rule_json: self.rule_json,
+            start_time: self.start_time,
+            end_time: self.end_time,
+            max_count_per_user: self.max_count_per_user,
+            global_quota: self.global_quota,
+            change_type: self.change_type,
+            source_version: self.source_version,
+            created_by: self.created_by,
+            created_at: self.created_at,
+            is_live: self.version == live_version,
+        }
+    }
+}
+
+const RULE_VERSION_SQL: &str = r#"
+    SELECT
+        rule_id, version, event_type, rule_code, name, description, rule_json,
+        start_time, end_time, max_count_per_user, global_quota,
+        change_type, source_version, created_by, created_at
+    FROM badge_rule_versions
+"#;
+
+/// 参与版本对比的规则属性（camelCase，与 RuleVersionDto 序列化后的键一致）
+const VERSIONED_FIELDS: &[&str] = &[
+    "eventType",
+    "ruleCode",
+    "name",
+    "description",
+    "startTime",
+    "endTime",
+    "maxCountPerUser",
+    "globalQuota",
+];
+
+/// 以 badge_rules 当前行生成版本快照
+///
+/// 版本号取自 `current_version`，调用方需在同一事务中先更新规则行（含版本号递增）再调用。
+pub(crate) async fn record_rule_version(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    rule_id: i64,
+    change_type: &str,
+    source_version: Option<i32>,
+    created_by: &str,
+) -> Result<i32, AdminError> {
+    let row: (i32,) = sqlx::query_as(
+        r#"
+        INSERT INTO badge_rule_versions (
+            rule_id, version, event_type, rule_code, name, description, rule_json,
+            start_time, end_time, max_count_per_user, global_quota,
+            change_type, source_version, created_by
+        )
+        SELECT
+            id, current_version, event_type, rule_code, name, description, rule_json,
+            start_time, end_time, max_count_per_user, global_quota,
+            $2, $3, $4
+        FROM badge_rules
+        WHERE id = $1
+        RETURNING version
+        "#,
+    )
+    .bind(rule_id)
+    .bind(change_type)
+    .bind(source_version)
+    .bind(created_by)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(row.0)
+}
+
+/// 查询规则当前生效的版本号
+async fn fetch_live_version(pool: &sqlx::PgPool, id: i64) -> Result<i32, AdminError> {
+    let row: Option<(i32,)> = sqlx::query_as("SELECT current_version FROM badge_rules WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+    row.map(|r| r.0).ok_or(AdminError::RuleNotFound(id))
+}
+
+/// 查询规则的指定版本
+async fn fetch_rule_version(
+    pool: &sqlx::PgPool,
+    id: i64,
+    version: i32,
+) -> Result<RuleVersionRow, AdminError> {
+    let sql = format!("{} WHERE rule_id = $1 AND version = $2", RULE_VERSION_SQL);
+
+    sqlx::query_as::<_, RuleVersionRow>(&sql)
+        .bind(id)
+        .bind(version)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AdminError::NotFound(format!("规则 {} 的版本 {} 不存在", id, version)))
+}
+
+/// 获取规则版本历史（分页，按版本号倒序）
+///
+/// GET /api/admin/rules/:id/versions
+pub async fn list_rule_versions(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Query(pagination): Query<PaginationParams>,
+) -> Result<Json<ApiResponse<PageResponse<RuleVersionDto>>>, AdminError> {
+    let live_version = fetch_live_version(&state.pool, id).await?;
+
+    let total: (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM badge_rule_versions WHERE rule_id = $1")
+            .bind(id)
+            .fetch_one(&state.pool)
+            .await?;
+
+    if total.0 == 0 {
+        return Ok(Json(ApiResponse::success(PageResponse::empty(
+            pagination.page,
+            pagination.page_size,
+        ))));
+    }
+
+    let sql = format!(
+        "{} WHERE rule_id = $1 ORDER BY version DESC LIMIT $2 OFFSET $3",
+        RULE_VERSION_SQL
+    );
+
+    let rows = sqlx::query_as::<_, RuleVersionRow>(&sql)
+        .bind(id)
+        .bind(pagination.limit())
+        .bind(pagination.offset())
+        .fetch_all(&state.pool)
+        .await?;
+
+    let items: Vec<RuleVersionDto> = rows.into_iter().map(|r| r.into_dto(live_version)).collect();
+    let response = PageResponse::new(items, total.0, pagination.page, pagination.page_size);
+    Ok(Json(ApiResponse::success(response)))
+}
+
+/// 获取规则的指定版本
+///
+/// GET /api/admin/rules/:id/versions/:version
+pub async fn get_rule_version(
+    State(state): State<AppState>,
+    Path((id, version)): Path<(i64, i32)>,
+) -> Result<Json<ApiResponse<RuleVersionDto>>, AdminError> {
+    let live_version = fetch_live_version(&state.pool, id).await?;
+    let row = fetch_rule_version(&state.pool, id, version).await?;
+    Ok(Json(ApiResponse::success(row.into_dto(live_version))))
+}
+
+/// 对比规则的两个版本
+///
+/// GET /api/admin/rules/:id/versions/diff?from=1&to=2
+///
+/// 返回规则属性（时间窗口、配额等）的变化和规则树的结构化差异。
+pub async fn diff_rule_versions(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Query(params): Query<RuleVersionDiffParams>,
+) -> Result<Json<ApiResponse<RuleVersionDiffDto>>, AdminError> {
+    let live_version = fetch_live_version(&state.pool, id).await?;
+    let from = fetch_rule_version(&state.pool, id, params.from)
+        .await?
+        .into_dto(live_version);
+    let to = fetch_rule_version(&state.pool, id, params.to)
+        .await?
+        .into_dto(live_version);
+
+    Ok(Json(ApiResponse::success(build_version_diff(&from, &to))))
+}
+
+/// 计算两个版本快照之间的差异
+fn build_version_diff(from: &RuleVersionDto, to: &RuleVersionDto) -> RuleVersionDiffDto {
+    let before = serde_json::to_value(from).unwrap_or_default();
+    let after = serde_json::to_value(to).unwrap_or_default();
+
+    let field_changes = VERSIONED_FIELDS
+        .iter()
+        .filter_map(|field| {
+            let b = before.get(*field).cloned().unwrap_or(Value::Null);
+            let a = after.get(*field).cloned().unwrap_or(Value::Null);
+            (b != a).then(|| RuleFieldChangeDto {
+                field: field.to_string(),
+                before: b,
+                after: a,
+            })
+        })
+        .collect();
+
+    let node_changes = match (
+        serde_json::from_value::<::rule_engine::RuleNode>(from.rule_json.clone()),
+        serde_json::from_value::<::rule_engine::RuleNode>(to.rule_json.clone()),
+    ) {
+        (Ok(old), Ok(new)) => ::rule_engine::diff_rule_nodes(&old, &new),
+        // 早期规则的 rule_json 可能不符合规则引擎格式，无法逐节点对比时整体视为修改
+        _ if from.rule_json != to.rule_json => vec![::rule_engine::NodeChange {
+            path: "root".to_string(),
+            kind: ::rule_engine::ChangeKind::Modified,
+            fields: Vec::new(),
+            before: Some(from.rule_json.clone()),
+            after: Some(to.rule_json.clone()),
+        }],
+        _ => Vec::new(),
+    };
+
+    RuleVersionDiffDto {
+        rule_id: from.rule_id,
+        from_version: from.version,
+        to_version: to.version,
+        field_changes,
+        node_changes,
+    }
+}
+
+/// 回滚规则到指定版本
+///
+/// POST /api/admin/rules/:id/versions/:version/rollback
+///
+/// 回滚不会改写历史：以目标版本的内容生成一个新版本并设为生效版本，
+/// 规则的启用状态保持不变。
+pub async fn rollback_rule(
+    State(state): State<AppState>,
+    Path((id, version)): Path<(i64, i32)>,
+    Extension(audit_ctx): Extension<AuditContext>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<ApiResponse<RuleDto>>, AdminError> {
+    let target = fetch_rule_version(&state.pool, id, version).await?;
+
+    // 审计快照：记录变更前状态
+    audit_ctx.snapshot(&state.pool, "badge_rules", id).await;
+
+    let mut tx = state.pool.begin().await?;
+
+    let current: Option<(Option<String>, bool, i32)> = sqlx::query_as(
+        "SELECT event_type, enabled, current_version FROM badge_rules WHERE id = $1 FOR UPDATE",
+    )
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let (old_event_type, enabled, live_version) = current.ok_or(AdminError::RuleNotFound(id))?;
+
+    if live_version == version {
+        return Err(AdminError::Validation(format!(
+            "版本 {} 已是当前生效版本",
+            version
+        )));
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE badge_rules
+        SET
+            event_type = $2,
+            rule_code = $3,
+            name = $4,
+            description = $5,
+            rule_json = $6,
+            start_time = $7,
+            end_time = $8,
+            max_count_per_user = $9,
+            global_quota = $10,
+            current_version = current_version + 1,
+            updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(&target.event_type)
+    .bind(&target.rule_code)
+    .bind(&target.name)
+    .bind(&target.description)
+    .bind(&target.rule_json)
+    .bind(target.start_time)
+    .bind(target.end_time)
+    .bind(target.max_count_per_user)
+    .bind(target.global_quota)
+    .execute(&mut *tx)
+    .await?;
+
+    let new_version =
+        record_rule_version(&mut tx, id, "rollback", Some(version), &claims.username).await?;
+    tx.commit().await?;
+
+    info!(
+        rule_id = id,
+        from_version = live_version,
+        target_version = version,
+        new_version,
+        "Rule rolled back"
+    );
+
+    let dto = fetch_rule_by_id(&state.pool, id).await?;
+
+    if enabled {
+        let event_types: Vec<&str> = old_event_type
+            .as_deref()
+            .into_iter()
+            .chain([dto.event_type.as_str()])
+            .collect();
+        notify_rule_reload(&state, id, &event_types, "rule_rolled_back").await;
+    }
+
     Ok(Json(ApiResponse::success(dto)))
 }
 
@@ -370,13 +814,14 @@ fn json_to_proto_rule(
     rule_json: &Value,
     id: &str,
     name: &str,
+    version: &str,
 ) -> Result<ProtoRule, AdminError> {
     let root = json_to_proto_rule_node(rule_json)?;
 
     Ok(ProtoRule {
         id: id.to_string(),
         name: name.to_string(),
-        version: "1.0".to_string(),
+        version: version.to_string(),
         root: Some(root),
         created_at: None,
         updated_at: None,
@@ -444,8 +889,62 @@ fn json_to_proto_rule_node(value: &Value) -> Result<ProtoRuleNode, AdminError> {
                 })),
             })
         }
+        "aggregate" => {
+            let str_field = |key: &str| {
+                obj.get(key)
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string()
+            };
+
+            let function = str_to_proto_aggregate_function(&str_field("function"))?;
+            let operator = str_to_proto_operator(&str_field("operator"))?;
+
+            let window = obj
+                .get("window")
+                .and_then(|v| v.as_object())
+                .ok_or_else(|| {
+                    AdminError::InvalidRuleJson("aggregate 节点缺少 window 对象".to_string())
+                })?;
+            let window_type = match window.get("type").and_then(|v| v.as_str()) {
+                // 与规则引擎一致，未指定窗口类型时按滑动窗口处理
+                None | Some("sliding") => ProtoWindowType::Sliding,
+                Some("tumbling") => ProtoWindowType::Tumbling,
+                Some(other) => {
+                    return Err(AdminError::InvalidRuleJson(format!(
+                        "未知的窗口类型: {}，期望 sliding 或 tumbling",
+                        other
+                    )));
+                }
+            };
+            let size = window
+                .get("size")
+                .and_then(|v| v.as_u64())
+                .and_then(|v| u32::try_from(v).ok())
+                .ok_or_else(|| {
+                    AdminError::InvalidRuleJson("aggregate 节点的 window.size 必须是正整数".to_string())
+                })?;
+            let unit = str_to_proto_time_unit(
+                window.get("unit").and_then(|v| v.as_str()).unwrap_or_default(),
+            )?;
+
+            Ok(ProtoRuleNode {
+                node: Some(rule_engine::rule_node::Node::Aggregate(AggregateNode {
+                    function: function.into(),
+                    event_type: str_field("event_type"),
+                    field: str_field("field"),
+                    window: Some(ProtoTimeWindow {
+                        r#type: window_type.into(),
+                        size,
+                        unit: unit.into(),
+                    }),
+                    operator: operator.into(),
+                    value: obj.get("value").map(json_value_to_proto_value),
+                })),
+            })
+        }
         other => Err(AdminError::InvalidRuleJson(format!(
-            "未知的规则节点类型: {}，期望 condition 或 group",
+            "未知的规则节点类型: {}，期望 condition、group 或 aggregate",
             other
         ))),
     }
@@ -480,6 +979,35 @@ fn str_to_proto_operator(s: &str) -> Result<ProtoOperator, AdminError> {
     }
 }
 
+/// 聚合函数字符串映射到 Proto 枚举
+fn str_to_proto_aggregate_function(s: &str) -> Result<ProtoAggregateFunction, AdminError> {
+    match s {
+        "count" => Ok(ProtoAggregateFunction::Count),
+        "sum" => Ok(ProtoAggregateFunction::Sum),
+        "distinct_count" => Ok(ProtoAggregateFunction::DistinctCount),
+        "streak" => Ok(ProtoAggregateFunction::Streak),
+        other => Err(AdminError::InvalidRuleJson(format!(
+            "未知的聚合函数: {}",
+            other
+        ))),
+    }
+}
+
+/// 窗口时间单位字符串映射到 Proto 枚举
+fn str_to_proto_time_unit(s: &str) -> Result<ProtoTimeUnit, AdminError> {
+    match s {
+        "minute" => Ok(ProtoTimeUnit::Minute),
+        "hour" => Ok(ProtoTimeUnit::Hour),
+        "day" => Ok(ProtoTimeUnit::Day),
+        "week" => Ok(ProtoTimeUnit::Week),
+        "month" => Ok(ProtoTimeUnit::Month),
+        other => Err(AdminError::InvalidRuleJson(format!(
+            "未知的窗口时间单位: {}",
+            other
+        ))),
+    }
+}
+
 /// 逻辑操作符字符串映射到 Proto 枚举
 fn str_to_proto_logical_operator(s: &str) -> Result<ProtoLogicalOperator, AdminError> {
     // 兼容大写（Proto 惯例）和大写全称（JSON 存储格式）
@@ -560,7 +1088,12 @@ pub async fn test_rule(
     let rule = fetch_rule_by_id(&state.pool, id).await?;
 
     // 将数据库中的 rule_json 转为 Proto Rule，供 gRPC 调用
-    let proto_rule = json_to_proto_rule(&rule.rule_json, &id.to_string(), &format!("rule-{}", id))?;
+    let proto_rule = json_to_proto_rule(
+        &rule.rule_json,
+        &id.to_string(),
+        &format!("rule-{}", id),
+        &rule.current_version.to_string(),
+    )?;
 
     // 从请求体提取上下文（可选），不存在则使用空上下文
     let context = test_data
@@ -598,7 +1131,7 @@ pub async fn test_rule(
     let response = cb.call(|| {
         let mut c = client.clone();
         let req = grpc_request.clone();
-        async move { c.test_rule(tonic::Request::new(req)).await }
+        async move { c.test_rule(traced_request(req)).await }
     })
     .await
     .map_err(|e| {
@@ -645,6 +1178,8 @@ pub async fn disable_rule(
     info!(rule_id = id, "Rule disabled");
 
     let dto = fetch_rule_by_id(&state.pool, id).await?;
+    notify_rule_reload(&state, id, &[&dto.event_type], "rule_disabled").await;
+
     Ok(Json(ApiResponse::success(dto)))
 }
 
@@ -664,7 +1199,7 @@ pub async fn test_rule_definition(
 
     // 临时规则使用 UUID 标识，不与数据库中的规则关联
     let temp_id = uuid::Uuid::new_v4().to_string();
-    let proto_rule = json_to_proto_rule(&req.rule_json, &temp_id, "test-rule")?;
+    let proto_rule = json_to_proto_rule(&req.rule_json, &temp_id, "test-rule", "draft")?;
 
     let proto_context = req
         .context
@@ -689,7 +1224,7 @@ pub async fn test_rule_definition(
     let response = cb.call(|| {
         let mut c = client.clone();
         let req = grpc_request.clone();
-        async move { c.test_rule(tonic::Request::new(req)).await }
+        async move { c.test_rule(traced_request(req)).await }
     })
     .await
     .map_err(|e| {
@@ -765,6 +1300,7 @@ mod tests {
             global_quota: None,
             global_granted: 0,
             enabled: false,
+            current_version: 2,
             created_at: now,
             updated_at: now,
         };
@@ -777,5 +1313,101 @@ mod tests {
         assert_eq!(dto.description, Some("测试规则描述".to_string()));
         assert_eq!(dto.max_count_per_user, Some(3));
         assert!(!dto.enabled);
+        assert_eq!(dto.current_version, 2);
+    }
+
+    fn version_dto(version: i32, rule_json: Value, global_quota: Option<i32>) -> RuleVersionDto {
+        RuleVersionDto {
+            rule_id: 1,
+            version,
+            event_type: Some("purchase".to_string()),
+            rule_code: Some("rule_001".to_string()),
+            name: Some("规则".to_string()),
+            description: None,
+            rule_json,
+            start_time: None,
+            end_time: None,
+            max_count_per_user: None,
+            global_quota,
+            change_type: "update".to_string(),
+            source_version: None,
+            created_by: Some("admin".to_string()),
+            created_at: Utc::now(),
+            is_live: false,
+        }
+    }
+
+    #[test]
+    fn test_build_version_diff() {
+        let v1 = version_dto(
+            1,
+            serde_json::json!({
+                "type": "group",
+                "operator": "AND",
+                "children": [
+                    {"type": "condition", "field": "amount", "operator": "gte", "value": 100}
+                ]
+            }),
+            None,
+        );
+        let v2 = version_dto(
+            2,
+            serde_json::json!({
+                "type": "group",
+                "operator": "AND",
+                "children": [
+                    {"type": "condition", "field": "amount", "operator": "gte", "value": 500}
+                ]
+            }),
+            Some(1000),
+        );
+
+        let diff = build_version_diff(&v1, &v2);
+        assert_eq!(diff.from_version, 1);
+        assert_eq!(diff.to_version, 2);
+        assert_eq!(diff.field_changes.len(), 1);
+        assert_eq!(diff.field_changes[0].field, "globalQuota");
+        assert_eq!(diff.field_changes[0].after, serde_json::json!(1000));
+        assert_eq!(diff.node_changes.len(), 1);
+        assert_eq!(diff.node_changes[0].path, "root.children[0]");
+        assert_eq!(diff.node_changes[0].fields, vec!["value"]);
+    }
+
+    #[test]
+    fn test_build_version_diff_with_legacy_rule_json() {
+        let v1 = version_dto(1, serde_json::json!({"type": "event"}), None);
+        let v2 = version_dto(2, serde_json::json!({"type": "event", "x": 1}), None);
+
+        let diff = build_version_diff(&v1, &v2);
+        assert!(diff.field_changes.is_empty());
+        assert_eq!(diff.node_changes.len(), 1);
+        assert_eq!(diff.node_changes[0].path, "root");
+
+        assert!(build_version_diff(&v1, &v1).node_changes.is_empty());
+    }
+
+    #[test]
+    fn test_json_to_proto_aggregate_node() {
+        let json = serde_json::json!({
+            "type": "aggregate",
+            "function": "sum",
+            "event_type": "purchase",
+            "field": "amount",
+            "window": {"type": "tumbling", "size": 3, "unit": "month"},
+            "operator": "gte",
+            "value": 2000
+        });
+
+        let node = json_to_proto_rule_node(&json).unwrap();
+        let Some(rule_engine::rule_node::Node::Aggregate(agg)) = node.node else {
+            panic!("应转换为聚合节点");
+        };
+        assert_eq!(agg.function(), ProtoAggregateFunction::Sum);
+        assert_eq!(agg.field, "amount");
+        assert_eq!(agg.operator(), ProtoOperator::Gte);
+        let window = agg.window.unwrap();
+        assert_eq!(window.r#type(), ProtoWindowType::Tumbling);
+        assert_eq!(window.unit(), ProtoTimeUnit::Month);
+        assert_eq!(window.size, 3);
     }
 }
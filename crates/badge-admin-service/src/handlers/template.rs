@@ -4,7 +4,7 @@
 //! 模板允许运营人员通过参数化配置快速创建规则，降低规则配置的复杂度。
 
 use axum::{
-    Json,
+    Extension, Json,
     extract::{Path, Query, State},
 };
 use chrono::{DateTime, Utc};
@@ -13,7 +13,7 @@ use serde_json::Value;
 use std::collections::HashMap;
 use tracing::info;
 
-use crate::{dto::ApiResponse, error::AdminError, state::AppState};
+use crate::{auth::Claims, dto::ApiResponse, error::AdminError, state::AppState};
 
 /// 模板列表查询参数
 #[derive(Debug, Deserialize)]
@@ -296,6 +296,7 @@ pub async fn preview_template(
 /// 通过模板和参数创建新的徽章规则。创建的规则会记录模板来源和版本，便于后续追踪模板更新。
 pub async fn create_rule_from_template(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Json(req): Json<CreateRuleFromTemplateRequest>,
 ) -> Result<Json<ApiResponse<CreateRuleFromTemplateResponse>>, AdminError> {
     use rule_engine::template::{ParameterDef, RuleTemplate, TemplateCategory, TemplateCompiler};
@@ -362,6 +363,8 @@ pub async fn create_rule_from_template(
     let enabled = req.enabled.unwrap_or(true);
     let params_json = serde_json::to_value(&req.params).unwrap_or_default();
 
+    let mut tx = state.pool.begin().await?;
+
     let rule_row: (i64,) = sqlx::query_as(
         r#"INSERT INTO badge_rules
            (badge_id, rule_json, template_id, template_version, template_params, enabled)
@@ -374,9 +377,13 @@ pub async fn create_rule_from_template(
     .bind(&version)
     .bind(&params_json)
     .bind(enabled)
-    .fetch_one(&state.pool)
+    .fetch_one(&mut *tx)
     .await?;
 
+    super::rule::record_rule_version(&mut tx, rule_row.0, "create", None, &claims.username)
+        .await?;
+    tx.commit().await?;
+
     info!(
         rule_id = rule_row.0,
         badge_id = req.badge_id,
@@ -385,6 +392,12 @@ pub async fn create_rule_from_template(
         "Rule created from template"
     );
 
+    // 模板创建的规则默认启用，与发布一样通知各服务加载；规则未设置事件类型，通知所有服务
+    if enabled {
+        super::rule::notify_rule_reload(&state, rule_row.0, &[], "rule_created_from_template")
+            .await;
+    }
+
     Ok(Json(ApiResponse::success(CreateRuleFromTemplateResponse {
         id: rule_row.0,
         badge_id: req.badge_id,
@@ -1,12 +1,14 @@
 //! 会员视图 API 处理器
 //!
 //! 提供以用户为中心的徽章查看能力：
-//! 用户徽章列表、兑换记录、统计汇总、账本流水。
+//! 用户徽章列表、兑换记录、统计汇总、账本流水、徽章进度。
 
 use axum::{
     Json,
     extract::{Path, Query, State},
 };
+use badge_proto::badge::GetBadgeProgressRequest;
+use badge_shared::observability::middleware::grpc::traced_request;
 use chrono::{DateTime, Utc};
 use tracing::instrument;
 
@@ -161,6 +163,7 @@ struct UserBadgeRow {
     status: String,
     acquired_at: DateTime<Utc>,
     expires_at: Option<DateTime<Utc>>,
+    pin_order: Option<i32>,
 }
 
 /// 查询用户持有的所有徽章
@@ -168,7 +171,8 @@ struct UserBadgeRow {
 /// GET /api/admin/users/:id/badges
 ///
 /// 关联 user_badges 和 badges 表，返回徽章基本信息、
-/// 持有数量、状态和获取/过期时间。
+/// 持有数量、状态、获取/过期时间和徽章墙置顶状态。
+/// 置顶徽章按展示顺序排在前面，其余按获取时间倒序。
 #[instrument(skip(state))]
 pub async fn get_user_badges(
     State(state): State<AppState>,
@@ -200,11 +204,12 @@ pub async fn get_user_badges(
             ub.quantity,
             ub.status::text as status,
             ub.first_acquired_at as acquired_at,
-            ub.expires_at
+            ub.expires_at,
+            ub.pin_order
         FROM user_badges ub
         JOIN badges b ON b.id = ub.badge_id
         WHERE ub.user_id = $1
-        ORDER BY ub.first_acquired_at DESC
+        ORDER BY ub.pin_order ASC NULLS LAST, ub.first_acquired_at DESC
         LIMIT $2 OFFSET $3
         "#,
     )
@@ -225,6 +230,8 @@ pub async fn get_user_badges(
             status: row.status,
             acquired_at: row.acquired_at,
             expires_at: row.expires_at,
+            pinned: row.pin_order.is_some(),
+            pin_order: row.pin_order,
         })
         .collect();
 
@@ -447,6 +454,131 @@ pub async fn get_user_ledger(
     Ok(Json(ApiResponse::success(response)))
 }
 
+// ---------------------------------------------------------------------------
+// 用户徽章进度
+// ---------------------------------------------------------------------------
+
+/// 徽章进度查询参数
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BadgeProgressParams {
+    /// 逗号分隔的徽章 ID，为空时返回所有有进度可展示的徽章
+    pub badge_ids: Option<String>,
+}
+
+/// 徽章进度 DTO
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BadgeProgressDto {
+    pub badge_id: String,
+    pub badge_name: String,
+    pub earned: bool,
+    pub quantity: i32,
+    pub percentage: f64,
+    pub items: Vec<ProgressItemDto>,
+}
+
+/// 进度项 DTO
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgressItemDto {
+    /// rule_aggregate / prerequisite / next_level
+    pub kind: String,
+    pub label: String,
+    pub current: f64,
+    pub target: f64,
+    pub percentage: f64,
+    pub achieved: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub related_badge_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level: Option<i32>,
+}
+
+impl From<badge_proto::badge::BadgeProgress> for BadgeProgressDto {
+    fn from(proto: badge_proto::badge::BadgeProgress) -> Self {
+        Self {
+            badge_id: proto.badge_id,
+            badge_name: proto.badge_name,
+            earned: proto.earned,
+            quantity: proto.quantity,
+            percentage: proto.percentage,
+            items: proto
+                .items
+                .into_iter()
+                .map(|item| ProgressItemDto {
+                    kind: item.kind,
+                    label: item.label,
+                    current: item.current,
+                    target: item.target,
+                    percentage: item.percentage,
+                    achieved: item.achieved,
+                    related_badge_id: (!item.related_badge_id.is_empty())
+                        .then_some(item.related_badge_id),
+                    level: (item.level > 0).then_some(item.level),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// 解析逗号分隔的徽章 ID 列表
+fn parse_badge_ids(raw: Option<&str>) -> Result<Vec<String>, AdminError> {
+    raw.unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            s.parse::<i64>()
+                .map(|id| id.to_string())
+                .map_err(|_| AdminError::Validation(format!("无效的徽章 ID: {}", s)))
+        })
+        .collect()
+}
+
+/// 查询用户徽章进度
+///
+/// GET /api/admin/users/:id/progress?badgeIds=1,2
+///
+/// 由 badge-management-service 计算未获得徽章的完成度（规则聚合条件、前置徽章）
+/// 和已获得分级徽章的升级进度。
+#[instrument(skip(state))]
+pub async fn get_user_badge_progress(
+    State(state): State<AppState>,
+    Path(user_id): Path<String>,
+    Query(params): Query<BadgeProgressParams>,
+) -> Result<Json<ApiResponse<Vec<BadgeProgressDto>>>, AdminError> {
+    let badge_ids = parse_badge_ids(params.badge_ids.as_deref())?;
+
+    let client = state
+        .badge_management_client
+        .read()
+        .await
+        .clone()
+        .ok_or_else(|| {
+            AdminError::Internal("Badge-management-service gRPC 客户端未配置".to_string())
+        })?;
+
+    let request = GetBadgeProgressRequest { user_id, badge_ids };
+    let response = state
+        .badge_mgmt_circuit_breaker
+        .call(|| {
+            let mut c = client.clone();
+            let request = request.clone();
+            async move { c.get_badge_progress(traced_request(request)).await }
+        })
+        .await
+        .map_err(|e| AdminError::Internal(format!("查询徽章进度失败: {}", e)))?;
+
+    let items = response
+        .into_inner()
+        .badges
+        .into_iter()
+        .map(BadgeProgressDto::from)
+        .collect();
+    Ok(Json(ApiResponse::success(items)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -463,10 +595,14 @@ mod tests {
             status: "active".to_string(),
             acquired_at: Utc::now(),
             expires_at: None,
+            pinned: true,
+            pin_order: Some(1),
         };
 
         let json = serde_json::to_string(&dto).unwrap();
         assert!(json.contains("\"id\":100"));
+        assert!(json.contains("\"pinned\":true"));
+        assert!(json.contains("\"pinOrder\":1"));
         assert!(json.contains("\"badgeId\":1"));
         assert!(json.contains("\"quantity\":3"));
         assert!(json.contains("\"expiresAt\":null"));
@@ -520,4 +656,44 @@ mod tests {
         assert!(json.contains("\"changeType\":\"acquire\""));
         assert!(json.contains("\"sourceType\":\"manual\""));
     }
+
+    #[test]
+    fn test_parse_badge_ids() {
+        assert!(parse_badge_ids(None).unwrap().is_empty());
+        assert_eq!(
+            parse_badge_ids(Some("1, 2,,3")).unwrap(),
+            vec!["1", "2", "3"]
+        );
+        assert!(matches!(
+            parse_badge_ids(Some("1,abc")),
+            Err(AdminError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_badge_progress_dto_from_proto() {
+        let proto = badge_proto::badge::BadgeProgress {
+            badge_id: "10".to_string(),
+            badge_name: "签到达人".to_string(),
+            earned: false,
+            quantity: 0,
+            percentage: 60.0,
+            items: vec![badge_proto::badge::ProgressItem {
+                kind: "rule_aggregate".to_string(),
+                label: "count(checkin.*)@sliding:30day".to_string(),
+                current: 3.0,
+                target: 5.0,
+                percentage: 60.0,
+                achieved: false,
+                related_badge_id: String::new(),
+                level: 0,
+            }],
+        };
+
+        let json = serde_json::to_value(BadgeProgressDto::from(proto)).unwrap();
+        assert_eq!(json["badgeId"], "10");
+        assert_eq!(json["items"][0]["target"], 5.0);
+        assert!(json["items"][0].get("relatedBadgeId").is_none());
+        assert!(json["items"][0].get("level").is_none());
+    }
 }
@@ -49,6 +49,6 @@ pub use models::{BatchTask, BatchTaskStatus, BatchTaskType, OperationLog};
 // 从 badge-management-service 重新导出核心模型
 // 便于 admin service 的其他模块直接使用
 pub use badge_management::{
-    Badge, BadgeAssets, BadgeCategory, BadgeRule, BadgeSeries, BadgeStatus, BadgeType,
-    CategoryStatus, SourceType, ValidityConfig, ValidityType,
+    Badge, BadgeAssets, BadgeCategory, BadgeLevel, BadgeRule, BadgeSeries, BadgeStatus,
+    BadgeType, CategoryStatus, SourceType, ValidityConfig, ValidityType,
 };
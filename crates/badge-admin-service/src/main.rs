@@ -14,9 +14,13 @@ use badge_shared::{
     config_watcher::{self, DynamicConfig},
     crypto::FieldEncryptor,
     database::Database,
+    dlq::{DeadLetterStore, DlqConsumer, DlqReplayScheduler, PgDeadLetterStore},
+    kafka::KafkaProducer,
     observability::{self, middleware as obs_middleware},
+    rules::RuleReloadPublisher,
 };
 use tokio::net::TcpListener;
+use tokio::sync::watch;
 use tower_http::cors::{Any, CorsLayer};
 use tracing::{info, warn};
 
@@ -95,14 +99,53 @@ async fn main() -> anyhow::Result<()> {
     let redemption_repo = Arc::new(
         badge_management::RedemptionRepository::new(db.pool().clone()),
     );
-    let redemption_service = Arc::new(badge_management::RedemptionService::new(
-        redemption_repo,
-        cache.clone(),
-        db.pool().clone(),
-    ));
+    let redemption_timezone = config.redemption.tz().unwrap_or_else(|e| {
+        warn!(
+            timezone = %config.redemption.timezone,
+            error = %e,
+            "兑换频率限制时区无效，回退到 Asia/Shanghai"
+        );
+        chrono_tz::Asia::Shanghai
+    });
+    let redemption_service = Arc::new(
+        badge_management::RedemptionService::new(redemption_repo, cache.clone(), db.pool().clone())
+            .with_timezone(redemption_timezone),
+    );
+    redemption_service
+        .set_leaderboard(Arc::new(state.leaderboard()))
+        .await;
     state.set_redemption_service(redemption_service);
     info!("RedemptionService initialized");
 
+    // 级联评估器：手动/自动撤销后执行反向级联、依赖变更后刷新依赖图，
+    // 过期 Worker 共用同一实例。反向级联只读依赖图、写用户徽章，不需要发放服务
+    state.set_cascade_evaluator(Arc::new(badge_management::cascade::CascadeEvaluator::new(
+        badge_management::cascade::CascadeConfig::default(),
+        Arc::new(badge_management::repository::DependencyRepository::new(
+            db.pool().clone(),
+        )),
+        Arc::new(badge_management::UserBadgeRepository::new(db.pool().clone())),
+    )));
+
+    // 规则变更后通过 Kafka 通知规则引擎和事件处理服务即时刷新；
+    // 同一个生产者也用于通知任务分发和死信重放
+    let mut notification_producer = None;
+    match KafkaProducer::new(&config.kafka) {
+        Ok(producer) => {
+            state.set_rule_reload_publisher(RuleReloadPublisher::new(producer.clone()));
+            notification_producer = Some(producer);
+            info!("Rule reload publisher initialized");
+        }
+        Err(e) => {
+            warn!(
+                "Failed to create Kafka producer: {}. \
+                Rule changes will take effect on the next scheduled refresh, \
+                notification tasks will stay pending.",
+                e
+            );
+        }
+    }
+
     // 构建 gRPC 客户端 TLS 配置（TLS 未启用时为 None，客户端使用明文连接）
     let client_tls = badge_shared::grpc_tls::build_client_tls_config(&config.tls)
         .await
@@ -180,20 +223,66 @@ async fn main() -> anyhow::Result<()> {
     };
 
     // 启动批量任务后台 Worker
-    // 在 state 被 move 到 Router 之前克隆连接池
+    // 在 state 被 move 到 Router 之前克隆连接池；发放/撤销经由 badge-management-service
+    // 的批量 gRPC 接口执行，与 AppState 共享客户端槽位和熔断器
     let batch_worker_pool = db.pool().clone();
+    let batch_worker_client = state.badge_management_client.clone();
+    let batch_worker_breaker = state.badge_mgmt_circuit_breaker.clone();
     tokio::spawn(async move {
-        let worker = badge_admin_service::worker::BatchTaskWorker::new(batch_worker_pool);
+        let worker = badge_admin_service::worker::BatchTaskWorker::new(batch_worker_pool)
+            .with_badge_management_client(batch_worker_client, batch_worker_breaker);
         worker.run().await;
     });
 
-    // 启动徽章过期处理 Worker
+    // 启动徽章过期处理 Worker，同时重试撤销和过期后失败的反向级联
     let expire_worker_pool = db.pool().clone();
+    let expire_worker_leaderboard = state.leaderboard();
+    let expire_cascade_evaluator = state.cascade_evaluator.clone();
     tokio::spawn(async move {
-        let worker = badge_admin_service::worker::ExpireWorker::with_defaults(expire_worker_pool);
+        let mut worker =
+            badge_admin_service::worker::ExpireWorker::with_defaults(expire_worker_pool)
+                .with_leaderboard(expire_worker_leaderboard);
+        if let Some(evaluator) = expire_cascade_evaluator {
+            worker = worker.with_cascade_evaluator(evaluator);
+        }
         worker.run().await;
     });
 
+    // 启动通知任务分发 Worker（依赖 Kafka，生产者不可用时任务保持 pending）
+    let dlq_producer = notification_producer.clone();
+    if let Some(producer) = notification_producer {
+        let notification_worker_pool = db.pool().clone();
+        tokio::spawn(async move {
+            let worker = badge_admin_service::worker::NotificationTaskWorker::with_defaults(
+                notification_worker_pool,
+                producer,
+            );
+            worker.run().await;
+        });
+    }
+
+    // 启动死信消费者和重放调度器（依赖 Kafka）：死信持久化后按退避策略发回原始 topic
+    let (dlq_shutdown_tx, dlq_shutdown_rx) = watch::channel(false);
+    if let Some(producer) = dlq_producer {
+        let dlq_store: Arc<dyn DeadLetterStore> =
+            Arc::new(PgDeadLetterStore::new(db.pool().clone()));
+        match DlqConsumer::new(&config, dlq_store.clone()) {
+            Ok(consumer) => {
+                let shutdown = dlq_shutdown_rx.clone();
+                tokio::spawn(async move { consumer.run(shutdown).await });
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to create DLQ consumer: {}. Dead letters will not be persisted.",
+                    e
+                );
+            }
+        }
+        let scheduler = DlqReplayScheduler::new(dlq_store, producer);
+        let shutdown = dlq_shutdown_rx.clone();
+        tokio::spawn(async move { scheduler.run(shutdown).await });
+    }
+
     // 启动定时任务调度 Worker
     let scheduled_worker_pool = db.pool().clone();
     tokio::spawn(async move {
@@ -236,6 +325,7 @@ async fn main() -> anyhow::Result<()> {
         .with_graceful_shutdown(shutdown_signal())
         .await?;
 
+    let _ = dlq_shutdown_tx.send(true);
     info!("Server shutdown complete");
 
     Ok(())
@@ -68,6 +68,20 @@ fn system_routes() -> Router<AppState> {
             .layer(axum_mw::from_fn(require_permission("system:apikey:write"))))
         .route("/system/api-keys/{id}/status", patch(handlers::api_key::toggle_api_key_status)
             .layer(axum_mw::from_fn(require_permission("system:apikey:write"))))
+        // ── 死信队列 — 读 ──
+        .route("/system/dlq/messages", get(handlers::dlq::list_dead_letters)
+            .layer(axum_mw::from_fn(require_permission("system:dlq:read"))))
+        .route("/system/dlq/messages/{id}", get(handlers::dlq::get_dead_letter)
+            .layer(axum_mw::from_fn(require_permission("system:dlq:read"))))
+        .route("/system/dlq/stats", get(handlers::dlq::get_dead_letter_stats)
+            .layer(axum_mw::from_fn(require_permission("system:dlq:read"))))
+        // ── 死信队列 — 写 ──
+        .route("/system/dlq/messages/{id}/replay", post(handlers::dlq::replay_dead_letter)
+            .layer(axum_mw::from_fn(require_permission("system:dlq:write"))))
+        .route("/system/dlq/messages/replay", post(handlers::dlq::replay_dead_letters)
+            .layer(axum_mw::from_fn(require_permission("system:dlq:write"))))
+        .route("/system/dlq/messages/purge", post(handlers::dlq::purge_dead_letters)
+            .layer(axum_mw::from_fn(require_permission("system:dlq:write"))))
 }
 
 /// 构建徽章管理相关的路由
@@ -161,16 +175,22 @@ fn cache_routes() -> Router<AppState> {
 
 /// 构建事件类型路由
 ///
-/// 提供事件类型列表查询（用于规则配置）
+/// 提供事件类型列表查询（用于规则配置）和自定义事件类型注册
 fn event_type_routes() -> Router<AppState> {
     Router::new()
         .route("/event-types", get(handlers::event_type::list_event_types)
             .layer(axum_mw::from_fn(require_permission("rule:rule:read"))))
+        .route("/event-types/{code}", get(handlers::event_type::get_event_type)
+            .layer(axum_mw::from_fn(require_permission("rule:rule:read"))))
+        .route("/event-types", post(handlers::event_type::create_event_type)
+            .layer(axum_mw::from_fn(require_permission("rule:rule:write"))))
+        .route("/event-types/{code}", put(handlers::event_type::update_event_type)
+            .layer(axum_mw::from_fn(require_permission("rule:rule:write"))))
 }
 
 /// 构建规则管理路由
 ///
-/// 包含规则 CRUD、发布和测试操作
+/// 包含规则 CRUD、发布、测试和版本管理操作
 fn rule_routes() -> Router<AppState> {
     Router::new()
         // ── 读 ──
@@ -195,6 +215,15 @@ fn rule_routes() -> Router<AppState> {
             .layer(axum_mw::from_fn(require_permission("rule:rule:publish"))))
         .route("/rules/{id}/disable", post(handlers::rule::disable_rule)
             .layer(axum_mw::from_fn(require_permission("rule:rule:publish"))))
+        // ── 版本（静态路径 diff 优先于 {version} 匹配）──
+        .route("/rules/{id}/versions", get(handlers::rule::list_rule_versions)
+            .layer(axum_mw::from_fn(require_permission("rule:rule:read"))))
+        .route("/rules/{id}/versions/diff", get(handlers::rule::diff_rule_versions)
+            .layer(axum_mw::from_fn(require_permission("rule:rule:read"))))
+        .route("/rules/{id}/versions/{version}", get(handlers::rule::get_rule_version)
+            .layer(axum_mw::from_fn(require_permission("rule:rule:read"))))
+        .route("/rules/{id}/versions/{version}/rollback", post(handlers::rule::rollback_rule)
+            .layer(axum_mw::from_fn(require_permission("rule:rule:write"))))
 }
 
 /// 构建发放管理路由
@@ -267,7 +296,7 @@ fn stats_routes() -> Router<AppState> {
 
 /// 构建会员视图路由
 ///
-/// 包含用户搜索、详情、徽章、兑换记录、统计、账本流水和权益
+/// 包含用户搜索、详情、徽章、进度、兑换记录、统计、账本流水、权益和通知偏好
 fn user_view_routes() -> Router<AppState> {
     Router::new()
         .route("/users/search", get(handlers::user_view::search_users)
@@ -276,6 +305,8 @@ fn user_view_routes() -> Router<AppState> {
             .layer(axum_mw::from_fn(require_permission("user:view:read"))))
         .route("/users/{user_id}/badges", get(handlers::user_view::get_user_badges)
             .layer(axum_mw::from_fn(require_permission("user:badge:read"))))
+        .route("/users/{user_id}/progress", get(handlers::user_view::get_user_badge_progress)
+            .layer(axum_mw::from_fn(require_permission("user:badge:read"))))
         .route("/users/{user_id}/redemptions", get(handlers::user_view::get_user_redemptions)
             .layer(axum_mw::from_fn(require_permission("user:view:read"))))
         .route("/users/{user_id}/stats", get(handlers::user_view::get_user_stats)
@@ -286,6 +317,16 @@ fn user_view_routes() -> Router<AppState> {
             .layer(axum_mw::from_fn(require_permission("user:view:read"))))
         .route("/users/{user_id}/redemption-history", get(handlers::redemption::get_user_redemption_history)
             .layer(axum_mw::from_fn(require_permission("user:view:read"))))
+        .route("/users/{user_id}/notification-preferences", get(handlers::notification_preference::get_notification_preferences)
+            .layer(axum_mw::from_fn(require_permission("user:view:read"))))
+        .route("/users/{user_id}/notification-preferences", put(handlers::notification_preference::update_notification_preferences)
+            .layer(axum_mw::from_fn(require_permission("user:notification:write"))))
+        .route("/users/{user_id}/notification-contacts", get(handlers::notification_contact::get_notification_contacts)
+            .layer(axum_mw::from_fn(require_permission("user:view:read"))))
+        .route("/users/{user_id}/notification-contacts", put(handlers::notification_contact::upsert_notification_contacts)
+            .layer(axum_mw::from_fn(require_permission("user:notification:write"))))
+        .route("/users/notification-contacts/batch", post(handlers::notification_contact::batch_upsert_notification_contacts)
+            .layer(axum_mw::from_fn(require_permission("user:notification:write"))))
 }
 
 /// 构建操作日志路由
@@ -366,7 +407,7 @@ fn benefit_routes() -> Router<AppState> {
 
 /// 构建通知配置管理路由
 ///
-/// 包含通知配置的 CRUD、测试发送和任务查询
+/// 包含通知配置的 CRUD、测试发送、任务查询，以及通知模板的 CRUD 和预览
 fn notification_routes() -> Router<AppState> {
     Router::new()
         // ── 读 ──
@@ -386,6 +427,19 @@ fn notification_routes() -> Router<AppState> {
         // ── 测试 ──
         .route("/notification-configs/test", post(handlers::notification::test_notification)
             .layer(axum_mw::from_fn(require_permission("notification:config:write"))))
+        // ── 模板 ──
+        .route("/notification-templates", get(handlers::notification_template::list_notification_templates)
+            .layer(axum_mw::from_fn(require_permission("notification:config:read"))))
+        .route("/notification-templates/{id}", get(handlers::notification_template::get_notification_template)
+            .layer(axum_mw::from_fn(require_permission("notification:config:read"))))
+        .route("/notification-templates", post(handlers::notification_template::create_notification_template)
+            .layer(axum_mw::from_fn(require_permission("notification:config:write"))))
+        .route("/notification-templates/{id}", put(handlers::notification_template::update_notification_template)
+            .layer(axum_mw::from_fn(require_permission("notification:config:write"))))
+        .route("/notification-templates/{id}", delete(handlers::notification_template::delete_notification_template)
+            .layer(axum_mw::from_fn(require_permission("notification:config:write"))))
+        .route("/notification-templates/{id}/preview", post(handlers::notification_template::preview_notification_template)
+            .layer(axum_mw::from_fn(require_permission("notification:config:read"))))
 }
 
 /// 构建兑换管理路由
@@ -411,6 +465,8 @@ fn redemption_routes() -> Router<AppState> {
             .layer(axum_mw::from_fn(require_permission("benefit:redemption:write"))))
         .route("/redemption/redeem", post(handlers::redemption::redeem)
             .layer(axum_mw::from_fn(require_permission("benefit:redemption:write"))))
+        .route("/redemption/orders/{order_no}/cancel", post(handlers::redemption::cancel_redemption_order)
+            .layer(axum_mw::from_fn(require_permission("benefit:redemption:write"))))
 }
 
 /// 构建自动权益管理路由
@@ -479,7 +535,7 @@ pub fn api_routes() -> Router<AppState> {
 /// 构建外部 API 路由（供第三方系统调用，API Key 认证）
 ///
 /// 与管理后台 `/api/admin/` 分离，使用 `/api/v1/` 前缀。
-/// 暴露只读查询和兑换操作接口。
+/// 暴露只读查询、兑换操作和用户通知偏好接口。
 /// 每条路由均附加细粒度权限校验，权限检查在 API Key 认证之后执行。
 pub fn external_api_routes(external_state: crate::middleware::ExternalApiState) -> Router<AppState> {
     use crate::middleware::{api_key_auth_middleware, require_api_key_permission};
@@ -491,11 +547,44 @@ pub fn external_api_routes(external_state: crate::middleware::ExternalApiState)
             get(handlers::user_view::get_user_badges)
                 .layer(axum_mw::from_fn(require_api_key_permission("read:badges"))),
         )
+        .route(
+            "/users/{user_id}/progress",
+            get(handlers::user_view::get_user_badge_progress)
+                .layer(axum_mw::from_fn(require_api_key_permission("read:badges"))),
+        )
         .route(
             "/users/{user_id}/stats",
             get(handlers::user_view::get_user_stats)
                 .layer(axum_mw::from_fn(require_api_key_permission("read:users"))),
         )
+        .route(
+            "/users/{user_id}/leaderboards/{board}",
+            get(handlers::leaderboard::get_user_leaderboard_rank)
+                .layer(axum_mw::from_fn(require_api_key_permission("read:badges"))),
+        )
+        // 用户排行榜 — 只读
+        .route(
+            "/leaderboards/{board}",
+            get(handlers::leaderboard::get_leaderboard)
+                .layer(axum_mw::from_fn(require_api_key_permission("read:badges"))),
+        )
+        // 用户通知偏好 — 读写
+        .route(
+            "/users/{user_id}/notification-preferences",
+            get(handlers::notification_preference::get_notification_preferences)
+                .layer(axum_mw::from_fn(require_api_key_permission("read:users"))),
+        )
+        .route(
+            "/users/{user_id}/notification-preferences",
+            put(handlers::notification_preference::update_notification_preferences)
+                .layer(axum_mw::from_fn(require_api_key_permission("write:users"))),
+        )
+        // 可兑换目录 — 只读
+        .route(
+            "/users/{user_id}/redeemable-benefits",
+            get(handlers::redemption::list_redeemable_benefits)
+                .layer(axum_mw::from_fn(require_api_key_permission("read:redemption"))),
+        )
         // 兑换操作 — 写
         .route(
             "/redemption/redeem",
@@ -507,6 +596,11 @@ pub fn external_api_routes(external_state: crate::middleware::ExternalApiState)
             get(handlers::redemption::get_redemption_order)
                 .layer(axum_mw::from_fn(require_api_key_permission("read:redemption"))),
         )
+        .route(
+            "/redemption/orders/{order_no}/cancel",
+            post(handlers::redemption::cancel_user_redemption_order)
+                .layer(axum_mw::from_fn(require_api_key_permission("write:redemption"))),
+        )
         // 发放记录查询 — 只读
         .route(
             "/grants/logs",
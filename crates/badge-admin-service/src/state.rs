@@ -10,11 +10,14 @@ use badge_proto::rule_engine::rule_engine_service_client::RuleEngineServiceClien
 use badge_shared::cache::Cache;
 use badge_shared::circuit_breaker::{CircuitBreaker, CircuitBreakerConfig};
 use badge_shared::crypto::FieldEncryptor;
+use badge_shared::leaderboard::{BadgeChange, LeaderboardStore};
+use badge_shared::rules::RuleReloadPublisher;
 use sqlx::PgPool;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::RwLock;
 use tonic::transport::Channel;
+use tracing::warn;
 
 use crate::auth::{JwtConfig, JwtManager};
 use crate::error::AdminError;
@@ -49,7 +52,7 @@ pub struct AppState {
     pub jwt_manager: Arc<JwtManager>,
     /// 依赖关系仓储（可选，用于依赖关系 CRUD）
     dependency_repo: Option<Arc<DependencyRepository>>,
-    /// 级联评估器（可选，用于缓存刷新）
+    /// 级联评估器（可选，用于撤销后的反向级联和依赖缓存刷新）
     pub cascade_evaluator: Option<Arc<CascadeEvaluator>>,
     /// 兑换服务（可选，用于执行兑换操作）
     pub redemption_service: Option<Arc<RedemptionService>>,
@@ -63,6 +66,8 @@ pub struct AppState {
     pub rule_engine_circuit_breaker: CircuitBreaker,
     /// 字段级加密器（审计日志、敏感字段加密写入时使用）
     pub encryptor: Arc<FieldEncryptor>,
+    /// 规则刷新事件发布器（可选，未配置时规则变更依赖各服务定时刷新）
+    pub rule_reload_publisher: Option<RuleReloadPublisher>,
 }
 
 impl AppState {
@@ -84,6 +89,7 @@ impl AppState {
             badge_mgmt_circuit_breaker: badge_mgmt_cb,
             rule_engine_circuit_breaker: rule_engine_cb,
             encryptor: Arc::new(FieldEncryptor::passthrough()),
+            rule_reload_publisher: None,
         }
     }
 
@@ -104,6 +110,7 @@ impl AppState {
             badge_mgmt_circuit_breaker: badge_mgmt_cb,
             rule_engine_circuit_breaker: rule_engine_cb,
             encryptor: Arc::new(FieldEncryptor::passthrough()),
+            rule_reload_publisher: None,
         }
     }
 
@@ -132,6 +139,7 @@ impl AppState {
             badge_mgmt_circuit_breaker: badge_mgmt_cb,
             rule_engine_circuit_breaker: rule_engine_cb,
             encryptor: Arc::new(FieldEncryptor::passthrough()),
+            rule_reload_publisher: None,
         }
     }
 
@@ -140,6 +148,11 @@ impl AppState {
         self.encryptor = Arc::new(encryptor);
     }
 
+    /// 设置规则刷新事件发布器
+    pub fn set_rule_reload_publisher(&mut self, publisher: RuleReloadPublisher) {
+        self.rule_reload_publisher = Some(publisher);
+    }
+
     /// 设置依赖关系仓储
     pub fn set_dependency_repo(&mut self, repo: Arc<DependencyRepository>) {
         self.dependency_repo = Some(repo);
@@ -178,4 +191,21 @@ impl AppState {
             Ok(Arc::new(DependencyRepository::new(self.pool.clone())))
         }
     }
+
+    /// 获取排行榜存储（复用共享的缓存和连接池）
+    pub fn leaderboard(&self) -> LeaderboardStore {
+        LeaderboardStore::new(self.cache.clone(), self.pool.clone())
+    }
+
+    /// 将徽章持有变化同步到排行榜，失败不影响业务操作
+    pub async fn record_leaderboard(&self, change: BadgeChange) {
+        if let Err(e) = self.leaderboard().record(&change).await {
+            warn!(
+                user_id = %change.user_id,
+                badge_id = change.badge_id,
+                error = %e,
+                "排行榜更新失败"
+            );
+        }
+    }
 }
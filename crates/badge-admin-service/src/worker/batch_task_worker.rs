@@ -3,19 +3,31 @@
 //! 轮询 batch_tasks 表中 pending 状态的任务，逐条处理用户的发放/撤销操作。
 //! 使用 `FOR UPDATE SKIP LOCKED` 保证多实例部署时任务不会被重复消费。
 //!
+//! 实际的发放/撤销通过 badge-management-service 的 `BatchGrantBadges` /
+//! `BatchRevokeBadges` gRPC 接口执行，与单次发放共享前置条件、互斥组、库存、
+//! 级联评估、自动权益和通知等全部语义；Worker 只负责任务调度和失败记录。
+//!
 //! 优化特性：
 //! - 文件大小限制：默认 50MB，防止 OOM
 //! - 流式 CSV 解析：分批读取，避免一次性加载
-//! - 分片并发处理：每批 100 条并发执行，提升吞吐
+//! - 分片批量调用：每批 100 条合并为一次 gRPC 请求，降低调用开销
 
 use std::io::BufRead;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+use badge_proto::badge::badge_management_service_client::BadgeManagementServiceClient;
+use badge_proto::badge::{
+    BatchBadgeOperationResponse, BatchGrantBadgesRequest, BatchGrantItem,
+    BatchRevokeBadgesRequest, BatchRevokeItem,
+};
+use badge_shared::circuit_breaker::{CircuitBreaker, CircuitBreakerConfig};
 use badge_shared::observability::metrics;
 use chrono::Utc;
 use sqlx::PgPool;
+use tokio::sync::RwLock;
+use tonic::transport::Channel;
 use tracing::{error, info, warn};
-use uuid::Uuid;
 
 /// 文件大小上限（50MB），超过此大小的 CSV 将被拒绝处理
 const MAX_FILE_SIZE_BYTES: u64 = 50 * 1024 * 1024;
@@ -29,6 +41,15 @@ const MAX_RETRY_COUNT: i32 = 3;
 /// 重试间隔基数（秒），实际间隔 = 2^retry_count * 60
 const RETRY_INTERVAL_BASE_SECS: i64 = 60;
 
+/// gRPC 调用失败（连接异常、熔断跳闸）时写入失败记录的错误码，由重试流程兜底
+const SERVICE_UNAVAILABLE: &str = "SERVICE_UNAVAILABLE";
+
+/// 管理服务未返回错误码时的兜底错误码
+const PROCESS_ERROR: &str = "PROCESS_ERROR";
+
+/// 与 AppState 共享的 badge-management-service 客户端槽位
+pub type BadgeManagementClientSlot = Arc<RwLock<Option<BadgeManagementServiceClient<Channel>>>>;
+
 /// 批量任务 Worker
 ///
 /// 以固定间隔轮询数据库，领取并执行 pending 状态的批量发放/撤销任务。
@@ -36,10 +57,14 @@ const RETRY_INTERVAL_BASE_SECS: i64 = 60;
 pub struct BatchTaskWorker {
     pool: PgPool,
     poll_interval: Duration,
-    /// 并发处理的分片大小
+    /// 每次批量调用的分片大小
     chunk_size: usize,
     /// 文件大小上限（字节）
     max_file_size: u64,
+    /// badge-management-service gRPC 客户端（未连接时暂停领取任务）
+    badge_management_client: BadgeManagementClientSlot,
+    /// gRPC 调用的熔断器
+    circuit_breaker: CircuitBreaker,
 }
 
 /// 从数据库查询出的待处理任务行
@@ -49,6 +74,7 @@ struct PendingTask {
     task_type: String,
     params: Option<serde_json::Value>,
     file_url: Option<String>,
+    created_by: String,
 }
 
 /// 从 params JSON 中解析出的任务参数
@@ -64,33 +90,88 @@ struct TaskParams {
 struct RetryableFailure {
     id: i64,
     task_id: i64,
+    row_number: i32,
     user_id: String,
     retry_count: i32,
     params: Option<serde_json::Value>,
     task_type: String,
+    created_by: String,
 }
 
-impl BatchTaskWorker {
-    pub fn new(pool: PgPool) -> Self {
+/// 批量调用中的单个用户
+struct BatchItem {
+    /// 源列表中的行号（从 1 开始），同时用于生成幂等键
+    row_number: i32,
+    user_id: String,
+}
+
+/// 单个用户处理失败的原因，写入 batch_task_failures
+#[derive(Debug, Clone, PartialEq)]
+struct ItemFailure {
+    error_code: String,
+    message: String,
+}
+
+impl ItemFailure {
+    fn new(error_code: impl Into<String>, message: impl Into<String>) -> Self {
         Self {
-            pool,
-            poll_interval: Duration::from_secs(5),
-            chunk_size: BATCH_CHUNK_SIZE,
-            max_file_size: MAX_FILE_SIZE_BYTES,
+            error_code: error_code.into(),
+            message: message.into(),
         }
     }
+}
+
+/// 同一任务内所有用户共享的操作参数
+struct OperationParams<'a> {
+    task_type: &'a str,
+    task_id: i64,
+    badge_id: i64,
+    quantity: i32,
+    reason: &'a str,
+    operator: &'a str,
+}
 
-    /// 创建带自定义配置的 Worker（主要用于测试）
-    #[allow(dead_code)]
-    pub fn with_config(pool: PgPool, poll_secs: u64, chunk_size: usize, max_file_mb: u64) -> Self {
+impl BatchTaskWorker {
+    pub fn new(pool: PgPool) -> Self {
+        Self::with_config(pool, 5, BATCH_CHUNK_SIZE, MAX_FILE_SIZE_BYTES)
+    }
+
+    /// 创建带自定义配置的 Worker，`max_file_size` 单位为字节
+    pub fn with_config(
+        pool: PgPool,
+        poll_secs: u64,
+        chunk_size: usize,
+        max_file_size: u64,
+    ) -> Self {
         Self {
             pool,
             poll_interval: Duration::from_secs(poll_secs),
             chunk_size,
-            max_file_size: max_file_mb * 1024 * 1024,
+            max_file_size,
+            badge_management_client: Arc::new(RwLock::new(None)),
+            circuit_breaker: CircuitBreaker::new(CircuitBreakerConfig::new(
+                "batch-task-badge-management",
+            )),
         }
     }
 
+    /// 使用与 AppState 共享的 badge-management-service 客户端和熔断器
+    ///
+    /// 共享槽位使得服务启动后再连上的客户端也能被 Worker 使用
+    pub fn with_badge_management_client(
+        mut self,
+        client: BadgeManagementClientSlot,
+        circuit_breaker: CircuitBreaker,
+    ) -> Self {
+        self.badge_management_client = client;
+        self.circuit_breaker = circuit_breaker;
+        self
+    }
+
+    async fn client(&self) -> Option<BadgeManagementServiceClient<Channel>> {
+        self.badge_management_client.read().await.clone()
+    }
+
     /// 主循环：持续轮询待处理任务直到进程退出
     ///
     /// 每次循环只领取一个任务处理完毕后再取下一个，
@@ -103,6 +184,13 @@ impl BatchTaskWorker {
             "BatchTaskWorker 已启动"
         );
         loop {
+            // 管理服务不可达时不领取任务，避免整批用户被记为失败
+            if self.client().await.is_none() {
+                warn!("badge-management-service gRPC 客户端未连接，暂停处理批量任务");
+                tokio::time::sleep(self.poll_interval).await;
+                continue;
+            }
+
             // 处理新任务
             if let Err(e) = self.process_pending_tasks().await {
                 error!(error = %e, "批量任务处理出错");
@@ -130,7 +218,7 @@ impl BatchTaskWorker {
         // 在事务内抢占任务，确保领取和状态变更是原子操作
         let task = sqlx::query_as::<_, PendingTask>(
             r#"
-            SELECT id, task_type, params, file_url
+            SELECT id, task_type, params, file_url, created_by
             FROM batch_tasks
             WHERE status = 'pending'
             ORDER BY created_at ASC
@@ -237,10 +325,10 @@ impl BatchTaskWorker {
         );
     }
 
-    /// 分片并发处理用户列表
+    /// 分片批量处理用户列表
     ///
-    /// 将用户列表按 chunk_size 分片，每个分片内的用户并发处理，
-    /// 分片之间顺序执行以控制并发度，避免数据库连接池耗尽。
+    /// 将用户列表按 chunk_size 分片，每个分片合并为一次批量 gRPC 调用，
+    /// 分片之间顺序执行，每批结束后更新进度。
     async fn process_users_in_chunks(
         &self,
         task: &PendingTask,
@@ -251,57 +339,40 @@ impl BatchTaskWorker {
         let mut failure_count: i32 = 0;
         let mut processed: usize = 0;
 
-        // 预先克隆需要在 async 块中使用的数据，避免生命周期问题
-        let task_type = task.task_type.clone();
-        let task_id = task.id;
-        let badge_id = params.badge_id;
-        let quantity = params.quantity;
-        let reason = params.reason.clone();
+        let op = OperationParams {
+            task_type: &task.task_type,
+            task_id: task.id,
+            badge_id: params.badge_id,
+            quantity: params.quantity,
+            reason: &params.reason,
+            operator: &task.created_by,
+        };
 
-        // 按 chunk_size 分片
         for (chunk_idx, chunk) in params.user_ids.chunks(self.chunk_size).enumerate() {
-            // 每个分片内并发处理
-            let mut handles = Vec::with_capacity(chunk.len());
-
-            for (idx_in_chunk, user_id) in chunk.iter().enumerate() {
-                let global_idx = chunk_idx * self.chunk_size + idx_in_chunk;
-                let user_id_owned = user_id.clone();
-                let task_type_ref = task_type.clone();
-                let reason_ref = reason.clone();
-                let pool = self.pool.clone();
-
-                handles.push(async move {
-                    let result = match task_type_ref.as_str() {
-                        "batch_grant" => {
-                            Self::grant_one_user_static(&pool, &user_id_owned, badge_id, quantity, task_id, &reason_ref)
-                                .await
-                        }
-                        "batch_revoke" => {
-                            Self::revoke_one_user_static(&pool, &user_id_owned, badge_id, task_id, &reason_ref)
-                                .await
-                        }
-                        _ => Err(format!("不支持的任务类型: {}", task_type_ref)),
-                    };
-                    (global_idx, user_id_owned, result)
-                });
-            }
+            let items: Vec<BatchItem> = chunk
+                .iter()
+                .enumerate()
+                .map(|(idx_in_chunk, user_id)| BatchItem {
+                    row_number: (chunk_idx * self.chunk_size + idx_in_chunk + 1) as i32,
+                    user_id: user_id.clone(),
+                })
+                .collect();
 
-            // 并发执行本批次
-            let results = futures::future::join_all(handles).await;
+            let results = self.execute_items(&op, &items).await;
 
             // 统计本批次结果
-            for (global_idx, user_id, result) in results {
+            for (item, result) in items.iter().zip(results) {
                 processed += 1;
                 match result {
                     Ok(()) => success_count += 1,
-                    Err(err_msg) => {
+                    Err(failure) => {
                         failure_count += 1;
                         self.insert_failure_record(
-                            task_id,
-                            (global_idx + 1) as i32,
-                            &user_id,
-                            "PROCESS_ERROR",
-                            &err_msg,
+                            task.id,
+                            item.row_number,
+                            &item.user_id,
+                            &failure.error_code,
+                            &failure.message,
                         )
                         .await;
                     }
@@ -321,7 +392,7 @@ impl BatchTaskWorker {
                 WHERE id = $1
                 "#,
             )
-            .bind(task_id)
+            .bind(task.id)
             .bind(success_count)
             .bind(failure_count)
             .bind(progress)
@@ -332,217 +403,58 @@ impl BatchTaskWorker {
         (success_count, failure_count)
     }
 
-    /// 发放徽章的静态版本（用于并发处理）
-    async fn grant_one_user_static(
-        pool: &PgPool,
-        user_id: &str,
-        badge_id: i64,
-        quantity: i32,
-        task_id: i64,
-        reason: &str,
-    ) -> Result<(), String> {
-        let source_ref_id = format!("batch-{}-{}", task_id, Uuid::new_v4());
-        let now = Utc::now();
-
-        let mut tx = pool
-            .begin()
-            .await
-            .map_err(|e| format!("开启事务失败: {e}"))?;
-
-        // 1. 插入或累加 user_badges（使用实际 quantity 而非硬编码 1）
-        sqlx::query(
-            r#"
-            INSERT INTO user_badges (user_id, badge_id, quantity, status, first_acquired_at, source_type, created_at, updated_at)
-            VALUES ($1, $2, $4, 'active', $3, 'BATCH', $3, $3)
-            ON CONFLICT (user_id, badge_id)
-            DO UPDATE SET quantity = user_badges.quantity + $4, status = 'active', updated_at = $3
-            "#,
-        )
-        .bind(user_id)
-        .bind(badge_id)
-        .bind(now)
-        .bind(quantity)
-        .execute(&mut *tx)
-        .await
-        .map_err(|e| format!("upsert user_badges 失败: {e}"))?;
-
-        // 2. 写入 badge_ledger
-        let balance_row: (i32,) = sqlx::query_as(
-            "SELECT COALESCE(SUM(quantity), 0)::INT FROM badge_ledger WHERE user_id = $1 AND badge_id = $2",
-        )
-        .bind(user_id)
-        .bind(badge_id)
-        .fetch_one(&mut *tx)
-        .await
-        .map_err(|e| format!("查询余额失败: {e}"))?;
-
-        let balance_after = balance_row.0 + quantity;
-
-        sqlx::query(
-            r#"
-            INSERT INTO badge_ledger (user_id, badge_id, change_type, source_type, ref_id, quantity, balance_after, remark, created_at)
-            VALUES ($1, $2, 'acquire', 'BATCH', $3, $4, $5, $6, $7)
-            "#,
-        )
-        .bind(user_id)
-        .bind(badge_id)
-        .bind(&source_ref_id)
-        .bind(quantity)
-        .bind(balance_after)
-        .bind(reason)
-        .bind(now)
-        .execute(&mut *tx)
-        .await
-        .map_err(|e| format!("写入 badge_ledger 失败: {e}"))?;
-
-        // 3. 写入操作日志
-        sqlx::query(
-            r#"
-            INSERT INTO user_badge_logs (user_id, badge_id, action, quantity, source_type, source_ref_id, remark, created_at)
-            VALUES ($1, $2, 'grant', $3, 'BATCH', $4, $5, $6)
-            "#,
-        )
-        .bind(user_id)
-        .bind(badge_id)
-        .bind(quantity)
-        .bind(&source_ref_id)
-        .bind(reason)
-        .bind(now)
-        .execute(&mut *tx)
-        .await
-        .map_err(|e| format!("写入 user_badge_logs 失败: {e}"))?;
-
-        // 4. 累加徽章已发放计数
-        sqlx::query(
-            "UPDATE badges SET issued_count = issued_count + $2, updated_at = $3 WHERE id = $1",
-        )
-        .bind(badge_id)
-        .bind(quantity as i64)
-        .bind(now)
-        .execute(&mut *tx)
-        .await
-        .map_err(|e| format!("更新 issued_count 失败: {e}"))?;
-
-        tx.commit()
-            .await
-            .map_err(|e| format!("提交事务失败: {e}"))?;
-
-        Ok(())
-    }
-
-    /// 撤销徽章的静态版本（用于并发处理）
-    async fn revoke_one_user_static(
-        pool: &PgPool,
-        user_id: &str,
-        badge_id: i64,
-        task_id: i64,
-        reason: &str,
-    ) -> Result<(), String> {
-        let source_ref_id = format!("batch-{}-{}", task_id, Uuid::new_v4());
-        let now = Utc::now();
-
-        // 先检查用户是否持有该徽章且数量足够
-        let qty_row: Option<(i32,)> = sqlx::query_as(
-            "SELECT quantity FROM user_badges WHERE user_id = $1 AND badge_id = $2",
-        )
-        .bind(user_id)
-        .bind(badge_id)
-        .fetch_optional(pool)
-        .await
-        .map_err(|e| format!("查询用户徽章失败: {e}"))?;
-
-        let current_qty = match qty_row {
-            Some((q,)) if q >= 1 => q,
-            Some((q,)) => return Err(format!("用户徽章数量不足 (当前: {q})")),
-            None => return Err("用户未持有该徽章".to_string()),
+    /// 通过 badge-management-service 执行一批用户的发放/撤销
+    ///
+    /// 返回结果与 items 一一对应。整批调用失败（未连接、熔断、网络异常）时
+    /// 所有用户记为 SERVICE_UNAVAILABLE，由失败重试流程兜底。
+    async fn execute_items(
+        &self,
+        op: &OperationParams<'_>,
+        items: &[BatchItem],
+    ) -> Vec<Result<(), ItemFailure>> {
+        let fail_all = |failure: ItemFailure| items.iter().map(|_| Err(failure.clone())).collect();
+
+        let Some(client) = self.client().await else {
+            return fail_all(ItemFailure::new(
+                SERVICE_UNAVAILABLE,
+                "badge-management-service gRPC 客户端未连接",
+            ));
         };
 
-        let remaining = current_qty - 1;
-
-        let mut tx = pool
-            .begin()
-            .await
-            .map_err(|e| format!("开启事务失败: {e}"))?;
+        let result = match op.task_type {
+            "batch_grant" => {
+                let request = build_grant_request(op, items);
+                self.circuit_breaker
+                    .call(|| {
+                        let mut c = client.clone();
+                        async move { c.batch_grant_badges(request).await }
+                    })
+                    .await
+            }
+            "batch_revoke" => {
+                let request = build_revoke_request(op, items);
+                self.circuit_breaker
+                    .call(|| {
+                        let mut c = client.clone();
+                        async move { c.batch_revoke_badges(request).await }
+                    })
+                    .await
+            }
+            other => {
+                return fail_all(ItemFailure::new(
+                    PROCESS_ERROR,
+                    format!("不支持的任务类型: {}", other),
+                ));
+            }
+        };
 
-        // 1. 扣减 user_badges
-        if remaining == 0 {
-            sqlx::query(
-                r#"
-                UPDATE user_badges
-                SET quantity = 0, status = 'revoked', updated_at = $3
-                WHERE user_id = $1 AND badge_id = $2
-                "#,
-            )
-            .bind(user_id)
-            .bind(badge_id)
-            .bind(now)
-            .execute(&mut *tx)
-            .await
-            .map_err(|e| format!("扣减 user_badges 失败: {e}"))?;
-        } else {
-            sqlx::query(
-                r#"
-                UPDATE user_badges
-                SET quantity = quantity - 1, updated_at = $3
-                WHERE user_id = $1 AND badge_id = $2
-                "#,
-            )
-            .bind(user_id)
-            .bind(badge_id)
-            .bind(now)
-            .execute(&mut *tx)
-            .await
-            .map_err(|e| format!("扣减 user_badges 失败: {e}"))?;
+        match result {
+            Ok(response) => map_batch_results(items.len(), response.into_inner()),
+            Err(e) => {
+                warn!(task_id = op.task_id, count = items.len(), error = %e, "批量 gRPC 调用失败");
+                fail_all(ItemFailure::new(SERVICE_UNAVAILABLE, e.to_string()))
+            }
         }
-
-        // 2. 写入 badge_ledger
-        sqlx::query(
-            r#"
-            INSERT INTO badge_ledger (user_id, badge_id, change_type, source_type, ref_id, quantity, balance_after, remark, created_at)
-            VALUES ($1, $2, 'cancel', 'BATCH', $3, -1, $4, $5, $6)
-            "#,
-        )
-        .bind(user_id)
-        .bind(badge_id)
-        .bind(&source_ref_id)
-        .bind(remaining)
-        .bind(reason)
-        .bind(now)
-        .execute(&mut *tx)
-        .await
-        .map_err(|e| format!("写入 badge_ledger 失败: {e}"))?;
-
-        // 3. 写入操作日志
-        sqlx::query(
-            r#"
-            INSERT INTO user_badge_logs (user_id, badge_id, action, quantity, source_type, source_ref_id, remark, created_at)
-            VALUES ($1, $2, 'revoke', 1, 'BATCH', $3, $4, $5)
-            "#,
-        )
-        .bind(user_id)
-        .bind(badge_id)
-        .bind(&source_ref_id)
-        .bind(reason)
-        .bind(now)
-        .execute(&mut *tx)
-        .await
-        .map_err(|e| format!("写入 user_badge_logs 失败: {e}"))?;
-
-        // 4. 扣减徽章已发放计数（GREATEST 防止负数）
-        sqlx::query(
-            "UPDATE badges SET issued_count = GREATEST(issued_count - 1, 0), updated_at = $2 WHERE id = $1",
-        )
-        .bind(badge_id)
-        .bind(now)
-        .execute(&mut *tx)
-        .await
-        .map_err(|e| format!("更新 issued_count 失败: {e}"))?;
-
-        tx.commit()
-            .await
-            .map_err(|e| format!("提交事务失败: {e}"))?;
-
-        Ok(())
     }
 
     /// 解析任务参数，支持两种用户列表来源
@@ -692,8 +604,8 @@ impl BatchTaskWorker {
         // 且距离上次重试已过指数退避间隔
         let failures: Vec<RetryableFailure> = sqlx::query_as(
             r#"
-            SELECT f.id, f.task_id, f.user_id, f.retry_count,
-                   t.params, t.task_type
+            SELECT f.id, f.task_id, f.row_number, f.user_id, f.retry_count,
+                   t.params, t.task_type, t.created_by
             FROM batch_task_failures f
             JOIN batch_tasks t ON t.id = f.task_id
             WHERE f.retry_status = 'PENDING'
@@ -750,16 +662,24 @@ impl BatchTaskWorker {
         let quantity = params.get("quantity").and_then(|v| v.as_i64()).unwrap_or(1) as i32;
         let reason = params.get("reason").and_then(|v| v.as_str()).unwrap_or("");
 
-        // 执行重试
-        let result = match failure.task_type.as_str() {
-            "batch_grant" => {
-                Self::grant_one_user_static(&self.pool, &failure.user_id, badge_id, quantity, failure.task_id, reason).await
-            }
-            "batch_revoke" => {
-                Self::revoke_one_user_static(&self.pool, &failure.user_id, badge_id, failure.task_id, reason).await
-            }
-            _ => Err(format!("不支持的任务类型: {}", failure.task_type)),
+        // 执行重试：沿用原行号生成的幂等键，管理服务侧已成功的发放不会重复执行
+        let op = OperationParams {
+            task_type: &failure.task_type,
+            task_id: failure.task_id,
+            badge_id,
+            quantity,
+            reason,
+            operator: &failure.created_by,
         };
+        let item = BatchItem {
+            row_number: failure.row_number,
+            user_id: failure.user_id.clone(),
+        };
+        let result = self
+            .execute_items(&op, std::slice::from_ref(&item))
+            .await
+            .pop()
+            .unwrap_or_else(|| Err(ItemFailure::new(PROCESS_ERROR, "批量调用未返回结果")));
 
         let new_retry_count = failure.retry_count + 1;
 
@@ -782,14 +702,15 @@ impl BatchTaskWorker {
                 .execute(&self.pool)
                 .await;
             }
-            Err(err_msg) => {
+            Err(failure_info) => {
                 // 重试失败
                 warn!(
                     failure_id = failure.id,
                     task_id = failure.task_id,
                     user_id = failure.user_id,
                     retry_count = new_retry_count,
-                    error = %err_msg,
+                    error_code = %failure_info.error_code,
+                    error = %failure_info.message,
                     "重试失败"
                 );
 
@@ -802,10 +723,11 @@ impl BatchTaskWorker {
 
                 // 更新错误信息
                 let _ = sqlx::query(
-                    "UPDATE batch_task_failures SET error_message = $2 WHERE id = $1",
+                    "UPDATE batch_task_failures SET error_code = $2, error_message = $3 WHERE id = $1",
                 )
                 .bind(failure.id)
-                .bind(&err_msg)
+                .bind(&failure_info.error_code)
+                .bind(&failure_info.message)
                 .execute(&self.pool)
                 .await;
             }
@@ -876,6 +798,86 @@ impl BatchTaskWorker {
     }
 }
 
+/// 批量发放的幂等键：同一任务同一行号在首次执行和后续重试中保持不变
+fn idempotency_key(task_id: i64, row_number: i32) -> String {
+    format!("batch-{}-{}", task_id, row_number)
+}
+
+fn build_grant_request(op: &OperationParams<'_>, items: &[BatchItem]) -> BatchGrantBadgesRequest {
+    BatchGrantBadgesRequest {
+        badge_id: op.badge_id.to_string(),
+        source_type: "manual".to_string(),
+        source_ref: format!("batch-task-{}", op.task_id),
+        reason: op.reason.to_string(),
+        operator: op.operator.to_string(),
+        items: items
+            .iter()
+            .map(|item| BatchGrantItem {
+                user_id: item.user_id.clone(),
+                quantity: op.quantity,
+                idempotency_key: idempotency_key(op.task_id, item.row_number),
+            })
+            .collect(),
+    }
+}
+
+fn build_revoke_request(op: &OperationParams<'_>, items: &[BatchItem]) -> BatchRevokeBadgesRequest {
+    // 管理服务要求取消原因必填，任务未填写时使用默认说明
+    let reason = if op.reason.is_empty() {
+        format!("批量任务 {} 撤销", op.task_id)
+    } else {
+        op.reason.to_string()
+    };
+
+    BatchRevokeBadgesRequest {
+        badge_id: op.badge_id.to_string(),
+        reason,
+        operator: op.operator.to_string(),
+        items: items
+            .iter()
+            .map(|item| BatchRevokeItem {
+                user_id: item.user_id.clone(),
+                quantity: op.quantity,
+                idempotency_key: idempotency_key(op.task_id, item.row_number),
+            })
+            .collect(),
+    }
+}
+
+/// 将批量响应映射为逐条结果
+///
+/// 响应条数与请求不一致说明服务端行为异常，此时无法确定对应关系，整批记为失败
+fn map_batch_results(
+    expected: usize,
+    response: BatchBadgeOperationResponse,
+) -> Vec<Result<(), ItemFailure>> {
+    if response.results.len() != expected {
+        let failure = ItemFailure::new(
+            PROCESS_ERROR,
+            format!(
+                "批量响应条数不匹配: 期望 {}，实际 {}",
+                expected,
+                response.results.len()
+            ),
+        );
+        return (0..expected).map(|_| Err(failure.clone())).collect();
+    }
+
+    response
+        .results
+        .into_iter()
+        .map(|r| {
+            if r.success {
+                Ok(())
+            } else if r.error_code.is_empty() {
+                Err(ItemFailure::new(PROCESS_ERROR, r.message))
+            } else {
+                Err(ItemFailure::new(r.error_code, r.message))
+            }
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -893,10 +895,118 @@ mod tests {
     #[tokio::test]
     async fn test_worker_custom_config() {
         let pool = sqlx::PgPool::connect_lazy("postgres://localhost/test").unwrap();
-        let worker = BatchTaskWorker::with_config(pool, 10, 50, 100);
+        let worker = BatchTaskWorker::with_config(pool, 10, 50, 100 * 1024 * 1024);
 
         assert_eq!(worker.poll_interval.as_secs(), 10);
         assert_eq!(worker.chunk_size, 50);
         assert_eq!(worker.max_file_size, 100 * 1024 * 1024);
     }
+
+    fn sample_op(task_type: &str) -> OperationParams<'_> {
+        OperationParams {
+            task_type,
+            task_id: 42,
+            badge_id: 7,
+            quantity: 2,
+            reason: "",
+            operator: "admin",
+        }
+    }
+
+    fn sample_items() -> Vec<BatchItem> {
+        vec![
+            BatchItem {
+                row_number: 1,
+                user_id: "user-1".to_string(),
+            },
+            BatchItem {
+                row_number: 2,
+                user_id: "user-2".to_string(),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_build_grant_request_uses_stable_idempotency_keys() {
+        let request = build_grant_request(&sample_op("batch_grant"), &sample_items());
+
+        assert_eq!(request.badge_id, "7");
+        assert_eq!(request.source_ref, "batch-task-42");
+        assert_eq!(request.operator, "admin");
+        assert_eq!(request.items.len(), 2);
+        assert_eq!(request.items[0].quantity, 2);
+        assert_eq!(request.items[0].idempotency_key, "batch-42-1");
+        assert_eq!(request.items[1].idempotency_key, "batch-42-2");
+    }
+
+    #[test]
+    fn test_build_revoke_request_fills_default_reason() {
+        let request = build_revoke_request(&sample_op("batch_revoke"), &sample_items());
+
+        assert_eq!(request.reason, "批量任务 42 撤销");
+        assert_eq!(request.items[1].user_id, "user-2");
+        assert_eq!(request.items[1].quantity, 2);
+        assert_eq!(request.items[0].idempotency_key, "batch-42-1");
+        assert_eq!(request.items[1].idempotency_key, "batch-42-2");
+    }
+
+    #[test]
+    fn test_map_batch_results() {
+        use badge_proto::badge::BatchItemResult;
+
+        let response = BatchBadgeOperationResponse {
+            success_count: 1,
+            failed_count: 2,
+            results: vec![
+                BatchItemResult {
+                    user_id: "user-1".to_string(),
+                    success: true,
+                    ..Default::default()
+                },
+                BatchItemResult {
+                    user_id: "user-2".to_string(),
+                    success: false,
+                    error_code: "PREREQUISITE_NOT_MET".to_string(),
+                    message: "前置条件未满足".to_string(),
+                },
+                BatchItemResult {
+                    user_id: "user-3".to_string(),
+                    success: false,
+                    error_code: String::new(),
+                    message: "未知错误".to_string(),
+                },
+            ],
+        };
+
+        let results = map_batch_results(3, response);
+        assert!(results[0].is_ok());
+        assert_eq!(
+            results[1],
+            Err(ItemFailure::new("PREREQUISITE_NOT_MET", "前置条件未满足"))
+        );
+        assert_eq!(results[2], Err(ItemFailure::new(PROCESS_ERROR, "未知错误")));
+    }
+
+    #[test]
+    fn test_map_batch_results_length_mismatch_fails_all() {
+        let results = map_batch_results(2, BatchBadgeOperationResponse::default());
+
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.is_err()));
+    }
+
+    #[tokio::test]
+    async fn test_execute_items_without_client_marks_service_unavailable() {
+        let pool = sqlx::PgPool::connect_lazy("postgres://localhost/test").unwrap();
+        let worker = BatchTaskWorker::new(pool);
+
+        let results = worker
+            .execute_items(&sample_op("batch_grant"), &sample_items())
+            .await;
+
+        assert_eq!(results.len(), 2);
+        for result in results {
+            assert_eq!(result.unwrap_err().error_code, SERVICE_UNAVAILABLE);
+        }
+    }
 }
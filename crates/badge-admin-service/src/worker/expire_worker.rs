@@ -2,17 +2,24 @@
 //!
 //! 定期扫描即将过期和已过期的用户徽章：
 //! 1. 对即将过期的徽章发送提醒通知（提前 N 天）
-//! 2. 将已过期的徽章状态变更为 expired
+//! 2. 将已过期的徽章状态变更为 expired，并同步扣减排行榜
+//! 3. 对依赖过期徽章的其他徽章执行反向级联（按依赖的 revoke_policy 撤销或暂停）
+//! 4. 重试撤销或过期后失败的待处理反向级联
 //!
 //! 使用 `FOR UPDATE SKIP LOCKED` 保证多实例部署时不会重复处理
 
+use std::sync::Arc;
 use std::time::Duration;
 
+use badge_management::cascade::{CascadeEvaluator, CascadeResult, RevokePolicy, RevokedDependent};
+use badge_shared::leaderboard::{BadgeChange, LeaderboardStore};
 use badge_shared::observability::metrics;
 use chrono::{DateTime, Utc};
 use serde_json;
 use sqlx::PgPool;
-use tracing::{error, info};
+use tracing::{error, info, warn};
+
+use crate::error::AdminError;
 
 /// 过期处理 Worker
 ///
@@ -26,6 +33,10 @@ pub struct ExpireWorker {
     batch_size: i64,
     /// 过期提醒提前天数（默认 3 天）
     advance_days: i64,
+    /// 排行榜存储（可选，未设置时过期不同步排行榜）
+    leaderboard: Option<LeaderboardStore>,
+    /// 级联评估器（可选，未设置时过期不做反向级联）
+    cascade_evaluator: Option<Arc<CascadeEvaluator>>,
 }
 
 /// 即将过期的徽章记录
@@ -62,6 +73,8 @@ impl ExpireWorker {
             poll_interval: Duration::from_secs(poll_interval_secs),
             batch_size,
             advance_days,
+            leaderboard: None,
+            cascade_evaluator: None,
         }
     }
 
@@ -70,6 +83,18 @@ impl ExpireWorker {
         Self::new(pool, 300, 1000, 3)
     }
 
+    /// 设置排行榜存储，徽章过期后扣减用户排行榜分数
+    pub fn with_leaderboard(mut self, leaderboard: LeaderboardStore) -> Self {
+        self.leaderboard = Some(leaderboard);
+        self
+    }
+
+    /// 设置级联评估器，徽章过期后处理依赖它的徽章
+    pub fn with_cascade_evaluator(mut self, evaluator: Arc<CascadeEvaluator>) -> Self {
+        self.cascade_evaluator = Some(evaluator);
+        self
+    }
+
     /// 主循环：持续处理过期任务直到进程退出
     pub async fn run(&self) {
         info!(
@@ -89,6 +114,10 @@ impl ExpireWorker {
                 error!(error = %e, "处理已过期徽章出错");
             }
 
+            if let Err(e) = self.process_pending_cascades().await {
+                error!(error = %e, "重试待处理反向级联出错");
+            }
+
             // 记录 Worker 健康状态
             metrics::set_worker_last_run("expire_worker");
 
@@ -225,17 +254,22 @@ impl ExpireWorker {
             "days_remaining": days_left
         });
 
-        // 创建通知任务（channels 为 NOT NULL，从关联配置中取值）
+        // 创建通知任务（channels 为 NOT NULL，从关联配置中取值），
+        // 模板和重试策略同样沿用配置，由 NotificationTaskWorker 分发
         sqlx::query(
             r#"
             INSERT INTO notification_tasks (
-                user_id, trigger_type, channels, template_params, status, created_at
+                user_id, trigger_type, channels, template_id, template_params,
+                max_retries, retry_interval_seconds, status, created_at
             )
             SELECT
                 $1,
                 'expire_remind',
                 nc.channels,
+                nc.template_id,
                 $2,
+                nc.retry_count,
+                nc.retry_interval_seconds,
                 'pending',
                 NOW()
             FROM notification_configs nc
@@ -261,8 +295,9 @@ impl ExpireWorker {
 
     /// 处理已过期的徽章
     ///
-    /// 将过期徽章状态变更为 expired，并记录流水
-    async fn process_expired_badges(&self) -> Result<(), sqlx::Error> {
+    /// 将过期徽章状态变更为 expired，并记录流水；
+    /// 需要反向级联的徽章在同一事务内登记待处理记录
+    async fn process_expired_badges(&self) -> Result<(), AdminError> {
         let now = Utc::now();
 
         let mut tx = self.pool.begin().await?;
@@ -293,6 +328,9 @@ impl ExpireWorker {
         let count = badges.len();
         info!(count, "发现已过期的徽章，准备处理");
 
+        let mut changes = Vec::with_capacity(count);
+        let mut pending_cascades = Vec::new();
+
         for badge in &badges {
             // 更新徽章状态为 expired
             sqlx::query(
@@ -329,6 +367,16 @@ impl ExpireWorker {
             .execute(&mut *tx)
             .await?;
 
+            changes.push(BadgeChange::removed(&badge.user_id, badge.badge_id, qty, 0));
+
+            if let Some(evaluator) = &self.cascade_evaluator
+                && let Some(pending_id) = evaluator
+                    .enqueue_revoke_in_tx(&mut tx, &badge.user_id, badge.badge_id)
+                    .await?
+            {
+                pending_cascades.push((pending_id, badge));
+            }
+
             info!(
                 user_badge_id = badge.id,
                 user_id = %badge.user_id,
@@ -339,12 +387,175 @@ impl ExpireWorker {
 
         tx.commit().await?;
 
+        // 反向级联：依赖已过期徽章的徽章按策略撤销或暂停，受影响的徽章同样扣减排行榜
+        for (pending_id, badge) in pending_cascades {
+            for dependent in self
+                .cascade_revoke(pending_id, &badge.user_id, badge.badge_id)
+                .await
+            {
+                changes.push(BadgeChange::removed(
+                    &badge.user_id,
+                    dependent.badge_id,
+                    dependent.quantity,
+                    0,
+                ));
+            }
+        }
+
+        self.sync_leaderboard(&changes).await;
+
         // 记录过期处理指标
         metrics::record_badge_expiration(count as u64);
 
         info!(count, "已过期徽章处理完成");
         Ok(())
     }
+
+    /// 重试撤销或过期后失败的反向级联
+    ///
+    /// 待处理记录与撤销/过期同事务写入，级联成功后才删除，进程中断或评估失败均可在此补偿
+    async fn process_pending_cascades(&self) -> Result<(), AdminError> {
+        let Some(evaluator) = &self.cascade_evaluator else {
+            return Ok(());
+        };
+
+        let completed = evaluator.retry_pending_revokes(self.batch_size).await?;
+        if completed.is_empty() {
+            return Ok(());
+        }
+
+        let mut changes = Vec::new();
+        for (pending, result) in &completed {
+            for dependent in self.notify_cascade(&pending.user_id, result).await {
+                changes.push(BadgeChange::removed(
+                    &pending.user_id,
+                    dependent.badge_id,
+                    dependent.quantity,
+                    0,
+                ));
+            }
+        }
+        self.sync_leaderboard(&changes).await;
+
+        info!(count = completed.len(), "待处理反向级联重试完成");
+        Ok(())
+    }
+
+    /// 将徽章变更同步到排行榜，失败只记录日志
+    async fn sync_leaderboard(&self, changes: &[BadgeChange]) {
+        let Some(leaderboard) = &self.leaderboard else {
+            return;
+        };
+        for change in changes {
+            if let Err(e) = leaderboard.record(change).await {
+                warn!(
+                    user_id = %change.user_id,
+                    badge_id = change.badge_id,
+                    error = %e,
+                    "徽章变更同步排行榜失败"
+                );
+            }
+        }
+    }
+
+    /// 对过期徽章执行反向级联，返回受影响的徽章
+    ///
+    /// 级联失败只记录日志：待处理记录已随过期事务提交，由 `process_pending_cascades` 重试
+    async fn cascade_revoke(
+        &self,
+        pending_id: i64,
+        user_id: &str,
+        badge_id: i64,
+    ) -> Vec<RevokedDependent> {
+        let Some(evaluator) = &self.cascade_evaluator else {
+            return Vec::new();
+        };
+
+        match evaluator
+            .evaluate_pending_revoke(pending_id, user_id, badge_id)
+            .await
+        {
+            Ok(result) => self.notify_cascade(user_id, &result).await,
+            Err(e) => {
+                warn!(
+                    user_id = %user_id,
+                    badge_id,
+                    pending_id,
+                    error = %e,
+                    "过期徽章反向级联失败，已登记待重试"
+                );
+                Vec::new()
+            }
+        }
+    }
+
+    /// 为反向级联受影响的徽章创建通知任务，返回受影响的徽章
+    async fn notify_cascade(&self, user_id: &str, result: &CascadeResult) -> Vec<RevokedDependent> {
+        let dependents = result.revoked_badges.clone();
+        for dependent in &dependents {
+            if let Err(e) = self.create_cascade_notification(user_id, dependent).await {
+                error!(
+                    user_id = %user_id,
+                    badge_id = dependent.badge_id,
+                    error = %e,
+                    "创建级联撤销通知任务失败"
+                );
+            }
+        }
+
+        dependents
+    }
+
+    /// 创建级联撤销/暂停通知任务
+    ///
+    /// 沿用目标徽章 trigger_type = 'revoke' 的通知配置，未配置时不发送
+    async fn create_cascade_notification(
+        &self,
+        user_id: &str,
+        dependent: &RevokedDependent,
+    ) -> Result<(), sqlx::Error> {
+        let badge_name: Option<String> =
+            sqlx::query_scalar("SELECT name FROM badges WHERE id = $1")
+                .bind(dependent.badge_id)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        let template_params = serde_json::json!({
+            "badge_name": badge_name.unwrap_or_else(|| "徽章".to_string()),
+            "reason": format!("前置徽章 {} 已过期", dependent.triggered_by),
+            "suspended": dependent.policy == RevokePolicy::Suspend,
+        });
+
+        sqlx::query(
+            r#"
+            INSERT INTO notification_tasks (
+                user_id, trigger_type, channels, template_id, template_params,
+                max_retries, retry_interval_seconds, status, created_at
+            )
+            SELECT
+                $1,
+                'revoke',
+                nc.channels,
+                nc.template_id,
+                $2,
+                nc.retry_count,
+                nc.retry_interval_seconds,
+                'pending',
+                NOW()
+            FROM notification_configs nc
+            WHERE nc.badge_id = $3
+              AND nc.trigger_type = 'revoke'
+              AND nc.status = 'active'
+            "#,
+        )
+        .bind(user_id)
+        .bind(&template_params)
+        .bind(dependent.badge_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
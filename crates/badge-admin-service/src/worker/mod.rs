@@ -1,7 +1,9 @@
 pub mod batch_task_worker;
 pub mod expire_worker;
+pub mod notification_task_worker;
 pub mod scheduled_task_worker;
 
 pub use batch_task_worker::BatchTaskWorker;
 pub use expire_worker::ExpireWorker;
+pub use notification_task_worker::NotificationTaskWorker;
 pub use scheduled_task_worker::ScheduledTaskWorker;
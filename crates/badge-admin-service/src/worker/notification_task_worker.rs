@@ -0,0 +1,669 @@
+//! 通知任务分发 Worker
+//!
+//! 轮询 notification_tasks 表中 pending 状态的任务（由 ExpireWorker 等写入），
+//! 渲染通知内容后按渠道逐个投递 `NotificationEvent` 到 `badge.notifications`，
+//! 由 notification-worker 完成实际推送。
+//!
+//! - 通知内容使用 `notification_templates` 表中的模板渲染（与 notification-worker、
+//!   badge-management-service 共用 `badge_shared::notification_templates`），
+//!   未配置的类型使用内置默认模板
+//! - 使用 `FOR UPDATE SKIP LOCKED` 领取任务，多实例部署时不会重复投递
+//! - 每个渠道的投递结果记录在 `channel_results`，重试时只重投失败的渠道
+//! - 失败渠道按 `retry_interval_seconds * 2^retry_count` 退避，超过 `max_retries` 标记为 failed
+//! - 长时间停留在 processing 的任务（Worker 崩溃）会被重新置为 pending
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use badge_shared::events::{NotificationChannel, NotificationEvent, NotificationType};
+use badge_shared::kafka::{KafkaProducer, topics};
+use badge_shared::notification_preferences::{DEFAULT_LOCALE, LOCALE_DATA_KEY};
+use badge_shared::notification_templates::{PgTemplateStore, TemplateRegistry, TemplateSet};
+use badge_shared::observability::metrics;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use tracing::{error, info, warn};
+
+/// processing 状态超过该时长视为 Worker 异常退出，任务重新回到 pending
+const STALE_PROCESSING_SECS: i64 = 300;
+
+/// 退避指数上限，避免重试间隔无限增长
+const MAX_BACKOFF_EXPONENT: u32 = 10;
+
+/// 通知任务分发 Worker
+pub struct NotificationTaskWorker {
+    pool: PgPool,
+    producer: KafkaProducer,
+    poll_interval: Duration,
+    /// 每轮最多领取的任务数
+    batch_size: i64,
+    templates: Arc<TemplateRegistry>,
+}
+
+/// 领取到的通知任务
+#[derive(sqlx::FromRow)]
+struct ClaimedTask {
+    id: i64,
+    user_id: String,
+    trigger_type: String,
+    channels: serde_json::Value,
+    template_id: Option<String>,
+    template_params: Option<serde_json::Value>,
+    retry_count: i32,
+    max_retries: i32,
+    retry_interval_seconds: i32,
+    channel_results: serde_json::Value,
+}
+
+/// 单渠道投递状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ChannelStatus {
+    /// 已投递到 badge.notifications
+    Sent,
+    /// 投递失败，等待重试
+    Failed,
+    /// 渠道名无法识别或通知内容无法渲染，不会重试
+    Unsupported,
+}
+
+/// 单渠道投递结果，序列化后写入 channel_results
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct ChannelResult {
+    status: ChannelStatus,
+    attempts: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sent_at: Option<DateTime<Utc>>,
+}
+
+/// 一轮投递后任务的去向
+#[derive(Debug, PartialEq)]
+enum TaskOutcome {
+    /// 所有可投递渠道均已成功
+    Completed,
+    /// 存在失败渠道，在指定时间后重试
+    Retry { next_retry_at: DateTime<Utc> },
+    /// 重试耗尽或没有可投递的渠道
+    Failed { reason: String },
+}
+
+impl NotificationTaskWorker {
+    pub fn new(
+        pool: PgPool,
+        producer: KafkaProducer,
+        poll_interval_secs: u64,
+        batch_size: i64,
+    ) -> Self {
+        Self {
+            templates: Arc::new(
+                TemplateRegistry::builtin()
+                    .with_store(Arc::new(PgTemplateStore::new(pool.clone()))),
+            ),
+            pool,
+            producer,
+            poll_interval: Duration::from_secs(poll_interval_secs),
+            batch_size,
+        }
+    }
+
+    /// 替换模板注册表（默认使用内置模板叠加 notification_templates 表）
+    pub fn with_template_registry(mut self, templates: Arc<TemplateRegistry>) -> Self {
+        self.templates = templates;
+        self
+    }
+
+    /// 使用默认配置创建（10 秒轮询，每轮 100 条）
+    pub fn with_defaults(pool: PgPool, producer: KafkaProducer) -> Self {
+        Self::new(pool, producer, 10, 100)
+    }
+
+    /// 主循环：持续分发待处理的通知任务直到进程退出
+    pub async fn run(&self) {
+        info!(
+            poll_interval = ?self.poll_interval,
+            batch_size = self.batch_size,
+            "NotificationTaskWorker 已启动"
+        );
+
+        loop {
+            if let Err(e) = self.recover_stale_tasks().await {
+                error!(error = %e, "回收超时通知任务出错");
+            }
+
+            match self.claim_tasks().await {
+                Ok(tasks) => {
+                    for task in tasks {
+                        self.dispatch_task(task).await;
+                    }
+                }
+                Err(e) => error!(error = %e, "领取通知任务出错"),
+            }
+
+            // 记录 Worker 健康状态
+            metrics::set_worker_last_run("notification_task_worker");
+
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    /// 将长时间停留在 processing 的任务重新置为 pending
+    async fn recover_stale_tasks(&self) -> Result<(), sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE notification_tasks
+            SET status = 'pending'
+            WHERE status = 'processing'
+              AND updated_at < NOW() - make_interval(secs => $1)
+            "#,
+        )
+        .bind(STALE_PROCESSING_SECS as f64)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() > 0 {
+            warn!(count = result.rows_affected(), "回收超时未完成的通知任务");
+        }
+        Ok(())
+    }
+
+    /// 领取一批到期的 pending 任务并标记为 processing
+    ///
+    /// 领取和状态变更在同一事务内完成，投递在事务外进行，避免长事务持有行锁。
+    async fn claim_tasks(&self) -> Result<Vec<ClaimedTask>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let tasks = sqlx::query_as::<_, ClaimedTask>(
+            r#"
+            SELECT id, user_id, trigger_type, channels, template_id, template_params,
+                   retry_count, max_retries, retry_interval_seconds, channel_results
+            FROM notification_tasks
+            WHERE status = 'pending'
+              AND (next_retry_at IS NULL OR next_retry_at <= NOW())
+            ORDER BY created_at ASC
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+            "#,
+        )
+        .bind(self.batch_size)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        if !tasks.is_empty() {
+            let ids: Vec<i64> = tasks.iter().map(|t| t.id).collect();
+            sqlx::query("UPDATE notification_tasks SET status = 'processing' WHERE id = ANY($1)")
+                .bind(&ids)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(tasks)
+    }
+
+    /// 投递单个任务的所有未完成渠道，并根据结果更新任务状态
+    async fn dispatch_task(&self, task: ClaimedTask) {
+        let params = template_context(&task.trigger_type, task.template_params.as_ref());
+        let mut results: BTreeMap<String, ChannelResult> =
+            serde_json::from_value(task.channel_results.clone()).unwrap_or_default();
+
+        let outcome = match notification_type_for(&task.trigger_type) {
+            Some(notification_type) => {
+                let templates = self.templates.current().await;
+                let channels: Vec<String> =
+                    serde_json::from_value(task.channels.clone()).unwrap_or_default();
+                for channel in channels {
+                    let done = results
+                        .get(&channel)
+                        .is_some_and(|r| r.status != ChannelStatus::Failed);
+                    if done {
+                        continue;
+                    }
+                    let result = self
+                        .publish_channel(&task, &templates, &notification_type, &params, &channel)
+                        .await;
+                    let attempts = results.get(&channel).map_or(0, |r| r.attempts) + 1;
+                    results.insert(channel, ChannelResult { attempts, ..result });
+                }
+                decide_outcome(&results, &task, Utc::now())
+            }
+            None => TaskOutcome::Failed {
+                reason: format!("不支持的触发类型: {}", task.trigger_type),
+            },
+        };
+
+        if let Err(e) = self.save_outcome(&task, &results, &outcome).await {
+            error!(task_id = task.id, error = %e, "更新通知任务状态失败");
+        }
+    }
+
+    /// 投递单个渠道的通知事件
+    ///
+    /// notification_id 由任务 ID 和渠道确定，重投时保持不变，下游可据此去重。
+    /// 模板变量同时平铺到 `data`，notification-worker 按用户语言重新渲染时可直接使用。
+    async fn publish_channel(
+        &self,
+        task: &ClaimedTask,
+        templates: &TemplateSet,
+        notification_type: &NotificationType,
+        params: &serde_json::Value,
+        channel: &str,
+    ) -> ChannelResult {
+        let Some(parsed) = parse_channel(channel) else {
+            warn!(task_id = task.id, channel = %channel, "不支持的通知渠道");
+            return unsupported(format!("不支持的通知渠道: {}", channel));
+        };
+
+        let rendered = render_notification(templates, notification_type, &parsed, params);
+        let (title, body) = match rendered {
+            Ok(content) => content,
+            Err(reason) => {
+                warn!(task_id = task.id, channel = %channel, reason = %reason, "通知内容渲染失败");
+                return unsupported(reason);
+            }
+        };
+
+        // 渲染上下文始终是对象（见 template_context）
+        let mut data = params.clone();
+        data["task_id"] = serde_json::json!(task.id);
+        data["trigger_type"] = serde_json::json!(task.trigger_type);
+        data["template_id"] = serde_json::json!(task.template_id);
+        data["params"] = params.clone();
+
+        let notification = NotificationEvent {
+            notification_id: format!("notification-task-{}-{}", task.id, channel),
+            user_id: task.user_id.clone(),
+            notification_type: notification_type.clone(),
+            title,
+            body,
+            data,
+            channels: vec![parsed],
+            created_at: Utc::now(),
+        };
+
+        match self
+            .producer
+            .send_json(
+                topics::BADGE_NOTIFICATIONS,
+                &notification.notification_id,
+                &notification,
+            )
+            .await
+        {
+            Ok(_) => ChannelResult {
+                status: ChannelStatus::Sent,
+                attempts: 0,
+                error: None,
+                sent_at: Some(Utc::now()),
+            },
+            Err(e) => {
+                warn!(task_id = task.id, channel = %channel, error = %e, "通知投递失败");
+                ChannelResult {
+                    status: ChannelStatus::Failed,
+                    attempts: 0,
+                    error: Some(e.to_string()),
+                    sent_at: None,
+                }
+            }
+        }
+    }
+
+    /// 写回渠道结果和任务状态
+    async fn save_outcome(
+        &self,
+        task: &ClaimedTask,
+        results: &BTreeMap<String, ChannelResult>,
+        outcome: &TaskOutcome,
+    ) -> Result<(), sqlx::Error> {
+        let channel_results = serde_json::to_value(results).unwrap_or_default();
+
+        match outcome {
+            TaskOutcome::Completed => {
+                sqlx::query(
+                    r#"
+                    UPDATE notification_tasks
+                    SET status = 'completed', channel_results = $2, last_error = NULL,
+                        next_retry_at = NULL, completed_at = NOW()
+                    WHERE id = $1
+                    "#,
+                )
+                .bind(task.id)
+                .bind(&channel_results)
+                .execute(&self.pool)
+                .await?;
+                info!(task_id = task.id, user_id = %task.user_id, "通知任务已投递");
+            }
+            TaskOutcome::Retry { next_retry_at } => {
+                sqlx::query(
+                    r#"
+                    UPDATE notification_tasks
+                    SET status = 'pending', channel_results = $2, retry_count = retry_count + 1,
+                        last_error = $3, next_retry_at = $4
+                    WHERE id = $1
+                    "#,
+                )
+                .bind(task.id)
+                .bind(&channel_results)
+                .bind(failed_channels_summary(results))
+                .bind(next_retry_at)
+                .execute(&self.pool)
+                .await?;
+                warn!(
+                    task_id = task.id,
+                    retry_count = task.retry_count + 1,
+                    next_retry_at = %next_retry_at,
+                    "通知任务部分渠道投递失败，等待重试"
+                );
+            }
+            TaskOutcome::Failed { reason } => {
+                sqlx::query(
+                    r#"
+                    UPDATE notification_tasks
+                    SET status = 'failed', channel_results = $2, last_error = $3,
+                        next_retry_at = NULL, completed_at = NOW()
+                    WHERE id = $1
+                    "#,
+                )
+                .bind(task.id)
+                .bind(&channel_results)
+                .bind(reason)
+                .execute(&self.pool)
+                .await?;
+                warn!(task_id = task.id, reason = %reason, "通知任务投递失败");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// 将配置中的渠道名映射为通知渠道
+///
+/// notification_configs 使用小写下划线命名（app_push、wechat 等），
+/// 同时兼容 NotificationChannel 序列化后的大写形式
+fn parse_channel(channel: &str) -> Option<NotificationChannel> {
+    match channel.to_ascii_lowercase().as_str() {
+        "app_push" | "push" => Some(NotificationChannel::AppPush),
+        "sms" => Some(NotificationChannel::Sms),
+        "wechat" | "we_chat" => Some(NotificationChannel::WeChat),
+        "email" => Some(NotificationChannel::Email),
+        _ => None,
+    }
+}
+
+fn unsupported(error: String) -> ChannelResult {
+    ChannelResult {
+        status: ChannelStatus::Unsupported,
+        attempts: 0,
+        error: Some(error),
+        sent_at: None,
+    }
+}
+
+/// 将任务触发类型映射为通知类型，返回 None 表示触发类型无法识别
+///
+/// 已过期（expire）与即将过期（expire_remind）共用 BadgeExpiring 模板，
+/// 渲染上下文中的 `days_left` 为 0
+fn notification_type_for(trigger_type: &str) -> Option<NotificationType> {
+    match trigger_type {
+        "grant" => Some(NotificationType::BadgeGranted),
+        "revoke" => Some(NotificationType::BadgeRevoked),
+        "expire_remind" | "expire" => Some(NotificationType::BadgeExpiring),
+        "redeem" => Some(NotificationType::RedemptionSuccess),
+        _ => None,
+    }
+}
+
+/// 由任务参数构造模板渲染上下文
+///
+/// ExpireWorker 写入的剩余天数为 `days_remaining`，模板变量为 `days_left`
+fn template_context(trigger_type: &str, params: Option<&serde_json::Value>) -> serde_json::Value {
+    let mut context = match params {
+        Some(serde_json::Value::Object(map)) => map.clone(),
+        _ => serde_json::Map::new(),
+    };
+    if !context.contains_key("days_left") {
+        let days_left = match trigger_type {
+            "expire" => Some(serde_json::json!(0)),
+            _ => context.get("days_remaining").cloned(),
+        };
+        if let Some(days_left) = days_left {
+            context.insert("days_left".to_string(), days_left);
+        }
+    }
+    serde_json::Value::Object(context)
+}
+
+/// 按通知类型、语言和渠道渲染通知标题和正文
+///
+/// 语言取自任务参数中的 `locale`，默认 zh-CN。没有匹配的模板或缺少模板变量时
+/// 返回错误原因，避免把占位符发给用户。
+fn render_notification(
+    templates: &TemplateSet,
+    notification_type: &NotificationType,
+    channel: &NotificationChannel,
+    context: &serde_json::Value,
+) -> Result<(String, String), String> {
+    let locale = context
+        .get(LOCALE_DATA_KEY)
+        .and_then(|v| v.as_str())
+        .unwrap_or(DEFAULT_LOCALE);
+    let content = templates
+        .render(notification_type, locale, Some(channel), context)
+        .ok_or_else(|| format!("未配置通知模板: {:?}", notification_type))?;
+    if !content.is_complete() {
+        return Err(format!(
+            "通知模板缺少变量: {}",
+            content.missing_variables.join(", ")
+        ));
+    }
+    Ok((content.title, content.body))
+}
+
+/// 根据各渠道结果决定任务去向
+fn decide_outcome(
+    results: &BTreeMap<String, ChannelResult>,
+    task: &ClaimedTask,
+    now: DateTime<Utc>,
+) -> TaskOutcome {
+    let has_failed = results.values().any(|r| r.status == ChannelStatus::Failed);
+
+    if !has_failed {
+        if results.values().any(|r| r.status == ChannelStatus::Sent) {
+            return TaskOutcome::Completed;
+        }
+        return TaskOutcome::Failed {
+            reason: "没有可投递的通知渠道".to_string(),
+        };
+    }
+
+    if task.retry_count >= task.max_retries {
+        return TaskOutcome::Failed {
+            reason: failed_channels_summary(results),
+        };
+    }
+
+    let exponent = (task.retry_count.max(0) as u32).min(MAX_BACKOFF_EXPONENT);
+    let delay_secs = i64::from(task.retry_interval_seconds.max(1)) * (1i64 << exponent);
+    TaskOutcome::Retry {
+        next_retry_at: now + chrono::Duration::seconds(delay_secs),
+    }
+}
+
+/// 汇总失败渠道的错误信息，写入 last_error
+fn failed_channels_summary(results: &BTreeMap<String, ChannelResult>) -> String {
+    results
+        .iter()
+        .filter(|(_, r)| r.status == ChannelStatus::Failed)
+        .map(|(channel, r)| format!("{}: {}", channel, r.error.as_deref().unwrap_or("未知错误")))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use badge_shared::notification_templates::NotificationTemplate;
+
+    fn task(retry_count: i32, max_retries: i32) -> ClaimedTask {
+        ClaimedTask {
+            id: 1,
+            user_id: "user-1".to_string(),
+            trigger_type: "expire_remind".to_string(),
+            channels: serde_json::json!(["app_push", "sms"]),
+            template_id: None,
+            template_params: None,
+            retry_count,
+            max_retries,
+            retry_interval_seconds: 60,
+            channel_results: serde_json::json!({}),
+        }
+    }
+
+    fn result(status: ChannelStatus) -> ChannelResult {
+        ChannelResult {
+            status,
+            attempts: 1,
+            error: (status == ChannelStatus::Failed).then(|| "broker down".to_string()),
+            sent_at: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_channel() {
+        assert_eq!(
+            parse_channel("app_push"),
+            Some(NotificationChannel::AppPush)
+        );
+        assert_eq!(parse_channel("WECHAT"), Some(NotificationChannel::WeChat));
+        assert_eq!(parse_channel("email"), Some(NotificationChannel::Email));
+        assert_eq!(parse_channel("in_app"), None);
+    }
+
+    #[test]
+    fn test_render_expire_remind() {
+        let templates = TemplateSet::builtin();
+        let params = serde_json::json!({"badge_name": "年度会员", "days_remaining": 3});
+        let context = template_context("expire_remind", Some(&params));
+        let notification_type = notification_type_for("expire_remind").unwrap();
+
+        let (title, body) = render_notification(
+            &templates,
+            &notification_type,
+            &NotificationChannel::AppPush,
+            &context,
+        )
+        .unwrap();
+        assert_eq!(notification_type, NotificationType::BadgeExpiring);
+        assert_eq!(title, "徽章即将过期");
+        assert_eq!(body, "您的「年度会员」徽章将在 3 天后过期，请及时使用！");
+        assert!(notification_type_for("unknown").is_none());
+    }
+
+    #[test]
+    fn test_render_uses_stored_template_and_locale() {
+        let templates = TemplateSet::new(
+            badge_shared::notification_templates::builtin_templates()
+                .into_iter()
+                .chain([NotificationTemplate::new(
+                    NotificationType::BadgeRevoked,
+                    "zh-CN",
+                    "徽章变更",
+                    "「{{badge_name}}」已失效：{{reason}}",
+                )]),
+        );
+        let params = serde_json::json!({"badge_name": "VIP", "reason": "前置徽章已过期"});
+
+        let (title, body) = render_notification(
+            &templates,
+            &NotificationType::BadgeRevoked,
+            &NotificationChannel::Sms,
+            &template_context("revoke", Some(&params)),
+        )
+        .unwrap();
+        assert_eq!(title, "徽章变更");
+        assert_eq!(body, "「VIP」已失效：前置徽章已过期");
+
+        let params = serde_json::json!({"badge_name": "VIP", "locale": "en-US"});
+        let (title, _) = render_notification(
+            &templates,
+            &NotificationType::BadgeExpiring,
+            &NotificationChannel::AppPush,
+            &template_context("expire", Some(&params)),
+        )
+        .unwrap();
+        assert_eq!(title, "Badge expiring soon");
+    }
+
+    #[test]
+    fn test_render_missing_variables_fails() {
+        let result = render_notification(
+            &TemplateSet::builtin(),
+            &NotificationType::BadgeExpiring,
+            &NotificationChannel::AppPush,
+            &template_context("expire_remind", None),
+        );
+        assert!(result.unwrap_err().contains("badge_name"));
+
+        let result = render_notification(
+            &TemplateSet::default(),
+            &NotificationType::BadgeGranted,
+            &NotificationChannel::AppPush,
+            &serde_json::json!({}),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_decide_outcome_completed_ignores_unsupported() {
+        let results = BTreeMap::from([
+            ("app_push".to_string(), result(ChannelStatus::Sent)),
+            ("in_app".to_string(), result(ChannelStatus::Unsupported)),
+        ]);
+
+        assert_eq!(
+            decide_outcome(&results, &task(0, 3), Utc::now()),
+            TaskOutcome::Completed
+        );
+    }
+
+    #[test]
+    fn test_decide_outcome_without_deliverable_channel_fails() {
+        let results = BTreeMap::from([("in_app".to_string(), result(ChannelStatus::Unsupported))]);
+
+        assert!(matches!(
+            decide_outcome(&results, &task(0, 3), Utc::now()),
+            TaskOutcome::Failed { .. }
+        ));
+    }
+
+    #[test]
+    fn test_decide_outcome_retry_backoff() {
+        let now = Utc::now();
+        let results = BTreeMap::from([
+            ("app_push".to_string(), result(ChannelStatus::Sent)),
+            ("sms".to_string(), result(ChannelStatus::Failed)),
+        ]);
+
+        assert_eq!(
+            decide_outcome(&results, &task(0, 3), now),
+            TaskOutcome::Retry {
+                next_retry_at: now + chrono::Duration::seconds(60)
+            }
+        );
+        assert_eq!(
+            decide_outcome(&results, &task(2, 3), now),
+            TaskOutcome::Retry {
+                next_retry_at: now + chrono::Duration::seconds(240)
+            }
+        );
+
+        match decide_outcome(&results, &task(3, 3), now) {
+            TaskOutcome::Failed { reason } => assert_eq!(reason, "sms: broker down"),
+            other => panic!("unexpected outcome: {:?}", other),
+        }
+    }
+}
@@ -120,6 +120,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let redeem_req = RedeemBadgeRequest {
         user_id: test_user.clone(),
         redemption_rule_id: "1".to_string(),
+        selected_badges: vec![],
     };
 
     match client.redeem_badge(redeem_req).await {
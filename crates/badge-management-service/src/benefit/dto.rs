@@ -95,6 +95,9 @@ pub struct BenefitGrantResult {
     /// 结果描述信息
     #[serde(skip_serializing_if = "Option::is_none")]
     pub message: Option<String>,
+    /// 失败是否由瞬时故障（超时、外部服务不可用等）引起，可以稍后重试
+    #[serde(default)]
+    pub retryable: bool,
 }
 
 impl BenefitGrantResult {
@@ -111,6 +114,7 @@ impl BenefitGrantResult {
             granted_at: None, // 留给 Handler 根据实际完成时间设置
             expires_at: None,
             message: None,
+            retryable: false,
         }
     }
 
@@ -130,6 +134,7 @@ impl BenefitGrantResult {
             granted_at: None,
             expires_at: None,
             message: None,
+            retryable: false,
         }
     }
 
@@ -143,9 +148,16 @@ impl BenefitGrantResult {
             granted_at: None,
             expires_at: None,
             message: Some(message.into()),
+            retryable: false,
         }
     }
 
+    /// 标记失败是否可重试
+    pub fn with_retryable(mut self, retryable: bool) -> Self {
+        self.retryable = retryable;
+        self
+    }
+
     /// 设置外部引用
     pub fn with_external_ref(mut self, external_ref: impl Into<String>) -> Self {
         self.external_ref = Some(external_ref.into());
@@ -197,6 +209,11 @@ pub struct BenefitRevokeResult {
     /// 结果描述信息
     #[serde(skip_serializing_if = "Option::is_none")]
     pub message: Option<String>,
+    /// 失败是否由瞬时故障（超时、外部服务不可用等）引起
+    ///
+    /// 为 true 时外部系统可能已经完成撤销，结果未知，调用方应稍后重试而不是视为拒绝
+    #[serde(default)]
+    pub retryable: bool,
 }
 
 impl BenefitRevokeResult {
@@ -207,6 +224,7 @@ impl BenefitRevokeResult {
             success: true,
             revoked_at: Some(Utc::now()),
             message: None,
+            retryable: false,
         }
     }
 
@@ -217,9 +235,16 @@ impl BenefitRevokeResult {
             success: false,
             revoked_at: None,
             message: Some(message.into()),
+            retryable: false,
         }
     }
 
+    /// 标记失败是否可重试
+    pub fn with_retryable(mut self, retryable: bool) -> Self {
+        self.retryable = retryable;
+        self
+    }
+
     /// 设置消息
     pub fn with_message(mut self, message: impl Into<String>) -> Self {
         self.message = Some(message.into());
@@ -300,6 +325,8 @@ mod tests {
         assert!(!result.success);
         assert!(result.revoked_at.is_none());
         assert_eq!(result.message, Some("权益已使用，无法撤销".to_string()));
+        assert!(!result.retryable);
+        assert!(result.with_retryable(true).retryable);
     }
 
     #[test]
@@ -1,18 +1,26 @@
 //! 优惠券权益处理器
 //!
-//! 同步发放优惠券，调用外部优惠券系统 API
+//! 同步发放优惠券，调用外部优惠券系统 REST API。
+//!
+//! 发放和撤销请求都以 `grant_no` 作为幂等键，优惠券系统按幂等键去重，
+//! 超时重试或重复投递不会多发券，撤销时也无需事先记录优惠券 ID。
+
+use std::time::Duration;
 
 use async_trait::async_trait;
+use badge_shared::circuit_breaker::CircuitBreakerConfig;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use tracing::{debug, error, info, instrument, warn};
-use uuid::Uuid;
+use tracing::{error, info, instrument, warn};
 
 use crate::benefit::dto::{BenefitGrantRequest, BenefitGrantResult, BenefitRevokeResult};
 use crate::benefit::handler::BenefitHandler;
 use crate::error::{BadgeError, Result};
 use crate::models::{BenefitType, GrantStatus};
 
+use super::service_client::{RemoteError, ServiceClient};
+
 /// 优惠券配置结构
 ///
 /// 配置需要包含 `coupon_template_id`，可选 `quantity`（默认为 1）
@@ -31,32 +39,62 @@ fn default_quantity() -> i32 {
     1
 }
 
-/// 优惠券发放响应（模拟外部系统返回）
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct CouponIssueResponse {
+/// 发券请求
+#[derive(Debug, Serialize)]
+struct IssueCouponRequest<'a> {
+    user_id: &'a str,
+    template_id: &'a str,
+    quantity: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    valid_days: Option<i32>,
+    idempotency_key: &'a str,
+}
+
+/// 撤销请求
+#[derive(Debug, Serialize)]
+struct RevokeCouponRequest<'a> {
+    idempotency_key: &'a str,
+}
+
+/// 优惠券系统响应
+#[derive(Debug, Deserialize)]
+struct CouponResponse {
+    coupon: RemoteCoupon,
+}
+
+#[derive(Debug, Deserialize)]
+struct RemoteCoupon {
     coupon_id: String,
-    coupon_code: String,
-    template_id: String,
-    expires_at: Option<String>,
+    expires_at: Option<DateTime<Utc>>,
 }
 
 /// 优惠券处理器
 ///
-/// 通过调用外部优惠券服务同步发放优惠券。
+/// 通过调用外部优惠券服务同步发放优惠券，调用受熔断器保护。
 /// 支持撤销操作（优惠券回收）。
 pub struct CouponHandler {
-    /// 优惠券服务 base URL（实际实现时使用）
-    #[allow(dead_code)]
-    coupon_service_url: String,
+    client: ServiceClient,
 }
 
 impl CouponHandler {
     /// 创建优惠券处理器
     pub fn new(coupon_service_url: impl Into<String>) -> Self {
         Self {
-            coupon_service_url: coupon_service_url.into(),
+            client: ServiceClient::new("coupon-service", coupon_service_url),
         }
     }
+
+    /// 设置单次请求超时
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client.set_timeout(timeout);
+        self
+    }
+
+    /// 设置熔断器参数
+    pub fn with_circuit_breaker(mut self, config: CircuitBreakerConfig) -> Self {
+        self.client.set_circuit_breaker(config);
+        self
+    }
 }
 
 impl Default for CouponHandler {
@@ -72,48 +110,37 @@ impl CouponHandler {
             .map_err(|e| BadgeError::Validation(format!("优惠券配置解析失败: {}", e)))
     }
 
-    /// 调用外部优惠券服务发放（stub 实现）
-    ///
-    /// TODO: 替换为实际的优惠券服务 SDK 调用
+    /// 调用外部优惠券服务发放
     async fn issue_coupon(
         &self,
         user_id: &str,
         config: &CouponConfig,
         grant_no: &str,
-    ) -> Result<CouponIssueResponse> {
-        // 生成模拟的优惠券数据
-        let coupon_id = Uuid::new_v4().to_string();
-        let coupon_code = format!("CPN{}", &coupon_id[..8].to_uppercase());
-
-        debug!(
-            user_id = %user_id,
-            template_id = %config.coupon_template_id,
-            grant_no = %grant_no,
-            coupon_id = %coupon_id,
-            "模拟发放优惠券"
-        );
-
-        // 模拟过期时间（如果配置了有效期）
-        let expires_at = config.validity_days.map(|days| {
-            let expires = chrono::Utc::now() + chrono::Duration::days(days as i64);
-            expires.to_rfc3339()
-        });
+    ) -> std::result::Result<RemoteCoupon, RemoteError> {
+        let request = IssueCouponRequest {
+            user_id,
+            template_id: &config.coupon_template_id,
+            quantity: config.quantity,
+            valid_days: config.validity_days,
+            idempotency_key: grant_no,
+        };
 
-        Ok(CouponIssueResponse {
-            coupon_id,
-            coupon_code,
-            template_id: config.coupon_template_id.clone(),
-            expires_at,
-        })
+        self.client
+            .post::<_, CouponResponse>("/coupons", &request)
+            .await
+            .map(|r| r.coupon)
     }
 
-    /// 调用外部优惠券服务撤销（stub 实现）
-    ///
-    /// TODO: 替换为实际的优惠券服务 SDK 调用
-    async fn revoke_coupon(&self, coupon_id: &str) -> Result<()> {
-        debug!(coupon_id = %coupon_id, "模拟撤销优惠券");
-        // 模拟撤销成功
-        Ok(())
+    /// 调用外部优惠券服务撤销
+    async fn revoke_coupon(&self, grant_no: &str) -> std::result::Result<(), RemoteError> {
+        let request = RevokeCouponRequest {
+            idempotency_key: grant_no,
+        };
+
+        self.client
+            .post::<_, CouponResponse>("/coupons/revoke", &request)
+            .await
+            .map(|_| ())
     }
 }
 
@@ -152,35 +179,28 @@ impl BenefitHandler for CouponHandler {
             .issue_coupon(&request.user_id, &config, &request.grant_no)
             .await
         {
-            Ok(response) => {
-                info!(
-                    coupon_id = %response.coupon_id,
-                    coupon_code = %response.coupon_code,
-                    "优惠券发放成功"
-                );
-
-                // 构建成功结果
+            Ok(coupon) => {
+                info!(coupon_id = %coupon.coupon_id, "优惠券发放成功");
+
                 let mut result = BenefitGrantResult::success(&request.grant_no)
                     .with_granted_now()
-                    .with_external_ref(&response.coupon_id)
+                    .with_external_ref(&coupon.coupon_id)
                     .with_payload(serde_json::json!({
-                        "coupon_id": response.coupon_id,
-                        "coupon_code": response.coupon_code,
-                        "template_id": response.template_id,
+                        "coupon_id": coupon.coupon_id,
+                        "template_id": config.coupon_template_id,
+                        "quantity": config.quantity,
                     }));
 
-                // 设置过期时间
-                if let Some(expires_str) = &response.expires_at
-                    && let Ok(expires) = chrono::DateTime::parse_from_rfc3339(expires_str)
-                {
-                    result = result.with_expires_at(expires.with_timezone(&chrono::Utc));
+                if let Some(expires_at) = coupon.expires_at {
+                    result = result.with_expires_at(expires_at);
                 }
 
                 Ok(result)
             }
             Err(e) => {
-                error!(error = %e, "优惠券发放失败");
-                Ok(BenefitGrantResult::failed(&request.grant_no, e.to_string()))
+                error!(error = %e, retryable = e.retryable, "优惠券发放失败");
+                Ok(BenefitGrantResult::failed(&request.grant_no, e.message)
+                    .with_retryable(e.retryable))
             }
         }
     }
@@ -194,14 +214,12 @@ impl BenefitHandler for CouponHandler {
     async fn revoke(&self, grant_no: &str) -> Result<BenefitRevokeResult> {
         info!(grant_no = %grant_no, "撤销优惠券");
 
-        // TODO: 实际实现需要：
-        // 1. 从 benefit_grants 表查询 external_ref（coupon_id）
-        // 2. 使用查询到的 coupon_id 调用优惠券系统撤销 API
-        // 当前为 stub 实现，使用 grant_no 模拟调用
-        warn!("revoke() 当前为 stub 实现，生产环境需要从数据库查询 coupon_id");
         match self.revoke_coupon(grant_no).await {
             Ok(()) => Ok(BenefitRevokeResult::success(grant_no)),
-            Err(e) => Ok(BenefitRevokeResult::failed(grant_no, e.to_string())),
+            Err(e) => {
+                warn!(error = %e, retryable = e.retryable, "优惠券撤销失败");
+                Ok(BenefitRevokeResult::failed(grant_no, e.message).with_retryable(e.retryable))
+            }
         }
     }
 
@@ -234,12 +252,18 @@ impl BenefitHandler for CouponHandler {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::benefit::test_support::ExternalStubs;
     use serde_json::json;
 
     fn create_handler() -> CouponHandler {
         CouponHandler::default()
     }
 
+    async fn create_stub_handler() -> (CouponHandler, ExternalStubs) {
+        let stubs = ExternalStubs::start().await;
+        (CouponHandler::new(&stubs.base_url), stubs)
+    }
+
     #[test]
     fn test_parse_config_success() {
         let handler = create_handler();
@@ -326,14 +350,15 @@ mod tests {
 
     #[tokio::test]
     async fn test_grant_success() {
-        let handler = create_handler();
+        let (handler, stubs) = create_stub_handler().await;
         let request = BenefitGrantRequest::new(
             "grant-001",
             "user-123",
             1,
             json!({
                 "coupon_template_id": "tpl-001",
-                "quantity": 1
+                "quantity": 1,
+                "validity_days": 7
             }),
         );
 
@@ -344,11 +369,46 @@ mod tests {
         assert!(result.external_ref.is_some());
         assert!(result.payload.is_some());
         assert!(result.granted_at.is_some());
+        assert!(result.expires_at.is_some());
 
         // 验证 payload 包含预期字段
         let payload = result.payload.unwrap();
         assert!(payload.get("coupon_id").is_some());
-        assert!(payload.get("coupon_code").is_some());
+        assert_eq!(payload.get("template_id").unwrap(), "tpl-001");
+        assert_eq!(stubs.coupon_state.coupons.count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_grant_is_idempotent_by_grant_no() {
+        let (handler, stubs) = create_stub_handler().await;
+        let request = BenefitGrantRequest::new(
+            "grant-001",
+            "user-123",
+            1,
+            json!({"coupon_template_id": "tpl-001"}),
+        );
+
+        let first = handler.grant(request.clone()).await.unwrap();
+        let second = handler.grant(request).await.unwrap();
+
+        assert_eq!(first.external_ref, second.external_ref);
+        assert_eq!(stubs.coupon_state.coupons.count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_grant_service_unavailable_is_retryable() {
+        let handler = CouponHandler::new("http://127.0.0.1:1");
+        let request = BenefitGrantRequest::new(
+            "grant-001",
+            "user-123",
+            1,
+            json!({"coupon_template_id": "tpl-001"}),
+        );
+
+        let result = handler.grant(request).await.unwrap();
+
+        assert_eq!(result.status, GrantStatus::Failed);
+        assert!(result.retryable);
     }
 
     #[tokio::test]
@@ -377,11 +437,23 @@ mod tests {
 
     #[tokio::test]
     async fn test_revoke() {
-        let handler = create_handler();
+        let (handler, _stubs) = create_stub_handler().await;
+        let request = BenefitGrantRequest::new(
+            "grant-001",
+            "user-123",
+            1,
+            json!({"coupon_template_id": "tpl-001"}),
+        );
+        handler.grant(request).await.unwrap();
+
         let result = handler.revoke("grant-001").await.unwrap();
 
         assert!(result.success);
         assert_eq!(result.grant_no, "grant-001");
+
+        // 优惠券系统中不存在的发放记录无法撤销
+        let result = handler.revoke("grant-unknown").await.unwrap();
+        assert!(!result.success);
     }
 
     #[test]
@@ -0,0 +1,316 @@
+//! 数字资产权益处理器
+//!
+//! 异步发放数字资产（NFT、虚拟物品等），铸造请求提交后需等待资产服务确认。
+//!
+//! 铸造、查询和回收都以 `grant_no` 作为幂等键调用资产服务。
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use badge_shared::circuit_breaker::CircuitBreakerConfig;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{error, info, instrument};
+
+use crate::benefit::dto::{BenefitGrantRequest, BenefitGrantResult, BenefitRevokeResult};
+use crate::benefit::handler::BenefitHandler;
+use crate::error::{BadgeError, Result};
+use crate::models::{BenefitType, GrantStatus};
+
+use super::fulfillment::{FulfillmentClient, FulfillmentPaths};
+
+/// 数字资产配置结构
+///
+/// 配置需要包含 `asset_id`，可选 `quantity`（默认为 1）和资产属性
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigitalAssetConfig {
+    /// 资产模板 ID（必填）
+    pub asset_id: String,
+    /// 资产名称（可选，用于显示）
+    pub asset_name: Option<String>,
+    /// 发放数量（默认 1）
+    #[serde(default = "default_quantity")]
+    pub quantity: i32,
+    /// 资产属性（可选，写入铸造的资产元数据，必须为 JSON 对象）
+    pub attributes: Option<Value>,
+}
+
+fn default_quantity() -> i32 {
+    1
+}
+
+/// 铸造请求
+#[derive(Debug, Serialize)]
+struct MintRequest<'a> {
+    user_id: &'a str,
+    asset_id: &'a str,
+    quantity: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    attributes: Option<&'a Value>,
+    idempotency_key: &'a str,
+}
+
+/// 数字资产处理器
+///
+/// 发放时向资产服务提交铸造请求，资产服务确认前返回 Processing，
+/// 之后通过 `query_status` 查询资产服务获取最终状态。支持撤销（回收资产）。
+pub struct DigitalAssetHandler {
+    client: FulfillmentClient,
+}
+
+impl DigitalAssetHandler {
+    /// 创建数字资产处理器
+    pub fn new(asset_service_url: impl Into<String>) -> Self {
+        Self {
+            client: FulfillmentClient::new(
+                "asset-service",
+                asset_service_url,
+                FulfillmentPaths {
+                    submit: "/assets/mint",
+                    query: "/assets/query",
+                    cancel: "/assets/reclaim",
+                },
+            ),
+        }
+    }
+
+    /// 设置单次请求超时
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client.set_timeout(timeout);
+        self
+    }
+
+    /// 设置熔断器参数
+    pub fn with_circuit_breaker(mut self, config: CircuitBreakerConfig) -> Self {
+        self.client.set_circuit_breaker(config);
+        self
+    }
+}
+
+impl Default for DigitalAssetHandler {
+    fn default() -> Self {
+        Self::new("http://asset-service:8080")
+    }
+}
+
+impl DigitalAssetHandler {
+    /// 解析数字资产配置
+    fn parse_config(&self, config: &Value) -> Result<DigitalAssetConfig> {
+        serde_json::from_value(config.clone())
+            .map_err(|e| BadgeError::Validation(format!("数字资产配置解析失败: {}", e)))
+    }
+}
+
+#[async_trait]
+impl BenefitHandler for DigitalAssetHandler {
+    fn benefit_type(&self) -> BenefitType {
+        BenefitType::DigitalAsset
+    }
+
+    #[instrument(
+        skip(self, request),
+        fields(
+            grant_no = %request.grant_no,
+            user_id = %request.user_id,
+            benefit_type = "digital_asset"
+        )
+    )]
+    async fn grant(&self, request: BenefitGrantRequest) -> Result<BenefitGrantResult> {
+        let config = match self.parse_config(&request.benefit_config) {
+            Ok(c) => c,
+            Err(e) => {
+                error!(error = %e, "数字资产配置解析失败");
+                return Ok(BenefitGrantResult::failed(&request.grant_no, e.to_string()));
+            }
+        };
+
+        info!(
+            asset_id = %config.asset_id,
+            quantity = config.quantity,
+            "开始发放数字资产"
+        );
+
+        let mint = MintRequest {
+            user_id: &request.user_id,
+            asset_id: &config.asset_id,
+            quantity: config.quantity,
+            attributes: config.attributes.as_ref(),
+            idempotency_key: &request.grant_no,
+        };
+
+        match self.client.submit(&mint).await {
+            Ok(response) => {
+                let mint_id = response.reference.clone();
+                let result = response.into_grant_result(&request.grant_no);
+                if !result.is_success() && !result.is_processing() {
+                    error!(mint_id = %mint_id, message = ?result.message, "资产服务拒绝铸造");
+                    return Ok(result);
+                }
+
+                info!(mint_id = %mint_id, status = ?result.status, "数字资产铸造请求已提交");
+
+                let mut result = result.with_payload(serde_json::json!({
+                    "mint_id": mint_id,
+                    "asset_id": config.asset_id,
+                    "asset_name": config.asset_name,
+                    "quantity": config.quantity,
+                }));
+                if result.is_processing() {
+                    result = result.with_message("铸造请求已提交，等待资产服务确认");
+                }
+
+                Ok(result)
+            }
+            Err(e) => {
+                error!(error = %e, retryable = e.retryable, "数字资产铸造请求提交失败");
+                Ok(BenefitGrantResult::failed(&request.grant_no, e.message)
+                    .with_retryable(e.retryable))
+            }
+        }
+    }
+
+    async fn query_status(&self, grant_no: &str) -> Result<GrantStatus> {
+        self.client.query_status(grant_no).await
+    }
+
+    async fn revoke(&self, grant_no: &str) -> Result<BenefitRevokeResult> {
+        info!(grant_no = %grant_no, "撤销数字资产");
+        Ok(self.client.cancel(grant_no).await)
+    }
+
+    fn validate_config(&self, config: &Value) -> Result<()> {
+        let asset_config = self.parse_config(config)?;
+
+        if asset_config.asset_id.is_empty() {
+            return Err(BadgeError::Validation("asset_id 不能为空".into()));
+        }
+
+        if asset_config.quantity <= 0 {
+            return Err(BadgeError::Validation("quantity 必须大于 0".into()));
+        }
+
+        if let Some(attributes) = &asset_config.attributes
+            && !attributes.is_object()
+        {
+            return Err(BadgeError::Validation("attributes 必须为 JSON 对象".into()));
+        }
+
+        Ok(())
+    }
+
+    fn description(&self) -> &'static str {
+        "Digital Asset Handler - 数字资产异步发放"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benefit::test_support::ExternalStubs;
+    use mock_services::services::FulfillmentKind;
+    use serde_json::json;
+
+    fn create_handler() -> DigitalAssetHandler {
+        DigitalAssetHandler::default()
+    }
+
+    #[test]
+    fn test_validate_config() {
+        let handler = create_handler();
+
+        assert!(handler.validate_config(&json!({"asset_id": "NFT-001"})).is_ok());
+        assert!(
+            handler
+                .validate_config(&json!({"asset_id": "NFT-001", "attributes": {"rarity": "SSR"}}))
+                .is_ok()
+        );
+
+        assert!(handler.validate_config(&json!({})).is_err());
+        assert!(handler.validate_config(&json!({"asset_id": ""})).is_err());
+        assert!(
+            handler
+                .validate_config(&json!({"asset_id": "NFT-001", "quantity": 0}))
+                .is_err()
+        );
+        assert!(
+            handler
+                .validate_config(&json!({"asset_id": "NFT-001", "attributes": [1, 2]}))
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_grant_processing_then_success() {
+        let stubs = ExternalStubs::start().await;
+        let handler = DigitalAssetHandler::new(&stubs.base_url);
+        let request = BenefitGrantRequest::new(
+            "grant-001",
+            "user-123",
+            1,
+            json!({"asset_id": "NFT-001", "asset_name": "周年纪念徽章"}),
+        );
+
+        let result = handler.grant(request).await.unwrap();
+        assert!(result.is_processing());
+        assert!(result.external_ref.unwrap().starts_with("MINT-"));
+
+        let record = stubs
+            .fulfillment_state
+            .get_record(FulfillmentKind::Asset, "grant-001")
+            .await
+            .unwrap();
+        assert_eq!(record.user_id, "user-123");
+        assert_eq!(record.details["asset_id"], "NFT-001");
+
+        // 状态以资产服务为准，新建的处理器实例同样可以查询
+        let handler = DigitalAssetHandler::new(&stubs.base_url);
+        assert_eq!(
+            handler.query_status("grant-001").await.unwrap(),
+            GrantStatus::Success
+        );
+        assert!(handler.query_status("unknown").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_grant_is_idempotent() {
+        let stubs = ExternalStubs::start().await;
+        let handler = DigitalAssetHandler::new(&stubs.base_url);
+        let request =
+            BenefitGrantRequest::new("grant-001", "user-123", 1, json!({"asset_id": "NFT-001"}));
+
+        let first = handler.grant(request.clone()).await.unwrap();
+        let second = handler.grant(request).await.unwrap();
+        assert_eq!(first.external_ref, second.external_ref);
+    }
+
+    #[tokio::test]
+    async fn test_grant_service_unavailable_is_retryable() {
+        let stubs = ExternalStubs::start().await;
+        stubs.fulfillment_state.set_fail_next(1);
+        let handler = DigitalAssetHandler::new(&stubs.base_url);
+        let request =
+            BenefitGrantRequest::new("grant-001", "user-123", 1, json!({"asset_id": "NFT-001"}));
+
+        let result = handler.grant(request).await.unwrap();
+        assert_eq!(result.status, GrantStatus::Failed);
+        assert!(result.retryable);
+    }
+
+    #[tokio::test]
+    async fn test_revoke() {
+        let stubs = ExternalStubs::start().await;
+        let handler = DigitalAssetHandler::new(&stubs.base_url);
+        let request =
+            BenefitGrantRequest::new("grant-001", "user-123", 1, json!({"asset_id": "NFT-001"}));
+        handler.grant(request).await.unwrap();
+
+        assert!(handler.revoke("grant-001").await.unwrap().success);
+        assert_eq!(
+            handler.query_status("grant-001").await.unwrap(),
+            GrantStatus::Revoked
+        );
+
+        // 资产服务中不存在的流水号撤销失败
+        assert!(!handler.revoke("unknown").await.unwrap().success);
+    }
+}
@@ -0,0 +1,601 @@
+//! 外部回调权益处理器
+//!
+//! 通用权益接口：将发放、查询、撤销请求签名后 POST 到配置的回调地址，
+//! 由外部系统完成具体的权益处理。
+//!
+//! ## 回调协议
+//!
+//! 请求体为 JSON，`action` 取值 `grant` / `query` / `revoke`，请求头携带
+//! `X-Badge-Timestamp` 和 `X-Badge-Signature`（见 `badge_shared::signature`）。
+//! 外部系统返回 2xx 和 `{"status": "SUCCESS" | "PROCESSING" | "FAILED" | "REVOKED",
+//! "reference": "...", "message": "..."}`。
+//!
+//! 网络错误、超时、5xx 和 429 按指数退避重试，其余 4xx 视为业务拒绝不再重试。
+//!
+//! 查询和撤销需要发放时的回调地址，统一从 `benefit_grants.benefit_config`
+//! （发放时的权益配置快照）读取，不在进程内缓存；发放状态始终以外部系统的
+//! 查询结果为准。未配置数据库连接池时无法查询和撤销。
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use badge_shared::retry::RetryPolicy;
+use badge_shared::signature;
+use chrono::Utc;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::PgPool;
+use tracing::{error, info, instrument, warn};
+
+use crate::benefit::dto::{BenefitGrantRequest, BenefitGrantResult, BenefitRevokeResult};
+use crate::benefit::handler::BenefitHandler;
+use crate::error::{BadgeError, Result};
+use crate::models::{BenefitType, GrantStatus};
+
+/// 单次回调超时上限（毫秒）
+const MAX_TIMEOUT_MS: u64 = 60_000;
+/// 重试次数上限
+const MAX_RETRIES: u32 = 10;
+
+/// 外部回调配置结构
+///
+/// 配置需要包含 `callback_url`，可选超时、重试次数和透传参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExternalCallbackConfig {
+    /// 回调地址（必填，http 或 https）
+    pub callback_url: String,
+    /// 单次请求超时（毫秒，默认 5000）
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+    /// 最大重试次数（不含首次请求，默认 3）
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    /// 透传给外部系统的业务参数（可选，必须为 JSON 对象）
+    pub params: Option<Value>,
+}
+
+fn default_timeout_ms() -> u64 {
+    5000
+}
+
+fn default_max_retries() -> u32 {
+    3
+}
+
+/// 回调动作
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum CallbackAction {
+    Grant,
+    Query,
+    Revoke,
+}
+
+/// 回调请求体
+#[derive(Debug, Serialize)]
+struct CallbackRequest<'a> {
+    action: CallbackAction,
+    grant_no: &'a str,
+    user_id: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    benefit_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    redemption_order_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    params: Option<&'a Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    metadata: Option<&'a Value>,
+}
+
+/// 外部系统响应
+#[derive(Debug, Deserialize)]
+struct CallbackResponse {
+    /// 外部系统的处理状态，PENDING 视为 PROCESSING
+    status: String,
+    reference: Option<String>,
+    message: Option<String>,
+    payload: Option<Value>,
+}
+
+impl CallbackResponse {
+    fn grant_status(&self) -> Option<GrantStatus> {
+        match self.status.to_ascii_uppercase().as_str() {
+            "SUCCESS" => Some(GrantStatus::Success),
+            "PROCESSING" | "PENDING" => Some(GrantStatus::Processing),
+            "FAILED" => Some(GrantStatus::Failed),
+            "REVOKED" => Some(GrantStatus::Revoked),
+            _ => None,
+        }
+    }
+}
+
+/// 后续查询和撤销需要的回调目标
+#[derive(Debug, Clone)]
+struct CallbackTarget {
+    callback_url: String,
+    timeout_ms: u64,
+    max_retries: u32,
+    user_id: String,
+}
+
+impl CallbackTarget {
+    fn new(config: &ExternalCallbackConfig, user_id: impl Into<String>) -> Self {
+        Self {
+            callback_url: config.callback_url.clone(),
+            timeout_ms: config.timeout_ms,
+            max_retries: config.max_retries,
+            user_id: user_id.into(),
+        }
+    }
+}
+
+/// 回调失败的分类
+#[derive(Debug)]
+enum CallbackError {
+    /// 瞬时故障，可以重试；重试耗尽后外部系统是否已处理无法确认
+    Transient(String),
+    /// 外部系统明确拒绝，重试无意义
+    Rejected(String),
+}
+
+impl CallbackError {
+    fn is_transient(&self) -> bool {
+        matches!(self, Self::Transient(_))
+    }
+}
+
+impl std::fmt::Display for CallbackError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Transient(e) | Self::Rejected(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// 外部回调处理器
+///
+/// 回调地址按权益配置，签名密钥由服务统一配置。支持撤销（通知外部系统回收）。
+pub struct ExternalCallbackHandler {
+    client: reqwest::Client,
+    signing_secret: String,
+    /// 退避策略，`max_retries` 由每个权益的配置覆盖
+    retry_policy: RetryPolicy,
+    /// 可选的数据库连接池，查询和撤销时据此读取回调目标
+    pool: Option<PgPool>,
+}
+
+impl ExternalCallbackHandler {
+    /// 创建外部回调处理器
+    pub fn new(signing_secret: impl Into<String>) -> Self {
+        let signing_secret = signing_secret.into();
+        if signing_secret.is_empty() {
+            warn!("未配置外部回调签名密钥，接收方将无法校验回调来源");
+        }
+
+        Self {
+            client: reqwest::Client::new(),
+            signing_secret,
+            retry_policy: RetryPolicy {
+                max_retries: default_max_retries(),
+                initial_delay: Duration::from_millis(500),
+                max_delay: Duration::from_secs(5),
+                multiplier: 2.0,
+            },
+            pool: None,
+        }
+    }
+
+    /// 设置数据库连接池（从 benefit_grants 恢复回调目标）
+    pub fn with_pool(mut self, pool: PgPool) -> Self {
+        self.pool = Some(pool);
+        self
+    }
+
+    /// 设置重试退避策略
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+}
+
+impl Default for ExternalCallbackHandler {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
+impl ExternalCallbackHandler {
+    /// 解析外部回调配置
+    fn parse_config(&self, config: &Value) -> Result<ExternalCallbackConfig> {
+        serde_json::from_value(config.clone())
+            .map_err(|e| BadgeError::Validation(format!("外部回调配置解析失败: {}", e)))
+    }
+
+    /// 获取发放时的回调目标
+    ///
+    /// 从 benefit_grants 读取发放时保存的权益配置
+    async fn load_target(&self, grant_no: &str) -> Result<Option<CallbackTarget>> {
+        let Some(ref pool) = self.pool else {
+            return Ok(None);
+        };
+
+        let row: Option<(String, Option<Value>)> = sqlx::query_as(
+            "SELECT user_id, benefit_config FROM benefit_grants WHERE grant_no = $1",
+        )
+        .bind(grant_no)
+        .fetch_optional(pool)
+        .await?;
+
+        let Some((user_id, Some(benefit_config))) = row else {
+            return Ok(None);
+        };
+        let config = self.parse_config(&benefit_config)?;
+        Ok(Some(CallbackTarget::new(&config, user_id)))
+    }
+
+    /// 发送回调，按策略重试瞬时故障
+    async fn call(
+        &self,
+        target: &CallbackTarget,
+        request: &CallbackRequest<'_>,
+    ) -> std::result::Result<CallbackResponse, CallbackError> {
+        let body =
+            serde_json::to_vec(request).map_err(|e| CallbackError::Rejected(e.to_string()))?;
+        let policy = RetryPolicy {
+            max_retries: target.max_retries,
+            ..self.retry_policy.clone()
+        };
+
+        let mut attempt = 0;
+        loop {
+            match self.send_once(target, &body).await {
+                Ok(response) => return Ok(response),
+                Err(CallbackError::Rejected(e)) => return Err(CallbackError::Rejected(e)),
+                Err(CallbackError::Transient(e)) => {
+                    if !policy.should_retry(attempt) {
+                        return Err(CallbackError::Transient(format!(
+                            "外部回调重试 {} 次后仍失败: {}",
+                            attempt, e
+                        )));
+                    }
+                    let delay = policy.delay_for_attempt(attempt);
+                    warn!(
+                        callback_url = %target.callback_url,
+                        grant_no = %request.grant_no,
+                        attempt,
+                        delay_ms = delay.as_millis() as u64,
+                        error = %e,
+                        "外部回调失败，将在退避后重试"
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    /// 签名并发送一次回调
+    async fn send_once(
+        &self,
+        target: &CallbackTarget,
+        body: &[u8],
+    ) -> std::result::Result<CallbackResponse, CallbackError> {
+        // 每次请求重新签名，避免重试间隔过长导致时间戳超出接收方容忍窗口
+        let timestamp = Utc::now().timestamp();
+        let sig = signature::sign(&self.signing_secret, timestamp, body);
+
+        let response = self
+            .client
+            .post(&target.callback_url)
+            .timeout(Duration::from_millis(target.timeout_ms))
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(signature::TIMESTAMP_HEADER, timestamp.to_string())
+            .header(signature::SIGNATURE_HEADER, sig)
+            .body(body.to_vec())
+            .send()
+            .await
+            .map_err(|e| CallbackError::Transient(e.to_string()))?;
+
+        let status = response.status();
+        if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+            return Err(CallbackError::Transient(format!("HTTP {}", status)));
+        }
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(CallbackError::Rejected(format!(
+                "外部系统拒绝请求: HTTP {} {}",
+                status, text
+            )));
+        }
+
+        response
+            .json::<CallbackResponse>()
+            .await
+            .map_err(|e| CallbackError::Rejected(format!("外部系统响应格式无效: {}", e)))
+    }
+}
+
+#[async_trait]
+impl BenefitHandler for ExternalCallbackHandler {
+    fn benefit_type(&self) -> BenefitType {
+        BenefitType::ExternalCallback
+    }
+
+    #[instrument(
+        skip(self, request),
+        fields(
+            grant_no = %request.grant_no,
+            user_id = %request.user_id,
+            benefit_type = "external_callback"
+        )
+    )]
+    async fn grant(&self, request: BenefitGrantRequest) -> Result<BenefitGrantResult> {
+        let config = match self.parse_config(&request.benefit_config) {
+            Ok(c) => c,
+            Err(e) => {
+                error!(error = %e, "外部回调配置解析失败");
+                return Ok(BenefitGrantResult::failed(&request.grant_no, e.to_string()));
+            }
+        };
+
+        // 外部系统按 grant_no 去重，重复发放返回原结果
+        let target = CallbackTarget::new(&config, &request.user_id);
+        let callback_request = CallbackRequest {
+            action: CallbackAction::Grant,
+            grant_no: &request.grant_no,
+            user_id: &request.user_id,
+            benefit_id: Some(request.benefit_id),
+            redemption_order_id: request.redemption_order_id,
+            params: config.params.as_ref(),
+            metadata: request.metadata.as_ref(),
+        };
+
+        info!(callback_url = %config.callback_url, "开始发送外部回调");
+
+        let response = match self.call(&target, &callback_request).await {
+            Ok(r) => r,
+            Err(e) => {
+                error!(error = %e, "外部回调失败");
+                return Ok(BenefitGrantResult::failed(&request.grant_no, e.to_string()));
+            }
+        };
+
+        let status = match response.grant_status() {
+            Some(s @ (GrantStatus::Success | GrantStatus::Processing)) => s,
+            _ => {
+                let message = response
+                    .message
+                    .unwrap_or_else(|| format!("外部系统返回状态 {}", response.status));
+                warn!(message = %message, "外部系统拒绝发放");
+                return Ok(BenefitGrantResult::failed(&request.grant_no, message));
+            }
+        };
+
+        let reference = response
+            .reference
+            .unwrap_or_else(|| request.grant_no.clone());
+
+        info!(reference = %reference, status = ?status, "外部回调完成");
+
+        let mut result = if status == GrantStatus::Success {
+            BenefitGrantResult::success(&request.grant_no).with_granted_now()
+        } else {
+            BenefitGrantResult::processing(&request.grant_no)
+        }
+        .with_external_ref(reference);
+        if let Some(payload) = response.payload {
+            result = result.with_payload(payload);
+        }
+        if let Some(message) = response.message {
+            result = result.with_message(message);
+        }
+
+        Ok(result)
+    }
+
+    async fn query_status(&self, grant_no: &str) -> Result<GrantStatus> {
+        let target = self
+            .load_target(grant_no)
+            .await?
+            .ok_or_else(|| BadgeError::Internal(format!("发放记录不存在: {}", grant_no)))?;
+
+        let request = CallbackRequest {
+            action: CallbackAction::Query,
+            grant_no,
+            user_id: &target.user_id,
+            benefit_id: None,
+            redemption_order_id: None,
+            params: None,
+            metadata: None,
+        };
+
+        match self.call(&target, &request).await {
+            Ok(response) => match response.grant_status() {
+                Some(status) => Ok(status),
+                None => {
+                    warn!(status = %response.status, "外部系统返回未知状态，保持处理中");
+                    Ok(GrantStatus::Processing)
+                }
+            },
+            Err(e) => {
+                // 查询失败不影响发放本身，保持处理中等待下次查询
+                warn!(grant_no = %grant_no, error = %e, "外部回调状态查询失败");
+                Ok(GrantStatus::Processing)
+            }
+        }
+    }
+
+    async fn revoke(&self, grant_no: &str) -> Result<BenefitRevokeResult> {
+        info!(grant_no = %grant_no, "撤销外部回调权益");
+
+        let target = match self.load_target(grant_no).await? {
+            Some(t) => t,
+            None => {
+                return Ok(BenefitRevokeResult::failed(
+                    grant_no,
+                    format!("发放记录不存在: {}", grant_no),
+                ));
+            }
+        };
+
+        let request = CallbackRequest {
+            action: CallbackAction::Revoke,
+            grant_no,
+            user_id: &target.user_id,
+            benefit_id: None,
+            redemption_order_id: None,
+            params: None,
+            metadata: None,
+        };
+
+        match self.call(&target, &request).await {
+            Ok(response)
+                if matches!(
+                    response.grant_status(),
+                    Some(GrantStatus::Revoked | GrantStatus::Success)
+                ) =>
+            {
+                Ok(BenefitRevokeResult::success(grant_no))
+            }
+            Ok(response) => Ok(BenefitRevokeResult::failed(
+                grant_no,
+                response
+                    .message
+                    .unwrap_or_else(|| format!("外部系统返回状态 {}", response.status)),
+            )),
+            Err(e) => {
+                warn!(grant_no = %grant_no, error = %e, "外部回调撤销失败");
+                Ok(BenefitRevokeResult::failed(grant_no, e.to_string())
+                    .with_retryable(e.is_transient()))
+            }
+        }
+    }
+
+    fn validate_config(&self, config: &Value) -> Result<()> {
+        let callback_config = self.parse_config(config)?;
+
+        let url = reqwest::Url::parse(&callback_config.callback_url)
+            .map_err(|e| BadgeError::Validation(format!("callback_url 无效: {}", e)))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(BadgeError::Validation(
+                "callback_url 必须为 http 或 https 地址".into(),
+            ));
+        }
+
+        if callback_config.timeout_ms == 0 || callback_config.timeout_ms > MAX_TIMEOUT_MS {
+            return Err(BadgeError::Validation(format!(
+                "timeout_ms 必须在 1-{} 之间",
+                MAX_TIMEOUT_MS
+            )));
+        }
+
+        if callback_config.max_retries > MAX_RETRIES {
+            return Err(BadgeError::Validation(format!(
+                "max_retries 不能超过 {}",
+                MAX_RETRIES
+            )));
+        }
+
+        if let Some(params) = &callback_config.params
+            && !params.is_object()
+        {
+            return Err(BadgeError::Validation("params 必须为 JSON 对象".into()));
+        }
+
+        Ok(())
+    }
+
+    fn description(&self) -> &'static str {
+        "External Callback Handler - 外部系统签名回调"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_validate_config() {
+        let handler = ExternalCallbackHandler::new("secret");
+
+        assert!(
+            handler
+                .validate_config(&json!({"callback_url": "https://partner.example.com/benefits"}))
+                .is_ok()
+        );
+        assert!(
+            handler
+                .validate_config(&json!({
+                    "callback_url": "http://partner:8080/cb",
+                    "timeout_ms": 1000,
+                    "max_retries": 5,
+                    "params": {"sku": "VIP-30D"}
+                }))
+                .is_ok()
+        );
+
+        assert!(handler.validate_config(&json!({})).is_err());
+        assert!(handler.validate_config(&json!({"callback_url": "not a url"})).is_err());
+        assert!(
+            handler
+                .validate_config(&json!({"callback_url": "ftp://partner/cb"}))
+                .is_err()
+        );
+        assert!(
+            handler
+                .validate_config(&json!({"callback_url": "http://p/cb", "timeout_ms": 0}))
+                .is_err()
+        );
+        assert!(
+            handler
+                .validate_config(&json!({"callback_url": "http://p/cb", "max_retries": 11}))
+                .is_err()
+        );
+        assert!(
+            handler
+                .validate_config(&json!({"callback_url": "http://p/cb", "params": "x"}))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_response_status_mapping() {
+        let response = |status: &str| CallbackResponse {
+            status: status.to_string(),
+            reference: None,
+            message: None,
+            payload: None,
+        };
+
+        assert_eq!(response("SUCCESS").grant_status(), Some(GrantStatus::Success));
+        assert_eq!(response("pending").grant_status(), Some(GrantStatus::Processing));
+        assert_eq!(response("FAILED").grant_status(), Some(GrantStatus::Failed));
+        assert_eq!(response("REVOKED").grant_status(), Some(GrantStatus::Revoked));
+        assert_eq!(response("UNKNOWN").grant_status(), None);
+    }
+
+    #[tokio::test]
+    async fn test_grant_unreachable_fails_after_retries() {
+        let handler = ExternalCallbackHandler::new("secret").with_retry_policy(RetryPolicy {
+            max_retries: 0,
+            initial_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(1),
+            multiplier: 1.0,
+        });
+        // 端口 9 (discard) 通常无服务监听，连接会被拒绝
+        let request = BenefitGrantRequest::new(
+            "grant-001",
+            "user-123",
+            1,
+            json!({"callback_url": "http://127.0.0.1:9/cb", "max_retries": 1, "timeout_ms": 500}),
+        );
+
+        let result = handler.grant(request).await.unwrap();
+        assert_eq!(result.status, GrantStatus::Failed);
+        assert!(result.message.unwrap().contains("重试 1 次"));
+        assert!(handler.query_status("grant-001").await.is_err());
+        assert!(!handler.revoke("grant-001").await.unwrap().success);
+    }
+}
@@ -0,0 +1,154 @@
+//! 异步履约服务客户端
+//!
+//! 数字资产、预约资格、会员权益都由外部履约系统异步完成：提交后返回
+//! PROCESSING，之后通过查询接口获取最终状态，撤销时调用对应的回退接口。
+//! 三者接口形状一致，均以 `grant_no` 作为幂等键，因此无需在本地保存外部引用，
+//! 服务重启或多副本部署时任意实例都能查询和撤销。
+
+use std::time::Duration;
+
+use badge_shared::circuit_breaker::CircuitBreakerConfig;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::benefit::dto::{BenefitGrantResult, BenefitRevokeResult};
+use crate::error::{BadgeError, Result};
+use crate::models::GrantStatus;
+
+use super::service_client::{RemoteError, ServiceClient};
+
+/// 履约服务的三个接口路径
+pub(super) struct FulfillmentPaths {
+    pub submit: &'static str,
+    pub query: &'static str,
+    pub cancel: &'static str,
+}
+
+/// 查询和撤销请求
+#[derive(Debug, Serialize)]
+struct KeyRequest<'a> {
+    idempotency_key: &'a str,
+}
+
+/// 履约服务响应
+#[derive(Debug, Deserialize)]
+pub(super) struct FulfillmentResponse {
+    /// 外部系统的关联引用（铸造任务号、预约凭证号、开通单号）
+    pub reference: String,
+    pub status: String,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+impl FulfillmentResponse {
+    /// 映射为发放状态，未识别的状态按失败处理
+    pub fn grant_status(&self) -> GrantStatus {
+        match self.status.as_str() {
+            "PROCESSING" => GrantStatus::Processing,
+            "SUCCESS" => GrantStatus::Success,
+            "REVOKED" => GrantStatus::Revoked,
+            _ => GrantStatus::Failed,
+        }
+    }
+
+    /// 转换为发放结果：处理中和成功都带上外部引用，其余状态视为外部系统拒绝
+    pub fn into_grant_result(self, grant_no: &str) -> BenefitGrantResult {
+        match self.grant_status() {
+            GrantStatus::Success => BenefitGrantResult::success(grant_no)
+                .with_granted_now()
+                .with_external_ref(self.reference),
+            GrantStatus::Processing => {
+                BenefitGrantResult::processing(grant_no).with_external_ref(self.reference)
+            }
+            _ => BenefitGrantResult::failed(
+                grant_no,
+                self.message
+                    .unwrap_or_else(|| format!("外部系统返回状态 {}", self.status)),
+            ),
+        }
+    }
+}
+
+/// 异步履约服务客户端
+pub(super) struct FulfillmentClient {
+    client: ServiceClient,
+    paths: FulfillmentPaths,
+}
+
+impl FulfillmentClient {
+    pub fn new(name: &str, base_url: impl Into<String>, paths: FulfillmentPaths) -> Self {
+        Self {
+            client: ServiceClient::new(name, base_url),
+            paths,
+        }
+    }
+
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.client.set_timeout(timeout);
+    }
+
+    pub fn set_circuit_breaker(&mut self, config: CircuitBreakerConfig) {
+        self.client.set_circuit_breaker(config);
+    }
+
+    /// 提交履约请求，请求体需包含 `idempotency_key`
+    pub async fn submit<B: Serialize>(
+        &self,
+        body: &B,
+    ) -> std::result::Result<FulfillmentResponse, RemoteError> {
+        self.client.post(self.paths.submit, body).await
+    }
+
+    /// 查询履约状态
+    ///
+    /// 瞬时故障时保持 Processing，由下次查询继续推进；
+    /// 外部系统明确拒绝（如记录不存在）时返回错误。
+    pub async fn query_status(&self, grant_no: &str) -> Result<GrantStatus> {
+        let request = KeyRequest {
+            idempotency_key: grant_no,
+        };
+
+        match self
+            .client
+            .post::<_, FulfillmentResponse>(self.paths.query, &request)
+            .await
+        {
+            Ok(response) => Ok(response.grant_status()),
+            Err(e) if e.retryable => {
+                warn!(grant_no = %grant_no, error = %e, "履约状态查询失败，保持处理中");
+                Ok(GrantStatus::Processing)
+            }
+            Err(e) => Err(BadgeError::Internal(format!(
+                "履约状态查询失败: {}",
+                e.message
+            ))),
+        }
+    }
+
+    /// 撤销履约，外部系统返回 REVOKED 视为成功
+    pub async fn cancel(&self, grant_no: &str) -> BenefitRevokeResult {
+        let request = KeyRequest {
+            idempotency_key: grant_no,
+        };
+
+        match self
+            .client
+            .post::<_, FulfillmentResponse>(self.paths.cancel, &request)
+            .await
+        {
+            Ok(response) if response.grant_status() == GrantStatus::Revoked => {
+                BenefitRevokeResult::success(grant_no)
+            }
+            Ok(response) => BenefitRevokeResult::failed(
+                grant_no,
+                response
+                    .message
+                    .unwrap_or_else(|| format!("外部系统返回状态 {}", response.status)),
+            ),
+            Err(e) => {
+                warn!(grant_no = %grant_no, error = %e, retryable = e.retryable, "履约撤销失败");
+                BenefitRevokeResult::failed(grant_no, e.message).with_retryable(e.retryable)
+            }
+        }
+    }
+}
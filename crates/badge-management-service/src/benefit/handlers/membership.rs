@@ -0,0 +1,250 @@
+//! 会员权益处理器
+//!
+//! 异步开通会员等级、VIP 资格等，由会员系统完成开通后生效。
+//!
+//! 开通、查询和回退都以 `grant_no` 作为幂等键调用会员服务。
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use badge_shared::circuit_breaker::CircuitBreakerConfig;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{error, info, instrument};
+
+use crate::benefit::dto::{BenefitGrantRequest, BenefitGrantResult, BenefitRevokeResult};
+use crate::benefit::handler::BenefitHandler;
+use crate::error::{BadgeError, Result};
+use crate::models::{BenefitType, GrantStatus};
+
+use super::fulfillment::{FulfillmentClient, FulfillmentPaths};
+
+/// 会员权益配置结构
+///
+/// 配置需要包含 `membership_level`，可选 `duration_days`（不填则永久有效）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MembershipConfig {
+    /// 会员等级（必填），如 gold、platinum
+    pub membership_level: String,
+    /// 会员有效天数（可选，不填则永久有效）
+    pub duration_days: Option<i32>,
+}
+
+/// 开通请求
+#[derive(Debug, Serialize)]
+struct ActivateRequest<'a> {
+    user_id: &'a str,
+    membership_level: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duration_days: Option<i32>,
+    idempotency_key: &'a str,
+}
+
+/// 会员权益处理器
+///
+/// 发放时向会员系统提交开通请求，开通完成前返回 Processing，
+/// 之后通过 `query_status` 查询会员系统获取最终状态。支持撤销（回退会员等级）。
+pub struct MembershipHandler {
+    client: FulfillmentClient,
+}
+
+impl MembershipHandler {
+    /// 创建会员权益处理器
+    pub fn new(membership_service_url: impl Into<String>) -> Self {
+        Self {
+            client: FulfillmentClient::new(
+                "membership-service",
+                membership_service_url,
+                FulfillmentPaths {
+                    submit: "/memberships/activate",
+                    query: "/memberships/query",
+                    cancel: "/memberships/deactivate",
+                },
+            ),
+        }
+    }
+
+    /// 设置单次请求超时
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client.set_timeout(timeout);
+        self
+    }
+
+    /// 设置熔断器参数
+    pub fn with_circuit_breaker(mut self, config: CircuitBreakerConfig) -> Self {
+        self.client.set_circuit_breaker(config);
+        self
+    }
+}
+
+impl Default for MembershipHandler {
+    fn default() -> Self {
+        Self::new("http://membership-service:8080")
+    }
+}
+
+impl MembershipHandler {
+    /// 解析会员权益配置
+    fn parse_config(&self, config: &Value) -> Result<MembershipConfig> {
+        serde_json::from_value(config.clone())
+            .map_err(|e| BadgeError::Validation(format!("会员权益配置解析失败: {}", e)))
+    }
+}
+
+#[async_trait]
+impl BenefitHandler for MembershipHandler {
+    fn benefit_type(&self) -> BenefitType {
+        BenefitType::Membership
+    }
+
+    #[instrument(
+        skip(self, request),
+        fields(
+            grant_no = %request.grant_no,
+            user_id = %request.user_id,
+            benefit_type = "membership"
+        )
+    )]
+    async fn grant(&self, request: BenefitGrantRequest) -> Result<BenefitGrantResult> {
+        let config = match self.parse_config(&request.benefit_config) {
+            Ok(c) => c,
+            Err(e) => {
+                error!(error = %e, "会员权益配置解析失败");
+                return Ok(BenefitGrantResult::failed(&request.grant_no, e.to_string()));
+            }
+        };
+
+        info!(
+            membership_level = %config.membership_level,
+            duration_days = ?config.duration_days,
+            "开始发放会员权益"
+        );
+
+        let activate = ActivateRequest {
+            user_id: &request.user_id,
+            membership_level: &config.membership_level,
+            duration_days: config.duration_days,
+            idempotency_key: &request.grant_no,
+        };
+
+        match self.client.submit(&activate).await {
+            Ok(response) => {
+                let activation_id = response.reference.clone();
+                let result = response.into_grant_result(&request.grant_no);
+                if !result.is_success() && !result.is_processing() {
+                    error!(activation_id = %activation_id, message = ?result.message, "会员系统拒绝开通");
+                    return Ok(result);
+                }
+
+                info!(activation_id = %activation_id, status = ?result.status, "会员开通请求已提交");
+
+                let mut result = result.with_payload(serde_json::json!({
+                    "activation_id": activation_id,
+                    "membership_level": config.membership_level,
+                    "duration_days": config.duration_days,
+                }));
+                if result.is_processing() {
+                    result = result.with_message("会员开通请求已提交，等待会员系统确认");
+                }
+                if let Some(days) = config.duration_days {
+                    result =
+                        result.with_expires_at(Utc::now() + chrono::Duration::days(days as i64));
+                }
+
+                Ok(result)
+            }
+            Err(e) => {
+                error!(error = %e, retryable = e.retryable, "会员开通请求提交失败");
+                Ok(BenefitGrantResult::failed(&request.grant_no, e.message)
+                    .with_retryable(e.retryable))
+            }
+        }
+    }
+
+    async fn query_status(&self, grant_no: &str) -> Result<GrantStatus> {
+        self.client.query_status(grant_no).await
+    }
+
+    async fn revoke(&self, grant_no: &str) -> Result<BenefitRevokeResult> {
+        info!(grant_no = %grant_no, "撤销会员权益");
+        Ok(self.client.cancel(grant_no).await)
+    }
+
+    fn validate_config(&self, config: &Value) -> Result<()> {
+        let membership_config = self.parse_config(config)?;
+
+        if membership_config.membership_level.is_empty() {
+            return Err(BadgeError::Validation("membership_level 不能为空".into()));
+        }
+
+        if let Some(days) = membership_config.duration_days
+            && days <= 0
+        {
+            return Err(BadgeError::Validation("duration_days 必须大于 0".into()));
+        }
+
+        Ok(())
+    }
+
+    fn description(&self) -> &'static str {
+        "Membership Handler - 会员权益异步发放"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benefit::test_support::ExternalStubs;
+    use serde_json::json;
+
+    fn create_handler() -> MembershipHandler {
+        MembershipHandler::default()
+    }
+
+    #[test]
+    fn test_validate_config() {
+        let handler = create_handler();
+        assert!(handler.validate_config(&json!({"membership_level": "gold"})).is_ok());
+        assert!(
+            handler
+                .validate_config(&json!({"membership_level": "gold", "duration_days": 90}))
+                .is_ok()
+        );
+
+        assert!(handler.validate_config(&json!({})).is_err());
+        assert!(handler.validate_config(&json!({"membership_level": ""})).is_err());
+        assert!(
+            handler
+                .validate_config(&json!({"membership_level": "gold", "duration_days": -1}))
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_grant_and_revoke() {
+        let stubs = ExternalStubs::start().await;
+        let handler = MembershipHandler::new(&stubs.base_url);
+        let request = BenefitGrantRequest::new(
+            "grant-001",
+            "user-123",
+            1,
+            json!({"membership_level": "gold", "duration_days": 90}),
+        );
+
+        let result = handler.grant(request).await.unwrap();
+        assert!(result.is_processing());
+        assert!(result.expires_at.is_some());
+        assert!(result.external_ref.unwrap().starts_with("MBR-"));
+
+        assert_eq!(
+            handler.query_status("grant-001").await.unwrap(),
+            GrantStatus::Success
+        );
+        assert!(handler.revoke("grant-001").await.unwrap().success);
+        assert_eq!(
+            handler.query_status("grant-001").await.unwrap(),
+            GrantStatus::Revoked
+        );
+    }
+}
@@ -2,19 +2,35 @@
 //!
 //! 提供各种权益类型的具体 Handler 实现：
 //!
-//! - `CouponHandler`: 优惠券发放（同步）
-//! - `PointsHandler`: 积分发放（同步）
+//! - `CouponHandler`: 优惠券发放（同步，调用优惠券服务）
+//! - `PointsHandler`: 积分发放（同步，调用积分服务）
 //! - `PhysicalHandler`: 实物奖品发放（异步，通过 Kafka）
+//! - `DigitalAssetHandler`: 数字资产发放（异步，调用资产服务铸造）
+//! - `ReservationHandler`: 预约资格发放（异步，调用预约服务占用名额）
+//! - `MembershipHandler`: 会员权益发放（异步，调用会员服务开通）
+//! - `ExternalCallbackHandler`: 外部回调（签名 HTTP 回调，支持同步或异步结果）
 //!
 //! ## 设计说明
 //!
 //! 每个 Handler 专注于单一权益类型的发放逻辑，通过实现 `BenefitHandler` trait
-//! 提供统一接口。外部系统调用目前为 stub 实现，实际对接时需替换为真实的 SDK 调用。
+//! 提供统一接口。优惠券和积分通过 `service_client` 调用外部 REST 服务（超时 + 熔断）。
+//! 数字资产、预约资格、会员权益通过 `fulfillment` 对接异步履约服务，
+//! `query_status` 直接查询外部系统推进 Processing -> Success。
 
 mod coupon;
+mod digital_asset;
+mod external_callback;
+mod fulfillment;
+mod membership;
 mod physical;
 mod points;
+mod reservation;
+mod service_client;
 
 pub use coupon::CouponHandler;
+pub use digital_asset::DigitalAssetHandler;
+pub use external_callback::ExternalCallbackHandler;
+pub use membership::MembershipHandler;
 pub use physical::PhysicalHandler;
 pub use points::PointsHandler;
+pub use reservation::ReservationHandler;
@@ -1,18 +1,26 @@
 //! 积分权益处理器
 //!
-//! 同步发放积分，调用外部积分系统 API
+//! 同步发放积分，调用外部积分系统 REST API。
+//!
+//! 发放和撤销请求都以 `grant_no` 作为幂等键，积分系统按幂等键去重，
+//! 重试不会重复加分，撤销时也无需事先记录积分流水号和金额。
+
+use std::time::Duration;
 
 use async_trait::async_trait;
+use badge_shared::circuit_breaker::CircuitBreakerConfig;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use tracing::{debug, error, info, instrument, warn};
-use uuid::Uuid;
+use tracing::{error, info, instrument, warn};
 
 use crate::benefit::dto::{BenefitGrantRequest, BenefitGrantResult, BenefitRevokeResult};
 use crate::benefit::handler::BenefitHandler;
 use crate::error::{BadgeError, Result};
 use crate::models::{BenefitType, GrantStatus};
 
+use super::service_client::{RemoteError, ServiceClient};
+
 /// 积分配置结构
 ///
 /// 配置需要包含 `point_amount`，可选 `point_type`（默认为 "general"）
@@ -33,32 +41,67 @@ fn default_point_type() -> String {
     "general".to_string()
 }
 
-/// 积分发放响应（模拟外部系统返回）
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct PointsGrantResponse {
-    transaction_id: String,
-    point_amount: i64,
-    point_type: String,
-    balance_after: i64,
+/// 积分发放请求
+#[derive(Debug, Serialize)]
+struct PointsGrantRequest<'a> {
+    user_id: &'a str,
+    benefit_type: &'static str,
+    /// 积分类型作为权益编码
+    benefit_code: &'a str,
+    value: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    validity_days: Option<i32>,
+    idempotency_key: &'a str,
+}
+
+/// 积分撤销请求
+#[derive(Debug, Serialize)]
+struct PointsRevokeRequest<'a> {
+    idempotency_key: &'a str,
+}
+
+/// 积分系统返回的发放记录
+#[derive(Debug, Deserialize)]
+struct PointsGrantRecord {
+    grant_id: String,
+    external_ref: Option<String>,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+impl PointsGrantRecord {
+    /// 积分流水号，积分系统未返回外部引用时使用其内部记录 ID
+    fn transaction_id(&self) -> &str {
+        self.external_ref.as_deref().unwrap_or(&self.grant_id)
+    }
 }
 
 /// 积分处理器
 ///
-/// 通过调用外部积分服务同步发放积分。
+/// 通过调用外部积分服务同步发放积分，调用受熔断器保护。
 /// 支持撤销操作（积分回收）。
 pub struct PointsHandler {
-    /// 积分服务 base URL（实际实现时使用）
-    #[allow(dead_code)]
-    points_service_url: String,
+    client: ServiceClient,
 }
 
 impl PointsHandler {
     /// 创建积分处理器
     pub fn new(points_service_url: impl Into<String>) -> Self {
         Self {
-            points_service_url: points_service_url.into(),
+            client: ServiceClient::new("points-service", points_service_url),
         }
     }
+
+    /// 设置单次请求超时
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client.set_timeout(timeout);
+        self
+    }
+
+    /// 设置熔断器参数
+    pub fn with_circuit_breaker(mut self, config: CircuitBreakerConfig) -> Self {
+        self.client.set_circuit_breaker(config);
+        self
+    }
 }
 
 impl Default for PointsHandler {
@@ -74,48 +117,38 @@ impl PointsHandler {
             .map_err(|e| BadgeError::Validation(format!("积分配置解析失败: {}", e)))
     }
 
-    /// 调用外部积分服务发放（stub 实现）
-    ///
-    /// TODO: 替换为实际的积分服务 SDK 调用
+    /// 调用外部积分服务发放
     async fn grant_points(
         &self,
         user_id: &str,
         config: &PointsConfig,
         grant_no: &str,
-    ) -> Result<PointsGrantResponse> {
-        let transaction_id = Uuid::new_v4().to_string();
-
-        debug!(
-            user_id = %user_id,
-            point_amount = config.point_amount,
-            point_type = %config.point_type,
-            grant_no = %grant_no,
-            transaction_id = %transaction_id,
-            "模拟发放积分"
-        );
-
-        // 模拟发放后的余额（实际应由积分服务返回）
-        let mock_balance_after = config.point_amount + 1000;
+    ) -> std::result::Result<PointsGrantRecord, RemoteError> {
+        let request = PointsGrantRequest {
+            user_id,
+            benefit_type: "points",
+            benefit_code: &config.point_type,
+            value: serde_json::json!({
+                "point_amount": config.point_amount,
+                "remark": config.remark,
+            }),
+            validity_days: config.validity_days,
+            idempotency_key: grant_no,
+        };
 
-        Ok(PointsGrantResponse {
-            transaction_id,
-            point_amount: config.point_amount,
-            point_type: config.point_type.clone(),
-            balance_after: mock_balance_after,
-        })
+        self.client.post("/benefits/grant", &request).await
     }
 
-    /// 调用外部积分服务撤销（stub 实现）
-    ///
-    /// TODO: 替换为实际的积分服务 SDK 调用
-    async fn revoke_points(&self, transaction_id: &str, amount: i64) -> Result<()> {
-        debug!(
-            transaction_id = %transaction_id,
-            amount = amount,
-            "模拟撤销积分"
-        );
-        // 模拟撤销成功
-        Ok(())
+    /// 调用外部积分服务撤销
+    async fn revoke_points(&self, grant_no: &str) -> std::result::Result<(), RemoteError> {
+        let request = PointsRevokeRequest {
+            idempotency_key: grant_no,
+        };
+
+        self.client
+            .post::<_, PointsGrantRecord>("/benefits/revoke", &request)
+            .await
+            .map(|_| ())
     }
 }
 
@@ -154,29 +187,29 @@ impl BenefitHandler for PointsHandler {
             .grant_points(&request.user_id, &config, &request.grant_no)
             .await
         {
-            Ok(response) => {
-                info!(
-                    transaction_id = %response.transaction_id,
-                    balance_after = response.balance_after,
-                    "积分发放成功"
-                );
-
-                // 构建成功结果
-                let result = BenefitGrantResult::success(&request.grant_no)
+            Ok(record) => {
+                let transaction_id = record.transaction_id().to_string();
+                info!(transaction_id = %transaction_id, "积分发放成功");
+
+                let mut result = BenefitGrantResult::success(&request.grant_no)
                     .with_granted_now()
-                    .with_external_ref(&response.transaction_id)
+                    .with_external_ref(&transaction_id)
                     .with_payload(serde_json::json!({
-                        "transaction_id": response.transaction_id,
-                        "point_amount": response.point_amount,
-                        "point_type": response.point_type,
-                        "balance_after": response.balance_after,
+                        "transaction_id": transaction_id,
+                        "point_amount": config.point_amount,
+                        "point_type": config.point_type,
                     }));
 
+                if let Some(expires_at) = record.expires_at {
+                    result = result.with_expires_at(expires_at);
+                }
+
                 Ok(result)
             }
             Err(e) => {
-                error!(error = %e, "积分发放失败");
-                Ok(BenefitGrantResult::failed(&request.grant_no, e.to_string()))
+                error!(error = %e, retryable = e.retryable, "积分发放失败");
+                Ok(BenefitGrantResult::failed(&request.grant_no, e.message)
+                    .with_retryable(e.retryable))
             }
         }
     }
@@ -190,14 +223,12 @@ impl BenefitHandler for PointsHandler {
     async fn revoke(&self, grant_no: &str) -> Result<BenefitRevokeResult> {
         info!(grant_no = %grant_no, "撤销积分");
 
-        // TODO: 实际实现需要：
-        // 1. 从 benefit_grants 表查询 payload（包含 point_amount）
-        // 2. 使用查询到的金额调用积分系统撤销 API
-        // 当前为 stub 实现，使用 0 作为占位符
-        warn!("revoke() 当前为 stub 实现，生产环境需要从数据库查询发放金额");
-        match self.revoke_points(grant_no, 0).await {
+        match self.revoke_points(grant_no).await {
             Ok(()) => Ok(BenefitRevokeResult::success(grant_no)),
-            Err(e) => Ok(BenefitRevokeResult::failed(grant_no, e.to_string())),
+            Err(e) => {
+                warn!(error = %e, retryable = e.retryable, "积分撤销失败");
+                Ok(BenefitRevokeResult::failed(grant_no, e.message).with_retryable(e.retryable))
+            }
         }
     }
 
@@ -230,12 +261,18 @@ impl BenefitHandler for PointsHandler {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::benefit::test_support::ExternalStubs;
     use serde_json::json;
 
     fn create_handler() -> PointsHandler {
         PointsHandler::default()
     }
 
+    async fn create_stub_handler() -> (PointsHandler, ExternalStubs) {
+        let stubs = ExternalStubs::start().await;
+        (PointsHandler::new(&stubs.base_url), stubs)
+    }
+
     #[test]
     fn test_parse_config_success() {
         let handler = create_handler();
@@ -332,7 +369,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_grant_success() {
-        let handler = create_handler();
+        let (handler, stubs) = create_stub_handler().await;
         let request = BenefitGrantRequest::new(
             "grant-001",
             "user-123",
@@ -356,7 +393,37 @@ mod tests {
         assert!(payload.get("transaction_id").is_some());
         assert_eq!(payload.get("point_amount").unwrap(), 100);
         assert_eq!(payload.get("point_type").unwrap(), "bonus");
-        assert!(payload.get("balance_after").is_some());
+        assert_eq!(
+            stubs
+                .benefit_state
+                .get_user_benefits("user-123")
+                .await
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_grant_remote_errors() {
+        let (handler, stubs) = create_stub_handler().await;
+        let request =
+            BenefitGrantRequest::new("grant-001", "user-123", 1, json!({"point_amount": 100}));
+
+        // 外部服务 503：失败但可重试
+        stubs.benefit_state.set_simulate_failure(true).await;
+        let result = handler.grant(request.clone()).await.unwrap();
+        assert_eq!(result.status, GrantStatus::Failed);
+        assert!(result.retryable);
+
+        // 外部服务业务拒绝：失败且不可重试
+        stubs.benefit_state.set_simulate_failure(false).await;
+        stubs
+            .benefit_state
+            .set_failing_types(vec!["points".to_string()])
+            .await;
+        let result = handler.grant(request).await.unwrap();
+        assert_eq!(result.status, GrantStatus::Failed);
+        assert!(!result.retryable);
     }
 
     #[tokio::test]
@@ -385,11 +452,21 @@ mod tests {
 
     #[tokio::test]
     async fn test_revoke() {
-        let handler = create_handler();
+        let (handler, stubs) = create_stub_handler().await;
+        let request =
+            BenefitGrantRequest::new("grant-001", "user-123", 1, json!({"point_amount": 100}));
+        handler.grant(request).await.unwrap();
+
         let result = handler.revoke("grant-001").await.unwrap();
 
         assert!(result.success);
         assert_eq!(result.grant_no, "grant-001");
+        let record = stubs
+            .benefit_state
+            .get_by_idempotency_key("grant-001")
+            .await
+            .unwrap();
+        assert_eq!(record.status, "revoked");
     }
 
     #[test]
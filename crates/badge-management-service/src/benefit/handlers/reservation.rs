@@ -0,0 +1,276 @@
+//! 预约资格权益处理器
+//!
+//! 异步发放 VIP 通道、优先预约等资格，先占用名额，由预约系统确认后生效。
+//!
+//! 占用、查询和释放名额都以 `grant_no` 作为幂等键调用预约服务。
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use badge_shared::circuit_breaker::CircuitBreakerConfig;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{error, info, instrument};
+
+use crate::benefit::dto::{BenefitGrantRequest, BenefitGrantResult, BenefitRevokeResult};
+use crate::benefit::handler::BenefitHandler;
+use crate::error::{BadgeError, Result};
+use crate::models::{BenefitType, GrantStatus};
+
+use super::fulfillment::{FulfillmentClient, FulfillmentPaths};
+
+/// 预约资格配置结构
+///
+/// 配置需要包含 `reservation_type` 和 `resource_id`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReservationConfig {
+    /// 资格类型（必填），如 vip_channel、priority_booking
+    pub reservation_type: String,
+    /// 预约资源 ID（必填），如活动场次、服务窗口
+    pub resource_id: String,
+    /// 资格次数（默认 1）
+    #[serde(default = "default_quantity")]
+    pub quantity: i32,
+    /// 资格有效期天数（可选，不填则跟随资源本身的有效期）
+    pub valid_days: Option<i32>,
+}
+
+fn default_quantity() -> i32 {
+    1
+}
+
+/// 名额占用请求
+#[derive(Debug, Serialize)]
+struct HoldRequest<'a> {
+    user_id: &'a str,
+    reservation_type: &'a str,
+    resource_id: &'a str,
+    quantity: i32,
+    idempotency_key: &'a str,
+}
+
+/// 预约资格处理器
+///
+/// 发放时向预约系统提交名额占用，确认前返回 Processing，
+/// 之后通过 `query_status` 查询预约系统获取最终状态。支持撤销（释放名额）。
+pub struct ReservationHandler {
+    client: FulfillmentClient,
+}
+
+impl ReservationHandler {
+    /// 创建预约资格处理器
+    pub fn new(reservation_service_url: impl Into<String>) -> Self {
+        Self {
+            client: FulfillmentClient::new(
+                "reservation-service",
+                reservation_service_url,
+                FulfillmentPaths {
+                    submit: "/reservations/hold",
+                    query: "/reservations/query",
+                    cancel: "/reservations/release",
+                },
+            ),
+        }
+    }
+
+    /// 设置单次请求超时
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client.set_timeout(timeout);
+        self
+    }
+
+    /// 设置熔断器参数
+    pub fn with_circuit_breaker(mut self, config: CircuitBreakerConfig) -> Self {
+        self.client.set_circuit_breaker(config);
+        self
+    }
+}
+
+impl Default for ReservationHandler {
+    fn default() -> Self {
+        Self::new("http://reservation-service:8080")
+    }
+}
+
+impl ReservationHandler {
+    /// 解析预约资格配置
+    fn parse_config(&self, config: &Value) -> Result<ReservationConfig> {
+        serde_json::from_value(config.clone())
+            .map_err(|e| BadgeError::Validation(format!("预约资格配置解析失败: {}", e)))
+    }
+}
+
+#[async_trait]
+impl BenefitHandler for ReservationHandler {
+    fn benefit_type(&self) -> BenefitType {
+        BenefitType::Reservation
+    }
+
+    #[instrument(
+        skip(self, request),
+        fields(
+            grant_no = %request.grant_no,
+            user_id = %request.user_id,
+            benefit_type = "reservation"
+        )
+    )]
+    async fn grant(&self, request: BenefitGrantRequest) -> Result<BenefitGrantResult> {
+        let config = match self.parse_config(&request.benefit_config) {
+            Ok(c) => c,
+            Err(e) => {
+                error!(error = %e, "预约资格配置解析失败");
+                return Ok(BenefitGrantResult::failed(&request.grant_no, e.to_string()));
+            }
+        };
+
+        info!(
+            reservation_type = %config.reservation_type,
+            resource_id = %config.resource_id,
+            "开始发放预约资格"
+        );
+
+        let hold = HoldRequest {
+            user_id: &request.user_id,
+            reservation_type: &config.reservation_type,
+            resource_id: &config.resource_id,
+            quantity: config.quantity,
+            idempotency_key: &request.grant_no,
+        };
+
+        match self.client.submit(&hold).await {
+            Ok(response) => {
+                let reservation_no = response.reference.clone();
+                let result = response.into_grant_result(&request.grant_no);
+                if !result.is_success() && !result.is_processing() {
+                    error!(reservation_no = %reservation_no, message = ?result.message, "预约系统拒绝占用名额");
+                    return Ok(result);
+                }
+
+                info!(reservation_no = %reservation_no, status = ?result.status, "预约名额已提交占用");
+
+                let mut result = result.with_payload(serde_json::json!({
+                    "reservation_no": reservation_no,
+                    "reservation_type": config.reservation_type,
+                    "resource_id": config.resource_id,
+                    "quantity": config.quantity,
+                }));
+                if result.is_processing() {
+                    result = result.with_message("预约名额已提交，等待预约系统确认");
+                }
+                if let Some(days) = config.valid_days {
+                    result =
+                        result.with_expires_at(Utc::now() + chrono::Duration::days(days as i64));
+                }
+
+                Ok(result)
+            }
+            Err(e) => {
+                error!(error = %e, retryable = e.retryable, "预约名额占用失败");
+                Ok(BenefitGrantResult::failed(&request.grant_no, e.message)
+                    .with_retryable(e.retryable))
+            }
+        }
+    }
+
+    async fn query_status(&self, grant_no: &str) -> Result<GrantStatus> {
+        self.client.query_status(grant_no).await
+    }
+
+    async fn revoke(&self, grant_no: &str) -> Result<BenefitRevokeResult> {
+        info!(grant_no = %grant_no, "撤销预约资格");
+        Ok(self.client.cancel(grant_no).await)
+    }
+
+    fn validate_config(&self, config: &Value) -> Result<()> {
+        let reservation_config = self.parse_config(config)?;
+
+        if reservation_config.reservation_type.is_empty() {
+            return Err(BadgeError::Validation("reservation_type 不能为空".into()));
+        }
+
+        if reservation_config.resource_id.is_empty() {
+            return Err(BadgeError::Validation("resource_id 不能为空".into()));
+        }
+
+        if reservation_config.quantity <= 0 {
+            return Err(BadgeError::Validation("quantity 必须大于 0".into()));
+        }
+
+        if let Some(days) = reservation_config.valid_days
+            && days <= 0
+        {
+            return Err(BadgeError::Validation("valid_days 必须大于 0".into()));
+        }
+
+        Ok(())
+    }
+
+    fn description(&self) -> &'static str {
+        "Reservation Handler - 预约资格异步发放"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benefit::test_support::ExternalStubs;
+    use serde_json::json;
+
+    fn create_handler() -> ReservationHandler {
+        ReservationHandler::default()
+    }
+
+    fn valid_config() -> Value {
+        json!({
+            "reservation_type": "vip_channel",
+            "resource_id": "EVENT-2025-001",
+            "valid_days": 30
+        })
+    }
+
+    #[test]
+    fn test_validate_config() {
+        let handler = create_handler();
+        assert!(handler.validate_config(&valid_config()).is_ok());
+
+        assert!(handler.validate_config(&json!({"reservation_type": "vip_channel"})).is_err());
+        assert!(
+            handler
+                .validate_config(&json!({"reservation_type": "", "resource_id": "R1"}))
+                .is_err()
+        );
+        assert!(
+            handler
+                .validate_config(&json!({
+                    "reservation_type": "vip_channel",
+                    "resource_id": "R1",
+                    "valid_days": 0
+                }))
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_grant_and_revoke() {
+        let stubs = ExternalStubs::start().await;
+        let handler = ReservationHandler::new(&stubs.base_url);
+        let request = BenefitGrantRequest::new("grant-001", "user-123", 1, valid_config());
+
+        let result = handler.grant(request).await.unwrap();
+        assert!(result.is_processing());
+        assert!(result.expires_at.is_some());
+        assert!(result.external_ref.unwrap().starts_with("RSV-"));
+
+        assert_eq!(
+            handler.query_status("grant-001").await.unwrap(),
+            GrantStatus::Success
+        );
+
+        assert!(handler.revoke("grant-001").await.unwrap().success);
+        assert_eq!(
+            handler.query_status("grant-001").await.unwrap(),
+            GrantStatus::Revoked
+        );
+    }
+}
@@ -0,0 +1,172 @@
+//! 外部权益服务 HTTP 客户端
+//!
+//! 优惠券、积分等同步权益通过 REST 接口对接外部系统。客户端统一处理超时、
+//! 熔断和错误分类，Handler 只关心业务字段的组装和解析。
+//!
+//! ## 错误分类
+//!
+//! - 网络错误、超时、5xx、429 以及熔断器跳闸视为瞬时故障，可由上层重试
+//! - 其余 4xx 和无法解析的响应视为业务拒绝，重试无意义
+//!
+//! 只有瞬时故障计入熔断器失败次数，业务拒绝说明外部服务本身是健康的。
+
+use std::time::Duration;
+
+use badge_shared::circuit_breaker::{CircuitBreaker, CircuitBreakerConfig};
+use reqwest::StatusCode;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tracing::warn;
+
+/// 默认请求超时
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 外部服务调用失败
+#[derive(Debug, Clone)]
+pub(super) struct RemoteError {
+    pub message: String,
+    /// 是否为瞬时故障，供发放和撤销结果标记 retryable
+    pub retryable: bool,
+}
+
+impl RemoteError {
+    fn transient(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            retryable: true,
+        }
+    }
+
+    fn rejected(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            retryable: false,
+        }
+    }
+}
+
+impl std::fmt::Display for RemoteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// 受熔断器保护的外部服务客户端
+pub(super) struct ServiceClient {
+    client: reqwest::Client,
+    base_url: String,
+    timeout: Duration,
+    breaker: CircuitBreaker,
+}
+
+impl ServiceClient {
+    /// 创建客户端，`name` 同时作为熔断器名称用于日志和指标
+    pub fn new(name: &str, base_url: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            timeout: DEFAULT_TIMEOUT,
+            breaker: CircuitBreaker::new(CircuitBreakerConfig::new(name)),
+        }
+    }
+
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    pub fn set_circuit_breaker(&mut self, config: CircuitBreakerConfig) {
+        self.breaker = CircuitBreaker::new(config);
+    }
+
+    /// POST JSON 请求并解析响应
+    pub async fn post<B, R>(&self, path: &str, body: &B) -> Result<R, RemoteError>
+    where
+        B: Serialize + ?Sized,
+        R: DeserializeOwned,
+    {
+        if !self.breaker.allow_request() {
+            return Err(RemoteError::transient(format!(
+                "外部服务熔断中，请求被拒绝: {}",
+                self.base_url
+            )));
+        }
+
+        let result = self.send(path, body).await;
+        match &result {
+            Err(e) if e.retryable => self.breaker.record_failure(),
+            _ => self.breaker.record_success(),
+        }
+        result
+    }
+
+    async fn send<B, R>(&self, path: &str, body: &B) -> Result<R, RemoteError>
+    where
+        B: Serialize + ?Sized,
+        R: DeserializeOwned,
+    {
+        let url = format!("{}{}", self.base_url, path);
+        let response = self
+            .client
+            .post(&url)
+            .timeout(self.timeout)
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| {
+                warn!(url = %url, error = %e, "外部服务请求失败");
+                RemoteError::transient(format!("外部服务请求失败: {}", e))
+            })?;
+
+        let status = response.status();
+        if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+            return Err(RemoteError::transient(format!(
+                "外部服务暂不可用: HTTP {}",
+                status
+            )));
+        }
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(RemoteError::rejected(format!(
+                "外部服务拒绝请求: HTTP {} {}",
+                status, text
+            )));
+        }
+
+        response
+            .json::<R>()
+            .await
+            .map_err(|e| RemoteError::rejected(format!("外部服务响应格式无效: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_unreachable_service_is_retryable_and_trips_breaker() {
+        // 端口 1 不会有服务监听，连接立即失败
+        let mut client = ServiceClient::new("test-service", "http://127.0.0.1:1/");
+        client.set_circuit_breaker(
+            CircuitBreakerConfig::new("test-service")
+                .with_failure_threshold(2)
+                .with_recovery_timeout(Duration::from_secs(60)),
+        );
+
+        for _ in 0..2 {
+            let err = client
+                .post::<_, serde_json::Value>("/ping", &serde_json::json!({}))
+                .await
+                .unwrap_err();
+            assert!(err.retryable);
+            assert!(err.message.contains("请求失败"));
+        }
+
+        let err = client
+            .post::<_, serde_json::Value>("/ping", &serde_json::json!({}))
+            .await
+            .unwrap_err();
+        assert!(err.retryable);
+        assert!(err.message.contains("熔断"));
+    }
+}
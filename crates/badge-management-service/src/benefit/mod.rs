@@ -47,6 +47,9 @@ pub mod handlers;
 pub mod registry;
 pub mod service;
 
+#[cfg(test)]
+pub(crate) mod test_support;
+
 // Re-export commonly used types from dto
 pub use dto::{BenefitGrantRequest, BenefitGrantResult, BenefitRevokeResult};
 
@@ -54,7 +57,10 @@ pub use dto::{BenefitGrantRequest, BenefitGrantResult, BenefitRevokeResult};
 pub use handler::BenefitHandler;
 
 // Re-export handlers for convenience
-pub use handlers::{CouponHandler, PhysicalHandler, PointsHandler};
+pub use handlers::{
+    CouponHandler, DigitalAssetHandler, ExternalCallbackHandler, MembershipHandler,
+    PhysicalHandler, PointsHandler, ReservationHandler,
+};
 
 // Re-export registry types
 pub use registry::{HandlerRegistry, RegistryConfig};
@@ -26,10 +26,14 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use sqlx::PgPool;
 use tracing::{debug, info};
 
 use crate::benefit::handler::BenefitHandler;
-use crate::benefit::handlers::{CouponHandler, PhysicalHandler, PointsHandler};
+use crate::benefit::handlers::{
+    CouponHandler, DigitalAssetHandler, ExternalCallbackHandler, MembershipHandler,
+    PhysicalHandler, PointsHandler, ReservationHandler,
+};
 use crate::models::BenefitType;
 
 /// Handler 注册表
@@ -96,6 +100,10 @@ impl HandlerRegistry {
     /// - CouponHandler: 优惠券发放
     /// - PointsHandler: 积分发放
     /// - PhysicalHandler: 实物发放
+    /// - DigitalAssetHandler: 数字资产发放
+    /// - ReservationHandler: 预约资格发放
+    /// - MembershipHandler: 会员权益发放
+    /// - ExternalCallbackHandler: 外部回调（未配置签名密钥）
     pub fn with_defaults() -> Self {
         let mut registry = Self::new();
 
@@ -110,6 +118,14 @@ impl HandlerRegistry {
         // 注册实物处理器
         registry.register(Arc::new(PhysicalHandler::default()));
 
+        // 注册数字资产、预约资格、会员权益处理器
+        registry.register(Arc::new(DigitalAssetHandler::default()));
+        registry.register(Arc::new(ReservationHandler::default()));
+        registry.register(Arc::new(MembershipHandler::default()));
+
+        // 注册外部回调处理器
+        registry.register(Arc::new(ExternalCallbackHandler::default()));
+
         info!(
             handler_count = registry.len(),
             types = ?registry.registered_types(),
@@ -139,6 +155,24 @@ impl HandlerRegistry {
             &config.physical_shipment_topic,
         )));
 
+        // 注册数字资产、预约资格、会员权益处理器
+        registry.register(Arc::new(DigitalAssetHandler::new(
+            &config.digital_asset_service_url,
+        )));
+        registry.register(Arc::new(ReservationHandler::new(
+            &config.reservation_service_url,
+        )));
+        registry.register(Arc::new(MembershipHandler::new(
+            &config.membership_service_url,
+        )));
+
+        // 注册外部回调处理器，配置了数据库时可从 benefit_grants 恢复回调目标
+        let mut callback_handler = ExternalCallbackHandler::new(&config.callback_signing_secret);
+        if let Some(pool) = config.pool {
+            callback_handler = callback_handler.with_pool(pool);
+        }
+        registry.register(Arc::new(callback_handler));
+
         info!(handler_count = registry.len(), "自定义权益处理器初始化完成");
 
         registry
@@ -164,6 +198,16 @@ pub struct RegistryConfig {
     pub kafka_brokers: String,
     /// 实物发货消息 topic
     pub physical_shipment_topic: String,
+    /// 数字资产服务 URL
+    pub digital_asset_service_url: String,
+    /// 预约服务 URL
+    pub reservation_service_url: String,
+    /// 会员服务 URL
+    pub membership_service_url: String,
+    /// 外部回调签名密钥
+    pub callback_signing_secret: String,
+    /// 数据库连接池（可选，外部回调据此恢复其他实例或重启前发放的回调目标）
+    pub pool: Option<PgPool>,
 }
 
 impl Default for RegistryConfig {
@@ -173,6 +217,11 @@ impl Default for RegistryConfig {
             points_service_url: "http://points-service:8080".to_string(),
             kafka_brokers: "localhost:9092".to_string(),
             physical_shipment_topic: "physical_shipment".to_string(),
+            digital_asset_service_url: "http://asset-service:8080".to_string(),
+            reservation_service_url: "http://reservation-service:8080".to_string(),
+            membership_service_url: "http://membership-service:8080".to_string(),
+            callback_signing_secret: String::new(),
+            pool: None,
         }
     }
 }
@@ -181,6 +230,7 @@ impl Default for RegistryConfig {
 mod tests {
     use super::*;
     use crate::benefit::dto::{BenefitGrantRequest, BenefitGrantResult};
+    use crate::benefit::test_support::ExternalStubs;
     use crate::models::GrantStatus;
     use async_trait::async_trait;
     use serde_json::{Value, json};
@@ -293,8 +343,8 @@ mod tests {
     fn test_registry_with_defaults() {
         let registry = HandlerRegistry::with_defaults();
 
-        // 应该包含 3 个默认 Handler
-        assert_eq!(registry.len(), 3);
+        // 应该包含全部 7 种权益类型的 Handler
+        assert_eq!(registry.len(), 7);
         assert!(registry.contains(BenefitType::Coupon));
         assert!(registry.contains(BenefitType::Points));
         assert!(registry.contains(BenefitType::Physical));
@@ -312,12 +362,24 @@ mod tests {
             registry.get(BenefitType::Physical).unwrap().benefit_type(),
             BenefitType::Physical
         );
+
+        for benefit_type in [
+            BenefitType::DigitalAsset,
+            BenefitType::Reservation,
+            BenefitType::Membership,
+            BenefitType::ExternalCallback,
+        ] {
+            assert_eq!(
+                registry.get(benefit_type).unwrap().benefit_type(),
+                benefit_type
+            );
+        }
     }
 
     #[test]
     fn test_registry_default() {
         let registry = HandlerRegistry::default();
-        assert_eq!(registry.len(), 3);
+        assert_eq!(registry.len(), 7);
     }
 
     #[test]
@@ -327,10 +389,12 @@ mod tests {
             points_service_url: "http://custom-points:8080".to_string(),
             kafka_brokers: "kafka:9092".to_string(),
             physical_shipment_topic: "custom_shipment".to_string(),
+            callback_signing_secret: "custom-secret".to_string(),
+            ..Default::default()
         };
 
         let registry = HandlerRegistry::with_config(config);
-        assert_eq!(registry.len(), 3);
+        assert_eq!(registry.len(), 7);
     }
 
     #[test]
@@ -344,7 +408,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_registry_handler_grant() {
-        let registry = HandlerRegistry::with_defaults();
+        let stubs = ExternalStubs::start().await;
+        let registry = stubs.registry();
 
         let handler = registry.get(BenefitType::Coupon).unwrap();
         let request = BenefitGrantRequest::new(
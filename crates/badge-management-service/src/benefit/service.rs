@@ -141,6 +141,9 @@ pub struct GrantBenefitResponse {
     /// 错误消息（发放失败时）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error_message: Option<String>,
+    /// 失败是否可重试（瞬时故障时为 true，可用同一流水号再次发放）
+    #[serde(default)]
+    pub retryable: bool,
     /// 处理耗时（毫秒）
     pub duration_ms: u64,
 }
@@ -161,6 +164,7 @@ impl GrantBenefitResponse {
             expires_at: result.expires_at,
             payload: result.payload,
             error_message: result.message,
+            retryable: result.retryable,
             duration_ms,
         }
     }
@@ -194,6 +198,9 @@ pub struct RevokeResult {
     /// 结果消息
     #[serde(skip_serializing_if = "Option::is_none")]
     pub message: Option<String>,
+    /// 失败是否由瞬时故障引起，为 true 时外部系统可能已经撤销，调用方应稍后重试
+    #[serde(default)]
+    pub retryable: bool,
 }
 
 impl RevokeResult {
@@ -205,6 +212,7 @@ impl RevokeResult {
             reason,
             revoked_at: Some(Utc::now()),
             message: None,
+            retryable: false,
         }
     }
 
@@ -220,8 +228,14 @@ impl RevokeResult {
             reason,
             revoked_at: None,
             message: Some(message.into()),
+            retryable: false,
         }
     }
+
+    fn with_retryable(mut self, retryable: bool) -> Self {
+        self.retryable = retryable;
+        self
+    }
 }
 
 /// 内存中的发放记录（用于本地缓存）
@@ -368,6 +382,23 @@ impl BenefitService {
 
         let duration_ms = start.elapsed().as_millis() as u64;
 
+        // 瞬时故障不占用流水号：释放幂等占位，调用方可用同一流水号重试，
+        // 外部服务同样按流水号去重，不会重复发放
+        if result.status == GrantStatus::Failed && result.retryable {
+            self.release_idempotency(&grant_no).await;
+            warn!(
+                grant_no = %grant_no,
+                error = ?result.message,
+                duration_ms = duration_ms,
+                "权益发放遇到瞬时故障，可稍后重试"
+            );
+            return Ok(GrantBenefitResponse::from_result(
+                result,
+                request.benefit_type,
+                duration_ms,
+            ));
+        }
+
         // 记录发放结果到内存缓存
         let now = Utc::now();
         {
@@ -410,6 +441,7 @@ impl BenefitService {
                     response.status,
                     response.external_ref.as_deref(),
                     response.payload.as_ref(),
+                    &request.benefit_config,
                 )
                 .await
         {
@@ -448,13 +480,8 @@ impl BenefitService {
     pub async fn revoke_grant(&self, grant_no: &str, reason: RevokeReason) -> Result<RevokeResult> {
         info!("开始撤销权益");
 
-        // 查找发放记录
-        let record = {
-            let grants = self.grants.read().await;
-            grants.get(grant_no).cloned()
-        };
-
-        let record = match record {
+        // 查找发放记录（本实例未缓存时从 benefit_grants 加载）
+        let record = match self.load_grant_record(grant_no).await? {
             Some(r) => r,
             None => {
                 warn!("发放记录不存在");
@@ -497,7 +524,13 @@ impl BenefitService {
 
         match revoke_result {
             Ok(result) if result.success => {
-                // 更新记录状态
+                // 外部系统已撤销但持久化失败时向上返回错误，记录保持 success，调用方可安全重试
+                if let Err(e) = self.persist_revoke_to_db(grant_no, reason).await {
+                    error!(error = %e, "持久化权益撤销失败");
+                    return Err(e);
+                }
+
+                // 持久化成功后再更新内存记录，避免与数据库状态不一致
                 {
                     let mut grants = self.grants.write().await;
                     if let Some(r) = grants.get_mut(grant_no) {
@@ -510,16 +543,18 @@ impl BenefitService {
                 Ok(RevokeResult::success(grant_no, reason))
             }
             Ok(result) => {
-                warn!(message = ?result.message, "权益撤销失败");
+                warn!(message = ?result.message, retryable = result.retryable, "权益撤销失败");
                 Ok(RevokeResult::failed(
                     grant_no,
                     reason,
                     result.message.unwrap_or_else(|| "撤销失败".to_string()),
-                ))
+                )
+                .with_retryable(result.retryable))
             }
+            // Handler 异常（如读取发放配置失败）无法确认外部系统状态，按可重试处理
             Err(e) => {
                 error!(error = %e, "权益撤销异常");
-                Ok(RevokeResult::failed(grant_no, reason, e.to_string()))
+                Ok(RevokeResult::failed(grant_no, reason, e.to_string()).with_retryable(true))
             }
         }
     }
@@ -529,13 +564,19 @@ impl BenefitService {
     /// 先查询本地记录，如果是处理中状态则调用 Handler 获取最新状态
     #[instrument(skip(self))]
     pub async fn query_grant_status(&self, grant_no: &str) -> Result<GrantStatus> {
+        self.find_grant_status(grant_no).await?.ok_or_else(|| {
+            warn!("发放记录不存在");
+            BadgeError::Internal(format!("发放记录不存在: {}", grant_no))
+        })
+    }
+
+    /// 查询发放状态，记录不存在时返回 None
+    ///
+    /// 本实例未缓存的记录从 benefit_grants 加载，供跨实例的撤销、取消流程判断发放结果
+    pub async fn find_grant_status(&self, grant_no: &str) -> Result<Option<GrantStatus>> {
         debug!("查询发放状态");
 
-        // 查找本地记录
-        let record = {
-            let grants = self.grants.read().await;
-            grants.get(grant_no).cloned()
-        };
+        let record = self.load_grant_record(grant_no).await?;
 
         match record {
             Some(r) => {
@@ -554,18 +595,12 @@ impl BenefitService {
                         }
                     }
 
-                    return Ok(latest_status);
+                    return Ok(Some(latest_status));
                 }
 
-                Ok(r.status)
-            }
-            None => {
-                warn!("发放记录不存在");
-                Err(BadgeError::Internal(format!(
-                    "发放记录不存在: {}",
-                    grant_no
-                )))
+                Ok(Some(r.status))
             }
+            None => Ok(None),
         }
     }
 
@@ -735,6 +770,7 @@ impl BenefitService {
                                 expires_at: None,
                                 payload: None,
                                 error_message: Some("重复的发放请求".to_string()),
+                                retryable: false,
                                 duration_ms: start.elapsed().as_millis() as u64,
                             });
                         }
@@ -767,6 +803,7 @@ impl BenefitService {
                 expires_at: None,
                 payload: None,
                 error_message: Some("重复的发放请求".to_string()),
+                retryable: false,
                 duration_ms: start.elapsed().as_millis() as u64,
             });
         }
@@ -802,10 +839,25 @@ impl BenefitService {
         }
     }
 
+    /// 释放幂等占位
+    ///
+    /// 删除失败只记录警告，占位记录会在 TTL 到期后自动释放。
+    async fn release_idempotency(&self, grant_no: &str) {
+        if let Some(ref cache) = self.cache {
+            let idempotent_key = format!("benefit:idempotent:{}", grant_no);
+            if let Err(e) = cache.delete(&idempotent_key).await {
+                warn!(grant_no = %grant_no, error = %e, "释放 Redis 幂等占位失败");
+            }
+        }
+    }
+
     /// 持久化权益发放记录到数据库
     ///
     /// 使用事务确保发放记录插入和库存扣减的原子性。
     /// 当发放成功时，同时扣减 benefits 表中的 remaining_stock。
+    ///
+    /// 发放时使用的权益配置写入 `benefit_config`，Handler 后续查询和撤销时
+    /// 可据此恢复外部系统地址等上下文（如外部回调的 callback_url）。
     #[allow(clippy::too_many_arguments)]
     async fn persist_grant_to_db(
         &self,
@@ -815,7 +867,8 @@ impl BenefitService {
         _benefit_type: BenefitType,
         status: GrantStatus,
         external_ref: Option<&str>,
-        payload: Option<&Value>,
+        external_response: Option<&Value>,
+        benefit_config: &Value,
     ) -> Result<Option<i64>> {
         let Some(ref pool) = self.pool else {
             // 没有配置数据库池，跳过持久化
@@ -837,22 +890,25 @@ impl BenefitService {
         let id: (i64,) = sqlx::query_as(
             r#"
             INSERT INTO benefit_grants (
-                grant_no, user_id, benefit_id, status, external_ref, external_response, granted_at
+                grant_no, user_id, benefit_id, status, external_ref, external_response,
+                benefit_config, granted_at
             )
-            VALUES ($1, $2, $3, $4, $5, $6, NOW())
+            VALUES ($1, $2, $3, $4, $5, $6, $7, NOW())
             ON CONFLICT (grant_no) DO UPDATE SET
                 status = EXCLUDED.status,
                 external_ref = EXCLUDED.external_ref,
                 updated_at = NOW()
             RETURNING id
             "#,
+                benefit_config = EXCLUDED.benefit_config,
         )
         .bind(grant_no)
         .bind(user_id)
         .bind(benefit_id)
         .bind(status_str)
         .bind(external_ref)
-        .bind(payload)
+        .bind(external_response)
+        .bind(benefit_config)
         .fetch_one(&mut *tx)
         .await?;
 
@@ -909,15 +965,131 @@ impl BenefitService {
             _ => None,
         })
     }
+
+    /// 查找发放记录
+    ///
+    /// 优先使用内存记录；未命中且配置了数据库池时从 benefit_grants 加载并回填内存，
+    /// 使其他实例发放、或服务重启前发放的记录同样可以查询和撤销
+    async fn load_grant_record(&self, grant_no: &str) -> Result<Option<GrantRecord>> {
+        {
+            let grants = self.grants.read().await;
+            if let Some(record) = grants.get(grant_no) {
+                return Ok(Some(record.clone()));
+            }
+        }
+
+        let Some(ref pool) = self.pool else {
+            return Ok(None);
+        };
+
+        let row: Option<GrantRecordRow> = sqlx::query_as(
+            r#"
+            SELECT g.grant_no, g.user_id, g.benefit_id, b.benefit_type, g.status,
+                   g.external_ref, g.created_at, g.updated_at
+            FROM benefit_grants g
+            JOIN benefits b ON b.id = g.benefit_id
+            WHERE g.grant_no = $1
+            "#,
+        )
+        .bind(grant_no)
+        .fetch_optional(pool)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let record = GrantRecord {
+            grant_no: row.grant_no,
+            user_id: row.user_id,
+            benefit_type: serde_json::from_value(Value::String(row.benefit_type.to_uppercase()))?,
+            benefit_id: row.benefit_id,
+            status: serde_json::from_value(Value::String(row.status.to_uppercase()))?,
+            external_ref: row.external_ref,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        };
+
+        let mut grants = self.grants.write().await;
+        Ok(Some(
+            grants.entry(grant_no.to_string()).or_insert(record).clone(),
+        ))
+    }
+
+    /// 持久化权益撤销
+    ///
+    /// 在同一事务中将发放记录置为 revoked，并归还发放成功时扣减的库存。
+    /// 仅处理 success 状态的记录，重复调用不会重复归还库存；
+    /// 归还后的库存不超过 total_stock，不限量（remaining_stock 为空）的权益不做调整。
+    async fn persist_revoke_to_db(&self, grant_no: &str, reason: RevokeReason) -> Result<()> {
+        let Some(ref pool) = self.pool else {
+            return Ok(());
+        };
+
+        let mut tx = pool.begin().await?;
+
+        let benefit_id: Option<(i64,)> = sqlx::query_as(
+            r#"
+            UPDATE benefit_grants
+            SET status = 'revoked',
+                status_message = $2,
+                revoked_at = NOW(),
+                updated_at = NOW()
+            WHERE grant_no = $1 AND status = 'success'
+            RETURNING benefit_id
+            "#,
+        )
+        .bind(grant_no)
+        .bind(format!("撤销原因: {:?}", reason))
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if let Some((benefit_id,)) = benefit_id {
+            sqlx::query(
+                r#"
+                UPDATE benefits
+                SET remaining_stock = CASE
+                        WHEN total_stock IS NULL THEN remaining_stock + 1
+                        ELSE LEAST(remaining_stock + 1, total_stock)
+                    END,
+                    updated_at = NOW()
+                WHERE id = $1 AND remaining_stock IS NOT NULL
+                "#,
+            )
+            .bind(benefit_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        debug!(grant_no = %grant_no, "权益撤销已持久化");
+        Ok(())
+    }
+}
+
+/// benefit_grants 查询行
+#[derive(sqlx::FromRow)]
+struct GrantRecordRow {
+    grant_no: String,
+    user_id: String,
+    benefit_id: i64,
+    benefit_type: String,
+    status: String,
+    external_ref: Option<String>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::benefit::test_support::ExternalStubs;
     use serde_json::json;
 
-    fn create_service() -> BenefitService {
-        BenefitService::with_defaults()
+    /// 优惠券和积分指向 mock 服务桩的 BenefitService
+    async fn create_service() -> BenefitService {
+        ExternalStubs::start().await.service()
     }
 
     #[test]
@@ -963,7 +1135,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_grant_benefit_coupon() {
-        let service = create_service();
+        let service = create_service().await;
 
         let request = GrantBenefitRequest::new(
             "user-123",
@@ -982,7 +1154,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_grant_benefit_points() {
-        let service = create_service();
+        let service = create_service().await;
 
         let request = GrantBenefitRequest::new(
             "user-123",
@@ -999,7 +1171,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_grant_benefit_physical() {
-        let service = create_service();
+        let service = create_service().await;
 
         let request = GrantBenefitRequest::new(
             "user-123",
@@ -1027,7 +1199,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_grant_benefit_idempotent() {
-        let service = create_service();
+        let service = create_service().await;
 
         let grant_no = "idempotent-test-001";
         let request1 = GrantBenefitRequest::new(
@@ -1058,9 +1230,34 @@ mod tests {
         assert!(response2.error_message.unwrap().contains("重复"));
     }
 
+    #[tokio::test]
+    async fn test_grant_benefit_retryable_failure_releases_grant_no() {
+        let stubs = ExternalStubs::start().await;
+        let service = stubs.service();
+        let request = GrantBenefitRequest::new(
+            "user-123",
+            BenefitType::Points,
+            1,
+            json!({"point_amount": 100}),
+        )
+        .with_grant_no("retry-test-001");
+
+        // 积分服务暂不可用：发放失败但标记可重试
+        stubs.benefit_state.set_simulate_failure(true).await;
+        let response = service.grant_benefit(request.clone()).await.unwrap();
+        assert_eq!(response.status, GrantStatus::Failed);
+        assert!(response.retryable);
+
+        // 服务恢复后使用同一流水号重试，不会被幂等检查拦截
+        stubs.benefit_state.set_simulate_failure(false).await;
+        let response = service.grant_benefit(request).await.unwrap();
+        assert!(response.is_success());
+        assert!(!response.retryable);
+    }
+
     #[tokio::test]
     async fn test_grant_benefit_invalid_config() {
-        let service = create_service();
+        let service = create_service().await;
 
         let request = GrantBenefitRequest::new(
             "user-123",
@@ -1078,7 +1275,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_query_grant_status() {
-        let service = create_service();
+        let service = create_service().await;
 
         // 先发放
         let request = GrantBenefitRequest::new(
@@ -1098,7 +1295,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_query_grant_status_not_found() {
-        let service = create_service();
+        let service = create_service().await;
 
         let result = service.query_grant_status("non-existent").await;
         assert!(result.is_err());
@@ -1106,7 +1303,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_revoke_grant_coupon() {
-        let service = create_service();
+        let service = create_service().await;
 
         // 先发放
         let grant_no = "revoke-test-001";
@@ -1135,9 +1332,38 @@ mod tests {
         assert_eq!(status, GrantStatus::Revoked);
     }
 
+    #[tokio::test]
+    async fn test_revoke_grant_persist_failure_propagates() {
+        // 指向不可达数据库，持久化必然失败
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .acquire_timeout(std::time::Duration::from_millis(200))
+            .connect_lazy("postgres://localhost:1/test")
+            .unwrap();
+        let service = create_service().await.with_pool(pool);
+
+        let grant_no = "revoke-persist-fail";
+        let request = GrantBenefitRequest::new(
+            "user-123",
+            BenefitType::Coupon,
+            1,
+            json!({"coupon_template_id": "tpl-001"}),
+        )
+        .with_grant_no(grant_no);
+        service.grant_benefit(request).await.unwrap();
+
+        let result = service
+            .revoke_grant(grant_no, RevokeReason::OrderRefund)
+            .await;
+        assert!(result.is_err());
+
+        // 持久化失败时记录保持 success，调用方可重试
+        let status = service.query_grant_status(grant_no).await.unwrap();
+        assert_eq!(status, GrantStatus::Success);
+    }
+
     #[tokio::test]
     async fn test_revoke_grant_physical_not_supported() {
-        let service = create_service();
+        let service = create_service().await;
 
         // 先发放实物
         let grant_no = "revoke-physical-test";
@@ -1173,7 +1399,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_revoke_grant_not_found() {
-        let service = create_service();
+        let service = create_service().await;
 
         let result = service
             .revoke_grant("non-existent", RevokeReason::UserRequest)
@@ -1186,7 +1412,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_revoke_grant_already_revoked() {
-        let service = create_service();
+        let service = create_service().await;
 
         // 先发放
         let grant_no = "double-revoke-test";
@@ -1218,7 +1444,7 @@ mod tests {
 
     #[test]
     fn test_validate_config() {
-        let service = create_service();
+        let service = BenefitService::with_defaults();
 
         // 有效的优惠券配置
         let valid_config = json!({"coupon_template_id": "tpl-001"});
@@ -1239,19 +1465,20 @@ mod tests {
 
     #[test]
     fn test_supported_types() {
-        let service = create_service();
+        let service = BenefitService::with_defaults();
 
         let types = service.supported_types();
-        assert_eq!(types.len(), 3);
+        assert_eq!(types.len(), 7);
         assert!(service.supports(BenefitType::Coupon));
         assert!(service.supports(BenefitType::Points));
         assert!(service.supports(BenefitType::Physical));
-        assert!(!service.supports(BenefitType::DigitalAsset));
+        assert!(service.supports(BenefitType::DigitalAsset));
+        assert!(service.supports(BenefitType::ExternalCallback));
     }
 
     #[tokio::test]
     async fn test_batch_query_statuses() {
-        let service = create_service();
+        let service = create_service().await;
 
         // 发放多个
         for i in 1..=3 {
@@ -0,0 +1,65 @@
+//! 测试辅助
+//!
+//! 在随机端口启动 mock-services 中的优惠券服务、权益服务和履约服务，
+//! 供对接外部系统的权益 Handler 和 BenefitService 的单元测试调用。
+
+use std::sync::Arc;
+
+use mock_services::services::{
+    BenefitServiceState, CouponServiceState, FulfillmentServiceState, benefit_routes,
+    coupon_routes, fulfillment_routes,
+};
+
+use super::registry::{HandlerRegistry, RegistryConfig};
+use super::service::BenefitService;
+
+/// 运行中的外部服务桩
+pub(crate) struct ExternalStubs {
+    pub base_url: String,
+    pub coupon_state: Arc<CouponServiceState>,
+    pub benefit_state: Arc<BenefitServiceState>,
+    pub fulfillment_state: Arc<FulfillmentServiceState>,
+}
+
+impl ExternalStubs {
+    /// 启动服务桩，所有外部服务共用同一个地址
+    pub async fn start() -> Self {
+        let coupon_state = Arc::new(CouponServiceState::default());
+        let benefit_state = Arc::new(BenefitServiceState::new());
+        let fulfillment_state = Arc::new(FulfillmentServiceState::new());
+        let app = coupon_routes()
+            .with_state(coupon_state.clone())
+            .merge(benefit_routes().with_state(benefit_state.clone()))
+            .merge(fulfillment_routes().with_state(fulfillment_state.clone()));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        Self {
+            base_url: format!("http://{}", addr),
+            coupon_state,
+            benefit_state,
+            fulfillment_state,
+        }
+    }
+
+    /// 指向服务桩的默认注册表
+    pub fn registry(&self) -> HandlerRegistry {
+        HandlerRegistry::with_config(RegistryConfig {
+            coupon_service_url: self.base_url.clone(),
+            points_service_url: self.base_url.clone(),
+            digital_asset_service_url: self.base_url.clone(),
+            reservation_service_url: self.base_url.clone(),
+            membership_service_url: self.base_url.clone(),
+            ..Default::default()
+        })
+    }
+
+    /// 指向服务桩的 BenefitService
+    pub fn service(&self) -> BenefitService {
+        BenefitService::new(Arc::new(self.registry()))
+    }
+}
@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use super::dto::BadgeDependency;
+use super::dto::{BadgeDependency, DependencyType};
 use crate::repository::BadgeDependencyRow;
 
 /// 依赖图
@@ -12,6 +12,8 @@ pub struct DependencyGraph {
     triggered_by: HashMap<i64, Vec<BadgeDependency>>,
     /// badge_id -> 此徽章的前置条件
     prerequisites: HashMap<i64, Vec<BadgeDependency>>,
+    /// badge_id -> 以此徽章为前置条件的依赖（反向级联和恢复暂停徽章用，不区分 auto_trigger）
+    dependents: HashMap<i64, Vec<BadgeDependency>>,
     /// exclusive_group_id -> 组内徽章列表
     exclusive_groups: HashMap<String, Vec<i64>>,
 }
@@ -33,6 +35,15 @@ impl DependencyGraph {
                     .push(dependency.clone());
             }
 
+            // 构建 dependents 映射（仅前置条件类型）
+            if dependency.dependency_type == DependencyType::Prerequisite {
+                graph
+                    .dependents
+                    .entry(dependency.depends_on_badge_id)
+                    .or_default()
+                    .push(dependency.clone());
+            }
+
             // 构建 prerequisites 映射
             graph
                 .prerequisites
@@ -68,6 +79,14 @@ impl DependencyGraph {
             .unwrap_or(&[])
     }
 
+    /// 获取以某徽章为前置条件的所有依赖
+    pub fn get_dependents(&self, badge_id: i64) -> &[BadgeDependency] {
+        self.dependents
+            .get(&badge_id)
+            .map(|v| v.as_slice())
+            .unwrap_or(&[])
+    }
+
     /// 获取某徽章的所有前置条件
     pub fn get_prerequisites(&self, badge_id: i64) -> &[BadgeDependency] {
         self.prerequisites
@@ -106,6 +125,7 @@ impl BadgeDependency {
             auto_trigger: row.auto_trigger,
             priority: row.priority,
             dependency_group_id: row.dependency_group_id,
+            revoke_policy: row.revoke_policy.parse().unwrap_or_default(),
         }
     }
 }
@@ -113,6 +133,7 @@ impl BadgeDependency {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cascade::RevokePolicy;
     use chrono::Utc;
     use std::sync::atomic::{AtomicI64, Ordering};
 
@@ -140,6 +161,7 @@ mod tests {
             auto_trigger,
             priority: 0,
             dependency_group_id: group_id.to_string(),
+            revoke_policy: "keep".to_string(),
             enabled: true,
             created_at: Utc::now(),
             updated_at: Utc::now(),
@@ -254,4 +276,27 @@ mod tests {
         let prereqs = graph.get_prerequisites(badge_b);
         assert_eq!(prereqs.len(), 1);
     }
+
+    #[test]
+    fn test_dependents_mapping() {
+        let badge_a = next_test_id();
+        let badge_b = next_test_id();
+        let badge_c = next_test_id();
+
+        let mut revoke_row =
+            create_test_row(badge_b, badge_a, "prerequisite", false, "default", None);
+        revoke_row.revoke_policy = "revoke".to_string();
+        let rows = vec![
+            revoke_row,
+            create_test_row(badge_c, badge_a, "consume", false, "default", None),
+        ];
+
+        let graph = DependencyGraph::from_rows(rows);
+
+        // 只有前置条件类型进入反向映射，且不要求 auto_trigger
+        let dependents = graph.get_dependents(badge_a);
+        assert_eq!(dependents.len(), 1);
+        assert_eq!(dependents[0].badge_id, badge_b);
+        assert_eq!(dependents[0].revoke_policy, RevokePolicy::Revoke);
+    }
 }
@@ -32,6 +32,45 @@ impl DependencyType {
     }
 }
 
+/// 前置徽章失效时的处理策略
+///
+/// 仅对前置条件类型的依赖生效。同一目标徽章存在多条依赖时取最严格的策略
+/// （`Revoke` > `Suspend` > `Keep`），变体顺序即严格程度。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RevokePolicy {
+    /// 保留 - 不处理已获得的目标徽章
+    #[default]
+    Keep,
+    /// 暂停 - 目标徽章暂停生效，重新满足前置条件后自动恢复
+    Suspend,
+    /// 撤销 - 目标徽章随前置徽章一并撤销
+    Revoke,
+}
+
+impl std::str::FromStr for RevokePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "keep" => Ok(Self::Keep),
+            "suspend" => Ok(Self::Suspend),
+            "revoke" => Ok(Self::Revoke),
+            _ => Err(format!("unknown revoke policy: {}", s)),
+        }
+    }
+}
+
+impl RevokePolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Keep => "keep",
+            Self::Suspend => "suspend",
+            Self::Revoke => "revoke",
+        }
+    }
+}
+
 /// 徽章依赖关系
 #[derive(Debug, Clone)]
 pub struct BadgeDependency {
@@ -52,6 +91,8 @@ pub struct BadgeDependency {
     pub priority: i32,
     /// 依赖组ID（同组 AND，不同组 OR）
     pub dependency_group_id: String,
+    /// 前置徽章失效时的处理策略
+    pub revoke_policy: RevokePolicy,
 }
 
 /// 级联配置
@@ -135,6 +176,10 @@ pub struct CascadeResult {
     pub granted_badges: Vec<GrantedBadge>,
     /// 被阻止的徽章
     pub blocked_badges: Vec<BlockedBadge>,
+    /// 反向级联中被撤销或暂停的徽章
+    pub revoked_badges: Vec<RevokedDependent>,
+    /// 重新满足前置条件而恢复的暂停徽章
+    pub restored_badges: Vec<RestoredBadge>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -144,6 +189,26 @@ pub struct GrantedBadge {
     pub triggered_by: i64,
 }
 
+/// 反向级联中受影响的目标徽章
+#[derive(Debug, Clone, Serialize)]
+pub struct RevokedDependent {
+    pub badge_id: i64,
+    /// 失效的前置徽章
+    pub triggered_by: i64,
+    /// 实际执行的策略（revoke / suspend）
+    pub policy: RevokePolicy,
+    /// 受影响的数量（撤销时为扣减数量，暂停时为冻结数量）
+    pub quantity: i32,
+}
+
+/// 重新满足前置条件后恢复的暂停徽章
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoredBadge {
+    pub badge_id: i64,
+    pub triggered_by: i64,
+    pub quantity: i32,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct BlockedBadge {
     pub badge_id: i64,
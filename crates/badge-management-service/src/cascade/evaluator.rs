@@ -11,24 +11,36 @@
 //! - 循环依赖检测
 //! - 深度和超时限制
 //! - 递归级联触发
+//! - 反向级联：前置徽章撤销或过期后，按依赖策略撤销/暂停目标徽章
+//! - 重新满足前置条件后恢复暂停的徽章
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use std::time::Instant;
 
 use async_trait::async_trait;
 use chrono::Utc;
+use sqlx::PgConnection;
 use tokio::sync::RwLock;
 use tracing::{debug, info, warn};
 
 use super::DependencyGraph;
 use super::dto::{
     BadgeDependency, BlockReason, BlockedBadge, CascadeConfig, CascadeContext, CascadeResult,
-    DependencyType, GrantedBadge,
+    DependencyType, GrantedBadge, RestoredBadge, RevokePolicy, RevokedDependent,
 };
 use crate::error::{BadgeError, Result};
-use crate::models::UserBadgeStatus;
-use crate::repository::{CascadeEvaluationLog, DependencyRepository, UserBadgeRepository};
+use crate::models::{
+    BadgeLedger, ChangeType, LogAction, RecipientType, SourceType, UserBadgeStatus,
+};
+use crate::repository::{
+    BadgeLedgerRepository, CascadeEvaluationLog, DependencyRepository, PendingRevokeCascade,
+    UserBadgeRepository,
+};
+
+/// 级联评估方向（写入评估日志）
+const DIRECTION_GRANT: &str = "grant";
+const DIRECTION_REVOKE: &str = "revoke";
 
 /// 徽章发放接口
 ///
@@ -125,9 +137,13 @@ impl CascadeEvaluator {
         // 获取依赖图
         let graph = self.get_or_refresh_graph().await?;
 
-        // 检查是否有候选徽章
+        // 检查是否有候选徽章，或可能因此恢复的暂停徽章
         let candidates = graph.get_triggered_by(trigger_badge_id);
-        if candidates.is_empty() {
+        let has_suspendable = graph
+            .get_dependents(trigger_badge_id)
+            .iter()
+            .any(|d| d.revoke_policy == RevokePolicy::Suspend);
+        if candidates.is_empty() && !has_suspendable {
             debug!(
                 user_id = %user_id,
                 trigger_badge_id = %trigger_badge_id,
@@ -143,21 +159,30 @@ impl CascadeEvaluator {
             "开始级联评估"
         );
 
-        // 递归评估
-        let eval_result = Box::pin(self.evaluate_recursive(
-            user_id,
-            trigger_badge_id,
-            &graph,
-            &mut context,
-            &mut result,
-        ))
-        .await;
+        // 先恢复暂停的徽章（独立上下文，避免恢复路径被正向评估误判为循环），再递归评估
+        let restore_result = if has_suspendable {
+            self.restore_suspended(user_id, trigger_badge_id, &graph, &mut result)
+                .await
+        } else {
+            Ok(())
+        };
+        let eval_result = restore_result.and(
+            Box::pin(self.evaluate_recursive(
+                user_id,
+                trigger_badge_id,
+                &graph,
+                &mut context,
+                &mut result,
+            ))
+            .await,
+        );
 
         // 记录评估日志
         let error_msg = eval_result.as_ref().err().map(|e| e.to_string());
         self.log_evaluation(
             user_id,
             trigger_badge_id,
+            DIRECTION_GRANT,
             &context,
             &result,
             error_msg.as_deref(),
@@ -201,6 +226,164 @@ impl CascadeEvaluator {
         Ok(result)
     }
 
+    /// 在撤销或过期事务内登记反向级联
+    ///
+    /// 仅当存在策略不为 keep 的依赖时写入待处理记录并返回其 ID。
+    /// 记录与撤销/过期同事务提交，保证提交后的级联即使失败或进程中断也能被重试。
+    pub async fn enqueue_revoke_in_tx(
+        &self,
+        tx: &mut PgConnection,
+        user_id: &str,
+        revoked_badge_id: i64,
+    ) -> Result<Option<i64>> {
+        let graph = self.get_or_refresh_graph().await?;
+        let affected = graph
+            .get_dependents(revoked_badge_id)
+            .iter()
+            .any(|d| d.revoke_policy != RevokePolicy::Keep);
+        if !affected {
+            debug!(
+                user_id = %user_id,
+                revoked_badge_id = %revoked_badge_id,
+                "无需反向级联处理的依赖"
+            );
+            return Ok(None);
+        }
+
+        let id = DependencyRepository::create_pending_revoke_in_tx(tx, user_id, revoked_badge_id)
+            .await?;
+        Ok(Some(id))
+    }
+
+    /// 反向级联入口：执行 `enqueue_revoke_in_tx` 登记的待处理级联
+    ///
+    /// 找出以 revoked_badge_id 为前置条件、且策略不为 keep 的目标徽章，
+    /// 若用户已不再满足其前置条件（依赖组 OR 逻辑下任一组满足即视为满足），
+    /// 按策略撤销或暂停，并以受影响的徽章为触发继续向下处理。
+    ///
+    /// 整个反向级联与删除待处理记录在单个事务内执行，任何一步失败都会整体回滚，
+    /// 待处理记录保留并按退避时间由 `retry_pending_revokes` 重试；
+    /// 深度、超时和循环检测与正向级联共用 `CascadeConfig` / `CascadeContext`。
+    /// 调用方负责据返回结果刷新缓存、排行榜并发送通知。
+    pub async fn evaluate_pending_revoke(
+        &self,
+        pending_id: i64,
+        user_id: &str,
+        revoked_badge_id: i64,
+    ) -> Result<CascadeResult> {
+        let result = self
+            .evaluate_revoke(pending_id, user_id, revoked_badge_id)
+            .await;
+        if let Err(e) = &result
+            && let Err(mark_err) = self
+                .dependency_repo
+                .mark_pending_revoke_failed(pending_id, &e.to_string())
+                .await
+        {
+            warn!(
+                pending_id,
+                error = %mark_err,
+                "记录反向级联失败信息出错"
+            );
+        }
+        result
+    }
+
+    /// 重试已到期的待处理反向级联
+    ///
+    /// 返回成功处理的记录及其结果，失败的记录已更新退避时间，只记录日志
+    pub async fn retry_pending_revokes(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<(PendingRevokeCascade, CascadeResult)>> {
+        let pending = self.dependency_repo.list_due_pending_revokes(limit).await?;
+
+        let mut completed = Vec::with_capacity(pending.len());
+        for item in pending {
+            match self
+                .evaluate_pending_revoke(item.id, &item.user_id, item.revoked_badge_id)
+                .await
+            {
+                Ok(result) => completed.push((item, result)),
+                Err(e) => warn!(
+                    pending_id = item.id,
+                    user_id = %item.user_id,
+                    revoked_badge_id = item.revoked_badge_id,
+                    attempts = item.attempts + 1,
+                    error = %e,
+                    "反向级联重试失败"
+                ),
+            }
+        }
+
+        Ok(completed)
+    }
+
+    async fn evaluate_revoke(
+        &self,
+        pending_id: i64,
+        user_id: &str,
+        revoked_badge_id: i64,
+    ) -> Result<CascadeResult> {
+        let mut context = CascadeContext::new();
+        let mut result = CascadeResult::default();
+
+        let graph = self.get_or_refresh_graph().await?;
+
+        info!(
+            user_id = %user_id,
+            revoked_badge_id = %revoked_badge_id,
+            "开始反向级联评估"
+        );
+
+        let eval_result = self
+            .execute_revoke_cascade(
+                pending_id,
+                user_id,
+                revoked_badge_id,
+                &graph,
+                &mut context,
+                &mut result,
+            )
+            .await;
+        if eval_result.is_err() {
+            // 事务已回滚，实际没有徽章受影响
+            result.revoked_badges.clear();
+        }
+
+        let error_msg = eval_result.as_ref().err().map(|e| e.to_string());
+        self.log_evaluation(
+            user_id,
+            revoked_badge_id,
+            DIRECTION_REVOKE,
+            &context,
+            &result,
+            error_msg.as_deref(),
+        )
+        .await;
+
+        badge_shared::observability::metrics::record_cascade_evaluation(
+            context.depth,
+            if eval_result.is_err() {
+                "error"
+            } else {
+                "success"
+            },
+            context.started_at.elapsed().as_secs_f64(),
+        );
+
+        eval_result?;
+
+        info!(
+            user_id = %user_id,
+            revoked_badge_id = %revoked_badge_id,
+            affected_count = result.revoked_badges.len(),
+            "反向级联评估完成"
+        );
+
+        Ok(result)
+    }
+
     /// 获取或刷新依赖图缓存
     async fn get_or_refresh_graph(&self) -> Result<DependencyGraph> {
         // 快速路径：检查缓存是否有效
@@ -226,6 +409,17 @@ impl CascadeEvaluator {
         Ok(())
     }
 
+    /// 获取徽章需要持有或消耗的依赖（互斥依赖除外），用于进度展示
+    pub async fn required_dependencies(&self, badge_id: i64) -> Result<Vec<BadgeDependency>> {
+        let graph = self.get_or_refresh_graph().await?;
+        Ok(graph
+            .get_prerequisites(badge_id)
+            .iter()
+            .filter(|d| d.dependency_type != DependencyType::Exclusive)
+            .cloned()
+            .collect())
+    }
+
     /// 内部刷新缓存方法
     async fn refresh_cache_internal(&self) -> Result<DependencyGraph> {
         let rows = self.dependency_repo.list_all_enabled().await?;
@@ -250,21 +444,8 @@ impl CascadeEvaluator {
         context: &mut CascadeContext,
         result: &mut CascadeResult,
     ) -> Result<()> {
-        // 1. 深度检查
-        if context.depth > self.config.max_depth {
-            return Err(BadgeError::CascadeDepthExceeded {
-                current: context.depth,
-                max: self.config.max_depth,
-            });
-        }
-
-        // 2. 超时检查
-        if context.elapsed_ms() > self.config.timeout_ms {
-            return Err(BadgeError::CascadeTimeout {
-                elapsed_ms: context.elapsed_ms(),
-                timeout_ms: self.config.timeout_ms,
-            });
-        }
+        // 1-2. 深度和超时检查
+        self.check_limits(context)?;
 
         // 3. 获取候选徽章
         let candidates = graph.get_triggered_by(trigger_badge_id);
@@ -405,6 +586,370 @@ impl CascadeEvaluator {
         Ok(())
     }
 
+    /// 深度和超时检查，正向、反向级联共用
+    fn check_limits(&self, context: &CascadeContext) -> Result<()> {
+        if context.depth > self.config.max_depth {
+            return Err(BadgeError::CascadeDepthExceeded {
+                current: context.depth,
+                max: self.config.max_depth,
+            });
+        }
+
+        if context.elapsed_ms() > self.config.timeout_ms {
+            return Err(BadgeError::CascadeTimeout {
+                elapsed_ms: context.elapsed_ms(),
+                timeout_ms: self.config.timeout_ms,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// 对用户加事务级咨询锁，串行化同一用户的反向级联和暂停恢复
+    async fn lock_user(tx: &mut PgConnection, user_id: &str) -> Result<()> {
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+            .bind(format!("user_badge_cascade:{}", user_id))
+            .execute(&mut *tx)
+            .await?;
+        Ok(())
+    }
+
+    /// 在事务内执行反向级联，并删除对应的待处理记录
+    async fn execute_revoke_cascade(
+        &self,
+        pending_id: i64,
+        user_id: &str,
+        revoked_badge_id: i64,
+        graph: &DependencyGraph,
+        context: &mut CascadeContext,
+        result: &mut CascadeResult,
+    ) -> Result<()> {
+        let mut tx = self.user_badge_repo.pool().begin().await?;
+        Self::lock_user(&mut tx, user_id).await?;
+
+        // 即时评估与重试 Worker 可能同时处理同一记录，在用户锁内删除以保证只执行一次
+        if !DependencyRepository::delete_pending_revoke_in_tx(&mut tx, pending_id).await? {
+            debug!(pending_id, "待处理反向级联已被处理，跳过");
+            return Ok(());
+        }
+
+        Box::pin(self.revoke_recursive(&mut tx, user_id, revoked_badge_id, graph, context, result))
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// 反向级联递归处理
+    async fn revoke_recursive(
+        &self,
+        tx: &mut PgConnection,
+        user_id: &str,
+        trigger_badge_id: i64,
+        graph: &DependencyGraph,
+        context: &mut CascadeContext,
+        result: &mut CascadeResult,
+    ) -> Result<()> {
+        self.check_limits(context)?;
+
+        // 同一目标徽章可能经多个依赖组依赖触发徽章，取最严格的策略
+        let mut targets: BTreeMap<i64, RevokePolicy> = BTreeMap::new();
+        for dep in graph.get_dependents(trigger_badge_id) {
+            let policy = targets.entry(dep.badge_id).or_default();
+            *policy = (*policy).max(dep.revoke_policy);
+        }
+
+        for (target_badge_id, policy) in targets {
+            if policy == RevokePolicy::Keep {
+                continue;
+            }
+
+            if context.has_cycle(target_badge_id) {
+                debug!(
+                    target_badge_id = %target_badge_id,
+                    path = ?context.path,
+                    "反向级联检测到循环依赖"
+                );
+                result.blocked_badges.push(BlockedBadge {
+                    badge_id: target_badge_id,
+                    badge_name: None,
+                    reason: BlockReason::CycleDetected,
+                });
+                continue;
+            }
+
+            // 用户仍满足任一依赖组时保留目标徽章
+            let quantities = UserBadgeRepository::active_quantities_in_tx(tx, user_id).await?;
+            let (satisfied, _) = self.check_prerequisites_with_groups(
+                user_id,
+                graph.get_prerequisites(target_badge_id),
+                &quantities,
+            )?;
+            if satisfied {
+                continue;
+            }
+
+            let Some(user_badge) =
+                UserBadgeRepository::get_user_badge_for_update(tx, user_id, target_badge_id)
+                    .await?
+            else {
+                continue;
+            };
+            if user_badge.status != UserBadgeStatus::Active {
+                continue;
+            }
+
+            let ref_id = trigger_badge_id.to_string();
+            match policy {
+                RevokePolicy::Revoke => {
+                    let reason = format!("前置徽章 {} 已失效，级联撤销", trigger_badge_id);
+                    UserBadgeRepository::update_user_badge_quantity_in_tx(
+                        tx,
+                        user_badge.id,
+                        -user_badge.quantity,
+                    )
+                    .await?;
+                    UserBadgeRepository::update_user_badge_status_in_tx(
+                        tx,
+                        user_badge.id,
+                        UserBadgeStatus::Revoked,
+                    )
+                    .await?;
+
+                    let ledger = BadgeLedger {
+                        id: 0,
+                        user_id: user_id.to_string(),
+                        badge_id: target_badge_id,
+                        user_badge_id: Some(user_badge.id),
+                        change_type: ChangeType::Cancel,
+                        quantity: -user_badge.quantity,
+                        balance_after: 0,
+                        ref_id: Some(ref_id.clone()),
+                        ref_type: SourceType::Cascade,
+                        remark: Some(reason.clone()),
+                        operator: None,
+                        recipient_type: RecipientType::Owner,
+                        actual_user_id: None,
+                        created_at: Utc::now(),
+                    };
+                    BadgeLedgerRepository::create_in_tx(tx, &ledger).await?;
+                    Self::write_log_in_tx(
+                        tx,
+                        user_badge.id,
+                        user_id,
+                        target_badge_id,
+                        LogAction::Revoke,
+                        &reason,
+                        user_badge.quantity,
+                        &ref_id,
+                    )
+                    .await?;
+                }
+                RevokePolicy::Suspend => {
+                    // 暂停只变更状态，数量保留以便恢复
+                    UserBadgeRepository::update_user_badge_status_in_tx(
+                        tx,
+                        user_badge.id,
+                        UserBadgeStatus::Suspended,
+                    )
+                    .await?;
+                    Self::write_log_in_tx(
+                        tx,
+                        user_badge.id,
+                        user_id,
+                        target_badge_id,
+                        LogAction::Suspend,
+                        &format!("前置徽章 {} 已失效，级联暂停", trigger_badge_id),
+                        user_badge.quantity,
+                        &ref_id,
+                    )
+                    .await?;
+                }
+                RevokePolicy::Keep => unreachable!(),
+            }
+
+            info!(
+                user_id = %user_id,
+                target_badge_id = %target_badge_id,
+                triggered_by = %trigger_badge_id,
+                policy = policy.as_str(),
+                "反向级联处理完成"
+            );
+            result.revoked_badges.push(RevokedDependent {
+                badge_id: target_badge_id,
+                triggered_by: trigger_badge_id,
+                policy,
+                quantity: user_badge.quantity,
+            });
+
+            // 目标徽章失效后，依赖它的徽章同样需要检查
+            context.enter(target_badge_id);
+            Box::pin(self.revoke_recursive(tx, user_id, target_badge_id, graph, context, result))
+                .await?;
+            context.leave();
+        }
+
+        Ok(())
+    }
+
+    /// 恢复因前置徽章失效而暂停的徽章
+    ///
+    /// 在单独事务中执行：用户重新获得前置徽章后，依赖它的暂停徽章若再次满足前置条件，
+    /// 恢复为有效状态，并继续检查依赖被恢复徽章的暂停徽章。
+    async fn restore_suspended(
+        &self,
+        user_id: &str,
+        trigger_badge_id: i64,
+        graph: &DependencyGraph,
+        result: &mut CascadeResult,
+    ) -> Result<()> {
+        let mut context = CascadeContext::new();
+        let mut restored = Vec::new();
+
+        let mut tx = self.user_badge_repo.pool().begin().await?;
+        Self::lock_user(&mut tx, user_id).await?;
+        Box::pin(self.restore_recursive(
+            &mut tx,
+            user_id,
+            trigger_badge_id,
+            graph,
+            &mut context,
+            &mut restored,
+        ))
+        .await?;
+        tx.commit().await?;
+
+        result.restored_badges.extend(restored);
+        Ok(())
+    }
+
+    async fn restore_recursive(
+        &self,
+        tx: &mut PgConnection,
+        user_id: &str,
+        trigger_badge_id: i64,
+        graph: &DependencyGraph,
+        context: &mut CascadeContext,
+        restored: &mut Vec<RestoredBadge>,
+    ) -> Result<()> {
+        self.check_limits(context)?;
+
+        let mut targets: Vec<i64> = graph
+            .get_dependents(trigger_badge_id)
+            .iter()
+            .map(|d| d.badge_id)
+            .collect();
+        targets.sort_unstable();
+        targets.dedup();
+
+        for target_badge_id in targets {
+            if context.has_cycle(target_badge_id) {
+                continue;
+            }
+
+            let Some(user_badge) =
+                UserBadgeRepository::get_user_badge_for_update(tx, user_id, target_badge_id)
+                    .await?
+            else {
+                continue;
+            };
+            if user_badge.status != UserBadgeStatus::Suspended {
+                continue;
+            }
+
+            let quantities = UserBadgeRepository::active_quantities_in_tx(tx, user_id).await?;
+            let (satisfied, _) = self.check_prerequisites_with_groups(
+                user_id,
+                graph.get_prerequisites(target_badge_id),
+                &quantities,
+            )?;
+            if !satisfied {
+                continue;
+            }
+
+            UserBadgeRepository::update_user_badge_status_in_tx(
+                tx,
+                user_badge.id,
+                UserBadgeStatus::Active,
+            )
+            .await?;
+            Self::write_log_in_tx(
+                tx,
+                user_badge.id,
+                user_id,
+                target_badge_id,
+                LogAction::Resume,
+                &format!(
+                    "重新满足前置条件（徽章 {}），恢复暂停徽章",
+                    trigger_badge_id
+                ),
+                user_badge.quantity,
+                &trigger_badge_id.to_string(),
+            )
+            .await?;
+
+            info!(
+                user_id = %user_id,
+                target_badge_id = %target_badge_id,
+                triggered_by = %trigger_badge_id,
+                "暂停徽章已恢复"
+            );
+            restored.push(RestoredBadge {
+                badge_id: target_badge_id,
+                triggered_by: trigger_badge_id,
+                quantity: user_badge.quantity,
+            });
+
+            context.enter(target_badge_id);
+            Box::pin(self.restore_recursive(
+                tx,
+                user_id,
+                target_badge_id,
+                graph,
+                context,
+                restored,
+            ))
+            .await?;
+            context.leave();
+        }
+
+        Ok(())
+    }
+
+    /// 写入用户徽章操作日志（级联来源）
+    #[allow(clippy::too_many_arguments)]
+    async fn write_log_in_tx(
+        tx: &mut PgConnection,
+        user_badge_id: i64,
+        user_id: &str,
+        badge_id: i64,
+        action: LogAction,
+        reason: &str,
+        quantity: i32,
+        source_ref_id: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO user_badge_logs
+                (user_badge_id, user_id, badge_id, action, reason, operator, quantity, source_type, source_ref_id, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, NOW())
+            "#,
+        )
+        .bind(user_badge_id)
+        .bind(user_id)
+        .bind(badge_id)
+        .bind(action)
+        .bind(reason)
+        .bind::<Option<String>>(None)
+        .bind(quantity)
+        .bind(SourceType::Cascade)
+        .bind(source_ref_id)
+        .execute(&mut *tx)
+        .await?;
+
+        Ok(())
+    }
+
     /// 检查前置条件（支持依赖组逻辑）
     ///
     /// 依赖组逻辑：
@@ -563,6 +1108,7 @@ impl CascadeEvaluator {
         &self,
         user_id: &str,
         trigger_badge_id: i64,
+        direction: &str,
         context: &CascadeContext,
         result: &CascadeResult,
         error: Option<&str>,
@@ -573,15 +1119,28 @@ impl CascadeEvaluator {
 
         let result_status = if error.is_some() {
             "error"
-        } else if result.granted_badges.is_empty() && result.blocked_badges.is_empty() {
+        } else if result.granted_badges.is_empty()
+            && result.blocked_badges.is_empty()
+            && result.revoked_badges.is_empty()
+            && result.restored_badges.is_empty()
+        {
             "no_action"
         } else {
             "completed"
         };
 
+        let affected_badges = if direction == DIRECTION_REVOKE {
+            (!result.revoked_badges.is_empty())
+                .then(|| serde_json::to_value(&result.revoked_badges).unwrap_or_default())
+        } else {
+            (!result.restored_badges.is_empty())
+                .then(|| serde_json::to_value(&result.restored_badges).unwrap_or_default())
+        };
+
         let log = CascadeEvaluationLog {
             user_id: user_id.to_string(),
             trigger_badge_id,
+            direction: direction.to_string(),
             evaluation_context: serde_json::json!({
                 "max_depth_reached": context.depth,
                 "visited_count": context.visited.len(),
@@ -598,6 +1157,7 @@ impl CascadeEvaluator {
             } else {
                 Some(serde_json::to_value(&result.blocked_badges).unwrap_or_default())
             },
+            affected_badges,
             error_message: error.map(|s| s.to_string()),
             started_at,
             completed_at,
@@ -657,6 +1217,7 @@ mod tests {
             auto_trigger,
             priority: 0,
             dependency_group_id: group_id.to_string(),
+            revoke_policy: "keep".to_string(),
             enabled: true,
             created_at: Utc::now(),
             updated_at: Utc::now(),
@@ -1335,6 +1896,67 @@ mod tests {
         assert!(json.contains("123"));
     }
 
+    #[test]
+    fn test_revoke_policy_parsing_and_ordering() {
+        assert_eq!("keep".parse::<RevokePolicy>().unwrap(), RevokePolicy::Keep);
+        assert_eq!(
+            "SUSPEND".parse::<RevokePolicy>().unwrap(),
+            RevokePolicy::Suspend
+        );
+        assert_eq!(
+            "revoke".parse::<RevokePolicy>().unwrap(),
+            RevokePolicy::Revoke
+        );
+        assert!("delete".parse::<RevokePolicy>().is_err());
+        assert_eq!(RevokePolicy::default(), RevokePolicy::Keep);
+
+        // 同一目标经多个依赖指向触发徽章时取最严格策略
+        assert_eq!(
+            RevokePolicy::Keep.max(RevokePolicy::Suspend),
+            RevokePolicy::Suspend
+        );
+        assert_eq!(
+            RevokePolicy::Suspend.max(RevokePolicy::Revoke),
+            RevokePolicy::Revoke
+        );
+    }
+
+    #[test]
+    fn test_dependents_carry_revoke_policy() {
+        let badge_a = next_test_id();
+        let badge_b = next_test_id();
+        let badge_c = next_test_id();
+
+        let mut row_b =
+            create_test_dependency_row(badge_b, badge_a, "prerequisite", false, "default", None, 1);
+        row_b.revoke_policy = "suspend".to_string();
+        let mut row_c =
+            create_test_dependency_row(badge_c, badge_a, "consume", false, "default", None, 1);
+        row_c.revoke_policy = "revoke".to_string();
+
+        let graph = DependencyGraph::from_rows(vec![row_b, row_c]);
+
+        // 反向级联只关心前置条件依赖，且不受 auto_trigger 影响
+        let dependents = graph.get_dependents(badge_a);
+        assert_eq!(dependents.len(), 1);
+        assert_eq!(dependents[0].badge_id, badge_b);
+        assert_eq!(dependents[0].revoke_policy, RevokePolicy::Suspend);
+    }
+
+    #[test]
+    fn test_revoked_dependent_serialization() {
+        let revoked = RevokedDependent {
+            badge_id: 456,
+            triggered_by: 123,
+            policy: RevokePolicy::Suspend,
+            quantity: 2,
+        };
+
+        let json = serde_json::to_value(&revoked).unwrap();
+        assert_eq!(json["policy"], "suspend");
+        assert_eq!(json["triggered_by"], 123);
+    }
+
     #[test]
     fn test_timeout_block_reason() {
         let reason = BlockReason::Timeout;
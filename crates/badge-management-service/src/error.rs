@@ -27,6 +27,9 @@ pub enum BadgeError {
     #[error("用户徽章不存在: user_id={user_id}, badge_id={badge_id}")]
     UserBadgeNotFound { user_id: String, badge_id: i64 },
 
+    #[error("用户徽章记录不存在: user_badge_id={0}")]
+    UserBadgeRecordNotFound(i64),
+
     #[error("用户徽章已过期: user_badge_id={0}")]
     UserBadgeExpired(i64),
 
@@ -36,6 +39,9 @@ pub enum BadgeError {
     #[error("用户已达到徽章获取上限: badge_id={badge_id}, limit={limit}")]
     BadgeAcquisitionLimitReached { badge_id: i64, limit: i32 },
 
+    #[error("置顶徽章数量已达上限: limit={limit}")]
+    PinLimitReached { limit: u32 },
+
     // === 兑换相关错误 ===
     #[error("兑换规则不存在: {0}")]
     RedemptionRuleNotFound(i64),
@@ -64,6 +70,21 @@ pub enum BadgeError {
     #[error("重复的兑换请求: idempotency_key={0}")]
     DuplicateRedemption(String),
 
+    #[error("兑换订单不存在: order_no={0}")]
+    RedemptionOrderNotFound(String),
+
+    #[error("已超过可取消时限: order_no={order_no}, 时限={window_minutes} 分钟")]
+    CancelWindowExpired {
+        order_no: String,
+        window_minutes: i64,
+    },
+
+    #[error("权益撤销失败: order_no={order_no}, 原因={reason}")]
+    BenefitRevokeFailed { order_no: String, reason: String },
+
+    #[error("权益撤销结果未确认，订单保持取消中，请稍后重试: order_no={order_no}, 原因={reason}")]
+    BenefitRevokePending { order_no: String, reason: String },
+
     // === 依赖关系和级联评估相关错误 ===
     #[error("前置条件不满足: badge_id={badge_id}, 缺失的前置徽章={missing:?}")]
     PrerequisiteNotMet { badge_id: i64, missing: Vec<i64> },
@@ -116,6 +137,7 @@ impl BadgeError {
                 | Self::Redis(_)
                 | Self::ConcurrencyConflict
                 | Self::LockConflict { .. }
+                | Self::BenefitRevokePending { .. }
         )
     }
 
@@ -155,9 +177,11 @@ impl BadgeError {
             Self::SeriesNotFound(_) => "SERIES_NOT_FOUND",
             Self::CategoryNotFound(_) => "CATEGORY_NOT_FOUND",
             Self::UserBadgeNotFound { .. } => "USER_BADGE_NOT_FOUND",
+            Self::UserBadgeRecordNotFound(_) => "USER_BADGE_NOT_FOUND",
             Self::UserBadgeExpired(_) => "USER_BADGE_EXPIRED",
             Self::InsufficientBadges { .. } => "INSUFFICIENT_BADGES",
             Self::BadgeAcquisitionLimitReached { .. } => "ACQUISITION_LIMIT_REACHED",
+            Self::PinLimitReached { .. } => "PIN_LIMIT_REACHED",
             Self::RedemptionRuleNotFound(_) => "REDEMPTION_RULE_NOT_FOUND",
             Self::RedemptionRuleInactive(_) => "REDEMPTION_RULE_INACTIVE",
             Self::BenefitNotFound(_) => "BENEFIT_NOT_FOUND",
@@ -166,6 +190,10 @@ impl BadgeError {
             Self::OrderNotFound(_) => "ORDER_NOT_FOUND",
             Self::InvalidOrderStatus { .. } => "INVALID_ORDER_STATUS",
             Self::DuplicateRedemption(_) => "DUPLICATE_REDEMPTION",
+            Self::RedemptionOrderNotFound(_) => "ORDER_NOT_FOUND",
+            Self::CancelWindowExpired { .. } => "CANCEL_WINDOW_EXPIRED",
+            Self::BenefitRevokeFailed { .. } => "BENEFIT_REVOKE_FAILED",
+            Self::BenefitRevokePending { .. } => "BENEFIT_REVOKE_PENDING",
             Self::PrerequisiteNotMet { .. } => "PREREQUISITE_NOT_MET",
             Self::ExclusiveConflict { .. } => "EXCLUSIVE_CONFLICT",
             Self::CascadeDepthExceeded { .. } => "CASCADE_DEPTH_EXCEEDED",
@@ -190,6 +218,20 @@ mod tests {
     fn test_error_is_retryable() {
         assert!(BadgeError::ConcurrencyConflict.is_retryable());
         assert!(BadgeError::Redis("connection failed".to_string()).is_retryable());
+        assert!(
+            BadgeError::BenefitRevokePending {
+                order_no: "RD-1".to_string(),
+                reason: "timeout".to_string()
+            }
+            .is_retryable()
+        );
+        assert!(
+            !BadgeError::BenefitRevokeFailed {
+                order_no: "RD-1".to_string(),
+                reason: "refused".to_string()
+            }
+            .is_retryable()
+        );
         assert!(!BadgeError::BadgeNotFound(1).is_retryable());
         assert!(
             !BadgeError::InsufficientBadges {
@@ -229,6 +271,14 @@ mod tests {
             BadgeError::ConcurrencyConflict.error_code(),
             "CONCURRENCY_CONFLICT"
         );
+        assert_eq!(
+            BadgeError::CancelWindowExpired {
+                order_no: "RD-1".to_string(),
+                window_minutes: 30
+            }
+            .error_code(),
+            "CANCEL_WINDOW_EXPIRED"
+        );
     }
 
     #[test]
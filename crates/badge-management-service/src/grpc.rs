@@ -9,32 +9,48 @@ use tonic::{Request, Response, Status};
 use tracing::instrument;
 
 use badge_proto::badge::{
-    Badge as ProtoBadge, BadgeStatus as ProtoBadgeStatus, BadgeType as ProtoBadgeType,
+    Badge as ProtoBadge, BadgeProgress as ProtoBadgeProgress, BatchBadgeOperationResponse, BatchGrantBadgesRequest, BatchItemResult,
+    BatchRevokeBadgesRequest, BadgeStatus as ProtoBadgeStatus, BadgeType as ProtoBadgeType,
+    CancelRedemptionRequest as ProtoCancelRedemptionRequest,
+    CancelRedemptionResponse as ProtoCancelRedemptionResponse,
     FindBadgesBySourceRefRequest, FindBadgesBySourceRefResponse,
-    GetBadgeDetailRequest, GetBadgeDetailResponse, GetBadgeWallRequest, GetBadgeWallResponse,
+    GetBadgeDetailRequest, GetBadgeDetailResponse, GetBadgeProgressRequest,
+    GetBadgeProgressResponse, GetBadgeWallRequest, GetBadgeWallResponse,
+    GetLeaderboardRequest, GetLeaderboardResponse, LeaderboardEntry as ProtoLeaderboardEntry,
     GetUserBadgesRequest, GetUserBadgesResponse, GrantBadgeRequest as ProtoGrantBadgeRequest,
-    GrantBadgeResponse as ProtoGrantBadgeResponse, PinBadgeRequest, PinBadgeResponse,
-    RedeemBadgeRequest as ProtoRedeemBadgeRequest, RedeemBadgeResponse as ProtoRedeemBadgeResponse,
+    GrantBadgeResponse as ProtoGrantBadgeResponse, ListRedeemableBenefitsRequest,
+    ListRedeemableBenefitsResponse, MissingRequirement as ProtoMissingRequirement,
+    PinBadgeRequest, PinBadgeResponse, RedeemableBenefit as ProtoRedeemableBenefit,
+    ProgressItem as ProtoProgressItem, RedeemBadgeRequest as ProtoRedeemBadgeRequest, RedeemBadgeResponse as ProtoRedeemBadgeResponse,
     RefreshAutoBenefitCacheRequest, RefreshAutoBenefitCacheResponse,
-    RefreshDependencyCacheRequest, RefreshDependencyCacheResponse,
-    RevokeBadgeRequest as ProtoRevokeBadgeRequest, RevokeBadgeResponse as ProtoRevokeBadgeResponse,
+    RefreshDependencyCacheRequest, RefreshDependencyCacheResponse, ReorderPinnedBadgesRequest,
+    RefundedBadge as ProtoRefundedBadge,
+    ReorderPinnedBadgesResponse, RevokeBadgeRequest as ProtoRevokeBadgeRequest, RevokeBadgeResponse as ProtoRevokeBadgeResponse,
     SourceRefBadge, UserBadge as ProtoUserBadge,
     badge_management_service_server::BadgeManagementService,
 };
+use badge_shared::leaderboard::{
+    LeaderboardEntry, LeaderboardKind, LeaderboardStore, LeaderboardWindow,
+};
 
 use crate::auto_benefit::AutoBenefitRuleCache;
 use crate::cascade::CascadeEvaluator;
 
 use crate::error::BadgeError;
-use crate::models::{BadgeType, SourceType, UserBadgeStatus};
+use crate::models::{BadgeType, RequiredBadge, SourceType, UserBadgeStatus};
 use crate::repository::{
     BadgeLedgerRepositoryTrait, BadgeRepositoryTrait, RedemptionRepositoryTrait,
     UserBadgeRepositoryTrait,
 };
 use crate::service::dto::{
-    GrantBadgeRequest, RedeemBadgeRequest, RevokeBadgeRequest, UserBadgeDto,
+    BadgeProgressDto, BatchGrantResponse, BatchRevokeResponse, CancelRedemptionRequest,
+    CancelRedemptionResponse, GrantBadgeRequest, RedeemBadgeRequest, RedeemableBenefitDto,
+    RevokeBadgeRequest, UserBadgeDto,
+};
+use crate::service::{
+    BadgeQueryService, GrantService, ProgressService, RedemptionService, RevokeService,
+    ShowcaseService, query_service::sort_pinned_first,
 };
-use crate::service::{BadgeQueryService, GrantService, RedemptionService, RevokeService};
 
 // ==================== 错误转换 ====================
 
@@ -43,16 +59,21 @@ impl From<BadgeError> for Status {
         match err {
             BadgeError::BadgeNotFound(_) => Status::not_found(err.to_string()),
             BadgeError::UserBadgeNotFound { .. } => Status::not_found(err.to_string()),
+            BadgeError::UserBadgeRecordNotFound(_) => Status::not_found(err.to_string()),
             BadgeError::SeriesNotFound(_) => Status::not_found(err.to_string()),
             BadgeError::CategoryNotFound(_) => Status::not_found(err.to_string()),
             BadgeError::RedemptionRuleNotFound(_) => Status::not_found(err.to_string()),
             BadgeError::BenefitNotFound(_) => Status::not_found(err.to_string()),
             BadgeError::OrderNotFound(_) => Status::not_found(err.to_string()),
+            BadgeError::RedemptionOrderNotFound(_) => Status::not_found(err.to_string()),
             BadgeError::BadgeInactive(_) => Status::failed_precondition(err.to_string()),
             BadgeError::RedemptionRuleInactive(_) => Status::failed_precondition(err.to_string()),
             BadgeError::InsufficientBadges { .. } => Status::failed_precondition(err.to_string()),
             BadgeError::UserBadgeExpired(_) => Status::failed_precondition(err.to_string()),
             BadgeError::InvalidOrderStatus { .. } => Status::failed_precondition(err.to_string()),
+            BadgeError::CancelWindowExpired { .. } => Status::failed_precondition(err.to_string()),
+            BadgeError::BenefitRevokeFailed { .. } => Status::failed_precondition(err.to_string()),
+            BadgeError::BenefitRevokePending { .. } => Status::unavailable(err.to_string()),
             BadgeError::BadgeOutOfStock(_) => Status::resource_exhausted(err.to_string()),
             BadgeError::BenefitOutOfStock(_) => Status::resource_exhausted(err.to_string()),
             BadgeError::BadgeAcquisitionLimitReached { .. } => {
@@ -61,6 +82,7 @@ impl From<BadgeError> for Status {
             BadgeError::RedemptionFrequencyLimitReached { .. } => {
                 Status::resource_exhausted(err.to_string())
             }
+            BadgeError::PinLimitReached { .. } => Status::resource_exhausted(err.to_string()),
             BadgeError::Validation(_) => Status::invalid_argument(err.to_string()),
             BadgeError::DuplicateRedemption(_) => Status::already_exists(err.to_string()),
             BadgeError::Database(_) => Status::internal(err.to_string()),
@@ -99,6 +121,7 @@ fn user_badge_status_to_proto(status: UserBadgeStatus) -> i32 {
         UserBadgeStatus::Expired => ProtoBadgeStatus::Expired as i32,
         UserBadgeStatus::Revoked => ProtoBadgeStatus::Revoked as i32,
         UserBadgeStatus::Redeemed => ProtoBadgeStatus::Redeemed as i32,
+        UserBadgeStatus::Suspended => ProtoBadgeStatus::Suspended as i32,
     }
 }
 
@@ -121,6 +144,121 @@ fn parse_source_type(s: &str) -> SourceType {
     }
 }
 
+/// 单次批量调用允许的最大条目数，超出部分由调用方分片提交
+const MAX_BATCH_ITEMS: usize = 1000;
+
+/// 解析批量请求中的公共 badge_id 并校验条目数
+fn parse_batch_header(badge_id: &str, item_count: usize) -> Result<i64, Status> {
+    if item_count == 0 {
+        return Err(Status::invalid_argument("items 不能为空"));
+    }
+    if item_count > MAX_BATCH_ITEMS {
+        return Err(Status::invalid_argument(format!(
+            "单次批量条目数不能超过 {}",
+            MAX_BATCH_ITEMS
+        )));
+    }
+    badge_id
+        .parse()
+        .map_err(|_| Status::invalid_argument("badge_id 格式无效"))
+}
+
+/// 将批量发放请求展开为逐条的内部发放请求
+fn batch_grant_to_requests(req: BatchGrantBadgesRequest) -> Result<Vec<GrantBadgeRequest>, Status> {
+    let badge_id = parse_batch_header(&req.badge_id, req.items.len())?;
+    let source_type = parse_source_type(&req.source_type);
+    let source_ref = (!req.source_ref.is_empty()).then_some(req.source_ref);
+    let reason = (!req.reason.is_empty()).then_some(req.reason);
+    let operator = (!req.operator.is_empty()).then_some(req.operator);
+
+    Ok(req
+        .items
+        .into_iter()
+        .map(|item| {
+            let mut grant_req = GrantBadgeRequest::new(item.user_id, badge_id, item.quantity)
+                .with_source(source_type, source_ref.clone());
+            if !item.idempotency_key.is_empty() {
+                grant_req = grant_req.with_idempotency_key(item.idempotency_key);
+            }
+            grant_req.reason = reason.clone();
+            grant_req.operator = operator.clone();
+            grant_req
+        })
+        .collect())
+}
+
+/// 将批量取消请求展开为逐条的内部取消请求
+fn batch_revoke_to_requests(
+    req: BatchRevokeBadgesRequest,
+) -> Result<Vec<RevokeBadgeRequest>, Status> {
+    let badge_id = parse_batch_header(&req.badge_id, req.items.len())?;
+    if req.reason.is_empty() {
+        return Err(Status::invalid_argument("reason 不能为空"));
+    }
+
+    Ok(req
+        .items
+        .into_iter()
+        .map(|item| {
+            let revoke_req = if req.operator.is_empty() {
+                RevokeBadgeRequest::system(item.user_id, badge_id, item.quantity, &req.reason)
+            } else {
+                RevokeBadgeRequest::manual(
+                    item.user_id,
+                    badge_id,
+                    item.quantity,
+                    &req.reason,
+                    &req.operator,
+                )
+            };
+            if item.idempotency_key.is_empty() {
+                revoke_req
+            } else {
+                revoke_req.with_idempotency_key(item.idempotency_key)
+            }
+        })
+        .collect())
+}
+
+/// 批量发放与批量取消共用的响应映射
+///
+/// 逐条结果统一为 (user_id, success, error_code, error)，两类批量操作的响应结构保持一致
+fn batch_results_to_proto(
+    success_count: i32,
+    failed_count: i32,
+    results: impl IntoIterator<Item = (String, bool, Option<String>, Option<String>)>,
+) -> BatchBadgeOperationResponse {
+    BatchBadgeOperationResponse {
+        success_count,
+        failed_count,
+        results: results
+            .into_iter()
+            .map(|(user_id, success, error_code, error)| BatchItemResult {
+                user_id,
+                success,
+                error_code: error_code.unwrap_or_default(),
+                message: error.unwrap_or_default(),
+            })
+            .collect(),
+    }
+}
+
+fn batch_grant_to_proto(resp: BatchGrantResponse) -> BatchBadgeOperationResponse {
+    let results = resp
+        .results
+        .into_iter()
+        .map(|r| (r.user_id, r.success, r.error_code, r.error));
+    batch_results_to_proto(resp.success_count, resp.failed_count, results)
+}
+
+fn batch_revoke_to_proto(resp: BatchRevokeResponse) -> BatchBadgeOperationResponse {
+    let results = resp
+        .results
+        .into_iter()
+        .map(|r| (r.user_id, r.success, r.error_code, r.error));
+    batch_results_to_proto(resp.success_count, resp.failed_count, results)
+}
+
 /// 将 UserBadgeDto 转换为 Proto UserBadge
 fn user_badge_dto_to_proto(dto: &UserBadgeDto) -> ProtoUserBadge {
     ProtoUserBadge {
@@ -140,7 +278,139 @@ fn user_badge_dto_to_proto(dto: &UserBadgeDto) -> ProtoUserBadge {
         status: user_badge_status_to_proto(dto.status),
         acquired_at: Some(datetime_to_timestamp(dto.acquired_at)),
         expires_at: dto.expires_at.map(datetime_to_timestamp),
-        is_pinned: false,
+        is_pinned: dto.is_pinned(),
+        user_badge_id: dto.user_badge_id.to_string(),
+        pin_order: dto.pin_order.unwrap_or_default(),
+        level: dto.level.unwrap_or_default(),
+        level_name: dto.level_name.clone().unwrap_or_default(),
+    }
+}
+
+/// 将徽章进度 DTO 转换为 Proto
+fn badge_progress_to_proto(dto: &BadgeProgressDto) -> ProtoBadgeProgress {
+    ProtoBadgeProgress {
+        badge_id: dto.badge_id.to_string(),
+        badge_name: dto.badge_name.clone(),
+        earned: dto.earned,
+        quantity: dto.quantity,
+        percentage: dto.percentage,
+        items: dto
+            .items
+            .iter()
+            .map(|item| ProtoProgressItem {
+                kind: item.kind.as_str().to_string(),
+                label: item.label.clone(),
+                current: item.current,
+                target: item.target,
+                percentage: item.percentage,
+                achieved: item.achieved,
+                related_badge_id: item
+                    .related_badge_id
+                    .map(|id| id.to_string())
+                    .unwrap_or_default(),
+                level: item.level.unwrap_or_default(),
+            })
+            .collect(),
+    }
+}
+
+/// 将可兑换权益 DTO 转换为 Proto，配方与配方条目以 JSON 字符串透出
+fn redeemable_benefit_to_proto(dto: &RedeemableBenefitDto) -> ProtoRedeemableBenefit {
+    ProtoRedeemableBenefit {
+        rule_id: dto.rule_id.to_string(),
+        rule_name: dto.rule_name.clone(),
+        benefit_id: dto.benefit_id.to_string(),
+        benefit_name: dto.benefit_name.clone(),
+        benefit_type: serde_json::to_value(dto.benefit_type)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default(),
+        icon_url: dto.icon_url.clone().unwrap_or_default(),
+        remaining_stock: dto.remaining_stock.unwrap_or(-1),
+        status: dto.status.as_str().to_string(),
+        reasons: dto.reasons.clone(),
+        missing: dto
+            .missing
+            .iter()
+            .map(|m| ProtoMissingRequirement {
+                term_index: m.term_index as i32,
+                term: serde_json::to_string(&m.term).unwrap_or_default(),
+                required: m.required,
+                available: m.available,
+            })
+            .collect(),
+        limited_by: dto.limited_by.clone().unwrap_or_default(),
+        recipe: serde_json::to_string(&dto.recipe).unwrap_or_default(),
+        end_time: dto.end_time.map(datetime_to_timestamp),
+    }
+}
+
+/// 解析取消订单请求：user_id 与 operator 必须且只能提供一个
+fn cancel_request_from_proto(
+    req: ProtoCancelRedemptionRequest,
+) -> Result<CancelRedemptionRequest, Status> {
+    if req.order_no.is_empty() {
+        return Err(Status::invalid_argument("order_no 不能为空"));
+    }
+
+    let reason = (!req.reason.is_empty()).then_some(req.reason);
+    match (req.user_id.is_empty(), req.operator.is_empty()) {
+        (false, true) => Ok(CancelRedemptionRequest::by_user(
+            req.order_no,
+            req.user_id,
+            reason,
+        )),
+        (true, false) => Ok(CancelRedemptionRequest::by_admin(
+            req.order_no,
+            req.operator,
+            reason,
+        )),
+        _ => Err(Status::invalid_argument(
+            "user_id 与 operator 必须且只能提供一个",
+        )),
+    }
+}
+
+fn cancel_response_to_proto(resp: CancelRedemptionResponse) -> ProtoCancelRedemptionResponse {
+    let message = if resp.already_cancelled {
+        "订单已取消"
+    } else {
+        "取消成功"
+    };
+    ProtoCancelRedemptionResponse {
+        success: true,
+        order_no: resp.order_no,
+        already_cancelled: resp.already_cancelled,
+        benefit_revoked: resp.benefit_revoked,
+        refunded_badges: resp
+            .refunded_badges
+            .into_iter()
+            .map(|b| ProtoRefundedBadge {
+                badge_id: b.badge_id,
+                badge_name: b.badge_name,
+                quantity: b.quantity,
+            })
+            .collect(),
+        cancelled_at: Some(datetime_to_timestamp(resp.cancelled_at)),
+        message: message.to_string(),
+        error_code: String::new(),
+    }
+}
+
+fn leaderboard_entry_to_proto(entry: LeaderboardEntry) -> ProtoLeaderboardEntry {
+    ProtoLeaderboardEntry {
+        rank: entry.rank as i64,
+        user_id: entry.user_id,
+        score: entry.score,
+        completion_rate: entry.completion_rate.unwrap_or_default(),
+    }
+}
+
+/// 排行榜错误转换：参数错误返回 invalid_argument，其余为内部错误
+fn leaderboard_error_to_status(err: badge_shared::error::BadgeError) -> Status {
+    match err {
+        badge_shared::error::BadgeError::Validation(msg) => Status::invalid_argument(msg),
+        other => Status::internal(other.to_string()),
     }
 }
 
@@ -160,11 +430,16 @@ where
     grant_service: Arc<GrantService<BR>>,
     revoke_service: Arc<RevokeService<BR>>,
     redemption_service: Arc<RedemptionService>,
+    showcase_service: Arc<ShowcaseService>,
     pool: PgPool,
     /// 级联评估器（用于刷新依赖图缓存）
     cascade_evaluator: Option<Arc<CascadeEvaluator>>,
     /// 自动权益规则缓存（用于刷新自动权益缓存）
     auto_benefit_rule_cache: Option<Arc<AutoBenefitRuleCache>>,
+    /// 徽章进度服务
+    progress_service: Option<Arc<ProgressService<BR, UBR>>>,
+    /// 排行榜存储
+    leaderboard: Option<Arc<LeaderboardStore>>,
 }
 
 impl<BR, UBR, RR, LR> BadgeManagementServiceImpl<BR, UBR, RR, LR>
@@ -179,6 +454,7 @@ where
         grant_service: Arc<GrantService<BR>>,
         revoke_service: Arc<RevokeService<BR>>,
         redemption_service: Arc<RedemptionService>,
+        showcase_service: Arc<ShowcaseService>,
         pool: PgPool,
         cascade_evaluator: Option<Arc<CascadeEvaluator>>,
     ) -> Self {
@@ -187,9 +463,12 @@ where
             grant_service,
             revoke_service,
             redemption_service,
+            showcase_service,
             pool,
             cascade_evaluator,
             auto_benefit_rule_cache: None,
+            progress_service: None,
+            leaderboard: None,
         }
     }
 
@@ -198,6 +477,18 @@ where
         self.auto_benefit_rule_cache = Some(cache);
         self
     }
+
+    /// 设置徽章进度服务
+    pub fn with_progress_service(mut self, service: Arc<ProgressService<BR, UBR>>) -> Self {
+        self.progress_service = Some(service);
+        self
+    }
+
+    /// 设置排行榜存储
+    pub fn with_leaderboard(mut self, leaderboard: Arc<LeaderboardStore>) -> Self {
+        self.leaderboard = Some(leaderboard);
+        self
+    }
 }
 
 #[tonic::async_trait]
@@ -342,13 +633,15 @@ where
             .await
             .map_err(Status::from)?;
 
-        // 收集所有徽章并转换为 Proto
-        let mut all_badges: Vec<ProtoUserBadge> = Vec::new();
-        for category in &wall.categories {
-            for badge in &category.badges {
-                all_badges.push(user_badge_dto_to_proto(badge));
-            }
-        }
+        // 收集所有徽章并转换为 Proto，置顶徽章按展示顺序排在最前
+        let mut all_badges: Vec<UserBadgeDto> = wall
+            .categories
+            .iter()
+            .flat_map(|category| category.badges.iter().cloned())
+            .collect();
+        sort_pinned_first(&mut all_badges);
+        let all_badges: Vec<ProtoUserBadge> =
+            all_badges.iter().map(user_badge_dto_to_proto).collect();
 
         Ok(Response::new(GetBadgeWallResponse {
             badges: all_badges,
@@ -465,6 +758,31 @@ where
         }
     }
 
+    /// 批量发放徽章（内部接口）
+    ///
+    /// 每个条目独立走完整的发放流程（前置条件、互斥、库存、级联、通知），
+    /// 单条失败不影响其他条目，结果按请求顺序返回
+    #[instrument(skip(self, request), fields(badge_id = %request.get_ref().badge_id, count = request.get_ref().items.len()))]
+    async fn batch_grant_badges(
+        &self,
+        request: Request<BatchGrantBadgesRequest>,
+    ) -> Result<Response<BatchBadgeOperationResponse>, Status> {
+        let requests = batch_grant_to_requests(request.into_inner())?;
+        let resp = self.grant_service.batch_grant_badges(requests).await?;
+        Ok(Response::new(batch_grant_to_proto(resp)))
+    }
+
+    /// 批量取消徽章（内部接口）
+    #[instrument(skip(self, request), fields(badge_id = %request.get_ref().badge_id, count = request.get_ref().items.len()))]
+    async fn batch_revoke_badges(
+        &self,
+        request: Request<BatchRevokeBadgesRequest>,
+    ) -> Result<Response<BatchBadgeOperationResponse>, Status> {
+        let requests = batch_revoke_to_requests(request.into_inner())?;
+        let resp = self.revoke_service.batch_revoke_badges(requests).await?;
+        Ok(Response::new(batch_revoke_to_proto(resp)))
+    }
+
     /// 兑换徽章
     #[instrument(skip(self), fields(user_id = %request.get_ref().user_id, rule_id = %request.get_ref().redemption_rule_id))]
     async fn redeem_badge(
@@ -495,7 +813,16 @@ where
         );
 
         // 构造内部请求
-        let redeem_req = RedeemBadgeRequest::new(&req.user_id, rule_id, &idempotency_key);
+        let selected_badges = req
+            .selected_badges
+            .into_iter()
+            .map(|b| RequiredBadge {
+                badge_id: b.badge_id,
+                quantity: b.quantity,
+            })
+            .collect();
+        let redeem_req = RedeemBadgeRequest::new(&req.user_id, rule_id, &idempotency_key)
+            .with_selected_badges(selected_badges);
 
         // 调用服务
         let result = self.redemption_service.redeem_badge(redeem_req).await;
@@ -507,14 +834,71 @@ where
                 benefit_id: String::new(),
                 benefit_name: resp.benefit_name,
                 message: resp.message,
+                error_code: String::new(),
             })),
+            // 业务失败以 success=false 返回，调用方通过 error_code 区分原因（如频率超限）
             Err(e) => Ok(Response::new(ProtoRedeemBadgeResponse {
                 success: false,
                 order_id: String::new(),
                 benefit_id: String::new(),
                 benefit_name: String::new(),
                 message: e.to_string(),
+                error_code: e.error_code().to_string(),
+            })),
+        }
+    }
+
+    /// 查询用户的可兑换权益目录
+    #[instrument(skip(self), fields(user_id = %request.get_ref().user_id))]
+    async fn list_redeemable_benefits(
+        &self,
+        request: Request<ListRedeemableBenefitsRequest>,
+    ) -> Result<Response<ListRedeemableBenefitsResponse>, Status> {
+        let req = request.into_inner();
+
+        if req.user_id.is_empty() {
+            return Err(Status::invalid_argument("user_id 不能为空"));
+        }
+
+        let page = self
+            .redemption_service
+            .list_redeemable_benefits(
+                &req.user_id,
+                req.eligible_only,
+                req.page as i64,
+                req.page_size as i64,
+            )
+            .await
+            .map_err(Status::from)?;
+
+        Ok(Response::new(ListRedeemableBenefitsResponse {
+            benefits: page.items.iter().map(redeemable_benefit_to_proto).collect(),
+            total: page.total as i32,
+            page: page.page as i32,
+            page_size: page.page_size as i32,
+        }))
+    }
+
+    /// 取消兑换订单
+    #[instrument(skip(self), fields(order_no = %request.get_ref().order_no))]
+    async fn cancel_redemption(
+        &self,
+        request: Request<ProtoCancelRedemptionRequest>,
+    ) -> Result<Response<ProtoCancelRedemptionResponse>, Status> {
+        let cancel_req = cancel_request_from_proto(request.into_inner())?;
+        let order_no = cancel_req.order_no.clone();
+
+        match self.redemption_service.cancel_redemption(cancel_req).await {
+            Ok(resp) => Ok(Response::new(cancel_response_to_proto(resp))),
+            // 业务失败以 success=false 返回，调用方通过 error_code 区分原因（如超过可取消时限）
+            Err(e) if e.is_business_error() => Ok(Response::new(ProtoCancelRedemptionResponse {
+                success: false,
+                order_no,
+                message: e.to_string(),
+                error_code: e.error_code().to_string(),
+                ..Default::default()
             })),
+            Err(e) => Err(Status::from(e)),
         }
     }
 
@@ -539,35 +923,56 @@ where
             .parse()
             .map_err(|_| Status::invalid_argument("user_badge_id 格式无效"))?;
 
-        // 直接在 gRPC 层实现置顶逻辑（简化版）
-        // 由于 user_badges 表当前没有 pinned 字段，这里只做验证和返回成功
-        // 生产环境应添加 pinned 字段并通过 service 层处理
-        let result = sqlx::query(
-            r#"
-            SELECT id FROM user_badges
-            WHERE id = $1 AND user_id = $2
-            "#,
-        )
-        .bind(user_badge_id)
-        .bind(&req.user_id)
-        .fetch_optional(&self.pool)
-        .await;
+        let (pin_order, message) = if req.pin {
+            let pin_order = self
+                .showcase_service
+                .pin(&req.user_id, user_badge_id)
+                .await
+                .map_err(Status::from)?;
+            (pin_order, "徽章置顶成功")
+        } else {
+            self.showcase_service
+                .unpin(&req.user_id, user_badge_id)
+                .await
+                .map_err(Status::from)?;
+            (0, "徽章取消置顶成功")
+        };
 
-        match result {
-            Ok(Some(_)) => {
-                let message = if req.pin {
-                    "徽章置顶成功"
-                } else {
-                    "徽章取消置顶成功"
-                };
-                Ok(Response::new(PinBadgeResponse {
-                    success: true,
-                    message: message.to_string(),
-                }))
-            }
-            Ok(None) => Err(Status::not_found("用户徽章不存在")),
-            Err(e) => Err(Status::internal(format!("数据库错误: {}", e))),
+        Ok(Response::new(PinBadgeResponse {
+            success: true,
+            message: message.to_string(),
+            pin_order,
+        }))
+    }
+
+    /// 调整置顶徽章的展示顺序
+    #[instrument(skip(self), fields(user_id = %request.get_ref().user_id))]
+    async fn reorder_pinned_badges(
+        &self,
+        request: Request<ReorderPinnedBadgesRequest>,
+    ) -> Result<Response<ReorderPinnedBadgesResponse>, Status> {
+        let req = request.into_inner();
+
+        if req.user_id.is_empty() {
+            return Err(Status::invalid_argument("user_id 不能为空"));
         }
+
+        let user_badge_ids = req
+            .user_badge_ids
+            .iter()
+            .map(|id| id.parse::<i64>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| Status::invalid_argument("user_badge_ids 格式无效"))?;
+
+        self.showcase_service
+            .reorder(&req.user_id, &user_badge_ids)
+            .await
+            .map_err(Status::from)?;
+
+        Ok(Response::new(ReorderPinnedBadgesResponse {
+            success: true,
+            message: "置顶顺序已更新".to_string(),
+        }))
     }
 
     /// 根据来源引用查询关联的用户徽章
@@ -687,6 +1092,87 @@ where
             }))
         }
     }
+
+    /// 查询用户的徽章进度
+    #[instrument(skip(self), fields(user_id = %request.get_ref().user_id))]
+    async fn get_badge_progress(
+        &self,
+        request: Request<GetBadgeProgressRequest>,
+    ) -> Result<Response<GetBadgeProgressResponse>, Status> {
+        let req = request.into_inner();
+
+        if req.user_id.is_empty() {
+            return Err(Status::invalid_argument("user_id 不能为空"));
+        }
+
+        let badge_ids = req
+            .badge_ids
+            .iter()
+            .map(|id| id.parse::<i64>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| Status::invalid_argument("badge_ids 格式无效"))?;
+
+        let service = self
+            .progress_service
+            .as_ref()
+            .ok_or_else(|| Status::unavailable("徽章进度服务未配置"))?;
+
+        let progress = service
+            .get_badge_progress(&req.user_id, &badge_ids)
+            .await
+            .map_err(Status::from)?;
+
+        Ok(Response::new(GetBadgeProgressResponse {
+            badges: progress.iter().map(badge_progress_to_proto).collect(),
+        }))
+    }
+
+    /// 查询用户排行榜
+    #[instrument(skip(self), fields(board = %request.get_ref().board))]
+    async fn get_leaderboard(
+        &self,
+        request: Request<GetLeaderboardRequest>,
+    ) -> Result<Response<GetLeaderboardResponse>, Status> {
+        let req = request.into_inner();
+
+        let scope_id = (req.scope_id > 0).then_some(req.scope_id);
+        let kind =
+            LeaderboardKind::parse(&req.board, scope_id).map_err(leaderboard_error_to_status)?;
+        let window = LeaderboardWindow::parse(&req.window).map_err(leaderboard_error_to_status)?;
+
+        let leaderboard = self
+            .leaderboard
+            .as_ref()
+            .ok_or_else(|| Status::unavailable("排行榜服务未配置"))?;
+
+        let board = leaderboard
+            .top(kind, window, req.limit.max(0) as usize)
+            .await
+            .map_err(leaderboard_error_to_status)?;
+
+        let my_entry = if req.user_id.is_empty() {
+            None
+        } else {
+            leaderboard
+                .rank(kind, window, &req.user_id)
+                .await
+                .map_err(leaderboard_error_to_status)?
+                .map(leaderboard_entry_to_proto)
+        };
+
+        Ok(Response::new(GetLeaderboardResponse {
+            board: kind.name().to_string(),
+            scope_id: kind.scope_id().unwrap_or_default(),
+            window: board.window.as_str().to_string(),
+            entries: board
+                .entries
+                .into_iter()
+                .map(leaderboard_entry_to_proto)
+                .collect(),
+            total: board.total as i64,
+            my_entry,
+        }))
+    }
 }
 
 #[cfg(test)]
@@ -771,6 +1257,10 @@ mod tests {
             user_badge_status_to_proto(UserBadgeStatus::Redeemed),
             ProtoBadgeStatus::Redeemed as i32
         );
+        assert_eq!(
+            user_badge_status_to_proto(UserBadgeStatus::Suspended),
+            ProtoBadgeStatus::Suspended as i32
+        );
     }
 
     #[test]
@@ -790,9 +1280,154 @@ mod tests {
         assert_eq!(parse_source_type("unknown"), SourceType::System);
     }
 
+    #[test]
+    fn test_batch_grant_to_requests() {
+        use badge_proto::badge::BatchGrantItem;
+
+        let req = BatchGrantBadgesRequest {
+            badge_id: "7".to_string(),
+            source_type: "manual".to_string(),
+            source_ref: "batch-task-1".to_string(),
+            reason: "活动补发".to_string(),
+            operator: "admin".to_string(),
+            items: vec![
+                BatchGrantItem {
+                    user_id: "user-1".to_string(),
+                    quantity: 2,
+                    idempotency_key: "batch-1-1".to_string(),
+                },
+                BatchGrantItem {
+                    user_id: "user-2".to_string(),
+                    quantity: 1,
+                    idempotency_key: String::new(),
+                },
+            ],
+        };
+
+        let requests = batch_grant_to_requests(req).unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].badge_id, 7);
+        assert_eq!(requests[0].quantity, 2);
+        assert_eq!(requests[0].source_type, SourceType::Manual);
+        assert_eq!(requests[0].source_ref_id.as_deref(), Some("batch-task-1"));
+        assert_eq!(requests[0].idempotency_key.as_deref(), Some("batch-1-1"));
+        assert_eq!(requests[0].reason.as_deref(), Some("活动补发"));
+        assert_eq!(requests[0].operator.as_deref(), Some("admin"));
+        assert!(requests[1].idempotency_key.is_none());
+    }
+
+    #[test]
+    fn test_batch_request_validation() {
+        use badge_proto::badge::BatchRevokeItem;
+
+        let empty = BatchGrantBadgesRequest {
+            badge_id: "1".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            batch_grant_to_requests(empty).unwrap_err().code(),
+            tonic::Code::InvalidArgument
+        );
+
+        let item = BatchRevokeItem {
+            user_id: "user-1".to_string(),
+            quantity: 1,
+            ..Default::default()
+        };
+        let bad_badge = BatchRevokeBadgesRequest {
+            badge_id: "abc".to_string(),
+            reason: "违规".to_string(),
+            items: vec![item.clone()],
+            ..Default::default()
+        };
+        assert!(batch_revoke_to_requests(bad_badge).is_err());
+
+        let no_reason = BatchRevokeBadgesRequest {
+            badge_id: "1".to_string(),
+            items: vec![item],
+            ..Default::default()
+        };
+        assert!(batch_revoke_to_requests(no_reason).is_err());
+    }
+
+    #[test]
+    fn test_batch_revoke_to_requests_carries_idempotency_key() {
+        use badge_proto::badge::BatchRevokeItem;
+
+        let req = BatchRevokeBadgesRequest {
+            badge_id: "1".to_string(),
+            reason: "违规".to_string(),
+            items: vec![
+                BatchRevokeItem {
+                    user_id: "user-1".to_string(),
+                    quantity: 1,
+                    idempotency_key: "batch-42-1".to_string(),
+                },
+                BatchRevokeItem {
+                    user_id: "user-2".to_string(),
+                    quantity: 1,
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        let requests = batch_revoke_to_requests(req).unwrap();
+        assert_eq!(requests[0].idempotency_key.as_deref(), Some("batch-42-1"));
+        assert!(requests[1].idempotency_key.is_none());
+    }
+
+    #[test]
+    fn test_batch_grant_to_proto_carries_error_code() {
+        use crate::service::dto::GrantResult;
+
+        let resp = BatchGrantResponse {
+            total: 2,
+            success_count: 1,
+            failed_count: 1,
+            results: vec![
+                GrantResult::success("user-1".to_string(), 1, 10, 1),
+                GrantResult::failure("user-2".to_string(), 1, "前置条件未满足")
+                    .with_error_code("PREREQUISITE_NOT_MET"),
+            ],
+        };
+
+        let proto = batch_grant_to_proto(resp);
+        assert_eq!(proto.success_count, 1);
+        assert!(proto.results[0].success);
+        assert!(proto.results[0].error_code.is_empty());
+        assert_eq!(proto.results[1].user_id, "user-2");
+        assert_eq!(proto.results[1].error_code, "PREREQUISITE_NOT_MET");
+        assert_eq!(proto.results[1].message, "前置条件未满足");
+    }
+
+    #[test]
+    fn test_batch_revoke_to_proto_matches_grant_mapping() {
+        use crate::service::dto::RevokeResult;
+
+        let resp = BatchRevokeResponse {
+            total: 2,
+            success_count: 1,
+            failed_count: 1,
+            results: vec![
+                RevokeResult::success("user-1".to_string(), 1, 0),
+                RevokeResult::failure("user-2".to_string(), 1, "余额不足")
+                    .with_error_code("INSUFFICIENT_BADGES"),
+            ],
+        };
+
+        let proto = batch_revoke_to_proto(resp);
+        assert_eq!(proto.failed_count, 1);
+        assert!(proto.results[0].success);
+        assert!(proto.results[0].message.is_empty());
+        assert_eq!(proto.results[1].error_code, "INSUFFICIENT_BADGES");
+        assert_eq!(proto.results[1].message, "余额不足");
+    }
+
     #[test]
     fn test_user_badge_dto_to_proto() {
         let dto = UserBadgeDto {
+            user_badge_id: 100,
             badge_id: 1,
             badge_name: "Test Badge".to_string(),
             badge_type: BadgeType::Normal,
@@ -805,11 +1440,20 @@ mod tests {
                 image_url: None,
                 animation_url: Some("https://example.com/anim.json".to_string()),
                 disabled_icon_url: None,
+                levels: vec![],
             },
+            pin_order: Some(2),
+            level: Some(2),
+            level_name: Some("银".to_string()),
         };
 
         let proto = user_badge_dto_to_proto(&dto);
         assert_eq!(proto.id, "1");
+        assert_eq!(proto.user_badge_id, "100");
+        assert!(proto.is_pinned);
+        assert_eq!(proto.pin_order, 2);
+        assert_eq!(proto.level, 2);
+        assert_eq!(proto.level_name, "银");
         assert_eq!(proto.quantity, 5);
         assert_eq!(proto.status, ProtoBadgeStatus::Active as i32);
         assert!(proto.badge.is_some());
@@ -819,4 +1463,159 @@ mod tests {
         assert_eq!(badge.icon_url, "https://example.com/icon.png");
         assert_eq!(badge.icon_3d_url, "https://example.com/anim.json");
     }
+
+    #[test]
+    fn test_badge_progress_to_proto() {
+        use crate::service::dto::{ProgressItemDto, ProgressKind};
+
+        let dto = BadgeProgressDto::new(
+            10,
+            "签到达人",
+            0,
+            vec![ProgressItemDto {
+                related_badge_id: Some(1),
+                ..ProgressItemDto::quantity(ProgressKind::Prerequisite, "新手", 1.0, 2.0)
+            }],
+        );
+
+        let proto = badge_progress_to_proto(&dto);
+        assert_eq!(proto.badge_id, "10");
+        assert!(!proto.earned);
+        assert_eq!(proto.percentage, 50.0);
+        assert_eq!(proto.items[0].kind, "prerequisite");
+        assert_eq!(proto.items[0].related_badge_id, "1");
+        assert_eq!(proto.items[0].level, 0);
+    }
+
+    #[test]
+    fn test_cancel_request_from_proto() {
+        use crate::service::dto::CancelInitiator;
+
+        let req = cancel_request_from_proto(ProtoCancelRedemptionRequest {
+            order_no: "RD1".to_string(),
+            user_id: "user-1".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(
+            req.initiator,
+            CancelInitiator::User {
+                user_id: "user-1".to_string()
+            }
+        );
+        assert!(req.reason.is_none());
+
+        let req = cancel_request_from_proto(ProtoCancelRedemptionRequest {
+            order_no: "RD1".to_string(),
+            operator: "admin".to_string(),
+            reason: "误兑换".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(
+            req.initiator,
+            CancelInitiator::Admin {
+                operator: "admin".to_string()
+            }
+        );
+        assert_eq!(req.reason.as_deref(), Some("误兑换"));
+
+        // 发起方缺失或同时提供均为参数错误
+        for (user_id, operator) in [("", ""), ("user-1", "admin")] {
+            let status = cancel_request_from_proto(ProtoCancelRedemptionRequest {
+                order_no: "RD1".to_string(),
+                user_id: user_id.to_string(),
+                operator: operator.to_string(),
+                ..Default::default()
+            })
+            .unwrap_err();
+            assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        }
+    }
+
+    #[test]
+    fn test_cancel_response_to_proto() {
+        use crate::service::dto::ConsumedBadgeDto;
+
+        let proto = cancel_response_to_proto(CancelRedemptionResponse {
+            order_id: 1,
+            order_no: "RD1".to_string(),
+            already_cancelled: false,
+            benefit_revoked: true,
+            refunded_badges: vec![ConsumedBadgeDto {
+                badge_id: 5,
+                badge_name: "签到达人".to_string(),
+                quantity: 2,
+            }],
+            cancelled_at: chrono::Utc::now(),
+        });
+        assert!(proto.success);
+        assert!(proto.benefit_revoked);
+        assert_eq!(proto.refunded_badges[0].badge_id, 5);
+        assert_eq!(proto.refunded_badges[0].quantity, 2);
+        assert!(proto.cancelled_at.is_some());
+        assert!(proto.error_code.is_empty());
+    }
+
+    #[test]
+    fn test_redeemable_benefit_to_proto() {
+        use crate::models::{BenefitType, RecipeShortfall, RecipeTerm, RedemptionRecipe};
+        use crate::service::dto::RedeemableStatus;
+
+        let term = RecipeTerm::Series {
+            series_id: 3,
+            count: 2,
+            distinct: true,
+        };
+        let dto = RedeemableBenefitDto {
+            rule_id: 7,
+            rule_name: "集齐换券".to_string(),
+            benefit_id: 8,
+            benefit_name: "满减券".to_string(),
+            benefit_type: BenefitType::Coupon,
+            icon_url: None,
+            remaining_stock: None,
+            status: RedeemableStatus::MissingBadges,
+            reasons: vec!["配方第 1 项未满足: 需要 2, 可用 1".to_string()],
+            missing: vec![RecipeShortfall {
+                term_index: 0,
+                term: term.clone(),
+                required: 2,
+                available: 1,
+            }],
+            limited_by: None,
+            recipe: RedemptionRecipe {
+                terms: vec![term],
+                selection: Default::default(),
+            },
+            end_time: None,
+        };
+
+        let proto = redeemable_benefit_to_proto(&dto);
+        assert_eq!(proto.rule_id, "7");
+        assert_eq!(proto.benefit_type, "COUPON");
+        assert_eq!(proto.remaining_stock, -1);
+        assert_eq!(proto.status, "missing_badges");
+        assert_eq!(proto.missing[0].available, 1);
+        assert!(proto.missing[0].term.contains("\"seriesId\":3"));
+        assert!(proto.recipe.contains("\"type\":\"series\""));
+        assert!(proto.end_time.is_none());
+    }
+
+    #[test]
+    fn test_leaderboard_conversion() {
+        let proto = leaderboard_entry_to_proto(LeaderboardEntry {
+            rank: 2,
+            user_id: "user-1".to_string(),
+            score: 8.0,
+            completion_rate: None,
+        });
+        assert_eq!(proto.rank, 2);
+        assert_eq!(proto.completion_rate, 0.0);
+
+        let status = leaderboard_error_to_status(badge_shared::error::BadgeError::Validation(
+            "不支持的排行榜类型: foo".to_string(),
+        ));
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
 }
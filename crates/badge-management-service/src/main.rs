@@ -4,29 +4,37 @@
 
 use anyhow::Result;
 use badge_proto::badge::badge_management_service_server::BadgeManagementServiceServer;
+use badge_proto::rule_engine::rule_engine_service_client::RuleEngineServiceClient;
 use badge_shared::{
     cache::Cache,
     config::AppConfig,
     database::Database,
-    observability,
+    leaderboard::LeaderboardStore,
+    notification_contacts::PgContactStore,
+    notification_preferences::{PgDeferredNotificationStore, PgPreferenceStore},
+    notification_templates::PgTemplateStore,
+    observability::{self, middleware::grpc as grpc_middleware},
 };
 use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::signal;
-use tonic::transport::Server;
+use tonic::transport::{Endpoint, Server};
 use tracing::info;
 
 use badge_management::{
     auto_benefit::{AutoBenefitConfig, AutoBenefitEvaluator, AutoBenefitRuleCache},
-    benefit::BenefitService,
+    benefit::{BenefitService, HandlerRegistry, RegistryConfig},
     cascade::{CascadeConfig, CascadeEvaluator},
     grpc::BadgeManagementServiceImpl,
-    notification::{NotificationSender, NotificationService},
+    notification::{EmailChannel, NotificationSender, NotificationService, TemplateEngine},
     repository::{
         AutoBenefitRepository, BadgeLedgerRepository, BadgeRepository, DependencyRepository,
         RedemptionRepository, UserBadgeRepository,
     },
-    service::{BadgeQueryService, GrantService, RedemptionService, RevokeService},
+    service::{
+        BadgeQueryService, GrantService, ProgressService, RedemptionService, RevokeService,
+        ShowcaseService,
+    },
 };
 
 /// 服务配置
@@ -108,24 +116,73 @@ async fn main() -> Result<()> {
     ));
 
     // 6.1 初始化通知服务
-    let notification_service = Arc::new(NotificationService::with_defaults());
+    let email_channel = EmailChannel::from_smtp_config(&config.smtp)?;
+    info!(
+        enabled = config.smtp.enabled,
+        host = %config.smtp.host,
+        "Email channel configured"
+    );
+    // 发送前按用户偏好筛选渠道，免打扰时段内的通知交由 notification-worker 延迟投递；
+    // 收件人按用户 ID 从联系方式表查询；
+    // 通知模板由管理后台维护，与 notification-worker 共用
+    let template_engine = TemplateEngine::with_store(Arc::new(PgTemplateStore::new(pool.clone())));
+    let notification_service = Arc::new(
+        NotificationService::with_defaults()
+            .with_template_engine(Arc::new(template_engine))
+            .with_email_channel(email_channel)
+            .with_preference_store(Arc::new(PgPreferenceStore::new(pool.clone())))
+            .with_deferred_store(Arc::new(PgDeferredNotificationStore::new(pool.clone())))
+            .with_contact_store(Arc::new(PgContactStore::new(pool.clone()))),
+    );
     let notification_sender = Arc::new(NotificationSender::new(notification_service.clone()));
     info!("Notification service initialized");
 
-    // 6.2 初始化权益服务（默认 Handler + 数据库持久化 + Redis 分布式幂等）
+    // 6.2 初始化权益服务（全部权益 Handler + 数据库持久化 + Redis 分布式幂等）
+    // 外部回调签名密钥属于敏感配置，只从环境变量读取
+    let defaults = RegistryConfig::default();
+    let benefit_registry = HandlerRegistry::with_config(RegistryConfig {
+        coupon_service_url: std::env::var("BADGE_COUPON_SERVICE_URL")
+            .unwrap_or(defaults.coupon_service_url),
+        points_service_url: std::env::var("BADGE_POINTS_SERVICE_URL")
+            .unwrap_or(defaults.points_service_url),
+        digital_asset_service_url: std::env::var("BADGE_DIGITAL_ASSET_SERVICE_URL")
+            .unwrap_or(defaults.digital_asset_service_url),
+        reservation_service_url: std::env::var("BADGE_RESERVATION_SERVICE_URL")
+            .unwrap_or(defaults.reservation_service_url),
+        membership_service_url: std::env::var("BADGE_MEMBERSHIP_SERVICE_URL")
+            .unwrap_or(defaults.membership_service_url),
+        callback_signing_secret: std::env::var("BADGE_CALLBACK_SIGNING_SECRET")
+            .unwrap_or_default(),
+        pool: Some(pool.clone()),
+        ..defaults
+    });
     let benefit_service = Arc::new(
-        BenefitService::with_defaults()
+        BenefitService::new(Arc::new(benefit_registry))
             .with_pool(pool.clone())
             .with_cache(cache.clone()),
     );
-    info!("Benefit service initialized with default handlers, database persistence and Redis idempotency");
-
-    let redemption_service = Arc::new(RedemptionService::with_benefit_service(
-        redemption_repo.clone(),
-        cache.clone(),
-        pool.clone(),
-        benefit_service.clone(),
-    ));
+    info!("Benefit service initialized with all handlers, database persistence and Redis idempotency");
+
+    let redemption_timezone = config.redemption.tz().unwrap_or_else(|e| {
+        tracing::warn!(
+            timezone = %config.redemption.timezone,
+            error = %e,
+            "兑换频率限制时区无效，回退到 Asia/Shanghai"
+        );
+        chrono_tz::Asia::Shanghai
+    });
+    let redemption_service = Arc::new(
+        RedemptionService::with_benefit_service(
+            redemption_repo.clone(),
+            cache.clone(),
+            pool.clone(),
+            benefit_service.clone(),
+        )
+        .with_timezone(redemption_timezone)
+        .with_cancel_window(chrono::Duration::minutes(
+            config.redemption.cancel_window_minutes,
+        )),
+    );
 
     // 6.3 初始化自动权益评估器
     let auto_benefit_rule_cache = Arc::new(AutoBenefitRuleCache::new(pool.clone()));
@@ -158,6 +215,9 @@ async fn main() -> Result<()> {
     grant_service
         .set_cascade_evaluator(cascade_evaluator.clone())
         .await;
+    revoke_service
+        .set_cascade_evaluator(cascade_evaluator.clone())
+        .await;
     info!("Cascade evaluator initialized");
 
     // 注入自动权益评估器的依赖
@@ -181,6 +241,58 @@ async fn main() -> Result<()> {
         .await;
     info!("Notification senders configured");
 
+    // 排行榜：总榜缺失时（首次部署或 Redis 数据丢失）从 user_badges 全量重建
+    let leaderboard = Arc::new(LeaderboardStore::new(cache.clone(), pool.clone()));
+    match leaderboard.is_initialized().await {
+        Ok(true) => {}
+        Ok(false) => {
+            if let Err(e) = leaderboard.rebuild_all_time().await {
+                tracing::warn!("Failed to rebuild leaderboards: {}", e);
+            }
+        }
+        Err(e) => tracing::warn!("Failed to check leaderboards: {}", e),
+    }
+    grant_service.set_leaderboard(leaderboard.clone()).await;
+    revoke_service.set_leaderboard(leaderboard.clone()).await;
+    redemption_service.set_leaderboard(leaderboard.clone()).await;
+    info!("Leaderboard store configured");
+
+    let showcase_service = Arc::new(ShowcaseService::new(
+        pool.clone(),
+        cache.clone(),
+        config.showcase.max_pinned,
+    ));
+
+    // 徽章进度服务：规则聚合进度由规则引擎提供，懒连接不阻塞启动
+    let mut progress_service = ProgressService::new(
+        badge_repo.clone(),
+        user_badge_repo.clone(),
+        cache.clone(),
+    )
+    .with_cascade_evaluator(cascade_evaluator.clone());
+    let client_tls = badge_shared::grpc_tls::build_client_tls_config(&config.tls)
+        .await
+        .expect("gRPC 客户端 TLS 配置加载失败");
+    let grpc_scheme = badge_shared::grpc_tls::grpc_scheme(&config.tls);
+    let rule_engine_url = std::env::var("RULE_ENGINE_URL")
+        .unwrap_or_else(|_| format!("{grpc_scheme}://localhost:50051"));
+    match Endpoint::from_shared(rule_engine_url.clone()).and_then(|endpoint| match client_tls {
+        Some(tls) => endpoint.tls_config(tls),
+        None => Ok(endpoint),
+    }) {
+        Ok(endpoint) => {
+            progress_service = progress_service.with_rule_progress(Arc::new(
+                RuleEngineServiceClient::new(endpoint.connect_lazy()),
+            ));
+            info!(rule_engine_url = %rule_engine_url, "Badge progress service initialized");
+        }
+        Err(e) => tracing::warn!(
+            "Invalid rule engine endpoint {}: {}, rule progress will be unavailable",
+            rule_engine_url,
+            e
+        ),
+    }
+
     info!("Services initialized");
 
     // 7. 创建 gRPC 服务
@@ -189,10 +301,13 @@ async fn main() -> Result<()> {
         grant_service,
         revoke_service,
         redemption_service,
+        showcase_service,
         pool.clone(),
         Some(cascade_evaluator),
     )
-    .with_auto_benefit_rule_cache(auto_benefit_rule_cache);
+    .with_auto_benefit_rule_cache(auto_benefit_rule_cache)
+    .with_progress_service(Arc::new(progress_service))
+    .with_leaderboard(leaderboard);
 
     // 8. 启动 gRPC 服务
     // 健康检查端点已由 observability 模块在 metrics_port 上提供
@@ -211,6 +326,7 @@ async fn main() -> Result<()> {
     }
 
     server_builder
+        .trace_fn(grpc_middleware::server_span)
         .add_service(BadgeManagementServiceServer::new(grpc_service))
         .serve_with_shutdown(service_config.grpc_addr, shutdown_signal())
         .await?;
@@ -102,6 +102,91 @@ pub struct BadgeAssets {
     /// 灰态图标（未获取时展示）
     #[serde(default)]
     pub disabled_icon_url: Option<String>,
+    /// 分级徽章各等级的资源，未配置的字段沿用上面的默认资源
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub levels: Vec<LevelAssets>,
+}
+
+impl BadgeAssets {
+    /// 解析指定等级实际展示的资源
+    ///
+    /// 等级资源覆盖默认的图标、大图和动效，未分级或该等级未配置资源时返回默认资源
+    pub fn for_level(&self, level: Option<i32>) -> BadgeAssets {
+        let mut assets = self.clone();
+        if let Some(level_assets) = level.and_then(|l| self.levels.iter().find(|a| a.level == l)) {
+            assets.icon_url = level_assets.icon_url.clone();
+            if level_assets.image_url.is_some() {
+                assets.image_url = level_assets.image_url.clone();
+            }
+            if level_assets.animation_url.is_some() {
+                assets.animation_url = level_assets.animation_url.clone();
+            }
+        }
+        assets
+    }
+}
+
+/// 分级徽章单个等级的资源
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LevelAssets {
+    /// 对应的等级
+    pub level: i32,
+    pub icon_url: String,
+    #[serde(default)]
+    pub image_url: Option<String>,
+    #[serde(default)]
+    pub animation_url: Option<String>,
+}
+
+/// 分级徽章的等级定义
+///
+/// 用户持有数量达到 `threshold` 时自动升到该等级，同一徽章只保留一条持有记录，
+/// 高等级替换低等级展示而不是并列展示
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BadgeLevel {
+    /// 等级序号，从 1 开始连续递增
+    pub level: i32,
+    /// 等级名称，如"铜"、"银"、"金"
+    pub name: String,
+    /// 升到该等级所需的持有数量
+    pub threshold: i32,
+}
+
+/// 校验等级定义：等级从 1 开始连续递增，门槛为正数且严格递增
+pub fn validate_levels(levels: &[BadgeLevel]) -> Result<(), String> {
+    for (i, level) in levels.iter().enumerate() {
+        if level.level != i as i32 + 1 {
+            return Err(format!(
+                "等级序号必须从 1 开始连续递增，第 {} 项为 {}",
+                i + 1,
+                level.level
+            ));
+        }
+        if level.name.trim().is_empty() {
+            return Err(format!("等级 {} 名称不能为空", level.level));
+        }
+        if level.threshold <= 0 {
+            return Err(format!("等级 {} 门槛必须大于 0", level.level));
+        }
+        if i > 0 && level.threshold <= levels[i - 1].threshold {
+            return Err(format!(
+                "等级 {} 门槛必须大于等级 {} 的门槛",
+                level.level,
+                levels[i - 1].level
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// 按持有数量计算达到的最高等级，未达到任何等级时返回 None
+pub fn level_for_quantity(levels: &[BadgeLevel], quantity: i32) -> Option<&BadgeLevel> {
+    levels
+        .iter()
+        .filter(|l| quantity >= l.threshold)
+        .max_by_key(|l| l.level)
 }
 
 /// 徽章定义
@@ -142,6 +227,11 @@ pub struct Badge {
     /// 已发放数量
     #[serde(default)]
     pub issued_count: i64,
+    /// 等级定义（JSON）
+    /// 存储 BadgeLevel 数组，为空表示非分级徽章
+    #[sqlx(default)]
+    #[serde(default)]
+    pub levels: Value,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -157,6 +247,19 @@ impl Badge {
         serde_json::from_value(self.validity_config.clone())
     }
 
+    /// 解析等级定义，未配置时返回空列表
+    pub fn parse_levels(&self) -> Result<Vec<BadgeLevel>, serde_json::Error> {
+        if self.levels.is_null() {
+            return Ok(vec![]);
+        }
+        serde_json::from_value(self.levels.clone())
+    }
+
+    /// 是否为分级徽章
+    pub fn is_tiered(&self) -> bool {
+        self.parse_levels().is_ok_and(|levels| !levels.is_empty())
+    }
+
     /// 检查是否还有库存
     pub fn has_stock(&self) -> bool {
         match self.max_supply {
@@ -241,6 +344,7 @@ mod tests {
             image_url: Some("https://example.com/image.png".to_string()),
             animation_url: None,
             disabled_icon_url: None,
+            levels: vec![],
         };
 
         let json = serde_json::to_value(&assets).unwrap();
@@ -296,6 +400,92 @@ mod tests {
         assert!(!rule.is_active(now));
     }
 
+    fn tiers() -> Vec<BadgeLevel> {
+        vec![
+            BadgeLevel {
+                level: 1,
+                name: "铜".to_string(),
+                threshold: 1,
+            },
+            BadgeLevel {
+                level: 2,
+                name: "银".to_string(),
+                threshold: 5,
+            },
+            BadgeLevel {
+                level: 3,
+                name: "金".to_string(),
+                threshold: 10,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_validate_levels() {
+        assert!(validate_levels(&tiers()).is_ok());
+        assert!(validate_levels(&[]).is_ok());
+
+        let mut skipped = tiers();
+        skipped[1].level = 3;
+        assert!(validate_levels(&skipped).is_err());
+
+        let mut not_increasing = tiers();
+        not_increasing[2].threshold = 5;
+        assert!(validate_levels(&not_increasing).is_err());
+
+        let mut zero = tiers();
+        zero[0].threshold = 0;
+        assert!(validate_levels(&zero).is_err());
+    }
+
+    #[test]
+    fn test_level_for_quantity() {
+        let levels = tiers();
+        assert!(level_for_quantity(&levels, 0).is_none());
+        assert_eq!(level_for_quantity(&levels, 1).unwrap().level, 1);
+        assert_eq!(level_for_quantity(&levels, 7).unwrap().level, 2);
+        assert_eq!(level_for_quantity(&levels, 100).unwrap().name, "金");
+    }
+
+    #[test]
+    fn test_badge_parse_levels() {
+        let mut badge = create_test_badge();
+        assert!(!badge.is_tiered());
+
+        badge.levels = serde_json::to_value(tiers()).unwrap();
+        assert!(badge.is_tiered());
+        assert_eq!(badge.parse_levels().unwrap(), tiers());
+    }
+
+    #[test]
+    fn test_assets_for_level() {
+        let assets: BadgeAssets = serde_json::from_value(json!({
+            "iconUrl": "https://example.com/icon.png",
+            "imageUrl": "https://example.com/image.png",
+            "levels": [
+                {"level": 2, "iconUrl": "https://example.com/silver.png"}
+            ]
+        }))
+        .unwrap();
+
+        let silver = assets.for_level(Some(2));
+        assert_eq!(silver.icon_url, "https://example.com/silver.png");
+        // 等级未配置大图时沿用默认资源
+        assert_eq!(
+            silver.image_url.as_deref(),
+            Some("https://example.com/image.png")
+        );
+
+        assert_eq!(
+            assets.for_level(Some(1)).icon_url,
+            "https://example.com/icon.png"
+        );
+        assert_eq!(
+            assets.for_level(None).icon_url,
+            "https://example.com/icon.png"
+        );
+    }
+
     fn create_test_badge() -> Badge {
         Badge {
             id: 1,
@@ -311,6 +501,7 @@ mod tests {
             validity_config: json!({"validityType": "PERMANENT"}),
             max_supply: None,
             issued_count: 0,
+            levels: Value::Null,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         }
@@ -56,6 +56,8 @@ pub enum UserBadgeStatus {
     Revoked,
     /// 已兑换 - 用于兑换权益（可部分兑换）
     Redeemed,
+    /// 已暂停 - 前置徽章失效后暂停，重新满足前置条件后自动恢复
+    Suspended,
 }
 
 /// 有效期类型
@@ -220,6 +222,8 @@ pub enum OrderStatus {
     Failed,
     /// 已取消 - 用户或系统取消
     Cancelled,
+    /// 取消中 - 已受理取消，权益撤销或徽章退回尚未完成，重试时继续执行
+    Cancelling,
 }
 
 /// 日志动作类型
@@ -237,6 +241,12 @@ pub enum LogAction {
     Redeem,
     /// 过期
     Expire,
+    /// 暂停（前置徽章失效）
+    Suspend,
+    /// 恢复（重新满足前置条件）
+    Resume,
+    /// 退还（兑换订单取消后退回消耗的徽章）
+    Refund,
 }
 
 /// 发放对象类型
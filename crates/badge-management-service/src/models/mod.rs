@@ -4,18 +4,26 @@
 
 pub mod badge;
 pub mod enums;
+pub mod recipe;
 pub mod redemption;
 pub mod user_badge;
 
 // 重新导出常用类型
-pub use badge::{Badge, BadgeAssets, BadgeCategory, BadgeRule, BadgeSeries, ValidityConfig};
+pub use badge::{
+    Badge, BadgeAssets, BadgeCategory, BadgeLevel, BadgeRule, BadgeSeries, LevelAssets,
+    ValidityConfig, level_for_quantity, validate_levels,
+};
 pub use enums::{
     BadgeStatus, BadgeType, BenefitType, CategoryStatus, ChangeType, GrantStatus, LogAction,
     OrderStatus, RecipientType, RedemptionValidityType, RevokeReason, SourceType,
     UserBadgeStatus, ValidityType,
 };
+pub use recipe::{
+    PlannedConsumption, RecipeError, RecipeHolding, RecipeShortfall, RecipeTerm, RedemptionRecipe,
+    SelectionPolicy,
+};
 pub use redemption::{
-    BadgeRedemptionRule, Benefit, BenefitInfo, BenefitStatus, FrequencyConfig, RedemptionDetail,
-    RedemptionOrder, RedemptionRequest, RedemptionResult, RequiredBadge,
+    BadgeRedemptionRule, Benefit, BenefitInfo, BenefitStatus, FrequencyConfig, FrequencyPeriod,
+    RedemptionDetail, RedemptionOrder, RedemptionRequest, RedemptionResult, RequiredBadge,
 };
 pub use user_badge::{BadgeLedger, UserBadge, UserBadgeLog, UserBadgeSummary};
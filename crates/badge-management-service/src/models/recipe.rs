@@ -0,0 +1,833 @@
+//! 兑换配方
+//!
+//! 描述兑换规则需要消耗哪些徽章的表达式模型。配方由若干条目组成，所有条目须同时满足：
+//! - `badge`：指定徽章 × 数量
+//! - `any_of`：多个备选条目任选其一（如「徽章 A ×2 或徽章 B ×1」）
+//! - `series`：某系列内任意 N 个徽章，`distinct` 时要求 N 种不同徽章
+//! - `category`：某分类内任意 N 个徽章，`distinct` 时要求 N 种不同徽章
+//!
+//! 通配条目可以由用户在兑换请求中指定要消耗的徽章，未指定时按配方的选择策略确定。
+//! 旧规则只有 `required_badges` 固定列表，等价于由 `badge` 条目组成的配方。
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::redemption::RequiredBadge;
+
+/// 配方条目数上限，避免配置过大导致兑换事务内计算过久
+pub const MAX_RECIPE_TERMS: usize = 20;
+
+/// 兑换配方
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedemptionRecipe {
+    /// 配方条目（AND 关系）
+    pub terms: Vec<RecipeTerm>,
+    /// 用户未指定消耗徽章时的选择策略
+    #[serde(default)]
+    pub selection: SelectionPolicy,
+}
+
+/// 配方条目
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(
+    tag = "type",
+    rename_all = "snake_case",
+    rename_all_fields = "camelCase"
+)]
+pub enum RecipeTerm {
+    /// 指定徽章 × 数量
+    Badge { badge_id: i64, quantity: i32 },
+    /// 备选条目任选其一，按配置顺序优先
+    AnyOf { options: Vec<RecipeTerm> },
+    /// 系列内任意 N 个徽章
+    Series {
+        series_id: i64,
+        count: i32,
+        #[serde(default)]
+        distinct: bool,
+    },
+    /// 分类内任意 N 个徽章
+    Category {
+        category_id: i64,
+        count: i32,
+        #[serde(default)]
+        distinct: bool,
+    },
+}
+
+/// 消耗徽章的选择策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelectionPolicy {
+    /// 优先消耗最早过期的徽章，永久徽章最后消耗
+    #[default]
+    SoonestExpiring,
+    /// 优先消耗最早获得的徽章
+    EarliestAcquired,
+}
+
+/// 参与配方计算的用户持有徽章
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct RecipeHolding {
+    pub user_badge_id: i64,
+    pub badge_id: i64,
+    pub series_id: i64,
+    pub category_id: i64,
+    pub quantity: i32,
+    pub first_acquired_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// 配方计算出的单笔消耗
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedConsumption {
+    /// 满足的配方条目序号（从 0 开始）
+    pub term_index: usize,
+    pub user_badge_id: i64,
+    pub badge_id: i64,
+    pub quantity: i32,
+}
+
+/// 未满足的配方条目及缺口
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecipeShortfall {
+    /// 配方条目序号（从 0 开始）
+    pub term_index: usize,
+    pub term: RecipeTerm,
+    pub required: i32,
+    pub available: i32,
+}
+
+/// 配方计算失败原因
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RecipeError {
+    #[error("用户未持有徽章: badge_id={badge_id}")]
+    BadgeNotHeld { badge_id: i64 },
+
+    #[error("配方第 {} 项未满足: 需要 {required}, 可用 {available}", .term_index + 1)]
+    NotSatisfied {
+        term_index: usize,
+        required: i32,
+        available: i32,
+    },
+
+    #[error("所选徽章与兑换配方不匹配: {0}")]
+    SelectionMismatch(String),
+
+    #[error("兑换配方无效: {0}")]
+    InvalidRecipe(String),
+}
+
+impl RedemptionRecipe {
+    /// 由旧的固定徽章列表构造配方
+    pub fn from_required_badges(required: &[RequiredBadge]) -> Self {
+        Self {
+            terms: required
+                .iter()
+                .map(|r| RecipeTerm::Badge {
+                    badge_id: r.badge_id,
+                    quantity: r.quantity,
+                })
+                .collect(),
+            selection: SelectionPolicy::default(),
+        }
+    }
+
+    /// 校验配方结构
+    pub fn validate(&self) -> Result<(), String> {
+        if self.terms.is_empty() {
+            return Err("兑换配方至少需要一个条目".to_string());
+        }
+        if self.terms.len() > MAX_RECIPE_TERMS {
+            return Err(format!("兑换配方条目不能超过 {} 个", MAX_RECIPE_TERMS));
+        }
+
+        for (i, term) in self.terms.iter().enumerate() {
+            term.validate(false)
+                .map_err(|e| format!("配方第 {} 项无效: {}", i + 1, e))?;
+        }
+
+        Ok(())
+    }
+
+    /// 配方引用的徽章 ID
+    pub fn badge_ids(&self) -> Vec<i64> {
+        self.collect_ids(|term| match term {
+            RecipeTerm::Badge { badge_id, .. } => Some(*badge_id),
+            _ => None,
+        })
+    }
+
+    /// 配方引用的系列 ID
+    pub fn series_ids(&self) -> Vec<i64> {
+        self.collect_ids(|term| match term {
+            RecipeTerm::Series { series_id, .. } => Some(*series_id),
+            _ => None,
+        })
+    }
+
+    /// 配方引用的分类 ID
+    pub fn category_ids(&self) -> Vec<i64> {
+        self.collect_ids(|term| match term {
+            RecipeTerm::Category { category_id, .. } => Some(*category_id),
+            _ => None,
+        })
+    }
+
+    /// 配方中指定徽章的需求数量（取第一个引用该徽章的条目），未直接引用时返回 None
+    pub fn required_quantity_of(&self, badge_id: i64) -> Option<i32> {
+        self.flatten_terms().find_map(|term| match term {
+            RecipeTerm::Badge {
+                badge_id: id,
+                quantity,
+            } if *id == badge_id => Some(*quantity),
+            _ => None,
+        })
+    }
+
+    /// 展开 any_of 后的全部叶子条目
+    fn flatten_terms(&self) -> impl Iterator<Item = &RecipeTerm> {
+        self.terms.iter().flat_map(|term| match term {
+            RecipeTerm::AnyOf { options } => options.iter().collect::<Vec<_>>(),
+            other => vec![other],
+        })
+    }
+
+    fn collect_ids(&self, f: impl Fn(&RecipeTerm) -> Option<i64>) -> Vec<i64> {
+        let mut ids: Vec<i64> = self.flatten_terms().filter_map(f).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    /// 计算本次兑换消耗哪些持有徽章
+    ///
+    /// `selected` 为用户指定的消耗徽章（为空时按选择策略从全部持有中挑选）；
+    /// 指定时所选徽章必须恰好满足配方，不能多选。
+    ///
+    /// 条目按「指定徽章 → 备选 → 去重通配 → 数量通配」的顺序分配，
+    /// 让约束最强的条目先占用徽章，避免通配条目先消耗掉指定徽章。
+    pub fn plan(
+        &self,
+        holdings: &[RecipeHolding],
+        selected: &[RequiredBadge],
+    ) -> Result<Vec<PlannedConsumption>, RecipeError> {
+        self.validate().map_err(RecipeError::InvalidRecipe)?;
+        let mut pool = self.build_pool(holdings, selected)?;
+
+        let mut planned = Vec::new();
+        for term_index in self.allocation_order() {
+            let takes = allocate(&self.terms[term_index], &pool)
+                .map_err(|shortfall| shortfall.into_error(term_index))?;
+            for (slot, quantity) in takes {
+                pool[slot].remaining -= quantity;
+                planned.push(PlannedConsumption {
+                    term_index,
+                    user_badge_id: pool[slot].holding.user_badge_id,
+                    badge_id: pool[slot].holding.badge_id,
+                    quantity,
+                });
+            }
+        }
+
+        if !selected.is_empty()
+            && let Some(left) = pool.iter().find(|slot| slot.remaining > 0)
+        {
+            return Err(RecipeError::SelectionMismatch(format!(
+                "徽章 {} 多选了 {} 个",
+                left.holding.badge_id, left.remaining
+            )));
+        }
+
+        planned.sort_by_key(|p| p.term_index);
+        Ok(planned)
+    }
+
+    /// 评估用户持有徽章对配方的缺口
+    ///
+    /// 与 `plan` 使用相同的分配顺序和选择策略，但未满足的条目不中断计算，
+    /// 返回全部缺口（按条目序号排序），为空表示当前持有可以完成兑换。
+    pub fn shortfalls(&self, holdings: &[RecipeHolding]) -> Vec<RecipeShortfall> {
+        // 未指定消耗徽章时构建徽章池不会失败
+        let mut pool = self.build_pool(holdings, &[]).unwrap_or_default();
+
+        let mut shortfalls = Vec::new();
+        for term_index in self.allocation_order() {
+            match allocate(&self.terms[term_index], &pool) {
+                Ok(takes) => {
+                    for (slot, quantity) in takes {
+                        pool[slot].remaining -= quantity;
+                    }
+                }
+                Err(shortfall) => shortfalls.push(RecipeShortfall {
+                    term_index,
+                    term: self.terms[term_index].clone(),
+                    required: shortfall.required,
+                    available: shortfall.available,
+                }),
+            }
+        }
+
+        shortfalls.sort_by_key(|s| s.term_index);
+        shortfalls
+    }
+
+    /// 条目分配顺序：约束越强越先分配，避免通配条目先消耗掉指定徽章
+    fn allocation_order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.terms.len()).collect();
+        order.sort_by_key(|&i| self.terms[i].allocation_rank());
+        order
+    }
+
+    /// 构建可分配的徽章池，按选择策略排序
+    fn build_pool<'a>(
+        &self,
+        holdings: &'a [RecipeHolding],
+        selected: &[RequiredBadge],
+    ) -> Result<Vec<PoolSlot<'a>>, RecipeError> {
+        let mut pool: Vec<PoolSlot> = if selected.is_empty() {
+            holdings
+                .iter()
+                .filter(|h| h.quantity > 0)
+                .map(|holding| PoolSlot {
+                    holding,
+                    remaining: holding.quantity,
+                })
+                .collect()
+        } else {
+            let mut wanted: BTreeMap<i64, i32> = BTreeMap::new();
+            for s in selected {
+                if s.quantity < 1 {
+                    return Err(RecipeError::SelectionMismatch(format!(
+                        "徽章 {} 的数量必须大于 0",
+                        s.badge_id
+                    )));
+                }
+                *wanted.entry(s.badge_id).or_default() += s.quantity;
+            }
+
+            let mut pool = Vec::with_capacity(wanted.len());
+            for (badge_id, quantity) in wanted {
+                let holding = holdings
+                    .iter()
+                    .find(|h| h.badge_id == badge_id)
+                    .ok_or(RecipeError::BadgeNotHeld { badge_id })?;
+                if holding.quantity < quantity {
+                    return Err(RecipeError::SelectionMismatch(format!(
+                        "徽章 {} 仅持有 {} 个，选择了 {} 个",
+                        badge_id, holding.quantity, quantity
+                    )));
+                }
+                pool.push(PoolSlot {
+                    holding,
+                    remaining: quantity,
+                });
+            }
+            pool
+        };
+
+        match self.selection {
+            SelectionPolicy::SoonestExpiring => pool.sort_by_key(|slot| {
+                (
+                    slot.holding.expires_at.is_none(),
+                    slot.holding.expires_at,
+                    slot.holding.badge_id,
+                )
+            }),
+            SelectionPolicy::EarliestAcquired => {
+                pool.sort_by_key(|slot| (slot.holding.first_acquired_at, slot.holding.badge_id))
+            }
+        }
+
+        Ok(pool)
+    }
+}
+
+impl RecipeTerm {
+    fn validate(&self, nested: bool) -> Result<(), String> {
+        match self {
+            Self::Badge { quantity, .. } if *quantity < 1 => Err("数量必须大于 0".to_string()),
+            Self::Series { count, .. } | Self::Category { count, .. } if *count < 1 => {
+                Err("数量必须大于 0".to_string())
+            }
+            Self::AnyOf { .. } if nested => Err("any_of 不支持嵌套".to_string()),
+            Self::AnyOf { options } if options.len() < 2 => {
+                Err("any_of 至少需要两个备选项".to_string())
+            }
+            Self::AnyOf { options } => options.iter().try_for_each(|o| o.validate(true)),
+            _ => Ok(()),
+        }
+    }
+
+    /// 分配顺序：约束越强越先分配
+    fn allocation_rank(&self) -> u8 {
+        match self {
+            Self::Badge { .. } => 0,
+            Self::AnyOf { .. } => 1,
+            Self::Series { distinct: true, .. } | Self::Category { distinct: true, .. } => 2,
+            Self::Series { .. } | Self::Category { .. } => 3,
+        }
+    }
+}
+
+/// 徽章池中的一项：持有记录及尚可分配的数量
+struct PoolSlot<'a> {
+    holding: &'a RecipeHolding,
+    remaining: i32,
+}
+
+/// 条目未满足时的缺口
+struct Shortfall {
+    /// 指定徽章完全未持有
+    missing_badge: Option<i64>,
+    required: i32,
+    available: i32,
+}
+
+impl Shortfall {
+    fn into_error(self, term_index: usize) -> RecipeError {
+        match self.missing_badge {
+            Some(badge_id) => RecipeError::BadgeNotHeld { badge_id },
+            None => RecipeError::NotSatisfied {
+                term_index,
+                required: self.required,
+                available: self.available,
+            },
+        }
+    }
+}
+
+/// 为单个条目试分配徽章，返回 (池下标, 数量)，不修改徽章池
+fn allocate(term: &RecipeTerm, pool: &[PoolSlot]) -> Result<Vec<(usize, i32)>, Shortfall> {
+    match term {
+        RecipeTerm::Badge { badge_id, quantity } => {
+            let Some(slot) = pool.iter().position(|s| s.holding.badge_id == *badge_id) else {
+                return Err(Shortfall {
+                    missing_badge: Some(*badge_id),
+                    required: *quantity,
+                    available: 0,
+                });
+            };
+            if pool[slot].remaining < *quantity {
+                return Err(Shortfall {
+                    missing_badge: None,
+                    required: *quantity,
+                    available: pool[slot].remaining,
+                });
+            }
+            Ok(vec![(slot, *quantity)])
+        }
+        RecipeTerm::AnyOf { options } => {
+            let mut first_shortfall = None;
+            for option in options {
+                match allocate(option, pool) {
+                    Ok(takes) => return Ok(takes),
+                    Err(shortfall) => {
+                        first_shortfall.get_or_insert(shortfall);
+                    }
+                }
+            }
+            // 没有备选项的 any_of 无法满足（validate 会拒绝这类配方）
+            let Some(shortfall) = first_shortfall else {
+                return Err(Shortfall {
+                    missing_badge: None,
+                    required: 1,
+                    available: 0,
+                });
+            };
+            // 报告第一个备选项的缺口；未持有也视为数量不足，避免误导为必须持有该徽章
+            Err(Shortfall {
+                missing_badge: None,
+                ..shortfall
+            })
+        }
+        RecipeTerm::Series {
+            series_id,
+            count,
+            distinct,
+        } => allocate_wildcard(pool, *count, *distinct, |h| h.series_id == *series_id),
+        RecipeTerm::Category {
+            category_id,
+            count,
+            distinct,
+        } => allocate_wildcard(pool, *count, *distinct, |h| h.category_id == *category_id),
+    }
+}
+
+/// 通配条目：按徽章池顺序挑选匹配的徽章
+fn allocate_wildcard(
+    pool: &[PoolSlot],
+    count: i32,
+    distinct: bool,
+    matches: impl Fn(&RecipeHolding) -> bool,
+) -> Result<Vec<(usize, i32)>, Shortfall> {
+    let candidates = pool
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| s.remaining > 0 && matches(s.holding));
+
+    let mut takes = Vec::new();
+    let mut taken = 0;
+    for (slot, s) in candidates {
+        if taken == count {
+            break;
+        }
+        let quantity = if distinct {
+            1
+        } else {
+            s.remaining.min(count - taken)
+        };
+        takes.push((slot, quantity));
+        taken += quantity;
+    }
+
+    if taken < count {
+        return Err(Shortfall {
+            missing_badge: None,
+            required: count,
+            available: taken,
+        });
+    }
+    Ok(takes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use serde_json::json;
+
+    fn holding(
+        user_badge_id: i64,
+        badge_id: i64,
+        series_id: i64,
+        quantity: i32,
+        expires_in_days: Option<i64>,
+    ) -> RecipeHolding {
+        RecipeHolding {
+            user_badge_id,
+            badge_id,
+            series_id,
+            category_id: 1,
+            quantity,
+            first_acquired_at: Utc::now() - Duration::days(user_badge_id),
+            expires_at: expires_in_days.map(|d| Utc::now() + Duration::days(d)),
+        }
+    }
+
+    #[test]
+    fn test_recipe_deserialize() {
+        let recipe: RedemptionRecipe = serde_json::from_value(json!({
+            "terms": [
+                {"type": "badge", "badgeId": 1, "quantity": 2},
+                {"type": "any_of", "options": [
+                    {"type": "badge", "badgeId": 2, "quantity": 2},
+                    {"type": "badge", "badgeId": 3, "quantity": 1}
+                ]},
+                {"type": "series", "seriesId": 10, "count": 3, "distinct": true},
+                {"type": "category", "categoryId": 5, "count": 5}
+            ],
+            "selection": "earliest_acquired"
+        }))
+        .unwrap();
+
+        assert_eq!(recipe.terms.len(), 4);
+        assert_eq!(recipe.selection, SelectionPolicy::EarliestAcquired);
+        assert!(recipe.validate().is_ok());
+        assert_eq!(recipe.badge_ids(), vec![1, 2, 3]);
+        assert_eq!(recipe.series_ids(), vec![10]);
+        assert_eq!(recipe.category_ids(), vec![5]);
+        assert_eq!(recipe.required_quantity_of(3), Some(1));
+        assert_eq!(recipe.required_quantity_of(9), None);
+    }
+
+    #[test]
+    fn test_recipe_validate() {
+        let empty = RedemptionRecipe {
+            terms: vec![],
+            selection: SelectionPolicy::default(),
+        };
+        assert!(empty.validate().is_err());
+
+        let nested = RedemptionRecipe {
+            terms: vec![RecipeTerm::AnyOf {
+                options: vec![
+                    RecipeTerm::Badge {
+                        badge_id: 1,
+                        quantity: 1,
+                    },
+                    RecipeTerm::AnyOf { options: vec![] },
+                ],
+            }],
+            selection: SelectionPolicy::default(),
+        };
+        assert!(nested.validate().is_err());
+
+        let zero_count = RedemptionRecipe {
+            terms: vec![RecipeTerm::Series {
+                series_id: 1,
+                count: 0,
+                distinct: true,
+            }],
+            selection: SelectionPolicy::default(),
+        };
+        assert!(zero_count.validate().is_err());
+
+        let empty_any_of = RedemptionRecipe {
+            terms: vec![RecipeTerm::AnyOf { options: vec![] }],
+            selection: SelectionPolicy::default(),
+        };
+        assert!(empty_any_of.validate().is_err());
+        assert!(matches!(
+            empty_any_of.plan(&[holding(11, 1, 10, 1, None)], &[]),
+            Err(RecipeError::InvalidRecipe(_))
+        ));
+        assert_eq!(empty_any_of.shortfalls(&[]).len(), 1);
+    }
+
+    #[test]
+    fn test_plan_fixed_badges() {
+        let recipe = RedemptionRecipe::from_required_badges(&[
+            RequiredBadge {
+                badge_id: 1,
+                quantity: 2,
+            },
+            RequiredBadge {
+                badge_id: 2,
+                quantity: 1,
+            },
+        ]);
+        let holdings = vec![holding(11, 1, 10, 3, None), holding(12, 2, 10, 1, None)];
+
+        let plan = recipe.plan(&holdings, &[]).unwrap();
+        assert_eq!(plan.len(), 2);
+        assert_eq!((plan[0].user_badge_id, plan[0].quantity), (11, 2));
+        assert_eq!((plan[1].user_badge_id, plan[1].quantity), (12, 1));
+
+        // 未持有 → BadgeNotHeld；数量不足 → NotSatisfied
+        assert_eq!(
+            recipe.plan(&holdings[..1], &[]),
+            Err(RecipeError::BadgeNotHeld { badge_id: 2 })
+        );
+        let short = vec![holding(11, 1, 10, 1, None), holding(12, 2, 10, 1, None)];
+        assert_eq!(
+            recipe.plan(&short, &[]),
+            Err(RecipeError::NotSatisfied {
+                term_index: 0,
+                required: 2,
+                available: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_plan_any_of_prefers_first_option() {
+        let recipe = RedemptionRecipe {
+            terms: vec![RecipeTerm::AnyOf {
+                options: vec![
+                    RecipeTerm::Badge {
+                        badge_id: 1,
+                        quantity: 2,
+                    },
+                    RecipeTerm::Badge {
+                        badge_id: 2,
+                        quantity: 1,
+                    },
+                ],
+            }],
+            selection: SelectionPolicy::default(),
+        };
+
+        let plan = recipe
+            .plan(
+                &[holding(11, 1, 10, 1, None), holding(12, 2, 10, 1, None)],
+                &[],
+            )
+            .unwrap();
+        assert_eq!(plan.len(), 1);
+        assert_eq!(plan[0].badge_id, 2);
+
+        let plan = recipe
+            .plan(
+                &[holding(11, 1, 10, 2, None), holding(12, 2, 10, 1, None)],
+                &[],
+            )
+            .unwrap();
+        assert_eq!((plan[0].badge_id, plan[0].quantity), (1, 2));
+
+        assert!(matches!(
+            recipe.plan(&[holding(11, 1, 10, 1, None)], &[]),
+            Err(RecipeError::NotSatisfied { .. })
+        ));
+    }
+
+    #[test]
+    fn test_plan_distinct_series_soonest_expiring() {
+        let recipe = RedemptionRecipe {
+            terms: vec![RecipeTerm::Series {
+                series_id: 10,
+                count: 2,
+                distinct: true,
+            }],
+            selection: SelectionPolicy::SoonestExpiring,
+        };
+        let holdings = vec![
+            holding(11, 1, 10, 5, None),
+            holding(12, 2, 10, 1, Some(30)),
+            holding(13, 3, 10, 1, Some(3)),
+            holding(14, 4, 20, 1, Some(1)),
+        ];
+
+        let plan = recipe.plan(&holdings, &[]).unwrap();
+        let badges: Vec<i64> = plan.iter().map(|p| p.badge_id).collect();
+        assert_eq!(badges, vec![3, 2]);
+        assert!(plan.iter().all(|p| p.quantity == 1));
+
+        // 去重要求 3 种，系列内只有 3 种 → 满足；要求 4 种 → 不满足
+        let mut four = recipe.clone();
+        four.terms[0] = RecipeTerm::Series {
+            series_id: 10,
+            count: 4,
+            distinct: true,
+        };
+        assert_eq!(
+            four.plan(&holdings, &[]),
+            Err(RecipeError::NotSatisfied {
+                term_index: 0,
+                required: 4,
+                available: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_plan_category_quantity_after_specific_badge() {
+        // 指定徽章先分配，分类通配只能使用剩余数量
+        let recipe = RedemptionRecipe {
+            terms: vec![
+                RecipeTerm::Category {
+                    category_id: 1,
+                    count: 3,
+                    distinct: false,
+                },
+                RecipeTerm::Badge {
+                    badge_id: 1,
+                    quantity: 2,
+                },
+            ],
+            selection: SelectionPolicy::EarliestAcquired,
+        };
+        let holdings = vec![holding(11, 1, 10, 3, None), holding(12, 2, 20, 2, None)];
+
+        let plan = recipe.plan(&holdings, &[]).unwrap();
+        assert_eq!(plan[0].term_index, 0);
+        let category_total: i32 = plan
+            .iter()
+            .filter(|p| p.term_index == 0)
+            .map(|p| p.quantity)
+            .sum();
+        assert_eq!(category_total, 3);
+        let badge_1_total: i32 = plan
+            .iter()
+            .filter(|p| p.badge_id == 1)
+            .map(|p| p.quantity)
+            .sum();
+        assert_eq!(badge_1_total, 3);
+    }
+
+    #[test]
+    fn test_plan_with_user_selection() {
+        let recipe = RedemptionRecipe {
+            terms: vec![RecipeTerm::Series {
+                series_id: 10,
+                count: 2,
+                distinct: true,
+            }],
+            selection: SelectionPolicy::SoonestExpiring,
+        };
+        let holdings = vec![
+            holding(11, 1, 10, 1, Some(1)),
+            holding(12, 2, 10, 1, None),
+            holding(13, 3, 10, 1, None),
+        ];
+        let select = |ids: &[i64]| -> Vec<RequiredBadge> {
+            ids.iter()
+                .map(|&badge_id| RequiredBadge {
+                    badge_id,
+                    quantity: 1,
+                })
+                .collect()
+        };
+
+        // 用户选择覆盖策略：不消耗即将过期的徽章 1
+        let plan = recipe.plan(&holdings, &select(&[2, 3])).unwrap();
+        let mut badges: Vec<i64> = plan.iter().map(|p| p.badge_id).collect();
+        badges.sort_unstable();
+        assert_eq!(badges, vec![2, 3]);
+
+        // 多选、未持有、选择不足
+        assert!(matches!(
+            recipe.plan(&holdings, &select(&[1, 2, 3])),
+            Err(RecipeError::SelectionMismatch(_))
+        ));
+        assert_eq!(
+            recipe.plan(&holdings, &select(&[2, 9])),
+            Err(RecipeError::BadgeNotHeld { badge_id: 9 })
+        );
+        assert!(matches!(
+            recipe.plan(&holdings, &select(&[2])),
+            Err(RecipeError::NotSatisfied { .. })
+        ));
+    }
+
+    #[test]
+    fn test_shortfalls_reports_every_unmet_term() {
+        let recipe = RedemptionRecipe {
+            terms: vec![
+                RecipeTerm::Badge {
+                    badge_id: 1,
+                    quantity: 2,
+                },
+                RecipeTerm::Series {
+                    series_id: 10,
+                    count: 3,
+                    distinct: false,
+                },
+                RecipeTerm::Badge {
+                    badge_id: 9,
+                    quantity: 1,
+                },
+            ],
+            selection: SelectionPolicy::default(),
+        };
+        // 徽章 1 先被指定条目占用 2 个，系列通配只剩 1 + 1 个
+        let holdings = vec![holding(11, 1, 10, 3, None), holding(12, 2, 10, 1, None)];
+
+        let shortfalls = recipe.shortfalls(&holdings);
+        assert_eq!(shortfalls.len(), 2);
+        assert_eq!(
+            (
+                shortfalls[0].term_index,
+                shortfalls[0].required,
+                shortfalls[0].available
+            ),
+            (1, 3, 2)
+        );
+        assert_eq!(
+            (
+                shortfalls[1].term_index,
+                shortfalls[1].required,
+                shortfalls[1].available
+            ),
+            (2, 1, 0)
+        );
+
+        let enough = vec![holding(11, 1, 10, 5, None), holding(13, 9, 20, 1, None)];
+        assert!(recipe.shortfalls(&enough).is_empty());
+        assert!(recipe.plan(&enough, &[]).is_ok());
+    }
+}
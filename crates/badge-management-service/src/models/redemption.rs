@@ -2,11 +2,13 @@
 //!
 //! 包含兑换规则、兑换订单、权益定义等
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, Days, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use super::enums::{BenefitType, OrderStatus};
+use super::recipe::RedemptionRecipe;
 
 /// 权益状态
 ///
@@ -107,6 +109,70 @@ pub struct FrequencyConfig {
     pub max_per_month: Option<i32>,
 }
 
+impl FrequencyConfig {
+    /// 已配置的限制，按统计周期从短到长排列
+    pub fn limits(&self) -> Vec<(FrequencyPeriod, i32)> {
+        [
+            (FrequencyPeriod::Day, self.max_per_day),
+            (FrequencyPeriod::Week, self.max_per_week),
+            (FrequencyPeriod::Month, self.max_per_month),
+            (FrequencyPeriod::Lifetime, self.max_per_user),
+        ]
+        .into_iter()
+        .filter_map(|(period, max)| max.map(|max| (period, max)))
+        .collect()
+    }
+
+    /// 是否配置了任一限制
+    pub fn is_limited(&self) -> bool {
+        !self.limits().is_empty()
+    }
+}
+
+/// 频率限制的统计周期
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrequencyPeriod {
+    /// 自然日
+    Day,
+    /// 自然周（周一开始）
+    Week,
+    /// 自然月
+    Month,
+    /// 不限周期（单用户总次数）
+    Lifetime,
+}
+
+impl FrequencyPeriod {
+    /// 限制类型标识，用于错误信息
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Day => "per_day",
+            Self::Week => "per_week",
+            Self::Month => "per_month",
+            Self::Lifetime => "per_user",
+        }
+    }
+
+    /// 当前周期在指定时区的起点，不限周期返回 None
+    pub fn start(&self, now: DateTime<Utc>, tz: Tz) -> Option<DateTime<Utc>> {
+        let today = now.with_timezone(&tz).date_naive();
+        let first_day = match self {
+            Self::Lifetime => return None,
+            Self::Day => today,
+            Self::Week => today - Days::new(u64::from(today.weekday().num_days_from_monday())),
+            Self::Month => today.with_day(1)?,
+        };
+        let midnight = first_day.and_time(NaiveTime::MIN);
+        // 夏令时切换可能跳过本地零点，此时按 UTC 零点处理
+        let start = tz
+            .from_local_datetime(&midnight)
+            .earliest()
+            .map(|t| t.with_timezone(&Utc))
+            .unwrap_or_else(|| midnight.and_utc());
+        Some(start)
+    }
+}
+
 /// 徽章兑换规则
 ///
 /// 定义如何用徽章兑换权益
@@ -124,6 +190,9 @@ pub struct BadgeRedemptionRule {
     /// 需要的徽章配置（JSON）
     /// 格式：[{ "badgeId": 1, "quantity": 2 }, { "badgeId": 2, "quantity": 1 }]
     pub required_badges: Value,
+    /// 兑换配方（JSON，可选），配置后取代 required_badges，支持备选、系列/分类通配
+    #[sqlx(default)]
+    pub recipe: Option<Value>,
     /// 频率限制配置（JSON）
     pub frequency_config: Value,
     /// 有效期类型：FIXED-固定时间段，RELATIVE-相对徽章获取时间
@@ -158,8 +227,29 @@ impl BadgeRedemptionRule {
         serde_json::from_value(self.required_badges.clone())
     }
 
-    /// 解析频率限制配置
+    /// 解析兑换配方，未配置时由 required_badges 构造
+    ///
+    /// 配置的配方同时做结构校验，绕过管理后台直接写库的非法配方按解析失败处理。
+    pub fn parse_recipe(&self) -> Result<RedemptionRecipe, serde_json::Error> {
+        match &self.recipe {
+            Some(recipe) if !recipe.is_null() => {
+                let recipe: RedemptionRecipe = serde_json::from_value(recipe.clone())?;
+                recipe
+                    .validate()
+                    .map_err(<serde_json::Error as serde::de::Error>::custom)?;
+                Ok(recipe)
+            }
+            _ => Ok(RedemptionRecipe::from_required_badges(
+                &self.parse_required_badges()?,
+            )),
+        }
+    }
+
+    /// 解析频率限制配置，未配置（NULL）时视为不限制
     pub fn parse_frequency_config(&self) -> Result<FrequencyConfig, serde_json::Error> {
+        if self.frequency_config.is_null() {
+            return Ok(FrequencyConfig::default());
+        }
         serde_json::from_value(self.frequency_config.clone())
     }
 
@@ -233,6 +323,9 @@ pub struct RedemptionDetail {
     pub badge_id: i64,
     /// 消耗数量
     pub quantity: i32,
+    /// 满足的兑换配方条目序号（从 0 开始），配方上线前的明细为空
+    #[sqlx(default)]
+    pub recipe_term: Option<i32>,
     pub created_at: DateTime<Utc>,
 }
 
@@ -320,6 +413,65 @@ mod tests {
         assert_eq!(badges[1].quantity, 1);
     }
 
+    #[test]
+    fn test_redemption_rule_parse_recipe() {
+        let mut rule = create_test_redemption_rule();
+
+        // 未配置配方时等价于固定徽章列表
+        let recipe = rule.parse_recipe().unwrap();
+        assert_eq!(recipe.badge_ids(), vec![1, 2]);
+
+        rule.recipe = Some(json!({
+            "terms": [{"type": "series", "seriesId": 7, "count": 3, "distinct": true}]
+        }));
+        let recipe = rule.parse_recipe().unwrap();
+        assert!(recipe.badge_ids().is_empty());
+        assert_eq!(recipe.series_ids(), vec![7]);
+
+        rule.recipe = Some(json!({"terms": [{"type": "any_of", "options": []}]}));
+        assert!(rule.parse_recipe().is_err());
+    }
+
+    #[test]
+    fn test_frequency_config_limits() {
+        let mut rule = create_test_redemption_rule();
+        let config = rule.parse_frequency_config().unwrap();
+        assert_eq!(
+            config.limits(),
+            vec![(FrequencyPeriod::Day, 1), (FrequencyPeriod::Lifetime, 5)]
+        );
+
+        rule.frequency_config = Value::Null;
+        assert!(!rule.parse_frequency_config().unwrap().is_limited());
+    }
+
+    #[test]
+    fn test_frequency_period_start() {
+        let tz = chrono_tz::Asia::Shanghai;
+        // 北京时间 2025-03-05（周三）07:30
+        let now = Utc.with_ymd_and_hms(2025, 3, 4, 23, 30, 0).unwrap();
+
+        assert_eq!(
+            FrequencyPeriod::Day.start(now, tz),
+            Some(Utc.with_ymd_and_hms(2025, 3, 4, 16, 0, 0).unwrap())
+        );
+        assert_eq!(
+            FrequencyPeriod::Week.start(now, tz),
+            Some(Utc.with_ymd_and_hms(2025, 3, 2, 16, 0, 0).unwrap())
+        );
+        assert_eq!(
+            FrequencyPeriod::Month.start(now, tz),
+            Some(Utc.with_ymd_and_hms(2025, 2, 28, 16, 0, 0).unwrap())
+        );
+        assert_eq!(FrequencyPeriod::Lifetime.start(now, tz), None);
+
+        // 同一时刻在 UTC 下仍是 3 月 4 日
+        assert_eq!(
+            FrequencyPeriod::Day.start(now, chrono_tz::UTC),
+            Some(Utc.with_ymd_and_hms(2025, 3, 4, 0, 0, 0).unwrap())
+        );
+    }
+
     #[test]
     fn test_redemption_rule_is_active() {
         let now = Utc::now();
@@ -389,6 +541,7 @@ mod tests {
                 {"badgeId": 1, "quantity": 2},
                 {"badgeId": 2, "quantity": 1}
             ]),
+            recipe: None,
             frequency_config: json!({"maxPerUser": 5, "maxPerDay": 1}),
             validity_type: None,
             relative_days: None,
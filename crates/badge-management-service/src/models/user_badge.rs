@@ -43,6 +43,10 @@ pub struct UserBadge {
     /// 实际使用人 ID（当 recipient_type=USER 时必填）
     #[sqlx(default)]
     pub actual_user_id: Option<String>,
+    /// 分级徽章当前等级，非分级徽章为 None
+    #[sqlx(default)]
+    #[serde(default)]
+    pub level: Option<i32>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -294,6 +298,7 @@ mod tests {
             expired_at: None,
             recipient_type: RecipientType::Owner,
             actual_user_id: None,
+            level: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         }
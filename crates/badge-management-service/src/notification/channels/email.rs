@@ -1,18 +1,22 @@
 //! Email 邮件通知渠道
 //!
-//! 通过邮件服务发送邮件通知。
-//! 当前为模拟实现，生产环境需要接入真实的邮件服务（如 SendGrid、AWS SES）。
+//! 通过 SMTP 发送邮件通知，连接参数来自 `AppConfig.smtp`。
+//! 邮件同时包含纯文本和 HTML 两种正文，收件人地址从通知业务数据的 `email` 字段读取，
+//! 该字段由 `NotificationService` 发送前按用户联系方式补全。
 
 use std::time::Instant;
 
 use async_trait::async_trait;
 use tracing::{debug, info, warn};
-use uuid::Uuid;
 
+use badge_shared::config::SmtpConfig;
+use badge_shared::email::{self, EmailError, EmailMessage, SmtpMailer};
 use badge_shared::events::NotificationChannel as ChannelType;
+pub use badge_shared::notification_contacts::EMAIL_DATA_KEY;
+use badge_shared::notification_templates::HTML_BODY_DATA_KEY;
 
 use super::{ChannelConfig, ChannelResult, NotificationChannel};
-use crate::error::Result;
+use crate::error::{BadgeError, Result};
 use crate::notification::types::Notification;
 
 /// Email 邮件通知渠道
@@ -21,99 +25,77 @@ use crate::notification::types::Notification;
 /// 支持 HTML 格式的邮件内容。
 pub struct EmailChannel {
     config: ChannelConfig,
-    /// 发件人地址
-    from_address: String,
-    /// 发件人名称（用于构建邮件头 From 字段）
-    #[allow(dead_code)]
-    from_name: String,
+    mailer: SmtpMailer,
 }
 
 impl EmailChannel {
-    pub fn new(config: ChannelConfig, from_address: String, from_name: String) -> Self {
-        Self {
-            config,
-            from_address,
-            from_name,
-        }
+    pub fn new(config: ChannelConfig, mailer: SmtpMailer) -> Self {
+        Self { config, mailer }
+    }
+
+    /// 根据 SMTP 配置创建，SMTP 未启用时渠道处于禁用状态
+    pub fn from_smtp_config(smtp: &SmtpConfig) -> Result<Self> {
+        let mailer = SmtpMailer::from_config(smtp)
+            .map_err(|e| BadgeError::Internal(format!("SMTP 配置无效: {}", e)))?;
+        Ok(Self::new(
+            ChannelConfig::new(smtp.enabled).with_timeout(smtp.timeout_ms),
+            mailer,
+        ))
     }
 
     /// 使用默认配置创建
+    ///
+    /// 默认 SMTP 配置未启用，需通过 `from_smtp_config` 接入真实邮件服务器
     pub fn with_defaults() -> Self {
-        Self::new(
-            ChannelConfig::new(true).with_timeout(10000),
-            "noreply@badge-system.com".to_string(),
-            "徽章系统".to_string(),
-        )
+        Self::from_smtp_config(&SmtpConfig::default()).expect("默认 SMTP 配置必然有效")
+    }
+
+    /// 读取收件人邮箱
+    fn recipient(notification: &Notification) -> Option<&str> {
+        notification
+            .data
+            .get(EMAIL_DATA_KEY)
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
     }
 
     /// 构建 HTML 邮件内容
+    ///
+    /// 模板提供了 HTML 正文时直接使用，否则由纯文本正文转义生成
     fn build_html_content(&self, notification: &Notification) -> String {
-        format!(
-            r#"<!DOCTYPE html>
-<html>
-<head>
-    <meta charset="UTF-8">
-    <title>{}</title>
-    <style>
-        body {{ font-family: Arial, sans-serif; line-height: 1.6; color: #333; }}
-        .container {{ max-width: 600px; margin: 0 auto; padding: 20px; }}
-        .header {{ background: linear-gradient(135deg, #667eea 0%, #764ba2 100%); color: white; padding: 20px; border-radius: 8px 8px 0 0; }}
-        .content {{ background: #f9f9f9; padding: 20px; border-radius: 0 0 8px 8px; }}
-        .footer {{ text-align: center; color: #888; font-size: 12px; margin-top: 20px; }}
-    </style>
-</head>
-<body>
-    <div class="container">
-        <div class="header">
-            <h1>{}</h1>
-        </div>
-        <div class="content">
-            <p>{}</p>
-        </div>
-        <div class="footer">
-            <p>此邮件由徽章系统自动发送，请勿回复。</p>
-        </div>
-    </div>
-</body>
-</html>"#,
-            notification.title, notification.title, notification.body
-        )
+        match notification
+            .data
+            .get(HTML_BODY_DATA_KEY)
+            .and_then(|v| v.as_str())
+        {
+            Some(html) => email::render_html_content(&notification.title, html),
+            None => email::render_html(&notification.title, &notification.body),
+        }
     }
 
-    /// 模拟发送邮件（生产环境应接入真实邮件服务）
-    async fn send_email(&self, notification: &Notification) -> Result<String> {
-        // 模拟网络延迟
-        tokio::time::sleep(tokio::time::Duration::from_millis(30)).await;
-
-        let html_content = self.build_html_content(notification);
+    /// 通过 SMTP 发送邮件，返回邮件的 Message-ID
+    async fn send_email(&self, notification: &Notification, to: &str) -> Result<String> {
+        let message = EmailMessage {
+            to: to.to_string(),
+            subject: notification.title.clone(),
+            text_body: email::render_text(&notification.title, &notification.body),
+            html_body: self.build_html_content(notification),
+        };
 
         debug!(
             notification_id = %notification.notification_id,
             user_id = %notification.user_id,
-            from = %self.from_address,
+            from = %self.mailer.from(),
             subject = %notification.title,
-            content_length = html_content.len(),
+            content_length = message.html_body.len(),
             "Email 发送中..."
         );
 
-        // 模拟随机失败
-        #[cfg(test)]
-        if notification.user_id.contains("fail_email") {
-            return Err(crate::error::BadgeError::Internal(
-                "模拟 Email 发送失败".to_string(),
-            ));
-        }
-
-        // 模拟无效邮箱
-        #[cfg(test)]
-        if notification.user_id.contains("invalid_email") {
-            return Err(crate::error::BadgeError::Validation(
-                "用户邮箱地址无效".to_string(),
-            ));
-        }
-
-        // 生成模拟的消息 ID
-        let message_id = format!("email_{}", Uuid::new_v4());
+        let message_id = self.mailer.send(&message).await.map_err(|e| match e {
+            EmailError::InvalidAddress(_) => BadgeError::Validation(e.to_string()),
+            _ => BadgeError::Internal(e.to_string()),
+        })?;
 
         info!(
             notification_id = %notification.notification_id,
@@ -144,9 +126,7 @@ impl NotificationChannel for EmailChannel {
             return false;
         }
 
-        // 实际实现应检查用户是否有有效的邮箱地址
-        // 当前模拟实现：用户 ID 包含 "no_email" 则视为无邮箱
-        if notification.user_id.contains("no_email") {
+        if Self::recipient(notification).is_none() {
             warn!(
                 notification_id = %notification.notification_id,
                 user_id = %notification.user_id,
@@ -161,14 +141,17 @@ impl NotificationChannel for EmailChannel {
     async fn send(&self, notification: &Notification) -> Result<ChannelResult> {
         let start = Instant::now();
 
-        if !self.is_available(notification).await {
-            return Ok(ChannelResult::skipped(
-                self.channel_type(),
-                "用户未绑定邮箱或渠道已禁用",
-            ));
-        }
-
-        match self.send_email(notification).await {
+        let recipient = match Self::recipient(notification) {
+            Some(to) if self.is_available(notification).await => to,
+            _ => {
+                return Ok(ChannelResult::skipped(
+                    self.channel_type(),
+                    "用户未绑定邮箱或渠道已禁用",
+                ));
+            }
+        };
+
+        match self.send_email(notification, recipient).await {
             Ok(message_id) => Ok(ChannelResult::success(
                 self.channel_type(),
                 Some(message_id),
@@ -186,15 +169,33 @@ impl NotificationChannel for EmailChannel {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::notification::types::SendStatus;
+    use badge_shared::config::SmtpTlsMode;
     use badge_shared::events::NotificationType;
 
-    fn create_test_notification(user_id: &str) -> Notification {
-        Notification::new(
-            user_id,
+    fn create_test_notification(email: Option<&str>) -> Notification {
+        let notification = Notification::new(
+            "user-123",
             NotificationType::BadgeGranted,
             "测试标题",
             "测试内容",
-        )
+        );
+        match email {
+            Some(email) => notification.with_data(EMAIL_DATA_KEY, serde_json::json!(email)),
+            None => notification,
+        }
+    }
+
+    /// 指向无人监听端口的已启用渠道
+    fn unreachable_channel() -> EmailChannel {
+        EmailChannel::from_smtp_config(&SmtpConfig {
+            enabled: true,
+            host: "127.0.0.1".into(),
+            port: 1,
+            tls: SmtpTlsMode::None,
+            ..Default::default()
+        })
+        .unwrap()
     }
 
     #[tokio::test]
@@ -202,48 +203,50 @@ mod tests {
         let channel = EmailChannel::with_defaults();
         assert_eq!(channel.channel_type(), ChannelType::Email);
         assert_eq!(channel.name(), "Email");
-        assert_eq!(channel.from_address, "noreply@badge-system.com");
+        assert_eq!(
+            channel.mailer.from().email.to_string(),
+            "noreply@badge-system.com"
+        );
+        assert!(!channel.config.enabled);
     }
 
     #[tokio::test]
-    async fn test_email_send_success() {
-        let channel = EmailChannel::with_defaults();
-        let notification = create_test_notification("user-123");
+    async fn test_email_user_no_email() {
+        let channel = unreachable_channel();
+        let notification = create_test_notification(None);
 
-        let result = channel.send(&notification).await.unwrap();
+        assert!(!channel.is_available(&notification).await);
 
-        assert_eq!(result.channel, ChannelType::Email);
-        assert_eq!(result.status, crate::notification::types::SendStatus::Success);
-        assert!(result.external_message_id.is_some());
-        assert!(result.external_message_id.unwrap().starts_with("email_"));
+        let result = channel.send(&notification).await.unwrap();
+        assert_eq!(result.status, SendStatus::Skipped);
     }
 
     #[tokio::test]
-    async fn test_email_user_no_email() {
-        let channel = EmailChannel::with_defaults();
-        let notification = create_test_notification("user_no_email_123");
-
-        assert!(!channel.is_available(&notification).await);
+    async fn test_email_send_failure() {
+        let channel = unreachable_channel();
+        let notification = create_test_notification(Some("user@example.com"));
 
         let result = channel.send(&notification).await.unwrap();
-        assert_eq!(result.status, crate::notification::types::SendStatus::Skipped);
+
+        assert_eq!(result.status, SendStatus::Failed);
+        assert!(result.error.unwrap().contains("SMTP"));
     }
 
     #[tokio::test]
-    async fn test_email_send_failure() {
-        let channel = EmailChannel::with_defaults();
-        let notification = create_test_notification("fail_email_user");
+    async fn test_email_invalid_address() {
+        let channel = unreachable_channel();
+        let notification = create_test_notification(Some("not an address"));
 
         let result = channel.send(&notification).await.unwrap();
 
-        assert_eq!(result.status, crate::notification::types::SendStatus::Failed);
-        assert!(result.error.is_some());
+        assert_eq!(result.status, SendStatus::Failed);
+        assert!(result.error.unwrap().contains("邮件地址无效"));
     }
 
     #[tokio::test]
     async fn test_email_build_html_content() {
         let channel = EmailChannel::with_defaults();
-        let notification = create_test_notification("user-123");
+        let notification = create_test_notification(None);
 
         let html = channel.build_html_content(&notification);
 
@@ -254,15 +257,26 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_email_disabled() {
-        let config = ChannelConfig::new(false);
-        let channel = EmailChannel::new(
-            config,
-            "test@example.com".to_string(),
-            "Test".to_string(),
+    async fn test_email_build_html_content_from_template() {
+        let channel = EmailChannel::with_defaults();
+        let notification = create_test_notification(None).with_data(
+            HTML_BODY_DATA_KEY,
+            serde_json::json!("<ul><li>首次购物</li></ul>"),
         );
-        let notification = create_test_notification("user-123");
+
+        let html = channel.build_html_content(&notification);
+
+        assert!(html.contains("<ul><li>首次购物</li></ul>"));
+        assert!(!html.contains(&notification.body));
+    }
+
+    #[tokio::test]
+    async fn test_email_disabled() {
+        let channel = EmailChannel::with_defaults();
+        let notification = create_test_notification(Some("user@example.com"));
 
         assert!(!channel.is_available(&notification).await);
+        let result = channel.send(&notification).await.unwrap();
+        assert_eq!(result.status, SendStatus::Skipped);
     }
 }
@@ -15,7 +15,7 @@ mod sms;
 mod wechat;
 
 pub use app_push::AppPushChannel;
-pub use email::EmailChannel;
+pub use email::{EMAIL_DATA_KEY, EmailChannel};
 pub use sms::SmsChannel;
 pub use wechat::WeChatChannel;
 
@@ -14,7 +14,7 @@ use std::sync::Arc;
 
 use badge_shared::events::NotificationChannel as ChannelType;
 use chrono::{DateTime, Utc};
-use tracing::{error, info, warn};
+use tracing::{Instrument, error, info, warn};
 
 use super::service::NotificationService;
 use super::types::{Notification, NotificationBuilder};
@@ -62,6 +62,19 @@ impl NotificationSender {
         self.send_async(notification);
     }
 
+    /// 发送徽章暂停通知
+    pub fn send_badge_suspended(
+        &self,
+        user_id: &str,
+        badge_id: i64,
+        badge_name: &str,
+        reason: &str,
+    ) {
+        let notification =
+            NotificationBuilder::badge_suspended(user_id, badge_id, badge_name, reason);
+        self.send_async(notification);
+    }
+
     /// 发送兑换成功通知
     ///
     /// 在兑换成功后调用
@@ -110,13 +123,15 @@ impl NotificationSender {
     }
 
     /// 异步发送通知（fire-and-forget）
+    ///
+    /// 后台任务沿用当前 span，通知事件仍归属于触发它的请求链路
     fn send_async(&self, notification: Notification) {
         let service = self.service.clone();
         let notification_id = notification.notification_id.clone();
         let user_id = notification.user_id.clone();
         let notification_type = notification.notification_type.clone();
 
-        tokio::spawn(async move {
+        let task = async move {
             match service.send(notification).await {
                 Ok(result) => {
                     if result.success {
@@ -151,7 +166,8 @@ impl NotificationSender {
                     );
                 }
             }
-        });
+        };
+        tokio::spawn(task.in_current_span());
     }
 }
 
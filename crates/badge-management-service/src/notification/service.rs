@@ -8,16 +8,24 @@
 //! - **多渠道并行**：各渠道独立发送，互不影响
 //! - **部分失败容忍**：单渠道失败不影响其他渠道
 //! - **Kafka 集成**：发送结果通过 Kafka 传递给下游消费者
+//! - **用户偏好**：发送前按用户偏好筛选渠道，免打扰时段内的非紧急通知延迟投递
+//! - **收件人**：发送前按用户 ID 查询联系方式，补全各渠道的收件人字段
+//! - **模板渲染**：按用户语言和渠道选择模板，与 notification-worker 共用模板定义
 
 use std::sync::Arc;
 use std::time::Instant;
 
-use badge_shared::events::{NotificationChannel as ChannelType, NotificationEvent};
+use badge_shared::events::NotificationChannel as ChannelType;
 use badge_shared::kafka::{topics, KafkaProducer};
+use badge_shared::notification_contacts::ContactStore;
+use badge_shared::notification_preferences::{
+    DeferredNotificationStore, PreferenceStore, DEFAULT_LOCALE, LOCALE_DATA_KEY,
+};
+use badge_shared::notification_templates::{TemplateSet, HTML_BODY_DATA_KEY};
 use chrono::Utc;
 use futures::future::join_all;
 use tokio::sync::RwLock;
-use tracing::{debug, error, info, instrument, warn};
+use tracing::{Instrument, debug, error, info, instrument, warn};
 
 use super::channels::{
     AppPushChannel, EmailChannel, NotificationChannel, SmsChannel, WeChatChannel,
@@ -36,6 +44,12 @@ pub struct NotificationService {
     template_engine: Arc<TemplateEngine>,
     /// Kafka 生产者（用于发送通知事件）
     kafka_producer: Option<Arc<KafkaProducer>>,
+    /// 用户通知偏好
+    preference_store: Option<Arc<dyn PreferenceStore>>,
+    /// 免打扰时段内的延迟投递队列
+    deferred_store: Option<Arc<dyn DeferredNotificationStore>>,
+    /// 用户联系方式
+    contact_store: Option<Arc<dyn ContactStore>>,
     /// 是否启用异步发送
     async_enabled: RwLock<bool>,
 }
@@ -47,6 +61,9 @@ impl NotificationService {
             channels: Vec::new(),
             template_engine,
             kafka_producer: None,
+            preference_store: None,
+            deferred_store: None,
+            contact_store: None,
             async_enabled: RwLock::new(true),
         }
     }
@@ -60,12 +77,36 @@ impl NotificationService {
         service
     }
 
+    /// 替换模板引擎
+    pub fn with_template_engine(mut self, template_engine: Arc<TemplateEngine>) -> Self {
+        self.template_engine = template_engine;
+        self
+    }
+
     /// 设置 Kafka 生产者
     pub fn with_kafka_producer(mut self, producer: Arc<KafkaProducer>) -> Self {
         self.kafka_producer = Some(producer);
         self
     }
 
+    /// 设置用户偏好存储，发送前按偏好筛选渠道
+    pub fn with_preference_store(mut self, store: Arc<dyn PreferenceStore>) -> Self {
+        self.preference_store = Some(store);
+        self
+    }
+
+    /// 设置延迟投递队列，未设置时免打扰时段内的通知仍立即发送
+    pub fn with_deferred_store(mut self, store: Arc<dyn DeferredNotificationStore>) -> Self {
+        self.deferred_store = Some(store);
+        self
+    }
+
+    /// 设置联系方式存储，发送前按用户补全收件人
+    pub fn with_contact_store(mut self, store: Arc<dyn ContactStore>) -> Self {
+        self.contact_store = Some(store);
+        self
+    }
+
     /// 注册默认渠道
     fn register_default_channels(&mut self) {
         self.register_channel(Arc::new(AppPushChannel::with_defaults()));
@@ -74,6 +115,15 @@ impl NotificationService {
         self.register_channel(Arc::new(WeChatChannel::with_defaults()));
     }
 
+    /// 替换邮件渠道
+    ///
+    /// 默认邮件渠道未接入 SMTP，服务启动时用配置构建的渠道替换
+    pub fn with_email_channel(mut self, channel: EmailChannel) -> Self {
+        self.channels.retain(|c| c.channel_type() != ChannelType::Email);
+        self.register_channel(Arc::new(channel));
+        self
+    }
+
     /// 注册通知渠道
     pub fn register_channel(&mut self, channel: Arc<dyn NotificationChannel>) {
         info!(
@@ -117,11 +167,24 @@ impl NotificationService {
         // 渲染通知内容
         let rendered = self.render_notification(&notification);
 
+        // 按用户偏好筛选渠道，免打扰时段内延迟投递
+        let (rendered, skipped) = match apply_user_preferences(
+            self.preference_store.as_ref(),
+            self.deferred_store.as_ref(),
+            rendered,
+        )
+        .await
+        {
+            PreparedNotification::Send(rendered, skipped) => (rendered, skipped),
+            PreparedNotification::Done(result) => return Ok(result),
+        };
+        let rendered = resolve_recipients(self.contact_store.as_ref(), rendered).await;
+
         // 筛选出需要发送的渠道
         let target_channels: Vec<_> = self
             .channels
             .iter()
-            .filter(|c| notification.channels.contains(&c.channel_type()))
+            .filter(|c| rendered.channels.contains(&c.channel_type()))
             .cloned()
             .collect();
 
@@ -129,7 +192,7 @@ impl NotificationService {
             warn!("没有匹配的渠道可用");
             return Ok(NotificationResult::success(
                 notification.notification_id.clone(),
-                vec![],
+                skipped,
                 start.elapsed().as_millis() as u64,
             ));
         }
@@ -139,12 +202,14 @@ impl NotificationService {
             "找到匹配的渠道"
         );
 
-        // 并行发送到所有渠道
+        // 按渠道渲染后并行发送到所有渠道
+        let templates = self.template_engine.templates().await;
         let send_futures: Vec<_> = target_channels
             .iter()
             .map(|channel| {
                 let channel = channel.clone();
-                let notification = rendered.clone();
+                let notification =
+                    render_for_channel(&templates, &rendered, &channel.channel_type());
                 async move {
                     let result = channel.send(&notification).await;
                     (channel.channel_type(), result)
@@ -155,7 +220,7 @@ impl NotificationService {
         let results = join_all(send_futures).await;
 
         // 收集发送结果
-        let channel_results: Vec<ChannelResult> = results
+        let mut channel_results: Vec<ChannelResult> = results
             .into_iter()
             .map(|(channel_type, result)| match result {
                 Ok(r) => r,
@@ -169,6 +234,7 @@ impl NotificationService {
                 }
             })
             .collect();
+        channel_results.extend(skipped);
 
         let duration_ms = start.elapsed().as_millis() as u64;
         let notification_result = NotificationResult::success(
@@ -216,11 +282,14 @@ impl NotificationService {
     pub fn send_async(&self, notification: Notification) {
         let service = self.clone_for_async();
 
-        tokio::spawn(async move {
-            if let Err(e) = service.send(notification).await {
-                error!(error = %e, "异步发送通知失败");
+        tokio::spawn(
+            async move {
+                if let Err(e) = service.send(notification).await {
+                    error!(error = %e, "异步发送通知失败");
+                }
             }
-        });
+            .in_current_span(),
+        );
     }
 
     /// 克隆服务用于异步发送
@@ -229,6 +298,9 @@ impl NotificationService {
             channels: self.channels.clone(),
             template_engine: self.template_engine.clone(),
             kafka_producer: self.kafka_producer.clone(),
+            preference_store: self.preference_store.clone(),
+            deferred_store: self.deferred_store.clone(),
+            contact_store: self.contact_store.clone(),
         }
     }
 
@@ -294,22 +366,7 @@ impl NotificationService {
             return Ok(());
         };
 
-        let event = NotificationEvent {
-            notification_id: notification.notification_id.clone(),
-            user_id: notification.user_id.clone(),
-            notification_type: notification.notification_type.clone(),
-            title: notification.title.clone(),
-            body: notification.body.clone(),
-            data: serde_json::Value::Object(
-                notification
-                    .data
-                    .iter()
-                    .map(|(k, v)| (k.clone(), v.clone()))
-                    .collect(),
-            ),
-            channels: notification.channels.clone(),
-            created_at: Utc::now(),
-        };
+        let event = notification.to_event();
 
         producer
             .send_json(
@@ -345,6 +402,9 @@ struct NotificationServiceAsync {
     channels: Vec<Arc<dyn NotificationChannel>>,
     template_engine: Arc<TemplateEngine>,
     kafka_producer: Option<Arc<KafkaProducer>>,
+    preference_store: Option<Arc<dyn PreferenceStore>>,
+    deferred_store: Option<Arc<dyn DeferredNotificationStore>>,
+    contact_store: Option<Arc<dyn ContactStore>>,
 }
 
 impl NotificationServiceAsync {
@@ -364,20 +424,35 @@ impl NotificationServiceAsync {
         rendered.title = rendered_title;
         rendered.body = rendered_body;
 
+        // 按用户偏好筛选渠道，免打扰时段内延迟投递
+        let (rendered, skipped) = match apply_user_preferences(
+            self.preference_store.as_ref(),
+            self.deferred_store.as_ref(),
+            rendered,
+        )
+        .await
+        {
+            PreparedNotification::Send(rendered, skipped) => (rendered, skipped),
+            PreparedNotification::Done(result) => return Ok(result),
+        };
+        let rendered = resolve_recipients(self.contact_store.as_ref(), rendered).await;
+
         // 筛选渠道
         let target_channels: Vec<_> = self
             .channels
             .iter()
-            .filter(|c| notification.channels.contains(&c.channel_type()))
+            .filter(|c| rendered.channels.contains(&c.channel_type()))
             .cloned()
             .collect();
 
-        // 并行发送
+        // 按渠道渲染后并行发送
+        let templates = self.template_engine.templates().await;
         let send_futures: Vec<_> = target_channels
             .iter()
             .map(|channel| {
                 let channel = channel.clone();
-                let notification = rendered.clone();
+                let notification =
+                    render_for_channel(&templates, &rendered, &channel.channel_type());
                 async move {
                     let result = channel.send(&notification).await;
                     (channel.channel_type(), result)
@@ -387,13 +462,14 @@ impl NotificationServiceAsync {
 
         let results = join_all(send_futures).await;
 
-        let channel_results: Vec<ChannelResult> = results
+        let mut channel_results: Vec<ChannelResult> = results
             .into_iter()
             .map(|(channel_type, result)| match result {
                 Ok(r) => r,
                 Err(e) => ChannelResult::failed(channel_type, e.to_string(), 0),
             })
             .collect();
+        channel_results.extend(skipped);
 
         let duration_ms = start.elapsed().as_millis() as u64;
         let notification_result = NotificationResult::success(
@@ -404,22 +480,7 @@ impl NotificationServiceAsync {
 
         // 发布到 Kafka
         if let Some(ref producer) = self.kafka_producer {
-            let event = NotificationEvent {
-                notification_id: rendered.notification_id.clone(),
-                user_id: rendered.user_id.clone(),
-                notification_type: rendered.notification_type.clone(),
-                title: rendered.title.clone(),
-                body: rendered.body.clone(),
-                data: serde_json::Value::Object(
-                    rendered
-                        .data
-                        .iter()
-                        .map(|(k, v)| (k.clone(), v.clone()))
-                        .collect(),
-                ),
-                channels: rendered.channels.clone(),
-                created_at: Utc::now(),
-            };
+            let event = rendered.to_event();
 
             let _ = producer
                 .send_json(
@@ -434,10 +495,155 @@ impl NotificationServiceAsync {
     }
 }
 
+/// 按用户语言和渠道渲染通知内容
+///
+/// 渲染上下文为业务数据叠加模板变量。没有匹配的模板或缺少模板变量时，
+/// 沿用按默认模板渲染的内容。
+fn render_for_channel(
+    templates: &TemplateSet,
+    notification: &Notification,
+    channel: &ChannelType,
+) -> Notification {
+    let mut context = serde_json::Map::new();
+    for (key, value) in &notification.data {
+        context.insert(key.clone(), value.clone());
+    }
+    for (key, value) in &notification.variables {
+        context.insert(key.clone(), serde_json::json!(value));
+    }
+    let locale = notification
+        .data
+        .get(LOCALE_DATA_KEY)
+        .and_then(|v| v.as_str())
+        .unwrap_or(DEFAULT_LOCALE);
+
+    let mut rendered = notification.clone();
+    if let Some(content) = templates.render(
+        &notification.notification_type,
+        locale,
+        Some(channel),
+        &serde_json::Value::Object(context),
+    ) && content.is_complete()
+    {
+        rendered.title = content.title;
+        rendered.body = content.body;
+        if let (ChannelType::Email, Some(html)) = (channel, content.html_body) {
+            rendered
+                .data
+                .insert(HTML_BODY_DATA_KEY.to_string(), serde_json::json!(html));
+        }
+    }
+    rendered
+}
+
+/// 按用户偏好处理后的通知
+enum PreparedNotification {
+    /// 发送到筛选后的渠道，附带被用户关闭的渠道结果
+    Send(Notification, Vec<ChannelResult>),
+    /// 无需立即发送（延迟投递或用户关闭了所有渠道）
+    Done(NotificationResult),
+}
+
+/// 按用户偏好筛选渠道，免打扰时段内的非紧急通知写入延迟队列
+///
+/// 偏好读取或延迟队列写入失败时按原渠道立即发送，偏好服务故障不应阻断通知。
+async fn apply_user_preferences(
+    preference_store: Option<&Arc<dyn PreferenceStore>>,
+    deferred_store: Option<&Arc<dyn DeferredNotificationStore>>,
+    mut notification: Notification,
+) -> PreparedNotification {
+    let Some(store) = preference_store else {
+        return PreparedNotification::Send(notification, vec![]);
+    };
+    let preferences = match store.get(&notification.user_id).await {
+        Ok(Some(preferences)) => preferences,
+        Ok(None) => return PreparedNotification::Send(notification, vec![]),
+        Err(e) => {
+            warn!(error = %e, "读取用户通知偏好失败，按原渠道发送");
+            return PreparedNotification::Send(notification, vec![]);
+        }
+    };
+
+    let allowed =
+        preferences.allowed_channels(&notification.notification_type, &notification.channels);
+    let skipped: Vec<ChannelResult> = notification
+        .channels
+        .iter()
+        .filter(|c| !allowed.contains(c))
+        .map(|c| ChannelResult::skipped(c.clone(), "用户已关闭该渠道"))
+        .collect();
+    notification.channels = allowed;
+    notification
+        .data
+        .entry(LOCALE_DATA_KEY.to_string())
+        .or_insert_with(|| serde_json::json!(preferences.locale));
+
+    if notification.channels.is_empty() {
+        debug!("用户已关闭该类型通知的所有渠道");
+        return PreparedNotification::Done(NotificationResult::accepted(
+            notification.notification_id,
+            skipped,
+        ));
+    }
+
+    if let (Some(until), Some(deferred)) = (
+        preferences.deferred_until(&notification.notification_type, Utc::now()),
+        deferred_store,
+    ) {
+        match deferred.defer(&notification.to_event(), until).await {
+            Ok(()) => {
+                info!(deliver_at = %until, "免打扰时段内，通知延迟投递");
+                let mut results: Vec<ChannelResult> = notification
+                    .channels
+                    .iter()
+                    .map(|c| ChannelResult::pending(c.clone()))
+                    .collect();
+                results.extend(skipped);
+                return PreparedNotification::Done(NotificationResult::accepted(
+                    notification.notification_id,
+                    results,
+                ));
+            }
+            Err(e) => warn!(error = %e, "写入延迟队列失败，立即发送"),
+        }
+    }
+
+    PreparedNotification::Send(notification, skipped)
+}
+
+/// 按用户联系方式补全收件人字段，通知已携带的字段保持不变
+///
+/// 查询失败时原样发送，由各渠道按收件人缺失处理。
+async fn resolve_recipients(
+    contact_store: Option<&Arc<dyn ContactStore>>,
+    mut notification: Notification,
+) -> Notification {
+    let Some(store) = contact_store else {
+        return notification;
+    };
+    match store.get(&notification.user_id).await {
+        Ok(Some(contacts)) => {
+            for (key, value) in contacts.fields() {
+                notification
+                    .data
+                    .entry(key.to_string())
+                    .or_insert_with(|| serde_json::json!(value));
+            }
+        }
+        Ok(None) => {}
+        Err(e) => warn!(error = %e, "读取用户联系方式失败，按通知自带的收件人发送"),
+    }
+    notification
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::notification::types::SendStatus;
     use badge_shared::events::NotificationType;
+    use badge_shared::notification_preferences::{
+        InMemoryDeferredStore, InMemoryPreferenceStore, NotificationPreferences, QuietHours,
+    };
 
     fn create_test_notification(user_id: &str) -> Notification {
         Notification::new(
@@ -480,7 +686,7 @@ mod tests {
             "测试标题",
             "测试内容",
         )
-        .with_channels(vec![ChannelType::AppPush, ChannelType::Sms, ChannelType::Email]);
+        .with_channels(vec![ChannelType::AppPush, ChannelType::Sms, ChannelType::WeChat]);
 
         let result = service.send(notification).await.unwrap();
 
@@ -526,6 +732,60 @@ mod tests {
         assert!(result.success);
     }
 
+    #[test]
+    fn test_render_for_channel_uses_locale_and_channel() {
+        use badge_shared::notification_templates::NotificationTemplate;
+
+        let templates = TemplateSet::new([
+            NotificationTemplate::new(
+                NotificationType::BadgeGranted,
+                "en-US",
+                "New badge",
+                "You earned {{badge_name}}",
+            ),
+            NotificationTemplate::new(
+                NotificationType::BadgeGranted,
+                "en-US",
+                "New badge",
+                "[Badges] {{badge_name}} x{{quantity}}",
+            )
+            .with_channel(ChannelType::Sms),
+        ]);
+        let notification = create_test_notification("user-123")
+            .with_variable("badge_name", "First order")
+            .with_data("quantity", serde_json::json!(2))
+            .with_data(LOCALE_DATA_KEY, serde_json::json!("en-US"));
+
+        let push = render_for_channel(&templates, &notification, &ChannelType::AppPush);
+        assert_eq!(push.body, "You earned First order");
+
+        let sms = render_for_channel(&templates, &notification, &ChannelType::Sms);
+        assert_eq!(sms.body, "[Badges] First order x2");
+
+        // 缺少模板变量时沿用默认渲染内容
+        let incomplete = create_test_notification("user-123")
+            .with_data(LOCALE_DATA_KEY, serde_json::json!("en-US"));
+        let push = render_for_channel(&templates, &incomplete, &ChannelType::AppPush);
+        assert_eq!(push.body, "测试内容");
+    }
+
+    #[test]
+    fn test_render_for_channel_email_html() {
+        let templates = TemplateSet::builtin();
+        let notification =
+            create_test_notification("user-123").with_variable("badge_name", "<首单>");
+
+        let email = render_for_channel(&templates, &notification, &ChannelType::Email);
+        assert_eq!(email.body, "您已获得「<首单>」徽章！");
+        assert_eq!(
+            email.data[HTML_BODY_DATA_KEY],
+            "<p>您已获得以下徽章：</p><ul><li>&lt;首单&gt;</li></ul>"
+        );
+
+        let push = render_for_channel(&templates, &notification, &ChannelType::AppPush);
+        assert!(!push.data.contains_key(HTML_BODY_DATA_KEY));
+    }
+
     #[tokio::test]
     async fn test_send_batch() {
         let service = NotificationService::with_defaults();
@@ -561,6 +821,93 @@ mod tests {
         assert!(service.get_channel(ChannelType::Sms).is_some());
     }
 
+    #[tokio::test]
+    async fn test_send_respects_channel_preferences() {
+        let store = Arc::new(InMemoryPreferenceStore::new());
+        store
+            .save(
+                &NotificationPreferences::new("user-123")
+                    .with_channels(NotificationType::BadgeGranted, vec![ChannelType::Sms]),
+            )
+            .await
+            .unwrap();
+        let service = NotificationService::with_defaults().with_preference_store(store);
+        let notification = create_test_notification("user-123")
+            .with_channels(vec![ChannelType::AppPush, ChannelType::Sms]);
+
+        let result = service.send(notification).await.unwrap();
+
+        assert_eq!(result.channel_results.len(), 2);
+        assert_eq!(result.success_count(), 1);
+        let app_push = result
+            .channel_results
+            .iter()
+            .find(|r| r.channel == ChannelType::AppPush)
+            .unwrap();
+        assert_eq!(app_push.status, SendStatus::Skipped);
+    }
+
+    #[tokio::test]
+    async fn test_send_all_channels_disabled() {
+        let store = Arc::new(InMemoryPreferenceStore::new());
+        store
+            .save(
+                &NotificationPreferences::new("user-123")
+                    .with_channels(NotificationType::BadgeGranted, vec![]),
+            )
+            .await
+            .unwrap();
+        let service = NotificationService::with_defaults().with_preference_store(store);
+
+        let result = service
+            .send(create_test_notification("user-123"))
+            .await
+            .unwrap();
+
+        assert!(result.success);
+        assert_eq!(result.success_count(), 0);
+        assert_eq!(result.channel_results[0].status, SendStatus::Skipped);
+    }
+
+    #[tokio::test]
+    async fn test_send_deferred_during_quiet_hours() {
+        let now = Utc::now().time();
+        let quiet_hours = QuietHours::new(
+            now - chrono::Duration::hours(1),
+            now + chrono::Duration::hours(1),
+            "UTC",
+        )
+        .unwrap();
+        let preferences = Arc::new(InMemoryPreferenceStore::new());
+        preferences
+            .save(&NotificationPreferences::new("user-123").with_quiet_hours(quiet_hours))
+            .await
+            .unwrap();
+        let deferred = Arc::new(InMemoryDeferredStore::new());
+        let service = NotificationService::with_defaults()
+            .with_preference_store(preferences)
+            .with_deferred_store(deferred.clone());
+
+        let result = service
+            .send(create_test_notification("user-123"))
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert_eq!(result.channel_results[0].status, SendStatus::Pending);
+        assert_eq!(deferred.len(), 1);
+
+        // 紧急通知不受免打扰限制
+        let urgent = Notification::new(
+            "user-123",
+            NotificationType::RedemptionFailed,
+            "兑换失败",
+            "库存不足",
+        );
+        let result = service.send(urgent).await.unwrap();
+        assert_eq!(result.channel_results[0].status, SendStatus::Success);
+        assert_eq!(deferred.len(), 1);
+    }
+
     #[tokio::test]
     async fn test_async_enabled_setting() {
         let service = NotificationService::with_defaults();
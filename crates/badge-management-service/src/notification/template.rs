@@ -1,6 +1,9 @@
 //! 通知模板引擎
 //!
-//! 提供模板变量替换功能，支持 `{{variable}}` 语法。
+//! 封装 `badge_shared::notification_templates` 的模板注册表，与 notification-worker
+//! 共用同一套模板模型和渲染规则：支持 `{{variable}}` 变量、`{{#if}}` 条件和
+//! `{{#each}}` 循环，模板按通知类型、语言和渠道区分。配置模板存储后，
+//! 运营在管理后台维护的模板会覆盖内置默认模板。
 //!
 //! ## 使用示例
 //!
@@ -16,23 +19,22 @@
 //! ```
 
 use std::collections::HashMap;
+use std::sync::Arc;
 
-use regex::Regex;
 use tracing::warn;
 
 use super::types::NotificationContext;
 use badge_shared::events::NotificationType;
+use badge_shared::notification_preferences::DEFAULT_LOCALE;
+use badge_shared::notification_templates::{
+    NotificationTemplate, Template, TemplateRegistry, TemplateSet, TemplateStore,
+};
 
 /// 模板引擎
 ///
 /// 管理通知模板并提供变量替换功能
 pub struct TemplateEngine {
-    /// 标题模板（按通知类型）
-    title_templates: HashMap<NotificationType, String>,
-    /// 正文模板（按通知类型）
-    body_templates: HashMap<NotificationType, String>,
-    /// 变量匹配正则
-    variable_regex: Regex,
+    registry: TemplateRegistry,
 }
 
 impl Default for TemplateEngine {
@@ -45,97 +47,70 @@ impl TemplateEngine {
     /// 创建空的模板引擎
     pub fn new() -> Self {
         Self {
-            title_templates: HashMap::new(),
-            body_templates: HashMap::new(),
-            // 匹配 {{variable_name}} 格式，变量名支持字母、数字、下划线
-            variable_regex: Regex::new(r"\{\{(\w+)\}\}").unwrap(),
+            registry: TemplateRegistry::new(Vec::new()),
         }
     }
 
-    /// 创建带有默认模板的引擎
+    /// 创建带有内置默认模板的引擎
     pub fn with_defaults() -> Self {
-        let mut engine = Self::new();
-        engine.register_default_templates();
-        engine
+        Self {
+            registry: TemplateRegistry::builtin(),
+        }
     }
 
-    /// 注册默认模板
-    fn register_default_templates(&mut self) {
-        // 徽章获取通知
-        self.register_template(
-            NotificationType::BadgeGranted,
-            "恭喜获得新徽章！",
-            "您已获得「{{badge_name}}」徽章，快去看看吧！",
-        );
-
-        // 徽章即将过期通知
-        self.register_template(
-            NotificationType::BadgeExpiring,
-            "徽章即将过期提醒",
-            "您的「{{badge_name}}」徽章将在 {{days_left}} 天后过期，请及时使用！",
-        );
-
-        // 徽章撤销通知
-        self.register_template(
-            NotificationType::BadgeRevoked,
-            "徽章已被撤销",
-            "您的「{{badge_name}}」徽章已被撤销，原因：{{reason}}",
-        );
-
-        // 兑换成功通知
-        self.register_template(
-            NotificationType::RedemptionSuccess,
-            "兑换成功！",
-            "您已成功兑换「{{benefit_name}}」，请查收！",
-        );
-
-        // 兑换失败通知
-        self.register_template(
-            NotificationType::RedemptionFailed,
-            "兑换失败",
-            "兑换失败，原因：{{reason}}。如有疑问请联系客服。",
-        );
+    /// 创建从模板存储加载模板的引擎，存储中的模板覆盖内置默认模板
+    pub fn with_store(store: Arc<dyn TemplateStore>) -> Self {
+        Self {
+            registry: TemplateRegistry::builtin().with_store(store),
+        }
     }
 
-    /// 注册模板
+    /// 注册默认语言的模板
     pub fn register_template(
         &mut self,
         notification_type: NotificationType,
         title_template: impl Into<String>,
         body_template: impl Into<String>,
     ) {
-        self.title_templates
-            .insert(notification_type.clone(), title_template.into());
-        self.body_templates
-            .insert(notification_type, body_template.into());
+        self.registry.register(NotificationTemplate::new(
+            notification_type,
+            DEFAULT_LOCALE,
+            title_template,
+            body_template,
+        ));
     }
 
-    /// 获取模板
-    pub fn get_template(&self, notification_type: &NotificationType) -> Option<(&str, &str)> {
-        let title = self.title_templates.get(notification_type)?;
-        let body = self.body_templates.get(notification_type)?;
-        Some((title, body))
+    /// 是否有该通知类型的默认语言模板
+    pub fn has_template(&self, notification_type: &NotificationType) -> bool {
+        self.registry
+            .snapshot()
+            .resolve(notification_type, DEFAULT_LOCALE, None)
+            .is_some()
+    }
+
+    /// 当前模板集合，超过刷新间隔时先从模板存储重新加载
+    pub async fn templates(&self) -> Arc<TemplateSet> {
+        self.registry.current().await
     }
 
     /// 渲染模板
     ///
     /// 将模板中的 `{{variable}}` 替换为上下文中的对应值。
-    /// 未找到的变量会保留原样并记录警告日志。
+    /// 未找到的变量会保留原样并记录警告日志，语法错误的模板原样返回。
     pub fn render(&self, template: &str, context: &NotificationContext) -> String {
-        let result = self
-            .variable_regex
-            .replace_all(template, |caps: &regex::Captures| {
-                let var_name = &caps[1];
-                match context.get(var_name) {
-                    Some(value) => value.to_string(),
-                    None => {
-                        warn!(variable = var_name, "模板变量未找到，保留原样");
-                        caps[0].to_string()
-                    }
-                }
-            });
+        let parsed = match Template::parse(template) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                warn!(error = %e, "模板解析失败，保留原样");
+                return template.to_string();
+            }
+        };
 
-        result.into_owned()
+        let output = parsed.render(&context_value(context), false);
+        for name in &output.missing {
+            warn!(variable = %name, "模板变量未找到，保留原样");
+        }
+        output.text
     }
 
     /// 使用变量 HashMap 渲染模板
@@ -146,7 +121,7 @@ impl TemplateEngine {
 
     /// 渲染标题和正文
     ///
-    /// 如果通知类型有对应模板，使用模板渲染；否则使用传入的默认值。
+    /// 如果通知类型有默认语言模板，使用模板渲染；否则使用传入的默认值。
     pub fn render_notification(
         &self,
         notification_type: &NotificationType,
@@ -154,41 +129,54 @@ impl TemplateEngine {
         default_body: &str,
         context: &NotificationContext,
     ) -> (String, String) {
-        let (title_template, body_template) = self
-            .get_template(notification_type)
-            .unwrap_or((default_title, default_body));
-
-        let rendered_title = self.render(title_template, context);
-        let rendered_body = self.render(body_template, context);
-
-        (rendered_title, rendered_body)
+        let templates = self.registry.snapshot();
+        match templates.render(
+            notification_type,
+            DEFAULT_LOCALE,
+            None,
+            &context_value(context),
+        ) {
+            Some(rendered) => {
+                for name in &rendered.missing_variables {
+                    warn!(variable = %name, "模板变量未找到，保留原样");
+                }
+                (rendered.title, rendered.body)
+            }
+            None => (
+                self.render(default_title, context),
+                self.render(default_body, context),
+            ),
+        }
     }
 
     /// 验证模板语法
     ///
     /// 检查模板中的变量是否都能在给定的上下文中找到
     pub fn validate_template(&self, template: &str, context: &NotificationContext) -> Vec<String> {
-        let mut missing_vars = Vec::new();
-
-        for caps in self.variable_regex.captures_iter(template) {
-            let var_name = &caps[1];
-            if context.get(var_name).is_none() {
-                missing_vars.push(var_name.to_string());
-            }
-        }
-
-        missing_vars
+        Template::parse(template)
+            .map(|t| t.render(&context_value(context), false).missing)
+            .unwrap_or_default()
     }
 
     /// 提取模板中的所有变量名
     pub fn extract_variables(&self, template: &str) -> Vec<String> {
-        self.variable_regex
-            .captures_iter(template)
-            .map(|caps| caps[1].to_string())
-            .collect()
+        Template::parse(template)
+            .map(|t| t.variables())
+            .unwrap_or_default()
     }
 }
 
+/// 将模板变量转换为渲染上下文
+fn context_value(context: &NotificationContext) -> serde_json::Value {
+    serde_json::Value::Object(
+        context
+            .variables
+            .iter()
+            .map(|(k, v)| (k.clone(), serde_json::Value::String(v.clone())))
+            .collect(),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -196,8 +184,7 @@ mod tests {
     #[test]
     fn test_template_engine_creation() {
         let engine = TemplateEngine::new();
-        assert!(engine.title_templates.is_empty());
-        assert!(engine.body_templates.is_empty());
+        assert!(!engine.has_template(&NotificationType::BadgeGranted));
     }
 
     #[test]
@@ -205,9 +192,9 @@ mod tests {
         let engine = TemplateEngine::with_defaults();
 
         // 验证默认模板已注册
-        assert!(engine.get_template(&NotificationType::BadgeGranted).is_some());
-        assert!(engine.get_template(&NotificationType::BadgeExpiring).is_some());
-        assert!(engine.get_template(&NotificationType::RedemptionSuccess).is_some());
+        assert!(engine.has_template(&NotificationType::BadgeGranted));
+        assert!(engine.has_template(&NotificationType::BadgeExpiring));
+        assert!(engine.has_template(&NotificationType::RedemptionSuccess));
     }
 
     #[test]
@@ -219,9 +206,12 @@ mod tests {
             "自定义正文 {{badge_name}}",
         );
 
-        let (title, body) = engine.get_template(&NotificationType::BadgeGranted).unwrap();
+        let mut context = NotificationContext::new();
+        context.set("badge_name", "首单");
+        let (title, body) =
+            engine.render_notification(&NotificationType::BadgeGranted, "", "", &context);
         assert_eq!(title, "自定义标题");
-        assert_eq!(body, "自定义正文 {{badge_name}}");
+        assert_eq!(body, "自定义正文 首单");
     }
 
     #[test]
@@ -258,6 +248,27 @@ mod tests {
         assert_eq!(result, "你好，{{name}}！");
     }
 
+    #[test]
+    fn test_render_conditional() {
+        let engine = TemplateEngine::new();
+        let template = "徽章已回收{{#if reason}}，原因：{{reason}}{{/if}}";
+
+        let mut context = NotificationContext::new();
+        assert_eq!(engine.render(template, &context), "徽章已回收");
+
+        context.set("reason", "违规");
+        assert_eq!(engine.render(template, &context), "徽章已回收，原因：违规");
+    }
+
+    #[test]
+    fn test_render_invalid_template() {
+        let engine = TemplateEngine::new();
+        let context = NotificationContext::new();
+
+        // 语法错误的模板原样返回
+        assert_eq!(engine.render("{{#if a}}未闭合", &context), "{{#if a}}未闭合");
+    }
+
     #[test]
     fn test_render_with_map() {
         let engine = TemplateEngine::new();
@@ -281,10 +292,27 @@ mod tests {
             &context,
         );
 
-        assert_eq!(title, "恭喜获得新徽章！");
+        assert_eq!(title, "恭喜获得新徽章");
         assert!(body.contains("消费达人"));
     }
 
+    #[test]
+    fn test_render_notification_without_template() {
+        let engine = TemplateEngine::new();
+        let mut context = NotificationContext::new();
+        context.set("badge_name", "消费达人");
+
+        let (title, body) = engine.render_notification(
+            &NotificationType::BadgeGranted,
+            "默认标题",
+            "获得「{{badge_name}}」",
+            &context,
+        );
+
+        assert_eq!(title, "默认标题");
+        assert_eq!(body, "获得「消费达人」");
+    }
+
     #[test]
     fn test_validate_template() {
         let engine = TemplateEngine::new();
@@ -8,7 +8,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use badge_shared::events::{NotificationChannel as Channel, NotificationType};
+use badge_shared::events::{NotificationChannel as Channel, NotificationEvent, NotificationType};
 
 /// 通知请求
 ///
@@ -103,6 +103,25 @@ impl Notification {
         self.priority = priority.min(10);
         self
     }
+
+    /// 转换为 Kafka 通知事件
+    pub fn to_event(&self) -> NotificationEvent {
+        NotificationEvent {
+            notification_id: self.notification_id.clone(),
+            user_id: self.user_id.clone(),
+            notification_type: self.notification_type.clone(),
+            title: self.title.clone(),
+            body: self.body.clone(),
+            data: serde_json::Value::Object(
+                self.data
+                    .iter()
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect(),
+            ),
+            channels: self.channels.clone(),
+            created_at: Utc::now(),
+        }
+    }
 }
 
 /// 通知构建器
@@ -183,6 +202,36 @@ impl NotificationBuilder {
         .with_channels(vec![Channel::AppPush])
     }
 
+    /// 创建徽章暂停通知
+    ///
+    /// 前置徽章失效导致级联暂停时发送，沿用撤销通知类型，通过 data.suspended 区分
+    pub fn badge_suspended(
+        user_id: impl Into<String>,
+        badge_id: i64,
+        badge_name: impl Into<String>,
+        reason: impl Into<String>,
+    ) -> Notification {
+        let badge_name = badge_name.into();
+        let reason = reason.into();
+
+        Notification::new(
+            user_id,
+            NotificationType::BadgeRevoked,
+            "徽章已暂停",
+            format!(
+                "您的「{}」徽章已暂停，原因：{}。重新满足获取条件后将自动恢复",
+                badge_name, reason
+            ),
+        )
+        .with_data("badge_id", serde_json::json!(badge_id))
+        .with_data("badge_name", serde_json::json!(&badge_name))
+        .with_data("reason", serde_json::json!(&reason))
+        .with_data("suspended", serde_json::json!(true))
+        .with_variable("badge_name", &badge_name)
+        .with_variable("reason", &reason)
+        .with_channels(vec![Channel::AppPush])
+    }
+
     /// 创建兑换成功通知
     pub fn redemption_success(
         user_id: impl Into<String>,
@@ -311,6 +360,19 @@ impl NotificationResult {
         }
     }
 
+    /// 创建已受理结果
+    ///
+    /// 通知按用户偏好延迟投递或无需投递时使用，不视为发送失败
+    pub fn accepted(notification_id: String, channel_results: Vec<ChannelResult>) -> Self {
+        Self {
+            notification_id,
+            success: true,
+            channel_results,
+            duration_ms: 0,
+            sent_at: Utc::now(),
+        }
+    }
+
     /// 获取成功的渠道数量
     pub fn success_count(&self) -> usize {
         self.channel_results
@@ -373,6 +435,17 @@ impl ChannelResult {
         }
     }
 
+    /// 创建待发送结果（延迟投递）
+    pub fn pending(channel: Channel) -> Self {
+        Self {
+            channel,
+            status: SendStatus::Pending,
+            error: None,
+            external_message_id: None,
+            duration_ms: 0,
+        }
+    }
+
     /// 创建跳过结果（渠道未配置或不可用）
     pub fn skipped(channel: Channel, reason: impl Into<String>) -> Self {
         Self {
@@ -450,6 +523,17 @@ mod tests {
         assert_eq!(notification.data.get("badge_id").unwrap(), &serde_json::json!(42));
     }
 
+    #[test]
+    fn test_notification_builder_badge_suspended() {
+        let notification =
+            NotificationBuilder::badge_suspended("user-123", 7, "黄金会员", "前置徽章已失效");
+
+        assert_eq!(notification.notification_type, NotificationType::BadgeRevoked);
+        assert_eq!(notification.title, "徽章已暂停");
+        assert!(notification.body.contains("黄金会员"));
+        assert_eq!(notification.data.get("suspended").unwrap(), &serde_json::json!(true));
+    }
+
     #[test]
     fn test_notification_builder_redemption_success() {
         let notification = NotificationBuilder::redemption_success(
@@ -145,7 +145,7 @@ impl BadgeRepository {
             r#"
             SELECT id, series_id, code, badge_type, name, description, obtain_description,
                    sort_order, status, assets, validity_config, max_supply,
-                   issued_count, created_at, updated_at
+                   issued_count, levels, created_at, updated_at
             FROM badges
             WHERE id = $1
             "#,
@@ -167,7 +167,7 @@ impl BadgeRepository {
             r#"
             SELECT id, series_id, code, badge_type, name, description, obtain_description,
                    sort_order, status, assets, validity_config, max_supply,
-                   issued_count, created_at, updated_at
+                   issued_count, levels, created_at, updated_at
             FROM badges
             WHERE id = ANY($1)
             ORDER BY sort_order ASC, id ASC
@@ -186,7 +186,7 @@ impl BadgeRepository {
             r#"
             SELECT id, series_id, code, badge_type, name, description, obtain_description,
                    sort_order, status, assets, validity_config, max_supply,
-                   issued_count, created_at, updated_at
+                   issued_count, levels, created_at, updated_at
             FROM badges
             WHERE series_id = $1 AND status = $2
             ORDER BY sort_order ASC, id ASC
@@ -206,7 +206,7 @@ impl BadgeRepository {
             r#"
             SELECT id, series_id, code, badge_type, name, description, obtain_description,
                    sort_order, status, assets, validity_config, max_supply,
-                   issued_count, created_at, updated_at
+                   issued_count, levels, created_at, updated_at
             FROM badges
             WHERE status = $1
             ORDER BY sort_order ASC, id ASC
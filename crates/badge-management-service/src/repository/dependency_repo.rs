@@ -1,9 +1,9 @@
 //! 依赖关系仓储
 //!
-//! 提供徽章依赖关系和级联评估日志的数据访问
+//! 提供徽章依赖关系、级联评估日志和待处理反向级联的数据访问
 
 use chrono::{DateTime, Utc};
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool};
 
 use crate::error::Result;
 
@@ -19,6 +19,8 @@ pub struct BadgeDependencyRow {
     pub auto_trigger: bool,
     pub priority: i32,
     pub dependency_group_id: String,
+    /// 前置徽章失效时的处理策略：keep / revoke / suspend
+    pub revoke_policy: String,
     pub enabled: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
@@ -35,6 +37,7 @@ pub struct CreateDependencyRequest {
     pub auto_trigger: bool,
     pub priority: i32,
     pub dependency_group_id: String,
+    pub revoke_policy: String,
 }
 
 /// 更新依赖关系请求
@@ -49,6 +52,7 @@ pub struct UpdateDependencyRequest {
     pub auto_trigger: Option<bool>,
     pub priority: Option<i32>,
     pub dependency_group_id: Option<String>,
+    pub revoke_policy: Option<String>,
     pub enabled: Option<bool>,
 }
 
@@ -57,16 +61,31 @@ pub struct UpdateDependencyRequest {
 pub struct CascadeEvaluationLog {
     pub user_id: String,
     pub trigger_badge_id: i64,
+    /// 评估方向：grant（正向级联）/ revoke（反向级联）
+    pub direction: String,
     pub evaluation_context: serde_json::Value,
     pub result_status: String,
     pub granted_badges: Option<serde_json::Value>,
     pub blocked_badges: Option<serde_json::Value>,
+    /// 反向级联撤销/暂停的徽章，或正向级联恢复的徽章
+    pub affected_badges: Option<serde_json::Value>,
     pub error_message: Option<String>,
     pub started_at: DateTime<Utc>,
     pub completed_at: DateTime<Utc>,
     pub duration_ms: i32,
 }
 
+/// 待处理的反向级联
+///
+/// 撤销或过期事务内写入，级联成功后删除；失败时保留供重试
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct PendingRevokeCascade {
+    pub id: i64,
+    pub user_id: String,
+    pub revoked_badge_id: i64,
+    pub attempts: i32,
+}
+
 /// 依赖关系仓储
 ///
 /// 负责徽章依赖关系的数据访问，支持前置条件、消耗关系和互斥关系的查询与管理
@@ -87,7 +106,8 @@ impl DependencyRepository {
             r#"
             SELECT id, badge_id, depends_on_badge_id, dependency_type,
                    required_quantity, exclusive_group_id, auto_trigger,
-                   priority, dependency_group_id, enabled, created_at, updated_at
+                   priority, dependency_group_id, revoke_policy, enabled,
+                   created_at, updated_at
             FROM badge_dependencies
             WHERE enabled = true
             ORDER BY priority ASC, id ASC
@@ -107,7 +127,8 @@ impl DependencyRepository {
             r#"
             SELECT id, badge_id, depends_on_badge_id, dependency_type,
                    required_quantity, exclusive_group_id, auto_trigger,
-                   priority, dependency_group_id, enabled, created_at, updated_at
+                   priority, dependency_group_id, revoke_policy, enabled,
+                   created_at, updated_at
             FROM badge_dependencies
             WHERE depends_on_badge_id = $1
               AND auto_trigger = true
@@ -130,7 +151,8 @@ impl DependencyRepository {
             r#"
             SELECT id, badge_id, depends_on_badge_id, dependency_type,
                    required_quantity, exclusive_group_id, auto_trigger,
-                   priority, dependency_group_id, enabled, created_at, updated_at
+                   priority, dependency_group_id, revoke_policy, enabled,
+                   created_at, updated_at
             FROM badge_dependencies
             WHERE badge_id = $1 AND enabled = true
             ORDER BY dependency_group_id ASC, priority ASC, id ASC
@@ -168,12 +190,13 @@ impl DependencyRepository {
             INSERT INTO badge_dependencies (
                 badge_id, depends_on_badge_id, dependency_type,
                 required_quantity, exclusive_group_id, auto_trigger,
-                priority, dependency_group_id
+                priority, dependency_group_id, revoke_policy
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
             RETURNING id, badge_id, depends_on_badge_id, dependency_type,
                       required_quantity, exclusive_group_id, auto_trigger,
-                      priority, dependency_group_id, enabled, created_at, updated_at
+                      priority, dependency_group_id, revoke_policy, enabled,
+                      created_at, updated_at
             "#,
         )
         .bind(request.badge_id)
@@ -184,6 +207,7 @@ impl DependencyRepository {
         .bind(request.auto_trigger)
         .bind(request.priority)
         .bind(&request.dependency_group_id)
+        .bind(&request.revoke_policy)
         .fetch_one(&self.pool)
         .await?;
 
@@ -221,11 +245,13 @@ impl DependencyRepository {
                 priority = COALESCE($6, priority),
                 dependency_group_id = COALESCE($7, dependency_group_id),
                 enabled = COALESCE($8, enabled),
+                revoke_policy = COALESCE($9, revoke_policy),
                 updated_at = NOW()
             WHERE id = $1
             RETURNING id, badge_id, depends_on_badge_id, dependency_type,
                       required_quantity, exclusive_group_id, auto_trigger,
-                      priority, dependency_group_id, enabled, created_at, updated_at
+                      priority, dependency_group_id, revoke_policy, enabled,
+                      created_at, updated_at
             "#,
         )
         .bind(request.id)
@@ -236,6 +262,7 @@ impl DependencyRepository {
         .bind(request.priority)
         .bind(&request.dependency_group_id)
         .bind(request.enabled)
+        .bind(&request.revoke_policy)
         .fetch_optional(&self.pool)
         .await?;
 
@@ -248,7 +275,8 @@ impl DependencyRepository {
             r#"
             SELECT id, badge_id, depends_on_badge_id, dependency_type,
                    required_quantity, exclusive_group_id, auto_trigger,
-                   priority, dependency_group_id, enabled, created_at, updated_at
+                   priority, dependency_group_id, revoke_policy, enabled,
+                   created_at, updated_at
             FROM badge_dependencies
             WHERE id = $1
             "#,
@@ -307,20 +335,22 @@ impl DependencyRepository {
         let id = sqlx::query_scalar::<_, i64>(
             r#"
             INSERT INTO cascade_evaluation_logs (
-                user_id, trigger_badge_id, evaluation_context,
-                result_status, granted_badges, blocked_badges,
+                user_id, trigger_badge_id, direction, evaluation_context,
+                result_status, granted_badges, blocked_badges, affected_badges,
                 error_message, started_at, completed_at, duration_ms
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
             RETURNING id
             "#,
         )
         .bind(&log.user_id)
         .bind(log.trigger_badge_id)
+        .bind(&log.direction)
         .bind(&log.evaluation_context)
         .bind(&log.result_status)
         .bind(&log.granted_badges)
         .bind(&log.blocked_badges)
+        .bind(&log.affected_badges)
         .bind(&log.error_message)
         .bind(log.started_at)
         .bind(log.completed_at)
@@ -330,6 +360,78 @@ impl DependencyRepository {
 
         Ok(id)
     }
+
+    /// 在事务中登记待处理的反向级联
+    ///
+    /// 首次重试时间推迟一分钟，让提交后的即时评估优先处理，重试 Worker 只兜底失败或中断的记录
+    pub async fn create_pending_revoke_in_tx(
+        tx: &mut PgConnection,
+        user_id: &str,
+        revoked_badge_id: i64,
+    ) -> Result<i64> {
+        let id = sqlx::query_scalar::<_, i64>(
+            r#"
+            INSERT INTO pending_revoke_cascades (user_id, revoked_badge_id, next_attempt_at)
+            VALUES ($1, $2, NOW() + INTERVAL '1 minute')
+            RETURNING id
+            "#,
+        )
+        .bind(user_id)
+        .bind(revoked_badge_id)
+        .fetch_one(tx)
+        .await?;
+
+        Ok(id)
+    }
+
+    /// 在事务中删除待处理的反向级联
+    ///
+    /// 返回 false 表示记录已被其他实例处理
+    pub async fn delete_pending_revoke_in_tx(tx: &mut PgConnection, id: i64) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM pending_revoke_cascades WHERE id = $1")
+            .bind(id)
+            .execute(tx)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 获取已到重试时间的待处理反向级联
+    pub async fn list_due_pending_revokes(&self, limit: i64) -> Result<Vec<PendingRevokeCascade>> {
+        let rows = sqlx::query_as::<_, PendingRevokeCascade>(
+            r#"
+            SELECT id, user_id, revoked_badge_id, attempts
+            FROM pending_revoke_cascades
+            WHERE next_attempt_at <= NOW()
+            ORDER BY next_attempt_at ASC, id ASC
+            LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// 记录反向级联失败，按失败次数线性退避（上限一小时）
+    pub async fn mark_pending_revoke_failed(&self, id: i64, error: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE pending_revoke_cascades
+            SET attempts = attempts + 1,
+                last_error = $2,
+                next_attempt_at = NOW() + LEAST(attempts + 1, 60) * INTERVAL '1 minute'
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(error)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
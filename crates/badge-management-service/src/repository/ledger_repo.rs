@@ -7,7 +7,7 @@ use sqlx::{PgConnection, PgPool, Row};
 
 use super::traits::BadgeLedgerRepositoryTrait;
 use crate::error::Result;
-use crate::models::BadgeLedger;
+use crate::models::{BadgeLedger, ChangeType};
 
 /// 徽章账本仓储
 ///
@@ -176,6 +176,29 @@ impl BadgeLedgerRepository {
         Ok(row.get("balance"))
     }
 
+    /// 在事务中检查指定 ref_id 与变动类型的流水是否已存在
+    ///
+    /// 用于幂等键去重，ref_id 存储调用方传入的幂等键
+    pub async fn exists_by_ref_in_tx(
+        tx: &mut PgConnection,
+        ref_id: &str,
+        change_type: ChangeType,
+    ) -> Result<bool> {
+        let row = sqlx::query(
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM badge_ledger WHERE ref_id = $1 AND change_type = $2
+            ) as found
+            "#,
+        )
+        .bind(ref_id)
+        .bind(change_type)
+        .fetch_one(tx)
+        .await?;
+
+        Ok(row.get("found"))
+    }
+
     /// 获取用户所有徽章的余额汇总
     ///
     /// 返回 (badge_id, balance) 列表
@@ -21,7 +21,7 @@ pub use auto_benefit_repo::AutoBenefitRepository;
 pub use badge_repo::BadgeRepository;
 pub use dependency_repo::{
     BadgeDependencyRow, CascadeEvaluationLog, CreateDependencyRequest, DependencyRepository,
-    UpdateDependencyRequest,
+    PendingRevokeCascade, UpdateDependencyRequest,
 };
 pub use ledger_repo::BadgeLedgerRepository;
 pub use redemption_repo::RedemptionRepository;
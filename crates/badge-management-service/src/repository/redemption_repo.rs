@@ -7,7 +7,9 @@ use sqlx::{PgConnection, PgPool, Row};
 
 use super::traits::RedemptionRepositoryTrait;
 use crate::error::Result;
-use crate::models::{BadgeRedemptionRule, Benefit, OrderStatus, RedemptionDetail, RedemptionOrder};
+use crate::models::{
+    BadgeRedemptionRule, Benefit, OrderStatus, RecipeHolding, RedemptionDetail, RedemptionOrder,
+};
 
 /// 兑换仓储
 ///
@@ -105,7 +107,7 @@ impl RedemptionRepository {
     pub async fn get_redemption_rule(&self, id: i64) -> Result<Option<BadgeRedemptionRule>> {
         let rule = sqlx::query_as::<_, BadgeRedemptionRule>(
             r#"
-            SELECT id, name, description, benefit_id, required_badges,
+            SELECT id, name, description, benefit_id, required_badges, recipe,
                    frequency_config, validity_type, relative_days,
                    start_time, end_time, enabled,
                    created_at, updated_at
@@ -122,20 +124,25 @@ impl RedemptionRepository {
 
     /// 列出某徽章关联的兑换规则
     ///
-    /// 通过 required_badges JSON 字段进行匹配
+    /// 通过 required_badges JSON 字段，或兑换配方中直接引用该徽章的条目进行匹配
     pub async fn list_rules_by_badge(&self, badge_id: i64) -> Result<Vec<BadgeRedemptionRule>> {
         // 使用 PostgreSQL JSON 查询功能匹配包含指定徽章的规则
         let rules = sqlx::query_as::<_, BadgeRedemptionRule>(
             r#"
-            SELECT id, name, description, benefit_id, required_badges,
+            SELECT id, name, description, benefit_id, required_badges, recipe,
                    frequency_config, validity_type, relative_days,
                    start_time, end_time, enabled,
                    created_at, updated_at
             FROM badge_redemption_rules
             WHERE enabled = true
-              AND EXISTS (
-                  SELECT 1 FROM jsonb_array_elements(required_badges) AS elem
-                  WHERE (elem->>'badgeId')::bigint = $1
+              AND (
+                  EXISTS (
+                      SELECT 1 FROM jsonb_array_elements(required_badges) AS elem
+                      WHERE (elem->>'badgeId')::bigint = $1
+                  )
+                  OR jsonb_path_exists(
+                      recipe, '$.**.badgeId ? (@ == $badge)', jsonb_build_object('badge', $1)
+                  )
               )
             ORDER BY id ASC
             "#,
@@ -151,7 +158,7 @@ impl RedemptionRepository {
     pub async fn list_active_rules(&self) -> Result<Vec<BadgeRedemptionRule>> {
         let rules = sqlx::query_as::<_, BadgeRedemptionRule>(
             r#"
-            SELECT id, name, description, benefit_id, required_badges,
+            SELECT id, name, description, benefit_id, required_badges, recipe,
                    frequency_config, validity_type, relative_days,
                    start_time, end_time, enabled,
                    created_at, updated_at
@@ -172,7 +179,7 @@ impl RedemptionRepository {
     pub async fn list_auto_redeem_rules(&self) -> Result<Vec<BadgeRedemptionRule>> {
         let rules = sqlx::query_as::<_, BadgeRedemptionRule>(
             r#"
-            SELECT id, name, description, benefit_id, required_badges,
+            SELECT id, name, description, benefit_id, required_badges, recipe,
                    frequency_config, validity_type, relative_days,
                    start_time, end_time, enabled,
                    created_at, updated_at
@@ -196,7 +203,7 @@ impl RedemptionRepository {
     ) -> Result<Vec<BadgeRedemptionRule>> {
         let rules = sqlx::query_as::<_, BadgeRedemptionRule>(
             r#"
-            SELECT id, name, description, benefit_id, required_badges,
+            SELECT id, name, description, benefit_id, required_badges, recipe,
                    frequency_config, validity_type, relative_days,
                    start_time, end_time, enabled,
                    created_at, updated_at
@@ -381,6 +388,144 @@ impl RedemptionRepository {
         Ok(())
     }
 
+    /// 在事务中按订单号锁定订单
+    ///
+    /// 取消订单时使用，同一订单的并发取消在此串行化
+    pub async fn get_order_by_no_for_update_in_tx(
+        tx: &mut PgConnection,
+        order_no: &str,
+    ) -> Result<Option<RedemptionOrder>> {
+        let order = sqlx::query_as::<_, RedemptionOrder>(
+            r#"
+            SELECT id, order_no, user_id, redemption_rule_id AS rule_id, benefit_id, status,
+                   failure_reason, benefit_result, idempotency_key,
+                   created_at, updated_at
+            FROM redemption_orders
+            WHERE order_no = $1
+            FOR UPDATE
+            "#,
+        )
+        .bind(order_no)
+        .fetch_optional(tx)
+        .await?;
+
+        Ok(order)
+    }
+
+    /// 在事务中将订单置为取消中并记录取消信息
+    ///
+    /// 提交后再撤销外部权益，撤销或退回中断时订单停留在取消中，重试时继续执行
+    pub async fn mark_order_cancelling_in_tx(
+        tx: &mut PgConnection,
+        id: i64,
+        cancel_reason: Option<&str>,
+        cancelled_by: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE redemption_orders
+            SET status = $2, cancel_reason = $3, cancelled_by = $4, updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(OrderStatus::Cancelling)
+        .bind(cancel_reason)
+        .bind(cancelled_by)
+        .execute(tx)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 在事务中占用取消中订单的权益撤销
+    ///
+    /// 撤销外部权益时不持有订单行锁，由 `revoke_started_at` 保证同一订单同时只有一个撤销在进行。
+    /// 已有撤销且未超过租约时返回 false；租约过期视为上一次撤销的进程已中断，可以重新占用。
+    pub async fn claim_benefit_revoke_in_tx(
+        tx: &mut PgConnection,
+        id: i64,
+        lease: chrono::Duration,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE redemption_orders
+            SET revoke_started_at = NOW(), updated_at = NOW()
+            WHERE id = $1 AND status = $2
+              AND (revoke_started_at IS NULL
+                   OR revoke_started_at < NOW() - make_interval(secs => $3))
+            "#,
+        )
+        .bind(id)
+        .bind(OrderStatus::Cancelling)
+        .bind(lease.num_seconds() as f64)
+        .execute(tx)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// 释放权益撤销占用，订单保持取消中
+    ///
+    /// 撤销完成或结果未知时调用，之后的取消请求可以继续执行
+    pub async fn release_benefit_revoke(&self, id: i64) -> Result<()> {
+        sqlx::query("UPDATE redemption_orders SET revoke_started_at = NULL WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// 将取消中的订单恢复为成功并释放撤销占用
+    ///
+    /// 权益明确拒绝撤销时调用，订单不予取消
+    pub async fn restore_cancelling_order(&self, id: i64) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE redemption_orders
+            SET status = $2, cancel_reason = NULL, cancelled_by = NULL,
+                revoke_started_at = NULL, updated_at = NOW()
+            WHERE id = $1 AND status = $3
+            "#,
+        )
+        .bind(id)
+        .bind(OrderStatus::Success)
+        .bind(OrderStatus::Cancelling)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 在事务中将订单置为已取消并记录取消信息
+    ///
+    /// 返回取消时间
+    pub async fn cancel_order_in_tx(
+        tx: &mut PgConnection,
+        id: i64,
+        cancel_reason: Option<&str>,
+        cancelled_by: &str,
+    ) -> Result<chrono::DateTime<chrono::Utc>> {
+        let cancelled_at = sqlx::query_scalar(
+            r#"
+            UPDATE redemption_orders
+            SET status = $2, cancelled_at = NOW(), cancel_reason = $3, cancelled_by = $4,
+                revoke_started_at = NULL, updated_at = NOW()
+            WHERE id = $1
+            RETURNING cancelled_at
+            "#,
+        )
+        .bind(id)
+        .bind(OrderStatus::Cancelled)
+        .bind(cancel_reason)
+        .bind(cancelled_by)
+        .fetch_one(tx)
+        .await?;
+
+        Ok(cancelled_at)
+    }
+
     /// 更新订单权益结果
     pub async fn update_order_benefit_result(
         &self,
@@ -441,13 +586,14 @@ impl RedemptionRepository {
                 r#"
                 SELECT COUNT(*) as count
                 FROM redemption_orders
-                WHERE user_id = $1 AND redemption_rule_id = $2 AND status = $3 AND created_at >= $4
+                WHERE user_id = $1 AND redemption_rule_id = $2 AND status IN ($3, $5) AND created_at >= $4
                 "#,
             )
             .bind(user_id)
             .bind(redemption_rule_id)
             .bind(OrderStatus::Success)
             .bind(since_time)
+            .bind(OrderStatus::Cancelling)
             .fetch_one(&self.pool)
             .await?
         } else {
@@ -455,12 +601,13 @@ impl RedemptionRepository {
                 r#"
                 SELECT COUNT(*) as count
                 FROM redemption_orders
-                WHERE user_id = $1 AND redemption_rule_id = $2 AND status = $3
+                WHERE user_id = $1 AND redemption_rule_id = $2 AND status IN ($3, $4)
                 "#,
             )
             .bind(user_id)
             .bind(redemption_rule_id)
             .bind(OrderStatus::Success)
+            .bind(OrderStatus::Cancelling)
             .fetch_one(&self.pool)
             .await?
         };
@@ -468,14 +615,43 @@ impl RedemptionRepository {
         Ok(count)
     }
 
+    /// 在事务中统计用户对某规则的成功兑换次数
+    ///
+    /// 调用方需先持有该用户+规则的咨询锁，保证计数与随后的下单之间不会被并发兑换穿插。
+    /// `since` 为空时统计全部历史。取消中的订单尚未退回，仍计入次数。
+    pub async fn count_user_redemptions_in_tx(
+        tx: &mut PgConnection,
+        user_id: &str,
+        redemption_rule_id: i64,
+        since: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<i64> {
+        let count: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) as count
+            FROM redemption_orders
+            WHERE user_id = $1 AND redemption_rule_id = $2 AND status IN ($3, $5)
+              AND ($4::timestamptz IS NULL OR created_at >= $4)
+            "#,
+        )
+        .bind(user_id)
+        .bind(redemption_rule_id)
+        .bind(OrderStatus::Success)
+        .bind(since)
+        .bind(OrderStatus::Cancelling)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        Ok(count)
+    }
+
     // ==================== 兑换明细 ====================
 
     /// 创建兑换明细
     pub async fn create_detail(&self, detail: &RedemptionDetail) -> Result<i64> {
         let row = sqlx::query(
             r#"
-            INSERT INTO redemption_details (order_id, user_badge_id, badge_id, quantity, created_at)
-            VALUES ($1, $2, $3, $4, $5)
+            INSERT INTO redemption_details (order_id, user_badge_id, badge_id, quantity, recipe_term, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
             RETURNING id
             "#,
         )
@@ -483,6 +659,7 @@ impl RedemptionRepository {
         .bind(detail.user_badge_id)
         .bind(detail.badge_id)
         .bind(detail.quantity)
+        .bind(detail.recipe_term)
         .bind(detail.created_at)
         .fetch_one(&self.pool)
         .await?;
@@ -497,8 +674,8 @@ impl RedemptionRepository {
     ) -> Result<i64> {
         let row = sqlx::query(
             r#"
-            INSERT INTO redemption_details (order_id, user_badge_id, badge_id, quantity, created_at)
-            VALUES ($1, $2, $3, $4, $5)
+            INSERT INTO redemption_details (order_id, user_badge_id, badge_id, quantity, recipe_term, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
             RETURNING id
             "#,
         )
@@ -506,6 +683,7 @@ impl RedemptionRepository {
         .bind(detail.user_badge_id)
         .bind(detail.badge_id)
         .bind(detail.quantity)
+        .bind(detail.recipe_term)
         .bind(detail.created_at)
         .fetch_one(tx)
         .await?;
@@ -513,11 +691,81 @@ impl RedemptionRepository {
         Ok(row.get("id"))
     }
 
+    /// 在事务中锁定并查询可用于兑换配方的用户徽章
+    ///
+    /// 只返回有效、未过期且被配方引用（指定徽章、所属系列或分类）的持有记录，
+    /// 按 user_badges.id 顺序加行锁，避免并发兑换之间死锁。
+    pub async fn list_recipe_holdings_for_update_in_tx(
+        tx: &mut PgConnection,
+        user_id: &str,
+        badge_ids: &[i64],
+        series_ids: &[i64],
+        category_ids: &[i64],
+    ) -> Result<Vec<RecipeHolding>> {
+        let holdings = sqlx::query_as::<_, RecipeHolding>(
+            r#"
+            SELECT ub.id AS user_badge_id, ub.badge_id, b.series_id, s.category_id,
+                   ub.quantity, ub.first_acquired_at, ub.expires_at
+            FROM user_badges ub
+            JOIN badges b ON b.id = ub.badge_id
+            JOIN badge_series s ON s.id = b.series_id
+            WHERE ub.user_id = $1
+              AND ub.status = 'active'
+              AND ub.quantity > 0
+              AND (ub.expires_at IS NULL OR ub.expires_at > NOW())
+              AND (ub.badge_id = ANY($2) OR b.series_id = ANY($3) OR s.category_id = ANY($4))
+            ORDER BY ub.id
+            FOR UPDATE OF ub
+            "#,
+        )
+        .bind(user_id)
+        .bind(badge_ids)
+        .bind(series_ids)
+        .bind(category_ids)
+        .fetch_all(tx)
+        .await?;
+
+        Ok(holdings)
+    }
+
+    /// 查询可用于兑换配方的用户徽章（不加锁，用于可兑换目录展示）
+    pub async fn list_recipe_holdings(
+        &self,
+        user_id: &str,
+        badge_ids: &[i64],
+        series_ids: &[i64],
+        category_ids: &[i64],
+    ) -> Result<Vec<RecipeHolding>> {
+        let holdings = sqlx::query_as::<_, RecipeHolding>(
+            r#"
+            SELECT ub.id AS user_badge_id, ub.badge_id, b.series_id, s.category_id,
+                   ub.quantity, ub.first_acquired_at, ub.expires_at
+            FROM user_badges ub
+            JOIN badges b ON b.id = ub.badge_id
+            JOIN badge_series s ON s.id = b.series_id
+            WHERE ub.user_id = $1
+              AND ub.status = 'active'
+              AND ub.quantity > 0
+              AND (ub.expires_at IS NULL OR ub.expires_at > NOW())
+              AND (ub.badge_id = ANY($2) OR b.series_id = ANY($3) OR s.category_id = ANY($4))
+            ORDER BY ub.id
+            "#,
+        )
+        .bind(user_id)
+        .bind(badge_ids)
+        .bind(series_ids)
+        .bind(category_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(holdings)
+    }
+
     /// 列出订单的兑换明细
     pub async fn list_details_by_order(&self, order_id: i64) -> Result<Vec<RedemptionDetail>> {
         let details = sqlx::query_as::<_, RedemptionDetail>(
             r#"
-            SELECT id, order_id, user_badge_id, badge_id, quantity, created_at
+            SELECT id, order_id, user_badge_id, badge_id, quantity, recipe_term, created_at
             FROM redemption_details
             WHERE order_id = $1
             ORDER BY id ASC
@@ -529,6 +777,42 @@ impl RedemptionRepository {
 
         Ok(details)
     }
+
+    /// 在事务中列出订单尚未退回的兑换明细
+    pub async fn list_unreversed_details_in_tx(
+        tx: &mut PgConnection,
+        order_id: i64,
+    ) -> Result<Vec<RedemptionDetail>> {
+        let details = sqlx::query_as::<_, RedemptionDetail>(
+            r#"
+            SELECT id, order_id, user_badge_id, badge_id, quantity, recipe_term, created_at
+            FROM redemption_details
+            WHERE order_id = $1 AND reversed_at IS NULL
+            ORDER BY id ASC
+            "#,
+        )
+        .bind(order_id)
+        .fetch_all(tx)
+        .await?;
+
+        Ok(details)
+    }
+
+    /// 在事务中将兑换明细标记为已退回
+    pub async fn mark_detail_reversed_in_tx(tx: &mut PgConnection, id: i64) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE redemption_details
+            SET reversed_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .execute(tx)
+        .await?;
+
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -49,6 +49,8 @@ pub trait UserBadgeRepositoryTrait: Send + Sync {
         user_id: &str,
         status: UserBadgeStatus,
     ) -> Result<Vec<UserBadge>>;
+    /// 用户有效的置顶徽章 (user_badge_id, pin_order)，按展示顺序排列
+    async fn list_pinned(&self, user_id: &str) -> Result<Vec<(i64, i32)>>;
     async fn create_user_badge(&self, badge: &UserBadge) -> Result<i64>;
     async fn update_user_badge(&self, badge: &UserBadge) -> Result<()>;
     async fn update_user_badge_quantity(&self, id: i64, delta: i32) -> Result<()>;
@@ -2,6 +2,8 @@
 //!
 //! 提供用户持有徽章的数据访问，支持事务和行级锁
 
+use std::collections::HashMap;
+
 use async_trait::async_trait;
 use sqlx::{PgConnection, PgPool, Row};
 
@@ -21,6 +23,11 @@ impl UserBadgeRepository {
         Self { pool }
     }
 
+    /// 连接池，供需要在同一事务内组合多个 `*_in_tx` 操作的调用方开启事务
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+
     // ==================== 查询操作 ====================
 
     /// 获取用户的某个徽章记录
@@ -29,7 +36,7 @@ impl UserBadgeRepository {
             r#"
             SELECT id, user_id, badge_id, status, quantity, first_acquired_at AS acquired_at,
                    expires_at, source_type, source_ref, expire_reminded, expired_at,
-                   recipient_type, actual_user_id, created_at, updated_at
+                   recipient_type, actual_user_id, level, created_at, updated_at
             FROM user_badges
             WHERE user_id = $1 AND badge_id = $2
             "#,
@@ -48,7 +55,7 @@ impl UserBadgeRepository {
             r#"
             SELECT id, user_id, badge_id, status, quantity, first_acquired_at AS acquired_at,
                    expires_at, source_type, source_ref, expire_reminded, expired_at,
-                   recipient_type, actual_user_id, created_at, updated_at
+                   recipient_type, actual_user_id, level, created_at, updated_at
             FROM user_badges
             WHERE id = $1
             "#,
@@ -66,7 +73,7 @@ impl UserBadgeRepository {
             r#"
             SELECT id, user_id, badge_id, status, quantity, first_acquired_at AS acquired_at,
                    expires_at, source_type, source_ref, expire_reminded, expired_at,
-                   recipient_type, actual_user_id, created_at, updated_at
+                   recipient_type, actual_user_id, level, created_at, updated_at
             FROM user_badges
             WHERE user_id = $1
             ORDER BY first_acquired_at DESC
@@ -89,7 +96,7 @@ impl UserBadgeRepository {
             r#"
             SELECT id, user_id, badge_id, status, quantity, first_acquired_at AS acquired_at,
                    expires_at, source_type, source_ref, expire_reminded, expired_at,
-                   recipient_type, actual_user_id, created_at, updated_at
+                   recipient_type, actual_user_id, level, created_at, updated_at
             FROM user_badges
             WHERE user_id = $1 AND status = $2
             ORDER BY first_acquired_at DESC
@@ -123,6 +130,29 @@ impl UserBadgeRepository {
         Ok(badge_ids)
     }
 
+    /// 获取用户置顶徽章的展示顺序
+    ///
+    /// 返回 (user_badge_id, pin_order)，按 pin_order 升序。
+    /// 只包含有效徽章：撤销或过期后置顶记录保留，但不再占用展示位。
+    pub async fn list_pinned(&self, user_id: &str) -> Result<Vec<(i64, i32)>> {
+        let pinned = sqlx::query_as::<_, (i64, i32)>(
+            r#"
+            SELECT id, pin_order
+            FROM user_badges
+            WHERE user_id = $1
+              AND pin_order IS NOT NULL
+              AND UPPER(status) = 'ACTIVE'
+              AND (expires_at IS NULL OR expires_at > NOW())
+            ORDER BY pin_order, pinned_at
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(pinned)
+    }
+
     // ==================== 写入操作 ====================
 
     /// 创建用户徽章记录
@@ -131,8 +161,8 @@ impl UserBadgeRepository {
     pub async fn create_user_badge(&self, badge: &UserBadge) -> Result<i64> {
         let row = sqlx::query(
             r#"
-            INSERT INTO user_badges (user_id, badge_id, status, quantity, first_acquired_at, expires_at, source_type, source_ref, recipient_type, actual_user_id, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            INSERT INTO user_badges (user_id, badge_id, status, quantity, first_acquired_at, expires_at, source_type, source_ref, recipient_type, actual_user_id, level, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
             RETURNING id
             "#,
         )
@@ -146,6 +176,7 @@ impl UserBadgeRepository {
         .bind(&badge.source_ref)
         .bind(badge.recipient_type)
         .bind(&badge.actual_user_id)
+        .bind(badge.level)
         .bind(badge.created_at)
         .bind(badge.updated_at)
         .fetch_one(&self.pool)
@@ -206,7 +237,7 @@ impl UserBadgeRepository {
             r#"
             SELECT id, user_id, badge_id, status, quantity, first_acquired_at AS acquired_at,
                    expires_at, source_type, source_ref, expire_reminded, expired_at,
-                   recipient_type, actual_user_id, created_at, updated_at
+                   recipient_type, actual_user_id, level, created_at, updated_at
             FROM user_badges
             WHERE user_id = $1 AND badge_id = $2
             FOR UPDATE
@@ -220,12 +251,64 @@ impl UserBadgeRepository {
         Ok(user_badge)
     }
 
+    /// 在事务中按 ID 批量获取用户徽章（带行级锁）
+    ///
+    /// 按 id 顺序加锁，与兑换扣减的加锁顺序一致，避免死锁
+    pub async fn list_user_badges_for_update_in_tx(
+        tx: &mut PgConnection,
+        ids: &[i64],
+    ) -> Result<Vec<UserBadge>> {
+        let user_badges = sqlx::query_as::<_, UserBadge>(
+            r#"
+            SELECT id, user_id, badge_id, status, quantity, first_acquired_at AS acquired_at,
+                   expires_at, source_type, source_ref, expire_reminded, expired_at,
+                   recipient_type, actual_user_id, level, created_at, updated_at
+            FROM user_badges
+            WHERE id = ANY($1)
+            ORDER BY id
+            FOR UPDATE
+            "#,
+        )
+        .bind(ids)
+        .fetch_all(tx)
+        .await?;
+
+        Ok(user_badges)
+    }
+
+    /// 在事务中统计用户各徽章的有效持有数量
+    ///
+    /// 只计入 status=Active 且未过期的记录，与级联评估的前置条件口径一致
+    pub async fn active_quantities_in_tx(
+        tx: &mut PgConnection,
+        user_id: &str,
+    ) -> Result<HashMap<i64, i32>> {
+        let rows = sqlx::query_as::<_, (i64, i64)>(
+            r#"
+            SELECT badge_id, SUM(quantity)::BIGINT
+            FROM user_badges
+            WHERE user_id = $1
+              AND status = 'active'
+              AND (expires_at IS NULL OR expires_at > NOW())
+            GROUP BY badge_id
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(tx)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(badge_id, quantity)| (badge_id, quantity as i32))
+            .collect())
+    }
+
     /// 在事务中创建用户徽章
     pub async fn create_user_badge_in_tx(tx: &mut PgConnection, badge: &UserBadge) -> Result<i64> {
         let row = sqlx::query(
             r#"
-            INSERT INTO user_badges (user_id, badge_id, status, quantity, first_acquired_at, expires_at, source_type, source_ref, recipient_type, actual_user_id, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            INSERT INTO user_badges (user_id, badge_id, status, quantity, first_acquired_at, expires_at, source_type, source_ref, recipient_type, actual_user_id, level, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
             RETURNING id
             "#,
         )
@@ -239,6 +322,7 @@ impl UserBadgeRepository {
         .bind(&badge.source_ref)
         .bind(badge.recipient_type)
         .bind(&badge.actual_user_id)
+        .bind(badge.level)
         .bind(badge.created_at)
         .bind(badge.updated_at)
         .fetch_one(tx)
@@ -268,6 +352,29 @@ impl UserBadgeRepository {
         Ok(())
     }
 
+    /// 在事务中提升用户徽章等级
+    ///
+    /// 等级只升不降，返回是否发生了升级
+    pub async fn upgrade_user_badge_level_in_tx(
+        tx: &mut PgConnection,
+        id: i64,
+        level: i32,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE user_badges
+            SET level = $2, updated_at = NOW()
+            WHERE id = $1 AND (level IS NULL OR level < $2)
+            "#,
+        )
+        .bind(id)
+        .bind(level)
+        .execute(tx)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
     /// 在事务中更新用户徽章状态
     pub async fn update_user_badge_status_in_tx(
         tx: &mut PgConnection,
@@ -320,6 +427,10 @@ impl UserBadgeRepositoryTrait for UserBadgeRepository {
         self.update_user_badge(badge).await
     }
 
+    async fn list_pinned(&self, user_id: &str) -> Result<Vec<(i64, i32)>> {
+        self.list_pinned(user_id).await
+    }
+
     async fn update_user_badge_quantity(&self, id: i64, delta: i32) -> Result<()> {
         self.update_user_badge_quantity(id, delta).await
     }
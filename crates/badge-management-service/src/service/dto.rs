@@ -6,7 +6,8 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use crate::models::{
-    BadgeAssets, BadgeType, BenefitType, SourceType, UserBadgeStatus, ValidityConfig,
+    BadgeAssets, BadgeType, BenefitType, RecipeShortfall, RedemptionRecipe, RequiredBadge,
+    SourceType, UserBadgeStatus, ValidityConfig,
 };
 
 /// 用户徽章 DTO
@@ -15,6 +16,9 @@ use crate::models::{
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UserBadgeDto {
+    /// user_badges 表主键，置顶等针对持有记录的操作使用
+    #[serde(default)]
+    pub user_badge_id: i64,
     pub badge_id: i64,
     pub badge_name: String,
     pub badge_type: BadgeType,
@@ -23,7 +27,23 @@ pub struct UserBadgeDto {
     pub acquired_at: DateTime<Utc>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub expires_at: Option<DateTime<Utc>>,
+    /// 展示资源，分级徽章为当前等级的资源
     pub assets: BadgeAssets,
+    /// 置顶展示顺序（从 1 开始），未置顶为 None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pin_order: Option<i32>,
+    /// 分级徽章当前等级，非分级徽章为 None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<i32>,
+    /// 当前等级名称
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level_name: Option<String>,
+}
+
+impl UserBadgeDto {
+    pub fn is_pinned(&self) -> bool {
+        self.pin_order.is_some()
+    }
 }
 
 /// 徽章详情 DTO
@@ -73,6 +93,9 @@ pub struct BenefitSummaryDto {
 #[serde(rename_all = "camelCase")]
 pub struct BadgeWallDto {
     pub total_count: i32,
+    /// 置顶徽章，按展示顺序排列
+    #[serde(default)]
+    pub pinned: Vec<UserBadgeDto>,
     pub categories: Vec<BadgeWallCategoryDto>,
 }
 
@@ -160,6 +183,122 @@ pub struct CategoryStatsDto {
     pub count: i32,
 }
 
+// ==================== 进度查询 DTO ====================
+
+/// 进度项类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProgressKind {
+    /// 规则中的聚合条件（如"30 天内签到 5 次"）
+    RuleAggregate,
+    /// 级联前置徽章
+    Prerequisite,
+    /// 分级徽章的下一等级
+    NextLevel,
+}
+
+impl ProgressKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::RuleAggregate => "rule_aggregate",
+            Self::Prerequisite => "prerequisite",
+            Self::NextLevel => "next_level",
+        }
+    }
+}
+
+/// 单项进度 DTO
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgressItemDto {
+    pub kind: ProgressKind,
+    /// 展示标签：聚合口径、前置徽章名称或下一等级名称
+    pub label: String,
+    pub current: f64,
+    pub target: f64,
+    /// 完成百分比（0 ~ 100）
+    pub percentage: f64,
+    pub achieved: bool,
+    /// 前置徽章 ID（仅 prerequisite）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub related_badge_id: Option<i64>,
+    /// 目标等级（仅 next_level）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level: Option<i32>,
+}
+
+impl ProgressItemDto {
+    /// 按当前值和目标值构造数量类进度项
+    pub fn quantity(
+        kind: ProgressKind,
+        label: impl Into<String>,
+        current: f64,
+        target: f64,
+    ) -> Self {
+        let achieved = current >= target;
+        let percentage = if achieved {
+            100.0
+        } else if target > 0.0 {
+            (current / target * 100.0).clamp(0.0, 100.0)
+        } else {
+            0.0
+        };
+
+        Self {
+            kind,
+            label: label.into(),
+            current,
+            target,
+            percentage,
+            achieved,
+            related_badge_id: None,
+            level: None,
+        }
+    }
+}
+
+/// 徽章进度 DTO
+///
+/// 未获得的徽章展示规则聚合条件和前置徽章的完成情况，
+/// 已获得的分级徽章展示距离下一等级的进度。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BadgeProgressDto {
+    pub badge_id: i64,
+    pub badge_name: String,
+    /// 是否已持有（有效且数量大于 0）
+    pub earned: bool,
+    pub quantity: i32,
+    /// 各进度项的平均完成度；没有进度项时已持有为 100，否则为 0
+    pub percentage: f64,
+    pub items: Vec<ProgressItemDto>,
+}
+
+impl BadgeProgressDto {
+    pub fn new(
+        badge_id: i64,
+        badge_name: impl Into<String>,
+        quantity: i32,
+        items: Vec<ProgressItemDto>,
+    ) -> Self {
+        let earned = quantity > 0;
+        let percentage = if items.is_empty() {
+            if earned { 100.0 } else { 0.0 }
+        } else {
+            items.iter().map(|i| i.percentage).sum::<f64>() / items.len() as f64
+        };
+
+        Self {
+            badge_id,
+            badge_name: badge_name.into(),
+            earned,
+            quantity,
+            percentage,
+            items,
+        }
+    }
+}
+
 // ==================== 发放服务 DTO ====================
 
 /// 徽章发放请求
@@ -286,6 +425,9 @@ pub struct GrantResult {
     /// 错误信息（失败时返回）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    /// 错误码（失败时返回，取自 `BadgeError::error_code`）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<String>,
 }
 
 impl GrantResult {
@@ -297,6 +439,7 @@ impl GrantResult {
             user_badge_id: Some(user_badge_id),
             new_quantity: Some(new_quantity),
             error: None,
+            error_code: None,
         }
     }
 
@@ -308,8 +451,15 @@ impl GrantResult {
             user_badge_id: None,
             new_quantity: None,
             error: Some(error.into()),
+            error_code: None,
         }
     }
+
+    /// 附加错误码，便于调用方按错误类型归类失败记录
+    pub fn with_error_code(mut self, code: impl Into<String>) -> Self {
+        self.error_code = Some(code.into());
+        self
+    }
 }
 
 #[cfg(test)]
@@ -319,6 +469,7 @@ mod tests {
     #[test]
     fn test_user_badge_dto_serialization() {
         let dto = UserBadgeDto {
+            user_badge_id: 100,
             badge_id: 1,
             badge_name: "Test Badge".to_string(),
             badge_type: BadgeType::Normal,
@@ -331,21 +482,29 @@ mod tests {
                 image_url: None,
                 animation_url: None,
                 disabled_icon_url: None,
+                levels: vec![],
             },
+            pin_order: None,
+            level: None,
+            level_name: None,
         };
 
         let json = serde_json::to_value(&dto).unwrap();
+        assert_eq!(json["userBadgeId"], 100);
         assert_eq!(json["badgeId"], 1);
         assert_eq!(json["badgeName"], "Test Badge");
         assert_eq!(json["badgeType"], "NORMAL");
         // expires_at 为 None 时不应出现在 JSON 中
         assert!(!json.as_object().unwrap().contains_key("expiresAt"));
+        assert!(!json.as_object().unwrap().contains_key("pinOrder"));
+        assert!(!dto.is_pinned());
     }
 
     #[test]
     fn test_badge_wall_dto_structure() {
         let wall = BadgeWallDto {
             total_count: 5,
+            pinned: vec![],
             categories: vec![BadgeWallCategoryDto {
                 category_id: 1,
                 category_name: "Trading".to_string(),
@@ -355,6 +514,7 @@ mod tests {
 
         let json = serde_json::to_value(&wall).unwrap();
         assert_eq!(json["totalCount"], 5);
+        assert!(json["pinned"].is_array());
         assert!(json["categories"].is_array());
     }
 
@@ -445,6 +605,9 @@ pub struct RevokeBadgeRequest {
     /// 来源关联 ID
     #[serde(skip_serializing_if = "Option::is_none")]
     pub source_ref_id: Option<String>,
+    /// 幂等键（批量任务重试时保持不变，防止重复撤销）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
 }
 
 impl RevokeBadgeRequest {
@@ -464,6 +627,7 @@ impl RevokeBadgeRequest {
             operator: Some(operator.into()),
             source_type: SourceType::Manual,
             source_ref_id: None,
+            idempotency_key: None,
         }
     }
 
@@ -482,6 +646,7 @@ impl RevokeBadgeRequest {
             operator: None,
             source_type: SourceType::System,
             source_ref_id: None,
+            idempotency_key: None,
         }
     }
 
@@ -490,6 +655,12 @@ impl RevokeBadgeRequest {
         self.source_ref_id = Some(ref_id.into());
         self
     }
+
+    /// 设置幂等键
+    pub fn with_idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.idempotency_key = Some(key.into());
+        self
+    }
 }
 
 /// 徽章取消响应
@@ -520,6 +691,14 @@ impl RevokeBadgeResponse {
             message: message.into(),
         }
     }
+
+    pub fn from_existing(remaining_quantity: i32) -> Self {
+        Self {
+            success: true,
+            remaining_quantity,
+            message: "幂等请求，撤销已执行过".to_string(),
+        }
+    }
 }
 
 /// 批量取消响应
@@ -552,6 +731,9 @@ pub struct RevokeResult {
     /// 错误信息（失败时返回）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    /// 错误码（失败时返回，取自 `BadgeError::error_code`）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<String>,
 }
 
 impl RevokeResult {
@@ -562,6 +744,7 @@ impl RevokeResult {
             success: true,
             remaining_quantity: Some(remaining_quantity),
             error: None,
+            error_code: None,
         }
     }
 
@@ -572,8 +755,15 @@ impl RevokeResult {
             success: false,
             remaining_quantity: None,
             error: Some(error.into()),
+            error_code: None,
         }
     }
+
+    /// 附加错误码，便于调用方按错误类型归类失败记录
+    pub fn with_error_code(mut self, code: impl Into<String>) -> Self {
+        self.error_code = Some(code.into());
+        self
+    }
 }
 
 // ==================== 兑换服务 DTO ====================
@@ -590,6 +780,9 @@ pub struct RedeemBadgeRequest {
     pub rule_id: i64,
     /// 幂等键（防止重复提交）
     pub idempotency_key: String,
+    /// 用户指定消耗的徽章（为空时按兑换配方的选择策略挑选）
+    #[serde(default)]
+    pub selected_badges: Vec<RequiredBadge>,
 }
 
 impl RedeemBadgeRequest {
@@ -603,8 +796,15 @@ impl RedeemBadgeRequest {
             user_id: user_id.into(),
             rule_id,
             idempotency_key: idempotency_key.into(),
+            selected_badges: Vec::new(),
         }
     }
+
+    /// 指定本次兑换消耗的徽章
+    pub fn with_selected_badges(mut self, selected_badges: Vec<RequiredBadge>) -> Self {
+        self.selected_badges = selected_badges;
+        self
+    }
 }
 
 /// 徽章兑换响应
@@ -692,6 +892,156 @@ pub struct ConsumedBadgeDto {
     pub quantity: i32,
 }
 
+/// 兑换规则对用户的可兑换状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedeemableStatus {
+    /// 当前即可兑换
+    Eligible,
+    /// 持有徽章不满足兑换配方
+    MissingBadges,
+    /// 已达兑换频率上限
+    FrequencyLimited,
+    /// 权益库存不足或已停用
+    OutOfStock,
+}
+
+impl RedeemableStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Eligible => "eligible",
+            Self::MissingBadges => "missing_badges",
+            Self::FrequencyLimited => "frequency_limited",
+            Self::OutOfStock => "out_of_stock",
+        }
+    }
+}
+
+/// 可兑换权益 DTO
+///
+/// 一条有效兑换规则与用户持有徽章的比对结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedeemableBenefitDto {
+    pub rule_id: i64,
+    pub rule_name: String,
+    pub benefit_id: i64,
+    pub benefit_name: String,
+    pub benefit_type: BenefitType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon_url: Option<String>,
+    /// 剩余库存（None 表示不限量）
+    pub remaining_stock: Option<i64>,
+    /// 多个原因同时存在时取最靠前的一个：库存不足 > 频率受限 > 缺少徽章
+    pub status: RedeemableStatus,
+    /// 不可兑换的全部原因说明，可兑换时为空
+    pub reasons: Vec<String>,
+    /// 未满足的配方条目
+    pub missing: Vec<RecipeShortfall>,
+    /// 达到上限的频率维度（per_day / per_week / per_month / per_user）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limited_by: Option<String>,
+    pub recipe: RedemptionRecipe,
+    /// 规则截止时间（None 表示长期有效）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<DateTime<Utc>>,
+}
+
+impl RedeemableBenefitDto {
+    pub fn is_eligible(&self) -> bool {
+        self.status == RedeemableStatus::Eligible
+    }
+}
+
+/// 可兑换权益分页结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedeemableBenefitPage {
+    pub items: Vec<RedeemableBenefitDto>,
+    pub total: i64,
+    pub page: i64,
+    pub page_size: i64,
+}
+
+/// 兑换订单取消的发起方
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CancelInitiator {
+    /// 用户取消：只能取消本人订单，且须在可取消时限内
+    User { user_id: String },
+    /// 管理员取消：不受时限限制
+    Admin { operator: String },
+}
+
+impl CancelInitiator {
+    /// 写入订单 cancelled_by 的发起人标识
+    pub fn cancelled_by(&self) -> String {
+        match self {
+            Self::User { user_id } => format!("user:{}", user_id),
+            Self::Admin { operator } => operator.clone(),
+        }
+    }
+}
+
+/// 兑换订单取消请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelRedemptionRequest {
+    /// 订单号
+    pub order_no: String,
+    /// 发起方
+    pub initiator: CancelInitiator,
+    /// 取消原因
+    pub reason: Option<String>,
+}
+
+impl CancelRedemptionRequest {
+    /// 用户取消本人订单
+    pub fn by_user(
+        order_no: impl Into<String>,
+        user_id: impl Into<String>,
+        reason: Option<String>,
+    ) -> Self {
+        Self {
+            order_no: order_no.into(),
+            initiator: CancelInitiator::User {
+                user_id: user_id.into(),
+            },
+            reason,
+        }
+    }
+
+    /// 管理员取消订单
+    pub fn by_admin(
+        order_no: impl Into<String>,
+        operator: impl Into<String>,
+        reason: Option<String>,
+    ) -> Self {
+        Self {
+            order_no: order_no.into(),
+            initiator: CancelInitiator::Admin {
+                operator: operator.into(),
+            },
+            reason,
+        }
+    }
+}
+
+/// 兑换订单取消响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelRedemptionResponse {
+    pub order_id: i64,
+    pub order_no: String,
+    /// 订单此前已取消（重复请求），本次未做任何变更
+    pub already_cancelled: bool,
+    /// 本次是否撤销了已发放的权益
+    pub benefit_revoked: bool,
+    /// 本次退回的徽章
+    pub refunded_badges: Vec<ConsumedBadgeDto>,
+    pub cancelled_at: DateTime<Utc>,
+}
+
 // ==================== 退款处理 DTO ====================
 
 /// 退款事件
@@ -10,6 +10,7 @@
 //! - 幂等处理
 //! - 级联触发（发放后自动评估依赖此徽章的其他徽章）
 //! - 自动权益评估（发放后触发关联的权益自动发放）
+//! - 排行榜更新（发放后增量更新用户排行榜）
 //!
 //! ## 发放流程
 //!
@@ -27,6 +28,7 @@ use tokio::sync::RwLock;
 use tracing::{info, instrument, warn};
 
 use badge_shared::cache::Cache;
+use badge_shared::leaderboard::{BadgeChange, LeaderboardStore};
 
 use crate::auto_benefit::{AutoBenefitContext, AutoBenefitEvaluator};
 use crate::cascade::{BadgeGranter, CascadeEvaluator};
@@ -34,7 +36,7 @@ use crate::error::{BadgeError, Result};
 use crate::notification::NotificationSender;
 use crate::models::{
     BadgeLedger, BadgeStatus, ChangeType, LogAction, RecipientType, SourceType, UserBadge,
-    UserBadgeStatus, ValidityConfig, ValidityType,
+    UserBadgeStatus, ValidityConfig, ValidityType, level_for_quantity,
 };
 use crate::repository::{BadgeLedgerRepository, BadgeRepositoryTrait, UserBadgeRepository};
 use crate::service::dto::{BatchGrantResponse, GrantBadgeRequest, GrantBadgeResponse, GrantResult};
@@ -48,6 +50,14 @@ mod cache_keys {
     pub fn badge_wall(user_id: &str) -> String {
         format!("user:badge:wall:{}", user_id)
     }
+
+    pub fn badge_progress(user_id: &str) -> String {
+        format!("user:badge:progress:{}", user_id)
+    }
+
+    pub fn redeemable_benefits(user_id: &str) -> String {
+        format!("user:redeemable:{}", user_id)
+    }
 }
 
 /// 前置条件行（用于查询）
@@ -65,7 +75,8 @@ struct PrerequisiteRow {
 /// ## 级联触发
 ///
 /// 当徽章发放成功且来源类型不是 `SourceType::Cascade` 时，会自动触发级联评估。
-/// 级联评估器会检查是否有其他徽章依赖此徽章，并在条件满足时自动发放。
+/// 级联评估器会检查是否有其他徽章依赖此徽章，并在条件满足时自动发放，
+/// 同时恢复因前置徽章失效而暂停、现已重新满足条件的徽章。
 /// 级联评估失败不影响主发放流程，仅记录警告日志。
 ///
 /// ## 自动权益评估
@@ -85,6 +96,8 @@ where
     notification_sender: RwLock<Option<Arc<NotificationSender>>>,
     /// 自动权益评估器（延迟注入，解决循环依赖）
     auto_benefit_evaluator: RwLock<Option<Arc<AutoBenefitEvaluator>>>,
+    /// 排行榜存储（可选，未设置时不维护排行榜）
+    leaderboard: RwLock<Option<Arc<LeaderboardStore>>>,
 }
 
 impl<BR> GrantService<BR>
@@ -99,6 +112,7 @@ where
             cascade_evaluator: RwLock::new(None),
             notification_sender: RwLock::new(None),
             auto_benefit_evaluator: RwLock::new(None),
+            leaderboard: RwLock::new(None),
         }
     }
 
@@ -131,6 +145,13 @@ where
         info!("GrantService 自动权益评估器已设置");
     }
 
+    /// 设置排行榜存储
+    pub async fn set_leaderboard(&self, leaderboard: Arc<LeaderboardStore>) {
+        let mut guard = self.leaderboard.write().await;
+        *guard = Some(leaderboard);
+        info!("GrantService 排行榜存储已设置");
+    }
+
     /// 发放徽章给用户（公开接口）
     ///
     /// 完整的发放流程：
@@ -242,6 +263,15 @@ where
         // 6. 清除缓存
         self.invalidate_user_cache(&request.user_id).await;
 
+        // 7. 更新排行榜
+        self.update_leaderboard(BadgeChange::granted(
+            &request.user_id,
+            request.badge_id,
+            request.quantity,
+            new_quantity,
+        ))
+        .await;
+
         info!(
             user_id = %request.user_id,
             badge_id = %request.badge_id,
@@ -264,15 +294,33 @@ where
             guard.clone()
         };
 
-        if let Some(evaluator) = evaluator
-            && let Err(e) = evaluator.evaluate(user_id, badge_id).await
-        {
-            warn!(
-                user_id = %user_id,
-                badge_id = badge_id,
-                error = %e,
-                "级联评估失败，但不影响主发放流程"
-            );
+        let Some(evaluator) = evaluator else {
+            return;
+        };
+
+        match evaluator.evaluate(user_id, badge_id).await {
+            Ok(result) if !result.restored_badges.is_empty() => {
+                // 恢复的暂停徽章不经过发放流程，需要在此刷新缓存并重新计入排行榜
+                self.invalidate_user_cache(user_id).await;
+                for restored in &result.restored_badges {
+                    self.update_leaderboard(BadgeChange::granted(
+                        user_id,
+                        restored.badge_id,
+                        restored.quantity,
+                        restored.quantity,
+                    ))
+                    .await;
+                }
+            }
+            Ok(_) => {}
+            Err(e) => {
+                warn!(
+                    user_id = %user_id,
+                    badge_id = badge_id,
+                    error = %e,
+                    "级联评估失败，但不影响主发放流程"
+                );
+            }
         }
     }
 
@@ -361,7 +409,10 @@ where
                 Err(e) => {
                     failed_count += 1;
                     warn!(user_id = %user_id, badge_id = %badge_id, error = %e, "批量发放单条失败");
-                    results.push(GrantResult::failure(user_id, badge_id, e.to_string()));
+                    results.push(
+                        GrantResult::failure(user_id, badge_id, e.to_string())
+                            .with_error_code(e.error_code()),
+                    );
                 }
             }
         }
@@ -695,6 +746,7 @@ where
                 expired_at: None,
                 recipient_type: RecipientType::Owner,
                 actual_user_id: None,
+                level: None,
                 created_at: now,
                 updated_at: now,
             };
@@ -703,6 +755,28 @@ where
             (id, request.quantity)
         };
 
+        // 5.2.1 分级徽章按新的持有数量自动升级，等级只升不降
+        let levels = badge.parse_levels().unwrap_or_else(|e| {
+            warn!(badge_id = badge.id, error = %e, "徽章等级配置解析失败，按非分级徽章处理");
+            vec![]
+        });
+        if let Some(level) = level_for_quantity(&levels, new_quantity)
+            && UserBadgeRepository::upgrade_user_badge_level_in_tx(
+                &mut tx,
+                user_badge_id,
+                level.level,
+            )
+            .await?
+        {
+            info!(
+                user_id = %request.user_id,
+                badge_id = request.badge_id,
+                level = level.level,
+                level_name = %level.name,
+                "分级徽章等级提升"
+            );
+        }
+
         // 5.3 写入账本流水
         let ledger = BadgeLedger {
             id: 0,
@@ -769,6 +843,8 @@ where
         let keys = [
             cache_keys::user_badges(user_id),
             cache_keys::badge_wall(user_id),
+            cache_keys::badge_progress(user_id),
+            cache_keys::redeemable_benefits(user_id),
         ];
 
         for key in keys {
@@ -777,6 +853,21 @@ where
             }
         }
     }
+
+    /// 增量更新排行榜，失败不影响发放结果
+    async fn update_leaderboard(&self, change: BadgeChange) {
+        let leaderboard = self.leaderboard.read().await.clone();
+        if let Some(leaderboard) = leaderboard
+            && let Err(e) = leaderboard.record(&change).await
+        {
+            warn!(
+                user_id = %change.user_id,
+                badge_id = change.badge_id,
+                error = %e,
+                "排行榜更新失败"
+            );
+        }
+    }
 }
 
 /// 计算徽章过期时间
@@ -855,6 +946,7 @@ mod tests {
             validity_config: json!({"validityType": "PERMANENT"}),
             max_supply: Some(1000),
             issued_count: 100,
+            levels: serde_json::Value::Null,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         }
@@ -10,13 +10,17 @@
 //! - `revoke_service`: 徽章取消服务（写入操作）
 //! - `redemption_service`: 徽章兑换服务（写入操作）
 //! - `competitive_redemption`: 竞争兑换服务（需要消耗徽章的兑换）
+//! - `showcase_service`: 徽章墙展示服务（置顶与展示顺序）
+//! - `progress_service`: 徽章进度服务（未获得徽章的完成度与升级进度）
 
 pub mod competitive_redemption;
 pub mod dto;
 pub mod grant_service;
+pub mod progress_service;
 pub mod query_service;
 pub mod redemption_service;
 pub mod revoke_service;
+pub mod showcase_service;
 
 pub use competitive_redemption::{
     CompetitiveRedeemRequest, CompetitiveRedeemResponse, CompetitiveRedemptionService,
@@ -24,6 +28,8 @@ pub use competitive_redemption::{
 };
 pub use dto::*;
 pub use grant_service::GrantService;
+pub use progress_service::{ProgressService, RuleProgressProvider};
 pub use query_service::BadgeQueryService;
 pub use redemption_service::RedemptionService;
 pub use revoke_service::RevokeService;
+pub use showcase_service::ShowcaseService;
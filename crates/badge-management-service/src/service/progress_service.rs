@@ -0,0 +1,433 @@
+//! 徽章进度服务
+//!
+//! 计算用户距离获得（或升级）徽章还差多少：
+//!
+//! - 规则中的聚合条件（如"30 天内签到 5 次"）由规则引擎读取当前统计值
+//! - 级联前置条件对比用户持有的前置徽章数量
+//! - 已获得的分级徽章对比下一等级的门槛
+//!
+//! ## 缓存策略
+//!
+//! 按用户缓存各徽章的进度，TTL 1 分钟。发放、取消、兑换成功以及事件服务
+//! 评估新事件后都会主动使缓存失效。
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use tonic::transport::Channel;
+use tracing::{instrument, warn};
+
+use badge_proto::rule_engine::rule_engine_service_client::RuleEngineServiceClient;
+use badge_proto::rule_engine::{AggregateProgress, GetRuleProgressRequest, RuleProgress};
+use badge_shared::cache::Cache;
+use badge_shared::observability::middleware::grpc::traced_request;
+
+use crate::cascade::{BadgeDependency, CascadeEvaluator};
+use crate::error::{BadgeError, Result};
+use crate::models::Badge;
+use crate::repository::{BadgeRepositoryTrait, UserBadgeRepositoryTrait};
+use crate::service::dto::{BadgeProgressDto, ProgressItemDto, ProgressKind};
+
+/// 进度缓存 TTL（秒）
+const PROGRESS_TTL: u64 = 60;
+
+mod cache_keys {
+    pub fn badge_progress(user_id: &str) -> String {
+        format!("user:badge:progress:{}", user_id)
+    }
+}
+
+/// 规则聚合进度来源
+///
+/// 聚合状态保存在规则引擎侧，生产环境通过 gRPC 查询，测试中可替换为桩实现。
+#[async_trait]
+pub trait RuleProgressProvider: Send + Sync {
+    /// 查询用户在指定规则上的聚合条件进度
+    async fn rule_progress(&self, user_id: &str, rule_ids: &[String]) -> Result<Vec<RuleProgress>>;
+}
+
+#[async_trait]
+impl RuleProgressProvider for RuleEngineServiceClient<Channel> {
+    async fn rule_progress(&self, user_id: &str, rule_ids: &[String]) -> Result<Vec<RuleProgress>> {
+        // Channel 内部带连接池，clone 是廉价操作
+        let mut client = self.clone();
+        let response = client
+            .get_rule_progress(traced_request(GetRuleProgressRequest {
+                user_id: user_id.to_string(),
+                rule_ids: rule_ids.to_vec(),
+            }))
+            .await
+            .map_err(|e| BadgeError::Internal(format!("查询规则进度失败: {}", e)))?;
+
+        Ok(response.into_inner().rules)
+    }
+}
+
+/// 徽章进度服务
+pub struct ProgressService<BR, UBR>
+where
+    BR: BadgeRepositoryTrait,
+    UBR: UserBadgeRepositoryTrait,
+{
+    badge_repo: Arc<BR>,
+    user_badge_repo: Arc<UBR>,
+    cache: Arc<Cache>,
+    /// 级联评估器（提供前置依赖），未配置时不展示前置徽章进度
+    cascade_evaluator: Option<Arc<CascadeEvaluator>>,
+    /// 规则聚合进度来源，未配置时不展示规则进度
+    rule_progress: Option<Arc<dyn RuleProgressProvider>>,
+}
+
+impl<BR, UBR> ProgressService<BR, UBR>
+where
+    BR: BadgeRepositoryTrait,
+    UBR: UserBadgeRepositoryTrait,
+{
+    pub fn new(badge_repo: Arc<BR>, user_badge_repo: Arc<UBR>, cache: Arc<Cache>) -> Self {
+        Self {
+            badge_repo,
+            user_badge_repo,
+            cache,
+            cascade_evaluator: None,
+            rule_progress: None,
+        }
+    }
+
+    /// 设置级联评估器
+    pub fn with_cascade_evaluator(mut self, evaluator: Arc<CascadeEvaluator>) -> Self {
+        self.cascade_evaluator = Some(evaluator);
+        self
+    }
+
+    /// 设置规则聚合进度来源
+    pub fn with_rule_progress(mut self, provider: Arc<dyn RuleProgressProvider>) -> Self {
+        self.rule_progress = Some(provider);
+        self
+    }
+
+    /// 查询用户的徽章进度
+    ///
+    /// `badge_ids` 为空时遍历所有上架徽章，只返回有进度项可展示的徽章。
+    /// 不存在的徽章 ID 会被忽略。
+    #[instrument(skip(self), fields(user_id = %user_id))]
+    pub async fn get_badge_progress(
+        &self,
+        user_id: &str,
+        badge_ids: &[i64],
+    ) -> Result<Vec<BadgeProgressDto>> {
+        let cache_key = cache_keys::badge_progress(user_id);
+        let mut cached: HashMap<i64, BadgeProgressDto> = match self.cache.get(&cache_key).await {
+            Ok(Some(cached)) => cached,
+            Ok(None) => HashMap::new(),
+            Err(e) => {
+                warn!(key = %cache_key, error = %e, "Cache get failed, falling back to database");
+                HashMap::new()
+            }
+        };
+
+        let (targets, missing) = if badge_ids.is_empty() {
+            let badges = self.badge_repo.list_active_badges().await?;
+            let targets: Vec<i64> = badges.iter().map(|b| b.id).collect();
+            let missing: Vec<Badge> = badges
+                .into_iter()
+                .filter(|b| !cached.contains_key(&b.id))
+                .collect();
+            (targets, missing)
+        } else {
+            let missing_ids: Vec<i64> = badge_ids
+                .iter()
+                .filter(|id| !cached.contains_key(id))
+                .copied()
+                .collect();
+            let missing = if missing_ids.is_empty() {
+                vec![]
+            } else {
+                self.badge_repo.get_badges_by_ids(&missing_ids).await?
+            };
+            (badge_ids.to_vec(), missing)
+        };
+
+        if !missing.is_empty() {
+            for progress in self.compute(user_id, &missing).await? {
+                cached.insert(progress.badge_id, progress);
+            }
+            if let Err(e) = self
+                .cache
+                .set(&cache_key, &cached, Duration::from_secs(PROGRESS_TTL))
+                .await
+            {
+                warn!(key = %cache_key, error = %e, "Cache set failed");
+            }
+        }
+
+        let mut result: Vec<BadgeProgressDto> = targets
+            .iter()
+            .filter_map(|id| cached.get(id).cloned())
+            .collect();
+        if badge_ids.is_empty() {
+            result.retain(|p| !p.items.is_empty());
+        }
+
+        Ok(result)
+    }
+
+    /// 计算一批徽章的进度
+    async fn compute(&self, user_id: &str, badges: &[Badge]) -> Result<Vec<BadgeProgressDto>> {
+        let now = Utc::now();
+        let quantities: HashMap<i64, i32> = self
+            .user_badge_repo
+            .list_user_badges(user_id)
+            .await?
+            .into_iter()
+            .filter(|ub| ub.is_valid(now) && ub.quantity > 0)
+            .map(|ub| (ub.badge_id, ub.quantity))
+            .collect();
+
+        let mut badge_names: HashMap<i64, String> =
+            badges.iter().map(|b| (b.id, b.name.clone())).collect();
+        let mut dependencies: HashMap<i64, Vec<BadgeDependency>> = HashMap::new();
+        let mut rule_owner: HashMap<String, i64> = HashMap::new();
+
+        // 已获得的徽章只展示升级进度，不再查询获得条件
+        for badge in badges.iter().filter(|b| !quantities.contains_key(&b.id)) {
+            if let Some(ref evaluator) = self.cascade_evaluator {
+                let deps = evaluator.required_dependencies(badge.id).await?;
+                if !deps.is_empty() {
+                    dependencies.insert(badge.id, deps);
+                }
+            }
+            if self.rule_progress.is_some() {
+                for rule in self.badge_repo.get_badge_rules(badge.id).await? {
+                    rule_owner.insert(rule.id.to_string(), badge.id);
+                }
+            }
+        }
+
+        // 前置徽章可能不在本次查询范围内，补查名称用于展示
+        let unnamed: Vec<i64> = dependencies
+            .values()
+            .flatten()
+            .map(|d| d.depends_on_badge_id)
+            .filter(|id| !badge_names.contains_key(id))
+            .collect();
+        if !unnamed.is_empty() {
+            for badge in self.badge_repo.get_badges_by_ids(&unnamed).await? {
+                badge_names.insert(badge.id, badge.name);
+            }
+        }
+
+        let mut aggregates: HashMap<i64, Vec<AggregateProgress>> = HashMap::new();
+        if let Some(ref provider) = self.rule_progress
+            && !rule_owner.is_empty()
+        {
+            let rule_ids: Vec<String> = rule_owner.keys().cloned().collect();
+            // 规则引擎不可用时降级为只展示本地可计算的进度
+            match provider.rule_progress(user_id, &rule_ids).await {
+                Ok(rules) => {
+                    for rule in rules {
+                        if let Some(badge_id) = rule_owner.get(&rule.rule_id) {
+                            aggregates
+                                .entry(*badge_id)
+                                .or_default()
+                                .extend(rule.aggregates);
+                        }
+                    }
+                }
+                Err(e) => warn!(user_id = %user_id, error = %e, "规则进度查询失败，跳过规则进度"),
+            }
+        }
+
+        Ok(badges
+            .iter()
+            .map(|badge| {
+                build_progress(
+                    badge,
+                    &quantities,
+                    dependencies
+                        .get(&badge.id)
+                        .map(Vec::as_slice)
+                        .unwrap_or(&[]),
+                    &badge_names,
+                    aggregates.get(&badge.id).map(Vec::as_slice).unwrap_or(&[]),
+                )
+            })
+            .collect())
+    }
+}
+
+/// 汇总单个徽章的进度项
+fn build_progress(
+    badge: &Badge,
+    quantities: &HashMap<i64, i32>,
+    dependencies: &[BadgeDependency],
+    badge_names: &HashMap<i64, String>,
+    aggregates: &[AggregateProgress],
+) -> BadgeProgressDto {
+    let quantity = quantities.get(&badge.id).copied().unwrap_or(0);
+    let mut items = Vec::new();
+
+    if quantity == 0 {
+        items.extend(aggregates.iter().map(|a| ProgressItemDto {
+            kind: ProgressKind::RuleAggregate,
+            label: a.signature.clone(),
+            current: a.current,
+            target: a.target,
+            percentage: a.percentage,
+            achieved: a.achieved,
+            related_badge_id: None,
+            level: None,
+        }));
+
+        items.extend(dependencies.iter().map(|d| {
+            let label = badge_names
+                .get(&d.depends_on_badge_id)
+                .cloned()
+                .unwrap_or_else(|| format!("徽章 {}", d.depends_on_badge_id));
+            let owned = quantities.get(&d.depends_on_badge_id).copied().unwrap_or(0);
+            ProgressItemDto {
+                related_badge_id: Some(d.depends_on_badge_id),
+                ..ProgressItemDto::quantity(
+                    ProgressKind::Prerequisite,
+                    label,
+                    owned as f64,
+                    d.required_quantity as f64,
+                )
+            }
+        }));
+    } else {
+        // 等级定义在保存时已校验门槛严格递增
+        let levels = badge.parse_levels().unwrap_or_default();
+        if let Some(next) = levels.iter().find(|l| l.threshold > quantity) {
+            items.push(ProgressItemDto {
+                level: Some(next.level),
+                ..ProgressItemDto::quantity(
+                    ProgressKind::NextLevel,
+                    next.name.clone(),
+                    quantity as f64,
+                    next.threshold as f64,
+                )
+            });
+        }
+    }
+
+    BadgeProgressDto::new(badge.id, badge.name.clone(), quantity, items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cascade::{DependencyType, RevokePolicy};
+    use crate::models::{BadgeStatus, BadgeType};
+    use serde_json::json;
+
+    fn create_test_badge(id: i64, levels: serde_json::Value) -> Badge {
+        Badge {
+            id,
+            series_id: 1,
+            code: None,
+            badge_type: BadgeType::Normal,
+            name: format!("Badge {}", id),
+            description: None,
+            obtain_description: None,
+            sort_order: 0,
+            status: BadgeStatus::Active,
+            assets: json!({"iconUrl": "https://example.com/icon.png"}),
+            validity_config: json!({"validityType": "PERMANENT"}),
+            max_supply: None,
+            issued_count: 0,
+            levels,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn create_dependency(badge_id: i64, depends_on: i64, required: i32) -> BadgeDependency {
+        BadgeDependency {
+            id: 1,
+            badge_id,
+            depends_on_badge_id: depends_on,
+            dependency_type: DependencyType::Prerequisite,
+            required_quantity: required,
+            exclusive_group_id: None,
+            auto_trigger: true,
+            priority: 0,
+            dependency_group_id: "default".to_string(),
+            revoke_policy: RevokePolicy::Keep,
+        }
+    }
+
+    #[test]
+    fn test_unearned_badge_combines_rule_and_prerequisite_progress() {
+        let badge = create_test_badge(10, serde_json::Value::Null);
+        let quantities = HashMap::from([(1, 1)]);
+        let names = HashMap::from([(1, "新手".to_string())]);
+        let aggregates = vec![AggregateProgress {
+            signature: "count(checkin.*)@sliding:30day".to_string(),
+            current: 3.0,
+            target: 5.0,
+            percentage: 60.0,
+            achieved: false,
+            ..Default::default()
+        }];
+
+        let progress = build_progress(
+            &badge,
+            &quantities,
+            &[create_dependency(10, 1, 2)],
+            &names,
+            &aggregates,
+        );
+
+        assert!(!progress.earned);
+        assert_eq!(progress.items.len(), 2);
+        assert_eq!(progress.items[0].kind, ProgressKind::RuleAggregate);
+        assert_eq!(progress.items[1].kind, ProgressKind::Prerequisite);
+        assert_eq!(progress.items[1].label, "新手");
+        assert_eq!(progress.items[1].related_badge_id, Some(1));
+        assert_eq!(progress.items[1].percentage, 50.0);
+        assert_eq!(progress.percentage, 55.0);
+    }
+
+    #[test]
+    fn test_earned_tiered_badge_reports_next_level() {
+        let badge = create_test_badge(
+            10,
+            json!([
+                {"level": 1, "name": "铜", "threshold": 1},
+                {"level": 2, "name": "银", "threshold": 5},
+                {"level": 3, "name": "金", "threshold": 10}
+            ]),
+        );
+        let quantities = HashMap::from([(10, 6)]);
+
+        let progress = build_progress(&badge, &quantities, &[], &HashMap::new(), &[]);
+
+        assert!(progress.earned);
+        assert_eq!(progress.items.len(), 1);
+        let item = &progress.items[0];
+        assert_eq!(item.kind, ProgressKind::NextLevel);
+        assert_eq!(item.level, Some(3));
+        assert_eq!(item.label, "金");
+        assert_eq!(item.percentage, 60.0);
+    }
+
+    #[test]
+    fn test_earned_badge_at_top_level_has_no_items() {
+        let badge = create_test_badge(10, json!([{"level": 1, "name": "铜", "threshold": 1}]));
+        let quantities = HashMap::from([(10, 3)]);
+
+        let progress = build_progress(
+            &badge,
+            &quantities,
+            &[create_dependency(10, 1, 1)],
+            &HashMap::new(),
+            &[],
+        );
+
+        assert!(progress.items.is_empty());
+        assert_eq!(progress.percentage, 100.0);
+    }
+}
@@ -159,6 +159,16 @@ where
         let badges = self.badge_repo.get_badges_by_ids(&badge_ids).await?;
         let badge_map: HashMap<i64, Badge> = badges.into_iter().map(|b| (b.id, b)).collect();
 
+        // 置顶顺序按有效置顶记录重新编号，中间有徽章失效时展示顺序仍然连续
+        let pin_orders: HashMap<i64, i32> = self
+            .user_badge_repo
+            .list_pinned(user_id)
+            .await?
+            .into_iter()
+            .enumerate()
+            .map(|(i, (user_badge_id, _))| (user_badge_id, i as i32 + 1))
+            .collect();
+
         let mut result = Vec::with_capacity(user_badges.len());
         for ub in user_badges {
             if let Some(badge) = badge_map.get(&ub.badge_id) {
@@ -169,10 +179,20 @@ where
                         image_url: None,
                         animation_url: None,
                         disabled_icon_url: None,
+                        levels: vec![],
                     }
                 });
+                let level_name = ub.level.and_then(|level| {
+                    badge
+                        .parse_levels()
+                        .ok()?
+                        .into_iter()
+                        .find(|l| l.level == level)
+                        .map(|l| l.name)
+                });
 
                 result.push(UserBadgeDto {
+                    user_badge_id: ub.id,
                     badge_id: ub.badge_id,
                     badge_name: badge.name.clone(),
                     badge_type: badge.badge_type,
@@ -180,7 +200,10 @@ where
                     status: ub.status,
                     acquired_at: ub.acquired_at,
                     expires_at: ub.expires_at,
-                    assets,
+                    assets: assets.for_level(ub.level),
+                    pin_order: pin_orders.get(&ub.id).copied(),
+                    level: ub.level,
+                    level_name,
                 });
             }
         }
@@ -237,14 +260,9 @@ where
             if let Ok(Some(benefit)) = self.redemption_repo.get_benefit(rule.benefit_id).await {
                 // 解析该规则需要当前徽章的数量
                 let required_qty = rule
-                    .parse_required_badges()
+                    .parse_recipe()
                     .ok()
-                    .and_then(|badges| {
-                        badges
-                            .iter()
-                            .find(|b| b.badge_id == badge_id)
-                            .map(|b| b.quantity)
-                    })
+                    .and_then(|recipe| recipe.required_quantity_of(badge_id))
                     .unwrap_or(1);
 
                 redeemable_benefits.push(BenefitSummaryDto {
@@ -306,6 +324,7 @@ where
         if user_badges.is_empty() {
             return Ok(BadgeWallDto {
                 total_count: 0,
+                pinned: vec![],
                 categories: vec![],
             });
         }
@@ -359,10 +378,11 @@ where
             }
         }
 
-        // 构建响应
+        // 构建响应，分类内置顶徽章排在前面
         let categories: Vec<BadgeWallCategoryDto> = category_badges
             .into_iter()
-            .filter_map(|(cat_id, badges)| {
+            .filter_map(|(cat_id, mut badges)| {
+                sort_pinned_first(&mut badges);
                 category_names
                     .get(&cat_id)
                     .map(|name| BadgeWallCategoryDto {
@@ -373,10 +393,20 @@ where
             })
             .collect();
 
-        info!(user_id = %user_id, category_count = categories.len(), "Built badge wall from database");
+        let mut pinned: Vec<UserBadgeDto> =
+            user_badges.iter().filter(|b| b.is_pinned()).cloned().collect();
+        sort_pinned_first(&mut pinned);
+
+        info!(
+            user_id = %user_id,
+            category_count = categories.len(),
+            pinned_count = pinned.len(),
+            "Built badge wall from database"
+        );
 
         Ok(BadgeWallDto {
             total_count: user_badges.len() as i32,
+            pinned,
             categories,
         })
     }
@@ -516,7 +546,7 @@ where
                 }
                 UserBadgeStatus::Expired => expired_count += ub.quantity,
                 UserBadgeStatus::Redeemed => redeemed_count += ub.quantity,
-                UserBadgeStatus::Revoked => {}
+                UserBadgeStatus::Revoked | UserBadgeStatus::Suspended => {}
             }
         }
 
@@ -627,6 +657,11 @@ where
     }
 }
 
+/// 置顶徽章按展示顺序排在前面，其余徽章保持原有顺序
+pub(crate) fn sort_pinned_first(badges: &mut [UserBadgeDto]) {
+    badges.sort_by_key(|b| b.pin_order.unwrap_or(i32::MAX));
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -652,6 +687,7 @@ mod tests {
             validity_config: json!({"validityType": "PERMANENT"}),
             max_supply: Some(1000),
             issued_count: 100,
+            levels: serde_json::Value::Null,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         }
@@ -672,6 +708,7 @@ mod tests {
             expired_at: None,
             recipient_type: crate::models::RecipientType::Owner,
             actual_user_id: None,
+            level: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         }
@@ -772,4 +809,35 @@ mod tests {
         assert_eq!(category.id, 1);
         assert_eq!(category.name, "Category 1");
     }
+
+    #[test]
+    fn test_sort_pinned_first() {
+        let dto = |badge_id: i64, pin_order: Option<i32>| UserBadgeDto {
+            user_badge_id: badge_id * 10,
+            badge_id,
+            badge_name: format!("Badge {}", badge_id),
+            badge_type: crate::models::BadgeType::Normal,
+            quantity: 1,
+            status: UserBadgeStatus::Active,
+            acquired_at: Utc::now(),
+            expires_at: None,
+            assets: BadgeAssets {
+                icon_url: String::new(),
+                image_url: None,
+                animation_url: None,
+                disabled_icon_url: None,
+                levels: vec![],
+            },
+            pin_order,
+            level: None,
+            level_name: None,
+        };
+
+        let mut badges = vec![dto(1, None), dto(2, Some(2)), dto(3, None), dto(4, Some(1))];
+        sort_pinned_first(&mut badges);
+
+        // 置顶徽章按顺序在前，未置顶徽章保持原有相对顺序
+        let ids: Vec<i64> = badges.iter().map(|b| b.badge_id).collect();
+        assert_eq!(ids, vec![4, 2, 1, 3]);
+    }
 }
@@ -4,34 +4,43 @@
 //! - 幂等处理（防止重复兑换）
 //! - 兑换规则有效性检查
 //! - 权益库存检查
-//! - 用户徽章余额检查
+//! - 兑换配方计算（指定徽章、备选、系列/分类通配）与用户徽章余额检查
+//! - 兑换频率限制（按配置时区的自然日/周/月及终身次数）
 //! - 事务性扣减与订单创建
+//! - 按用户查询可兑换权益目录
+//! - 兑换订单取消（撤销权益、退回徽章）
 //!
 //! ## 兑换流程
 //!
-//! 1. 幂等检查 -> 2. 规则有效性 -> 3. 权益库存 -> 4. 徽章余额
-//!    -> 5. 事务写入 -> 6. 缓存失效
+//! 1. 幂等检查 -> 2. 规则有效性 -> 3. 权益库存 -> 4. 解析兑换配方
+//!    -> 5. 事务写入（含频率限制检查、配方计算与扣减） -> 6. 缓存失效
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use chrono::Utc;
+use chrono_tz::Tz;
 use sqlx::PgPool;
 use tokio::sync::RwLock;
 use tracing::{error, info, instrument, warn};
 use uuid::Uuid;
 
 use badge_shared::cache::Cache;
+use badge_shared::leaderboard::{BadgeChange, LeaderboardStore};
 
-use crate::benefit::{BenefitService, GrantBenefitRequest};
+use crate::benefit::{BenefitService, GrantBenefitRequest, RevokeResult};
 use crate::error::{BadgeError, Result};
 use crate::notification::NotificationSender;
 use crate::models::{
-    BadgeLedger, BadgeRedemptionRule, Benefit, ChangeType, LogAction, OrderStatus, RecipientType,
-    RedemptionDetail, RedemptionOrder, RequiredBadge, SourceType, UserBadgeStatus,
+    BadgeLedger, BadgeRedemptionRule, Benefit, ChangeType, FrequencyConfig, GrantStatus, LogAction,
+    OrderStatus, RecipeError, RecipientType, RedemptionDetail, RedemptionOrder, RedemptionRecipe,
+    RevokeReason, SourceType, UserBadgeStatus,
 };
 use crate::repository::{BadgeLedgerRepository, RedemptionRepository, UserBadgeRepository};
 use crate::service::dto::{
-    ConsumedBadgeDto, RedeemBadgeRequest, RedeemBadgeResponse, RedemptionHistoryDto,
+    CancelInitiator, CancelRedemptionRequest, CancelRedemptionResponse, ConsumedBadgeDto,
+    RedeemBadgeRequest, RedeemBadgeResponse, RedeemableBenefitDto, RedeemableBenefitPage,
+    RedeemableStatus, RedemptionHistoryDto,
 };
 
 /// 缓存键生成
@@ -43,8 +52,33 @@ mod cache_keys {
     pub fn badge_wall(user_id: &str) -> String {
         format!("user:badge:wall:{}", user_id)
     }
+
+    pub fn badge_progress(user_id: &str) -> String {
+        format!("user:badge:progress:{}", user_id)
+    }
+
+    pub fn redeemable_benefits(user_id: &str) -> String {
+        format!("user:redeemable:{}", user_id)
+    }
 }
 
+/// 可兑换目录缓存 TTL（秒）
+const REDEEMABLE_TTL: u64 = 60;
+
+/// 可兑换目录默认每页条数
+const DEFAULT_REDEEMABLE_PAGE_SIZE: i64 = 20;
+
+/// 可兑换目录每页最大条数
+const MAX_REDEEMABLE_PAGE_SIZE: i64 = 100;
+
+/// 用户自助取消订单的默认时限（分钟）
+const DEFAULT_CANCEL_WINDOW_MINUTES: i64 = 30;
+
+/// 取消订单时权益撤销占用的租约（分钟）
+///
+/// 须长于外部撤销的最长耗时（含外部回调重试），租约到期后其他取消请求可以接管
+const BENEFIT_REVOKE_LEASE_MINUTES: i64 = 15;
+
 /// 徽章兑换服务
 ///
 /// 负责徽章兑换权益的完整流程，包括验证、事务处理和缓存管理
@@ -56,6 +90,12 @@ pub struct RedemptionService {
     benefit_service: Option<Arc<BenefitService>>,
     /// 通知发送器（延迟注入，支持运行时设置）
     notification_sender: RwLock<Option<Arc<NotificationSender>>>,
+    /// 排行榜存储（延迟注入），兑换扣减后增量更新
+    leaderboard: RwLock<Option<Arc<LeaderboardStore>>>,
+    /// 频率限制的自然日/周/月按此时区划分
+    timezone: Tz,
+    /// 用户自助取消订单的时限（自下单起算）
+    cancel_window: chrono::Duration,
 }
 
 impl RedemptionService {
@@ -70,6 +110,9 @@ impl RedemptionService {
             pool,
             benefit_service: None,
             notification_sender: RwLock::new(None),
+            leaderboard: RwLock::new(None),
+            timezone: chrono_tz::Asia::Shanghai,
+            cancel_window: chrono::Duration::minutes(DEFAULT_CANCEL_WINDOW_MINUTES),
         }
     }
 
@@ -88,9 +131,24 @@ impl RedemptionService {
             pool,
             benefit_service: Some(benefit_service),
             notification_sender: RwLock::new(None),
+            leaderboard: RwLock::new(None),
+            timezone: chrono_tz::Asia::Shanghai,
+            cancel_window: chrono::Duration::minutes(DEFAULT_CANCEL_WINDOW_MINUTES),
         }
     }
 
+    /// 设置频率限制使用的时区（默认 Asia/Shanghai）
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// 设置用户自助取消订单的时限（默认 30 分钟）
+    pub fn with_cancel_window(mut self, cancel_window: chrono::Duration) -> Self {
+        self.cancel_window = cancel_window;
+        self
+    }
+
     /// 设置 BenefitService（允许运行时注入）
     pub fn set_benefit_service(&mut self, benefit_service: Arc<BenefitService>) {
         self.benefit_service = Some(benefit_service);
@@ -103,20 +161,27 @@ impl RedemptionService {
         info!("RedemptionService 通知发送器已设置");
     }
 
+    /// 设置排行榜存储
+    pub async fn set_leaderboard(&self, leaderboard: Arc<LeaderboardStore>) {
+        let mut guard = self.leaderboard.write().await;
+        *guard = Some(leaderboard);
+        info!("RedemptionService 排行榜存储已设置");
+    }
+
     /// 兑换徽章换取权益
     ///
     /// 完整事务流程：
     /// 1. 校验幂等键（防止重复兑换）
     /// 2. 校验兑换规则有效性
     /// 3. 校验权益库存
-    /// 4. 校验用户徽章余额
-    /// 5. 创建兑换订单
-    /// 6. 创建兑换明细
+    /// 4. 解析兑换配方
+    /// 5. 校验兑换频率限制，创建兑换订单
+    /// 6. 按配方计算消耗的徽章，创建兑换明细
     /// 7. 扣减用户徽章
     /// 8. 写入账本流水
     /// 9. 更新权益已兑换数量
     /// 10. 提交事务
-    /// 11. 清除缓存
+    /// 11. 清除缓存，更新排行榜
     #[instrument(skip(self), fields(user_id = %request.user_id, rule_id = %request.rule_id))]
     pub async fn redeem_badge(&self, request: RedeemBadgeRequest) -> Result<RedeemBadgeResponse> {
         let start = std::time::Instant::now();
@@ -146,9 +211,17 @@ impl RedemptionService {
             }
         };
 
-        // 4. 解析所需徽章
-        let required_badges = match rule.parse_required_badges() {
-            Ok(b) => b,
+        // 4. 解析兑换配方（未配置配方的旧规则由 required_badges 构造）
+        let recipe = match rule.parse_recipe() {
+            Ok(r) => r,
+            Err(e) => {
+                badge_shared::observability::metrics::record_redemption(rule_id, "error", start.elapsed().as_secs_f64());
+                return Err(BadgeError::Serialization(e));
+            }
+        };
+
+        let frequency = match rule.parse_frequency_config() {
+            Ok(f) => f,
             Err(e) => {
                 badge_shared::observability::metrics::record_redemption(rule_id, "error", start.elapsed().as_secs_f64());
                 return Err(BadgeError::Serialization(e));
@@ -156,8 +229,8 @@ impl RedemptionService {
         };
 
         // 5-10. 事务内执行兑换
-        let (order_id, order_no) = match self
-            .execute_redemption(&request, &rule, &benefit, &required_badges)
+        let (order_id, order_no, changes) = match self
+            .execute_redemption(&request, &rule, &benefit, &recipe, &frequency)
             .await
         {
             Ok(r) => r,
@@ -169,8 +242,11 @@ impl RedemptionService {
 
         badge_shared::observability::metrics::record_redemption(rule_id, "success", start.elapsed().as_secs_f64());
 
-        // 11. 清除缓存
+        // 11. 清除缓存，按消耗的徽章更新排行榜
         self.invalidate_user_cache(&request.user_id).await;
+        for change in &changes {
+            self.update_leaderboard(change).await;
+        }
 
         // 12. 发放权益（如果配置了 BenefitService）
         if let Some(ref benefit_service) = self.benefit_service {
@@ -280,6 +356,331 @@ impl RedemptionService {
         Ok(result)
     }
 
+    /// 查询用户的可兑换权益目录
+    ///
+    /// 将当前有效的兑换规则逐条与用户持有徽章、兑换频率和权益库存比对，
+    /// 给出可兑换 / 缺少徽章 / 频率受限 / 库存不足的状态及原因，可兑换的排在前面。
+    /// 完整目录按用户缓存，兑换、发放、取消徽章时主动失效，
+    /// 库存与规则配置的变化依赖 TTL 过期。
+    #[instrument(skip(self), fields(user_id = %user_id))]
+    pub async fn list_redeemable_benefits(
+        &self,
+        user_id: &str,
+        eligible_only: bool,
+        page: i64,
+        page_size: i64,
+    ) -> Result<RedeemableBenefitPage> {
+        let page = page.max(1);
+        let page_size = if page_size <= 0 {
+            DEFAULT_REDEEMABLE_PAGE_SIZE
+        } else {
+            page_size.min(MAX_REDEEMABLE_PAGE_SIZE)
+        };
+
+        let cache_key = cache_keys::redeemable_benefits(user_id);
+        let cached: Option<Vec<RedeemableBenefitDto>> = match self.cache.get(&cache_key).await {
+            Ok(cached) => cached,
+            Err(e) => {
+                warn!(key = %cache_key, error = %e, "Cache get failed, falling back to database");
+                None
+            }
+        };
+        let catalog = match cached {
+            Some(catalog) => catalog,
+            None => {
+                let catalog = self.compute_redeemable_benefits(user_id).await?;
+                if let Err(e) = self
+                    .cache
+                    .set(&cache_key, &catalog, Duration::from_secs(REDEEMABLE_TTL))
+                    .await
+                {
+                    warn!(key = %cache_key, error = %e, "Cache set failed");
+                }
+                catalog
+            }
+        };
+
+        let filtered: Vec<RedeemableBenefitDto> = catalog
+            .into_iter()
+            .filter(|item| !eligible_only || item.is_eligible())
+            .collect();
+        let total = filtered.len() as i64;
+        let items = filtered
+            .into_iter()
+            .skip(((page - 1) * page_size) as usize)
+            .take(page_size as usize)
+            .collect();
+
+        Ok(RedeemableBenefitPage {
+            items,
+            total,
+            page,
+            page_size,
+        })
+    }
+
+    /// 取消兑换订单并退回消耗的徽章
+    ///
+    /// 仅成功的订单可以取消。用户只能取消本人订单，且须在下单后的可取消时限内；
+    /// 管理员取消不受时限限制。外部权益撤销无法与数据库事务一起回滚，因此分两阶段：
+    /// 1. 锁定订单并置为取消中，占用权益撤销后提交；已取消的订单直接返回（幂等），
+    ///    其他请求正在撤销同一订单的权益时返回锁冲突
+    /// 2. 不持有订单行锁撤销已发放的权益，权益库存由 BenefitService 归还；权益明确拒绝撤销时
+    ///    订单恢复为成功，结果未知（超时、外部服务不可用）时订单保持取消中并返回可重试错误
+    /// 3. 重新锁定订单，并发取消中先完成的一方已将订单置为已取消
+    /// 4. 锁定消耗的用户徽章，按未退回的明细逐笔退回数量，写入账本流水和退还日志
+    /// 5. 扣减权益已兑换数量，订单置为已取消
+    /// 6. 提交事务，清除缓存，更新排行榜
+    ///
+    /// 第 1 步之后除权益明确拒绝撤销外的任何失败都会使订单停留在取消中，重试时跳过时限检查继续执行，
+    /// 已撤销的权益不会重复撤销。只有成功订单计入兑换频率限制，取消后释放对应的兑换次数。
+    #[instrument(skip(self), fields(order_no = %request.order_no))]
+    pub async fn cancel_redemption(
+        &self,
+        request: CancelRedemptionRequest,
+    ) -> Result<CancelRedemptionResponse> {
+        let cancelled_by = request.initiator.cancelled_by();
+
+        // 1. 锁定订单，置为取消中并占用权益撤销
+        let mut tx = self.pool.begin().await?;
+        let order =
+            RedemptionRepository::get_order_by_no_for_update_in_tx(&mut tx, &request.order_no)
+                .await?
+                .ok_or_else(|| BadgeError::RedemptionOrderNotFound(request.order_no.clone()))?;
+
+        // 用户取消他人订单按不存在处理，不暴露订单号是否有效
+        if let CancelInitiator::User { ref user_id } = request.initiator
+            && *user_id != order.user_id
+        {
+            return Err(BadgeError::RedemptionOrderNotFound(request.order_no));
+        }
+
+        match order.status {
+            OrderStatus::Success => {
+                if matches!(request.initiator, CancelInitiator::User { .. })
+                    && !within_cancel_window(order.created_at, Utc::now(), self.cancel_window)
+                {
+                    return Err(BadgeError::CancelWindowExpired {
+                        order_no: order.order_no,
+                        window_minutes: self.cancel_window.num_minutes(),
+                    });
+                }
+                RedemptionRepository::mark_order_cancelling_in_tx(
+                    &mut tx,
+                    order.id,
+                    request.reason.as_deref(),
+                    &cancelled_by,
+                )
+                .await?;
+            }
+            OrderStatus::Cancelling => {
+                info!(order_id = order.id, "订单取消未完成，继续执行");
+            }
+            OrderStatus::Cancelled => {
+                info!(order_id = order.id, "订单已取消，忽略重复请求");
+                return Ok(already_cancelled_response(order));
+            }
+            status => {
+                return Err(BadgeError::InvalidOrderStatus {
+                    order_id: order.id,
+                    current_status: format!("{:?}", status),
+                });
+            }
+        }
+        let claimed = RedemptionRepository::claim_benefit_revoke_in_tx(
+            &mut tx,
+            order.id,
+            chrono::Duration::minutes(BENEFIT_REVOKE_LEASE_MINUTES),
+        )
+        .await?;
+        if !claimed {
+            return Err(BadgeError::LockConflict {
+                resource: format!("redemption_order:{}", order.order_no),
+            });
+        }
+        tx.commit().await?;
+
+        // 2. 撤销已发放的权益，外部调用期间不持有订单行锁和数据库连接
+        let benefit_revoked = match self.benefit_service {
+            Some(ref benefit_service) => {
+                match revoke_order_benefit(benefit_service, &order.order_no).await {
+                    Ok(revoked) => revoked,
+                    Err(e) => {
+                        if restores_order_on_revoke_failure(&e) {
+                            self.restore_cancelling_order(order.id).await;
+                        } else {
+                            self.release_benefit_revoke(order.id).await;
+                        }
+                        return Err(e);
+                    }
+                }
+            }
+            None => false,
+        };
+        self.release_benefit_revoke(order.id).await;
+
+        // 3. 重新锁定订单，并发取消中先完成的一方已将订单置为已取消
+        let mut tx = self.pool.begin().await?;
+        let order =
+            RedemptionRepository::get_order_by_no_for_update_in_tx(&mut tx, &request.order_no)
+                .await?
+                .ok_or_else(|| BadgeError::RedemptionOrderNotFound(request.order_no.clone()))?;
+        match order.status {
+            OrderStatus::Cancelling => {}
+            OrderStatus::Cancelled => return Ok(already_cancelled_response(order)),
+            status => {
+                return Err(BadgeError::InvalidOrderStatus {
+                    order_id: order.id,
+                    current_status: format!("{:?}", status),
+                });
+            }
+        }
+
+        // 4. 退回徽章
+        let reason = request
+            .reason
+            .clone()
+            .unwrap_or_else(|| "兑换订单取消".to_string());
+
+        let details =
+            RedemptionRepository::list_unreversed_details_in_tx(&mut tx, order.id).await?;
+        let mut user_badge_ids: Vec<i64> = details.iter().map(|d| d.user_badge_id).collect();
+        user_badge_ids.sort_unstable();
+        user_badge_ids.dedup();
+        let mut user_badges: std::collections::HashMap<i64, (i32, UserBadgeStatus)> =
+            UserBadgeRepository::list_user_badges_for_update_in_tx(&mut tx, &user_badge_ids)
+                .await?
+                .into_iter()
+                .map(|ub| (ub.id, (ub.quantity, ub.status)))
+                .collect();
+        let mut changes = Vec::with_capacity(details.len());
+
+        for detail in &details {
+            let (balance, status) = user_badges
+                .get_mut(&detail.user_badge_id)
+                .ok_or(BadgeError::UserBadgeRecordNotFound(detail.user_badge_id))?;
+            *balance += detail.quantity;
+
+            UserBadgeRepository::update_user_badge_quantity_in_tx(
+                &mut tx,
+                detail.user_badge_id,
+                detail.quantity,
+            )
+            .await?;
+
+            // 兑换扣减归零时置为 Redeemed，退回后恢复有效
+            if *status == UserBadgeStatus::Redeemed {
+                UserBadgeRepository::update_user_badge_status_in_tx(
+                    &mut tx,
+                    detail.user_badge_id,
+                    UserBadgeStatus::Active,
+                )
+                .await?;
+                *status = UserBadgeStatus::Active;
+            }
+
+            // 只有有效持有计入排行榜，已过期或已撤销的徽章退回数量不改变榜单
+            if *status == UserBadgeStatus::Active {
+                changes.push(BadgeChange::granted(
+                    &order.user_id,
+                    detail.badge_id,
+                    detail.quantity,
+                    *balance,
+                ));
+            }
+
+            // 写入账本流水（REDEEM_FAIL 冲正兑换扣减）
+            let ledger = BadgeLedger {
+                id: 0,
+                user_id: order.user_id.clone(),
+                badge_id: detail.badge_id,
+                user_badge_id: Some(detail.user_badge_id),
+                change_type: ChangeType::RedeemFail,
+                quantity: detail.quantity,
+                balance_after: *balance,
+                ref_id: Some(order.order_no.clone()),
+                ref_type: SourceType::Redemption,
+                remark: Some(format!("兑换订单取消退回: {}", reason)),
+                operator: Some(cancelled_by.clone()),
+                recipient_type: RecipientType::Owner,
+                actual_user_id: None,
+                created_at: Utc::now(),
+            };
+            BadgeLedgerRepository::create_in_tx(&mut tx, &ledger).await?;
+
+            // 写入用户徽章日志
+            sqlx::query(
+                r#"
+                INSERT INTO user_badge_logs
+                    (user_badge_id, user_id, badge_id, action, reason, operator, quantity, source_type, source_ref_id, created_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, NOW())
+                "#,
+            )
+            .bind(detail.user_badge_id)
+            .bind(&order.user_id)
+            .bind(detail.badge_id)
+            .bind(LogAction::Refund)
+            .bind(&reason)
+            .bind(&cancelled_by)
+            .bind(detail.quantity)
+            .bind(SourceType::Redemption)
+            .bind(&order.order_no)
+            .execute(&mut *tx)
+            .await?;
+
+            RedemptionRepository::mark_detail_reversed_in_tx(&mut tx, detail.id).await?;
+        }
+
+        // 5. 扣减权益已兑换数量，订单置为已取消
+        RedemptionRepository::increment_redeemed_count_in_tx(&mut tx, order.benefit_id, -1).await?;
+        let cancelled_at = RedemptionRepository::cancel_order_in_tx(
+            &mut tx,
+            order.id,
+            request.reason.as_deref(),
+            &cancelled_by,
+        )
+        .await?;
+
+        // 6. 提交事务，清除缓存，按退回的徽章更新排行榜
+        tx.commit().await?;
+        self.invalidate_user_cache(&order.user_id).await;
+        for change in &changes {
+            self.update_leaderboard(change).await;
+        }
+
+        let badge_ids: Vec<i64> = details.iter().map(|d| d.badge_id).collect();
+        let badge_names = self.get_badge_names(&badge_ids).await?;
+        let refunded_badges = details
+            .iter()
+            .map(|d| ConsumedBadgeDto {
+                badge_id: d.badge_id,
+                badge_name: badge_names
+                    .get(&d.badge_id)
+                    .cloned()
+                    .unwrap_or_else(|| "未知徽章".to_string()),
+                quantity: d.quantity,
+            })
+            .collect();
+
+        info!(
+            order_id = order.id,
+            user_id = %order.user_id,
+            cancelled_by = %cancelled_by,
+            benefit_revoked = benefit_revoked,
+            refunded = details.len(),
+            "兑换订单已取消"
+        );
+
+        Ok(CancelRedemptionResponse {
+            order_id: order.id,
+            order_no: order.order_no,
+            already_cancelled: false,
+            benefit_revoked,
+            refunded_badges,
+            cancelled_at,
+        })
+    }
+
     // ==================== 私有方法 ====================
 
     /// 通过 BenefitService 发放权益
@@ -404,26 +805,78 @@ impl RedemptionService {
         Ok(benefit)
     }
 
+    /// 检查兑换频率限制
+    ///
+    /// 先对「用户 + 规则」加事务级咨询锁，同一用户对同一规则的并发兑换在此串行化，
+    /// 锁随事务结束释放，因此计数与随后的下单之间不会被其他请求穿插。
+    /// 只统计成功订单，自然日/周/月的边界按配置时区计算。
+    async fn check_frequency_limits(
+        &self,
+        tx: &mut sqlx::PgConnection,
+        user_id: &str,
+        rule_id: i64,
+        frequency: &FrequencyConfig,
+        now: chrono::DateTime<Utc>,
+    ) -> Result<()> {
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+            .bind(format!("redemption_frequency:{}:{}", user_id, rule_id))
+            .execute(&mut *tx)
+            .await?;
+
+        for (period, max) in frequency.limits() {
+            let since = period.start(now, self.timezone);
+            let count =
+                RedemptionRepository::count_user_redemptions_in_tx(tx, user_id, rule_id, since)
+                    .await?;
+            if count >= max as i64 {
+                warn!(
+                    user_id = %user_id,
+                    rule_id = rule_id,
+                    limit_type = period.as_str(),
+                    count = count,
+                    max = max,
+                    "兑换次数已达频率上限"
+                );
+                return Err(BadgeError::RedemptionFrequencyLimitReached {
+                    rule_id,
+                    limit_type: period.as_str().to_string(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
     /// 执行兑换事务
     ///
     /// 在单个事务内完成：
+    /// - 检查兑换频率限制
     /// - 创建兑换订单（Pending 状态）
-    /// - 锁定并检查用户徽章余额
+    /// - 锁定配方涉及的用户徽章，计算消耗方案
     /// - 扣减徽章数量
-    /// - 创建兑换明细
+    /// - 创建兑换明细（记录满足的配方条目）
     /// - 写入账本流水
     /// - 更新权益已兑换数量
     /// - 更新订单状态为 Success
+    ///
+    /// 返回订单 ID、订单号以及每笔扣减对应的排行榜变化，由调用方在提交后更新排行榜
     async fn execute_redemption(
         &self,
         request: &RedeemBadgeRequest,
         rule: &BadgeRedemptionRule,
         benefit: &Benefit,
-        required_badges: &[RequiredBadge],
-    ) -> Result<(i64, String)> {
+        recipe: &RedemptionRecipe,
+        frequency: &FrequencyConfig,
+    ) -> Result<(i64, String, Vec<BadgeChange>)> {
         let mut tx = self.pool.begin().await?;
         let now = Utc::now();
 
+        // 5.0 频率限制检查
+        if frequency.is_limited() {
+            self.check_frequency_limits(&mut tx, &request.user_id, rule.id, frequency, now)
+                .await?;
+        }
+
         // 5.1 生成订单号
         let order_no = generate_order_no();
 
@@ -443,44 +896,44 @@ impl RedemptionService {
         };
         let order_id = RedemptionRepository::create_order_in_tx(&mut tx, &order).await?;
 
-        // 5.3 对每个所需徽章进行处理
-        for required in required_badges {
-            // 锁定用户徽章（FOR UPDATE）
-            let user_badge = UserBadgeRepository::get_user_badge_for_update(
-                &mut tx,
+        // 5.3 锁定配方涉及的用户徽章并计算消耗方案
+        let holdings = RedemptionRepository::list_recipe_holdings_for_update_in_tx(
+            &mut tx,
+            &request.user_id,
+            &recipe.badge_ids(),
+            &recipe.series_ids(),
+            &recipe.category_ids(),
+        )
+        .await?;
+        let planned = recipe
+            .plan(&holdings, &request.selected_badges)
+            .map_err(|e| recipe_error(&request.user_id, e))?;
+
+        // 同一持有记录可能被多个条目消耗，按剩余余额逐笔扣减
+        let mut balances: std::collections::HashMap<i64, i32> = holdings
+            .iter()
+            .map(|h| (h.user_badge_id, h.quantity))
+            .collect();
+        let mut changes = Vec::with_capacity(planned.len());
+
+        for consumption in &planned {
+            let balance = balances
+                .get_mut(&consumption.user_badge_id)
+                .expect("消耗方案只包含已锁定的持有记录");
+            *balance -= consumption.quantity;
+            let new_quantity = *balance;
+            changes.push(BadgeChange::removed(
                 &request.user_id,
-                required.badge_id,
-            )
-            .await?
-            .ok_or_else(|| BadgeError::UserBadgeNotFound {
-                user_id: request.user_id.clone(),
-                badge_id: required.badge_id,
-            })?;
-
-            // 检查徽章状态
-            if user_badge.status != UserBadgeStatus::Active {
-                return Err(BadgeError::Validation(format!(
-                    "徽章状态不可兑换: badge_id={}, status={:?}",
-                    required.badge_id, user_badge.status
-                )));
-            }
-
-            // 检查余额
-            if user_badge.quantity < required.quantity {
-                return Err(BadgeError::InsufficientBadges {
-                    required: required.quantity,
-                    available: user_badge.quantity,
-                });
-            }
-
-            // 计算新余额
-            let new_quantity = user_badge.quantity - required.quantity;
+                consumption.badge_id,
+                consumption.quantity,
+                new_quantity,
+            ));
 
             // 扣减数量
             UserBadgeRepository::update_user_badge_quantity_in_tx(
                 &mut tx,
-                user_badge.id,
-                -required.quantity,
+                consumption.user_badge_id,
+                -consumption.quantity,
             )
             .await?;
 
@@ -488,7 +941,7 @@ impl RedemptionService {
             if new_quantity == 0 {
                 UserBadgeRepository::update_user_badge_status_in_tx(
                     &mut tx,
-                    user_badge.id,
+                    consumption.user_badge_id,
                     UserBadgeStatus::Redeemed,
                 )
                 .await?;
@@ -498,9 +951,10 @@ impl RedemptionService {
             let detail = RedemptionDetail {
                 id: 0,
                 order_id,
-                user_badge_id: user_badge.id,
-                badge_id: required.badge_id,
-                quantity: required.quantity,
+                user_badge_id: consumption.user_badge_id,
+                badge_id: consumption.badge_id,
+                quantity: consumption.quantity,
+                recipe_term: Some(consumption.term_index as i32),
                 created_at: now,
             };
             RedemptionRepository::create_detail_in_tx(&mut tx, &detail).await?;
@@ -509,10 +963,10 @@ impl RedemptionService {
             let ledger = BadgeLedger {
                 id: 0,
                 user_id: request.user_id.clone(),
-                badge_id: required.badge_id,
-                user_badge_id: Some(user_badge.id),
+                badge_id: consumption.badge_id,
+                user_badge_id: Some(consumption.user_badge_id),
                 change_type: ChangeType::RedeemOut,
-                quantity: required.quantity,
+                quantity: consumption.quantity,
                 balance_after: new_quantity,
                 ref_id: Some(order_no.clone()),
                 ref_type: SourceType::Redemption,
@@ -532,13 +986,13 @@ impl RedemptionService {
                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, NOW())
                 "#,
             )
-            .bind(user_badge.id)
+            .bind(consumption.user_badge_id)
             .bind(&request.user_id)
-            .bind(required.badge_id)
+            .bind(consumption.badge_id)
             .bind(LogAction::Redeem)
             .bind(format!("兑换权益: {}", benefit.name))
             .bind::<Option<String>>(None)
-            .bind(required.quantity)
+            .bind(consumption.quantity)
             .bind(SourceType::Redemption)
             .bind(&order_no)
             .execute(&mut *tx)
@@ -560,7 +1014,130 @@ impl RedemptionService {
         // 6. 提交事务
         tx.commit().await?;
 
-        Ok((order_id, order_no))
+        Ok((order_id, order_no, changes))
+    }
+
+    /// 计算用户对所有有效兑换规则的可兑换状态
+    async fn compute_redeemable_benefits(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<RedeemableBenefitDto>> {
+        let now = Utc::now();
+        let rules: Vec<(BadgeRedemptionRule, RedemptionRecipe, FrequencyConfig)> = self
+            .redemption_repo
+            .list_active_rules()
+            .await?
+            .into_iter()
+            .filter(|rule| rule.is_active(now))
+            .filter_map(
+                |rule| match (rule.parse_recipe(), rule.parse_frequency_config()) {
+                    (Ok(recipe), Ok(frequency)) => Some((rule, recipe, frequency)),
+                    (Err(e), _) | (_, Err(e)) => {
+                        warn!(rule_id = rule.id, error = %e, "兑换规则配置解析失败，不展示该规则");
+                        None
+                    }
+                },
+            )
+            .collect();
+
+        if rules.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // 一次性加载所有规则涉及的持有徽章，逐条规则在内存中比对
+        let mut badge_ids = Vec::new();
+        let mut series_ids = Vec::new();
+        let mut category_ids = Vec::new();
+        for (_, recipe, _) in &rules {
+            badge_ids.extend(recipe.badge_ids());
+            series_ids.extend(recipe.series_ids());
+            category_ids.extend(recipe.category_ids());
+        }
+        for ids in [&mut badge_ids, &mut series_ids, &mut category_ids] {
+            ids.sort_unstable();
+            ids.dedup();
+        }
+        let holdings = self
+            .redemption_repo
+            .list_recipe_holdings(user_id, &badge_ids, &series_ids, &category_ids)
+            .await?;
+
+        let mut benefit_ids: Vec<i64> = rules.iter().map(|(rule, _, _)| rule.benefit_id).collect();
+        benefit_ids.sort_unstable();
+        benefit_ids.dedup();
+        let benefits = self.get_benefits_by_ids(&benefit_ids).await?;
+
+        let mut catalog = Vec::with_capacity(rules.len());
+        for (rule, recipe, frequency) in rules {
+            // 权益已删除的规则无法兑换，也不展示
+            let Some(benefit) = benefits.get(&rule.benefit_id) else {
+                continue;
+            };
+
+            let mut reasons = Vec::new();
+            let mut status = None;
+
+            if !benefit.is_redeemable() {
+                reasons.push(if benefit.has_stock() {
+                    "权益已停用".to_string()
+                } else {
+                    "权益库存不足".to_string()
+                });
+                status.get_or_insert(RedeemableStatus::OutOfStock);
+            }
+
+            let mut limited_by = None;
+            for (period, max) in frequency.limits() {
+                let count = self
+                    .redemption_repo
+                    .count_user_redemptions(user_id, rule.id, period.start(now, self.timezone))
+                    .await?;
+                if count >= max as i64 {
+                    reasons.push(format!(
+                        "已达兑换频率上限 {}: {}/{}",
+                        period.as_str(),
+                        count,
+                        max
+                    ));
+                    limited_by = Some(period.as_str().to_string());
+                    status.get_or_insert(RedeemableStatus::FrequencyLimited);
+                    break;
+                }
+            }
+
+            let missing = recipe.shortfalls(&holdings);
+            if !missing.is_empty() {
+                reasons.extend(missing.iter().map(|m| {
+                    RecipeError::NotSatisfied {
+                        term_index: m.term_index,
+                        required: m.required,
+                        available: m.available,
+                    }
+                    .to_string()
+                }));
+                status.get_or_insert(RedeemableStatus::MissingBadges);
+            }
+
+            catalog.push(RedeemableBenefitDto {
+                rule_id: rule.id,
+                rule_name: rule.name,
+                benefit_id: benefit.id,
+                benefit_name: benefit.name.clone(),
+                benefit_type: benefit.benefit_type,
+                icon_url: benefit.icon_url.clone(),
+                remaining_stock: benefit.get_remaining_stock(),
+                status: status.unwrap_or(RedeemableStatus::Eligible),
+                reasons,
+                missing,
+                limited_by,
+                recipe,
+                end_time: rule.end_time,
+            });
+        }
+
+        // 可兑换的排在前面，其余保持规则 ID 顺序
+        catalog.sort_by_key(|item| !item.is_eligible());
+        Ok(catalog)
     }
 
     /// 批量获取权益信息
@@ -623,11 +1200,35 @@ impl RedemptionService {
         Ok(result)
     }
 
+    /// 权益拒绝撤销时将取消中的订单恢复为成功
+    ///
+    /// 恢复失败时订单停留在取消中，占用在租约到期后失效，重试取消会再次撤销
+    async fn restore_cancelling_order(&self, order_id: i64) {
+        if let Err(e) = self
+            .redemption_repo
+            .restore_cancelling_order(order_id)
+            .await
+        {
+            error!(order_id, error = %e, "恢复取消中订单失败");
+        }
+    }
+
+    /// 释放订单的权益撤销占用
+    ///
+    /// 释放失败时占用在租约到期后自动失效，不影响本次取消的结果
+    async fn release_benefit_revoke(&self, order_id: i64) {
+        if let Err(e) = self.redemption_repo.release_benefit_revoke(order_id).await {
+            warn!(order_id, error = %e, "释放权益撤销占用失败");
+        }
+    }
+
     /// 使用户徽章相关缓存失效
     async fn invalidate_user_cache(&self, user_id: &str) {
         let keys = [
             cache_keys::user_badges(user_id),
             cache_keys::badge_wall(user_id),
+            cache_keys::badge_progress(user_id),
+            cache_keys::redeemable_benefits(user_id),
         ];
 
         for key in keys {
@@ -636,6 +1237,114 @@ impl RedemptionService {
             }
         }
     }
+
+    /// 增量更新排行榜，失败不影响兑换结果
+    async fn update_leaderboard(&self, change: &BadgeChange) {
+        let leaderboard = self.leaderboard.read().await.clone();
+        if let Some(leaderboard) = leaderboard
+            && let Err(e) = leaderboard.record(change).await
+        {
+            warn!(
+                user_id = %change.user_id,
+                badge_id = change.badge_id,
+                error = %e,
+                "排行榜更新失败"
+            );
+        }
+    }
+}
+
+/// 配方计算失败转换为业务错误
+///
+/// 未持有指定徽章沿用 UserBadgeNotFound，数量不足沿用 InsufficientBadges，
+/// 与配方上线前固定徽章列表的错误保持一致。
+fn recipe_error(user_id: &str, err: RecipeError) -> BadgeError {
+    match err {
+        RecipeError::BadgeNotHeld { badge_id } => BadgeError::UserBadgeNotFound {
+            user_id: user_id.to_string(),
+            badge_id,
+        },
+        RecipeError::NotSatisfied {
+            required,
+            available,
+            ..
+        } => BadgeError::InsufficientBadges {
+            required,
+            available,
+        },
+        RecipeError::SelectionMismatch(_) | RecipeError::InvalidRecipe(_) => {
+            BadgeError::Validation(err.to_string())
+        }
+    }
+}
+
+/// 撤销兑换订单发放的权益
+///
+/// 返回本次是否实际撤销。未发放或发放失败的无需撤销；已撤销的说明此前的取消
+/// 在撤销权益后中断，重试时直接继续退回徽章。处理中或不支持撤销的权益返回错误，订单不予取消；
+/// 瞬时故障导致撤销结果未知时返回可重试错误。
+async fn revoke_order_benefit(benefit_service: &BenefitService, order_no: &str) -> Result<bool> {
+    let grant_no = format!("RG-{}", order_no);
+    match benefit_service.find_grant_status(&grant_no).await? {
+        None | Some(GrantStatus::Failed) | Some(GrantStatus::Revoked) => Ok(false),
+        Some(_) => {
+            let result = benefit_service
+                .revoke_grant(&grant_no, RevokeReason::OrderRefund)
+                .await?;
+            if result.success {
+                Ok(true)
+            } else {
+                Err(revoke_failure_error(order_no, result))
+            }
+        }
+    }
+}
+
+/// 将失败的撤销结果转换为取消订单的错误
+///
+/// 瞬时故障时外部系统可能已经撤销，不能按拒绝处理
+fn revoke_failure_error(order_no: &str, result: RevokeResult) -> BadgeError {
+    let reason = result.message.unwrap_or_else(|| "撤销失败".to_string());
+    if result.retryable {
+        BadgeError::BenefitRevokePending {
+            order_no: order_no.to_string(),
+            reason,
+        }
+    } else {
+        BadgeError::BenefitRevokeFailed {
+            order_no: order_no.to_string(),
+            reason,
+        }
+    }
+}
+
+/// 已取消订单的幂等响应
+fn already_cancelled_response(order: RedemptionOrder) -> CancelRedemptionResponse {
+    CancelRedemptionResponse {
+        order_id: order.id,
+        order_no: order.order_no,
+        already_cancelled: true,
+        benefit_revoked: false,
+        refunded_badges: Vec::new(),
+        cancelled_at: order.updated_at,
+    }
+}
+
+/// 权益撤销失败时是否将订单恢复为成功
+///
+/// 权益明确拒绝撤销（处理中或不支持撤销）时订单不予取消；瞬时故障和其他错误无法确认外部
+/// 是否已撤销，订单保持取消中，待重试时继续
+fn restores_order_on_revoke_failure(err: &BadgeError) -> bool {
+    matches!(err, BadgeError::BenefitRevokeFailed { .. })
+}
+
+/// 判断订单是否仍在用户可取消时限内
+fn within_cancel_window(
+    created_at: chrono::DateTime<Utc>,
+    now: chrono::DateTime<Utc>,
+    window: chrono::Duration,
+) -> bool {
+    now - created_at <= window
 }
 
 /// 生成兑换订单号
@@ -673,6 +1382,124 @@ mod tests {
         assert_eq!(order_no2.len(), 22);
     }
 
+    #[test]
+    fn test_within_cancel_window() {
+        let created_at = Utc::now();
+        let window = chrono::Duration::minutes(30);
+
+        assert!(within_cancel_window(created_at, created_at, window));
+        assert!(within_cancel_window(
+            created_at,
+            created_at + chrono::Duration::minutes(30),
+            window
+        ));
+        assert!(!within_cancel_window(
+            created_at,
+            created_at + chrono::Duration::minutes(31),
+            window
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_revoke_refused_restores_order() {
+        let benefit_service = crate::benefit::test_support::ExternalStubs::start()
+            .await
+            .service();
+
+        // 实物权益发放后处于处理中，不允许撤销
+        let request = GrantBenefitRequest::new(
+            "user-123",
+            BenefitType::Physical,
+            1,
+            json!({
+                "sku_id": "SKU001",
+                "shipping_address": {
+                    "recipient_name": "张三",
+                    "phone": "13800138000",
+                    "province": "北京市",
+                    "city": "北京市",
+                    "district": "朝阳区",
+                    "address": "某某街道"
+                }
+            }),
+        )
+        .with_grant_no("RG-RD-REFUSED");
+        benefit_service.grant_benefit(request).await.unwrap();
+
+        let err = revoke_order_benefit(&benefit_service, "RD-REFUSED")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, BadgeError::BenefitRevokeFailed { .. }));
+        assert!(restores_order_on_revoke_failure(&err));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_revoke_error_keeps_order_cancelling() {
+        // 外部撤销成功但持久化失败，无法确认最终状态，订单须保持取消中
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .acquire_timeout(Duration::from_millis(200))
+            .connect_lazy("postgres://localhost:1/test")
+            .unwrap();
+        let benefit_service = crate::benefit::test_support::ExternalStubs::start()
+            .await
+            .service()
+            .with_pool(pool);
+
+        let request = GrantBenefitRequest::new(
+            "user-123",
+            BenefitType::Coupon,
+            1,
+            json!({"coupon_template_id": "tpl-001"}),
+        )
+        .with_grant_no("RG-RD-PERSIST");
+        benefit_service.grant_benefit(request).await.unwrap();
+
+        let err = revoke_order_benefit(&benefit_service, "RD-PERSIST")
+            .await
+            .unwrap_err();
+        assert!(!restores_order_on_revoke_failure(&err));
+    }
+
+    #[test]
+    fn test_revoke_failure_error() {
+        let mut result = RevokeResult {
+            grant_no: "RG-RD1".to_string(),
+            success: false,
+            reason: RevokeReason::OrderRefund,
+            revoked_at: None,
+            message: Some("优惠券已使用".to_string()),
+            retryable: false,
+        };
+
+        // 外部系统明确拒绝时订单恢复为成功
+        let err = revoke_failure_error("RD1", result.clone());
+        assert!(matches!(err, BadgeError::BenefitRevokeFailed { .. }));
+        assert!(restores_order_on_revoke_failure(&err));
+
+        // 瞬时故障时撤销结果未知，订单保持取消中
+        result.retryable = true;
+        let err = revoke_failure_error("RD1", result);
+        assert!(matches!(err, BadgeError::BenefitRevokePending { .. }));
+        assert!(err.is_retryable());
+        assert!(!restores_order_on_revoke_failure(&err));
+    }
+
+    #[test]
+    fn test_cancel_redemption_request_initiator() {
+        let request = CancelRedemptionRequest::by_user("RD1", "user-123", None);
+        assert_eq!(
+            request.initiator,
+            CancelInitiator::User {
+                user_id: "user-123".to_string()
+            }
+        );
+        assert_eq!(request.initiator.cancelled_by(), "user:user-123");
+
+        let request = CancelRedemptionRequest::by_admin("RD1", "admin", Some("误操作".to_string()));
+        assert_eq!(request.initiator.cancelled_by(), "admin");
+        assert_eq!(request.reason.as_deref(), Some("误操作"));
+    }
+
     #[test]
     fn test_redeem_badge_request_new() {
         let request = RedeemBadgeRequest::new("user-123", 1, "idem-key-001");
@@ -748,6 +1575,42 @@ mod tests {
         assert_eq!(json["quantity"], 3);
     }
 
+    #[test]
+    fn test_redeemable_benefit_dto_serialization() {
+        use crate::models::{BenefitType, RedemptionRecipe, RequiredBadge};
+
+        let dto = RedeemableBenefitDto {
+            rule_id: 1,
+            rule_name: "新手礼包".to_string(),
+            benefit_id: 2,
+            benefit_name: "优惠券".to_string(),
+            benefit_type: BenefitType::Coupon,
+            icon_url: None,
+            remaining_stock: Some(5),
+            status: RedeemableStatus::FrequencyLimited,
+            reasons: vec!["已达兑换频率上限 per_day: 1/1".to_string()],
+            missing: vec![],
+            limited_by: Some("per_day".to_string()),
+            recipe: RedemptionRecipe::from_required_badges(&[RequiredBadge {
+                badge_id: 3,
+                quantity: 1,
+            }]),
+            end_time: None,
+        };
+
+        let json = serde_json::to_value(&dto).unwrap();
+        assert_eq!(json["status"], "frequency_limited");
+        assert_eq!(json["limitedBy"], "per_day");
+        assert_eq!(json["benefitType"], "COUPON");
+        assert_eq!(json["recipe"]["terms"][0]["badgeId"], 3);
+        assert!(json.get("endTime").is_none());
+        assert!(!dto.is_eligible());
+
+        // 缓存反序列化往返
+        let cached: RedeemableBenefitDto = serde_json::from_value(json).unwrap();
+        assert_eq!(cached.status, RedeemableStatus::FrequencyLimited);
+    }
+
     fn create_test_benefit() -> Benefit {
         Benefit {
             id: 1,
@@ -6,10 +6,13 @@
 //! - 事务性扣减（用户徽章、账本流水）
 //! - 状态变更（数量归零时标记为 Revoked）
 //! - 发送撤销通知
+//! - 排行榜更新
+//! - 反向级联：按依赖的 revoke_policy 撤销或暂停以该徽章为前置条件的徽章
 //!
 //! ## 取消流程
 //!
-//! 1. 参数校验 -> 2. 查询用户徽章 -> 3. 余额检查 -> 4. 事务内扣减 -> 5. 缓存失效 -> 6. 发送通知
+//! 1. 参数校验 -> 2. 查询用户徽章 -> 3. 余额检查 -> 4. 事务内扣减 -> 5. 缓存失效
+//!    -> 6. 更新排行榜 -> 7. 发送通知 -> 8. 反向级联
 
 use std::sync::Arc;
 
@@ -19,9 +22,11 @@ use tokio::sync::RwLock;
 use tracing::{info, instrument, warn};
 
 use badge_shared::cache::Cache;
+use badge_shared::leaderboard::{BadgeChange, LeaderboardStore};
 
 use crate::error::{BadgeError, Result};
 use crate::models::{BadgeLedger, ChangeType, LogAction, RecipientType, UserBadgeStatus};
+use crate::cascade::{CascadeEvaluator, RevokePolicy};
 use crate::notification::NotificationSender;
 use crate::repository::{BadgeLedgerRepository, BadgeRepositoryTrait, UserBadgeRepository};
 use crate::service::dto::{
@@ -38,6 +43,25 @@ mod cache_keys {
     pub fn badge_wall(user_id: &str) -> String {
         format!("user:badge:wall:{}", user_id)
     }
+
+    pub fn badge_progress(user_id: &str) -> String {
+        format!("user:badge:progress:{}", user_id)
+    }
+
+    pub fn redeemable_benefits(user_id: &str) -> String {
+        format!("user:redeemable:{}", user_id)
+    }
+}
+
+/// 事务内撤销的执行结果
+enum RevokeExecution {
+    /// 本次实际扣减，携带扣减后余额和同事务登记的待处理反向级联
+    Applied {
+        remaining: i32,
+        pending_cascade: Option<i64>,
+    },
+    /// 幂等键已处理过，携带当前余额
+    Duplicate(i32),
 }
 
 /// 徽章取消服务
@@ -53,6 +77,10 @@ where
     badge_repo: Arc<BR>,
     /// 通知发送器（可选，用于发送徽章撤销通知）
     notification_sender: RwLock<Option<Arc<NotificationSender>>>,
+    /// 排行榜存储（可选，未设置时不维护排行榜）
+    leaderboard: RwLock<Option<Arc<LeaderboardStore>>>,
+    /// 级联评估器（可选，未设置时不做反向级联）
+    cascade_evaluator: RwLock<Option<Arc<CascadeEvaluator>>>,
 }
 
 impl<BR> RevokeService<BR>
@@ -65,6 +93,8 @@ where
             pool,
             badge_repo,
             notification_sender: RwLock::new(None),
+            leaderboard: RwLock::new(None),
+            cascade_evaluator: RwLock::new(None),
         }
     }
 
@@ -77,6 +107,22 @@ where
         info!("RevokeService 通知发送器已设置");
     }
 
+    /// 设置排行榜存储
+    pub async fn set_leaderboard(&self, leaderboard: Arc<LeaderboardStore>) {
+        let mut guard = self.leaderboard.write().await;
+        *guard = Some(leaderboard);
+        info!("RevokeService 排行榜存储已设置");
+    }
+
+    /// 设置级联评估器
+    ///
+    /// 设置后撤销徽章会触发反向级联，处理依赖该徽章的其他徽章。
+    pub async fn set_cascade_evaluator(&self, evaluator: Arc<CascadeEvaluator>) {
+        let mut guard = self.cascade_evaluator.write().await;
+        *guard = Some(evaluator);
+        info!("RevokeService 级联评估器已设置");
+    }
+
     /// 取消/撤销徽章
     ///
     /// 完整的取消流程：
@@ -85,7 +131,9 @@ where
     /// 3. 检查徽章状态和余额
     /// 4. 事务内执行扣减
     /// 5. 清除缓存
-    /// 6. 发送撤销通知
+    /// 6. 更新排行榜
+    /// 7. 发送撤销通知
+    /// 8. 反向级联（待处理记录已随撤销事务提交，失败时由重试 Worker 补偿）
     #[instrument(skip(self), fields(user_id = %request.user_id, badge_id = %request.badge_id, quantity = %request.quantity))]
     pub async fn revoke_badge(&self, request: RevokeBadgeRequest) -> Result<RevokeBadgeResponse> {
         let start = std::time::Instant::now();
@@ -98,8 +146,18 @@ where
         }
 
         // 2-4. 事务内执行取消操作
-        let remaining_quantity = match self.execute_revoke(&request).await {
-            Ok(q) => q,
+        let (remaining_quantity, pending_cascade) = match self.execute_revoke(&request).await {
+            Ok(RevokeExecution::Applied {
+                remaining,
+                pending_cascade,
+            }) => (remaining, pending_cascade),
+            Ok(RevokeExecution::Duplicate(q)) => {
+                info!(
+                    idempotency_key = ?request.idempotency_key,
+                    "幂等请求，撤销已执行过"
+                );
+                return Ok(RevokeBadgeResponse::from_existing(q));
+            }
             Err(e) => {
                 badge_shared::observability::metrics::record_badge_revoke(&source_str, "error", start.elapsed().as_secs_f64());
                 return Err(e);
@@ -111,10 +169,25 @@ where
         // 5. 清除缓存
         self.invalidate_user_cache(&request.user_id).await;
 
-        // 6. 发送撤销通知（异步，不阻塞主流程）
+        // 6. 更新排行榜
+        self.update_leaderboard(BadgeChange::removed(
+            &request.user_id,
+            request.badge_id,
+            request.quantity,
+            remaining_quantity,
+        ))
+        .await;
+
+        // 7. 发送撤销通知（异步，不阻塞主流程）
         self.send_revoke_notification(&request.user_id, request.badge_id, &request.reason)
             .await;
 
+        // 8. 反向级联（依赖可能要求最低持有数量，部分扣减也需检查）
+        if let Some(pending_id) = pending_cascade {
+            self.trigger_revoke_cascade(pending_id, &request.user_id, request.badge_id)
+                .await;
+        }
+
         info!(
             user_id = %request.user_id,
             badge_id = %request.badge_id,
@@ -143,6 +216,64 @@ where
         }
     }
 
+    /// 触发反向级联并处理受影响徽章的缓存、排行榜和通知
+    ///
+    /// 失败时待处理记录保留，由过期 Worker 按退避时间重试
+    async fn trigger_revoke_cascade(&self, pending_id: i64, user_id: &str, badge_id: i64) {
+        let evaluator = self.cascade_evaluator.read().await.clone();
+        let Some(evaluator) = evaluator else {
+            return;
+        };
+
+        let result = match evaluator
+            .evaluate_pending_revoke(pending_id, user_id, badge_id)
+            .await
+        {
+            Ok(result) => result,
+            Err(e) => {
+                warn!(
+                    user_id = %user_id,
+                    badge_id = %badge_id,
+                    pending_id,
+                    error = %e,
+                    "反向级联评估失败，已登记待重试"
+                );
+                return;
+            }
+        };
+        if result.revoked_badges.is_empty() {
+            return;
+        }
+
+        self.invalidate_user_cache(user_id).await;
+
+        let sender = self.notification_sender.read().await.clone();
+        for dependent in &result.revoked_badges {
+            // 暂停的徽章同样不再计入排行榜，恢复时再重新计入
+            self.update_leaderboard(BadgeChange::removed(
+                user_id,
+                dependent.badge_id,
+                dependent.quantity,
+                0,
+            ))
+            .await;
+
+            let Some(sender) = &sender else {
+                continue;
+            };
+            let Ok(Some(badge)) = self.badge_repo.get_badge(dependent.badge_id).await else {
+                continue;
+            };
+            let reason = format!("前置徽章 {} 已失效", dependent.triggered_by);
+            match dependent.policy {
+                RevokePolicy::Suspend => {
+                    sender.send_badge_suspended(user_id, dependent.badge_id, &badge.name, &reason)
+                }
+                _ => sender.send_badge_revoked(user_id, dependent.badge_id, &badge.name, &reason),
+            }
+        }
+    }
+
     /// 批量取消徽章
     ///
     /// 对每个请求独立处理，单个失败不影响其他请求
@@ -172,7 +303,10 @@ where
                 Err(e) => {
                     failed_count += 1;
                     warn!(user_id = %user_id, badge_id = %badge_id, error = %e, "批量取消单条失败");
-                    results.push(RevokeResult::failure(user_id, badge_id, e.to_string()));
+                    results.push(
+                        RevokeResult::failure(user_id, badge_id, e.to_string())
+                            .with_error_code(e.error_code()),
+                    );
                 }
             }
         }
@@ -389,7 +523,9 @@ where
     /// - 更新状态（如归零）
     /// - 写入账本流水
     /// - 写入操作日志
-    async fn execute_revoke(&self, request: &RevokeBadgeRequest) -> Result<i32> {
+    ///
+    /// 幂等检查放在行锁之后，保证同一幂等键的并发重试只有一次生效
+    async fn execute_revoke(&self, request: &RevokeBadgeRequest) -> Result<RevokeExecution> {
         let mut tx = self.pool.begin().await?;
 
         // 2. 查询用户徽章（带行级锁防止并发）
@@ -404,6 +540,12 @@ where
             badge_id: request.badge_id,
         })?;
 
+        if let Some(ref key) = request.idempotency_key
+            && BadgeLedgerRepository::exists_by_ref_in_tx(&mut tx, key, ChangeType::Cancel).await?
+        {
+            return Ok(RevokeExecution::Duplicate(user_badge.quantity));
+        }
+
         // 检查徽章状态：只允许取消 Active 状态的徽章
         if user_badge.status != UserBadgeStatus::Active {
             return Err(BadgeError::Validation(format!(
@@ -450,7 +592,11 @@ where
             change_type: ChangeType::Cancel,
             quantity: -request.quantity, // 负数表示减少
             balance_after: new_quantity,
-            ref_id: request.source_ref_id.clone(),
+            // 幂等键优先写入 ref_id，供重试时识别
+            ref_id: request
+                .idempotency_key
+                .clone()
+                .or_else(|| request.source_ref_id.clone()),
             ref_type: request.source_type,
             remark: Some(request.reason.clone()),
             operator: request.operator.clone(),
@@ -480,10 +626,24 @@ where
         .execute(&mut *tx)
         .await?;
 
+        // 4.6 登记反向级联，与撤销同事务提交
+        let evaluator = self.cascade_evaluator.read().await.clone();
+        let pending_cascade = match evaluator {
+            Some(evaluator) => {
+                evaluator
+                    .enqueue_revoke_in_tx(&mut tx, &request.user_id, request.badge_id)
+                    .await?
+            }
+            None => None,
+        };
+
         // 5. 提交事务
         tx.commit().await?;
 
-        Ok(new_quantity)
+        Ok(RevokeExecution::Applied {
+            remaining: new_quantity,
+            pending_cascade,
+        })
     }
 
     /// 使用户徽章相关缓存失效
@@ -491,6 +651,8 @@ where
         let keys = [
             cache_keys::user_badges(user_id),
             cache_keys::badge_wall(user_id),
+            cache_keys::badge_progress(user_id),
+            cache_keys::redeemable_benefits(user_id),
         ];
 
         for key in keys {
@@ -499,6 +661,21 @@ where
             }
         }
     }
+
+    /// 增量更新排行榜，失败不影响取消结果
+    async fn update_leaderboard(&self, change: BadgeChange) {
+        let leaderboard = self.leaderboard.read().await.clone();
+        if let Some(leaderboard) = leaderboard
+            && let Err(e) = leaderboard.record(&change).await
+        {
+            warn!(
+                user_id = %change.user_id,
+                badge_id = change.badge_id,
+                error = %e,
+                "排行榜更新失败"
+            );
+        }
+    }
 }
 
 #[cfg(test)]
@@ -628,6 +805,18 @@ mod tests {
         assert_eq!(request.source_type, SourceType::System);
         assert_eq!(request.source_ref_id, Some("task-001".to_string()));
         assert!(request.operator.is_none());
+        assert!(request.idempotency_key.is_none());
+    }
+
+    #[test]
+    fn test_revoke_request_with_idempotency_key() {
+        let request = RevokeBadgeRequest::system("user-123", 1, 1, "批量撤销")
+            .with_idempotency_key("batch-42-1");
+        assert_eq!(request.idempotency_key, Some("batch-42-1".to_string()));
+
+        let response = RevokeBadgeResponse::from_existing(2);
+        assert!(response.success);
+        assert_eq!(response.remaining_quantity, 2);
     }
 
     #[test]
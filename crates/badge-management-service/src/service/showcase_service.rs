@@ -0,0 +1,313 @@
+//! 徽章墙展示服务
+//!
+//! 管理用户徽章墙的置顶展示位：
+//! - 置顶 / 取消置顶（置顶数量受 `showcase.max_pinned` 限制）
+//! - 调整置顶徽章的展示顺序
+//!
+//! 置顶状态存储在 `user_badges.pin_order` 上，同一用户的置顶操作通过
+//! 事务级 advisory lock 串行执行，避免并发置顶突破数量上限。
+//! 撤销、过期的徽章在下一次置顶操作时释放展示位。
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use sqlx::{PgConnection, PgPool};
+use tracing::{info, instrument, warn};
+
+use badge_shared::cache::Cache;
+
+use crate::error::{BadgeError, Result};
+
+/// 缓存键生成
+mod cache_keys {
+    pub fn user_badges(user_id: &str) -> String {
+        format!("user:badge:{}", user_id)
+    }
+
+    pub fn badge_wall(user_id: &str) -> String {
+        format!("user:badge:wall:{}", user_id)
+    }
+}
+
+/// 置顶目标徽章的当前状态
+#[derive(sqlx::FromRow)]
+struct PinTarget {
+    pin_order: Option<i32>,
+    /// 是否为有效徽章（状态有效且未过期）
+    valid: bool,
+    expired: bool,
+}
+
+/// 徽章墙展示服务
+pub struct ShowcaseService {
+    pool: PgPool,
+    cache: Arc<Cache>,
+    /// 每个用户最多可置顶的徽章数量
+    max_pinned: u32,
+}
+
+impl ShowcaseService {
+    pub fn new(pool: PgPool, cache: Arc<Cache>, max_pinned: u32) -> Self {
+        Self {
+            pool,
+            cache,
+            max_pinned,
+        }
+    }
+
+    pub fn max_pinned(&self) -> u32 {
+        self.max_pinned
+    }
+
+    /// 置顶徽章，返回其在置顶列表中的展示顺序（从 1 开始）
+    ///
+    /// 新置顶的徽章排在末尾；已置顶的徽章重复置顶直接返回当前顺序。
+    #[instrument(skip(self))]
+    pub async fn pin(&self, user_id: &str, user_badge_id: i64) -> Result<i32> {
+        let mut tx = self.pool.begin().await?;
+        Self::lock_user(&mut tx, user_id).await?;
+
+        let target = Self::get_target(&mut tx, user_id, user_badge_id).await?;
+        if !target.valid {
+            return Err(if target.expired {
+                BadgeError::UserBadgeExpired(user_badge_id)
+            } else {
+                BadgeError::Validation("只有有效状态的徽章可以置顶".to_string())
+            });
+        }
+
+        Self::release_stale_pins(&mut tx, user_id).await?;
+
+        let pinned = Self::list_pinned_ids(&mut tx, user_id).await?;
+        if let Some(pos) = pinned.iter().position(|&id| id == user_badge_id) {
+            tx.commit().await?;
+            return Ok(pos as i32 + 1);
+        }
+
+        if pinned.len() as u32 >= self.max_pinned {
+            return Err(BadgeError::PinLimitReached {
+                limit: self.max_pinned,
+            });
+        }
+
+        let pin_order = pinned.len() as i32 + 1;
+        sqlx::query(
+            r#"
+            UPDATE user_badges
+            SET pin_order = $2, pinned_at = NOW(), updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(user_badge_id)
+        .bind(pin_order)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        self.invalidate_user_cache(user_id).await;
+
+        info!(user_id = %user_id, user_badge_id, pin_order, "徽章已置顶");
+        Ok(pin_order)
+    }
+
+    /// 取消置顶
+    ///
+    /// 未置顶的徽章直接返回成功；其余置顶徽章的顺序保持不变并重新编号。
+    #[instrument(skip(self))]
+    pub async fn unpin(&self, user_id: &str, user_badge_id: i64) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        Self::lock_user(&mut tx, user_id).await?;
+
+        let target = Self::get_target(&mut tx, user_id, user_badge_id).await?;
+        if target.pin_order.is_none() {
+            tx.commit().await?;
+            return Ok(());
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE user_badges
+            SET pin_order = NULL, pinned_at = NULL, updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(user_badge_id)
+        .execute(&mut *tx)
+        .await?;
+
+        Self::release_stale_pins(&mut tx, user_id).await?;
+
+        tx.commit().await?;
+        self.invalidate_user_cache(user_id).await;
+
+        info!(user_id = %user_id, user_badge_id, "徽章已取消置顶");
+        Ok(())
+    }
+
+    /// 调整置顶徽章的展示顺序
+    ///
+    /// `user_badge_ids` 必须恰好包含用户当前全部有效的置顶徽章，按期望的展示顺序排列。
+    #[instrument(skip(self))]
+    pub async fn reorder(&self, user_id: &str, user_badge_ids: &[i64]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        Self::lock_user(&mut tx, user_id).await?;
+
+        Self::release_stale_pins(&mut tx, user_id).await?;
+        let pinned = Self::list_pinned_ids(&mut tx, user_id).await?;
+        validate_reorder(&pinned, user_badge_ids)?;
+
+        sqlx::query(
+            r#"
+            UPDATE user_badges ub
+            SET pin_order = o.ord::int, updated_at = NOW()
+            FROM UNNEST($2::bigint[]) WITH ORDINALITY AS o(id, ord)
+            WHERE ub.id = o.id AND ub.user_id = $1
+            "#,
+        )
+        .bind(user_id)
+        .bind(user_badge_ids)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        self.invalidate_user_cache(user_id).await;
+
+        info!(user_id = %user_id, count = user_badge_ids.len(), "置顶徽章顺序已调整");
+        Ok(())
+    }
+
+    /// 串行化同一用户的置顶操作，锁随事务结束自动释放
+    async fn lock_user(conn: &mut PgConnection, user_id: &str) -> Result<()> {
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+            .bind(format!("user_badge_pin:{}", user_id))
+            .execute(conn)
+            .await?;
+        Ok(())
+    }
+
+    async fn get_target(
+        conn: &mut PgConnection,
+        user_id: &str,
+        user_badge_id: i64,
+    ) -> Result<PinTarget> {
+        sqlx::query_as::<_, PinTarget>(
+            r#"
+            SELECT pin_order,
+                   UPPER(status) = 'ACTIVE' AND (expires_at IS NULL OR expires_at > NOW()) AS valid,
+                   UPPER(status) = 'EXPIRED' OR COALESCE(expires_at <= NOW(), FALSE) AS expired
+            FROM user_badges
+            WHERE id = $1 AND user_id = $2
+            "#,
+        )
+        .bind(user_badge_id)
+        .bind(user_id)
+        .fetch_optional(conn)
+        .await?
+        .ok_or(BadgeError::UserBadgeRecordNotFound(user_badge_id))
+    }
+
+    /// 释放已失效徽章占用的展示位，并将剩余置顶徽章重新编号为 1..n
+    async fn release_stale_pins(conn: &mut PgConnection, user_id: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE user_badges
+            SET pin_order = NULL, pinned_at = NULL, updated_at = NOW()
+            WHERE user_id = $1
+              AND pin_order IS NOT NULL
+              AND NOT (UPPER(status) = 'ACTIVE' AND (expires_at IS NULL OR expires_at > NOW()))
+            "#,
+        )
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            r#"
+            UPDATE user_badges ub
+            SET pin_order = r.rn
+            FROM (
+                SELECT id, ROW_NUMBER() OVER (ORDER BY pin_order, pinned_at)::int AS rn
+                FROM user_badges
+                WHERE user_id = $1 AND pin_order IS NOT NULL
+            ) r
+            WHERE ub.id = r.id AND ub.pin_order <> r.rn
+            "#,
+        )
+        .bind(user_id)
+        .execute(conn)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn list_pinned_ids(conn: &mut PgConnection, user_id: &str) -> Result<Vec<i64>> {
+        let ids = sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT id FROM user_badges
+            WHERE user_id = $1 AND pin_order IS NOT NULL
+            ORDER BY pin_order
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(conn)
+        .await?;
+
+        Ok(ids)
+    }
+
+    /// 使用户徽章相关缓存失效
+    async fn invalidate_user_cache(&self, user_id: &str) {
+        let keys = [
+            cache_keys::user_badges(user_id),
+            cache_keys::badge_wall(user_id),
+        ];
+
+        for key in keys {
+            if let Err(e) = self.cache.delete(&key).await {
+                warn!(key = %key, error = %e, "缓存失效失败");
+            }
+        }
+    }
+}
+
+/// 校验排序请求与当前置顶徽章集合一致
+fn validate_reorder(current: &[i64], requested: &[i64]) -> Result<()> {
+    let requested_set: HashSet<i64> = requested.iter().copied().collect();
+    if requested_set.len() != requested.len() {
+        return Err(BadgeError::Validation(
+            "置顶排序中存在重复的徽章".to_string(),
+        ));
+    }
+
+    let current_set: HashSet<i64> = current.iter().copied().collect();
+    if requested_set != current_set {
+        return Err(BadgeError::Validation(format!(
+            "置顶排序必须包含且仅包含当前全部置顶徽章: 当前 {:?}",
+            current
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_reorder() {
+        assert!(validate_reorder(&[1, 2, 3], &[3, 1, 2]).is_ok());
+        assert!(validate_reorder(&[], &[]).is_ok());
+
+        // 缺少、多出或重复的徽章都不允许
+        assert!(validate_reorder(&[1, 2, 3], &[1, 2]).is_err());
+        assert!(validate_reorder(&[1, 2], &[1, 2, 4]).is_err());
+        assert!(validate_reorder(&[1, 2], &[1, 1]).is_err());
+    }
+
+    #[test]
+    fn test_cache_keys_match_query_service() {
+        assert_eq!(cache_keys::user_badges("user-1"), "user:badge:user-1");
+        assert_eq!(cache_keys::badge_wall("user-1"), "user:badge:wall:user-1");
+    }
+}
@@ -1,16 +1,22 @@
 //! 权益发放集成测试
 //!
 //! 测试权益发放的完整业务流程，包括：
-//! - 优惠券发放与撤销
-//! - 积分发放
+//! - 优惠券发放与撤销（对接 mock-services 优惠券服务）
+//! - 积分发放（对接 mock-services 权益服务）
 //! - 实物异步发放
+//! - 数字资产等异步权益的状态流转（对接 mock-services 履约服务）
+//! - 外部回调（对接 mock-services 回调桩）
 //! - BenefitService 完整流程
 
 use badge_management::benefit::{
-    BenefitGrantRequest, BenefitHandler, BenefitService, CouponHandler, GrantBenefitRequest,
-    HandlerRegistry, PhysicalHandler, PointsHandler,
+    BenefitGrantRequest, BenefitHandler, BenefitService, CouponHandler, ExternalCallbackHandler,
+    GrantBenefitRequest, HandlerRegistry, PhysicalHandler, PointsHandler, RegistryConfig,
 };
 use badge_management::models::{BenefitType, GrantStatus, RevokeReason};
+use mock_services::services::{
+    BenefitServiceState, CouponServiceState, FulfillmentServiceState, benefit_routes,
+    coupon_routes, fulfillment_routes,
+};
 use serde_json::json;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
@@ -35,9 +41,32 @@ fn create_valid_address() -> serde_json::Value {
     })
 }
 
-/// 创建使用默认 Handler 的 BenefitService
-fn create_service() -> BenefitService {
-    BenefitService::with_defaults()
+/// 在随机端口启动 mock-services 的优惠券、权益和履约服务，返回 base URL
+async fn start_external_stubs() -> String {
+    let app = coupon_routes()
+        .with_state(Arc::new(CouponServiceState::default()))
+        .merge(benefit_routes().with_state(Arc::new(BenefitServiceState::new())))
+        .merge(fulfillment_routes().with_state(Arc::new(FulfillmentServiceState::new())));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    format!("http://{}", addr)
+}
+
+/// 创建使用默认 Handler 的 BenefitService，外部服务均指向 mock 服务桩
+async fn create_service() -> BenefitService {
+    let base_url = start_external_stubs().await;
+    let registry = HandlerRegistry::with_config(RegistryConfig {
+        coupon_service_url: base_url.clone(),
+        points_service_url: base_url.clone(),
+        digital_asset_service_url: base_url.clone(),
+        reservation_service_url: base_url.clone(),
+        membership_service_url: base_url,
+        ..Default::default()
+    });
+    BenefitService::new(Arc::new(registry))
 }
 
 /// 创建自定义注册表的 BenefitService
@@ -60,7 +89,7 @@ mod coupon_integration {
     /// 3. 状态为 Success
     #[tokio::test]
     async fn test_coupon_grant_success() {
-        let service = create_service();
+        let service = create_service().await;
         let grant_no = next_grant_no();
 
         let request = GrantBenefitRequest::new(
@@ -96,9 +125,10 @@ mod coupon_integration {
             payload.get("coupon_id").is_some(),
             "payload 应包含 coupon_id"
         );
-        assert!(
-            payload.get("coupon_code").is_some(),
-            "payload 应包含 coupon_code"
+        assert_eq!(
+            payload.get("template_id").unwrap(),
+            "tpl-summer-2024",
+            "payload 应包含优惠券模板 ID"
         );
     }
 
@@ -107,7 +137,7 @@ mod coupon_integration {
     /// 验证：缺少必要字段时返回 Failed 状态而非抛出异常
     #[tokio::test]
     async fn test_coupon_grant_invalid_config() {
-        let service = create_service();
+        let service = create_service().await;
         let grant_no = next_grant_no();
 
         let request = GrantBenefitRequest::new(
@@ -134,7 +164,7 @@ mod coupon_integration {
     /// 3. 再次查询状态应为 Revoked
     #[tokio::test]
     async fn test_coupon_revoke() {
-        let service = create_service();
+        let service = create_service().await;
         let grant_no = next_grant_no();
 
         // 先发放优惠券
@@ -172,7 +202,7 @@ mod coupon_integration {
     /// 验证：已撤销的优惠券不能再次撤销
     #[tokio::test]
     async fn test_coupon_double_revoke() {
-        let service = create_service();
+        let service = create_service().await;
         let grant_no = next_grant_no();
 
         // 发放并撤销
@@ -221,7 +251,7 @@ mod points_integration {
     /// 2. 返回结果包含交易 ID 和余额信息
     #[tokio::test]
     async fn test_points_grant_success() {
-        let service = create_service();
+        let service = create_service().await;
         let grant_no = next_grant_no();
 
         let request = GrantBenefitRequest::new(
@@ -259,7 +289,11 @@ mod points_integration {
             "bonus",
             "应返回积分类型"
         );
-        assert!(payload.get("balance_after").is_some(), "应返回发放后余额");
+        assert_eq!(
+            payload.get("transaction_id").and_then(|v| v.as_str()),
+            response.external_ref.as_deref(),
+            "payload 中的积分流水号应与外部引用一致"
+        );
     }
 
     /// 测试积分发放使用默认积分类型
@@ -267,7 +301,7 @@ mod points_integration {
     /// 验证：不指定 point_type 时使用默认值 "general"
     #[tokio::test]
     async fn test_points_grant_default_type() {
-        let service = create_service();
+        let service = create_service().await;
         let grant_no = next_grant_no();
 
         let request = GrantBenefitRequest::new(
@@ -299,7 +333,7 @@ mod points_integration {
     /// 生产环境应在发放前调用 validate_config 进行预校验
     #[test]
     fn test_points_validate_invalid_amount() {
-        let service = create_service();
+        let service = BenefitService::with_defaults();
 
         // 零金额应校验失败
         let zero_amount = json!({
@@ -340,7 +374,7 @@ mod points_integration {
     /// 验证：缺少 point_amount 字段时发放失败
     #[tokio::test]
     async fn test_points_grant_missing_amount() {
-        let service = create_service();
+        let service = create_service().await;
         let grant_no = next_grant_no();
 
         let request = GrantBenefitRequest::new(
@@ -363,7 +397,7 @@ mod points_integration {
     /// 验证：积分支持撤销操作
     #[tokio::test]
     async fn test_points_revoke() {
-        let service = create_service();
+        let service = create_service().await;
         let grant_no = next_grant_no();
 
         // 先发放积分
@@ -406,7 +440,7 @@ mod physical_integration {
     /// 3. 状态查询返回 Processing
     #[tokio::test]
     async fn test_physical_async_grant() {
-        let service = create_service();
+        let service = create_service().await;
         let grant_no = next_grant_no();
 
         let request = GrantBenefitRequest::new(
@@ -446,7 +480,7 @@ mod physical_integration {
     /// 验证：收货地址可以通过 metadata 传递而非配置
     #[tokio::test]
     async fn test_physical_address_from_metadata() {
-        let service = create_service();
+        let service = create_service().await;
         let grant_no = next_grant_no();
 
         let request = GrantBenefitRequest::new(
@@ -474,7 +508,7 @@ mod physical_integration {
     /// 验证：缺少收货地址时发放失败
     #[tokio::test]
     async fn test_physical_missing_address() {
-        let service = create_service();
+        let service = create_service().await;
         let grant_no = next_grant_no();
 
         let request = GrantBenefitRequest::new(
@@ -505,7 +539,7 @@ mod physical_integration {
     /// 验证：实物发放后不能撤销（状态为 Processing 也不允许）
     #[tokio::test]
     async fn test_physical_revoke_not_allowed() {
-        let service = create_service();
+        let service = create_service().await;
         let grant_no = next_grant_no();
 
         // 先发放实物
@@ -549,7 +583,7 @@ mod benefit_service_flow {
     /// 4. 配置验证
     #[tokio::test]
     async fn test_benefit_service_flow() {
-        let service = create_service();
+        let service = create_service().await;
 
         // 1. 测试自动生成流水号
         let request1 = GrantBenefitRequest::new(
@@ -634,30 +668,30 @@ mod benefit_service_flow {
     /// 验证默认注册表包含预期的 Handler
     #[tokio::test]
     async fn test_supported_benefit_types() {
-        let service = create_service();
+        let service = create_service().await;
 
         let types = service.supported_types();
-        assert_eq!(types.len(), 3, "默认应支持 3 种权益类型");
+        assert_eq!(types.len(), 7, "默认应支持全部 7 种权益类型");
 
         assert!(service.supports(BenefitType::Coupon), "应支持优惠券");
         assert!(service.supports(BenefitType::Points), "应支持积分");
         assert!(service.supports(BenefitType::Physical), "应支持实物");
-
-        // 未注册的类型
         assert!(
-            !service.supports(BenefitType::DigitalAsset),
-            "默认不应支持数字资产"
+            service.supports(BenefitType::DigitalAsset),
+            "应支持数字资产"
         );
+        assert!(service.supports(BenefitType::Reservation), "应支持预约资格");
+        assert!(service.supports(BenefitType::Membership), "应支持会员权益");
         assert!(
-            !service.supports(BenefitType::Membership),
-            "默认不应支持会员权益"
+            service.supports(BenefitType::ExternalCallback),
+            "应支持外部回调"
         );
     }
 
     /// 测试批量状态查询
     #[tokio::test]
     async fn test_batch_status_query() {
-        let service = create_service();
+        let service = create_service().await;
 
         // 发放多个权益
         let mut grant_nos = Vec::new();
@@ -690,7 +724,7 @@ mod benefit_service_flow {
     /// 测试带元数据的发放
     #[tokio::test]
     async fn test_grant_with_metadata() {
-        let service = create_service();
+        let service = create_service().await;
         let grant_no = next_grant_no();
 
         let request = GrantBenefitRequest::new(
@@ -716,7 +750,7 @@ mod benefit_service_flow {
     /// 测试不同撤销原因
     #[tokio::test]
     async fn test_revoke_with_different_reasons() {
-        let service = create_service();
+        let service = create_service().await;
 
         let reasons = vec![
             RevokeReason::UserRequest,
@@ -751,6 +785,423 @@ mod benefit_service_flow {
     }
 }
 
+// ============================================================================
+// 异步权益与外部回调测试
+// ============================================================================
+
+mod async_benefit_integration {
+    use super::*;
+    use badge_shared::retry::RetryPolicy;
+    use mock_services::services::callback_service::DEFAULT_CALLBACK_SECRET;
+    use mock_services::services::{CallbackServiceState, callback_routes};
+    use sqlx::PgPool;
+    use std::time::Duration;
+
+    /// 在随机端口启动回调桩，返回回调地址和桩状态
+    async fn start_callback_stub() -> (String, Arc<CallbackServiceState>) {
+        let state = Arc::new(CallbackServiceState::default());
+        let app = callback_routes().with_state(state.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        (format!("http://{}/callbacks/benefits", addr), state)
+    }
+
+    fn callback_handler(secret: &str) -> ExternalCallbackHandler {
+        ExternalCallbackHandler::new(secret).with_retry_policy(RetryPolicy {
+            max_retries: 3,
+            initial_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(50),
+            multiplier: 2.0,
+        })
+    }
+
+    fn callback_service(secret: &str) -> BenefitService {
+        let mut registry = HandlerRegistry::new();
+        registry.register(Arc::new(callback_handler(secret)));
+        create_service_with_registry(registry)
+    }
+
+    /// 测试数字资产 Processing -> Success 状态流转
+    #[tokio::test]
+    async fn test_digital_asset_status_transition() {
+        let service = create_service().await;
+        let grant_no = next_grant_no();
+
+        let request = GrantBenefitRequest::new(
+            "user-asset-001",
+            BenefitType::DigitalAsset,
+            1,
+            json!({"asset_id": "NFT-ANNIVERSARY"}),
+        )
+        .with_grant_no(&grant_no);
+
+        let response = service.grant_benefit(request).await.unwrap();
+        assert!(response.is_processing(), "数字资产应异步发放");
+
+        let status = service.query_grant_status(&grant_no).await.unwrap();
+        assert_eq!(status, GrantStatus::Success, "确认后应转为 Success");
+
+        let revoke = service
+            .revoke_grant(&grant_no, RevokeReason::UserRequest)
+            .await
+            .unwrap();
+        assert!(revoke.success, "数字资产应支持撤销");
+    }
+
+    /// 测试外部回调同步成功，并校验签名被接收方认可
+    #[tokio::test]
+    async fn test_external_callback_grant_success() {
+        let (url, stub) = start_callback_stub().await;
+        let service = callback_service(DEFAULT_CALLBACK_SECRET);
+        let grant_no = next_grant_no();
+
+        let request = GrantBenefitRequest::new(
+            "user-callback-001",
+            BenefitType::ExternalCallback,
+            1,
+            json!({"callback_url": url, "params": {"sku": "VIP-30D"}}),
+        )
+        .with_grant_no(&grant_no);
+
+        let response = service.grant_benefit(request).await.unwrap();
+        assert!(response.is_success(), "回调桩同步返回 SUCCESS");
+        assert!(response.external_ref.unwrap().starts_with("CB-"));
+
+        let received = stub.received().await;
+        assert_eq!(received.len(), 1);
+        assert!(received[0].signature_valid, "签名应通过接收方校验");
+        assert_eq!(received[0].action.as_deref(), Some("grant"));
+    }
+
+    /// 测试未配置数据库时无法恢复回调目标，撤销失败而不是使用进程内状态
+    #[tokio::test]
+    async fn test_external_callback_without_pool_cannot_revoke() {
+        let (url, stub) = start_callback_stub().await;
+        let service = callback_service(DEFAULT_CALLBACK_SECRET);
+        let grant_no = next_grant_no();
+
+        let request = GrantBenefitRequest::new(
+            "user-callback-006",
+            BenefitType::ExternalCallback,
+            1,
+            json!({"callback_url": url}),
+        )
+        .with_grant_no(&grant_no);
+        assert!(service.grant_benefit(request).await.unwrap().is_success());
+
+        let revoke = service
+            .revoke_grant(&grant_no, RevokeReason::UserRequest)
+            .await
+            .unwrap();
+        assert!(!revoke.success, "回调目标只能从 benefit_grants 恢复");
+        assert_eq!(stub.received().await.len(), 1, "撤销不应发出回调");
+    }
+
+    /// 插入外部回调权益定义（幂等），发放记录的 benefit_id 外键依赖它
+    async fn ensure_callback_benefit(pool: &PgPool) -> i64 {
+        sqlx::query(
+            r#"
+            INSERT INTO benefits (id, code, name, benefit_type, status, config, enabled)
+            VALUES (99950, 'TEST_EXTERNAL_CALLBACK', 'ExternalCallback Test Benefit',
+                    'external_callback', 'active', '{}', TRUE)
+            ON CONFLICT (id) DO NOTHING
+            "#,
+        )
+        .execute(pool)
+        .await
+        .expect("插入测试权益失败");
+        99950
+    }
+
+    /// 连接测试数据库并构建带连接池的回调权益服务
+    async fn callback_service_with_pool(secret: &str) -> (BenefitService, PgPool) {
+        let database_url =
+            std::env::var("DATABASE_URL").expect("DATABASE_URL must be set for integration tests");
+        let pool = PgPool::connect(&database_url).await.unwrap();
+
+        let mut registry = HandlerRegistry::new();
+        registry.register(Arc::new(callback_handler(secret).with_pool(pool.clone())));
+        let service = BenefitService::new(Arc::new(registry)).with_pool(pool.clone());
+        (service, pool)
+    }
+
+    /// 数据库测试的流水号需跨运行唯一，避免命中上次运行留下的回调地址
+    fn unique_grant_no() -> String {
+        format!(
+            "{}-{}",
+            next_grant_no(),
+            chrono::Utc::now().timestamp_millis()
+        )
+    }
+
+    /// 测试回调目标从 benefit_grants.benefit_config 恢复，撤销通知外部系统
+    #[tokio::test]
+    #[ignore = "需要 PostgreSQL 数据库连接"]
+    async fn test_external_callback_revoke_from_db() {
+        let (url, stub) = start_callback_stub().await;
+        let (service, pool) = callback_service_with_pool(DEFAULT_CALLBACK_SECRET).await;
+        let benefit_id = ensure_callback_benefit(&pool).await;
+        let grant_no = unique_grant_no();
+
+        let request = GrantBenefitRequest::new(
+            "user-callback-001",
+            BenefitType::ExternalCallback,
+            benefit_id,
+            json!({"callback_url": url, "params": {"sku": "VIP-30D"}}),
+        )
+        .with_grant_no(&grant_no);
+        assert!(service.grant_benefit(request).await.unwrap().is_success());
+
+        let (config, payload): (Option<serde_json::Value>, Option<serde_json::Value>) =
+            sqlx::query_as(
+                "SELECT benefit_config, payload FROM benefit_grants WHERE grant_no = $1",
+            )
+            .bind(&grant_no)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(config.unwrap()["callback_url"], json!(url));
+        assert!(payload.is_none(), "payload 只保存 Handler 的发放结果");
+
+        let revoke = service
+            .revoke_grant(&grant_no, RevokeReason::UserRequest)
+            .await
+            .unwrap();
+        assert!(revoke.success);
+        assert_eq!(stub.get_grant(&grant_no).await.unwrap().status, "REVOKED");
+    }
+
+    /// 测试外部回调异步处理，通过状态查询完成
+    #[tokio::test]
+    #[ignore = "需要 PostgreSQL 数据库连接"]
+    async fn test_external_callback_async_status() {
+        let (url, stub) = start_callback_stub().await;
+        stub.set_async_mode(true);
+        let (service, pool) = callback_service_with_pool(DEFAULT_CALLBACK_SECRET).await;
+        let benefit_id = ensure_callback_benefit(&pool).await;
+        let grant_no = unique_grant_no();
+
+        let request = GrantBenefitRequest::new(
+            "user-callback-002",
+            BenefitType::ExternalCallback,
+            benefit_id,
+            json!({"callback_url": url}),
+        )
+        .with_grant_no(&grant_no);
+
+        let response = service.grant_benefit(request).await.unwrap();
+        assert!(response.is_processing());
+
+        let status = service.query_grant_status(&grant_no).await.unwrap();
+        assert_eq!(status, GrantStatus::Success);
+
+        let actions: Vec<_> = stub
+            .received()
+            .await
+            .into_iter()
+            .filter_map(|r| r.action)
+            .collect();
+        assert_eq!(actions, vec!["grant", "query"]);
+    }
+
+    /// 测试瞬时故障重试
+    #[tokio::test]
+    async fn test_external_callback_retries_transient_failures() {
+        let (url, stub) = start_callback_stub().await;
+        stub.set_fail_next(2);
+        let handler = callback_handler(DEFAULT_CALLBACK_SECRET);
+
+        let request = BenefitGrantRequest::new(
+            next_grant_no(),
+            "user-callback-003",
+            1,
+            json!({"callback_url": url, "max_retries": 3}),
+        );
+
+        let result = handler.grant(request).await.unwrap();
+        assert!(result.is_success(), "两次 503 后第三次应成功");
+        assert_eq!(stub.received().await.len(), 3);
+    }
+
+    /// 测试重试次数耗尽后发放失败
+    #[tokio::test]
+    async fn test_external_callback_gives_up_after_max_retries() {
+        let (url, stub) = start_callback_stub().await;
+        stub.set_fail_next(10);
+        let handler = callback_handler(DEFAULT_CALLBACK_SECRET);
+
+        let request = BenefitGrantRequest::new(
+            next_grant_no(),
+            "user-callback-004",
+            1,
+            json!({"callback_url": url, "max_retries": 1}),
+        );
+
+        let result = handler.grant(request).await.unwrap();
+        assert_eq!(result.status, GrantStatus::Failed);
+        assert_eq!(stub.received().await.len(), 2, "首次请求 + 1 次重试");
+    }
+
+    /// 测试签名密钥不一致时被拒绝且不重试
+    #[tokio::test]
+    async fn test_external_callback_rejected_signature_not_retried() {
+        let (url, stub) = start_callback_stub().await;
+        let handler = callback_handler("wrong-secret");
+
+        let request = BenefitGrantRequest::new(
+            next_grant_no(),
+            "user-callback-005",
+            1,
+            json!({"callback_url": url}),
+        );
+
+        let result = handler.grant(request).await.unwrap();
+        assert_eq!(result.status, GrantStatus::Failed);
+        assert!(result.message.unwrap().contains("401"));
+        assert_eq!(stub.received().await.len(), 1, "4xx 不应重试");
+    }
+}
+
+// ============================================================================
+// 外部服务故障处理测试（对接 mock-services 权益服务）
+// ============================================================================
+
+mod external_service_integration {
+    use super::*;
+    use badge_shared::circuit_breaker::CircuitBreakerConfig;
+    use std::time::Duration;
+
+    /// 启动权益服务桩，返回 base URL 和桩状态
+    async fn start_benefit_stub() -> (String, Arc<BenefitServiceState>) {
+        let state = Arc::new(BenefitServiceState::new());
+        let app = benefit_routes().with_state(state.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        (format!("http://{}", addr), state)
+    }
+
+    fn points_request(grant_no: &str) -> GrantBenefitRequest {
+        GrantBenefitRequest::new(
+            "user-remote-001",
+            BenefitType::Points,
+            1,
+            json!({"point_amount": 100}),
+        )
+        .with_grant_no(grant_no)
+    }
+
+    /// 测试瞬时故障后使用同一流水号重试，外部服务按流水号去重
+    #[tokio::test]
+    async fn test_transient_failure_then_retry_same_grant_no() {
+        let (base_url, state) = start_benefit_stub().await;
+        let mut registry = HandlerRegistry::new();
+        registry.register(Arc::new(PointsHandler::new(&base_url)));
+        let service = create_service_with_registry(registry);
+        let grant_no = next_grant_no();
+
+        state.set_simulate_failure(true).await;
+        let response = service
+            .grant_benefit(points_request(&grant_no))
+            .await
+            .unwrap();
+        assert_eq!(response.status, GrantStatus::Failed);
+        assert!(response.retryable, "503 应标记为可重试");
+
+        state.set_simulate_failure(false).await;
+        let first = service
+            .grant_benefit(points_request(&grant_no))
+            .await
+            .unwrap();
+        assert!(first.is_success(), "恢复后重试应成功");
+
+        // 绕过 BenefitService 的幂等检查重复投递，外部服务仍只记一笔
+        let handler = PointsHandler::new(&base_url);
+        let request = BenefitGrantRequest::new(
+            &grant_no,
+            "user-remote-001",
+            1,
+            json!({"point_amount": 100}),
+        );
+        let second = handler.grant(request).await.unwrap();
+        assert_eq!(second.external_ref, first.external_ref);
+        assert_eq!(state.get_user_benefits("user-remote-001").await.len(), 1);
+    }
+
+    /// 测试业务拒绝不可重试且不触发熔断
+    #[tokio::test]
+    async fn test_rejection_not_retryable_and_keeps_breaker_closed() {
+        let (base_url, state) = start_benefit_stub().await;
+        let handler = PointsHandler::new(base_url).with_circuit_breaker(
+            CircuitBreakerConfig::new("points-test").with_failure_threshold(2),
+        );
+        state.set_failing_types(vec!["points".to_string()]).await;
+
+        for _ in 0..3 {
+            let request = BenefitGrantRequest::new(
+                next_grant_no(),
+                "user-remote-002",
+                1,
+                json!({"point_amount": 10}),
+            );
+            let result = handler.grant(request).await.unwrap();
+            assert_eq!(result.status, GrantStatus::Failed);
+            assert!(!result.retryable, "4xx 业务拒绝不应重试");
+            assert!(
+                result.message.unwrap().contains("拒绝"),
+                "应透传外部服务拒绝原因"
+            );
+        }
+    }
+
+    /// 测试连续瞬时故障后熔断，恢复窗口内请求直接被拒绝
+    #[tokio::test]
+    async fn test_circuit_breaker_opens_after_transient_failures() {
+        let (base_url, state) = start_benefit_stub().await;
+        let handler = PointsHandler::new(base_url)
+            .with_timeout(Duration::from_secs(1))
+            .with_circuit_breaker(
+                CircuitBreakerConfig::new("points-test")
+                    .with_failure_threshold(2)
+                    .with_recovery_timeout(Duration::from_secs(60)),
+            );
+        state.set_simulate_failure(true).await;
+
+        for _ in 0..2 {
+            let request = BenefitGrantRequest::new(
+                next_grant_no(),
+                "user-remote-003",
+                1,
+                json!({"point_amount": 10}),
+            );
+            let result = handler.grant(request).await.unwrap();
+            assert!(result.retryable);
+        }
+
+        // 服务已恢复，但熔断器仍处于跳闸状态
+        state.set_simulate_failure(false).await;
+        let request = BenefitGrantRequest::new(
+            next_grant_no(),
+            "user-remote-003",
+            1,
+            json!({"point_amount": 10}),
+        );
+        let result = handler.grant(request).await.unwrap();
+        assert_eq!(result.status, GrantStatus::Failed);
+        assert!(result.retryable, "熔断拒绝应可稍后重试");
+        assert!(result.message.unwrap().contains("熔断"));
+        assert!(
+            state.get_user_benefits("user-remote-003").await.is_empty(),
+            "熔断期间请求不应到达外部服务"
+        );
+    }
+}
+
 // ============================================================================
 // Handler 直接测试
 // ============================================================================
@@ -761,14 +1212,15 @@ mod handler_direct_tests {
     /// 直接测试 CouponHandler
     #[tokio::test]
     async fn test_coupon_handler_direct() {
-        let handler = CouponHandler::default();
+        let handler = CouponHandler::new(start_external_stubs().await);
 
         assert_eq!(handler.benefit_type(), BenefitType::Coupon);
         assert!(handler.description().contains("Coupon"));
 
         // 测试发放
+        let grant_no = next_grant_no();
         let request = BenefitGrantRequest::new(
-            next_grant_no(),
+            &grant_no,
             "user-direct-001",
             1,
             json!({
@@ -783,15 +1235,15 @@ mod handler_direct_tests {
         let status = handler.query_status("any").await.unwrap();
         assert_eq!(status, GrantStatus::Success);
 
-        // 测试撤销
-        let revoke_result = handler.revoke("any").await.unwrap();
+        // 测试撤销（优惠券系统按流水号定位优惠券）
+        let revoke_result = handler.revoke(&grant_no).await.unwrap();
         assert!(revoke_result.success);
     }
 
     /// 直接测试 PointsHandler
     #[tokio::test]
     async fn test_points_handler_direct() {
-        let handler = PointsHandler::default();
+        let handler = PointsHandler::new(start_external_stubs().await);
 
         assert_eq!(handler.benefit_type(), BenefitType::Points);
         assert!(handler.description().contains("Points"));
@@ -874,10 +1326,11 @@ mod registry_tests {
         assert!(registry.contains(BenefitType::Coupon));
         assert!(registry.contains(BenefitType::Points));
         assert!(registry.contains(BenefitType::Physical));
-        assert!(!registry.contains(BenefitType::DigitalAsset));
+        assert!(registry.contains(BenefitType::DigitalAsset));
+        assert!(registry.contains(BenefitType::ExternalCallback));
 
         let types = registry.registered_types();
-        assert_eq!(types.len(), 3);
+        assert_eq!(types.len(), 7);
     }
 
     /// 测试自定义注册表
@@ -903,8 +1356,9 @@ mod registry_tests {
     /// 测试使用自定义注册表创建服务
     #[tokio::test]
     async fn test_service_with_custom_registry() {
+        let base_url = start_external_stubs().await;
         let mut registry = HandlerRegistry::new();
-        registry.register(Arc::new(CouponHandler::default()));
+        registry.register(Arc::new(CouponHandler::new(base_url)));
         // 只注册 CouponHandler
 
         let service = create_service_with_registry(registry);
@@ -954,7 +1408,7 @@ mod edge_cases {
     /// 测试空用户 ID
     #[tokio::test]
     async fn test_empty_user_id() {
-        let service = create_service();
+        let service = create_service().await;
 
         let request = GrantBenefitRequest::new(
             "", // 空用户 ID
@@ -974,7 +1428,7 @@ mod edge_cases {
     /// 测试大量发放数量
     #[tokio::test]
     async fn test_large_quantity() {
-        let service = create_service();
+        let service = create_service().await;
 
         let request = GrantBenefitRequest::new(
             "user-large-qty",
@@ -994,7 +1448,7 @@ mod edge_cases {
     /// 测试大额积分
     #[tokio::test]
     async fn test_large_points_amount() {
-        let service = create_service();
+        let service = create_service().await;
 
         let request = GrantBenefitRequest::new(
             "user-large-points",
@@ -1013,7 +1467,7 @@ mod edge_cases {
     /// 测试复杂 metadata
     #[tokio::test]
     async fn test_complex_metadata() {
-        let service = create_service();
+        let service = create_service().await;
 
         let request = GrantBenefitRequest::new(
             "user-complex-meta",
@@ -1044,7 +1498,7 @@ mod edge_cases {
     /// 测试撤销不存在的记录
     #[tokio::test]
     async fn test_revoke_non_existent() {
-        let service = create_service();
+        let service = create_service().await;
 
         let result = service
             .revoke_grant("non-existent-grant", RevokeReason::UserRequest)
@@ -40,6 +40,7 @@ fn create_dependency_row(
         auto_trigger,
         priority: 0,
         dependency_group_id: group_id.to_string(),
+        revoke_policy: "keep".to_string(),
         enabled: true,
         created_at: Utc::now(),
         updated_at: Utc::now(),
@@ -0,0 +1,137 @@
+//! EmailChannel 集成测试
+//!
+//! 启动进程内 SMTP 测试服务，验证邮件渠道经由真实 SMTP 会话投递邮件：
+//! 收件人解析、multipart 正文结构以及 Message-ID 回传。无需外部依赖，默认运行。
+
+use std::sync::Arc;
+
+use badge_management::notification::channels::EMAIL_DATA_KEY;
+use badge_management::notification::{
+    EmailChannel, Notification, NotificationChannel, NotificationService, SendStatus,
+};
+use badge_shared::config::{SmtpConfig, SmtpTlsMode};
+use badge_shared::events::{NotificationChannel as ChannelType, NotificationType};
+use badge_shared::notification_contacts::{ContactStore, InMemoryContactStore, UserContacts};
+use badge_shared::test_utils::SmtpSink;
+
+fn sink_config(sink: &SmtpSink) -> SmtpConfig {
+    SmtpConfig {
+        enabled: true,
+        host: "127.0.0.1".into(),
+        port: sink.port(),
+        tls: SmtpTlsMode::None,
+        from_address: "badges@example.com".into(),
+        from_name: "Badge System".into(),
+        ..Default::default()
+    }
+}
+
+fn badge_notification(email: &str) -> Notification {
+    Notification::new(
+        "user-001",
+        NotificationType::BadgeGranted,
+        "Badge granted",
+        "You earned the <First Order> badge",
+    )
+    .with_data(EMAIL_DATA_KEY, serde_json::json!(email))
+}
+
+#[tokio::test]
+async fn test_email_delivered_through_smtp() {
+    let sink = SmtpSink::start().await.unwrap();
+    let channel = EmailChannel::from_smtp_config(&sink_config(&sink)).unwrap();
+
+    let result = channel
+        .send(&badge_notification("alice@example.com"))
+        .await
+        .unwrap();
+
+    assert_eq!(result.status, SendStatus::Success);
+    let message_id = result.external_message_id.unwrap();
+    assert!(message_id.starts_with('<') && message_id.ends_with("@example.com>"));
+
+    let messages = sink.messages().await;
+    assert_eq!(messages.len(), 1);
+    let mail = &messages[0];
+    assert_eq!(mail.from, "badges@example.com");
+    assert_eq!(mail.recipients, vec!["alice@example.com".to_string()]);
+    assert_eq!(mail.header("Message-ID"), Some(message_id.as_str()));
+    assert_eq!(
+        mail.header("From"),
+        Some("\"Badge System\" <badges@example.com>")
+    );
+    assert_eq!(mail.header("Subject"), Some("Badge granted"));
+    assert!(mail.data.contains("multipart/alternative"));
+    assert!(mail.data.contains("text/plain"));
+    assert!(mail.data.contains("text/html"));
+    assert!(mail.data.contains("&lt;First Order&gt;"));
+}
+
+#[tokio::test]
+async fn test_each_email_gets_distinct_message_id() {
+    let sink = SmtpSink::start().await.unwrap();
+    let channel = EmailChannel::from_smtp_config(&sink_config(&sink)).unwrap();
+
+    let first = channel
+        .send(&badge_notification("alice@example.com"))
+        .await
+        .unwrap();
+    let second = channel
+        .send(&badge_notification("bob@example.com"))
+        .await
+        .unwrap();
+
+    assert_ne!(first.external_message_id, second.external_message_id);
+
+    let messages = sink.messages().await;
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[1].recipients, vec!["bob@example.com".to_string()]);
+}
+
+#[tokio::test]
+async fn test_email_without_recipient_not_sent() {
+    let sink = SmtpSink::start().await.unwrap();
+    let channel = EmailChannel::from_smtp_config(&sink_config(&sink)).unwrap();
+
+    let notification = Notification::new(
+        "user-001",
+        NotificationType::BadgeGranted,
+        "Badge granted",
+        "body",
+    );
+    let result = channel.send(&notification).await.unwrap();
+
+    assert_eq!(result.status, SendStatus::Skipped);
+    assert!(sink.messages().await.is_empty());
+}
+
+#[tokio::test]
+async fn test_service_resolves_recipient_from_contacts() {
+    let sink = SmtpSink::start().await.unwrap();
+    let contacts = Arc::new(InMemoryContactStore::new());
+    contacts
+        .upsert(&UserContacts::new("user-001").with_email("carol@example.com"))
+        .await
+        .unwrap();
+    let service = NotificationService::with_defaults()
+        .with_email_channel(EmailChannel::from_smtp_config(&sink_config(&sink)).unwrap())
+        .with_contact_store(contacts);
+
+    // 与业务方构建的通知一致，只携带用户 ID
+    let notification = Notification::new(
+        "user-001",
+        NotificationType::BadgeGranted,
+        "Badge granted",
+        "body",
+    )
+    .with_channels(vec![ChannelType::Email]);
+    let result = service.send(notification).await.unwrap();
+
+    assert_eq!(result.channel_results[0].status, SendStatus::Success);
+    let messages = sink.messages().await;
+    assert_eq!(messages.len(), 1);
+    assert_eq!(
+        messages[0].recipients,
+        vec!["carol@example.com".to_string()]
+    );
+}
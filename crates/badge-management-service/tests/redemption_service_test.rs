@@ -13,10 +13,13 @@
 
 use badge_management::error::BadgeError;
 use badge_management::repository::RedemptionRepository;
-use badge_management::service::dto::RedeemBadgeRequest;
+use badge_management::service::dto::{CancelRedemptionRequest, RedeemBadgeRequest};
 use badge_management::service::RedemptionService;
 use badge_shared::cache::Cache;
 use badge_shared::config::RedisConfig;
+use badge_shared::leaderboard::{
+    BadgeChange, LeaderboardKind, LeaderboardStore, LeaderboardWindow,
+};
 use chrono::{Duration, Utc};
 use serde_json::json;
 use sqlx::PgPool;
@@ -703,3 +706,145 @@ async fn test_get_user_redemptions() {
 
     cleanup_test_data(&pool, &[user_id], &[rule_id], &[benefit_id], &[badge_id]).await;
 }
+
+/// 兑换扣减同步更新排行榜：徽章归零后用户从徽章数量总榜移除
+#[tokio::test]
+#[ignore = "需要 PostgreSQL 和 Redis"]
+async fn test_redeem_updates_leaderboard() {
+    let pool = PgPool::connect(&database_url()).await.unwrap();
+    let badge_id = 92012;
+    let benefit_id = 92012;
+    let rule_id = 92012;
+    let user_id = "integ_redeem_leaderboard_001";
+
+    cleanup_test_data(&pool, &[user_id], &[rule_id], &[benefit_id], &[badge_id]).await;
+
+    seed_test_badge(&pool, badge_id, "Leaderboard Badge").await;
+    seed_test_benefit(&pool, benefit_id, "BEN_92012", "排行榜权益", Some(100), Some(100), true).await;
+    seed_redemption_rule(
+        &pool, rule_id, "排行榜兑换规则", benefit_id,
+        json!([{"badgeId": badge_id, "quantity": 2}]),
+        true, None, None,
+    ).await;
+    seed_user_badge(&pool, user_id, badge_id, 2).await;
+
+    let cache = Arc::new(
+        Cache::new(&RedisConfig {
+            url: redis_url(),
+            pool_size: 2,
+        })
+        .expect("Redis connection failed"),
+    );
+    let leaderboard = Arc::new(LeaderboardStore::new(cache, pool.clone()));
+    leaderboard
+        .record(&BadgeChange::granted(user_id, badge_id, 2, 2))
+        .await
+        .unwrap();
+    let before = leaderboard
+        .rank(LeaderboardKind::Badges, LeaderboardWindow::AllTime, user_id)
+        .await
+        .unwrap();
+    assert!(before.is_some(), "发放后用户应在总榜上");
+
+    let svc = setup_redemption_service(&pool).await;
+    svc.set_leaderboard(leaderboard.clone()).await;
+    let resp = svc
+        .redeem_badge(RedeemBadgeRequest::new(
+            user_id,
+            rule_id,
+            "idem-leaderboard-92012",
+        ))
+        .await;
+    assert!(resp.is_ok(), "兑换应成功: {:?}", resp.err());
+
+    let after = leaderboard
+        .rank(LeaderboardKind::Badges, LeaderboardWindow::AllTime, user_id)
+        .await
+        .unwrap();
+    assert!(after.is_none(), "徽章兑换归零后应从总榜移除");
+
+    cleanup_test_data(&pool, &[user_id], &[rule_id], &[benefit_id], &[badge_id]).await;
+}
+
+/// 取消兑换退回徽章同步更新排行榜：兑换归零移出总榜，取消后恢复上榜且分数复原
+#[tokio::test]
+#[ignore = "需要 PostgreSQL 和 Redis"]
+async fn test_cancel_redemption_restores_leaderboard() {
+    let pool = PgPool::connect(&database_url()).await.unwrap();
+    let badge_id = 92013;
+    let benefit_id = 92013;
+    let rule_id = 92013;
+    let user_id = "integ_cancel_leaderboard_001";
+
+    cleanup_test_data(&pool, &[user_id], &[rule_id], &[benefit_id], &[badge_id]).await;
+
+    seed_test_badge(&pool, badge_id, "Cancel Leaderboard Badge").await;
+    seed_test_benefit(&pool, benefit_id, "BEN_92013", "取消排行榜权益", Some(100), Some(100), true).await;
+    seed_redemption_rule(
+        &pool, rule_id, "取消排行榜兑换规则", benefit_id,
+        json!([{"badgeId": badge_id, "quantity": 2}]),
+        true, None, None,
+    ).await;
+    seed_user_badge(&pool, user_id, badge_id, 2).await;
+
+    let cache = Arc::new(
+        Cache::new(&RedisConfig {
+            url: redis_url(),
+            pool_size: 2,
+        })
+        .expect("Redis connection failed"),
+    );
+    let leaderboard = Arc::new(LeaderboardStore::new(cache, pool.clone()));
+    leaderboard
+        .record(&BadgeChange::granted(user_id, badge_id, 2, 2))
+        .await
+        .unwrap();
+    let score_before = leaderboard
+        .rank(LeaderboardKind::Score, LeaderboardWindow::AllTime, user_id)
+        .await
+        .unwrap()
+        .map(|e| e.score);
+
+    let svc = setup_redemption_service(&pool).await;
+    svc.set_leaderboard(leaderboard.clone()).await;
+    let resp = svc
+        .redeem_badge(RedeemBadgeRequest::new(
+            user_id,
+            rule_id,
+            "idem-cancel-leaderboard-92013",
+        ))
+        .await
+        .expect("兑换应成功");
+    assert!(
+        leaderboard
+            .rank(LeaderboardKind::Badges, LeaderboardWindow::AllTime, user_id)
+            .await
+            .unwrap()
+            .is_none(),
+        "徽章兑换归零后应从总榜移除"
+    );
+
+    let cancelled = svc
+        .cancel_redemption(CancelRedemptionRequest::by_admin(
+            &resp.order_no,
+            "admin",
+            None,
+        ))
+        .await
+        .expect("取消应成功");
+    assert_eq!(cancelled.refunded_badges.len(), 1);
+
+    let badges_after = leaderboard
+        .rank(LeaderboardKind::Badges, LeaderboardWindow::AllTime, user_id)
+        .await
+        .unwrap();
+    assert_eq!(badges_after.map(|e| e.score), Some(1.0), "退回后应重新计入持有种类");
+    let score_after = leaderboard
+        .rank(LeaderboardKind::Score, LeaderboardWindow::AllTime, user_id)
+        .await
+        .unwrap()
+        .map(|e| e.score);
+    assert_eq!(score_after, score_before, "退回后徽章分数应复原");
+
+    cleanup_test_data(&pool, &[user_id], &[rule_id], &[benefit_id], &[badge_id]).await;
+}
@@ -0,0 +1,341 @@
+//! 注册周年事件调度器
+//!
+//! 定期扫描 `user_registrations`，为当天是注册周年纪念日的用户生成
+//! `ANNIVERSARY` 事件并投递到 `badge.lifecycle.events`，由生命周期处理器按规则发放徽章。
+//!
+//! 认领与投递分两步：先通过 `last_anniversary_year` 原子认领待处理用户（`FOR UPDATE SKIP LOCKED`），
+//! 再投递认领未投递的记录，成功后写入 `anniversary_published_year`，进程中断或 Kafka 不可用时
+//! 下一轮继续投递。扫描从 `scheduler_checkpoints` 记录的最后完成日期续扫，停机期间的周年不会遗漏。
+//! 事件 ID 由用户和年份确定，下游幂等校验可拦截极端情况下的重复投递。
+
+use std::time::Duration;
+
+use badge_shared::events::{EventPayload, EventType};
+use badge_shared::kafka::{KafkaProducer, topics};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use sqlx::PgPool;
+use tokio::sync::watch;
+use tracing::{error, info, warn};
+
+/// 周年事件来源标识
+const ANNIVERSARY_SOURCE: &str = "anniversary-scheduler";
+
+/// 调度器检查点名称
+const CHECKPOINT_NAME: &str = "anniversary";
+
+/// 停机后最多回溯扫描的天数
+const MAX_CATCH_UP_DAYS: i64 = 366;
+
+/// 已认领待投递的注册记录
+#[derive(sqlx::FromRow)]
+struct DueRegistration {
+    user_id: String,
+    registered_at: DateTime<Utc>,
+    /// 认领的周年年份
+    year: i32,
+}
+
+/// 注册周年事件调度器
+pub struct AnniversaryScheduler {
+    pool: PgPool,
+    producer: KafkaProducer,
+    /// 扫描间隔（建议 1 小时，跨天后的首次扫描即生成当天的周年事件）
+    poll_interval: Duration,
+    /// 每批认领的最大用户数
+    batch_size: i64,
+}
+
+impl AnniversaryScheduler {
+    pub fn new(
+        pool: PgPool,
+        producer: KafkaProducer,
+        poll_interval_secs: u64,
+        batch_size: i64,
+    ) -> Self {
+        Self {
+            pool,
+            producer,
+            poll_interval: Duration::from_secs(poll_interval_secs),
+            batch_size,
+        }
+    }
+
+    /// 使用默认配置创建调度器
+    pub fn with_defaults(pool: PgPool, producer: KafkaProducer) -> Self {
+        Self::new(pool, producer, 3600, 500)
+    }
+
+    /// 主循环：按间隔扫描直到收到 shutdown 信号
+    pub async fn run(self, mut shutdown: watch::Receiver<bool>) {
+        info!(
+            poll_interval = ?self.poll_interval,
+            batch_size = self.batch_size,
+            "周年事件调度器已启动"
+        );
+
+        loop {
+            match self.run_once(Utc::now().date_naive()).await {
+                Ok(0) => {}
+                Ok(count) => info!(count, "已生成注册周年事件"),
+                Err(e) => error!(error = %e, "生成注册周年事件出错"),
+            }
+
+            tokio::select! {
+                _ = tokio::time::sleep(self.poll_interval) => {}
+                _ = shutdown.changed() => {
+                    info!("周年事件调度器已停止");
+                    return;
+                }
+            }
+        }
+    }
+
+    /// 续扫到指定日期并投递全部待投递的周年事件，返回投递成功的事件数量
+    ///
+    /// 从检查点次日扫描到当天（当天总会重新扫描），每个日期认领完成后推进检查点；
+    /// 认领与投递分离，投递中断的记录留待下一轮重试。
+    pub async fn run_once(&self, today: NaiveDate) -> Result<usize, sqlx::Error> {
+        let last_completed = self.last_completed_date().await?;
+        for date in scan_dates(last_completed, today) {
+            loop {
+                let claimed = self.claim_due(date).await?;
+                if claimed < self.batch_size as u64 {
+                    break;
+                }
+            }
+            self.complete_date(date).await?;
+        }
+
+        let mut total = 0;
+        loop {
+            let (published, done) = self.publish_pending().await?;
+            total += published;
+            if done {
+                return Ok(total);
+            }
+        }
+    }
+
+    /// 读取已完整扫描的最后日期
+    async fn last_completed_date(&self) -> Result<Option<NaiveDate>, sqlx::Error> {
+        sqlx::query_scalar("SELECT last_completed_date FROM scheduler_checkpoints WHERE name = $1")
+            .bind(CHECKPOINT_NAME)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    /// 推进检查点，多实例并发时只前进不后退
+    async fn complete_date(&self, date: NaiveDate) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO scheduler_checkpoints (name, last_completed_date)
+            VALUES ($1, $2)
+            ON CONFLICT (name) DO UPDATE
+            SET last_completed_date = GREATEST(scheduler_checkpoints.last_completed_date, EXCLUDED.last_completed_date),
+                updated_at = NOW()
+            "#,
+        )
+        .bind(CHECKPOINT_NAME)
+        .bind(date)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// 认领指定日期周年且该年度尚未认领的用户，返回认领数量
+    async fn claim_due(&self, date: NaiveDate) -> Result<u64, sqlx::Error> {
+        let year = date.year();
+        let days = anniversary_days(date);
+
+        let result = sqlx::query(
+            r#"
+            UPDATE user_registrations
+            SET last_anniversary_year = $1
+            WHERE user_id IN (
+                SELECT user_id
+                FROM user_registrations
+                WHERE EXTRACT(MONTH FROM registered_at AT TIME ZONE 'UTC')::int = $2
+                  AND EXTRACT(DAY FROM registered_at AT TIME ZONE 'UTC')::int = ANY($3)
+                  AND EXTRACT(YEAR FROM registered_at AT TIME ZONE 'UTC')::int < $1
+                  AND (last_anniversary_year IS NULL OR last_anniversary_year < $1)
+                ORDER BY user_id
+                FOR UPDATE SKIP LOCKED
+                LIMIT $4
+            )
+            "#,
+        )
+        .bind(year)
+        .bind(date.month() as i32)
+        .bind(&days)
+        .bind(self.batch_size)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// 投递一批已认领未投递的周年事件
+    ///
+    /// 在事务内锁定记录，投递成功后写入已投递年份；投递失败通常是 Kafka 不可用，
+    /// 提交已成功的部分并结束本轮。返回 (投递数量, 本轮投递是否结束)。
+    async fn publish_pending(&self) -> Result<(usize, bool), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let batch = sqlx::query_as::<_, DueRegistration>(
+            r#"
+            SELECT user_id, registered_at, last_anniversary_year AS year
+            FROM user_registrations
+            WHERE last_anniversary_year IS NOT NULL
+              AND (anniversary_published_year IS NULL OR anniversary_published_year < last_anniversary_year)
+            ORDER BY user_id
+            FOR UPDATE SKIP LOCKED
+            LIMIT $1
+            "#,
+        )
+        .bind(self.batch_size)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut published = 0;
+        for registration in &batch {
+            if !self.publish(registration).await {
+                tx.commit().await?;
+                return Ok((published, true));
+            }
+            sqlx::query(
+                "UPDATE user_registrations SET anniversary_published_year = $2 WHERE user_id = $1",
+            )
+            .bind(&registration.user_id)
+            .bind(registration.year)
+            .execute(&mut *tx)
+            .await?;
+            published += 1;
+        }
+        tx.commit().await?;
+
+        Ok((published, (batch.len() as i64) < self.batch_size))
+    }
+
+    async fn publish(&self, registration: &DueRegistration) -> bool {
+        let event = build_anniversary_event(
+            &registration.user_id,
+            registration.registered_at,
+            registration.year,
+        );
+
+        match self
+            .producer
+            .send_json(topics::LIFECYCLE_EVENTS, &event.user_id, &event)
+            .await
+        {
+            Ok(_) => true,
+            Err(e) => {
+                warn!(
+                    user_id = %registration.user_id,
+                    error = %e,
+                    "投递注册周年事件失败"
+                );
+                false
+            }
+        }
+    }
+}
+
+/// 本轮需要扫描的日期：检查点次日到当天，当天总会重新扫描
+///
+/// 无检查点时只扫描当天；停机过久时最多回溯 `MAX_CATCH_UP_DAYS` 天，已覆盖一年中的每个注册日。
+fn scan_dates(last_completed: Option<NaiveDate>, today: NaiveDate) -> Vec<NaiveDate> {
+    let earliest = today - chrono::Duration::days(MAX_CATCH_UP_DAYS);
+    let start = match last_completed {
+        Some(last) if last < today => (last + chrono::Duration::days(1)).max(earliest),
+        _ => today,
+    };
+    start.iter_days().take_while(|d| *d <= today).collect()
+}
+
+/// 当天需要匹配的注册日（月份取当天月份）
+///
+/// 2 月 29 日注册的用户在平年的 2 月 28 日过周年。
+fn anniversary_days(today: NaiveDate) -> Vec<i32> {
+    let day = today.day() as i32;
+    let is_leap = NaiveDate::from_ymd_opt(today.year(), 2, 29).is_some();
+    if today.month() == 2 && day == 28 && !is_leap {
+        vec![28, 29]
+    } else {
+        vec![day]
+    }
+}
+
+/// 构造周年事件
+///
+/// 事件 ID 由用户和年份确定，同一用户同一年份的周年事件始终相同。
+fn build_anniversary_event(user_id: &str, registered_at: DateTime<Utc>, year: i32) -> EventPayload {
+    let years = year - registered_at.year();
+    let mut event = EventPayload::new(
+        EventType::Anniversary,
+        user_id,
+        serde_json::json!({
+            "years": years,
+            "registered_at": registered_at.to_rfc3339(),
+        }),
+        ANNIVERSARY_SOURCE,
+    );
+    event.event_id = format!("anniversary:{}:{}", user_id, year);
+    event
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_anniversary_days() {
+        assert_eq!(anniversary_days(date(2025, 6, 15)), vec![15]);
+        // 平年 2 月 28 日同时覆盖 2 月 29 日注册的用户
+        assert_eq!(anniversary_days(date(2025, 2, 28)), vec![28, 29]);
+        assert_eq!(anniversary_days(date(2024, 2, 28)), vec![28]);
+        assert_eq!(anniversary_days(date(2024, 2, 29)), vec![29]);
+    }
+
+    #[test]
+    fn test_build_anniversary_event() {
+        let registered_at = Utc.with_ymd_and_hms(2022, 3, 10, 8, 30, 0).unwrap();
+        let event = build_anniversary_event("user-001", registered_at, 2025);
+
+        assert_eq!(event.event_type, EventType::Anniversary);
+        assert_eq!(event.event_id, "anniversary:user-001:2025");
+        assert_eq!(event.source, ANNIVERSARY_SOURCE);
+        assert_eq!(event.data["years"], 3);
+
+        // 同一用户同一年份重复生成时事件 ID 不变
+        let again = build_anniversary_event("user-001", registered_at, 2025);
+        assert_eq!(again.event_id, event.event_id);
+    }
+
+    #[test]
+    fn test_scan_dates() {
+        let today = date(2025, 3, 10);
+
+        // 无检查点或当天已完成时只扫描当天
+        assert_eq!(scan_dates(None, today), vec![today]);
+        assert_eq!(scan_dates(Some(today), today), vec![today]);
+
+        // 停机期间的日期全部补扫，跨年同样适用
+        assert_eq!(
+            scan_dates(Some(date(2025, 3, 7)), today),
+            vec![date(2025, 3, 8), date(2025, 3, 9), today]
+        );
+        assert_eq!(
+            scan_dates(Some(date(2024, 12, 30)), date(2025, 1, 1)),
+            vec![date(2024, 12, 31), date(2025, 1, 1)]
+        );
+
+        // 回溯天数有上限
+        let dates = scan_dates(Some(date(2020, 1, 1)), today);
+        assert_eq!(dates.len() as i64, MAX_CATCH_UP_DAYS + 1);
+        assert_eq!(dates.last(), Some(&today));
+    }
+}
@@ -1,51 +1,66 @@
 //! Kafka 消费者与事件分发
 //!
-//! 将 Kafka 消息解码为事件信封，校验事件类型并路由到 EngagementEventProcessor，
+//! 将 Kafka 消息解码为事件信封，按来源 topic 路由到 EngagementEventProcessor
+//! （行为事件）或 LifecycleEventProcessor（身份与季节事件），
 //! 处理失败的消息发送到死信队列，处理成功的结果生成通知事件。
 //! 同时监听规则刷新 topic，在后台刷新任务之外支持管理后台即时触发规则重载。
 
 use std::sync::Arc;
 
 use badge_shared::config::AppConfig;
+use badge_shared::dlq::{self, DEFAULT_DLQ_MAX_RETRIES, DeadLetterMessage};
+use badge_shared::error::BadgeError;
 use badge_shared::events::{
     EventPayload, EventProcessor, EventType, NotificationChannel, NotificationEvent,
     NotificationType,
 };
 use badge_shared::kafka::{KafkaConsumer, KafkaProducer, topics};
-use badge_shared::rules::{RuleLoader, RuleReloadEvent};
+use badge_shared::rules::{RuleLoader, RuleReloadListener};
 use chrono::Utc;
 use tokio::sync::watch;
 use tracing::{error, info, warn};
 use uuid::Uuid;
 
 use crate::error::EngagementError;
+use crate::lifecycle::LifecycleEventProcessor;
 use crate::processor::EngagementEventProcessor;
 
+/// 写入死信消息的来源服务标识
+const SOURCE_SERVICE: &str = "event-engagement-service";
+
 /// 行为事件消费者
 ///
-/// 组合 KafkaConsumer（消息拉取）、EngagementEventProcessor（业务处理）
-/// 和 KafkaProducer（通知/DLQ 投递）三个组件，形成完整的消费管道。
+/// 组合 KafkaConsumer（消息拉取）、EngagementEventProcessor 与
+/// LifecycleEventProcessor（业务处理）和 KafkaProducer（通知/DLQ 投递），
+/// 形成完整的消费管道。
 /// 同时监听规则刷新 topic，支持管理后台即时触发规则重载。
 pub struct EngagementConsumer {
     consumer: KafkaConsumer,
     processor: EngagementEventProcessor,
+    lifecycle_processor: LifecycleEventProcessor,
     producer: KafkaProducer,
     rule_loader: Arc<RuleLoader>,
+    reload_listener: RuleReloadListener,
 }
 
 impl EngagementConsumer {
     pub fn new(
         config: &AppConfig,
         processor: EngagementEventProcessor,
+        lifecycle_processor: LifecycleEventProcessor,
         producer: KafkaProducer,
         rule_loader: Arc<RuleLoader>,
     ) -> Result<Self, EngagementError> {
         let consumer = KafkaConsumer::new(&config.kafka, None)?;
+        // 规则刷新需要通知到每个实例，使用独立的广播消费组，不能与事件消费组共用
+        let reload_listener = RuleReloadListener::new(&config.kafka, &config.service_name)?;
         Ok(Self {
             consumer,
             processor,
+            lifecycle_processor,
             producer,
             rule_loader,
+            reload_listener,
         })
     }
 
@@ -53,40 +68,42 @@ impl EngagementConsumer {
     ///
     /// 将 processor 和 producer 移入闭包，通过 KafkaConsumer::start
     /// 驱动消费循环。单独抽取 handle_message 方法方便单元测试。
-    /// 同时订阅规则刷新 topic，在消费循环中根据消息来源分发处理。
+    /// 同时启动规则刷新监听任务，随同一个 shutdown 信号退出。
     pub async fn run(self, shutdown: watch::Receiver<bool>) -> Result<(), EngagementError> {
         self.consumer
-            .subscribe(&[topics::ENGAGEMENT_EVENTS, topics::RULE_RELOAD])?;
+            .subscribe(&[topics::ENGAGEMENT_EVENTS, topics::LIFECYCLE_EVENTS])?;
+
+        self.rule_loader
+            .clone()
+            .start_reload_listener(self.reload_listener, shutdown.clone());
 
         info!(
             engagement_topic = topics::ENGAGEMENT_EVENTS,
+            lifecycle_topic = topics::LIFECYCLE_EVENTS,
             rule_reload_topic = topics::RULE_RELOAD,
             "行为事件消费者已启动"
         );
 
         let processor = self.processor;
+        let lifecycle_processor = self.lifecycle_processor;
         let producer = self.producer;
-        let rule_loader = self.rule_loader;
 
         self.consumer
             .start(shutdown, |msg| {
-                let processor = &processor;
+                let processor: &dyn EventProcessor = if msg.topic == topics::LIFECYCLE_EVENTS {
+                    &lifecycle_processor
+                } else {
+                    &processor
+                };
                 let producer = &producer;
-                let rule_loader = &rule_loader;
                 async move {
-                    // 根据 topic 分发：规则刷新 topic 走单独逻辑
-                    if msg.topic == topics::RULE_RELOAD {
-                        handle_rule_reload(rule_loader, &msg).await;
-                        return Ok(());
-                    }
-
                     if let Err(e) = handle_message(processor, producer, &msg).await {
                         error!(
                             error = %e,
                             topic = %msg.topic,
                             partition = msg.partition,
                             offset = msg.offset,
-                            "处理行为事件失败"
+                            "处理事件失败"
                         );
                     }
                     Ok(())
@@ -99,44 +116,12 @@ impl EngagementConsumer {
     }
 }
 
-/// 处理规则刷新消息
-///
-/// 解析 RuleReloadEvent 并判断是否需要刷新本服务的规则缓存。
-/// service_group 为空表示全部服务刷新，否则只刷新匹配的服务组。
-async fn handle_rule_reload(
-    rule_loader: &Arc<RuleLoader>,
-    msg: &badge_shared::kafka::ConsumerMessage,
-) {
-    let event: RuleReloadEvent = match serde_json::from_slice(&msg.payload) {
-        Ok(e) => e,
-        Err(e) => {
-            warn!(error = %e, "规则刷新事件反序列化失败，忽略");
-            return;
-        }
-    };
-
-    // 仅当 service_group 匹配或为空（全部刷新）时才执行
-    if event.service_group.is_none() || event.service_group.as_deref() == Some("engagement") {
-        info!(
-            trigger_source = %event.trigger_source,
-            triggered_at = %event.triggered_at,
-            "收到 Kafka 规则刷新事件"
-        );
-
-        if let Err(e) = rule_loader.reload_now().await {
-            warn!(error = %e, "Kafka 触发规则刷新失败");
-        } else {
-            info!("Kafka 触发规则刷新成功");
-        }
-    }
-}
-
 /// 处理单条 Kafka 消息的完整流程
 ///
 /// 拆分为独立函数而非方法，便于在测试中直接调用而无需构造完整的 Consumer。
 /// 流程：反序列化 -> 事件类型校验 -> 幂等检查 -> 业务处理 -> 标记已处理 -> 发送通知
 pub async fn handle_message(
-    processor: &EngagementEventProcessor,
+    processor: &dyn EventProcessor,
     producer: &KafkaProducer,
     msg: &badge_shared::kafka::ConsumerMessage,
 ) -> Result<(), EngagementError> {
@@ -150,15 +135,16 @@ pub async fn handle_message(
         event_id = %event.event_id,
         event_type = %event.event_type,
         user_id = %event.user_id,
-        "收到行为事件"
+        "收到事件"
     );
 
-    // 2. 校验是否为行为类事件
+    // 2. 校验事件类型与来源 topic 对应的处理器是否匹配
     if !is_supported_event_type(&event.event_type, processor) {
         warn!(
             event_type = %event.event_type,
             event_id = %event.event_id,
-            "收到非行为类事件，忽略"
+            topic = %msg.topic,
+            "收到不属于该 topic 的事件，忽略"
         );
         return Err(EngagementError::UnsupportedEventType {
             event_type: event.event_type.to_string(),
@@ -175,13 +161,27 @@ pub async fn handle_message(
     // 4. 执行业务处理
     let result = match processor.process(&event).await {
         Ok(r) => r,
+        Err(BadgeError::InvalidPayload(reason)) => {
+            // 自动重放同样无法通过校验，以不可重试的死信保留，修正 Schema 或数据后人工重放
+            warn!(
+                event_id = %event.event_id,
+                reason = %reason,
+                "事件负载不符合 Schema，发送到死信队列等待人工处理"
+            );
+            let error = BadgeError::InvalidPayload(reason.clone()).to_string();
+            send_to_dlq(producer, &event, &error, 0).await;
+            return Err(EngagementError::InvalidPayload {
+                event_id: event.event_id,
+                reason,
+            });
+        }
         Err(e) => {
             error!(
                 event_id = %event.event_id,
                 error = %e,
-                "行为事件处理失败，发送到死信队列"
+                "事件处理失败，发送到死信队列"
             );
-            send_to_dlq(producer, &event).await;
+            send_to_dlq(producer, &event, &e.to_string(), DEFAULT_DLQ_MAX_RETRIES).await;
             return Err(EngagementError::Shared(e));
         }
     };
@@ -206,23 +206,32 @@ pub async fn handle_message(
         matched_rules = result.matched_rules.len(),
         granted_badges = result.granted_badges.len(),
         processing_time_ms = result.processing_time_ms,
-        "行为事件处理完成"
+        "事件处理完成"
     );
 
     Ok(())
 }
 
 /// 校验事件类型是否在处理器的支持列表中
-fn is_supported_event_type(event_type: &EventType, processor: &EngagementEventProcessor) -> bool {
-    processor.supported_event_types().contains(event_type)
+fn is_supported_event_type(event_type: &EventType, processor: &dyn EventProcessor) -> bool {
+    processor.supports(event_type)
 }
 
-/// 将处理失败的事件发送到死信队列，供人工排查或延迟重试
-async fn send_to_dlq(producer: &KafkaProducer, event: &EventPayload) {
-    if let Err(e) = producer
-        .send_json(topics::DEAD_LETTER_QUEUE, &event.event_id, event)
-        .await
-    {
+/// 将处理失败的事件封装为死信消息发送到死信队列，由管理后台持久化后延迟重放
+///
+/// `max_retries` 为 0 时死信直接标记为耗尽，不会自动重放，只能由管理后台人工重放
+async fn send_to_dlq(
+    producer: &KafkaProducer,
+    event: &EventPayload,
+    error: &str,
+    max_retries: u32,
+) {
+    let result = match DeadLetterMessage::from_event(event, error, max_retries, SOURCE_SERVICE) {
+        Ok(message) => dlq::publish(producer, &message).await,
+        Err(e) => Err(e),
+    };
+
+    if let Err(e) = result {
         error!(
             event_id = %event.event_id,
             error = %e,
@@ -254,10 +263,12 @@ async fn send_notification(
             created_at: Utc::now(),
         };
 
+        // 以用户 ID 作为消息键：同一用户的通知落在同一分区，
+        // notification-worker 据此合并同一事件触发的多条发放通知
         if let Err(e) = producer
             .send_json(
                 topics::BADGE_NOTIFICATIONS,
-                &notification.notification_id,
+                &notification.user_id,
                 &notification,
             )
             .await
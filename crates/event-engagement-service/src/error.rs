@@ -17,6 +17,10 @@ pub enum EngagementError {
     #[error("不支持的事件类型: {event_type}")]
     UnsupportedEventType { event_type: String },
 
+    /// 负载不符合事件类型的 Schema，自动重放也不会通过校验，以不可重试的死信保留
+    #[error("事件负载无效: {event_id} - {reason}")]
+    InvalidPayload { event_id: String, reason: String },
+
     /// 规则引擎 gRPC 调用失败（网络、超时或服务端错误）
     #[error("规则引擎调用失败: {0}")]
     RuleEngineError(String),
@@ -46,6 +50,12 @@ mod tests {
         };
         assert_eq!(err.to_string(), "不支持的事件类型: PURCHASE");
 
+        let err = EngagementError::InvalidPayload {
+            event_id: "evt-002".to_string(),
+            reason: "缺少 level 字段".to_string(),
+        };
+        assert_eq!(err.to_string(), "事件负载无效: evt-002 - 缺少 level 字段");
+
         let err = EngagementError::RuleEngineError("连接超时".to_string());
         assert_eq!(err.to_string(), "规则引擎调用失败: 连接超时");
 
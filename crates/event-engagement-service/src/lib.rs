@@ -1,9 +1,12 @@
 //! 行为事件处理服务
 //!
-//! 消费 Kafka 中的用户行为事件（签到、浏览、分享等），
+//! 消费 Kafka 中的用户行为事件（签到、浏览、分享等）以及身份与季节事件
+//! （注册、会员升级、周年、季节活动、营销活动），
 //! 经过幂等校验后触发规则引擎评估与徽章发放。
 
+pub mod anniversary;
 pub mod consumer;
 pub mod error;
+pub mod lifecycle;
 pub mod processor;
 pub mod rule_client;
@@ -0,0 +1,302 @@
+//! 身份类与季节类事件处理器
+//!
+//! 处理 `badge.lifecycle.events` 中的注册、会员升级、周年、季节活动和营销活动事件。
+//! 在行为事件的 校验 -> 评估 -> 发放 流程之外补充两项处理：
+//! - 注册事件写入 `user_registrations`，供周年调度器生成周年事件；
+//!   事件携带的联系方式写入 `user_notification_contacts`，供通知分发查询收件人
+//! - 季节类事件只匹配所属徽章系列处于活动时间窗口内的规则
+
+use std::collections::HashMap;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use badge_shared::error::BadgeError;
+use badge_shared::events::{EventPayload, EventProcessor, EventResult, EventType};
+use badge_shared::notification_contacts::{ContactStore, PgContactStore, UserContacts};
+use badge_shared::rules::BadgeGrant;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use tracing::{debug, info};
+
+use crate::processor::EngagementEventProcessor;
+
+/// 徽章所属系列的活动时间窗口
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CampaignWindow {
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+}
+
+impl CampaignWindow {
+    /// 判断时间点是否落在窗口内（两端均包含），未设置的一端视为不限
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        self.start_time.is_none_or(|start| at >= start) && self.end_time.is_none_or(|end| at <= end)
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct CampaignWindowRow {
+    badge_id: i64,
+    start_time: Option<DateTime<Utc>>,
+    end_time: Option<DateTime<Utc>>,
+}
+
+/// 身份类与季节类事件处理器
+///
+/// 规则校验、评估、发放和幂等标记复用 `EngagementEventProcessor`，
+/// 本处理器只负责注册日期记录和候选规则的活动窗口筛选。
+pub struct LifecycleEventProcessor {
+    pipeline: EngagementEventProcessor,
+    contacts: PgContactStore,
+    pool: PgPool,
+}
+
+impl LifecycleEventProcessor {
+    pub fn new(pipeline: EngagementEventProcessor, pool: PgPool) -> Self {
+        Self {
+            pipeline,
+            contacts: PgContactStore::new(pool.clone()),
+            pool,
+        }
+    }
+
+    /// 记录用户注册时间
+    ///
+    /// 以首次注册事件为准，重复投递或重复注册不会覆盖已有记录。
+    async fn record_registration(&self, event: &EventPayload) -> Result<(), BadgeError> {
+        sqlx::query(
+            r#"
+            INSERT INTO user_registrations (user_id, registered_at, source_event_id)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (user_id) DO NOTHING
+            "#,
+        )
+        .bind(&event.user_id)
+        .bind(event.timestamp)
+        .bind(&event.event_id)
+        .execute(&self.pool)
+        .await?;
+
+        debug!(user_id = %event.user_id, registered_at = %event.timestamp, "已记录用户注册时间");
+
+        // 重复注册时以最新提供的联系方式为准
+        if let Some(contacts) = UserContacts::from_data(&event.user_id, &event.data) {
+            self.contacts.upsert(&contacts).await?;
+            debug!(user_id = %event.user_id, "已记录用户联系方式");
+        }
+        Ok(())
+    }
+
+    /// 查询规则所发放徽章的系列活动窗口
+    async fn load_campaign_windows(
+        &self,
+        rules: &[BadgeGrant],
+    ) -> Result<HashMap<i64, CampaignWindow>, BadgeError> {
+        let badge_ids: Vec<i64> = rules.iter().map(|r| r.badge_id).collect();
+
+        let rows = sqlx::query_as::<_, CampaignWindowRow>(
+            r#"
+            SELECT b.id AS badge_id, s.start_time, s.end_time
+            FROM badges b
+            JOIN badge_series s ON s.id = b.series_id
+            WHERE b.id = ANY($1)
+            "#,
+        )
+        .bind(&badge_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                (
+                    row.badge_id,
+                    CampaignWindow {
+                        start_time: row.start_time,
+                        end_time: row.end_time,
+                    },
+                )
+            })
+            .collect())
+    }
+}
+
+/// 保留活动窗口包含事件发生时间的规则
+///
+/// 以事件发生时间而非处理时间判断，活动结束前发生、延迟投递的事件仍然有效。
+/// 查不到系列信息的徽章不做限制，由后续发放环节处理。
+fn filter_by_campaign_window(
+    rules: Vec<BadgeGrant>,
+    windows: &HashMap<i64, CampaignWindow>,
+    at: DateTime<Utc>,
+) -> Vec<BadgeGrant> {
+    rules
+        .into_iter()
+        .filter(|rule| {
+            let in_window = windows
+                .get(&rule.badge_id)
+                .is_none_or(|window| window.contains(at));
+            if !in_window {
+                debug!(
+                    rule_id = rule.rule_id,
+                    badge_id = rule.badge_id,
+                    "徽章系列不在活动时间内，跳过规则"
+                );
+            }
+            in_window
+        })
+        .collect()
+}
+
+#[async_trait]
+impl EventProcessor for LifecycleEventProcessor {
+    async fn process(&self, event: &EventPayload) -> Result<EventResult, BadgeError> {
+        let start = Instant::now();
+
+        info!(
+            event_id = %event.event_id,
+            event_type = %event.event_type,
+            user_id = %event.user_id,
+            "开始处理身份/季节事件"
+        );
+
+        self.pipeline.validate_payload(event)?;
+
+        // 注册时间是周年事件的唯一来源，写入失败时整体失败以便重试
+        if event.event_type == EventType::Registration {
+            self.record_registration(event).await?;
+        }
+
+        let mut rules = self.pipeline.applicable_rules(event);
+
+        if event.event_type.is_seasonal() && !rules.is_empty() {
+            let windows = self.load_campaign_windows(&rules).await?;
+            let total = rules.len();
+            rules = filter_by_campaign_window(rules, &windows, event.timestamp);
+            if rules.len() < total {
+                info!(
+                    event_id = %event.event_id,
+                    skipped_count = total - rules.len(),
+                    "部分规则因活动时间窗口被跳过"
+                );
+            }
+        }
+
+        if rules.is_empty() {
+            debug!(
+                event_id = %event.event_id,
+                event_type = %event.event_type,
+                "无适用规则，跳过评估"
+            );
+            return Ok(EngagementEventProcessor::empty_result(event, start));
+        }
+
+        self.pipeline.evaluate_and_grant(event, rules, start).await
+    }
+
+    /// 本处理器负责的事件类型：身份类与季节类事件
+    fn supported_event_types(&self) -> Vec<EventType> {
+        vec![
+            EventType::Registration,
+            EventType::MembershipUpgrade,
+            EventType::Anniversary,
+            EventType::SeasonalActivity,
+            EventType::CampaignParticipation,
+        ]
+    }
+
+    async fn is_processed(&self, event_id: &str) -> Result<bool, BadgeError> {
+        self.pipeline.is_processed(event_id).await
+    }
+
+    async fn mark_processed(&self, event_id: &str) -> Result<(), BadgeError> {
+        self.pipeline.mark_processed(event_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn grant(rule_id: i64, badge_id: i64) -> BadgeGrant {
+        BadgeGrant {
+            rule_id,
+            rule_code: format!("RULE_{}", rule_id),
+            badge_id,
+            badge_name: format!("Badge {}", badge_id),
+            quantity: 1,
+            event_type: "seasonal_activity".to_string(),
+            start_time: None,
+            end_time: None,
+            max_count_per_user: None,
+            global_quota: None,
+            global_granted: 0,
+            rule_json: None,
+        }
+    }
+
+    fn ts(month: u32, day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, month, day, 0, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_campaign_window_bounds() {
+        let window = CampaignWindow {
+            start_time: Some(ts(1, 1)),
+            end_time: Some(ts(2, 1)),
+        };
+        assert!(window.contains(ts(1, 1)));
+        assert!(window.contains(ts(2, 1)));
+        assert!(!window.contains(ts(2, 2)));
+
+        let open_ended = CampaignWindow {
+            start_time: Some(ts(3, 1)),
+            end_time: None,
+        };
+        assert!(!open_ended.contains(ts(2, 28)));
+        assert!(open_ended.contains(ts(12, 31)));
+        assert!(CampaignWindow::default().contains(ts(6, 1)));
+    }
+
+    #[test]
+    fn test_filter_by_campaign_window() {
+        let windows = HashMap::from([
+            (
+                10,
+                CampaignWindow {
+                    start_time: Some(ts(1, 1)),
+                    end_time: Some(ts(1, 31)),
+                },
+            ),
+            (
+                20,
+                CampaignWindow {
+                    start_time: Some(ts(3, 1)),
+                    end_time: Some(ts(3, 31)),
+                },
+            ),
+        ]);
+
+        let rules = vec![grant(1, 10), grant(2, 20), grant(3, 30)];
+        let kept: Vec<i64> = filter_by_campaign_window(rules, &windows, ts(1, 15))
+            .iter()
+            .map(|r| r.rule_id)
+            .collect();
+
+        // 徽章 30 没有系列信息，不受窗口限制
+        assert_eq!(kept, vec![1, 3]);
+    }
+
+    #[test]
+    fn test_supported_event_types_are_identity_or_seasonal() {
+        let types = [
+            EventType::Registration,
+            EventType::MembershipUpgrade,
+            EventType::Anniversary,
+            EventType::SeasonalActivity,
+            EventType::CampaignParticipation,
+        ];
+        assert!(types.iter().all(|t| t.is_identity() || t.is_seasonal()));
+    }
+}
@@ -1,6 +1,7 @@
 //! 行为事件处理服务
 //!
-//! 消费 Kafka 行为事件（签到、浏览、分享等），触发规则引擎评估与徽章发放。
+//! 消费 Kafka 行为事件（签到、浏览、分享等）和身份与季节事件（注册、周年、活动等），
+//! 触发规则引擎评估与徽章发放；同时运行注册周年事件调度器。
 
 use std::sync::Arc;
 
@@ -8,10 +9,11 @@ use anyhow::Result;
 use tokio::sync::watch;
 use tracing::info;
 
-use badge_shared::config::AppConfig;
+use badge_shared::config::{AppConfig, RuleEvaluationMode};
 use badge_shared::database::Database;
 use badge_shared::observability;
-use badge_shared::rules::{RuleBadgeMapping, RuleLoader, RuleValidator};
+use badge_shared::rules::{EventTypeRegistry, RuleBadgeMapping, RuleLoader, RuleValidator};
+use rule_engine::{AggregateResolver, EmbeddedRuleEvaluator, RedisAggregateStore};
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -44,23 +46,29 @@ async fn main() -> Result<()> {
     let badge_service_url = std::env::var("BADGE_SERVICE_URL")
         .unwrap_or_else(|_| format!("{grpc_scheme}://localhost:50052"));
 
-    let rule_client = event_engagement_service::rule_client::BadgeRuleClient::new(
+    let rule_client = Arc::new(event_engagement_service::rule_client::BadgeRuleClient::new(
         &rule_engine_url,
         &badge_service_url,
         client_tls,
-    )?;
+    )?);
 
     // 初始化规则组件：RuleBadgeMapping 作为内存缓存存储从数据库加载的规则
     let rule_mapping = Arc::new(RuleBadgeMapping::new());
 
-    // RuleLoader 负责从数据库加载规则并维护到 rule_mapping
-    let rule_loader = Arc::new(RuleLoader::new(
-        db_pool.clone(),
-        "engagement",
-        rule_mapping.clone(),
-        config.rules.refresh_interval_secs,
-        config.rules.initial_load_timeout_secs,
-    ));
+    // 本服务组注册的事件类型（含自定义事件及其负载 Schema）
+    let event_types = Arc::new(EventTypeRegistry::new());
+
+    // RuleLoader 负责从数据库加载规则并维护到 rule_mapping，同时刷新事件类型注册表
+    let rule_loader = Arc::new(
+        RuleLoader::new(
+            db_pool.clone(),
+            "engagement",
+            rule_mapping.clone(),
+            config.rules.refresh_interval_secs,
+            config.rules.initial_load_timeout_secs,
+        )
+        .with_event_type_registry(event_types.clone()),
+    );
 
     // RuleValidator 在发放前校验规则的时间窗口、用户限额、全局配额等条件
     let rule_validator = Arc::new(RuleValidator::new(cache.clone(), db_pool.clone()));
@@ -76,16 +84,51 @@ async fn main() -> Result<()> {
         .clone()
         .start_background_refresh(shutdown_rx.clone());
 
-    let processor = event_engagement_service::processor::EngagementEventProcessor::new(
-        cache,
-        Arc::new(rule_client),
-        rule_mapping,
+    let mut processor = event_engagement_service::processor::EngagementEventProcessor::new(
+        cache.clone(),
+        rule_client.clone(),
+        rule_mapping.clone(),
+        rule_validator.clone(),
+    )
+    .with_event_type_registry(event_types.clone());
+    // 身份与季节事件复用同一套校验、评估、发放流程
+    let mut lifecycle_pipeline = event_engagement_service::processor::EngagementEventProcessor::new(
+        cache.clone(),
+        rule_client,
+        rule_mapping.clone(),
         rule_validator,
+    )
+    .with_event_type_registry(event_types);
+
+    // 内嵌模式下规则直接在本进程执行，聚合状态与规则引擎服务共用同一个 Redis
+    if config.rules.evaluation_mode == RuleEvaluationMode::Embedded {
+        let aggregates = Arc::new(AggregateResolver::new(Arc::new(RedisAggregateStore::new(
+            cache,
+        ))));
+        let evaluator = Arc::new(EmbeddedRuleEvaluator::new(rule_mapping, aggregates));
+        evaluator.sync();
+        info!(rule_count = evaluator.len(), "规则评估方式：内嵌规则引擎");
+        processor = processor.with_embedded_evaluator(evaluator.clone());
+        lifecycle_pipeline = lifecycle_pipeline.with_embedded_evaluator(evaluator);
+    }
+
+    let lifecycle_processor = event_engagement_service::lifecycle::LifecycleEventProcessor::new(
+        lifecycle_pipeline,
+        db_pool.clone(),
     );
 
+    // 注册周年调度器：多实例并行运行时通过数据库行锁认领，不会重复生成
+    let anniversary_scheduler =
+        event_engagement_service::anniversary::AnniversaryScheduler::with_defaults(
+            db_pool,
+            producer.clone(),
+        );
+    tokio::spawn(anniversary_scheduler.run(shutdown_rx.clone()));
+
     let consumer = event_engagement_service::consumer::EngagementConsumer::new(
         &config,
         processor,
+        lifecycle_processor,
         producer,
         rule_loader.clone(),
     )?;
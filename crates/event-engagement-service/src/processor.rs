@@ -4,146 +4,22 @@
 //! 幂等校验 -> 规则校验 -> 规则引擎评估 -> 徽章发放 -> 结果汇总。
 
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
-use badge_shared::cache::Cache;
+use badge_shared::cache::{Cache, CacheKey};
 use badge_shared::error::BadgeError;
 use badge_shared::events::{
     EventPayload, EventProcessor, EventResult, EventType, GrantedBadge, MatchedRule,
 };
-use badge_shared::rules::{BadgeGrant, RuleBadgeMapping, RuleValidator, SkippedRule};
+use badge_shared::rules::{
+    BadgeGrant, EventTypeRegistry, RuleBadgeMapping, RuleValidator, SkippedRule,
+};
+use rule_engine::{EmbeddedRuleEvaluator, evaluate_rule_ids};
 use tracing::{debug, info, warn};
 
 use crate::rule_client::BadgeRuleService;
 
-/// 本地评估 rule_json 条件
-///
-/// 当规则引擎没有加载规则时，使用本地 rule_json 进行简化评估。
-/// 支持简单条件（eq, neq, gt, gte, lt, lte）和组合条件（AND, OR）。
-fn evaluate_rule_json(data: &serde_json::Value, rule_json: Option<&serde_json::Value>) -> bool {
-    let rule = match rule_json {
-        Some(r) => r,
-        // 无 rule_json 时默认匹配（兼容仅事件类型匹配的规则）
-        None => return true,
-    };
-
-    evaluate_node(data, rule)
-}
-
-/// 评估单个规则节点
-fn evaluate_node(data: &serde_json::Value, node: &serde_json::Value) -> bool {
-    let node_type = node.get("type").and_then(|t| t.as_str()).unwrap_or("");
-
-    match node_type {
-        "condition" => evaluate_condition(data, node),
-        "group" => evaluate_group(data, node),
-        _ => {
-            // 未知类型，默认不匹配
-            warn!(node_type, "未知的规则节点类型");
-            false
-        }
-    }
-}
-
-/// 评估条件节点
-fn evaluate_condition(data: &serde_json::Value, condition: &serde_json::Value) -> bool {
-    let field = match condition.get("field").and_then(|f| f.as_str()) {
-        Some(f) => f,
-        None => return false,
-    };
-
-    let operator = condition
-        .get("operator")
-        .and_then(|o| o.as_str())
-        .unwrap_or("");
-
-    let expected = match condition.get("value") {
-        Some(v) => v,
-        None => return false,
-    };
-
-    // 从数据中获取字段值（支持点号分隔的路径）
-    let actual = get_field_value(data, field);
-
-    match operator.to_lowercase().as_str() {
-        "eq" => values_equal(actual, expected),
-        "neq" => !values_equal(actual, expected),
-        "gt" => compare_numbers(actual, expected, |a, b| a > b),
-        "gte" => compare_numbers(actual, expected, |a, b| a >= b),
-        "lt" => compare_numbers(actual, expected, |a, b| a < b),
-        "lte" => compare_numbers(actual, expected, |a, b| a <= b),
-        _ => {
-            // 不支持的操作符，默认不匹配
-            debug!(operator, "不支持的操作符");
-            false
-        }
-    }
-}
-
-/// 评估组节点
-fn evaluate_group(data: &serde_json::Value, group: &serde_json::Value) -> bool {
-    let operator = group
-        .get("operator")
-        .and_then(|o| o.as_str())
-        .unwrap_or("AND");
-
-    let children = match group.get("children").and_then(|c| c.as_array()) {
-        Some(c) => c,
-        None => return false,
-    };
-
-    match operator.to_uppercase().as_str() {
-        "AND" => children.iter().all(|child| evaluate_node(data, child)),
-        "OR" => children.iter().any(|child| evaluate_node(data, child)),
-        _ => false,
-    }
-}
-
-/// 从数据中获取字段值（支持点号分隔的路径）
-fn get_field_value<'a>(data: &'a serde_json::Value, path: &str) -> Option<&'a serde_json::Value> {
-    let parts: Vec<&str> = path.split('.').collect();
-    let mut current = data;
-
-    for part in parts {
-        match current {
-            serde_json::Value::Object(map) => {
-                current = map.get(part)?;
-            }
-            serde_json::Value::Array(arr) => {
-                let index: usize = part.parse().ok()?;
-                current = arr.get(index)?;
-            }
-            _ => return None,
-        }
-    }
-
-    Some(current)
-}
-
-/// 比较两个值是否相等
-fn values_equal(actual: Option<&serde_json::Value>, expected: &serde_json::Value) -> bool {
-    match actual {
-        Some(a) => a == expected,
-        None => false,
-    }
-}
-
-/// 比较两个数值
-fn compare_numbers(
-    actual: Option<&serde_json::Value>,
-    expected: &serde_json::Value,
-    cmp: fn(f64, f64) -> bool,
-) -> bool {
-    let actual_num = actual.and_then(|v| v.as_f64());
-    let expected_num = expected.as_f64();
-
-    match (actual_num, expected_num) {
-        (Some(a), Some(b)) => cmp(a, b),
-        _ => false,
-    }
-}
-
 /// 幂等键前缀，标记事件是否已处理
 const PROCESSED_KEY_PREFIX: &str = "event:processed:";
 /// 幂等记录保留 24 小时，超过此窗口的重复消费不再拦截，
@@ -165,6 +41,10 @@ pub struct EngagementEventProcessor {
     rule_client: Arc<dyn BadgeRuleService>,
     rule_mapping: Arc<RuleBadgeMapping>,
     rule_validator: Arc<RuleValidator>,
+    /// 内嵌规则引擎，配置后在进程内评估规则而不再调用规则引擎服务
+    embedded_evaluator: Option<Arc<EmbeddedRuleEvaluator>>,
+    /// 本服务组注册的事件类型，用于接收自定义事件并校验事件负载
+    event_types: Option<Arc<EventTypeRegistry>>,
 }
 
 impl EngagementEventProcessor {
@@ -179,63 +59,80 @@ impl EngagementEventProcessor {
             rule_client,
             rule_mapping,
             rule_validator,
+            embedded_evaluator: None,
+            event_types: None,
         }
     }
 
-    /// 构造 Redis 幂等键
-    fn processed_key(event_id: &str) -> String {
-        format!("{PROCESSED_KEY_PREFIX}{event_id}")
+    /// 启用内嵌规则评估（`rules.evaluation_mode = "embedded"`）
+    pub fn with_embedded_evaluator(mut self, evaluator: Arc<EmbeddedRuleEvaluator>) -> Self {
+        self.embedded_evaluator = Some(evaluator);
+        self
     }
-}
 
-#[async_trait]
-impl EventProcessor for EngagementEventProcessor {
-    /// 处理行为事件的完整流程
-    ///
-    /// 1. 根据事件类型获取适用的规则
-    /// 2. 对每条规则进行校验（时间窗口、用户限额、全局配额）
-    /// 3. 校验通过的规则交给规则引擎评估
-    /// 4. 对匹配的规则发放徽章
-    /// 5. 收集所有结果（含部分失败），不因单条规则失败中断整体流程
-    async fn process(&self, event: &EventPayload) -> Result<EventResult, BadgeError> {
-        let start = std::time::Instant::now();
+    /// 启用事件类型注册表：接收已注册的自定义事件，并在规则评估前校验事件负载
+    pub fn with_event_type_registry(mut self, registry: Arc<EventTypeRegistry>) -> Self {
+        self.event_types = Some(registry);
+        self
+    }
 
-        info!(
-            event_id = %event.event_id,
-            event_type = %event.event_type,
-            user_id = %event.user_id,
-            "开始处理行为事件"
-        );
+    /// 按注册的 JSON Schema 校验事件负载，不合规的事件不进入规则评估
+    pub(crate) fn validate_payload(&self, event: &EventPayload) -> Result<(), BadgeError> {
+        self.event_types
+            .as_ref()
+            .map_or(Ok(()), |registry| registry.validate_event(event))
+    }
 
-        // 1. 根据事件类型获取所有适用的规则
-        // 使用 to_db_key() 获取数据库中的事件类型键名（小写下划线格式）
-        let rules = self
-            .rule_mapping
-            .get_rules_by_event_type(event.event_type.to_db_key());
-        if rules.is_empty() {
-            debug!(
-                event_id = %event.event_id,
-                event_type = %event.event_type,
-                "无适用规则，跳过评估"
-            );
-            return Ok(EventResult {
-                event_id: event.event_id.clone(),
-                processed: true,
-                matched_rules: vec![],
-                granted_badges: vec![],
-                processing_time_ms: start.elapsed().as_millis() as i64,
-                errors: vec![],
-            });
-        }
+    /// 批量评估规则，返回命中的规则 ID
+    async fn evaluate_rules(
+        &self,
+        rule_ids: &[String],
+        context: serde_json::Value,
+    ) -> Result<Vec<String>, BadgeError> {
+        evaluate_rule_ids(
+            self.embedded_evaluator.as_deref(),
+            rule_ids,
+            context,
+            |context| async move {
+                self.rule_client
+                    .evaluate_rules(rule_ids, context)
+                    .await
+                    .map(|matches| matches.into_iter().map(|m| m.rule_id).collect())
+            },
+        )
+        .await
+    }
 
-        info!(
-            event_id = %event.event_id,
-            event_type = %event.event_type,
-            rule_count = rules.len(),
-            "找到适用规则"
-        );
+    /// 根据事件类型获取所有适用的规则
+    ///
+    /// 使用 to_db_key() 获取数据库中的事件类型键名（小写下划线格式）
+    pub(crate) fn applicable_rules(&self, event: &EventPayload) -> Vec<BadgeGrant> {
+        self.rule_mapping
+            .get_rules_by_event_type(event.event_type.to_db_key())
+    }
+
+    /// 未命中任何规则时的处理结果
+    pub(crate) fn empty_result(event: &EventPayload, start: Instant) -> EventResult {
+        EventResult {
+            event_id: event.event_id.clone(),
+            processed: true,
+            matched_rules: vec![],
+            granted_badges: vec![],
+            processing_time_ms: start.elapsed().as_millis() as i64,
+            errors: vec![],
+        }
+    }
 
-        // 2. 对每条规则进行校验
+    /// 对候选规则执行 校验 -> 评估 -> 发放，汇总处理结果
+    ///
+    /// 身份类与季节类事件的处理器在筛选候选规则后复用同一流程。
+    pub(crate) async fn evaluate_and_grant(
+        &self,
+        event: &EventPayload,
+        rules: Vec<BadgeGrant>,
+        start: Instant,
+    ) -> Result<EventResult, BadgeError> {
+        // 1. 对每条规则进行校验
         let mut valid_rules: Vec<BadgeGrant> = Vec::new();
         let mut skipped_rules: Vec<SkippedRule> = Vec::new();
 
@@ -275,57 +172,33 @@ impl EventProcessor for EngagementEventProcessor {
                 event_id = %event.event_id,
                 "所有规则校验未通过，跳过评估"
             );
-            return Ok(EventResult {
-                event_id: event.event_id.clone(),
-                processed: true,
-                matched_rules: vec![],
-                granted_badges: vec![],
-                processing_time_ms: start.elapsed().as_millis() as i64,
-                errors: vec![],
-            });
+            return Ok(Self::empty_result(event, start));
         }
 
-        // 3. 将事件转为规则引擎评估上下文
+        // 2. 将事件转为规则引擎评估上下文
         let context = event.to_evaluation_context();
 
         // 收集有效规则的 ID 用于批量评估
         let rule_ids: Vec<String> = valid_rules.iter().map(|r| r.rule_id.to_string()).collect();
 
-        // 4. 调用规则引擎批量评估
-        let matches = self
-            .rule_client
-            .evaluate_rules(&rule_ids, context)
-            .await
-            .map_err(|e| BadgeError::Internal(format!("规则评估失败: {e}")))?;
+        // 3. 批量评估规则（远程规则引擎或内嵌规则引擎）
+        let matched_rule_ids = self.evaluate_rules(&rule_ids, context).await?;
+        // 评估会累积聚合状态，用户的徽章进度随之变化
+        self.invalidate_progress(&event.user_id).await;
 
         let mut matched_rules = Vec::new();
         let mut granted_badges = Vec::new();
         let mut errors = Vec::new();
 
-        // 5. 对每条规则发放徽章
-        // 当规则引擎返回空（规则未加载到引擎）时，使用本地 rule_json 进行条件评估
-        let rules_to_grant: Vec<&BadgeGrant> = if matches.is_empty() {
-            // 规则引擎无匹配，使用本地 rule_json 进行条件评估（简化模式）
-            info!(
-                event_id = %event.event_id,
-                valid_rules_count = valid_rules.len(),
-                "规则引擎未返回匹配，使用本地 rule_json 评估"
-            );
-            valid_rules
-                .iter()
-                .filter(|r| evaluate_rule_json(&event.data, r.rule_json.as_ref()))
-                .collect()
-        } else {
-            // 使用规则引擎匹配结果
-            matches
-                .iter()
-                .filter_map(|rule_match| {
-                    valid_rules
-                        .iter()
-                        .find(|r| r.rule_id.to_string() == rule_match.rule_id)
-                })
-                .collect()
-        };
+        // 4. 对匹配的规则发放徽章
+        let rules_to_grant: Vec<&BadgeGrant> = matched_rule_ids
+            .iter()
+            .filter_map(|rule_id| {
+                valid_rules
+                    .iter()
+                    .find(|r| r.rule_id.to_string() == *rule_id)
+            })
+            .collect();
 
         for badge_grant in rules_to_grant {
             // 记录匹配的规则
@@ -337,7 +210,7 @@ impl EventProcessor for EngagementEventProcessor {
                 quantity: badge_grant.quantity,
             });
 
-            // 6. 调用徽章发放，失败只记录不中断
+            // 5. 调用徽章发放，失败只记录不中断
             match self
                 .rule_client
                 .grant_badge(
@@ -405,12 +278,68 @@ impl EventProcessor for EngagementEventProcessor {
             granted_count = result.granted_badges.len(),
             error_count = result.errors.len(),
             processing_time_ms = result.processing_time_ms,
-            "行为事件处理完成"
+            "事件处理完成"
         );
 
         Ok(result)
     }
 
+    /// 构造 Redis 幂等键
+    fn processed_key(event_id: &str) -> String {
+        format!("{PROCESSED_KEY_PREFIX}{event_id}")
+    }
+
+    /// 使用户的徽章进度缓存失效，失败只记录日志
+    async fn invalidate_progress(&self, user_id: &str) {
+        let key = CacheKey::user_badge_progress(user_id);
+        if let Err(e) = self.cache.delete(&key).await {
+            warn!(key = %key, error = %e, "徽章进度缓存失效失败");
+        }
+    }
+}
+
+#[async_trait]
+impl EventProcessor for EngagementEventProcessor {
+    /// 处理行为事件的完整流程
+    ///
+    /// 1. 根据事件类型获取适用的规则
+    /// 2. 对每条规则进行校验（时间窗口、用户限额、全局配额）
+    /// 3. 校验通过的规则交给规则引擎评估
+    /// 4. 对匹配的规则发放徽章
+    /// 5. 收集所有结果（含部分失败），不因单条规则失败中断整体流程
+    async fn process(&self, event: &EventPayload) -> Result<EventResult, BadgeError> {
+        let start = Instant::now();
+
+        info!(
+            event_id = %event.event_id,
+            event_type = %event.event_type,
+            user_id = %event.user_id,
+            "开始处理行为事件"
+        );
+
+        self.validate_payload(event)?;
+
+        // 根据事件类型获取所有适用的规则
+        let rules = self.applicable_rules(event);
+        if rules.is_empty() {
+            debug!(
+                event_id = %event.event_id,
+                event_type = %event.event_type,
+                "无适用规则，跳过评估"
+            );
+            return Ok(Self::empty_result(event, start));
+        }
+
+        info!(
+            event_id = %event.event_id,
+            event_type = %event.event_type,
+            rule_count = rules.len(),
+            "找到适用规则"
+        );
+
+        self.evaluate_and_grant(event, rules, start).await
+    }
+
     /// 本处理器负责的事件类型：所有行为类事件
     fn supported_event_types(&self) -> Vec<EventType> {
         vec![
@@ -422,6 +351,17 @@ impl EventProcessor for EngagementEventProcessor {
         ]
     }
 
+    /// 内置行为类事件，以及注册到行为服务组的自定义事件
+    fn supports(&self, event_type: &EventType) -> bool {
+        match event_type {
+            EventType::Custom(code) => self
+                .event_types
+                .as_ref()
+                .is_some_and(|registry| registry.contains(code)),
+            _ => self.supported_event_types().contains(event_type),
+        }
+    }
+
     /// 通过 Redis EXISTS 检查事件是否已处理过
     async fn is_processed(&self, event_id: &str) -> Result<bool, BadgeError> {
         let key = Self::processed_key(event_id);
@@ -10,6 +10,7 @@ use badge_proto::badge::GrantBadgeRequest;
 use badge_proto::badge::badge_management_service_client::BadgeManagementServiceClient;
 use badge_proto::rule_engine::BatchEvaluateRequest;
 use badge_proto::rule_engine::rule_engine_service_client::RuleEngineServiceClient;
+use badge_shared::observability::middleware::grpc::traced_request;
 use tonic::transport::Channel;
 use tracing::{debug, info, warn};
 
@@ -133,9 +134,12 @@ impl BadgeRuleService for BadgeRuleClient {
 
         // clone 客户端避免 &mut self 限制（tonic 客户端 clone 很轻量）
         let mut client = self.rule_engine.clone();
-        let response = client.batch_evaluate(request).await.map_err(|e| {
-            EngagementError::RuleEngineError(format!("BatchEvaluate 调用失败: {e}"))
-        })?;
+        let response = client
+            .batch_evaluate(traced_request(request))
+            .await
+            .map_err(|e| {
+                EngagementError::RuleEngineError(format!("BatchEvaluate 调用失败: {e}"))
+            })?;
 
         let batch_result = response.into_inner();
 
@@ -183,7 +187,7 @@ impl BadgeRuleService for BadgeRuleClient {
 
         let mut client = self.badge_service.clone();
         let response = client
-            .grant_badge(request)
+            .grant_badge(traced_request(request))
             .await
             .map_err(|e| EngagementError::BadgeGrantError(format!("GrantBadge 调用失败: {e}")))?;
 
@@ -10,12 +10,14 @@
 use std::sync::Arc;
 
 use badge_shared::config::AppConfig;
+use badge_shared::dlq::{self, DEFAULT_DLQ_MAX_RETRIES, DeadLetterMessage};
+use badge_shared::error::BadgeError;
 use badge_shared::events::{
     EventPayload, EventProcessor, EventType, NotificationChannel, NotificationEvent,
     NotificationType,
 };
 use badge_shared::kafka::{KafkaConsumer, KafkaProducer, topics};
-use badge_shared::rules::{RuleLoader, RuleReloadEvent};
+use badge_shared::rules::{RuleLoader, RuleReloadListener};
 use chrono::Utc;
 use tokio::sync::watch;
 use tracing::{error, info, warn};
@@ -24,6 +26,9 @@ use uuid::Uuid;
 use crate::error::TransactionError;
 use crate::processor::TransactionEventProcessor;
 
+/// 写入死信消息的来源服务标识
+const SOURCE_SERVICE: &str = "event-transaction-service";
+
 /// 交易事件消费者
 ///
 /// 组合 KafkaConsumer（消息拉取）、TransactionEventProcessor（业务处理）
@@ -34,6 +39,7 @@ pub struct TransactionConsumer {
     processor: TransactionEventProcessor,
     producer: KafkaProducer,
     rule_loader: Arc<RuleLoader>,
+    reload_listener: RuleReloadListener,
 }
 
 impl TransactionConsumer {
@@ -44,11 +50,14 @@ impl TransactionConsumer {
         rule_loader: Arc<RuleLoader>,
     ) -> Result<Self, TransactionError> {
         let consumer = KafkaConsumer::new(&config.kafka, None)?;
+        // 规则刷新需要通知到每个实例，使用独立的广播消费组，不能与事件消费组共用
+        let reload_listener = RuleReloadListener::new(&config.kafka, &config.service_name)?;
         Ok(Self {
             consumer,
             processor,
             producer,
             rule_loader,
+            reload_listener,
         })
     }
 
@@ -56,10 +65,13 @@ impl TransactionConsumer {
     ///
     /// 将 processor 和 producer 移入闭包，通过 KafkaConsumer::start
     /// 驱动消费循环。单独抽取 handle_message 方法方便单元测试。
-    /// 同时订阅规则刷新 topic，在消费循环中根据消息来源分发处理。
+    /// 同时启动规则刷新监听任务，随同一个 shutdown 信号退出。
     pub async fn run(self, shutdown: watch::Receiver<bool>) -> Result<(), TransactionError> {
-        self.consumer
-            .subscribe(&[topics::TRANSACTION_EVENTS, topics::RULE_RELOAD])?;
+        self.consumer.subscribe(&[topics::TRANSACTION_EVENTS])?;
+
+        self.rule_loader
+            .clone()
+            .start_reload_listener(self.reload_listener, shutdown.clone());
 
         info!(
             transaction_topic = topics::TRANSACTION_EVENTS,
@@ -69,20 +81,12 @@ impl TransactionConsumer {
 
         let processor = self.processor;
         let producer = self.producer;
-        let rule_loader = self.rule_loader;
 
         self.consumer
             .start(shutdown, |msg| {
                 let processor = &processor;
                 let producer = &producer;
-                let rule_loader = &rule_loader;
                 async move {
-                    // 根据 topic 分发：规则刷新 topic 走单独逻辑
-                    if msg.topic == topics::RULE_RELOAD {
-                        handle_rule_reload(rule_loader, &msg).await;
-                        return Ok(());
-                    }
-
                     if let Err(e) = handle_message(processor, producer, &msg).await {
                         error!(
                             error = %e,
@@ -102,38 +106,6 @@ impl TransactionConsumer {
     }
 }
 
-/// 处理规则刷新消息
-///
-/// 解析 RuleReloadEvent 并判断是否需要刷新本服务的规则缓存。
-/// service_group 为空表示全部服务刷新，否则只刷新匹配的服务组。
-async fn handle_rule_reload(
-    rule_loader: &Arc<RuleLoader>,
-    msg: &badge_shared::kafka::ConsumerMessage,
-) {
-    let event: RuleReloadEvent = match serde_json::from_slice(&msg.payload) {
-        Ok(e) => e,
-        Err(e) => {
-            warn!(error = %e, "规则刷新事件反序列化失败，忽略");
-            return;
-        }
-    };
-
-    // 仅当 service_group 匹配或为空（全部刷新）时才执行
-    if event.service_group.is_none() || event.service_group.as_deref() == Some("transaction") {
-        info!(
-            trigger_source = %event.trigger_source,
-            triggered_at = %event.triggered_at,
-            "收到 Kafka 规则刷新事件"
-        );
-
-        if let Err(e) = rule_loader.reload_now().await {
-            warn!(error = %e, "Kafka 触发规则刷新失败");
-        } else {
-            info!("Kafka 触发规则刷新成功");
-        }
-    }
-}
-
 /// 处理单条 Kafka 消息的完整流程
 ///
 /// 拆分为独立函数而非方法，便于在测试中直接调用而无需构造完整的 Consumer。
@@ -178,13 +150,27 @@ pub async fn handle_message(
     // 4. 执行业务处理
     let result = match processor.process(&event).await {
         Ok(r) => r,
+        Err(BadgeError::InvalidPayload(reason)) => {
+            // 自动重放同样无法通过校验，以不可重试的死信保留，修正 Schema 或数据后人工重放
+            warn!(
+                event_id = %event.event_id,
+                reason = %reason,
+                "事件负载不符合 Schema，发送到死信队列等待人工处理"
+            );
+            let error = BadgeError::InvalidPayload(reason.clone()).to_string();
+            send_to_dlq(producer, &event, &error, 0).await;
+            return Err(TransactionError::InvalidPayload {
+                event_id: event.event_id,
+                reason,
+            });
+        }
         Err(e) => {
             error!(
                 event_id = %event.event_id,
                 error = %e,
                 "交易事件处理失败，发送到死信队列"
             );
-            send_to_dlq(producer, &event).await;
+            send_to_dlq(producer, &event, &e.to_string(), DEFAULT_DLQ_MAX_RETRIES).await;
             return Err(TransactionError::Shared(e));
         }
     };
@@ -200,7 +186,7 @@ pub async fn handle_message(
 
     // 6. 根据事件类型发送不同的通知
     match event.event_type {
-        EventType::Purchase => {
+        EventType::Purchase | EventType::Custom(_) => {
             // 有徽章发放时才通知
             if !result.granted_badges.is_empty() {
                 send_grant_notification(producer, &event, &result).await;
@@ -228,15 +214,24 @@ pub async fn handle_message(
 
 /// 校验事件类型是否在处理器的支持列表中
 fn is_supported_event_type(event_type: &EventType, processor: &TransactionEventProcessor) -> bool {
-    processor.supported_event_types().contains(event_type)
+    processor.supports(event_type)
 }
 
-/// 将处理失败的事件发送到死信队列，供人工排查或延迟重试
-async fn send_to_dlq(producer: &KafkaProducer, event: &EventPayload) {
-    if let Err(e) = producer
-        .send_json(topics::DEAD_LETTER_QUEUE, &event.event_id, event)
-        .await
-    {
+/// 将处理失败的事件封装为死信消息发送到死信队列，由管理后台持久化后延迟重放
+///
+/// `max_retries` 为 0 时死信直接标记为耗尽，不会自动重放，只能由管理后台人工重放
+async fn send_to_dlq(
+    producer: &KafkaProducer,
+    event: &EventPayload,
+    error: &str,
+    max_retries: u32,
+) {
+    let result = match DeadLetterMessage::from_event(event, error, max_retries, SOURCE_SERVICE) {
+        Ok(message) => dlq::publish(producer, &message).await,
+        Err(e) => Err(e),
+    };
+
+    if let Err(e) = result {
         error!(
             event_id = %event.event_id,
             error = %e,
@@ -268,10 +263,12 @@ async fn send_grant_notification(
             created_at: Utc::now(),
         };
 
+        // 以用户 ID 作为消息键：同一用户的通知落在同一分区，
+        // notification-worker 据此合并同一事件触发的多条发放通知
         if let Err(e) = producer
             .send_json(
                 topics::BADGE_NOTIFICATIONS,
-                &notification.notification_id,
+                &notification.user_id,
                 &notification,
             )
             .await
@@ -310,7 +307,7 @@ async fn send_revoke_notification(producer: &KafkaProducer, event: &EventPayload
     if let Err(e) = producer
         .send_json(
             topics::BADGE_NOTIFICATIONS,
-            &notification.notification_id,
+            &notification.user_id,
             &notification,
         )
         .await
@@ -8,10 +8,11 @@ use anyhow::Result;
 use tokio::sync::watch;
 use tracing::info;
 
-use badge_shared::config::AppConfig;
+use badge_shared::config::{AppConfig, RuleEvaluationMode};
 use badge_shared::database::Database;
 use badge_shared::observability;
-use badge_shared::rules::{RuleBadgeMapping, RuleLoader, RuleValidator};
+use badge_shared::rules::{EventTypeRegistry, RuleBadgeMapping, RuleLoader, RuleValidator};
+use rule_engine::{AggregateResolver, EmbeddedRuleEvaluator, RedisAggregateStore};
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -52,14 +53,20 @@ async fn main() -> Result<()> {
     // 初始化规则组件：RuleBadgeMapping 作为内存缓存存储从数据库加载的规则
     let rule_mapping = Arc::new(RuleBadgeMapping::new());
 
-    // RuleLoader 负责从数据库加载规则并维护到 rule_mapping
-    let rule_loader = Arc::new(RuleLoader::new(
-        db_pool.clone(),
-        "transaction",
-        rule_mapping.clone(),
-        config.rules.refresh_interval_secs,
-        config.rules.initial_load_timeout_secs,
-    ));
+    // 本服务组注册的事件类型（含自定义事件及其负载 Schema）
+    let event_types = Arc::new(EventTypeRegistry::new());
+
+    // RuleLoader 负责从数据库加载规则并维护到 rule_mapping，同时刷新事件类型注册表
+    let rule_loader = Arc::new(
+        RuleLoader::new(
+            db_pool.clone(),
+            "transaction",
+            rule_mapping.clone(),
+            config.rules.refresh_interval_secs,
+            config.rules.initial_load_timeout_secs,
+        )
+        .with_event_type_registry(event_types.clone()),
+    );
 
     // RuleValidator 在发放前校验规则的时间窗口、用户限额、全局配额等条件
     let rule_validator = Arc::new(RuleValidator::new(cache.clone(), db_pool.clone()));
@@ -75,12 +82,24 @@ async fn main() -> Result<()> {
         .clone()
         .start_background_refresh(shutdown_rx.clone());
 
-    let processor = event_transaction_service::processor::TransactionEventProcessor::new(
-        cache,
+    let mut processor = event_transaction_service::processor::TransactionEventProcessor::new(
+        cache.clone(),
         Arc::new(rule_client),
-        rule_mapping,
+        rule_mapping.clone(),
         rule_validator,
-    );
+    )
+    .with_event_type_registry(event_types);
+
+    // 内嵌模式下规则直接在本进程执行，聚合状态与规则引擎服务共用同一个 Redis
+    if config.rules.evaluation_mode == RuleEvaluationMode::Embedded {
+        let aggregates = Arc::new(AggregateResolver::new(Arc::new(RedisAggregateStore::new(
+            cache,
+        ))));
+        let evaluator = Arc::new(EmbeddedRuleEvaluator::new(rule_mapping, aggregates));
+        evaluator.sync();
+        info!(rule_count = evaluator.len(), "规则评估方式：内嵌规则引擎");
+        processor = processor.with_embedded_evaluator(evaluator);
+    }
 
     let consumer = event_transaction_service::consumer::TransactionConsumer::new(
         &config,
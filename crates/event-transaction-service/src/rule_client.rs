@@ -10,6 +10,7 @@ use badge_proto::badge::badge_management_service_client::BadgeManagementServiceC
 use badge_proto::badge::{FindBadgesBySourceRefRequest, GrantBadgeRequest, RevokeBadgeRequest};
 use badge_proto::rule_engine::BatchEvaluateRequest;
 use badge_proto::rule_engine::rule_engine_service_client::RuleEngineServiceClient;
+use badge_shared::observability::middleware::grpc::traced_request;
 use tonic::transport::Channel;
 use tracing::{debug, info, warn};
 
@@ -165,9 +166,12 @@ impl TransactionRuleService for TransactionRuleClient {
         debug!(rule_count = rule_ids.len(), "调用规则引擎 BatchEvaluate");
 
         let mut client = self.rule_engine.clone();
-        let response = client.batch_evaluate(request).await.map_err(|e| {
-            TransactionError::RuleEngineError(format!("BatchEvaluate 调用失败: {e}"))
-        })?;
+        let response = client
+            .batch_evaluate(traced_request(request))
+            .await
+            .map_err(|e| {
+                TransactionError::RuleEngineError(format!("BatchEvaluate 调用失败: {e}"))
+            })?;
 
         let batch_result = response.into_inner();
 
@@ -212,7 +216,7 @@ impl TransactionRuleService for TransactionRuleClient {
 
         let mut client = self.badge_service.clone();
         let response = client
-            .grant_badge(request)
+            .grant_badge(traced_request(request))
             .await
             .map_err(|e| TransactionError::BadgeGrantError(format!("GrantBadge 调用失败: {e}")))?;
 
@@ -260,9 +264,12 @@ impl TransactionRuleService for TransactionRuleClient {
         debug!(user_id, badge_id, quantity, reason, "调用 RevokeBadge");
 
         let mut client = self.badge_service.clone();
-        let response = client.revoke_badge(request).await.map_err(|e| {
-            TransactionError::BadgeRevokeError(format!("RevokeBadge 调用失败: {e}"))
-        })?;
+        let response = client
+            .revoke_badge(traced_request(request))
+            .await
+            .map_err(|e| {
+                TransactionError::BadgeRevokeError(format!("RevokeBadge 调用失败: {e}"))
+            })?;
 
         let revoke_response = response.into_inner();
 
@@ -296,9 +303,12 @@ impl TransactionRuleService for TransactionRuleClient {
         debug!(user_id, source_ref, "调用 FindBadgesBySourceRef");
 
         let mut client = self.badge_service.clone();
-        let response = client.find_badges_by_source_ref(request).await.map_err(|e| {
-            TransactionError::BadgeRevokeError(format!("FindBadgesBySourceRef 调用失败: {e}"))
-        })?;
+        let response = client
+            .find_badges_by_source_ref(traced_request(request))
+            .await
+            .map_err(|e| {
+                TransactionError::BadgeRevokeError(format!("FindBadgesBySourceRef 调用失败: {e}"))
+            })?;
 
         let badges: Vec<SourceRefBadge> = response
             .into_inner()
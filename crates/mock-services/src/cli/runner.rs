@@ -25,7 +25,9 @@ use crate::generators::{DataGenerator, GenerationStats, GeneratorConfig};
 use crate::models::{MockCoupon, MockOrder, MockUser};
 use crate::scenarios::{PredefinedScenarios, Scenario, ScenarioRunner};
 use crate::services::{
-    CouponServiceState, OrderServiceState, ProfileServiceState, coupon_routes, order_routes,
+    BenefitServiceState, CallbackServiceState, CouponServiceState, FulfillmentServiceState,
+    NotificationServiceState, OrderServiceState, ProfileServiceState, benefit_routes,
+    callback_routes, coupon_routes, fulfillment_routes, notification_routes, order_routes,
     profile_routes,
 };
 use crate::store::MemoryStore;
@@ -46,7 +48,7 @@ impl CommandRunner {
 
     /// 执行 server 命令
     ///
-    /// 启动 HTTP REST API 服务器，合并订单、用户、优惠券路由。
+    /// 启动 HTTP REST API 服务器，合并订单、用户、优惠券、权益、外部回调和通知渠道路由。
     /// 支持可选的数据预填充，便于快速开始测试。
     pub async fn run_server(&self, port: u16, populate: bool, user_count: usize) -> Result<()> {
         info!(port, populate, user_count, "启动 Mock 服务");
@@ -55,6 +57,10 @@ impl CommandRunner {
         let order_state = Arc::new(OrderServiceState::new());
         let profile_state = Arc::new(ProfileServiceState::new());
         let coupon_state = Arc::new(CouponServiceState::default());
+        let benefit_state = Arc::new(BenefitServiceState::new());
+        let callback_state = Arc::new(CallbackServiceState::default());
+        let fulfillment_state = Arc::new(FulfillmentServiceState::new());
+        let notification_state = Arc::new(NotificationServiceState::new());
 
         // 预填充测试数据
         if populate {
@@ -93,7 +99,11 @@ impl CommandRunner {
             .route("/ready", get(readiness_check))
             .merge(order_routes().with_state(order_state))
             .merge(profile_routes().with_state(profile_state))
-            .merge(coupon_routes().with_state(coupon_state));
+            .merge(coupon_routes().with_state(coupon_state))
+            .merge(benefit_routes().with_state(benefit_state))
+            .merge(callback_routes().with_state(callback_state))
+            .merge(fulfillment_routes().with_state(fulfillment_state))
+            .merge(notification_routes().with_state(notification_state));
 
         let addr = SocketAddr::from(([0, 0, 0, 0], port));
         let listener = TcpListener::bind(addr).await.context("绑定端口失败")?;
@@ -105,6 +115,10 @@ impl CommandRunner {
         info!("  GET/POST /orders - 订单管理");
         info!("  GET/POST /users - 用户管理");
         info!("  GET/POST /coupons - 优惠券管理");
+        info!("  POST /benefits/grant - 权益发放（积分等）");
+        info!("  POST /callbacks/benefits - 外部回调权益（需签名）");
+        info!("  POST /assets/*, /reservations/*, /memberships/* - 异步履约（资产/预约/会员）");
+        info!("  POST /sms/send, /push/send, /cgi-bin/* - 通知渠道桩");
         info!("按 Ctrl+C 停止服务");
 
         // 启动服务器并等待关闭信号
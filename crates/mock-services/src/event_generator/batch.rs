@@ -80,12 +80,14 @@ impl BatchEventSender {
     /// 根据事件类型选择目标 topic
     ///
     /// 交易类事件需要更严格的处理（如幂等性、回滚），因此独立 topic。
+    /// 身份类和季节类事件需要记录注册日期、校验活动时间窗口，单独投递。
     /// 行为类事件量大但处理相对简单，统一到一个 topic。
     fn select_topic(event_type: &EventType) -> &'static str {
         match event_type {
             EventType::Purchase | EventType::Refund | EventType::OrderCancel => {
                 topics::TRANSACTION_EVENTS
             }
+            t if t.is_identity() || t.is_seasonal() => topics::LIFECYCLE_EVENTS,
             _ => topics::ENGAGEMENT_EVENTS,
         }
     }
@@ -160,9 +162,21 @@ mod tests {
             BatchEventSender::select_topic(&EventType::Review),
             topics::ENGAGEMENT_EVENTS
         );
+    }
+
+    #[test]
+    fn test_select_topic_lifecycle() {
         assert_eq!(
             BatchEventSender::select_topic(&EventType::Registration),
-            topics::ENGAGEMENT_EVENTS
+            topics::LIFECYCLE_EVENTS
+        );
+        assert_eq!(
+            BatchEventSender::select_topic(&EventType::Anniversary),
+            topics::LIFECYCLE_EVENTS
+        );
+        assert_eq!(
+            BatchEventSender::select_topic(&EventType::CampaignParticipation),
+            topics::LIFECYCLE_EVENTS
         );
     }
 
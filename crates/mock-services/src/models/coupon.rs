@@ -42,6 +42,8 @@ pub enum CouponStatus {
     Used,
     /// 已过期
     Expired,
+    /// 已撤销（发放方回收）
+    Revoked,
 }
 
 impl MockCoupon {
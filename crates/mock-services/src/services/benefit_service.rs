@@ -41,6 +41,12 @@ pub struct GrantBenefitRequest {
     pub idempotency_key: Option<String>,
 }
 
+/// 按幂等键撤销权益请求
+#[derive(Debug, Deserialize)]
+pub struct RevokeBenefitRequest {
+    pub idempotency_key: String,
+}
+
 /// 权益服务状态
 #[derive(Default)]
 pub struct BenefitServiceState {
@@ -81,6 +87,12 @@ impl BenefitServiceState {
             .insert(benefit_code.to_string(), count);
     }
 
+    /// 按幂等键获取权益记录
+    pub async fn get_by_idempotency_key(&self, key: &str) -> Option<BenefitGrantRecord> {
+        let grant_id = self.idempotency_cache.read().await.get(key).cloned()?;
+        self.grants.read().await.get(&grant_id).cloned()
+    }
+
     /// 获取用户的所有权益
     pub async fn get_user_benefits(&self, user_id: &str) -> Vec<BenefitGrantRecord> {
         let user_benefits = self.user_benefits.read().await;
@@ -109,9 +121,10 @@ impl BenefitServiceState {
 pub fn benefit_routes() -> Router<Arc<BenefitServiceState>> {
     Router::new()
         .route("/benefits/grant", post(grant_benefit))
-        .route("/benefits/:id", get(get_benefit))
-        .route("/users/:user_id/benefits", get(get_user_benefits))
-        .route("/benefits/:code/stock", get(get_stock))
+        .route("/benefits/revoke", post(revoke_benefit))
+        .route("/benefits/{id}", get(get_benefit))
+        .route("/users/{user_id}/benefits", get(get_user_benefits))
+        .route("/benefits/{code}/stock", get(get_stock))
         .route("/admin/benefits/clear", post(clear_benefits))
         .route("/admin/benefits/simulate-failure", post(set_failure))
         .route("/admin/benefits/stock", post(set_stock))
@@ -209,6 +222,44 @@ async fn grant_benefit(
     (StatusCode::OK, Json(serde_json::to_value(record).unwrap()))
 }
 
+/// 按幂等键撤销权益
+///
+/// 重复撤销返回成功，便于调用方安全重试
+async fn revoke_benefit(
+    State(state): State<Arc<BenefitServiceState>>,
+    Json(req): Json<RevokeBenefitRequest>,
+) -> impl IntoResponse {
+    if *state.simulate_failure.read().await {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({
+                "error": "Service temporarily unavailable"
+            })),
+        );
+    }
+
+    let grant_id = state
+        .idempotency_cache
+        .read()
+        .await
+        .get(&req.idempotency_key)
+        .cloned();
+    let mut grants = state.grants.write().await;
+    match grant_id.and_then(|id| grants.get_mut(&id)) {
+        Some(record) => {
+            record.status = "revoked".to_string();
+            (
+                StatusCode::OK,
+                Json(serde_json::to_value(&*record).unwrap()),
+            )
+        }
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "Benefit grant not found" })),
+        ),
+    }
+}
+
 /// 获取权益详情
 async fn get_benefit(
     State(state): State<Arc<BenefitServiceState>>,
@@ -278,3 +329,64 @@ async fn set_stock(
     }
     StatusCode::OK
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    fn json_request(uri: &str, body: serde_json::Value) -> Request<Body> {
+        Request::builder()
+            .method("POST")
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_vec(&body).unwrap()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_grant_is_idempotent_and_revocable() {
+        let state = Arc::new(BenefitServiceState::new());
+        let app = benefit_routes().with_state(state.clone());
+
+        let grant = serde_json::json!({
+            "user_id": "user-1",
+            "benefit_type": "points",
+            "benefit_code": "general",
+            "value": {"point_amount": 100},
+            "idempotency_key": "GRANT-001"
+        });
+        for _ in 0..2 {
+            let response = app
+                .clone()
+                .oneshot(json_request("/benefits/grant", grant.clone()))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+        assert_eq!(state.get_user_benefits("user-1").await.len(), 1);
+
+        let response = app
+            .clone()
+            .oneshot(json_request(
+                "/benefits/revoke",
+                serde_json::json!({"idempotency_key": "GRANT-001"}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let record = state.get_by_idempotency_key("GRANT-001").await.unwrap();
+        assert_eq!(record.status, "revoked");
+
+        let response = app
+            .oneshot(json_request(
+                "/benefits/revoke",
+                serde_json::json!({"idempotency_key": "UNKNOWN"}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,347 @@
+//! Mock 外部回调服务
+//!
+//! 模拟接收外部回调权益的第三方系统，用于测试签名、重试和异步状态流转。
+//!
+//! 所有动作（grant / query / revoke）通过同一个端点接收，请求需携带
+//! `X-Badge-Timestamp` 和 `X-Badge-Signature` 签名头，签名无效返回 401。
+
+use axum::{
+    Json, Router,
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    routing::{get, post},
+};
+use badge_shared::signature;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use tokio::sync::RwLock;
+
+/// 默认签名密钥（与徽章服务开发环境配置一致）
+pub const DEFAULT_CALLBACK_SECRET: &str = "mock-callback-secret";
+
+/// 回调请求体
+#[derive(Debug, Deserialize)]
+struct CallbackRequest {
+    action: String,
+    grant_no: String,
+    #[serde(default)]
+    user_id: Option<String>,
+}
+
+/// 收到的回调记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReceivedCallback {
+    pub action: Option<String>,
+    pub grant_no: Option<String>,
+    pub signature_valid: bool,
+    pub received_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// 回调发放记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallbackGrant {
+    pub grant_no: String,
+    pub user_id: Option<String>,
+    pub reference: String,
+    pub status: String,
+}
+
+/// 回调服务状态
+pub struct CallbackServiceState {
+    secret: String,
+    grants: RwLock<HashMap<String, CallbackGrant>>,
+    received: RwLock<Vec<ReceivedCallback>>,
+    /// 接下来需要返回 503 的请求数，用于测试重试
+    fail_next: AtomicU32,
+    /// 异步模式：发放返回 PROCESSING，首次查询时完成
+    async_mode: AtomicBool,
+}
+
+impl Default for CallbackServiceState {
+    fn default() -> Self {
+        Self::new(DEFAULT_CALLBACK_SECRET)
+    }
+}
+
+impl CallbackServiceState {
+    pub fn new(secret: impl Into<String>) -> Self {
+        Self {
+            secret: secret.into(),
+            grants: RwLock::new(HashMap::new()),
+            received: RwLock::new(Vec::new()),
+            fail_next: AtomicU32::new(0),
+            async_mode: AtomicBool::new(false),
+        }
+    }
+
+    /// 设置接下来需要失败的请求数
+    pub fn set_fail_next(&self, count: u32) {
+        self.fail_next.store(count, Ordering::SeqCst);
+    }
+
+    /// 设置异步模式
+    pub fn set_async_mode(&self, enabled: bool) {
+        self.async_mode.store(enabled, Ordering::SeqCst);
+    }
+
+    /// 获取收到的全部回调
+    pub async fn received(&self) -> Vec<ReceivedCallback> {
+        self.received.read().await.clone()
+    }
+
+    /// 获取发放记录
+    pub async fn get_grant(&self, grant_no: &str) -> Option<CallbackGrant> {
+        self.grants.read().await.get(grant_no).cloned()
+    }
+
+    /// 清空所有记录
+    pub async fn clear(&self) {
+        self.grants.write().await.clear();
+        self.received.write().await.clear();
+        self.fail_next.store(0, Ordering::SeqCst);
+        self.async_mode.store(false, Ordering::SeqCst);
+    }
+
+    /// 消耗一次失败配额
+    fn take_failure(&self) -> bool {
+        let mut current = self.fail_next.load(Ordering::SeqCst);
+        while current > 0 {
+            match self.fail_next.compare_exchange(
+                current,
+                current - 1,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            ) {
+                Ok(_) => return true,
+                Err(actual) => current = actual,
+            }
+        }
+        false
+    }
+}
+
+/// 创建回调服务路由
+pub fn callback_routes() -> Router<Arc<CallbackServiceState>> {
+    Router::new()
+        .route("/callbacks/benefits", post(handle_callback))
+        .route("/admin/callbacks/received", get(list_received))
+        .route("/admin/callbacks/clear", post(clear_callbacks))
+        .route("/admin/callbacks/simulate", post(set_simulation))
+}
+
+/// 校验请求签名
+fn verify_signature(secret: &str, headers: &HeaderMap, body: &[u8]) -> bool {
+    let timestamp = headers
+        .get(signature::TIMESTAMP_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<i64>().ok());
+    let sig = headers
+        .get(signature::SIGNATURE_HEADER)
+        .and_then(|v| v.to_str().ok());
+
+    match (timestamp, sig) {
+        (Some(ts), Some(sig)) => signature::verify(
+            secret,
+            ts,
+            body,
+            sig,
+            chrono::Utc::now().timestamp(),
+            signature::DEFAULT_TOLERANCE_SECS,
+        ),
+        _ => false,
+    }
+}
+
+fn status_response(grant: &CallbackGrant) -> (StatusCode, Json<serde_json::Value>) {
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "status": grant.status,
+            "reference": grant.reference,
+        })),
+    )
+}
+
+/// 处理回调请求
+async fn handle_callback(
+    State(state): State<Arc<CallbackServiceState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> impl IntoResponse {
+    let signature_valid = verify_signature(&state.secret, &headers, &body);
+    let request: Option<CallbackRequest> = serde_json::from_slice(&body).ok();
+
+    state.received.write().await.push(ReceivedCallback {
+        action: request.as_ref().map(|r| r.action.clone()),
+        grant_no: request.as_ref().map(|r| r.grant_no.clone()),
+        signature_valid,
+        received_at: chrono::Utc::now(),
+    });
+
+    if !signature_valid {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(serde_json::json!({ "error": "Invalid signature" })),
+        );
+    }
+
+    if state.take_failure() {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": "Service temporarily unavailable" })),
+        );
+    }
+
+    let Some(request) = request else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "Invalid request body" })),
+        );
+    };
+
+    let mut grants = state.grants.write().await;
+    match request.action.as_str() {
+        "grant" => {
+            // 同一流水号重复发放返回原结果
+            let async_mode = state.async_mode.load(Ordering::SeqCst);
+            let grant = grants
+                .entry(request.grant_no.clone())
+                .or_insert_with(|| CallbackGrant {
+                    grant_no: request.grant_no.clone(),
+                    user_id: request.user_id.clone(),
+                    reference: format!("CB-{}", uuid::Uuid::new_v4().simple()),
+                    status: if async_mode { "PROCESSING" } else { "SUCCESS" }.to_string(),
+                });
+            status_response(grant)
+        }
+        "query" | "revoke" => match grants.get_mut(&request.grant_no) {
+            Some(grant) => {
+                if request.action == "revoke" {
+                    grant.status = "REVOKED".to_string();
+                } else if grant.status == "PROCESSING" {
+                    grant.status = "SUCCESS".to_string();
+                }
+                status_response(grant)
+            }
+            None => (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({ "error": "Grant not found" })),
+            ),
+        },
+        other => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": format!("Unknown action: {}", other) })),
+        ),
+    }
+}
+
+/// 获取收到的回调（测试用）
+async fn list_received(State(state): State<Arc<CallbackServiceState>>) -> impl IntoResponse {
+    Json(state.received().await)
+}
+
+/// 清空回调记录（测试用）
+async fn clear_callbacks(State(state): State<Arc<CallbackServiceState>>) -> impl IntoResponse {
+    state.clear().await;
+    StatusCode::OK
+}
+
+/// 设置失败模拟和异步模式（测试用）
+async fn set_simulation(
+    State(state): State<Arc<CallbackServiceState>>,
+    Json(req): Json<serde_json::Value>,
+) -> impl IntoResponse {
+    if let Some(count) = req.get("fail_next").and_then(|v| v.as_u64()) {
+        state.set_fail_next(count as u32);
+    }
+    if let Some(enabled) = req.get("async_mode").and_then(|v| v.as_bool()) {
+        state.set_async_mode(enabled);
+    }
+    StatusCode::OK
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    fn signed_request(secret: &str, body: serde_json::Value) -> Request<Body> {
+        let body = serde_json::to_vec(&body).unwrap();
+        let ts = chrono::Utc::now().timestamp();
+        Request::builder()
+            .method("POST")
+            .uri("/callbacks/benefits")
+            .header("content-type", "application/json")
+            .header(signature::TIMESTAMP_HEADER, ts.to_string())
+            .header(signature::SIGNATURE_HEADER, signature::sign(secret, ts, &body))
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_rejects_invalid_signature() {
+        let state = Arc::new(CallbackServiceState::default());
+        let app = callback_routes().with_state(state.clone());
+
+        let response = app
+            .oneshot(signed_request(
+                "wrong-secret",
+                serde_json::json!({"action": "grant", "grant_no": "G-1"}),
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert!(!state.received().await[0].signature_valid);
+        assert!(state.get_grant("G-1").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_async_grant_completes_on_query() {
+        let state = Arc::new(CallbackServiceState::default());
+        state.set_async_mode(true);
+        let app = callback_routes().with_state(state.clone());
+
+        let grant = serde_json::json!({"action": "grant", "grant_no": "G-1", "user_id": "u-1"});
+        let response = app
+            .clone()
+            .oneshot(signed_request(DEFAULT_CALLBACK_SECRET, grant))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(state.get_grant("G-1").await.unwrap().status, "PROCESSING");
+
+        let query = serde_json::json!({"action": "query", "grant_no": "G-1"});
+        app.oneshot(signed_request(DEFAULT_CALLBACK_SECRET, query))
+            .await
+            .unwrap();
+        assert_eq!(state.get_grant("G-1").await.unwrap().status, "SUCCESS");
+    }
+
+    #[tokio::test]
+    async fn test_simulated_failures() {
+        let state = Arc::new(CallbackServiceState::default());
+        state.set_fail_next(1);
+        let app = callback_routes().with_state(state.clone());
+
+        let body = serde_json::json!({"action": "grant", "grant_no": "G-1"});
+        let first = app
+            .clone()
+            .oneshot(signed_request(DEFAULT_CALLBACK_SECRET, body.clone()))
+            .await
+            .unwrap();
+        let second = app
+            .oneshot(signed_request(DEFAULT_CALLBACK_SECRET, body))
+            .await
+            .unwrap();
+
+        assert_eq!(first.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(second.status(), StatusCode::OK);
+    }
+}
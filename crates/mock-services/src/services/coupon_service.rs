@@ -20,6 +20,8 @@ use crate::store::MemoryStore;
 #[derive(Default)]
 pub struct CouponServiceState {
     pub coupons: MemoryStore<MockCoupon>,
+    /// 幂等键 -> 优惠券 ID
+    pub idempotency_keys: MemoryStore<String>,
 }
 
 // ============================================================================
@@ -27,14 +29,37 @@ pub struct CouponServiceState {
 // ============================================================================
 
 /// 发放优惠券请求
+///
+/// 按模板发券时（携带 `template_id`）券面参数可省略，使用模板默认值
 #[derive(Debug, Deserialize)]
 pub struct IssueCouponRequest {
     pub user_id: String,
+    #[serde(default)]
+    pub template_id: Option<String>,
+    #[serde(default = "default_coupon_type")]
     pub coupon_type: CouponType,
+    #[serde(default = "default_discount_value")]
     pub discount_value: f64,
+    #[serde(default)]
     pub min_order_amount: f64,
     /// 有效期天数
+    #[serde(default = "default_valid_days")]
     pub valid_days: i64,
+    /// 幂等键，重复请求返回首次发放的优惠券
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
+}
+
+fn default_coupon_type() -> CouponType {
+    CouponType::FixedAmount
+}
+
+fn default_discount_value() -> f64 {
+    10.0
+}
+
+fn default_valid_days() -> i64 {
+    30
 }
 
 /// 批量发放优惠券请求
@@ -47,6 +72,12 @@ pub struct BatchIssueCouponRequest {
     pub valid_days: i64,
 }
 
+/// 按幂等键撤销优惠券请求
+#[derive(Debug, Deserialize)]
+pub struct RevokeCouponRequest {
+    pub idempotency_key: String,
+}
+
 /// 核销优惠券请求
 #[derive(Debug, Deserialize)]
 pub struct RedeemCouponRequest {
@@ -106,6 +137,7 @@ pub fn coupon_routes() -> Router<Arc<CouponServiceState>> {
         .route("/coupons/{coupon_id}/redeem", post(redeem_coupon))
         .route("/users/{user_id}/coupons", get(list_user_coupons))
         .route("/coupons/batch", post(batch_issue_coupons))
+        .route("/coupons/revoke", post(revoke_coupon))
 }
 
 // ============================================================================
@@ -134,6 +166,8 @@ async fn get_coupon(
 }
 
 /// 发放优惠券
+///
+/// 携带幂等键时，重复请求返回 200 和首次发放的优惠券
 #[tracing::instrument(skip(state))]
 async fn issue_coupon(
     State(state): State<Arc<CouponServiceState>>,
@@ -141,13 +175,65 @@ async fn issue_coupon(
 ) -> (StatusCode, Json<CouponResponse>) {
     tracing::info!("发放优惠券给用户: {}", req.user_id);
 
+    if let Some(coupon) = req
+        .idempotency_key
+        .as_deref()
+        .and_then(|key| state.idempotency_keys.get(key))
+        .and_then(|coupon_id| state.coupons.get(&coupon_id))
+    {
+        tracing::info!("幂等键已存在，返回已发放的优惠券: {}", coupon.coupon_id);
+        return (StatusCode::OK, Json(CouponResponse { coupon }));
+    }
+
     let coupon = create_coupon(&req.user_id, &req);
     state.coupons.insert(&coupon.coupon_id, coupon.clone());
+    if let Some(key) = &req.idempotency_key {
+        state.idempotency_keys.insert(key, coupon.coupon_id.clone());
+    }
 
     tracing::info!("优惠券发放成功: {}", coupon.coupon_id);
     (StatusCode::CREATED, Json(CouponResponse { coupon }))
 }
 
+/// 按幂等键撤销优惠券
+///
+/// 已撤销的优惠券重复撤销返回成功，已核销的优惠券无法撤销
+#[tracing::instrument(skip(state))]
+async fn revoke_coupon(
+    State(state): State<Arc<CouponServiceState>>,
+    Json(req): Json<RevokeCouponRequest>,
+) -> Result<Json<CouponResponse>, (StatusCode, Json<ErrorResponse>)> {
+    tracing::info!("撤销优惠券, 幂等键: {}", req.idempotency_key);
+
+    let Some(mut coupon) = state
+        .idempotency_keys
+        .get(&req.idempotency_key)
+        .and_then(|coupon_id| state.coupons.get(&coupon_id))
+    else {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("优惠券不存在: {}", req.idempotency_key),
+            }),
+        ));
+    };
+
+    if coupon.status == CouponStatus::Used {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ErrorResponse {
+                error: "优惠券已核销，无法撤销".to_string(),
+            }),
+        ));
+    }
+
+    coupon.status = CouponStatus::Revoked;
+    state.coupons.insert(&coupon.coupon_id, coupon.clone());
+
+    tracing::info!("优惠券撤销成功: {}", coupon.coupon_id);
+    Ok(Json(CouponResponse { coupon }))
+}
+
 /// 核销优惠券
 ///
 /// 核销流程：
@@ -249,10 +335,12 @@ async fn batch_issue_coupons(
 
     let issue_req = IssueCouponRequest {
         user_id: String::new(),
+        template_id: None,
         coupon_type: req.coupon_type,
         discount_value: req.discount_value,
         min_order_amount: req.min_order_amount,
         valid_days: req.valid_days,
+        idempotency_key: None,
     };
 
     let mut coupon_ids = Vec::with_capacity(req.user_ids.len());
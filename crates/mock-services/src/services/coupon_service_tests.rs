@@ -315,3 +315,92 @@ async fn test_batch_issue() {
     assert_eq!(resp.issued_count, 3);
     assert_eq!(resp.coupon_ids.len(), 3);
 }
+
+fn json_request(uri: &str, body: serde_json::Value) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_string(&body).unwrap()))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_issue_coupon_idempotent() {
+    let state = Arc::new(CouponServiceState::default());
+    let app = create_test_app_with_state(state.clone());
+
+    let req_body = serde_json::json!({
+        "user_id": "user-123",
+        "template_id": "tpl-001",
+        "idempotency_key": "GRANT-001"
+    });
+
+    let first = app
+        .clone()
+        .oneshot(json_request("/coupons", req_body.clone()))
+        .await
+        .unwrap();
+    assert_eq!(first.status(), StatusCode::CREATED);
+    let body = axum::body::to_bytes(first.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let first: CouponResponse = serde_json::from_slice(&body).unwrap();
+
+    let second = app
+        .oneshot(json_request("/coupons", req_body))
+        .await
+        .unwrap();
+    assert_eq!(second.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(second.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let second: CouponResponse = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(first.coupon.coupon_id, second.coupon.coupon_id);
+    assert_eq!(state.coupons.count(), 1);
+}
+
+#[tokio::test]
+async fn test_revoke_coupon_by_idempotency_key() {
+    let state = Arc::new(CouponServiceState::default());
+    let app = create_test_app_with_state(state.clone());
+
+    app.clone()
+        .oneshot(json_request(
+            "/coupons",
+            serde_json::json!({"user_id": "user-123", "idempotency_key": "GRANT-002"}),
+        ))
+        .await
+        .unwrap();
+
+    let revoke = serde_json::json!({"idempotency_key": "GRANT-002"});
+    let response = app
+        .clone()
+        .oneshot(json_request("/coupons/revoke", revoke.clone()))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let resp: CouponResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(resp.coupon.status, CouponStatus::Revoked);
+
+    // 重复撤销幂等
+    let response = app
+        .clone()
+        .oneshot(json_request("/coupons/revoke", revoke))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .oneshot(json_request(
+            "/coupons/revoke",
+            serde_json::json!({"idempotency_key": "UNKNOWN"}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,458 @@
+//! Mock 履约服务
+//!
+//! 模拟数字资产、预约资格、会员权益三类异步履约系统，用于测试徽章服务的
+//! 异步发放、状态查询和撤销流程。
+//!
+//! 三类资源的接口形状一致：提交（返回 PROCESSING）、查询、撤销，
+//! 均以请求中的 `idempotency_key`（徽章服务的发放流水号）定位记录。
+//! 提交后的首次查询即完成履约，除非通过管理端点开启了挂起模式。
+
+use axum::{
+    Json, Router,
+    extract::State,
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{MethodRouter, get, post},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use tokio::sync::RwLock;
+
+/// 履约资源类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FulfillmentKind {
+    /// 数字资产铸造
+    Asset,
+    /// 预约名额占用
+    Reservation,
+    /// 会员开通
+    Membership,
+}
+
+impl FulfillmentKind {
+    fn reference_prefix(self) -> &'static str {
+        match self {
+            Self::Asset => "MINT",
+            Self::Reservation => "RSV",
+            Self::Membership => "MBR",
+        }
+    }
+}
+
+/// 提交请求，其余业务字段（资产 ID、会员等级等）原样记录
+#[derive(Debug, Deserialize)]
+struct SubmitRequest {
+    idempotency_key: String,
+    user_id: String,
+    #[serde(flatten)]
+    details: serde_json::Value,
+}
+
+/// 查询和撤销请求
+#[derive(Debug, Deserialize)]
+struct KeyRequest {
+    idempotency_key: String,
+}
+
+/// 履约记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FulfillmentRecord {
+    pub kind: FulfillmentKind,
+    pub idempotency_key: String,
+    pub user_id: String,
+    pub reference: String,
+    pub status: String,
+    pub details: serde_json::Value,
+}
+
+/// 履约服务状态
+pub struct FulfillmentServiceState {
+    records: RwLock<HashMap<(FulfillmentKind, String), FulfillmentRecord>>,
+    /// 接下来需要返回 503 的请求数，用于测试重试
+    fail_next: AtomicU32,
+    /// 挂起模式：查询时保持 PROCESSING，用于测试长时间未确认的场景
+    hold: AtomicBool,
+}
+
+impl Default for FulfillmentServiceState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FulfillmentServiceState {
+    pub fn new() -> Self {
+        Self {
+            records: RwLock::new(HashMap::new()),
+            fail_next: AtomicU32::new(0),
+            hold: AtomicBool::new(false),
+        }
+    }
+
+    /// 设置接下来需要失败的请求数
+    pub fn set_fail_next(&self, count: u32) {
+        self.fail_next.store(count, Ordering::SeqCst);
+    }
+
+    /// 设置挂起模式
+    pub fn set_hold(&self, enabled: bool) {
+        self.hold.store(enabled, Ordering::SeqCst);
+    }
+
+    /// 获取履约记录
+    pub async fn get_record(
+        &self,
+        kind: FulfillmentKind,
+        idempotency_key: &str,
+    ) -> Option<FulfillmentRecord> {
+        self.records
+            .read()
+            .await
+            .get(&(kind, idempotency_key.to_string()))
+            .cloned()
+    }
+
+    /// 获取全部履约记录
+    pub async fn list_records(&self) -> Vec<FulfillmentRecord> {
+        self.records.read().await.values().cloned().collect()
+    }
+
+    /// 清空所有记录
+    pub async fn clear(&self) {
+        self.records.write().await.clear();
+        self.fail_next.store(0, Ordering::SeqCst);
+        self.hold.store(false, Ordering::SeqCst);
+    }
+
+    /// 消耗一次失败配额
+    fn take_failure(&self) -> bool {
+        let mut current = self.fail_next.load(Ordering::SeqCst);
+        while current > 0 {
+            match self.fail_next.compare_exchange(
+                current,
+                current - 1,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            ) {
+                Ok(_) => return true,
+                Err(actual) => current = actual,
+            }
+        }
+        false
+    }
+}
+
+/// 创建履约服务路由
+pub fn fulfillment_routes() -> Router<Arc<FulfillmentServiceState>> {
+    Router::new()
+        .route("/assets/mint", submit_route(FulfillmentKind::Asset))
+        .route("/assets/query", query_route(FulfillmentKind::Asset))
+        .route("/assets/reclaim", cancel_route(FulfillmentKind::Asset))
+        .route(
+            "/reservations/hold",
+            submit_route(FulfillmentKind::Reservation),
+        )
+        .route(
+            "/reservations/query",
+            query_route(FulfillmentKind::Reservation),
+        )
+        .route(
+            "/reservations/release",
+            cancel_route(FulfillmentKind::Reservation),
+        )
+        .route(
+            "/memberships/activate",
+            submit_route(FulfillmentKind::Membership),
+        )
+        .route(
+            "/memberships/query",
+            query_route(FulfillmentKind::Membership),
+        )
+        .route(
+            "/memberships/deactivate",
+            cancel_route(FulfillmentKind::Membership),
+        )
+        .route("/admin/fulfillment/records", get(list_records))
+        .route("/admin/fulfillment/clear", post(clear_records))
+        .route("/admin/fulfillment/simulate", post(set_simulation))
+}
+
+fn submit_route(kind: FulfillmentKind) -> MethodRouter<Arc<FulfillmentServiceState>> {
+    post(
+        move |State(state): State<Arc<FulfillmentServiceState>>, Json(req): Json<SubmitRequest>| async move {
+            submit(&state, kind, req).await
+        },
+    )
+}
+
+fn query_route(kind: FulfillmentKind) -> MethodRouter<Arc<FulfillmentServiceState>> {
+    post(
+        move |State(state): State<Arc<FulfillmentServiceState>>, Json(req): Json<KeyRequest>| async move {
+            query(&state, kind, req).await
+        },
+    )
+}
+
+fn cancel_route(kind: FulfillmentKind) -> MethodRouter<Arc<FulfillmentServiceState>> {
+    post(
+        move |State(state): State<Arc<FulfillmentServiceState>>, Json(req): Json<KeyRequest>| async move {
+            cancel(&state, kind, req).await
+        },
+    )
+}
+
+type ApiResponse = (StatusCode, Json<serde_json::Value>);
+
+fn status_response(record: &FulfillmentRecord) -> ApiResponse {
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "reference": record.reference,
+            "status": record.status,
+        })),
+    )
+}
+
+fn unavailable() -> ApiResponse {
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        Json(serde_json::json!({ "error": "Service temporarily unavailable" })),
+    )
+}
+
+fn not_found() -> ApiResponse {
+    (
+        StatusCode::NOT_FOUND,
+        Json(serde_json::json!({ "error": "Record not found" })),
+    )
+}
+
+/// 提交履约请求，同一幂等键重复提交返回原记录
+async fn submit(
+    state: &FulfillmentServiceState,
+    kind: FulfillmentKind,
+    req: SubmitRequest,
+) -> ApiResponse {
+    if state.take_failure() {
+        return unavailable();
+    }
+
+    let mut records = state.records.write().await;
+    let record = records
+        .entry((kind, req.idempotency_key.clone()))
+        .or_insert_with(|| FulfillmentRecord {
+            kind,
+            idempotency_key: req.idempotency_key,
+            user_id: req.user_id,
+            reference: format!(
+                "{}-{}",
+                kind.reference_prefix(),
+                uuid::Uuid::new_v4().simple()
+            ),
+            status: "PROCESSING".to_string(),
+            details: req.details,
+        });
+    status_response(record)
+}
+
+/// 查询履约状态，非挂起模式下处理中的记录在查询时完成
+async fn query(
+    state: &FulfillmentServiceState,
+    kind: FulfillmentKind,
+    req: KeyRequest,
+) -> ApiResponse {
+    if state.take_failure() {
+        return unavailable();
+    }
+
+    let hold = state.hold.load(Ordering::SeqCst);
+    let mut records = state.records.write().await;
+    match records.get_mut(&(kind, req.idempotency_key)) {
+        Some(record) => {
+            if record.status == "PROCESSING" && !hold {
+                record.status = "SUCCESS".to_string();
+            }
+            status_response(record)
+        }
+        None => not_found(),
+    }
+}
+
+/// 撤销履约（回收资产、释放名额、回退会员），重复撤销返回 REVOKED
+async fn cancel(
+    state: &FulfillmentServiceState,
+    kind: FulfillmentKind,
+    req: KeyRequest,
+) -> ApiResponse {
+    if state.take_failure() {
+        return unavailable();
+    }
+
+    let mut records = state.records.write().await;
+    match records.get_mut(&(kind, req.idempotency_key)) {
+        Some(record) => {
+            record.status = "REVOKED".to_string();
+            status_response(record)
+        }
+        None => not_found(),
+    }
+}
+
+/// 获取全部履约记录（测试用）
+async fn list_records(State(state): State<Arc<FulfillmentServiceState>>) -> impl IntoResponse {
+    Json(state.list_records().await)
+}
+
+/// 清空履约记录（测试用）
+async fn clear_records(State(state): State<Arc<FulfillmentServiceState>>) -> impl IntoResponse {
+    state.clear().await;
+    StatusCode::OK
+}
+
+/// 设置失败模拟和挂起模式（测试用）
+async fn set_simulation(
+    State(state): State<Arc<FulfillmentServiceState>>,
+    Json(req): Json<serde_json::Value>,
+) -> impl IntoResponse {
+    if let Some(count) = req.get("fail_next").and_then(|v| v.as_u64()) {
+        state.set_fail_next(count as u32);
+    }
+    if let Some(enabled) = req.get("hold").and_then(|v| v.as_bool()) {
+        state.set_hold(enabled);
+    }
+    StatusCode::OK
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    fn json_request(uri: &str, body: serde_json::Value) -> Request<Body> {
+        Request::builder()
+            .method("POST")
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_vec(&body).unwrap()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_submit_is_idempotent_and_completes_on_query() {
+        let state = Arc::new(FulfillmentServiceState::new());
+        let app = fulfillment_routes().with_state(state.clone());
+
+        let mint = serde_json::json!({
+            "idempotency_key": "G-1",
+            "user_id": "u-1",
+            "asset_id": "NFT-001"
+        });
+        for _ in 0..2 {
+            let response = app
+                .clone()
+                .oneshot(json_request("/assets/mint", mint.clone()))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+        assert_eq!(state.list_records().await.len(), 1);
+
+        let record = state
+            .get_record(FulfillmentKind::Asset, "G-1")
+            .await
+            .unwrap();
+        assert_eq!(record.status, "PROCESSING");
+        assert!(record.reference.starts_with("MINT-"));
+        assert_eq!(record.details["asset_id"], "NFT-001");
+
+        app.oneshot(json_request(
+            "/assets/query",
+            serde_json::json!({"idempotency_key": "G-1"}),
+        ))
+        .await
+        .unwrap();
+        let record = state
+            .get_record(FulfillmentKind::Asset, "G-1")
+            .await
+            .unwrap();
+        assert_eq!(record.status, "SUCCESS");
+    }
+
+    #[tokio::test]
+    async fn test_hold_keeps_processing_and_cancel_revokes() {
+        let state = Arc::new(FulfillmentServiceState::new());
+        state.set_hold(true);
+        let app = fulfillment_routes().with_state(state.clone());
+
+        app.clone()
+            .oneshot(json_request(
+                "/memberships/activate",
+                serde_json::json!({"idempotency_key": "G-1", "user_id": "u-1"}),
+            ))
+            .await
+            .unwrap();
+        app.clone()
+            .oneshot(json_request(
+                "/memberships/query",
+                serde_json::json!({"idempotency_key": "G-1"}),
+            ))
+            .await
+            .unwrap();
+        let record = state
+            .get_record(FulfillmentKind::Membership, "G-1")
+            .await
+            .unwrap();
+        assert_eq!(record.status, "PROCESSING");
+
+        let response = app
+            .clone()
+            .oneshot(json_request(
+                "/memberships/deactivate",
+                serde_json::json!({"idempotency_key": "G-1"}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let record = state
+            .get_record(FulfillmentKind::Membership, "G-1")
+            .await
+            .unwrap();
+        assert_eq!(record.status, "REVOKED");
+
+        // 不同资源类型的记录互不可见
+        let response = app
+            .oneshot(json_request(
+                "/reservations/release",
+                serde_json::json!({"idempotency_key": "G-1"}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_simulated_failures() {
+        let state = Arc::new(FulfillmentServiceState::new());
+        state.set_fail_next(1);
+        let app = fulfillment_routes().with_state(state.clone());
+
+        let body = serde_json::json!({"idempotency_key": "G-1", "user_id": "u-1"});
+        let first = app
+            .clone()
+            .oneshot(json_request("/reservations/hold", body.clone()))
+            .await
+            .unwrap();
+        let second = app
+            .oneshot(json_request("/reservations/hold", body))
+            .await
+            .unwrap();
+
+        assert_eq!(first.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(second.status(), StatusCode::OK);
+    }
+}
@@ -1,19 +1,34 @@
 //! Mock 通知服务
 //!
 //! 模拟通知发送服务，用于测试通知功能。
+//!
+//! 同时提供通知工作者对接的第三方渠道桩：
+//!
+//! - `POST /sms/send`：通用短信网关
+//! - `GET /cgi-bin/token`、`POST /cgi-bin/message/subscribe/send`：微信 access_token 与订阅消息
+//! - `POST /push/send`：FCM 风格的 HTTP 推送
+//!
+//! 渠道桩要求 `Authorization: Bearer <key>` 或有效的 access_token，
+//! 可通过 `fail_next` 让接下来的若干请求返回 503，用于测试重试分类。
 
 use axum::{
     Json, Router,
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
     response::IntoResponse,
     routing::{get, post},
 };
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
 use tokio::sync::RwLock;
 
+/// 短信网关和推送服务的默认 API 密钥
+pub const DEFAULT_PROVIDER_API_KEY: &str = "mock-provider-key";
+/// 微信桩接受的 AppSecret
+pub const DEFAULT_WECHAT_APP_SECRET: &str = "mock-wechat-secret";
+
 /// 通知记录
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NotificationRecord {
@@ -40,6 +55,19 @@ pub struct SendNotificationRequest {
     pub metadata: serde_json::Value,
 }
 
+/// 渠道桩收到的消息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderMessage {
+    /// 渠道：sms / wechat / push
+    pub provider: String,
+    /// 手机号、openid 或设备 token
+    pub recipient: String,
+    pub title: Option<String>,
+    pub content: String,
+    pub message_id: String,
+    pub received_at: chrono::DateTime<chrono::Utc>,
+}
+
 /// 通知服务状态
 #[derive(Default)]
 pub struct NotificationServiceState {
@@ -51,6 +79,14 @@ pub struct NotificationServiceState {
     simulate_failure: RwLock<bool>,
     /// 失败的渠道
     failing_channels: RwLock<Vec<String>>,
+    /// 渠道桩收到的消息
+    provider_messages: RwLock<Vec<ProviderMessage>>,
+    /// 已签发且仍有效的微信 access_token
+    access_tokens: RwLock<HashSet<String>>,
+    /// 累计签发的 access_token 数量，用于验证调用方缓存
+    tokens_issued: AtomicU32,
+    /// 接下来需要返回 503 的渠道桩请求数
+    provider_fail_next: AtomicU32,
 }
 
 impl NotificationServiceState {
@@ -83,10 +119,69 @@ impl NotificationServiceState {
             .unwrap_or_default()
     }
 
+    /// 设置接下来需要失败的渠道桩请求数
+    pub fn set_provider_fail_next(&self, count: u32) {
+        self.provider_fail_next.store(count, Ordering::SeqCst);
+    }
+
+    /// 获取渠道桩收到的全部消息
+    pub async fn provider_messages(&self) -> Vec<ProviderMessage> {
+        self.provider_messages.read().await.clone()
+    }
+
+    /// 累计签发的 access_token 数量
+    pub fn tokens_issued(&self) -> u32 {
+        self.tokens_issued.load(Ordering::SeqCst)
+    }
+
+    /// 使所有已签发的 access_token 失效，模拟令牌过期
+    pub async fn expire_access_tokens(&self) {
+        self.access_tokens.write().await.clear();
+    }
+
     /// 清空所有通知
     pub async fn clear(&self) {
         self.notifications.write().await.clear();
         self.user_notifications.write().await.clear();
+        self.provider_messages.write().await.clear();
+        self.access_tokens.write().await.clear();
+        self.provider_fail_next.store(0, Ordering::SeqCst);
+    }
+
+    /// 消耗一次渠道桩失败配额
+    fn take_provider_failure(&self) -> bool {
+        let mut current = self.provider_fail_next.load(Ordering::SeqCst);
+        while current > 0 {
+            match self.provider_fail_next.compare_exchange(
+                current,
+                current - 1,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            ) {
+                Ok(_) => return true,
+                Err(actual) => current = actual,
+            }
+        }
+        false
+    }
+
+    async fn record_provider_message(
+        &self,
+        provider: &str,
+        recipient: &str,
+        title: Option<String>,
+        content: String,
+    ) -> String {
+        let message_id = format!("{}-{}", provider, uuid::Uuid::now_v7().simple());
+        self.provider_messages.write().await.push(ProviderMessage {
+            provider: provider.to_string(),
+            recipient: recipient.to_string(),
+            title,
+            content,
+            message_id: message_id.clone(),
+            received_at: chrono::Utc::now(),
+        });
+        message_id
     }
 }
 
@@ -94,10 +189,18 @@ impl NotificationServiceState {
 pub fn notification_routes() -> Router<Arc<NotificationServiceState>> {
     Router::new()
         .route("/notifications", post(send_notification))
-        .route("/notifications/:id", get(get_notification))
-        .route("/users/:user_id/notifications", get(get_user_notifications))
+        .route("/notifications/{id}", get(get_notification))
+        .route(
+            "/users/{user_id}/notifications",
+            get(get_user_notifications),
+        )
         .route("/admin/notifications/clear", post(clear_notifications))
         .route("/admin/notifications/simulate-failure", post(set_failure))
+        .route("/sms/send", post(send_sms))
+        .route("/cgi-bin/token", get(issue_wechat_token))
+        .route("/cgi-bin/message/subscribe/send", post(send_wechat_message))
+        .route("/push/send", post(send_push))
+        .route("/admin/providers/messages", get(list_provider_messages))
 }
 
 /// 发送通知
@@ -207,5 +310,328 @@ async fn set_failure(
             .collect();
         state.set_failing_channels(channels).await;
     }
+    if let Some(count) = req.get("provider_fail_next").and_then(|v| v.as_u64()) {
+        state.set_provider_fail_next(count as u32);
+    }
     StatusCode::OK
 }
+
+/// 校验 Bearer API 密钥
+fn authorized(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        == Some(DEFAULT_PROVIDER_API_KEY)
+}
+
+fn unavailable() -> (StatusCode, Json<serde_json::Value>) {
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        Json(serde_json::json!({ "error": "Service temporarily unavailable" })),
+    )
+}
+
+fn unauthorized() -> (StatusCode, Json<serde_json::Value>) {
+    (
+        StatusCode::UNAUTHORIZED,
+        Json(serde_json::json!({ "error": "Invalid API key" })),
+    )
+}
+
+/// 短信网关请求体
+#[derive(Debug, Deserialize)]
+struct SmsRequest {
+    phone: String,
+    content: String,
+    #[serde(default)]
+    sign_name: Option<String>,
+}
+
+/// 短信发送
+///
+/// 手机号必须为 11 位数字，否则返回 400
+async fn send_sms(
+    State(state): State<Arc<NotificationServiceState>>,
+    headers: HeaderMap,
+    Json(req): Json<SmsRequest>,
+) -> impl IntoResponse {
+    if !authorized(&headers) {
+        return unauthorized();
+    }
+    if state.take_provider_failure() {
+        return unavailable();
+    }
+    if req.phone.len() != 11 || !req.phone.chars().all(|c| c.is_ascii_digit()) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "code": "INVALID_PHONE", "message": "Invalid phone number" })),
+        );
+    }
+
+    let content = match req.sign_name {
+        Some(sign) => format!("【{}】{}", sign, req.content),
+        None => req.content,
+    };
+    let message_id = state
+        .record_provider_message("sms", &req.phone, None, content)
+        .await;
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({ "code": "OK", "message_id": message_id })),
+    )
+}
+
+/// 微信 access_token 请求参数
+#[derive(Debug, Deserialize)]
+struct TokenQuery {
+    appid: String,
+    secret: String,
+}
+
+/// 签发微信 access_token
+///
+/// 与微信接口一致：HTTP 状态码恒为 200，错误通过 errcode 表示
+async fn issue_wechat_token(
+    State(state): State<Arc<NotificationServiceState>>,
+    Query(query): Query<TokenQuery>,
+) -> impl IntoResponse {
+    if query.secret != DEFAULT_WECHAT_APP_SECRET {
+        return Json(serde_json::json!({ "errcode": 40125, "errmsg": "invalid appsecret" }));
+    }
+
+    let token = format!("token-{}-{}", query.appid, uuid::Uuid::now_v7().simple());
+    state.access_tokens.write().await.insert(token.clone());
+    state.tokens_issued.fetch_add(1, Ordering::SeqCst);
+    Json(serde_json::json!({ "access_token": token, "expires_in": 7200 }))
+}
+
+/// 微信订阅消息请求参数
+#[derive(Debug, Deserialize)]
+struct AccessTokenQuery {
+    access_token: String,
+}
+
+/// 微信订阅消息请求体
+#[derive(Debug, Deserialize)]
+struct SubscribeMessageRequest {
+    touser: String,
+    template_id: String,
+    #[serde(default)]
+    data: serde_json::Value,
+}
+
+/// 发送微信订阅消息
+///
+/// openid 以 `blocked` 开头时模拟用户拒收（errcode 43101）
+async fn send_wechat_message(
+    State(state): State<Arc<NotificationServiceState>>,
+    Query(query): Query<AccessTokenQuery>,
+    Json(req): Json<SubscribeMessageRequest>,
+) -> impl IntoResponse {
+    if state.take_provider_failure() {
+        return Json(serde_json::json!({ "errcode": -1, "errmsg": "system error" }));
+    }
+    if !state
+        .access_tokens
+        .read()
+        .await
+        .contains(&query.access_token)
+    {
+        return Json(serde_json::json!({ "errcode": 42001, "errmsg": "access_token expired" }));
+    }
+    if req.touser.starts_with("blocked") {
+        return Json(
+            serde_json::json!({ "errcode": 43101, "errmsg": "user refuse to accept the msg" }),
+        );
+    }
+
+    let content = serde_json::json!({ "template_id": req.template_id, "data": req.data });
+    let message_id = state
+        .record_provider_message("wechat", &req.touser, None, content.to_string())
+        .await;
+    Json(serde_json::json!({ "errcode": 0, "errmsg": "ok", "msgid": message_id }))
+}
+
+/// 推送请求体（FCM HTTP v1 风格）
+#[derive(Debug, Deserialize)]
+struct PushRequest {
+    message: PushMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct PushMessage {
+    token: String,
+    notification: PushNotification,
+}
+
+#[derive(Debug, Deserialize)]
+struct PushNotification {
+    title: String,
+    body: String,
+}
+
+/// 发送 APP 推送
+///
+/// 设备 token 以 `invalid` 开头时模拟设备已注销（404 UNREGISTERED）
+async fn send_push(
+    State(state): State<Arc<NotificationServiceState>>,
+    headers: HeaderMap,
+    Json(req): Json<PushRequest>,
+) -> impl IntoResponse {
+    if !authorized(&headers) {
+        return unauthorized();
+    }
+    if state.take_provider_failure() {
+        return unavailable();
+    }
+    if req.message.token.starts_with("invalid") {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "error": { "code": 404, "status": "UNREGISTERED", "message": "Requested entity was not found." }
+            })),
+        );
+    }
+
+    let message_id = state
+        .record_provider_message(
+            "push",
+            &req.message.token,
+            Some(req.message.notification.title),
+            req.message.notification.body,
+        )
+        .await;
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({ "name": format!("projects/mock/messages/{}", message_id) })),
+    )
+}
+
+/// 获取渠道桩收到的消息（测试用）
+async fn list_provider_messages(
+    State(state): State<Arc<NotificationServiceState>>,
+) -> impl IntoResponse {
+    Json(state.provider_messages().await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    fn json_request(method: &str, uri: &str, body: serde_json::Value) -> Request<Body> {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .header(
+                "authorization",
+                format!("Bearer {}", DEFAULT_PROVIDER_API_KEY),
+            )
+            .body(Body::from(serde_json::to_vec(&body).unwrap()))
+            .unwrap()
+    }
+
+    async fn json_body(response: axum::response::Response) -> serde_json::Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_sms_validates_phone() {
+        let state = Arc::new(NotificationServiceState::new());
+        let app = notification_routes().with_state(state.clone());
+
+        let ok = app
+            .clone()
+            .oneshot(json_request(
+                "POST",
+                "/sms/send",
+                serde_json::json!({"phone": "13800138000", "content": "hi", "sign_name": "徽章"}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(ok.status(), StatusCode::OK);
+
+        let bad = app
+            .oneshot(json_request(
+                "POST",
+                "/sms/send",
+                serde_json::json!({"phone": "123", "content": "hi"}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(bad.status(), StatusCode::BAD_REQUEST);
+
+        let messages = state.provider_messages().await;
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].content, "【徽章】hi");
+    }
+
+    #[tokio::test]
+    async fn test_wechat_rejects_expired_token() {
+        let state = Arc::new(NotificationServiceState::new());
+        let app = notification_routes().with_state(state.clone());
+
+        let token = json_body(
+            app.clone()
+                .oneshot(
+                    Request::builder()
+                        .uri(format!(
+                            "/cgi-bin/token?grant_type=client_credential&appid=wx1&secret={}",
+                            DEFAULT_WECHAT_APP_SECRET
+                        ))
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap(),
+        )
+        .await["access_token"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        let body = serde_json::json!({"touser": "openid-1", "template_id": "tpl", "data": {}});
+        let uri = format!("/cgi-bin/message/subscribe/send?access_token={}", token);
+        let sent = json_body(
+            app.clone()
+                .oneshot(json_request("POST", &uri, body.clone()))
+                .await
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(sent["errcode"], 0);
+
+        state.expire_access_tokens().await;
+        let expired = json_body(app.oneshot(json_request("POST", &uri, body)).await.unwrap()).await;
+        assert_eq!(expired["errcode"], 42001);
+        assert_eq!(state.tokens_issued(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_push_unregistered_token() {
+        let state = Arc::new(NotificationServiceState::new());
+        let app = notification_routes().with_state(state.clone());
+        let body = |token: &str| serde_json::json!({"message": {"token": token, "notification": {"title": "t", "body": "b"}}});
+
+        let ok = app
+            .clone()
+            .oneshot(json_request("POST", "/push/send", body("device-1")))
+            .await
+            .unwrap();
+        assert_eq!(ok.status(), StatusCode::OK);
+
+        let gone = app
+            .oneshot(json_request("POST", "/push/send", body("invalid-device")))
+            .await
+            .unwrap();
+        assert_eq!(gone.status(), StatusCode::NOT_FOUND);
+        assert_eq!(state.provider_messages().await.len(), 1);
+    }
+}
@@ -1,46 +1,41 @@
 //! 通知消费者
 //!
-//! 从 Kafka 消费通知事件，分发到对应渠道的发送器执行推送。
-//! 多渠道发送并行执行，单个渠道失败不影响其他渠道。
+//! 从 Kafka 消费通知事件，交给 `NotificationDispatcher` 完成去重合并、
+//! 偏好过滤和多渠道发送。
 
 use std::collections::HashMap;
 use std::sync::Arc;
 
 use badge_shared::config::AppConfig;
 use badge_shared::events::{NotificationChannel, NotificationEvent};
-use badge_shared::kafka::{KafkaConsumer, KafkaProducer, topics};
+use badge_shared::kafka::{KafkaConsumer, topics};
+use badge_shared::notification_templates::TemplateSet;
 use tokio::sync::watch;
 use tracing::{error, info, warn};
 
+use crate::dispatcher::{DispatchOutcome, NotificationDispatcher};
 use crate::error::NotificationError;
 use crate::sender::{NotificationSender, SendResult};
-use crate::templates::NotificationTemplateEngine;
+use crate::templates::render_for_channel;
 
 /// 通知消费者
 ///
-/// 从 Kafka 消费通知事件，分发到对应渠道的发送器执行推送。
+/// 从 Kafka 消费通知事件，交给分发器处理。
 /// 多渠道发送并行执行，单个渠道失败不影响其他渠道。
 pub struct NotificationConsumer {
     consumer: KafkaConsumer,
-    senders: HashMap<NotificationChannel, Arc<dyn NotificationSender>>,
-    template_engine: NotificationTemplateEngine,
-    /// 发送失败的通知投递到死信队列，供后续排查或重试
-    producer: KafkaProducer,
+    dispatcher: Arc<NotificationDispatcher>,
 }
 
 impl NotificationConsumer {
     pub fn new(
         config: &AppConfig,
-        senders: HashMap<NotificationChannel, Arc<dyn NotificationSender>>,
-        producer: KafkaProducer,
+        dispatcher: Arc<NotificationDispatcher>,
     ) -> Result<Self, NotificationError> {
         let consumer = KafkaConsumer::new(&config.kafka, Some("notifications"))?;
-        let template_engine = NotificationTemplateEngine::new();
         Ok(Self {
             consumer,
-            senders,
-            template_engine,
-            producer,
+            dispatcher,
         })
     }
 
@@ -50,16 +45,13 @@ impl NotificationConsumer {
 
         info!(topic = topics::BADGE_NOTIFICATIONS, "通知消费者已启动");
 
-        let senders = self.senders;
-        let _template_engine = self.template_engine;
-        let producer = self.producer;
+        let dispatcher = self.dispatcher;
 
         self.consumer
             .start(shutdown, |msg| {
-                let senders = &senders;
-                let producer = &producer;
+                let dispatcher = &dispatcher;
                 async move {
-                    if let Err(e) = handle_message(senders, producer, &msg).await {
+                    if let Err(e) = handle_message(dispatcher, &msg).await {
                         error!(
                             error = %e,
                             topic = %msg.topic,
@@ -82,8 +74,7 @@ impl NotificationConsumer {
 ///
 /// 拆分为独立函数而非方法，便于在测试中直接调用而无需构造完整的 Consumer。
 async fn handle_message(
-    senders: &HashMap<NotificationChannel, Arc<dyn NotificationSender>>,
-    producer: &KafkaProducer,
+    dispatcher: &NotificationDispatcher,
     msg: &badge_shared::kafka::ConsumerMessage,
 ) -> Result<(), NotificationError> {
     let notification: NotificationEvent = serde_json::from_slice(&msg.payload)
@@ -97,43 +88,40 @@ async fn handle_message(
         "收到通知事件"
     );
 
-    let results = handle_notification(senders, &notification).await;
-
-    // 检查发送结果，对失败的渠道记录日志
-    let mut all_success = true;
-    for result in &results {
-        if !result.success {
-            all_success = false;
-            warn!(
-                notification_id = %notification.notification_id,
-                channel = ?result.channel,
-                error = ?result.error,
-                "渠道发送失败"
-            );
-        }
+    let notification_id = notification.notification_id.clone();
+    match dispatcher.accept(notification).await {
+        DispatchOutcome::Sent(results) => info!(
+            notification_id = %notification_id,
+            total_channels = results.len(),
+            success_count = results.iter().filter(|r| r.success).count(),
+            "通知事件处理完成"
+        ),
+        DispatchOutcome::Buffered => info!(
+            notification_id = %notification_id,
+            "通知已进入摘要窗口，等待合并"
+        ),
+        DispatchOutcome::Deferred(until) => info!(
+            notification_id = %notification_id,
+            deliver_at = %until,
+            "通知延迟到免打扰时段结束后投递"
+        ),
+        DispatchOutcome::Suppressed => info!(
+            notification_id = %notification_id,
+            "用户已关闭该通知，跳过投递"
+        ),
     }
 
-    // 所有渠道都失败时投递到死信队列，便于后续重试
-    if !all_success && results.iter().all(|r| !r.success) {
-        send_to_dlq(producer, &notification).await;
-    }
-
-    info!(
-        notification_id = %notification.notification_id,
-        total_channels = results.len(),
-        success_count = results.iter().filter(|r| r.success).count(),
-        "通知事件处理完成"
-    );
-
     Ok(())
 }
 
 /// 按通知指定的渠道列表并行发送
 ///
-/// 使用 futures::future::join_all 并行执行所有渠道的发送操作。
+/// 使用 futures::future::join_all 并行执行所有渠道的发送操作，
+/// 发送前按渠道渲染通知模板。
 /// 单个渠道的失败不会阻塞其他渠道，确保通知尽可能到达用户。
 pub async fn handle_notification(
     senders: &HashMap<NotificationChannel, Arc<dyn NotificationSender>>,
+    templates: &TemplateSet,
     notification: &NotificationEvent,
 ) -> Vec<SendResult> {
     let futures: Vec<_> = notification
@@ -141,7 +129,7 @@ pub async fn handle_notification(
         .iter()
         .map(|channel| {
             let senders = senders.clone();
-            let notification = notification.clone();
+            let notification = render_for_channel(templates, notification, channel);
             let channel = channel.clone();
             async move {
                 if let Some(sender) = senders.get(&channel) {
@@ -153,12 +141,7 @@ pub async fn handle_notification(
                                 error = %e,
                                 "发送器执行异常"
                             );
-                            SendResult {
-                                success: false,
-                                channel,
-                                message_id: None,
-                                error: Some(e.to_string()),
-                            }
+                            SendResult::failed(channel, e.to_string(), true)
                         }
                     }
                 } else {
@@ -167,12 +150,8 @@ pub async fn handle_notification(
                         channel = ?channel,
                         "未找到该渠道的发送器，跳过"
                     );
-                    SendResult {
-                        success: false,
-                        channel,
-                        message_id: None,
-                        error: Some("发送器未注册".to_string()),
-                    }
+                    // 发送器缺失属于部署问题，修复后重放仍可送达
+                    SendResult::failed(channel, "发送器未注册", true)
                 }
             }
         })
@@ -181,31 +160,34 @@ pub async fn handle_notification(
     futures::future::join_all(futures).await
 }
 
-/// 将处理失败的通知发送到死信队列
-async fn send_to_dlq(producer: &KafkaProducer, notification: &NotificationEvent) {
-    if let Err(e) = producer
-        .send_json(
-            topics::DEAD_LETTER_QUEUE,
-            &notification.notification_id,
-            notification,
-        )
-        .await
-    {
-        error!(
-            notification_id = %notification.notification_id,
-            error = %e,
-            "发送到死信队列失败，通知可能丢失"
-        );
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use async_trait::async_trait;
     use badge_shared::events::NotificationType;
     use badge_shared::kafka::ConsumerMessage;
     use chrono::Utc;
 
+    /// 总是发送成功的测试发送器
+    struct StubSender(NotificationChannel);
+
+    #[async_trait]
+    impl NotificationSender for StubSender {
+        async fn send(
+            &self,
+            notification: &NotificationEvent,
+        ) -> Result<SendResult, NotificationError> {
+            Ok(SendResult::success(
+                self.0.clone(),
+                format!("stub-{}", notification.notification_id),
+            ))
+        }
+
+        fn channel(&self) -> NotificationChannel {
+            self.0.clone()
+        }
+    }
+
     /// 构造测试用的通知事件
     fn make_test_notification() -> NotificationEvent {
         NotificationEvent {
@@ -265,15 +247,15 @@ mod tests {
         let mut senders: HashMap<NotificationChannel, Arc<dyn NotificationSender>> = HashMap::new();
         senders.insert(
             NotificationChannel::AppPush,
-            Arc::new(crate::sender::AppPushSender),
+            Arc::new(StubSender(NotificationChannel::AppPush)),
         );
         senders.insert(
             NotificationChannel::WeChat,
-            Arc::new(crate::sender::WeChatSender),
+            Arc::new(StubSender(NotificationChannel::WeChat)),
         );
 
         let notification = make_test_notification();
-        let results = handle_notification(&senders, &notification).await;
+        let results = handle_notification(&senders, &TemplateSet::default(), &notification).await;
 
         // 两个渠道都应成功
         assert_eq!(results.len(), 2);
@@ -286,11 +268,11 @@ mod tests {
         let mut senders: HashMap<NotificationChannel, Arc<dyn NotificationSender>> = HashMap::new();
         senders.insert(
             NotificationChannel::AppPush,
-            Arc::new(crate::sender::AppPushSender),
+            Arc::new(StubSender(NotificationChannel::AppPush)),
         );
 
         let notification = make_test_notification();
-        let results = handle_notification(&senders, &notification).await;
+        let results = handle_notification(&senders, &TemplateSet::default(), &notification).await;
 
         assert_eq!(results.len(), 2);
 
@@ -0,0 +1,301 @@
+//! 通知去重与摘要合并
+//!
+//! 一次事件可能通过级联发放多枚徽章，上游为每枚徽章各发一条 `BadgeGranted` 通知。
+//! 同一用户、同一来源事件（`data.source_event_id`）的发放通知在去重窗口内缓存，
+//! 窗口结束后合并为一条摘要通知投递，避免短时间内连续推送打扰用户。
+//!
+//! 上游以 `user_id` 作为 Kafka 消息键，同一用户的通知落在同一分区、
+//! 由同一个 worker 实例消费，因此进程内缓存即可完成合并。
+//! 缓存本身不持久化，进程异常退出时由分发器写入延迟队列的副本兜底。
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use badge_shared::events::{NotificationEvent, NotificationType};
+
+/// 通知数据中标识来源事件的字段
+pub const SOURCE_EVENT_DATA_KEY: &str = "source_event_id";
+
+/// 通知数据中标记"等待合并、尚未应用偏好"的字段，仅出现在延迟队列的兜底副本中
+pub const DIGEST_PENDING_DATA_KEY: &str = "digest_pending";
+
+/// 通知提交到摘要缓冲区的结果
+#[derive(Debug)]
+pub enum DigestOffer {
+    /// 不参与合并，立即投递
+    Deliver(NotificationEvent),
+    /// 已缓存，窗口结束后随摘要投递
+    Buffered,
+}
+
+struct DigestGroup {
+    opened_at: Instant,
+    events: Vec<NotificationEvent>,
+}
+
+/// 摘要缓冲区
+pub struct DigestBuffer {
+    window: Duration,
+    groups: Mutex<HashMap<(String, String), DigestGroup>>,
+}
+
+impl DigestBuffer {
+    /// 创建去重窗口为 `window` 的缓冲区，窗口为 0 时不做合并
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            groups: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 去重窗口
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// 通知是否会进入缓冲区参与合并
+    pub fn accepts(&self, event: &NotificationEvent) -> bool {
+        !self.window.is_zero()
+            && event.notification_type == NotificationType::BadgeGranted
+            && source_event_id(event).is_some()
+    }
+
+    /// 提交通知；可合并的通知进入缓冲区，其余原样返回
+    pub fn offer(&self, event: NotificationEvent) -> DigestOffer {
+        if !self.accepts(&event) {
+            return DigestOffer::Deliver(event);
+        }
+        let Some(source_event_id) = source_event_id(&event).map(str::to_string) else {
+            return DigestOffer::Deliver(event);
+        };
+
+        let mut groups = self.groups.lock().unwrap();
+        groups
+            .entry((event.user_id.clone(), source_event_id))
+            .or_insert_with(|| DigestGroup {
+                opened_at: Instant::now(),
+                events: Vec::new(),
+            })
+            .events
+            .push(event);
+        DigestOffer::Buffered
+    }
+
+    /// 取出窗口已结束的分组，每组合并为一条通知
+    pub fn drain_expired(&self, now: Instant) -> Vec<NotificationEvent> {
+        let mut groups = self.groups.lock().unwrap();
+        let expired: Vec<_> = groups
+            .iter()
+            .filter(|(_, g)| now.duration_since(g.opened_at) >= self.window)
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        expired
+            .into_iter()
+            .filter_map(|key| groups.remove(&key))
+            .map(|g| merge(g.events))
+            .collect()
+    }
+
+    /// 取出所有分组（停机时调用，避免缓存中的通知丢失）
+    pub fn drain_all(&self) -> Vec<NotificationEvent> {
+        self.groups
+            .lock()
+            .unwrap()
+            .drain()
+            .map(|(_, g)| merge(g.events))
+            .collect()
+    }
+
+    /// 缓冲区中等待合并的分组数
+    pub fn pending_groups(&self) -> usize {
+        self.groups.lock().unwrap().len()
+    }
+}
+
+fn source_event_id(event: &NotificationEvent) -> Option<&str> {
+    event
+        .data
+        .get(SOURCE_EVENT_DATA_KEY)
+        .and_then(|v| v.as_str())
+}
+
+/// 摘要包含的原始通知 ID，单条通知即其自身
+pub fn member_ids(digest: &NotificationEvent) -> Vec<String> {
+    match digest
+        .data
+        .get("merged_notification_ids")
+        .and_then(|v| v.as_array())
+    {
+        Some(ids) => ids
+            .iter()
+            .filter_map(|id| id.as_str().map(str::to_string))
+            .collect(),
+        None => vec![digest.notification_id.clone()],
+    }
+}
+
+/// 合并同一来源事件的发放通知
+///
+/// 单条通知原样返回。多条时沿用首条通知的 ID 和业务数据，
+/// 徽章信息汇总到 `data.badges`，渠道取并集。
+fn merge(mut events: Vec<NotificationEvent>) -> NotificationEvent {
+    if events.len() == 1 {
+        return events.remove(0);
+    }
+
+    let names: Vec<String> = events
+        .iter()
+        .map(|e| {
+            e.data
+                .get("badge_name")
+                .and_then(|v| v.as_str())
+                .unwrap_or("未知徽章")
+                .to_string()
+        })
+        .collect();
+    let badges: Vec<serde_json::Value> = events
+        .iter()
+        .map(|e| {
+            serde_json::json!({
+                "badge_id": e.data.get("badge_id"),
+                "badge_name": e.data.get("badge_name"),
+                "quantity": e.data.get("quantity"),
+            })
+        })
+        .collect();
+    let merged_ids: Vec<String> = events.iter().map(|e| e.notification_id.clone()).collect();
+
+    let mut channels = Vec::new();
+    for channel in events.iter().flat_map(|e| e.channels.iter()) {
+        if !channels.contains(channel) {
+            channels.push(channel.clone());
+        }
+    }
+    let created_at = events
+        .iter()
+        .map(|e| e.created_at)
+        .min()
+        .unwrap_or_else(chrono::Utc::now);
+    let count = events.len();
+
+    let mut digest = events.remove(0);
+    if let Some(data) = digest.data.as_object_mut() {
+        data.remove("badge_id");
+        data.remove("badge_name");
+        data.remove("quantity");
+        data.insert("badges".to_string(), serde_json::json!(badges));
+        data.insert("digest_count".to_string(), serde_json::json!(count));
+        data.insert(
+            "merged_notification_ids".to_string(),
+            serde_json::json!(merged_ids),
+        );
+    }
+    digest.title = format!("恭喜获得 {} 枚新徽章", count);
+    digest.body = format!(
+        "您已获得{}徽章！",
+        names
+            .iter()
+            .map(|n| format!("「{}」", n))
+            .collect::<String>()
+    );
+    digest.channels = channels;
+    digest.created_at = created_at;
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use badge_shared::events::NotificationChannel;
+    use chrono::Utc;
+
+    fn make_grant(id: &str, source: Option<&str>, badge_name: &str) -> NotificationEvent {
+        let mut data = serde_json::json!({"badge_id": 1, "badge_name": badge_name, "quantity": 1});
+        if let Some(source) = source {
+            data[SOURCE_EVENT_DATA_KEY] = serde_json::json!(source);
+        }
+        NotificationEvent {
+            notification_id: id.to_string(),
+            user_id: "user-001".to_string(),
+            notification_type: NotificationType::BadgeGranted,
+            title: "恭喜获得新徽章".to_string(),
+            body: format!("您已获得「{}」徽章！", badge_name),
+            data,
+            channels: vec![NotificationChannel::AppPush],
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_non_digestible_notifications_pass_through() {
+        let buffer = DigestBuffer::new(Duration::from_secs(5));
+
+        assert!(matches!(
+            buffer.offer(make_grant("n-1", None, "首单")),
+            DigestOffer::Deliver(_)
+        ));
+
+        let mut revoked = make_grant("n-2", Some("evt-1"), "首单");
+        revoked.notification_type = NotificationType::BadgeRevoked;
+        assert!(matches!(buffer.offer(revoked), DigestOffer::Deliver(_)));
+
+        let disabled = DigestBuffer::new(Duration::ZERO);
+        assert!(matches!(
+            disabled.offer(make_grant("n-3", Some("evt-1"), "首单")),
+            DigestOffer::Deliver(_)
+        ));
+        assert_eq!(buffer.pending_groups(), 0);
+    }
+
+    #[test]
+    fn test_cascading_grants_merge_into_digest() {
+        let buffer = DigestBuffer::new(Duration::from_secs(5));
+        for (id, name) in [("n-1", "首单"), ("n-2", "复购"), ("n-3", "铁粉")] {
+            assert!(matches!(
+                buffer.offer(make_grant(id, Some("evt-1"), name)),
+                DigestOffer::Buffered
+            ));
+        }
+        let mut sms = make_grant("n-4", Some("evt-2"), "其他");
+        sms.channels = vec![NotificationChannel::Sms];
+        buffer.offer(sms);
+        assert_eq!(buffer.pending_groups(), 2);
+
+        // 窗口未结束不刷出
+        assert!(buffer.drain_expired(Instant::now()).is_empty());
+
+        let mut drained = buffer.drain_expired(Instant::now() + Duration::from_secs(5));
+        drained.sort_by(|a, b| a.notification_id.cmp(&b.notification_id));
+        assert_eq!(drained.len(), 2);
+
+        let digest = &drained[0];
+        assert_eq!(digest.notification_id, "n-1");
+        assert_eq!(digest.title, "恭喜获得 3 枚新徽章");
+        assert_eq!(digest.body, "您已获得「首单」「复购」「铁粉」徽章！");
+        assert_eq!(digest.data["digest_count"], 3);
+        assert_eq!(digest.data["badges"].as_array().unwrap().len(), 3);
+        assert_eq!(digest.data[SOURCE_EVENT_DATA_KEY], "evt-1");
+        assert!(digest.data.get("badge_name").is_none());
+        assert_eq!(member_ids(digest), vec!["n-1", "n-2", "n-3"]);
+
+        // 单条分组原样投递
+        assert_eq!(drained[1].notification_id, "n-4");
+        assert_eq!(drained[1].body, "您已获得「其他」徽章！");
+        assert_eq!(member_ids(&drained[1]), vec!["n-4"]);
+        assert_eq!(buffer.pending_groups(), 0);
+    }
+
+    #[test]
+    fn test_drain_all() {
+        let buffer = DigestBuffer::new(Duration::from_secs(60));
+        buffer.offer(make_grant("n-1", Some("evt-1"), "首单"));
+        buffer.offer(make_grant("n-2", Some("evt-1"), "复购"));
+
+        let drained = buffer.drain_all();
+        assert_eq!(drained.len(), 1);
+        assert_eq!(drained[0].data["digest_count"], 2);
+        assert_eq!(buffer.pending_groups(), 0);
+    }
+}
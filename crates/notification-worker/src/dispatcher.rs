@@ -0,0 +1,735 @@
+//! 通知分发
+//!
+//! 在发送器之前串联去重合并与用户偏好：
+//!
+//! 1. 同一来源事件的徽章发放通知先进入摘要缓冲区，窗口结束后合并为一条；
+//!    缓冲的同时在延迟队列写入兜底副本，进程异常退出后由调度循环逐条补发
+//! 2. 按用户偏好筛选渠道；用户关闭了全部渠道时丢弃
+//! 3. 免打扰时段内的非紧急通知写入延迟队列，时段结束后由调度循环投递
+//! 4. 其余通知按用户联系方式补全收件人，按用户语言和渠道渲染模板后并行发送，
+//!    全部失败且可重试时投递到死信队列
+//!
+//! 摘要刷出和延迟通知投递由 `run_scheduler` 驱动的后台循环完成。
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use badge_shared::dlq::{self, DEFAULT_DLQ_MAX_RETRIES, DeadLetterMessage};
+use badge_shared::error::BadgeError;
+use badge_shared::events::{NotificationChannel, NotificationEvent};
+use badge_shared::kafka::{KafkaProducer, topics};
+use badge_shared::notification_contacts::{ContactStore, resolve_recipients};
+use badge_shared::notification_preferences::{
+    DeferredNotificationStore, PreferenceDecision, PreferenceStore, apply_preferences,
+};
+use badge_shared::notification_templates::TemplateRegistry;
+use chrono::{DateTime, Utc};
+use tokio::sync::watch;
+use tracing::{debug, error, info, warn};
+
+use crate::consumer::handle_notification;
+use crate::digest::{self, DIGEST_PENDING_DATA_KEY, DigestBuffer, DigestOffer};
+use crate::sender::{NotificationSender, SendResult};
+
+/// 写入死信消息的来源服务标识
+const SOURCE_SERVICE: &str = "notification-worker";
+
+/// 摘要兜底副本在窗口结束后额外保留的时长，期间由缓冲所在实例负责合并投递
+const DIGEST_RECOVERY_GRACE: Duration = Duration::from_secs(300);
+
+/// 分发配置
+#[derive(Debug, Clone)]
+pub struct DispatchConfig {
+    /// 去重窗口，0 表示不合并
+    pub digest_window_ms: u64,
+    /// 调度循环间隔
+    pub poll_interval_ms: u64,
+    /// 每轮最多投递的延迟通知数
+    pub deferred_batch_size: i64,
+}
+
+impl Default for DispatchConfig {
+    fn default() -> Self {
+        Self {
+            digest_window_ms: 3000,
+            poll_interval_ms: 1000,
+            deferred_batch_size: 100,
+        }
+    }
+}
+
+impl DispatchConfig {
+    /// 从环境变量加载，未设置的项使用默认值
+    ///
+    /// - `BADGE_NOTIFICATION_DIGEST_WINDOW_MS`
+    /// - `BADGE_NOTIFICATION_POLL_INTERVAL_MS`
+    /// - `BADGE_NOTIFICATION_DEFERRED_BATCH_SIZE`
+    pub fn from_env() -> Self {
+        fn env<T: std::str::FromStr>(key: &str) -> Option<T> {
+            std::env::var(key).ok().and_then(|v| v.parse().ok())
+        }
+
+        let default = Self::default();
+        Self {
+            digest_window_ms: env("BADGE_NOTIFICATION_DIGEST_WINDOW_MS")
+                .unwrap_or(default.digest_window_ms),
+            poll_interval_ms: env("BADGE_NOTIFICATION_POLL_INTERVAL_MS")
+                .unwrap_or(default.poll_interval_ms),
+            deferred_batch_size: env("BADGE_NOTIFICATION_DEFERRED_BATCH_SIZE")
+                .unwrap_or(default.deferred_batch_size),
+        }
+    }
+}
+
+/// 通知的分发结果
+#[derive(Debug)]
+pub enum DispatchOutcome {
+    /// 已发送到各渠道
+    Sent(Vec<SendResult>),
+    /// 进入摘要缓冲区，等待合并
+    Buffered,
+    /// 免打扰时段内，延迟到指定时刻投递
+    Deferred(DateTime<Utc>),
+    /// 用户关闭了该通知的所有渠道
+    Suppressed,
+}
+
+/// 通知分发器
+pub struct NotificationDispatcher {
+    senders: HashMap<NotificationChannel, Arc<dyn NotificationSender>>,
+    preferences: Option<Arc<dyn PreferenceStore>>,
+    deferred: Option<Arc<dyn DeferredNotificationStore>>,
+    /// 通知只携带用户 ID，发送前按用户查询收件人
+    contacts: Option<Arc<dyn ContactStore>>,
+    digest: DigestBuffer,
+    templates: Arc<TemplateRegistry>,
+    deferred_batch_size: i64,
+    /// 发送失败的通知投递到死信队列，供后续排查或重试
+    dead_letter: Option<KafkaProducer>,
+}
+
+impl NotificationDispatcher {
+    /// 创建分发器，默认不读取偏好、不合并，使用内置模板
+    pub fn new(senders: HashMap<NotificationChannel, Arc<dyn NotificationSender>>) -> Self {
+        Self {
+            senders,
+            preferences: None,
+            deferred: None,
+            contacts: None,
+            digest: DigestBuffer::new(Duration::ZERO),
+            templates: Arc::new(TemplateRegistry::builtin()),
+            deferred_batch_size: DispatchConfig::default().deferred_batch_size,
+            dead_letter: None,
+        }
+    }
+
+    pub fn with_config(mut self, config: &DispatchConfig) -> Self {
+        self.digest = DigestBuffer::new(Duration::from_millis(config.digest_window_ms));
+        self.deferred_batch_size = config.deferred_batch_size;
+        self
+    }
+
+    pub fn with_preference_store(mut self, store: Arc<dyn PreferenceStore>) -> Self {
+        self.preferences = Some(store);
+        self
+    }
+
+    pub fn with_deferred_store(mut self, store: Arc<dyn DeferredNotificationStore>) -> Self {
+        self.deferred = Some(store);
+        self
+    }
+
+    pub fn with_contact_store(mut self, store: Arc<dyn ContactStore>) -> Self {
+        self.contacts = Some(store);
+        self
+    }
+
+    pub fn with_template_registry(mut self, templates: Arc<TemplateRegistry>) -> Self {
+        self.templates = templates;
+        self
+    }
+
+    pub fn with_dead_letter_producer(mut self, producer: KafkaProducer) -> Self {
+        self.dead_letter = Some(producer);
+        self
+    }
+
+    /// 接收一条通知：可合并的进入摘要缓冲区，其余立即按偏好分发
+    ///
+    /// 摘要缓冲区只在内存中，消费位点却在进入缓冲区后即提交。配置了延迟队列时，
+    /// 先写入兜底副本再缓冲，写入失败则不合并、立即分发，保证通知不会随进程退出丢失。
+    pub async fn accept(&self, event: NotificationEvent) -> DispatchOutcome {
+        if let Some(store) = &self.deferred {
+            if self.digest.accepts(&event) {
+                if let Err(e) = self.persist_buffered(store.as_ref(), &event).await {
+                    warn!(
+                        notification_id = %event.notification_id,
+                        error = %e,
+                        "写入摘要兜底副本失败，不合并直接投递"
+                    );
+                    return self.dispatch(event).await;
+                }
+            }
+        }
+
+        match self.digest.offer(event) {
+            DigestOffer::Deliver(event) => self.dispatch(event).await,
+            DigestOffer::Buffered => DispatchOutcome::Buffered,
+        }
+    }
+
+    /// 将待合并的通知写入延迟队列，到期时间晚于窗口结束，正常情况下合并投递前即被删除
+    async fn persist_buffered(
+        &self,
+        store: &dyn DeferredNotificationStore,
+        event: &NotificationEvent,
+    ) -> Result<(), BadgeError> {
+        let mut pending = event.clone();
+        if let Some(data) = pending.data.as_object_mut() {
+            data.insert(
+                DIGEST_PENDING_DATA_KEY.to_string(),
+                serde_json::Value::Bool(true),
+            );
+        }
+        let deliver_at = Utc::now()
+            + chrono::Duration::from_std(self.digest.window() + DIGEST_RECOVERY_GRACE)
+                .unwrap_or(chrono::Duration::MAX);
+        store.defer(&pending, deliver_at).await
+    }
+
+    /// 投递合并后的摘要，先删除延迟队列中的兜底副本
+    ///
+    /// 删除失败时放弃合并投递，由兜底副本到期后逐条补发，避免重复通知。
+    async fn dispatch_digest(&self, digest: NotificationEvent) {
+        if let Some(store) = &self.deferred {
+            if let Err(e) = store.remove(&digest::member_ids(&digest)).await {
+                warn!(
+                    notification_id = %digest.notification_id,
+                    error = %e,
+                    "删除摘要兜底副本失败，改由延迟队列逐条补发"
+                );
+                return;
+            }
+        }
+        self.dispatch(digest).await;
+    }
+
+    /// 按用户偏好分发
+    ///
+    /// 偏好读取失败时按原渠道投递，偏好服务故障不应阻断通知。
+    pub async fn dispatch(&self, event: NotificationEvent) -> DispatchOutcome {
+        let preferences = match &self.preferences {
+            Some(store) => match store.get(&event.user_id).await {
+                Ok(preferences) => preferences,
+                Err(e) => {
+                    warn!(
+                        user_id = %event.user_id,
+                        error = %e,
+                        "读取用户通知偏好失败，按原渠道投递"
+                    );
+                    None
+                }
+            },
+            None => None,
+        };
+
+        match apply_preferences(preferences.as_ref(), event, Utc::now()) {
+            PreferenceDecision::Deliver(event) => DispatchOutcome::Sent(self.deliver(&event).await),
+            PreferenceDecision::Suppress => {
+                debug!("用户已关闭该类型通知的所有渠道，不投递");
+                DispatchOutcome::Suppressed
+            }
+            PreferenceDecision::Defer(event, until) => {
+                let Some(store) = &self.deferred else {
+                    return DispatchOutcome::Sent(self.deliver(&event).await);
+                };
+                match store.defer(&event, until).await {
+                    Ok(()) => {
+                        info!(
+                            notification_id = %event.notification_id,
+                            user_id = %event.user_id,
+                            deliver_at = %until,
+                            "免打扰时段内，通知延迟投递"
+                        );
+                        DispatchOutcome::Deferred(until)
+                    }
+                    Err(e) => {
+                        warn!(
+                            notification_id = %event.notification_id,
+                            error = %e,
+                            "写入延迟队列失败，立即投递"
+                        );
+                        DispatchOutcome::Sent(self.deliver(&event).await)
+                    }
+                }
+            }
+        }
+    }
+
+    /// 发送到通知指定的渠道（偏好已应用），按结果决定是否进入死信队列
+    ///
+    /// 收件人在发送前才查询，延迟投递和摘要合并的通知使用投递时的联系方式；
+    /// 死信队列保存原始通知，重放时重新查询。
+    async fn deliver(&self, event: &NotificationEvent) -> Vec<SendResult> {
+        let mut addressed = event.clone();
+        if let Some(store) = &self.contacts {
+            resolve_recipients(store.as_ref(), &mut addressed).await;
+        }
+
+        let templates = self.templates.current().await;
+        let results = handle_notification(&self.senders, &templates, &addressed).await;
+
+        for result in results.iter().filter(|r| !r.success) {
+            warn!(
+                notification_id = %event.notification_id,
+                channel = ?result.channel,
+                error = ?result.error,
+                retryable = result.retryable,
+                "渠道发送失败"
+            );
+        }
+
+        // 所有渠道都失败且存在可重试的失败时投递到死信队列，便于后续重试；
+        // 全部为永久失败（如收件人缺失）时重放也无法送达，只记录日志
+        if !results.is_empty()
+            && results.iter().all(|r| !r.success)
+            && results.iter().any(|r| r.retryable)
+        {
+            self.send_to_dlq(event, &results).await;
+        }
+
+        results
+    }
+
+    /// 刷出窗口已结束的摘要，并投递到期的延迟通知
+    pub async fn tick(&self) {
+        for digest in self.digest.drain_expired(Instant::now()) {
+            self.dispatch_digest(digest).await;
+        }
+        self.deliver_due().await;
+    }
+
+    /// 投递到期的延迟通知，免打扰已结束，不再重复判断
+    ///
+    /// 摘要兜底副本到期说明缓冲所在的进程未能合并投递，这类通知尚未应用偏好，
+    /// 去掉标记后按偏好重新分发。
+    async fn deliver_due(&self) {
+        let Some(store) = &self.deferred else {
+            return;
+        };
+        match store.take_due(Utc::now(), self.deferred_batch_size).await {
+            Ok(due) => {
+                for mut event in due {
+                    let recovered = event
+                        .data
+                        .as_object_mut()
+                        .and_then(|data| data.remove(DIGEST_PENDING_DATA_KEY))
+                        .is_some();
+                    if recovered {
+                        self.dispatch(event).await;
+                    } else {
+                        self.deliver(&event).await;
+                    }
+                }
+            }
+            Err(e) => warn!(error = %e, "读取到期的延迟通知失败"),
+        }
+    }
+
+    /// 立即刷出所有摘要（停机时调用）
+    pub async fn flush(&self) {
+        for digest in self.digest.drain_all() {
+            self.dispatch_digest(digest).await;
+        }
+    }
+
+    /// 调度循环：定期刷出摘要和投递延迟通知，收到停机信号后刷出剩余摘要
+    pub async fn run_scheduler(
+        self: Arc<Self>,
+        interval: Duration,
+        mut shutdown: watch::Receiver<bool>,
+    ) {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => self.tick().await,
+                _ = shutdown.changed() => break,
+            }
+        }
+
+        self.flush().await;
+        info!("通知调度循环已停止");
+    }
+
+    /// 将通知封装为死信消息投递到死信队列，重放时发回通知 topic
+    async fn send_to_dlq(&self, event: &NotificationEvent, results: &[SendResult]) {
+        let Some(producer) = &self.dead_letter else {
+            return;
+        };
+
+        let error = results
+            .iter()
+            .map(|r| {
+                format!(
+                    "{:?}: {}",
+                    r.channel,
+                    r.error.as_deref().unwrap_or("未知错误")
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("; ");
+        let result = match serde_json::to_string(event) {
+            Ok(payload) => {
+                let message = DeadLetterMessage::new(
+                    &event.notification_id,
+                    topics::BADGE_NOTIFICATIONS,
+                    payload,
+                    error,
+                    DEFAULT_DLQ_MAX_RETRIES,
+                    SOURCE_SERVICE,
+                );
+                dlq::publish(producer, &message).await
+            }
+            Err(e) => Err(BadgeError::Kafka(format!("序列化通知失败: {e}"))),
+        };
+
+        if let Err(e) = result {
+            error!(
+                notification_id = %event.notification_id,
+                error = %e,
+                "发送到死信队列失败，通知可能丢失"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::NotificationError;
+    use async_trait::async_trait;
+    use badge_shared::events::NotificationType;
+    use badge_shared::notification_contacts::{EMAIL_DATA_KEY, InMemoryContactStore, UserContacts};
+    use badge_shared::notification_preferences::{
+        InMemoryDeferredStore, InMemoryPreferenceStore, NotificationPreferences, QuietHours,
+    };
+    use std::sync::Mutex;
+
+    /// 记录收到的通知的发送器
+    #[derive(Default)]
+    struct RecordingSender {
+        sent: Mutex<Vec<NotificationEvent>>,
+    }
+
+    #[async_trait]
+    impl NotificationSender for RecordingSender {
+        async fn send(
+            &self,
+            notification: &NotificationEvent,
+        ) -> Result<SendResult, NotificationError> {
+            self.sent.lock().unwrap().push(notification.clone());
+            Ok(SendResult::success(
+                NotificationChannel::AppPush,
+                notification.notification_id.clone(),
+            ))
+        }
+
+        fn channel(&self) -> NotificationChannel {
+            NotificationChannel::AppPush
+        }
+    }
+
+    fn make_event(
+        id: &str,
+        notification_type: NotificationType,
+        data: serde_json::Value,
+    ) -> NotificationEvent {
+        NotificationEvent {
+            notification_id: id.to_string(),
+            user_id: "user-001".to_string(),
+            notification_type,
+            title: "恭喜获得新徽章".to_string(),
+            body: "您已获得徽章！".to_string(),
+            data,
+            channels: vec![NotificationChannel::AppPush],
+            created_at: Utc::now(),
+        }
+    }
+
+    fn dispatcher(sender: Arc<RecordingSender>) -> NotificationDispatcher {
+        NotificationDispatcher::new(HashMap::from([(
+            NotificationChannel::AppPush,
+            sender as Arc<dyn NotificationSender>,
+        )]))
+    }
+
+    /// 覆盖当前时刻前后一小时的免打扰时段
+    fn current_quiet_hours() -> QuietHours {
+        let now = Utc::now().time();
+        QuietHours::new(
+            now - chrono::Duration::hours(1),
+            now + chrono::Duration::hours(1),
+            "UTC",
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_without_preferences() {
+        let sender = Arc::new(RecordingSender::default());
+        let dispatcher = dispatcher(sender.clone());
+
+        let outcome = dispatcher
+            .accept(make_event(
+                "n-1",
+                NotificationType::BadgeGranted,
+                serde_json::json!({}),
+            ))
+            .await;
+        assert!(matches!(outcome, DispatchOutcome::Sent(ref r) if r.len() == 1 && r[0].success));
+        assert_eq!(sender.sent.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_recipients_resolved_before_send() {
+        let sender = Arc::new(RecordingSender::default());
+        let contacts = Arc::new(InMemoryContactStore::new());
+        contacts
+            .upsert(&UserContacts::new("user-001").with_email("user-001@example.com"))
+            .await
+            .unwrap();
+        let dispatcher = dispatcher(sender.clone()).with_contact_store(contacts);
+
+        dispatcher
+            .accept(make_event(
+                "n-1",
+                NotificationType::BadgeGranted,
+                serde_json::json!({}),
+            ))
+            .await;
+
+        let sent = sender.sent.lock().unwrap();
+        assert_eq!(sent[0].data[EMAIL_DATA_KEY], "user-001@example.com");
+    }
+
+    #[tokio::test]
+    async fn test_disabled_channels_are_suppressed() {
+        let sender = Arc::new(RecordingSender::default());
+        let store = Arc::new(InMemoryPreferenceStore::new());
+        store
+            .save(
+                &NotificationPreferences::new("user-001")
+                    .with_channels(NotificationType::BadgeRevoked, vec![])
+                    .with_locale("en-US"),
+            )
+            .await
+            .unwrap();
+        let dispatcher = dispatcher(sender.clone()).with_preference_store(store);
+
+        let outcome = dispatcher
+            .accept(make_event(
+                "n-1",
+                NotificationType::BadgeRevoked,
+                serde_json::json!({}),
+            ))
+            .await;
+        assert!(matches!(outcome, DispatchOutcome::Suppressed));
+
+        dispatcher
+            .accept(make_event(
+                "n-2",
+                NotificationType::BadgeExpiring,
+                serde_json::json!({}),
+            ))
+            .await;
+        let sent = sender.sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].data["locale"], "en-US");
+    }
+
+    #[tokio::test]
+    async fn test_quiet_hours_defer_non_urgent() {
+        let sender = Arc::new(RecordingSender::default());
+        let preferences = Arc::new(InMemoryPreferenceStore::new());
+        preferences
+            .save(&NotificationPreferences::new("user-001").with_quiet_hours(current_quiet_hours()))
+            .await
+            .unwrap();
+        let deferred = Arc::new(InMemoryDeferredStore::new());
+        let dispatcher = dispatcher(sender.clone())
+            .with_preference_store(preferences)
+            .with_deferred_store(deferred.clone());
+
+        let outcome = dispatcher
+            .accept(make_event(
+                "n-1",
+                NotificationType::BadgeExpiring,
+                serde_json::json!({}),
+            ))
+            .await;
+        assert!(matches!(outcome, DispatchOutcome::Deferred(_)));
+        assert_eq!(deferred.len(), 1);
+
+        // 紧急通知不受免打扰限制
+        let outcome = dispatcher
+            .accept(make_event(
+                "n-2",
+                NotificationType::RedemptionFailed,
+                serde_json::json!({}),
+            ))
+            .await;
+        assert!(matches!(outcome, DispatchOutcome::Sent(_)));
+        assert_eq!(sender.sent.lock().unwrap().len(), 1);
+
+        // 未到期不投递
+        dispatcher.tick().await;
+        assert_eq!(sender.sent.lock().unwrap().len(), 1);
+        assert_eq!(deferred.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_due_deferred_notifications_are_delivered() {
+        let sender = Arc::new(RecordingSender::default());
+        let deferred = Arc::new(InMemoryDeferredStore::new());
+        deferred
+            .defer(
+                &make_event(
+                    "n-1",
+                    NotificationType::BadgeExpiring,
+                    serde_json::json!({}),
+                ),
+                Utc::now() - chrono::Duration::seconds(1),
+            )
+            .await
+            .unwrap();
+        let dispatcher = dispatcher(sender.clone()).with_deferred_store(deferred.clone());
+
+        dispatcher.tick().await;
+        assert!(deferred.is_empty());
+        let sent = sender.sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].notification_id, "n-1");
+    }
+
+    #[tokio::test]
+    async fn test_cascading_grants_sent_as_single_digest() {
+        let sender = Arc::new(RecordingSender::default());
+        let dispatcher = dispatcher(sender.clone()).with_config(&DispatchConfig {
+            digest_window_ms: 20,
+            ..Default::default()
+        });
+
+        for (id, name) in [("n-1", "首单"), ("n-2", "复购")] {
+            let outcome = dispatcher
+                .accept(make_event(
+                    id,
+                    NotificationType::BadgeGranted,
+                    serde_json::json!({"badge_name": name, "source_event_id": "evt-1"}),
+                ))
+                .await;
+            assert!(matches!(outcome, DispatchOutcome::Buffered));
+        }
+        assert!(sender.sent.lock().unwrap().is_empty());
+
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        dispatcher.tick().await;
+
+        let sent = sender.sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].data["digest_count"], 2);
+    }
+
+    #[tokio::test]
+    async fn test_buffered_grants_are_persisted_until_digest_sent() {
+        let sender = Arc::new(RecordingSender::default());
+        let deferred = Arc::new(InMemoryDeferredStore::new());
+        let dispatcher = dispatcher(sender.clone())
+            .with_deferred_store(deferred.clone())
+            .with_config(&DispatchConfig {
+                digest_window_ms: 20,
+                ..Default::default()
+            });
+
+        for (id, name) in [("n-1", "首单"), ("n-2", "复购")] {
+            let outcome = dispatcher
+                .accept(make_event(
+                    id,
+                    NotificationType::BadgeGranted,
+                    serde_json::json!({"badge_name": name, "source_event_id": "evt-1"}),
+                ))
+                .await;
+            assert!(matches!(outcome, DispatchOutcome::Buffered));
+        }
+        assert_eq!(deferred.len(), 2);
+
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        dispatcher.tick().await;
+
+        assert!(deferred.is_empty());
+        let sent = sender.sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].data["digest_count"], 2);
+    }
+
+    #[tokio::test]
+    async fn test_orphaned_digest_copies_are_redispatched() {
+        let sender = Arc::new(RecordingSender::default());
+        let preferences = Arc::new(InMemoryPreferenceStore::new());
+        preferences
+            .save(&NotificationPreferences::new("user-001").with_locale("en-US"))
+            .await
+            .unwrap();
+        let deferred = Arc::new(InMemoryDeferredStore::new());
+        deferred
+            .defer(
+                &make_event(
+                    "n-1",
+                    NotificationType::BadgeGranted,
+                    serde_json::json!({"source_event_id": "evt-1", "digest_pending": true}),
+                ),
+                Utc::now() - chrono::Duration::seconds(1),
+            )
+            .await
+            .unwrap();
+        let dispatcher = dispatcher(sender.clone())
+            .with_preference_store(preferences)
+            .with_deferred_store(deferred.clone());
+
+        dispatcher.tick().await;
+        assert!(deferred.is_empty());
+        let sent = sender.sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].data["locale"], "en-US");
+        assert!(sent[0].data.get(DIGEST_PENDING_DATA_KEY).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_flushes_on_shutdown() {
+        let sender = Arc::new(RecordingSender::default());
+        let dispatcher = Arc::new(dispatcher(sender.clone()).with_config(&DispatchConfig {
+            digest_window_ms: 60_000,
+            ..Default::default()
+        }));
+        dispatcher
+            .accept(make_event(
+                "n-1",
+                NotificationType::BadgeGranted,
+                serde_json::json!({"badge_name": "首单", "source_event_id": "evt-1"}),
+            ))
+            .await;
+
+        let (tx, rx) = watch::channel(false);
+        let handle = tokio::spawn(
+            dispatcher
+                .clone()
+                .run_scheduler(Duration::from_millis(10), rx),
+        );
+        tx.send(true).unwrap();
+        handle.await.unwrap();
+
+        assert_eq!(sender.sent.lock().unwrap().len(), 1);
+    }
+}
@@ -5,15 +5,24 @@
 
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::Result;
 use badge_shared::config::AppConfig;
+use badge_shared::database::Database;
 use badge_shared::events::NotificationChannel;
 use badge_shared::kafka::KafkaProducer;
+use badge_shared::notification_contacts::PgContactStore;
+use badge_shared::notification_preferences::{PgDeferredNotificationStore, PgPreferenceStore};
+use badge_shared::notification_templates::{PgTemplateStore, TemplateRegistry};
 use badge_shared::observability;
 use notification_worker::consumer::NotificationConsumer;
+use notification_worker::dispatcher::{DispatchConfig, NotificationDispatcher};
+use notification_worker::provider::{
+    HttpPushProvider, HttpSmsProvider, NotificationProvider, RateLimiter, WeChatTemplateProvider,
+};
 use notification_worker::sender::{
-    AppPushSender, EmailSender, NotificationSender, SmsSender, WeChatSender,
+    DisabledSender, EmailSender, NotificationSender, ProviderSender,
 };
 use tokio::sync::watch;
 use tracing::info;
@@ -31,30 +40,58 @@ async fn main() -> Result<()> {
 
     let producer = KafkaProducer::new(&config.kafka)?;
 
-    // 注册所有渠道发送器，每个渠道独立实现 NotificationSender trait
+    // 注册所有渠道发送器：短信、微信、APP 推送经由 HTTP 提供方，邮件经由 SMTP；
+    // 未启用的渠道注册为 DisabledSender，发送直接按永久失败处理
     let senders: HashMap<NotificationChannel, Arc<dyn NotificationSender>> = HashMap::from([
-        (
+        provider_sender(
             NotificationChannel::AppPush,
-            Arc::new(AppPushSender) as Arc<dyn NotificationSender>,
+            config.push.enabled,
+            || Arc::new(HttpPushProvider::new(config.push.clone())),
+            config.push.rate_limit_per_sec,
         ),
-        (
+        provider_sender(
             NotificationChannel::Sms,
-            Arc::new(SmsSender) as Arc<dyn NotificationSender>,
+            config.sms.enabled,
+            || Arc::new(HttpSmsProvider::new(config.sms.clone())),
+            config.sms.rate_limit_per_sec,
         ),
-        (
+        provider_sender(
             NotificationChannel::WeChat,
-            Arc::new(WeChatSender) as Arc<dyn NotificationSender>,
+            config.wechat.enabled,
+            || Arc::new(WeChatTemplateProvider::new(config.wechat.clone())),
+            config.wechat.rate_limit_per_sec,
         ),
         (
             NotificationChannel::Email,
-            Arc::new(EmailSender) as Arc<dyn NotificationSender>,
+            Arc::new(EmailSender::from_config(&config.smtp)?) as Arc<dyn NotificationSender>,
         ),
     ]);
 
-    let consumer = NotificationConsumer::new(&config, senders, producer)?;
+    // 用户偏好、联系方式、免打扰延迟队列和通知模板存储在数据库中
+    let db = Database::connect(&config.database).await?;
+    let dispatch_config = DispatchConfig::from_env();
+    let templates = TemplateRegistry::builtin()
+        .with_store(Arc::new(PgTemplateStore::new(db.pool().clone())));
+    let dispatcher = Arc::new(
+        NotificationDispatcher::new(senders)
+            .with_config(&dispatch_config)
+            .with_preference_store(Arc::new(PgPreferenceStore::new(db.pool().clone())))
+            .with_deferred_store(Arc::new(PgDeferredNotificationStore::new(db.pool().clone())))
+            .with_contact_store(Arc::new(PgContactStore::new(db.pool().clone())))
+            .with_template_registry(Arc::new(templates))
+            .with_dead_letter_producer(producer),
+    );
+
+    let consumer = NotificationConsumer::new(&config, dispatcher.clone())?;
 
     let (shutdown_tx, shutdown_rx) = watch::channel(false);
 
+    // 摘要刷出与延迟通知投递
+    let scheduler_handle = tokio::spawn(dispatcher.run_scheduler(
+        Duration::from_millis(dispatch_config.poll_interval_ms),
+        shutdown_rx.clone(),
+    ));
+
     // 健康检查端点已由 observability 模块在 metrics_port 上提供
     let shutdown_handle = tokio::spawn(async move {
         shutdown_signal().await;
@@ -65,11 +102,27 @@ async fn main() -> Result<()> {
     consumer.run(shutdown_rx).await?;
 
     let _ = shutdown_handle.await;
+    let _ = scheduler_handle.await;
 
     info!("notification-worker 已关闭");
     Ok(())
 }
 
+/// 构建第三方提供方渠道的发送器，未启用时返回 `DisabledSender`
+fn provider_sender(
+    channel: NotificationChannel,
+    enabled: bool,
+    provider: impl FnOnce() -> Arc<dyn NotificationProvider>,
+    rate_limit_per_sec: u32,
+) -> (NotificationChannel, Arc<dyn NotificationSender>) {
+    if !enabled {
+        info!(channel = ?channel, "渠道未启用");
+        return (channel.clone(), Arc::new(DisabledSender::new(channel)));
+    }
+    let sender = ProviderSender::new(provider(), RateLimiter::per_second(rate_limit_per_sec));
+    (channel, Arc::new(sender))
+}
+
 /// 监听操作系统关闭信号
 ///
 /// 同时监听 SIGINT（Ctrl+C）和 SIGTERM（容器编排发送），
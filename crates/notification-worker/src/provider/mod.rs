@@ -0,0 +1,146 @@
+//! 第三方通知渠道提供方
+//!
+//! 每个渠道的外部接口（短信网关、微信订阅消息、HTTP 推送）实现 `NotificationProvider`，
+//! 由 `ProviderSender` 适配为 `NotificationSender` 并统一处理限流与结果转换。
+//!
+//! ## 错误分类
+//!
+//! - 网络错误、超时、5xx、429 以及渠道明确返回的繁忙错误视为瞬时故障，可重试
+//! - 收件人缺失或无效、鉴权失败、用户拒收等视为永久失败，重试无意义
+//!
+//! 收件人标识从通知事件的 `data` 中读取：手机号 `phone`、微信 `openid`、
+//! 推送设备 `device_token`，由分发器发送前按用户联系方式补全。
+//!
+//! 各渠道配置来自 `AppConfig` 的 `sms` / `wechat` / `push` 段，默认未启用。
+
+mod push;
+mod rate_limit;
+mod sms;
+mod wechat;
+
+pub use push::HttpPushProvider;
+pub use rate_limit::RateLimiter;
+pub use sms::HttpSmsProvider;
+pub use wechat::WeChatTemplateProvider;
+
+pub use badge_shared::config::{PushProviderConfig, SmsProviderConfig, WeChatProviderConfig};
+
+pub use badge_shared::notification_contacts::{
+    DEVICE_TOKEN_DATA_KEY, OPENID_DATA_KEY, PHONE_DATA_KEY,
+};
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use badge_shared::events::{NotificationChannel, NotificationEvent};
+use reqwest::StatusCode;
+use thiserror::Error;
+
+/// 渠道提供方调用失败
+#[derive(Debug, Clone, Error)]
+pub enum ProviderError {
+    /// 瞬时故障，稍后重试可能成功
+    #[error("{provider} 暂时不可用: {message}")]
+    Retryable {
+        provider: &'static str,
+        message: String,
+    },
+
+    /// 永久失败，重试无意义
+    #[error("{provider} 拒绝发送: {message}")]
+    Permanent {
+        provider: &'static str,
+        message: String,
+    },
+}
+
+impl ProviderError {
+    pub fn retryable(provider: &'static str, message: impl Into<String>) -> Self {
+        Self::Retryable {
+            provider,
+            message: message.into(),
+        }
+    }
+
+    pub fn permanent(provider: &'static str, message: impl Into<String>) -> Self {
+        Self::Permanent {
+            provider,
+            message: message.into(),
+        }
+    }
+
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::Retryable { .. })
+    }
+
+    /// 请求未得到响应（连接失败、超时等）均视为瞬时故障
+    pub(crate) fn from_request(provider: &'static str, error: reqwest::Error) -> Self {
+        Self::retryable(provider, format!("请求失败: {}", error))
+    }
+
+    /// 按 HTTP 状态码分类：5xx 和 429 可重试，其余 4xx 为永久失败
+    pub(crate) fn from_status(provider: &'static str, status: StatusCode, body: &str) -> Self {
+        let message = format!("HTTP {} {}", status, body);
+        if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+            Self::retryable(provider, message)
+        } else {
+            Self::permanent(provider, message)
+        }
+    }
+}
+
+/// 渠道提供方
+///
+/// 只负责把一条通知投递到外部系统，限流和结果封装由 `ProviderSender` 统一处理。
+#[async_trait]
+pub trait NotificationProvider: Send + Sync {
+    /// 提供方名称，用于日志和错误信息
+    fn name(&self) -> &'static str;
+
+    /// 对应的通知渠道
+    fn channel(&self) -> NotificationChannel;
+
+    /// 投递通知，成功时返回外部消息 ID
+    async fn deliver(&self, notification: &NotificationEvent) -> Result<String, ProviderError>;
+}
+
+/// 构建带超时的 HTTP 客户端
+fn http_client(timeout_ms: u64) -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(Duration::from_millis(timeout_ms))
+        .build()
+        .unwrap_or_default()
+}
+
+/// 从通知数据读取收件人标识，缺失时为永久失败
+fn recipient<'a>(
+    provider: &'static str,
+    notification: &'a NotificationEvent,
+    key: &str,
+) -> Result<&'a str, ProviderError> {
+    notification
+        .data
+        .get(key)
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| ProviderError::permanent(provider, format!("通知未携带收件人字段 {}", key)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_classification() {
+        let err = ProviderError::from_status("sms", StatusCode::SERVICE_UNAVAILABLE, "");
+        assert!(err.is_retryable());
+
+        let err = ProviderError::from_status("sms", StatusCode::TOO_MANY_REQUESTS, "");
+        assert!(err.is_retryable());
+
+        let err = ProviderError::from_status("sms", StatusCode::BAD_REQUEST, "invalid phone");
+        assert!(!err.is_retryable());
+        assert!(err.to_string().contains("invalid phone"));
+    }
+}
@@ -0,0 +1,143 @@
+//! HTTP APP 推送
+//!
+//! 按 FCM HTTP v1 的消息格式调用推送服务：`message.token` 指定设备，
+//! `message.notification` 为展示内容，`message.data` 透传业务数据（值必须为字符串）。
+//! 服务端密钥通过 `Authorization: Bearer` 传递，响应中的 `name` 作为消息 ID。
+
+use async_trait::async_trait;
+use badge_shared::config::PushProviderConfig;
+use badge_shared::events::{NotificationChannel, NotificationEvent};
+use serde::Deserialize;
+use tracing::debug;
+
+use super::{DEVICE_TOKEN_DATA_KEY, NotificationProvider, ProviderError, http_client, recipient};
+
+const PROVIDER: &str = "push";
+
+#[derive(Debug, Deserialize)]
+struct PushResponse {
+    name: Option<String>,
+}
+
+/// HTTP 推送提供方
+pub struct HttpPushProvider {
+    client: reqwest::Client,
+    config: PushProviderConfig,
+}
+
+impl HttpPushProvider {
+    pub fn new(config: PushProviderConfig) -> Self {
+        Self {
+            client: http_client(config.timeout_ms),
+            config,
+        }
+    }
+}
+
+/// 把通知数据展开为字符串键值对，并附带通知 ID 和类型供客户端路由
+fn push_data(notification: &NotificationEvent) -> serde_json::Map<String, serde_json::Value> {
+    let mut data = serde_json::Map::new();
+    if let Some(object) = notification.data.as_object() {
+        for (key, value) in object {
+            if key == DEVICE_TOKEN_DATA_KEY {
+                continue;
+            }
+            let value = match value {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            data.insert(key.clone(), serde_json::Value::String(value));
+        }
+    }
+    data.insert(
+        "notification_id".to_string(),
+        serde_json::Value::String(notification.notification_id.clone()),
+    );
+    data.insert(
+        "notification_type".to_string(),
+        serde_json::json!(notification.notification_type),
+    );
+    data
+}
+
+#[async_trait]
+impl NotificationProvider for HttpPushProvider {
+    fn name(&self) -> &'static str {
+        PROVIDER
+    }
+
+    fn channel(&self) -> NotificationChannel {
+        NotificationChannel::AppPush
+    }
+
+    async fn deliver(&self, notification: &NotificationEvent) -> Result<String, ProviderError> {
+        let device_token = recipient(PROVIDER, notification, DEVICE_TOKEN_DATA_KEY)?;
+
+        let response = self
+            .client
+            .post(&self.config.url)
+            .bearer_auth(&self.config.token)
+            .json(&serde_json::json!({
+                "message": {
+                    "token": device_token,
+                    "notification": {
+                        "title": notification.title,
+                        "body": notification.body,
+                    },
+                    "data": push_data(notification),
+                }
+            }))
+            .send()
+            .await
+            .map_err(|e| ProviderError::from_request(PROVIDER, e))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(ProviderError::from_status(PROVIDER, status, &body));
+        }
+
+        let body: PushResponse = response
+            .json()
+            .await
+            .map_err(|e| ProviderError::permanent(PROVIDER, format!("响应格式无效: {}", e)))?;
+        let message_id = body
+            .name
+            .ok_or_else(|| ProviderError::permanent(PROVIDER, "响应缺少 name"))?;
+
+        debug!(
+            notification_id = %notification.notification_id,
+            message_id = %message_id,
+            "APP 推送已受理"
+        );
+        Ok(message_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use badge_shared::events::NotificationType;
+    use chrono::Utc;
+
+    #[test]
+    fn test_push_data_stringifies_values() {
+        let notification = NotificationEvent {
+            notification_id: "n-1".to_string(),
+            user_id: "user-1".to_string(),
+            notification_type: NotificationType::BadgeGranted,
+            title: "t".to_string(),
+            body: "b".to_string(),
+            data: serde_json::json!({"badge_id": 42, "badge_name": "首单", "device_token": "d-1"}),
+            channels: vec![NotificationChannel::AppPush],
+            created_at: Utc::now(),
+        };
+
+        let data = push_data(&notification);
+        assert_eq!(data["badge_id"], "42");
+        assert_eq!(data["badge_name"], "首单");
+        assert_eq!(data["notification_id"], "n-1");
+        assert!(data["notification_type"].is_string());
+        assert!(!data.contains_key("device_token"));
+    }
+}
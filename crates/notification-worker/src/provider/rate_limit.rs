@@ -0,0 +1,101 @@
+//! 渠道限流
+//!
+//! 令牌桶实现，按提供方的每秒请求上限平滑发送速率。令牌不足时等待补充，
+//! 而不是直接失败：短时突发通知应延迟送达，不应被当作发送失败。
+
+use std::time::Duration;
+
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// 令牌桶限流器
+pub struct RateLimiter {
+    /// 每秒补充的令牌数，0 表示不限流
+    rate_per_sec: f64,
+    /// 桶容量，允许的最大突发量
+    capacity: f64,
+    bucket: Mutex<Bucket>,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl RateLimiter {
+    /// 创建每秒最多 `rate_per_sec` 次请求的限流器，突发容量为一秒的配额
+    pub fn per_second(rate_per_sec: u32) -> Self {
+        let rate = rate_per_sec as f64;
+        Self {
+            rate_per_sec: rate,
+            capacity: rate.max(1.0),
+            bucket: Mutex::new(Bucket {
+                tokens: rate.max(1.0),
+                updated_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// 不限流
+    pub fn unlimited() -> Self {
+        Self::per_second(0)
+    }
+
+    /// 获取一个令牌，令牌不足时等待
+    pub async fn acquire(&self) {
+        if self.rate_per_sec <= 0.0 {
+            return;
+        }
+
+        loop {
+            let wait = {
+                let mut bucket = self.bucket.lock().await;
+                let now = Instant::now();
+                let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+                bucket.tokens = (bucket.tokens + elapsed * self.rate_per_sec).min(self.capacity);
+                bucket.updated_at = now;
+
+                if bucket.tokens >= 1.0 {
+                    bucket.tokens -= 1.0;
+                    return;
+                }
+                Duration::from_secs_f64((1.0 - bucket.tokens) / self.rate_per_sec)
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_burst_within_capacity_is_immediate() {
+        let limiter = RateLimiter::per_second(10);
+        let start = Instant::now();
+        for _ in 0..10 {
+            limiter.acquire().await;
+        }
+        assert!(start.elapsed() < Duration::from_millis(50));
+    }
+
+    #[tokio::test]
+    async fn test_exceeding_rate_waits_for_refill() {
+        let limiter = RateLimiter::per_second(20);
+        let start = Instant::now();
+        // 容量 20，额外 5 个令牌需要约 250ms 补充
+        for _ in 0..25 {
+            limiter.acquire().await;
+        }
+        assert!(start.elapsed() >= Duration::from_millis(200));
+    }
+
+    #[tokio::test]
+    async fn test_unlimited() {
+        let limiter = RateLimiter::unlimited();
+        for _ in 0..1000 {
+            limiter.acquire().await;
+        }
+    }
+}
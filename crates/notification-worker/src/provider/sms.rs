@@ -0,0 +1,83 @@
+//! 通用短信网关
+//!
+//! 以 JSON 调用短信网关的发送接口，API 密钥通过 `Authorization: Bearer` 传递。
+//! 网关返回 2xx 且包含 `message_id` 视为发送成功。
+
+use async_trait::async_trait;
+use badge_shared::config::SmsProviderConfig;
+use badge_shared::events::{NotificationChannel, NotificationEvent};
+use serde::Deserialize;
+use tracing::debug;
+
+use super::{NotificationProvider, PHONE_DATA_KEY, ProviderError, http_client, recipient};
+
+const PROVIDER: &str = "sms";
+
+#[derive(Debug, Deserialize)]
+struct SmsResponse {
+    message_id: Option<String>,
+}
+
+/// HTTP 短信网关提供方
+pub struct HttpSmsProvider {
+    client: reqwest::Client,
+    config: SmsProviderConfig,
+}
+
+impl HttpSmsProvider {
+    pub fn new(config: SmsProviderConfig) -> Self {
+        Self {
+            client: http_client(config.timeout_ms),
+            config,
+        }
+    }
+}
+
+#[async_trait]
+impl NotificationProvider for HttpSmsProvider {
+    fn name(&self) -> &'static str {
+        PROVIDER
+    }
+
+    fn channel(&self) -> NotificationChannel {
+        NotificationChannel::Sms
+    }
+
+    async fn deliver(&self, notification: &NotificationEvent) -> Result<String, ProviderError> {
+        let phone = recipient(PROVIDER, notification, PHONE_DATA_KEY)?;
+
+        let response = self
+            .client
+            .post(&self.config.url)
+            .bearer_auth(&self.config.token)
+            .json(&serde_json::json!({
+                "phone": phone,
+                "content": notification.body,
+                "sign_name": self.config.sign_name,
+            }))
+            .send()
+            .await
+            .map_err(|e| ProviderError::from_request(PROVIDER, e))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(ProviderError::from_status(PROVIDER, status, &body));
+        }
+
+        let body: SmsResponse = response
+            .json()
+            .await
+            .map_err(|e| ProviderError::permanent(PROVIDER, format!("响应格式无效: {}", e)))?;
+        let message_id = body
+            .message_id
+            .ok_or_else(|| ProviderError::permanent(PROVIDER, "响应缺少 message_id"))?;
+
+        debug!(
+            notification_id = %notification.notification_id,
+            message_id = %message_id,
+            "短信网关已受理"
+        );
+        Ok(message_id)
+    }
+}
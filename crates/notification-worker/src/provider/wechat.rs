@@ -0,0 +1,273 @@
+//! 微信订阅消息
+//!
+//! 调用微信 `message/subscribe/send` 接口推送订阅消息。接口需要 access_token，
+//! access_token 有效期 2 小时且每日获取次数有限，因此在进程内缓存并提前刷新。
+//!
+//! 微信接口的 HTTP 状态码恒为 200，业务结果通过 `errcode` 区分：
+//!
+//! - `40001` / `40014` / `42001`：access_token 无效或过期，刷新后重试一次
+//! - `-1` / `45009`：系统繁忙或调用频率超限，可重试
+//! - 其他非零值（如 `43101` 用户拒收、`40003` openid 无效）为永久失败
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use badge_shared::config::WeChatProviderConfig;
+use badge_shared::events::{NotificationChannel, NotificationEvent};
+use serde::Deserialize;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+use tracing::{debug, info, warn};
+
+use super::{NotificationProvider, OPENID_DATA_KEY, ProviderError, http_client, recipient};
+
+const PROVIDER: &str = "wechat";
+
+/// access_token 提前刷新的时间余量
+const TOKEN_REFRESH_MARGIN: Duration = Duration::from_secs(300);
+
+/// `thing` 类型模板字段的最大字符数
+const THING_MAX_CHARS: usize = 20;
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: Option<String>,
+    expires_in: Option<u64>,
+    #[serde(default)]
+    errcode: i64,
+    #[serde(default)]
+    errmsg: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SendResponse {
+    #[serde(default)]
+    errcode: i64,
+    #[serde(default)]
+    errmsg: String,
+    msgid: Option<serde_json::Value>,
+}
+
+struct CachedToken {
+    token: String,
+    refresh_at: Instant,
+}
+
+/// 微信订阅消息提供方
+pub struct WeChatTemplateProvider {
+    client: reqwest::Client,
+    config: WeChatProviderConfig,
+    /// 互斥锁同时保证并发请求只触发一次刷新
+    token: Mutex<Option<CachedToken>>,
+}
+
+impl WeChatTemplateProvider {
+    pub fn new(config: WeChatProviderConfig) -> Self {
+        Self {
+            client: http_client(config.timeout_ms),
+            config,
+            token: Mutex::new(None),
+        }
+    }
+
+    /// 获取可用的 access_token，缓存失效时向微信重新申请
+    async fn access_token(&self) -> Result<String, ProviderError> {
+        let mut cached = self.token.lock().await;
+        if let Some(token) = cached.as_ref()
+            && Instant::now() < token.refresh_at
+        {
+            return Ok(token.token.clone());
+        }
+
+        let response = self
+            .client
+            .get(format!("{}/cgi-bin/token", self.config.api_base))
+            .query(&[
+                ("grant_type", "client_credential"),
+                ("appid", self.config.appid.as_str()),
+                ("secret", self.config.secret.as_str()),
+            ])
+            .send()
+            .await
+            .map_err(|e| ProviderError::from_request(PROVIDER, e))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(ProviderError::from_status(PROVIDER, status, &body));
+        }
+
+        let body: TokenResponse = response
+            .json()
+            .await
+            .map_err(|e| ProviderError::retryable(PROVIDER, format!("令牌响应格式无效: {}", e)))?;
+
+        let Some(token) = body.access_token else {
+            let message = format!("获取 access_token 失败: {} {}", body.errcode, body.errmsg);
+            return Err(if body.errcode == -1 {
+                ProviderError::retryable(PROVIDER, message)
+            } else {
+                ProviderError::permanent(PROVIDER, message)
+            });
+        };
+
+        let ttl = Duration::from_secs(body.expires_in.unwrap_or(7200));
+        let lifetime = if ttl > TOKEN_REFRESH_MARGIN * 2 {
+            ttl - TOKEN_REFRESH_MARGIN
+        } else {
+            ttl / 2
+        };
+        info!(expires_in = ttl.as_secs(), "微信 access_token 已刷新");
+
+        *cached = Some(CachedToken {
+            token: token.clone(),
+            refresh_at: Instant::now() + lifetime,
+        });
+        Ok(token)
+    }
+
+    /// 丢弃缓存的 access_token，仅当缓存的仍是失效的那一个时才清除
+    async fn invalidate_token(&self, token: &str) {
+        let mut cached = self.token.lock().await;
+        if cached.as_ref().is_some_and(|t| t.token == token) {
+            *cached = None;
+        }
+    }
+
+    async fn send_message(
+        &self,
+        token: &str,
+        openid: &str,
+        notification: &NotificationEvent,
+    ) -> Result<SendResponse, ProviderError> {
+        let mut data = serde_json::Map::new();
+        data.insert(
+            self.config.title_field.clone(),
+            serde_json::json!({ "value": truncate_chars(&notification.title, THING_MAX_CHARS) }),
+        );
+        data.insert(
+            self.config.content_field.clone(),
+            serde_json::json!({ "value": truncate_chars(&notification.body, THING_MAX_CHARS) }),
+        );
+
+        let mut payload = serde_json::json!({
+            "touser": openid,
+            "template_id": self.config.template_id,
+            "data": data,
+        });
+        if let Some(page) = &self.config.page {
+            payload["page"] = serde_json::json!(page);
+        }
+
+        let response = self
+            .client
+            .post(format!(
+                "{}/cgi-bin/message/subscribe/send",
+                self.config.api_base
+            ))
+            .query(&[("access_token", token)])
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| ProviderError::from_request(PROVIDER, e))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(ProviderError::from_status(PROVIDER, status, &body));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| ProviderError::retryable(PROVIDER, format!("响应格式无效: {}", e)))
+    }
+}
+
+/// access_token 无效或过期的错误码
+fn is_token_error(errcode: i64) -> bool {
+    matches!(errcode, 40001 | 40014 | 42001)
+}
+
+/// 按字符截断，模板字段按字符数而非字节数限制长度
+fn truncate_chars(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((idx, _)) => text[..idx].to_string(),
+        None => text.to_string(),
+    }
+}
+
+#[async_trait]
+impl NotificationProvider for WeChatTemplateProvider {
+    fn name(&self) -> &'static str {
+        PROVIDER
+    }
+
+    fn channel(&self) -> NotificationChannel {
+        NotificationChannel::WeChat
+    }
+
+    async fn deliver(&self, notification: &NotificationEvent) -> Result<String, ProviderError> {
+        let openid = recipient(PROVIDER, notification, OPENID_DATA_KEY)?;
+
+        let mut token = self.access_token().await?;
+        let mut response = self.send_message(&token, openid, notification).await?;
+
+        if is_token_error(response.errcode) {
+            warn!(
+                errcode = response.errcode,
+                "微信 access_token 已失效，刷新后重试"
+            );
+            self.invalidate_token(&token).await;
+            token = self.access_token().await?;
+            response = self.send_message(&token, openid, notification).await?;
+        }
+
+        match response.errcode {
+            0 => {
+                let message_id = match response.msgid {
+                    Some(serde_json::Value::String(id)) => id,
+                    Some(other) => other.to_string(),
+                    None => String::new(),
+                };
+                debug!(
+                    notification_id = %notification.notification_id,
+                    message_id = %message_id,
+                    "微信订阅消息已发送"
+                );
+                Ok(message_id)
+            }
+            code @ (-1 | 45009) => Err(ProviderError::retryable(
+                PROVIDER,
+                format!("{} {}", code, response.errmsg),
+            )),
+            code if is_token_error(code) => Err(ProviderError::retryable(
+                PROVIDER,
+                format!("access_token 刷新后仍无效: {} {}", code, response.errmsg),
+            )),
+            code => Err(ProviderError::permanent(
+                PROVIDER,
+                format!("{} {}", code, response.errmsg),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_chars() {
+        assert_eq!(truncate_chars("恭喜获得首次购物徽章", 4), "恭喜获得");
+        assert_eq!(truncate_chars("short", 20), "short");
+    }
+
+    #[test]
+    fn test_token_error_codes() {
+        assert!(is_token_error(40001));
+        assert!(is_token_error(42001));
+        assert!(!is_token_error(43101));
+        assert!(!is_token_error(0));
+    }
+}
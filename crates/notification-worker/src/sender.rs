@@ -1,25 +1,56 @@
 //! 多渠道通知发送器
 //!
 //! 通过 `NotificationSender` trait 抽象发送行为，各渠道（APP Push、SMS、微信、邮件）
-//! 提供独立实现。当前版本为模拟发送（仅记录日志），便于在无外部依赖的情况下
-//! 验证消费管道的完整性。未来替换为真实 SDK 调用时只需实现同一 trait。
+//! 提供独立实现。邮件通过 SMTP 投递；短信、微信和 APP 推送由 `ProviderSender`
+//! 包装对应的 HTTP 提供方（见 `provider` 模块），统一处理限流和失败分类。
+
+use std::sync::Arc;
 
 use async_trait::async_trait;
+use badge_shared::config::SmtpConfig;
+use badge_shared::email::{EmailMessage, SmtpMailer};
 use badge_shared::events::{NotificationChannel, NotificationEvent};
-use tracing::info;
-use uuid::Uuid;
+use badge_shared::notification_contacts::EMAIL_DATA_KEY;
+use badge_shared::notification_templates::HTML_BODY_DATA_KEY;
+use tracing::{info, warn};
 
 use crate::error::NotificationError;
+use crate::provider::{NotificationProvider, RateLimiter};
 
 /// 发送结果
 ///
 /// 统一记录各渠道的发送状态，consumer 汇总后决定是否需要重试。
+#[derive(Debug)]
 pub struct SendResult {
     pub success: bool,
     pub channel: NotificationChannel,
     /// 外部渠道返回的消息标识，用于追踪投递状态
     pub message_id: Option<String>,
     pub error: Option<String>,
+    /// 失败是否为瞬时故障，永久失败（如收件人无效）重试无意义
+    pub retryable: bool,
+}
+
+impl SendResult {
+    pub fn success(channel: NotificationChannel, message_id: impl Into<String>) -> Self {
+        Self {
+            success: true,
+            channel,
+            message_id: Some(message_id.into()),
+            error: None,
+            retryable: false,
+        }
+    }
+
+    pub fn failed(channel: NotificationChannel, error: impl Into<String>, retryable: bool) -> Self {
+        Self {
+            success: false,
+            channel,
+            message_id: None,
+            error: Some(error.into()),
+            retryable,
+        }
+    }
 }
 
 /// 通知发送器 trait，各渠道实现具体的推送逻辑
@@ -34,155 +65,193 @@ pub trait NotificationSender: Send + Sync {
 }
 
 // ---------------------------------------------------------------------------
-// APP 推送发送器
+// 第三方提供方发送器
 // ---------------------------------------------------------------------------
 
-/// 模拟 APP 推送发送器
+/// 基于第三方提供方的发送器
 ///
-/// 生产环境中替换为 APNs / FCM 等推送服务的 SDK 调用
-pub struct AppPushSender;
+/// 发送前按提供方限流，提供方的错误分类直接映射为 `SendResult.retryable`。
+pub struct ProviderSender {
+    provider: Arc<dyn NotificationProvider>,
+    limiter: RateLimiter,
+}
+
+impl ProviderSender {
+    pub fn new(provider: Arc<dyn NotificationProvider>, limiter: RateLimiter) -> Self {
+        Self { provider, limiter }
+    }
+}
 
 #[async_trait]
-impl NotificationSender for AppPushSender {
+impl NotificationSender for ProviderSender {
     async fn send(
         &self,
         notification: &NotificationEvent,
     ) -> Result<SendResult, NotificationError> {
-        let message_id = Uuid::now_v7().to_string();
-
-        info!(
-            channel = "APP_PUSH",
-            notification_id = %notification.notification_id,
-            user_id = %notification.user_id,
-            message_id = %message_id,
-            title = %notification.title,
-            "模拟发送 APP 推送通知"
-        );
-
-        Ok(SendResult {
-            success: true,
-            channel: NotificationChannel::AppPush,
-            message_id: Some(message_id),
-            error: None,
-        })
+        self.limiter.acquire().await;
+
+        let channel = self.provider.channel();
+        match self.provider.deliver(notification).await {
+            Ok(message_id) => {
+                info!(
+                    provider = self.provider.name(),
+                    notification_id = %notification.notification_id,
+                    user_id = %notification.user_id,
+                    message_id = %message_id,
+                    "通知已投递"
+                );
+                Ok(SendResult::success(channel, message_id))
+            }
+            Err(e) => {
+                warn!(
+                    provider = self.provider.name(),
+                    notification_id = %notification.notification_id,
+                    retryable = e.is_retryable(),
+                    error = %e,
+                    "通知投递失败"
+                );
+                Ok(SendResult::failed(channel, e.to_string(), e.is_retryable()))
+            }
+        }
     }
 
     fn channel(&self) -> NotificationChannel {
-        NotificationChannel::AppPush
+        self.provider.channel()
     }
 }
 
-// ---------------------------------------------------------------------------
-// 短信发送器
-// ---------------------------------------------------------------------------
-
-/// 模拟短信发送器
+/// 未启用渠道的发送器
 ///
-/// 生产环境中替换为短信服务商（如阿里云 SMS）的 API 调用
-pub struct SmsSender;
+/// 渠道未配置时仍需注册发送器，否则消费端会按"发送器未注册"重试并进入死信队列；
+/// 未启用属于永久失败，直接返回不可重试的结果。
+pub struct DisabledSender {
+    channel: NotificationChannel,
+}
+
+impl DisabledSender {
+    pub fn new(channel: NotificationChannel) -> Self {
+        Self { channel }
+    }
+}
 
 #[async_trait]
-impl NotificationSender for SmsSender {
+impl NotificationSender for DisabledSender {
     async fn send(
         &self,
-        notification: &NotificationEvent,
+        _notification: &NotificationEvent,
     ) -> Result<SendResult, NotificationError> {
-        let message_id = Uuid::now_v7().to_string();
-
-        info!(
-            channel = "SMS",
-            notification_id = %notification.notification_id,
-            user_id = %notification.user_id,
-            message_id = %message_id,
-            body = %notification.body,
-            "模拟发送短信通知"
-        );
-
-        Ok(SendResult {
-            success: true,
-            channel: NotificationChannel::Sms,
-            message_id: Some(message_id),
-            error: None,
-        })
+        Ok(SendResult::failed(
+            self.channel.clone(),
+            format!("{:?} 渠道未启用", self.channel),
+            false,
+        ))
     }
 
     fn channel(&self) -> NotificationChannel {
-        NotificationChannel::Sms
+        self.channel.clone()
     }
 }
 
 // ---------------------------------------------------------------------------
-// 微信发送器
+// 邮件发送器
 // ---------------------------------------------------------------------------
 
-/// 模拟微信推送发送器
+/// SMTP 邮件发送器
 ///
-/// 生产环境中替换为微信模板消息 / 订阅消息的 API 调用
-pub struct WeChatSender;
-
-#[async_trait]
-impl NotificationSender for WeChatSender {
-    async fn send(
-        &self,
-        notification: &NotificationEvent,
-    ) -> Result<SendResult, NotificationError> {
-        let message_id = Uuid::now_v7().to_string();
-
-        info!(
-            channel = "WECHAT",
-            notification_id = %notification.notification_id,
-            user_id = %notification.user_id,
-            message_id = %message_id,
-            title = %notification.title,
-            "模拟发送微信推送通知"
-        );
+/// 收件人地址取自通知数据的 `email` 字段（由分发器按用户联系方式补全），邮件包含纯文本和 HTML 两种正文，
+/// 返回的 `message_id` 即邮件头中的 `Message-ID`。
+pub struct EmailSender {
+    mailer: SmtpMailer,
+    /// SMTP 未启用时不投递，直接返回失败结果
+    enabled: bool,
+}
 
-        Ok(SendResult {
-            success: true,
-            channel: NotificationChannel::WeChat,
-            message_id: Some(message_id),
-            error: None,
-        })
+impl EmailSender {
+    pub fn new(mailer: SmtpMailer, enabled: bool) -> Self {
+        Self { mailer, enabled }
     }
 
-    fn channel(&self) -> NotificationChannel {
-        NotificationChannel::WeChat
+    /// 根据 SMTP 配置创建
+    pub fn from_config(config: &SmtpConfig) -> Result<Self, NotificationError> {
+        let mailer =
+            SmtpMailer::from_config(config).map_err(|e| NotificationError::SendFailed {
+                channel: "EMAIL".to_string(),
+                reason: e.to_string(),
+            })?;
+        Ok(Self::new(mailer, config.enabled))
     }
 }
 
-// ---------------------------------------------------------------------------
-// 邮件发送器
-// ---------------------------------------------------------------------------
-
-/// 模拟邮件发送器
-///
-/// 生产环境中替换为 SMTP 或邮件服务商（如 SendGrid）的 API 调用
-pub struct EmailSender;
-
 #[async_trait]
 impl NotificationSender for EmailSender {
     async fn send(
         &self,
         notification: &NotificationEvent,
     ) -> Result<SendResult, NotificationError> {
-        let message_id = Uuid::now_v7().to_string();
-
-        info!(
-            channel = "EMAIL",
-            notification_id = %notification.notification_id,
-            user_id = %notification.user_id,
-            message_id = %message_id,
-            title = %notification.title,
-            body = %notification.body,
-            "模拟发送邮件通知"
-        );
+        if !self.enabled {
+            return Ok(SendResult::failed(
+                NotificationChannel::Email,
+                "邮件渠道未启用",
+                false,
+            ));
+        }
 
-        Ok(SendResult {
-            success: true,
-            channel: NotificationChannel::Email,
-            message_id: Some(message_id),
-            error: None,
-        })
+        let Some(to) = notification
+            .data
+            .get(EMAIL_DATA_KEY)
+            .and_then(|v| v.as_str())
+            .filter(|v| !v.trim().is_empty())
+        else {
+            warn!(
+                channel = "EMAIL",
+                notification_id = %notification.notification_id,
+                user_id = %notification.user_id,
+                "通知未携带收件人邮箱，跳过邮件发送"
+            );
+            return Ok(SendResult::failed(
+                NotificationChannel::Email,
+                "用户未绑定邮箱",
+                false,
+            ));
+        };
+
+        let mut email =
+            EmailMessage::notification(to.trim(), &notification.title, &notification.body);
+        if let Some(html) = notification
+            .data
+            .get(HTML_BODY_DATA_KEY)
+            .and_then(|v| v.as_str())
+        {
+            email = email.with_html_content(html);
+        }
+
+        match self.mailer.send(&email).await {
+            Ok(message_id) => {
+                info!(
+                    channel = "EMAIL",
+                    notification_id = %notification.notification_id,
+                    user_id = %notification.user_id,
+                    message_id = %message_id,
+                    "邮件通知已发送"
+                );
+
+                Ok(SendResult::success(NotificationChannel::Email, message_id))
+            }
+            Err(e) => {
+                warn!(
+                    channel = "EMAIL",
+                    notification_id = %notification.notification_id,
+                    error = %e,
+                    "邮件通知发送失败"
+                );
+
+                Ok(SendResult::failed(
+                    NotificationChannel::Email,
+                    e.to_string(),
+                    e.is_retryable(),
+                ))
+            }
+        }
     }
 
     fn channel(&self) -> NotificationChannel {
@@ -197,7 +266,10 @@ impl NotificationSender for EmailSender {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::provider::ProviderError;
+    use badge_shared::config::SmtpTlsMode;
     use badge_shared::events::NotificationType;
+    use badge_shared::test_utils::SmtpSink;
     use chrono::Utc;
 
     /// 构造通用的测试通知事件
@@ -217,71 +289,122 @@ mod tests {
         }
     }
 
-    #[tokio::test]
-    async fn test_app_push_send() {
-        let sender = AppPushSender;
-        let notification = make_test_notification();
+    /// 按预设结果返回的提供方
+    struct FixedProvider(Result<String, ProviderError>);
 
-        let result = sender.send(&notification).await;
-        assert!(result.is_ok());
+    #[async_trait]
+    impl NotificationProvider for FixedProvider {
+        fn name(&self) -> &'static str {
+            "fixed"
+        }
 
-        let result = result.unwrap();
-        assert!(result.success);
-        assert_eq!(result.channel, NotificationChannel::AppPush);
-        assert!(result.message_id.is_some());
-        assert!(result.error.is_none());
+        fn channel(&self) -> NotificationChannel {
+            NotificationChannel::Sms
+        }
+
+        async fn deliver(&self, _: &NotificationEvent) -> Result<String, ProviderError> {
+            self.0.clone()
+        }
     }
 
     #[tokio::test]
-    async fn test_sms_send() {
-        let sender = SmsSender;
-        let notification = make_test_notification();
-
-        let result = sender.send(&notification).await;
-        assert!(result.is_ok());
+    async fn test_provider_sender_success() {
+        let sender = ProviderSender::new(
+            Arc::new(FixedProvider(Ok("msg-1".to_string()))),
+            RateLimiter::unlimited(),
+        );
 
-        let result = result.unwrap();
+        let result = sender.send(&make_test_notification()).await.unwrap();
         assert!(result.success);
         assert_eq!(result.channel, NotificationChannel::Sms);
-        assert!(result.message_id.is_some());
-        assert!(result.error.is_none());
+        assert_eq!(result.message_id.as_deref(), Some("msg-1"));
+        assert_eq!(sender.channel(), NotificationChannel::Sms);
     }
 
     #[tokio::test]
-    async fn test_wechat_send() {
-        let sender = WeChatSender;
-        let notification = make_test_notification();
+    async fn test_provider_sender_classifies_failures() {
+        let retryable = ProviderSender::new(
+            Arc::new(FixedProvider(Err(ProviderError::retryable(
+                "fixed", "busy",
+            )))),
+            RateLimiter::unlimited(),
+        );
+        let result = retryable.send(&make_test_notification()).await.unwrap();
+        assert!(!result.success);
+        assert!(result.retryable);
+
+        let permanent = ProviderSender::new(
+            Arc::new(FixedProvider(Err(ProviderError::permanent("fixed", "bad")))),
+            RateLimiter::unlimited(),
+        );
+        let result = permanent.send(&make_test_notification()).await.unwrap();
+        assert!(!result.success);
+        assert!(!result.retryable);
+        assert!(result.error.unwrap().contains("bad"));
+    }
 
-        let result = sender.send(&notification).await;
-        assert!(result.is_ok());
+    #[tokio::test]
+    async fn test_disabled_sender_fails_permanently() {
+        let sender = DisabledSender::new(NotificationChannel::Sms);
+        assert_eq!(sender.channel(), NotificationChannel::Sms);
 
-        let result = result.unwrap();
-        assert!(result.success);
-        assert_eq!(result.channel, NotificationChannel::WeChat);
-        assert!(result.message_id.is_some());
-        assert!(result.error.is_none());
+        let result = sender.send(&make_test_notification()).await.unwrap();
+        assert!(!result.success);
+        assert!(!result.retryable);
+        assert_eq!(result.channel, NotificationChannel::Sms);
     }
 
     #[tokio::test]
     async fn test_email_send() {
-        let sender = EmailSender;
-        let notification = make_test_notification();
-
-        let result = sender.send(&notification).await;
-        assert!(result.is_ok());
+        let sink = SmtpSink::start().await.unwrap();
+        let sender = EmailSender::from_config(&SmtpConfig {
+            enabled: true,
+            host: "127.0.0.1".to_string(),
+            port: sink.port(),
+            tls: SmtpTlsMode::None,
+            ..Default::default()
+        })
+        .unwrap();
+        let mut notification = make_test_notification();
+        notification.data[EMAIL_DATA_KEY] = serde_json::json!("user-001@example.com");
 
-        let result = result.unwrap();
+        let result = sender.send(&notification).await.unwrap();
         assert!(result.success);
         assert_eq!(result.channel, NotificationChannel::Email);
-        assert!(result.message_id.is_some());
         assert!(result.error.is_none());
+
+        let messages = sink.messages().await;
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].recipients, vec!["user-001@example.com"]);
+        assert_eq!(
+            messages[0].header("Message-ID"),
+            result.message_id.as_deref()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_email_send_without_address() {
+        let sender = EmailSender::from_config(&SmtpConfig {
+            enabled: true,
+            ..Default::default()
+        })
+        .unwrap();
+        let notification = make_test_notification();
+
+        let result = sender.send(&notification).await.unwrap();
+        assert!(!result.success);
+        assert!(!result.retryable);
+        assert!(result.message_id.is_none());
+        assert!(result.error.is_some());
     }
 
     #[test]
     fn test_sender_channel_type() {
-        assert_eq!(AppPushSender.channel(), NotificationChannel::AppPush);
-        assert_eq!(SmsSender.channel(), NotificationChannel::Sms);
-        assert_eq!(WeChatSender.channel(), NotificationChannel::WeChat);
-        assert_eq!(EmailSender.channel(), NotificationChannel::Email);
+        assert_eq!(
+            EmailSender::from_config(&SmtpConfig::default())
+                .unwrap()
+                .channel(),
+            NotificationChannel::Email
+        );
     }
 }
@@ -1,198 +1,157 @@
-//! 通知模板管理
+//! 通知模板渲染
 //!
-//! 根据通知类型生成对应的标题和正文内容。
-//! 当前使用硬编码模板以降低外部依赖，未来可扩展为
-//! 从数据库或配置中心动态加载模板。
-
-use badge_shared::events::NotificationType;
-
-/// 通知模板引擎
-///
-/// 根据通知类型生成标题和内容。当前版本使用硬编码模板，
-/// 未来可扩展为从数据库或配置中心加载。
-pub struct NotificationTemplateEngine {
-    // 预留扩展字段，用于后续添加模板缓存或外部配置源
-}
-
-impl NotificationTemplateEngine {
-    pub fn new() -> Self {
-        Self {}
+//! 发送前按用户语言和目标渠道重新渲染通知内容，模板模型和渲染引擎与
+//! badge-management-service 共用（见 `badge_shared::notification_templates`）。
+//! 没有匹配的模板或通知数据缺少模板变量时，沿用上游生成的标题和正文，
+//! 避免因上游数据不完整把占位符发给用户。
+
+use badge_shared::events::{NotificationChannel, NotificationEvent};
+use badge_shared::notification_preferences::{DEFAULT_LOCALE, LOCALE_DATA_KEY};
+use badge_shared::notification_templates::{HTML_BODY_DATA_KEY, TemplateSet};
+use tracing::debug;
+
+/// 按渠道渲染通知，返回只投递到该渠道的通知副本
+pub fn render_for_channel(
+    templates: &TemplateSet,
+    notification: &NotificationEvent,
+    channel: &NotificationChannel,
+) -> NotificationEvent {
+    let mut rendered = notification.clone();
+    rendered.channels = vec![channel.clone()];
+
+    let locale = notification
+        .data
+        .get(LOCALE_DATA_KEY)
+        .and_then(|v| v.as_str())
+        .unwrap_or(DEFAULT_LOCALE);
+    let Some(content) = templates.render(
+        &notification.notification_type,
+        locale,
+        Some(channel),
+        &notification.data,
+    ) else {
+        return rendered;
+    };
+    if !content.is_complete() {
+        debug!(
+            notification_id = %notification.notification_id,
+            channel = ?channel,
+            missing = ?content.missing_variables,
+            "通知数据缺少模板变量，沿用上游内容"
+        );
+        return rendered;
     }
 
-    /// 根据通知类型和上下文数据渲染标题
-    ///
-    /// 标题保持简洁固定，不做变量替换，便于客户端聚合展示同类通知
-    pub fn render_title(notification_type: &NotificationType, _data: &serde_json::Value) -> String {
-        match notification_type {
-            NotificationType::BadgeGranted => "恭喜获得新徽章".to_string(),
-            NotificationType::BadgeExpiring => "徽章即将过期".to_string(),
-            NotificationType::BadgeRevoked => "徽章已被回收".to_string(),
-            NotificationType::BadgeUnlocked => "徽章已解锁".to_string(),
-            NotificationType::RedemptionSuccess => "兑换成功".to_string(),
-            NotificationType::RedemptionFailed => "兑换失败".to_string(),
-            NotificationType::BenefitGranted => "权益已发放".to_string(),
-        }
-    }
-
-    /// 根据通知类型和上下文数据渲染正文
-    ///
-    /// 从 data 中提取业务字段填充模板。对于缺失字段使用默认占位符，
-    /// 避免因上游数据不完整导致通知发送失败。
-    pub fn render_body(notification_type: &NotificationType, data: &serde_json::Value) -> String {
-        match notification_type {
-            NotificationType::BadgeGranted => {
-                let badge_name = extract_str(data, "badge_name", "未知徽章");
-                format!("您已获得「{badge_name}」徽章！")
-            }
-            NotificationType::BadgeExpiring => {
-                let badge_name = extract_str(data, "badge_name", "未知徽章");
-                let days = extract_str(data, "days", "?");
-                format!("您的「{badge_name}」徽章将在 {days} 天后过期")
-            }
-            NotificationType::BadgeRevoked => {
-                let badge_name = extract_str(data, "badge_name", "未知徽章");
-                let reason = extract_str(data, "reason", "未知原因");
-                format!("您的「{badge_name}」徽章已被回收，原因：{reason}")
-            }
-            NotificationType::BadgeUnlocked => {
-                let badge_name = extract_str(data, "badge_name", "未知徽章");
-                format!("恭喜！您的「{badge_name}」徽章已解锁，快去查看吧！")
-            }
-            NotificationType::RedemptionSuccess => {
-                let badge_name = extract_str(data, "badge_name", "未知徽章");
-                let benefit_name = extract_str(data, "benefit_name", "未知权益");
-                format!("您已成功使用「{badge_name}」徽章兑换「{benefit_name}」")
-            }
-            NotificationType::RedemptionFailed => {
-                let reason = extract_str(data, "reason", "未知原因");
-                format!("您的兑换请求未能成功，原因：{reason}")
-            }
-            NotificationType::BenefitGranted => {
-                let benefit_name = extract_str(data, "benefit_name", "未知权益");
-                format!("您已获得「{benefit_name}」权益，快去使用吧！")
-            }
-        }
+    rendered.title = content.title;
+    rendered.body = content.body;
+    if let (NotificationChannel::Email, Some(html), Some(data)) = (
+        channel,
+        content.html_body,
+        rendered.data.as_object_mut(),
+    ) {
+        data.insert(HTML_BODY_DATA_KEY.to_string(), serde_json::json!(html));
     }
-}
-
-impl Default for NotificationTemplateEngine {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
-/// 从 JSON 对象中安全提取字符串值
-///
-/// 优先取字符串类型的值，对数值类型自动转换为字符串表示，
-/// 确保模板渲染不会因类型不匹配而 panic。
-fn extract_str<'a>(data: &'a serde_json::Value, key: &str, default: &'a str) -> String {
-    data.get(key)
-        .map(|v| match v {
-            serde_json::Value::String(s) => s.clone(),
-            // 数值等非字符串类型也能安全渲染
-            other => other.to_string(),
-        })
-        .unwrap_or_else(|| default.to_string())
+    rendered
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    #[test]
-    fn test_render_badge_granted() {
-        let data = serde_json::json!({
-            "badge_name": "首次购物"
-        });
-
-        let title =
-            NotificationTemplateEngine::render_title(&NotificationType::BadgeGranted, &data);
-        assert_eq!(title, "恭喜获得新徽章");
-
-        let body = NotificationTemplateEngine::render_body(&NotificationType::BadgeGranted, &data);
-        assert_eq!(body, "您已获得「首次购物」徽章！");
-    }
-
-    #[test]
-    fn test_render_badge_expiring() {
-        let data = serde_json::json!({
-            "badge_name": "年度会员",
-            "days": "7"
-        });
-
-        let title =
-            NotificationTemplateEngine::render_title(&NotificationType::BadgeExpiring, &data);
-        assert_eq!(title, "徽章即将过期");
-
-        let body = NotificationTemplateEngine::render_body(&NotificationType::BadgeExpiring, &data);
-        assert_eq!(body, "您的「年度会员」徽章将在 7 天后过期");
-    }
-
-    #[test]
-    fn test_render_badge_revoked() {
-        let data = serde_json::json!({
-            "badge_name": "VIP 徽章",
-            "reason": "会员已过期"
-        });
-
-        let title =
-            NotificationTemplateEngine::render_title(&NotificationType::BadgeRevoked, &data);
-        assert_eq!(title, "徽章已被回收");
-
-        let body = NotificationTemplateEngine::render_body(&NotificationType::BadgeRevoked, &data);
-        assert_eq!(body, "您的「VIP 徽章」徽章已被回收，原因：会员已过期");
+    use badge_shared::events::NotificationType;
+    use badge_shared::notification_templates::NotificationTemplate;
+    use chrono::Utc;
+
+    fn make_notification(data: serde_json::Value) -> NotificationEvent {
+        NotificationEvent {
+            notification_id: "notif-001".to_string(),
+            user_id: "user-001".to_string(),
+            notification_type: NotificationType::BadgeGranted,
+            title: "上游标题".to_string(),
+            body: "上游正文".to_string(),
+            data,
+            channels: vec![NotificationChannel::AppPush, NotificationChannel::Email],
+            created_at: Utc::now(),
+        }
     }
 
     #[test]
-    fn test_render_redemption_success() {
-        let data = serde_json::json!({
-            "badge_name": "限量版徽章",
-            "benefit_name": "免费咖啡券"
-        });
-
-        let title =
-            NotificationTemplateEngine::render_title(&NotificationType::RedemptionSuccess, &data);
-        assert_eq!(title, "兑换成功");
-
-        let body =
-            NotificationTemplateEngine::render_body(&NotificationType::RedemptionSuccess, &data);
-        assert_eq!(body, "您已成功使用「限量版徽章」徽章兑换「免费咖啡券」");
+    fn test_render_builtin_per_channel() {
+        let templates = TemplateSet::builtin();
+        let notification = make_notification(serde_json::json!({"badge_name": "首次购物"}));
+
+        let push = render_for_channel(&templates, &notification, &NotificationChannel::AppPush);
+        assert_eq!(push.channels, vec![NotificationChannel::AppPush]);
+        assert_eq!(push.title, "恭喜获得新徽章");
+        assert_eq!(push.body, "您已获得「首次购物」徽章！");
+        assert!(push.data.get(HTML_BODY_DATA_KEY).is_none());
+
+        let email = render_for_channel(&templates, &notification, &NotificationChannel::Email);
+        assert_eq!(
+            email.data[HTML_BODY_DATA_KEY],
+            "<p>您已获得以下徽章：</p><ul><li>首次购物</li></ul>"
+        );
     }
 
     #[test]
-    fn test_render_redemption_failed() {
-        let data = serde_json::json!({
-            "reason": "徽章余额不足"
-        });
-
-        let title =
-            NotificationTemplateEngine::render_title(&NotificationType::RedemptionFailed, &data);
-        assert_eq!(title, "兑换失败");
-
-        let body =
-            NotificationTemplateEngine::render_body(&NotificationType::RedemptionFailed, &data);
-        assert_eq!(body, "您的兑换请求未能成功，原因：徽章余额不足");
+    fn test_render_uses_locale_and_channel_variant() {
+        let templates = TemplateSet::new([
+            NotificationTemplate::new(
+                NotificationType::BadgeGranted,
+                "en-US",
+                "New badge",
+                "You earned {{badge_name}}",
+            ),
+            NotificationTemplate::new(
+                NotificationType::BadgeGranted,
+                "en-US",
+                "New badge",
+                "[Badges] {{badge_name}}",
+            )
+            .with_channel(NotificationChannel::Sms),
+        ]);
+        let notification =
+            make_notification(serde_json::json!({"badge_name": "First order", "locale": "en-US"}));
+
+        let push = render_for_channel(&templates, &notification, &NotificationChannel::AppPush);
+        assert_eq!(push.body, "You earned First order");
+
+        let sms = render_for_channel(&templates, &notification, &NotificationChannel::Sms);
+        assert_eq!(sms.body, "[Badges] First order");
     }
 
     #[test]
-    fn test_render_with_missing_data_uses_defaults() {
-        // 缺少 badge_name 字段时应使用默认值
-        let empty_data = serde_json::json!({});
-
-        let body =
-            NotificationTemplateEngine::render_body(&NotificationType::BadgeGranted, &empty_data);
-        assert_eq!(body, "您已获得「未知徽章」徽章！");
+    fn test_render_keeps_upstream_content() {
+        // 缺少模板变量
+        let notification = make_notification(serde_json::json!({"task_id": 1}));
+        let rendered = render_for_channel(
+            &TemplateSet::builtin(),
+            &notification,
+            &NotificationChannel::AppPush,
+        );
+        assert_eq!(rendered.title, "上游标题");
+        assert_eq!(rendered.body, "上游正文");
+
+        // 没有匹配的模板
+        let rendered = render_for_channel(
+            &TemplateSet::default(),
+            &make_notification(serde_json::json!({"badge_name": "首次购物"})),
+            &NotificationChannel::AppPush,
+        );
+        assert_eq!(rendered.body, "上游正文");
     }
 
     #[test]
-    fn test_render_with_numeric_days() {
-        // days 为数值类型时也应正确渲染
-        let data = serde_json::json!({
-            "badge_name": "季度徽章",
-            "days": 3
-        });
-
-        let body = NotificationTemplateEngine::render_body(&NotificationType::BadgeExpiring, &data);
-        assert_eq!(body, "您的「季度徽章」徽章将在 3 天后过期");
+    fn test_render_digest() {
+        let notification = make_notification(serde_json::json!({
+            "digest_count": 2,
+            "badges": [{"badge_name": "首单"}, {"badge_name": "复购"}]
+        }));
+        let rendered = render_for_channel(
+            &TemplateSet::builtin(),
+            &notification,
+            &NotificationChannel::AppPush,
+        );
+        assert_eq!(rendered.title, "恭喜获得 2 枚新徽章");
+        assert_eq!(rendered.body, "您已获得「首单」「复购」徽章！");
     }
 }
@@ -0,0 +1,321 @@
+//! 渠道提供方集成测试
+//!
+//! 在随机端口启动 mock-services 的通知渠道桩，验证：
+//! - 短信网关发送及无效手机号的永久失败
+//! - 微信 access_token 缓存、过期刷新及用户拒收
+//! - HTTP 推送发送及无效设备 token
+//! - 5xx 响应与缺失收件人的失败分类
+//! - 业务方构建的通知（只携带用户 ID）经分发器补全收件人后送达
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use badge_management::notification::NotificationBuilder;
+use badge_shared::events::{NotificationChannel, NotificationEvent, NotificationType};
+use badge_shared::notification_contacts::{ContactStore, InMemoryContactStore, UserContacts};
+use chrono::Utc;
+use mock_services::services::notification_service::{
+    DEFAULT_PROVIDER_API_KEY, DEFAULT_WECHAT_APP_SECRET,
+};
+use mock_services::services::{NotificationServiceState, notification_routes};
+use notification_worker::dispatcher::{DispatchOutcome, NotificationDispatcher};
+use notification_worker::provider::{
+    HttpPushProvider, HttpSmsProvider, NotificationProvider, PushProviderConfig, RateLimiter,
+    SmsProviderConfig, WeChatProviderConfig, WeChatTemplateProvider,
+};
+use notification_worker::sender::{NotificationSender, ProviderSender};
+
+/// 在随机端口启动通知渠道桩，返回 base URL 和桩状态
+async fn start_provider_stubs() -> (String, Arc<NotificationServiceState>) {
+    let state = Arc::new(NotificationServiceState::new());
+    let app = notification_routes().with_state(state.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    (format!("http://{}", addr), state)
+}
+
+fn make_notification(channel: NotificationChannel, data: serde_json::Value) -> NotificationEvent {
+    NotificationEvent {
+        notification_id: "notif-it-001".to_string(),
+        user_id: "user-001".to_string(),
+        notification_type: NotificationType::BadgeGranted,
+        title: "恭喜获得新徽章".to_string(),
+        body: "您已获得「首次购物」徽章！".to_string(),
+        data,
+        channels: vec![channel],
+        created_at: Utc::now(),
+    }
+}
+
+fn sms_provider(base_url: &str) -> HttpSmsProvider {
+    HttpSmsProvider::new(SmsProviderConfig {
+        enabled: true,
+        url: format!("{}/sms/send", base_url),
+        token: DEFAULT_PROVIDER_API_KEY.to_string(),
+        ..Default::default()
+    })
+}
+
+fn wechat_provider(base_url: &str) -> WeChatTemplateProvider {
+    WeChatTemplateProvider::new(WeChatProviderConfig {
+        enabled: true,
+        api_base: base_url.to_string(),
+        appid: "wx-mock-app".to_string(),
+        secret: DEFAULT_WECHAT_APP_SECRET.to_string(),
+        template_id: "badge-notification".to_string(),
+        ..Default::default()
+    })
+}
+
+fn push_provider(base_url: &str) -> HttpPushProvider {
+    HttpPushProvider::new(PushProviderConfig {
+        enabled: true,
+        url: format!("{}/push/send", base_url),
+        token: DEFAULT_PROVIDER_API_KEY.to_string(),
+        ..Default::default()
+    })
+}
+
+#[tokio::test]
+async fn test_sms_delivery() {
+    let (base_url, state) = start_provider_stubs().await;
+    let sender = ProviderSender::new(Arc::new(sms_provider(&base_url)), RateLimiter::unlimited());
+
+    let result = sender
+        .send(&make_notification(
+            NotificationChannel::Sms,
+            serde_json::json!({"phone": "13800138000"}),
+        ))
+        .await
+        .unwrap();
+    assert!(result.success);
+    assert_eq!(result.channel, NotificationChannel::Sms);
+
+    let messages = state.provider_messages().await;
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].provider, "sms");
+    assert_eq!(messages[0].recipient, "13800138000");
+    assert_eq!(
+        result.message_id.as_deref(),
+        Some(messages[0].message_id.as_str())
+    );
+}
+
+#[tokio::test]
+async fn test_sms_invalid_phone_is_permanent() {
+    let (base_url, state) = start_provider_stubs().await;
+    let provider = sms_provider(&base_url);
+
+    let err = provider
+        .deliver(&make_notification(
+            NotificationChannel::Sms,
+            serde_json::json!({"phone": "12345"}),
+        ))
+        .await
+        .unwrap_err();
+    assert!(!err.is_retryable());
+    assert!(state.provider_messages().await.is_empty());
+}
+
+#[tokio::test]
+async fn test_wechat_caches_access_token() {
+    let (base_url, state) = start_provider_stubs().await;
+    let provider = wechat_provider(&base_url);
+    let notification = make_notification(
+        NotificationChannel::WeChat,
+        serde_json::json!({"openid": "o-user-001"}),
+    );
+
+    provider.deliver(&notification).await.unwrap();
+    provider.deliver(&notification).await.unwrap();
+
+    assert_eq!(state.tokens_issued(), 1);
+    let messages = state.provider_messages().await;
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0].provider, "wechat");
+    assert_eq!(messages[0].recipient, "o-user-001");
+}
+
+#[tokio::test]
+async fn test_wechat_refreshes_expired_token() {
+    let (base_url, state) = start_provider_stubs().await;
+    let provider = wechat_provider(&base_url);
+    let notification = make_notification(
+        NotificationChannel::WeChat,
+        serde_json::json!({"openid": "o-user-001"}),
+    );
+
+    provider.deliver(&notification).await.unwrap();
+    state.expire_access_tokens().await;
+    provider.deliver(&notification).await.unwrap();
+
+    assert_eq!(state.tokens_issued(), 2);
+    assert_eq!(state.provider_messages().await.len(), 2);
+}
+
+#[tokio::test]
+async fn test_wechat_blocked_user_is_permanent() {
+    let (base_url, _state) = start_provider_stubs().await;
+    let provider = wechat_provider(&base_url);
+
+    let err = provider
+        .deliver(&make_notification(
+            NotificationChannel::WeChat,
+            serde_json::json!({"openid": "blocked-user"}),
+        ))
+        .await
+        .unwrap_err();
+    assert!(!err.is_retryable());
+}
+
+#[tokio::test]
+async fn test_push_delivery() {
+    let (base_url, state) = start_provider_stubs().await;
+    let sender = ProviderSender::new(Arc::new(push_provider(&base_url)), RateLimiter::unlimited());
+
+    let result = sender
+        .send(&make_notification(
+            NotificationChannel::AppPush,
+            serde_json::json!({"device_token": "device-001", "badge_id": 42}),
+        ))
+        .await
+        .unwrap();
+    assert!(result.success);
+    assert!(
+        result
+            .message_id
+            .unwrap()
+            .starts_with("projects/mock/messages/")
+    );
+
+    let messages = state.provider_messages().await;
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].provider, "push");
+    assert_eq!(messages[0].recipient, "device-001");
+}
+
+#[tokio::test]
+async fn test_push_invalid_token_is_permanent() {
+    let (base_url, _state) = start_provider_stubs().await;
+    let sender = ProviderSender::new(Arc::new(push_provider(&base_url)), RateLimiter::unlimited());
+
+    let result = sender
+        .send(&make_notification(
+            NotificationChannel::AppPush,
+            serde_json::json!({"device_token": "invalid-device"}),
+        ))
+        .await
+        .unwrap();
+    assert!(!result.success);
+    assert!(!result.retryable);
+}
+
+#[tokio::test]
+async fn test_provider_unavailable_is_retryable() {
+    let (base_url, state) = start_provider_stubs().await;
+    let sender = ProviderSender::new(Arc::new(sms_provider(&base_url)), RateLimiter::unlimited());
+    state.set_provider_fail_next(1);
+
+    let notification = make_notification(
+        NotificationChannel::Sms,
+        serde_json::json!({"phone": "13800138000"}),
+    );
+    let result = sender.send(&notification).await.unwrap();
+    assert!(!result.success);
+    assert!(result.retryable);
+
+    // 故障恢复后重试成功
+    let result = sender.send(&notification).await.unwrap();
+    assert!(result.success);
+}
+
+#[tokio::test]
+async fn test_missing_recipient_is_permanent() {
+    let (base_url, _state) = start_provider_stubs().await;
+
+    for provider in [
+        Arc::new(sms_provider(&base_url)) as Arc<dyn NotificationProvider>,
+        Arc::new(wechat_provider(&base_url)),
+        Arc::new(push_provider(&base_url)),
+    ] {
+        let err = provider
+            .deliver(&make_notification(
+                provider.channel(),
+                serde_json::json!({}),
+            ))
+            .await
+            .unwrap_err();
+        assert!(
+            !err.is_retryable(),
+            "{} 缺失收件人应为永久失败",
+            provider.name()
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_builder_notification_resolves_recipients() {
+    let (base_url, state) = start_provider_stubs().await;
+    let sender = |provider: Arc<dyn NotificationProvider>| {
+        (
+            provider.channel(),
+            Arc::new(ProviderSender::new(provider, RateLimiter::unlimited()))
+                as Arc<dyn NotificationSender>,
+        )
+    };
+    let contacts = Arc::new(InMemoryContactStore::new());
+    contacts
+        .upsert(
+            &UserContacts::new("user-001")
+                .with_phone("13800138000")
+                .with_wechat_openid("o-user-001")
+                .with_device_token("device-001"),
+        )
+        .await
+        .unwrap();
+    let dispatcher = NotificationDispatcher::new(HashMap::from([
+        sender(Arc::new(sms_provider(&base_url))),
+        sender(Arc::new(wechat_provider(&base_url))),
+        sender(Arc::new(push_provider(&base_url))),
+    ]))
+    .with_contact_store(contacts);
+
+    // 发放通知走 APP 推送和微信，过期提醒走 APP 推送和短信
+    let granted = NotificationBuilder::badge_granted("user-001", 42, "首次购物").to_event();
+    let expiring = NotificationBuilder::badge_expiring(
+        "user-001",
+        42,
+        "首次购物",
+        Utc::now() + chrono::Duration::days(3),
+    )
+    .to_event();
+
+    for event in [granted, expiring] {
+        let outcome = dispatcher.dispatch(event).await;
+        let DispatchOutcome::Sent(results) = outcome else {
+            panic!("通知应立即发送");
+        };
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.success), "{:?}", results);
+    }
+
+    let mut delivered: Vec<(String, String)> = state
+        .provider_messages()
+        .await
+        .into_iter()
+        .map(|m| (m.provider, m.recipient))
+        .collect();
+    delivered.sort();
+    assert_eq!(
+        delivered,
+        vec![
+            ("push".to_string(), "device-001".to_string()),
+            ("push".to_string(), "device-001".to_string()),
+            ("sms".to_string(), "13800138000".to_string()),
+            ("wechat".to_string(), "o-user-001".to_string()),
+        ]
+    );
+}
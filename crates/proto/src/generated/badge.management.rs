@@ -38,6 +38,18 @@ pub struct UserBadge {
     pub expires_at: ::core::option::Option<::prost_types::Timestamp>,
     #[prost(bool, tag = "7")]
     pub is_pinned: bool,
+    /// user_badges 表主键，置顶等操作使用
+    #[prost(string, tag = "8")]
+    pub user_badge_id: ::prost::alloc::string::String,
+    /// 置顶展示顺序（从 1 开始），未置顶为 0
+    #[prost(int32, tag = "9")]
+    pub pin_order: i32,
+    /// 分级徽章当前等级（从 1 开始），非分级徽章为 0
+    #[prost(int32, tag = "10")]
+    pub level: i32,
+    /// 当前等级名称，如"铜"、"银"、"金"
+    #[prost(string, tag = "11")]
+    pub level_name: ::prost::alloc::string::String,
 }
 /// 获取用户徽章列表请求
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
@@ -167,13 +179,100 @@ pub struct RevokeBadgeResponse {
     #[prost(string, tag = "2")]
     pub message: ::prost::alloc::string::String,
 }
-/// 兑换徽章请求
+/// 批量发放徽章请求
+///
+/// 同一批次共享徽章、来源和原因，逐个用户独立走完整的发放流程
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BatchGrantBadgesRequest {
+    #[prost(string, tag = "1")]
+    pub badge_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub source_type: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub source_ref: ::prost::alloc::string::String,
+    #[prost(string, tag = "4")]
+    pub reason: ::prost::alloc::string::String,
+    #[prost(string, tag = "5")]
+    pub operator: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag = "6")]
+    pub items: ::prost::alloc::vec::Vec<BatchGrantItem>,
+}
+/// 批量发放条目
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct BatchGrantItem {
+    #[prost(string, tag = "1")]
+    pub user_id: ::prost::alloc::string::String,
+    #[prost(int32, tag = "2")]
+    pub quantity: i32,
+    /// 重试时保持不变，避免重复发放
+    #[prost(string, tag = "3")]
+    pub idempotency_key: ::prost::alloc::string::String,
+}
+/// 批量取消徽章请求
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BatchRevokeBadgesRequest {
+    #[prost(string, tag = "1")]
+    pub badge_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub reason: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub operator: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag = "4")]
+    pub items: ::prost::alloc::vec::Vec<BatchRevokeItem>,
+}
+/// 批量取消条目
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct BatchRevokeItem {
+    #[prost(string, tag = "1")]
+    pub user_id: ::prost::alloc::string::String,
+    #[prost(int32, tag = "2")]
+    pub quantity: i32,
+    /// 重试时保持不变，避免重复撤销
+    #[prost(string, tag = "3")]
+    pub idempotency_key: ::prost::alloc::string::String,
+}
+/// 批量操作响应
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BatchBadgeOperationResponse {
+    #[prost(int32, tag = "1")]
+    pub success_count: i32,
+    #[prost(int32, tag = "2")]
+    pub failed_count: i32,
+    /// 与请求条目一一对应
+    #[prost(message, repeated, tag = "3")]
+    pub results: ::prost::alloc::vec::Vec<BatchItemResult>,
+}
+/// 批量操作单条结果
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct BatchItemResult {
+    #[prost(string, tag = "1")]
+    pub user_id: ::prost::alloc::string::String,
+    #[prost(bool, tag = "2")]
+    pub success: bool,
+    /// 失败时的业务错误码，如 PREREQUISITE_NOT_MET
+    #[prost(string, tag = "3")]
+    pub error_code: ::prost::alloc::string::String,
+    #[prost(string, tag = "4")]
+    pub message: ::prost::alloc::string::String,
+}
+/// 兑换徽章请求
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct RedeemBadgeRequest {
     #[prost(string, tag = "1")]
     pub user_id: ::prost::alloc::string::String,
     #[prost(string, tag = "2")]
     pub redemption_rule_id: ::prost::alloc::string::String,
+    /// 指定消耗的徽章（兑换配方含备选或系列/分类通配时使用），为空时按规则的选择策略挑选
+    #[prost(message, repeated, tag = "3")]
+    pub selected_badges: ::prost::alloc::vec::Vec<SelectedBadge>,
+}
+/// 兑换时指定消耗的徽章
+#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct SelectedBadge {
+    #[prost(int64, tag = "1")]
+    pub badge_id: i64,
+    #[prost(int32, tag = "2")]
+    pub quantity: i32,
 }
 /// 兑换徽章响应
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
@@ -188,6 +287,134 @@ pub struct RedeemBadgeResponse {
     pub benefit_name: ::prost::alloc::string::String,
     #[prost(string, tag = "5")]
     pub message: ::prost::alloc::string::String,
+    /// 失败时的错误码（如 FREQUENCY_LIMIT_REACHED），成功时为空
+    #[prost(string, tag = "6")]
+    pub error_code: ::prost::alloc::string::String,
+}
+/// 可兑换权益查询请求
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct ListRedeemableBenefitsRequest {
+    #[prost(string, tag = "1")]
+    pub user_id: ::prost::alloc::string::String,
+    /// 只返回当前可兑换的规则
+    #[prost(bool, tag = "2")]
+    pub eligible_only: bool,
+    /// 从 1 开始，默认 1
+    #[prost(int32, tag = "3")]
+    pub page: i32,
+    /// 默认 20，最大 100
+    #[prost(int32, tag = "4")]
+    pub page_size: i32,
+}
+/// 可兑换权益查询响应
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListRedeemableBenefitsResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub benefits: ::prost::alloc::vec::Vec<RedeemableBenefit>,
+    #[prost(int32, tag = "2")]
+    pub total: i32,
+    #[prost(int32, tag = "3")]
+    pub page: i32,
+    #[prost(int32, tag = "4")]
+    pub page_size: i32,
+}
+/// 单条兑换规则对用户的可兑换状态
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RedeemableBenefit {
+    #[prost(string, tag = "1")]
+    pub rule_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub rule_name: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub benefit_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "4")]
+    pub benefit_name: ::prost::alloc::string::String,
+    #[prost(string, tag = "5")]
+    pub benefit_type: ::prost::alloc::string::String,
+    #[prost(string, tag = "6")]
+    pub icon_url: ::prost::alloc::string::String,
+    /// -1 表示不限量
+    #[prost(int64, tag = "7")]
+    pub remaining_stock: i64,
+    /// eligible / missing_badges / frequency_limited / out_of_stock
+    #[prost(string, tag = "8")]
+    pub status: ::prost::alloc::string::String,
+    /// 不可兑换的全部原因说明
+    #[prost(string, repeated, tag = "9")]
+    pub reasons: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// 未满足的配方条目
+    #[prost(message, repeated, tag = "10")]
+    pub missing: ::prost::alloc::vec::Vec<MissingRequirement>,
+    /// 达到上限的频率维度（仅 frequency_limited）
+    #[prost(string, tag = "11")]
+    pub limited_by: ::prost::alloc::string::String,
+    /// 兑换配方 JSON
+    #[prost(string, tag = "12")]
+    pub recipe: ::prost::alloc::string::String,
+    /// 规则截止时间，长期有效时为空
+    #[prost(message, optional, tag = "13")]
+    pub end_time: ::core::option::Option<::prost_types::Timestamp>,
+}
+/// 未满足的配方条目
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct MissingRequirement {
+    /// 配方条目序号，从 0 开始
+    #[prost(int32, tag = "1")]
+    pub term_index: i32,
+    /// 配方条目 JSON
+    #[prost(string, tag = "2")]
+    pub term: ::prost::alloc::string::String,
+    #[prost(int32, tag = "3")]
+    pub required: i32,
+    #[prost(int32, tag = "4")]
+    pub available: i32,
+}
+/// 取消兑换订单请求
+/// user_id 与 operator 二选一：user_id 为用户自助取消（仅限本人订单且在可取消时限内），
+/// operator 为管理员取消（不受时限限制）
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct CancelRedemptionRequest {
+    #[prost(string, tag = "1")]
+    pub order_no: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub user_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub operator: ::prost::alloc::string::String,
+    #[prost(string, tag = "4")]
+    pub reason: ::prost::alloc::string::String,
+}
+/// 取消兑换订单响应
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CancelRedemptionResponse {
+    #[prost(bool, tag = "1")]
+    pub success: bool,
+    #[prost(string, tag = "2")]
+    pub order_no: ::prost::alloc::string::String,
+    /// 订单此前已取消，本次未做变更
+    #[prost(bool, tag = "3")]
+    pub already_cancelled: bool,
+    /// 本次是否撤销了已发放的权益
+    #[prost(bool, tag = "4")]
+    pub benefit_revoked: bool,
+    #[prost(message, repeated, tag = "5")]
+    pub refunded_badges: ::prost::alloc::vec::Vec<RefundedBadge>,
+    #[prost(message, optional, tag = "6")]
+    pub cancelled_at: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(string, tag = "7")]
+    pub message: ::prost::alloc::string::String,
+    /// 失败时的错误码（如 CANCEL_WINDOW_EXPIRED），成功时为空
+    #[prost(string, tag = "8")]
+    pub error_code: ::prost::alloc::string::String,
+}
+/// 取消订单退回的徽章
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct RefundedBadge {
+    #[prost(int64, tag = "1")]
+    pub badge_id: i64,
+    #[prost(string, tag = "2")]
+    pub badge_name: ::prost::alloc::string::String,
+    #[prost(int32, tag = "3")]
+    pub quantity: i32,
 }
 /// 置顶徽章请求
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
@@ -206,6 +433,26 @@ pub struct PinBadgeResponse {
     pub success: bool,
     #[prost(string, tag = "2")]
     pub message: ::prost::alloc::string::String,
+    /// 置顶后的展示顺序，取消置顶时为 0
+    #[prost(int32, tag = "3")]
+    pub pin_order: i32,
+}
+/// 调整置顶顺序请求
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct ReorderPinnedBadgesRequest {
+    #[prost(string, tag = "1")]
+    pub user_id: ::prost::alloc::string::String,
+    /// 当前全部置顶徽章，按期望的展示顺序排列
+    #[prost(string, repeated, tag = "2")]
+    pub user_badge_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+/// 调整置顶顺序响应
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct ReorderPinnedBadgesResponse {
+    #[prost(bool, tag = "1")]
+    pub success: bool,
+    #[prost(string, tag = "2")]
+    pub message: ::prost::alloc::string::String,
 }
 /// 根据来源引用查询关联的用户徽章请求
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
@@ -259,6 +506,112 @@ pub struct RefreshAutoBenefitCacheResponse {
     #[prost(int32, tag = "3")]
     pub rules_loaded: i32,
 }
+/// 徽章进度查询请求
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct GetBadgeProgressRequest {
+    #[prost(string, tag = "1")]
+    pub user_id: ::prost::alloc::string::String,
+    /// 为空时返回所有有进度可展示的徽章
+    #[prost(string, repeated, tag = "2")]
+    pub badge_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+/// 徽章进度查询响应
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetBadgeProgressResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub badges: ::prost::alloc::vec::Vec<BadgeProgress>,
+}
+/// 单个徽章的进度
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BadgeProgress {
+    #[prost(string, tag = "1")]
+    pub badge_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub badge_name: ::prost::alloc::string::String,
+    #[prost(bool, tag = "3")]
+    pub earned: bool,
+    #[prost(int32, tag = "4")]
+    pub quantity: i32,
+    /// 各进度项的平均完成度（0 ~ 100）
+    #[prost(double, tag = "5")]
+    pub percentage: f64,
+    #[prost(message, repeated, tag = "6")]
+    pub items: ::prost::alloc::vec::Vec<ProgressItem>,
+}
+/// 进度项
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ProgressItem {
+    /// rule_aggregate / prerequisite / next_level
+    #[prost(string, tag = "1")]
+    pub kind: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub label: ::prost::alloc::string::String,
+    #[prost(double, tag = "3")]
+    pub current: f64,
+    #[prost(double, tag = "4")]
+    pub target: f64,
+    #[prost(double, tag = "5")]
+    pub percentage: f64,
+    #[prost(bool, tag = "6")]
+    pub achieved: bool,
+    /// 前置徽章 ID（仅 prerequisite）
+    #[prost(string, tag = "7")]
+    pub related_badge_id: ::prost::alloc::string::String,
+    /// 目标等级（仅 next_level）
+    #[prost(int32, tag = "8")]
+    pub level: i32,
+}
+/// 排行榜查询请求
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct GetLeaderboardRequest {
+    /// badges / score / series / category
+    #[prost(string, tag = "1")]
+    pub board: ::prost::alloc::string::String,
+    /// 系列 ID 或分类 ID（仅 series / category）
+    #[prost(int64, tag = "2")]
+    pub scope_id: i64,
+    /// all_time / weekly / monthly，默认 all_time
+    #[prost(string, tag = "3")]
+    pub window: ::prost::alloc::string::String,
+    /// 返回条数，默认 10，最大 100
+    #[prost(int32, tag = "4")]
+    pub limit: i32,
+    /// 可选，同时返回该用户的名次
+    #[prost(string, tag = "5")]
+    pub user_id: ::prost::alloc::string::String,
+}
+/// 排行榜查询响应
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetLeaderboardResponse {
+    #[prost(string, tag = "1")]
+    pub board: ::prost::alloc::string::String,
+    #[prost(int64, tag = "2")]
+    pub scope_id: i64,
+    #[prost(string, tag = "3")]
+    pub window: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag = "4")]
+    pub entries: ::prost::alloc::vec::Vec<LeaderboardEntry>,
+    /// 上榜用户总数
+    #[prost(int64, tag = "5")]
+    pub total: i64,
+    /// 请求指定 user_id 且已上榜时返回
+    #[prost(message, optional, tag = "6")]
+    pub my_entry: ::core::option::Option<LeaderboardEntry>,
+}
+/// 排行榜条目
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LeaderboardEntry {
+    /// 名次，从 1 开始
+    #[prost(int64, tag = "1")]
+    pub rank: i64,
+    #[prost(string, tag = "2")]
+    pub user_id: ::prost::alloc::string::String,
+    #[prost(double, tag = "3")]
+    pub score: f64,
+    /// 系列完成率（0 ~ 100），仅 series 榜有值
+    #[prost(double, tag = "4")]
+    pub completion_rate: f64,
+}
 /// 徽章状态
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
@@ -268,6 +621,7 @@ pub enum BadgeStatus {
     Expired = 2,
     Revoked = 3,
     Redeemed = 4,
+    Suspended = 5,
 }
 impl BadgeStatus {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -281,6 +635,7 @@ impl BadgeStatus {
             Self::Expired => "EXPIRED",
             Self::Revoked => "REVOKED",
             Self::Redeemed => "REDEEMED",
+            Self::Suspended => "SUSPENDED",
         }
     }
     /// Creates an enum from field names used in the ProtoBuf definition.
@@ -291,6 +646,7 @@ impl BadgeStatus {
             "EXPIRED" => Some(Self::Expired),
             "REVOKED" => Some(Self::Revoked),
             "REDEEMED" => Some(Self::Redeemed),
+            "SUSPENDED" => Some(Self::Suspended),
             _ => None,
         }
     }
@@ -575,6 +931,66 @@ pub mod badge_management_service_client {
                 );
             self.inner.unary(req, path, codec).await
         }
+        /// 批量发放徽章（内部调用，后台批量任务使用）
+        pub async fn batch_grant_badges(
+            &mut self,
+            request: impl tonic::IntoRequest<super::BatchGrantBadgesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::BatchBadgeOperationResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/badge.management.BadgeManagementService/BatchGrantBadges",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "badge.management.BadgeManagementService",
+                        "BatchGrantBadges",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// 批量取消徽章（内部调用，后台批量任务使用）
+        pub async fn batch_revoke_badges(
+            &mut self,
+            request: impl tonic::IntoRequest<super::BatchRevokeBadgesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::BatchBadgeOperationResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/badge.management.BadgeManagementService/BatchRevokeBadges",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "badge.management.BadgeManagementService",
+                        "BatchRevokeBadges",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
         /// 兑换徽章
         pub async fn redeem_badge(
             &mut self,
@@ -605,12 +1021,12 @@ pub mod badge_management_service_client {
                 );
             self.inner.unary(req, path, codec).await
         }
-        /// 置顶/佩戴徽章
-        pub async fn pin_badge(
+        /// 查询用户的可兑换权益目录（逐条规则给出可兑换状态及原因）
+        pub async fn list_redeemable_benefits(
             &mut self,
-            request: impl tonic::IntoRequest<super::PinBadgeRequest>,
+            request: impl tonic::IntoRequest<super::ListRedeemableBenefitsRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::PinBadgeResponse>,
+            tonic::Response<super::ListRedeemableBenefitsResponse>,
             tonic::Status,
         > {
             self.inner
@@ -623,24 +1039,24 @@ pub mod badge_management_service_client {
                 })?;
             let codec = tonic_prost::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/badge.management.BadgeManagementService/PinBadge",
+                "/badge.management.BadgeManagementService/ListRedeemableBenefits",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "badge.management.BadgeManagementService",
-                        "PinBadge",
+                        "ListRedeemableBenefits",
                     ),
                 );
             self.inner.unary(req, path, codec).await
         }
-        /// 根据来源引用查询关联的用户徽章（退款撤销时使用）
-        pub async fn find_badges_by_source_ref(
+        /// 取消兑换订单：撤销已发放的权益并退回消耗的徽章
+        pub async fn cancel_redemption(
             &mut self,
-            request: impl tonic::IntoRequest<super::FindBadgesBySourceRefRequest>,
+            request: impl tonic::IntoRequest<super::CancelRedemptionRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::FindBadgesBySourceRefResponse>,
+            tonic::Response<super::CancelRedemptionResponse>,
             tonic::Status,
         > {
             self.inner
@@ -653,24 +1069,24 @@ pub mod badge_management_service_client {
                 })?;
             let codec = tonic_prost::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/badge.management.BadgeManagementService/FindBadgesBySourceRef",
+                "/badge.management.BadgeManagementService/CancelRedemption",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "badge.management.BadgeManagementService",
-                        "FindBadgesBySourceRef",
+                        "CancelRedemption",
                     ),
                 );
             self.inner.unary(req, path, codec).await
         }
-        /// 刷新依赖图缓存（内部调用）
-        pub async fn refresh_dependency_cache(
+        /// 置顶/佩戴徽章
+        pub async fn pin_badge(
             &mut self,
-            request: impl tonic::IntoRequest<super::RefreshDependencyCacheRequest>,
+            request: impl tonic::IntoRequest<super::PinBadgeRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::RefreshDependencyCacheResponse>,
+            tonic::Response<super::PinBadgeResponse>,
             tonic::Status,
         > {
             self.inner
@@ -683,24 +1099,24 @@ pub mod badge_management_service_client {
                 })?;
             let codec = tonic_prost::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/badge.management.BadgeManagementService/RefreshDependencyCache",
+                "/badge.management.BadgeManagementService/PinBadge",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "badge.management.BadgeManagementService",
-                        "RefreshDependencyCache",
+                        "PinBadge",
                     ),
                 );
             self.inner.unary(req, path, codec).await
         }
-        /// 刷新自动权益规则缓存（内部调用）
-        pub async fn refresh_auto_benefit_cache(
+        /// 调整置顶徽章的展示顺序
+        pub async fn reorder_pinned_badges(
             &mut self,
-            request: impl tonic::IntoRequest<super::RefreshAutoBenefitCacheRequest>,
+            request: impl tonic::IntoRequest<super::ReorderPinnedBadgesRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::RefreshAutoBenefitCacheResponse>,
+            tonic::Response<super::ReorderPinnedBadgesResponse>,
             tonic::Status,
         > {
             self.inner
@@ -713,29 +1129,179 @@ pub mod badge_management_service_client {
                 })?;
             let codec = tonic_prost::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/badge.management.BadgeManagementService/RefreshAutoBenefitCache",
+                "/badge.management.BadgeManagementService/ReorderPinnedBadges",
             );
             let mut req = request.into_request();
             req.extensions_mut()
                 .insert(
                     GrpcMethod::new(
                         "badge.management.BadgeManagementService",
-                        "RefreshAutoBenefitCache",
+                        "ReorderPinnedBadges",
                     ),
                 );
             self.inner.unary(req, path, codec).await
         }
-    }
-}
-/// Generated server implementations.
-pub mod badge_management_service_server {
-    #![allow(
-        unused_variables,
-        dead_code,
-        missing_docs,
-        clippy::wildcard_imports,
-        clippy::let_unit_value,
-    )]
+        /// 根据来源引用查询关联的用户徽章（退款撤销时使用）
+        pub async fn find_badges_by_source_ref(
+            &mut self,
+            request: impl tonic::IntoRequest<super::FindBadgesBySourceRefRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::FindBadgesBySourceRefResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/badge.management.BadgeManagementService/FindBadgesBySourceRef",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "badge.management.BadgeManagementService",
+                        "FindBadgesBySourceRef",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// 刷新依赖图缓存（内部调用）
+        pub async fn refresh_dependency_cache(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RefreshDependencyCacheRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RefreshDependencyCacheResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/badge.management.BadgeManagementService/RefreshDependencyCache",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "badge.management.BadgeManagementService",
+                        "RefreshDependencyCache",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// 刷新自动权益规则缓存（内部调用）
+        pub async fn refresh_auto_benefit_cache(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RefreshAutoBenefitCacheRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RefreshAutoBenefitCacheResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/badge.management.BadgeManagementService/RefreshAutoBenefitCache",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "badge.management.BadgeManagementService",
+                        "RefreshAutoBenefitCache",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// 查询用户的徽章进度（未获得徽章的完成度、分级徽章的升级进度）
+        pub async fn get_badge_progress(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetBadgeProgressRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetBadgeProgressResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/badge.management.BadgeManagementService/GetBadgeProgress",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "badge.management.BadgeManagementService",
+                        "GetBadgeProgress",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// 查询用户排行榜（徽章数 / 积分 / 系列完成度 / 分类），可同时返回指定用户的名次
+        pub async fn get_leaderboard(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetLeaderboardRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetLeaderboardResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/badge.management.BadgeManagementService/GetLeaderboard",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "badge.management.BadgeManagementService",
+                        "GetLeaderboard",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+    }
+}
+/// Generated server implementations.
+pub mod badge_management_service_server {
+    #![allow(
+        unused_variables,
+        dead_code,
+        missing_docs,
+        clippy::wildcard_imports,
+        clippy::let_unit_value,
+    )]
     use tonic::codegen::*;
     /// Generated trait containing gRPC methods that should be implemented for use with BadgeManagementServiceServer.
     #[async_trait]
@@ -780,6 +1346,22 @@ pub mod badge_management_service_server {
             tonic::Response<super::RevokeBadgeResponse>,
             tonic::Status,
         >;
+        /// 批量发放徽章（内部调用，后台批量任务使用）
+        async fn batch_grant_badges(
+            &self,
+            request: tonic::Request<super::BatchGrantBadgesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::BatchBadgeOperationResponse>,
+            tonic::Status,
+        >;
+        /// 批量取消徽章（内部调用，后台批量任务使用）
+        async fn batch_revoke_badges(
+            &self,
+            request: tonic::Request<super::BatchRevokeBadgesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::BatchBadgeOperationResponse>,
+            tonic::Status,
+        >;
         /// 兑换徽章
         async fn redeem_badge(
             &self,
@@ -788,6 +1370,22 @@ pub mod badge_management_service_server {
             tonic::Response<super::RedeemBadgeResponse>,
             tonic::Status,
         >;
+        /// 查询用户的可兑换权益目录（逐条规则给出可兑换状态及原因）
+        async fn list_redeemable_benefits(
+            &self,
+            request: tonic::Request<super::ListRedeemableBenefitsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListRedeemableBenefitsResponse>,
+            tonic::Status,
+        >;
+        /// 取消兑换订单：撤销已发放的权益并退回消耗的徽章
+        async fn cancel_redemption(
+            &self,
+            request: tonic::Request<super::CancelRedemptionRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CancelRedemptionResponse>,
+            tonic::Status,
+        >;
         /// 置顶/佩戴徽章
         async fn pin_badge(
             &self,
@@ -796,6 +1394,14 @@ pub mod badge_management_service_server {
             tonic::Response<super::PinBadgeResponse>,
             tonic::Status,
         >;
+        /// 调整置顶徽章的展示顺序
+        async fn reorder_pinned_badges(
+            &self,
+            request: tonic::Request<super::ReorderPinnedBadgesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ReorderPinnedBadgesResponse>,
+            tonic::Status,
+        >;
         /// 根据来源引用查询关联的用户徽章（退款撤销时使用）
         async fn find_badges_by_source_ref(
             &self,
@@ -820,6 +1426,22 @@ pub mod badge_management_service_server {
             tonic::Response<super::RefreshAutoBenefitCacheResponse>,
             tonic::Status,
         >;
+        /// 查询用户的徽章进度（未获得徽章的完成度、分级徽章的升级进度）
+        async fn get_badge_progress(
+            &self,
+            request: tonic::Request<super::GetBadgeProgressRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetBadgeProgressResponse>,
+            tonic::Status,
+        >;
+        /// 查询用户排行榜（徽章数 / 积分 / 系列完成度 / 分类），可同时返回指定用户的名次
+        async fn get_leaderboard(
+            &self,
+            request: tonic::Request<super::GetLeaderboardRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetLeaderboardResponse>,
+            tonic::Status,
+        >;
     }
     /// 徽章管理服务（C端）
     #[derive(Debug)]
@@ -1138,6 +1760,104 @@ pub mod badge_management_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/badge.management.BadgeManagementService/BatchGrantBadges" => {
+                    #[allow(non_camel_case_types)]
+                    struct BatchGrantBadgesSvc<T: BadgeManagementService>(pub Arc<T>);
+                    impl<
+                        T: BadgeManagementService,
+                    > tonic::server::UnaryService<super::BatchGrantBadgesRequest>
+                    for BatchGrantBadgesSvc<T> {
+                        type Response = super::BatchBadgeOperationResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::BatchGrantBadgesRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as BadgeManagementService>::batch_grant_badges(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = BatchGrantBadgesSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/badge.management.BadgeManagementService/BatchRevokeBadges" => {
+                    #[allow(non_camel_case_types)]
+                    struct BatchRevokeBadgesSvc<T: BadgeManagementService>(pub Arc<T>);
+                    impl<
+                        T: BadgeManagementService,
+                    > tonic::server::UnaryService<super::BatchRevokeBadgesRequest>
+                    for BatchRevokeBadgesSvc<T> {
+                        type Response = super::BatchBadgeOperationResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::BatchRevokeBadgesRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as BadgeManagementService>::batch_revoke_badges(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = BatchRevokeBadgesSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/badge.management.BadgeManagementService/RedeemBadge" => {
                     #[allow(non_camel_case_types)]
                     struct RedeemBadgeSvc<T: BadgeManagementService>(pub Arc<T>);
@@ -1184,6 +1904,106 @@ pub mod badge_management_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/badge.management.BadgeManagementService/ListRedeemableBenefits" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListRedeemableBenefitsSvc<T: BadgeManagementService>(
+                        pub Arc<T>,
+                    );
+                    impl<
+                        T: BadgeManagementService,
+                    > tonic::server::UnaryService<super::ListRedeemableBenefitsRequest>
+                    for ListRedeemableBenefitsSvc<T> {
+                        type Response = super::ListRedeemableBenefitsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListRedeemableBenefitsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as BadgeManagementService>::list_redeemable_benefits(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ListRedeemableBenefitsSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/badge.management.BadgeManagementService/CancelRedemption" => {
+                    #[allow(non_camel_case_types)]
+                    struct CancelRedemptionSvc<T: BadgeManagementService>(pub Arc<T>);
+                    impl<
+                        T: BadgeManagementService,
+                    > tonic::server::UnaryService<super::CancelRedemptionRequest>
+                    for CancelRedemptionSvc<T> {
+                        type Response = super::CancelRedemptionResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CancelRedemptionRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as BadgeManagementService>::cancel_redemption(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = CancelRedemptionSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/badge.management.BadgeManagementService/PinBadge" => {
                     #[allow(non_camel_case_types)]
                     struct PinBadgeSvc<T: BadgeManagementService>(pub Arc<T>);
@@ -1230,6 +2050,55 @@ pub mod badge_management_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/badge.management.BadgeManagementService/ReorderPinnedBadges" => {
+                    #[allow(non_camel_case_types)]
+                    struct ReorderPinnedBadgesSvc<T: BadgeManagementService>(pub Arc<T>);
+                    impl<
+                        T: BadgeManagementService,
+                    > tonic::server::UnaryService<super::ReorderPinnedBadgesRequest>
+                    for ReorderPinnedBadgesSvc<T> {
+                        type Response = super::ReorderPinnedBadgesResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ReorderPinnedBadgesRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as BadgeManagementService>::reorder_pinned_badges(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ReorderPinnedBadgesSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/badge.management.BadgeManagementService/FindBadgesBySourceRef" => {
                     #[allow(non_camel_case_types)]
                     struct FindBadgesBySourceRefSvc<T: BadgeManagementService>(
@@ -1385,6 +2254,104 @@ pub mod badge_management_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/badge.management.BadgeManagementService/GetBadgeProgress" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetBadgeProgressSvc<T: BadgeManagementService>(pub Arc<T>);
+                    impl<
+                        T: BadgeManagementService,
+                    > tonic::server::UnaryService<super::GetBadgeProgressRequest>
+                    for GetBadgeProgressSvc<T> {
+                        type Response = super::GetBadgeProgressResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetBadgeProgressRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as BadgeManagementService>::get_badge_progress(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = GetBadgeProgressSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/badge.management.BadgeManagementService/GetLeaderboard" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetLeaderboardSvc<T: BadgeManagementService>(pub Arc<T>);
+                    impl<
+                        T: BadgeManagementService,
+                    > tonic::server::UnaryService<super::GetLeaderboardRequest>
+                    for GetLeaderboardSvc<T> {
+                        type Response = super::GetLeaderboardResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetLeaderboardRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as BadgeManagementService>::get_leaderboard(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = GetLeaderboardSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         let mut response = http::Response::new(
@@ -15,10 +15,10 @@ pub struct Rule {
     #[prost(message, optional, tag = "6")]
     pub updated_at: ::core::option::Option<::prost_types::Timestamp>,
 }
-/// 规则节点（条件或组）
+/// 规则节点（条件、组或聚合条件）
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct RuleNode {
-    #[prost(oneof = "rule_node::Node", tags = "1, 2")]
+    #[prost(oneof = "rule_node::Node", tags = "1, 2, 3")]
     pub node: ::core::option::Option<rule_node::Node>,
 }
 /// Nested message and enum types in `RuleNode`.
@@ -29,6 +29,8 @@ pub mod rule_node {
         Condition(super::ConditionNode),
         #[prost(message, tag = "2")]
         Group(super::GroupNode),
+        #[prost(message, tag = "3")]
+        Aggregate(super::AggregateNode),
     }
 }
 /// 条件节点
@@ -49,6 +51,34 @@ pub struct GroupNode {
     #[prost(message, repeated, tag = "2")]
     pub children: ::prost::alloc::vec::Vec<RuleNode>,
 }
+/// 聚合节点：时间窗口内同一用户某类事件的统计值满足条件
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AggregateNode {
+    #[prost(enumeration = "AggregateFunction", tag = "1")]
+    pub function: i32,
+    /// 为空时取当前事件类型
+    #[prost(string, tag = "2")]
+    pub event_type: ::prost::alloc::string::String,
+    /// sum / distinct_count 的统计字段
+    #[prost(string, tag = "3")]
+    pub field: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "4")]
+    pub window: ::core::option::Option<TimeWindow>,
+    #[prost(enumeration = "Operator", tag = "5")]
+    pub operator: i32,
+    #[prost(message, optional, tag = "6")]
+    pub value: ::core::option::Option<::prost_types::Value>,
+}
+/// 时间窗口
+#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct TimeWindow {
+    #[prost(enumeration = "WindowType", tag = "1")]
+    pub r#type: i32,
+    #[prost(uint32, tag = "2")]
+    pub size: u32,
+    #[prost(enumeration = "TimeUnit", tag = "3")]
+    pub unit: i32,
+}
 /// 评估请求
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct EvaluateRequest {
@@ -139,6 +169,155 @@ pub struct TestRuleResponse {
     #[prost(int64, tag = "4")]
     pub evaluation_time_ms: i64,
 }
+/// 规则进度查询请求
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct GetRuleProgressRequest {
+    #[prost(string, tag = "1")]
+    pub user_id: ::prost::alloc::string::String,
+    #[prost(string, repeated, tag = "2")]
+    pub rule_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+/// 规则进度查询响应
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetRuleProgressResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub rules: ::prost::alloc::vec::Vec<RuleProgress>,
+}
+/// 单条规则的进度
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RuleProgress {
+    #[prost(string, tag = "1")]
+    pub rule_id: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag = "2")]
+    pub aggregates: ::prost::alloc::vec::Vec<AggregateProgress>,
+}
+/// 聚合条件的进度
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AggregateProgress {
+    /// 聚合口径标识，如 count(checkin.\*)@sliding:30day
+    #[prost(string, tag = "1")]
+    pub signature: ::prost::alloc::string::String,
+    #[prost(enumeration = "AggregateFunction", tag = "2")]
+    pub function: i32,
+    #[prost(string, tag = "3")]
+    pub event_type: ::prost::alloc::string::String,
+    #[prost(string, tag = "4")]
+    pub field: ::prost::alloc::string::String,
+    #[prost(double, tag = "5")]
+    pub current: f64,
+    #[prost(double, tag = "6")]
+    pub target: f64,
+    /// 0 ~ 100
+    #[prost(double, tag = "7")]
+    pub percentage: f64,
+    #[prost(bool, tag = "8")]
+    pub achieved: bool,
+}
+/// 聚合函数
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum AggregateFunction {
+    Unspecified = 0,
+    Count = 1,
+    Sum = 2,
+    DistinctCount = 3,
+    Streak = 4,
+}
+impl AggregateFunction {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Unspecified => "AGGREGATE_FUNCTION_UNSPECIFIED",
+            Self::Count => "COUNT",
+            Self::Sum => "SUM",
+            Self::DistinctCount => "DISTINCT_COUNT",
+            Self::Streak => "STREAK",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "AGGREGATE_FUNCTION_UNSPECIFIED" => Some(Self::Unspecified),
+            "COUNT" => Some(Self::Count),
+            "SUM" => Some(Self::Sum),
+            "DISTINCT_COUNT" => Some(Self::DistinctCount),
+            "STREAK" => Some(Self::Streak),
+            _ => None,
+        }
+    }
+}
+/// 窗口类型
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum WindowType {
+    Unspecified = 0,
+    Sliding = 1,
+    Tumbling = 2,
+}
+impl WindowType {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Unspecified => "WINDOW_TYPE_UNSPECIFIED",
+            Self::Sliding => "SLIDING",
+            Self::Tumbling => "TUMBLING",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "WINDOW_TYPE_UNSPECIFIED" => Some(Self::Unspecified),
+            "SLIDING" => Some(Self::Sliding),
+            "TUMBLING" => Some(Self::Tumbling),
+            _ => None,
+        }
+    }
+}
+/// 窗口时间单位
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum TimeUnit {
+    Unspecified = 0,
+    Minute = 1,
+    Hour = 2,
+    Day = 3,
+    Week = 4,
+    Month = 5,
+}
+impl TimeUnit {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Unspecified => "TIME_UNIT_UNSPECIFIED",
+            Self::Minute => "MINUTE",
+            Self::Hour => "HOUR",
+            Self::Day => "DAY",
+            Self::Week => "WEEK",
+            Self::Month => "MONTH",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "TIME_UNIT_UNSPECIFIED" => Some(Self::Unspecified),
+            "MINUTE" => Some(Self::Minute),
+            "HOUR" => Some(Self::Hour),
+            "DAY" => Some(Self::Day),
+            "WEEK" => Some(Self::Week),
+            "MONTH" => Some(Self::Month),
+            _ => None,
+        }
+    }
+}
 /// 操作符
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
@@ -480,6 +659,36 @@ pub mod rule_engine_service_client {
                 );
             self.inner.unary(req, path, codec).await
         }
+        /// 查询用户在规则聚合条件上的当前进度（只读）
+        pub async fn get_rule_progress(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetRuleProgressRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetRuleProgressResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/badge.rule_engine.RuleEngineService/GetRuleProgress",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "badge.rule_engine.RuleEngineService",
+                        "GetRuleProgress",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -535,6 +744,14 @@ pub mod rule_engine_service_server {
             tonic::Response<super::TestRuleResponse>,
             tonic::Status,
         >;
+        /// 查询用户在规则聚合条件上的当前进度（只读）
+        async fn get_rule_progress(
+            &self,
+            request: tonic::Request<super::GetRuleProgressRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetRuleProgressResponse>,
+            tonic::Status,
+        >;
     }
     /// 规则引擎服务
     #[derive(Debug)]
@@ -839,6 +1056,52 @@ pub mod rule_engine_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/badge.rule_engine.RuleEngineService/GetRuleProgress" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetRuleProgressSvc<T: RuleEngineService>(pub Arc<T>);
+                    impl<
+                        T: RuleEngineService,
+                    > tonic::server::UnaryService<super::GetRuleProgressRequest>
+                    for GetRuleProgressSvc<T> {
+                        type Response = super::GetRuleProgressResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetRuleProgressRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as RuleEngineService>::get_rule_progress(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = GetRuleProgressSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         let mut response = http::Response::new(
@@ -7,6 +7,7 @@ use crate::error::{BadgeError, Result};
 use redis::aio::MultiplexedConnection;
 use redis::{AsyncCommands, Client};
 use serde::{Serialize, de::DeserializeOwned};
+use std::collections::HashMap;
 use std::time::Duration;
 use tracing::{info, instrument};
 
@@ -155,6 +156,98 @@ impl Cache {
         let _: () = conn.expire(key, ttl.as_secs() as i64).await?;
         Ok(())
     }
+
+    /// 读取哈希的全部字段
+    pub async fn hget_all(&self, key: &str) -> Result<HashMap<String, String>> {
+        let mut conn = self.get_conn().await?;
+        let result: HashMap<String, String> = conn.hgetall(key).await?;
+        Ok(result)
+    }
+
+    /// 删除哈希字段
+    pub async fn hdel(&self, key: &str, fields: &[String]) -> Result<()> {
+        if fields.is_empty() {
+            return Ok(());
+        }
+        let mut conn = self.get_conn().await?;
+        let _: () = conn.hdel(key, fields).await?;
+        Ok(())
+    }
+
+    /// 读取集合全部成员
+    pub async fn smembers(&self, key: &str) -> Result<Vec<String>> {
+        let mut conn = self.get_conn().await?;
+        let members: Vec<String> = conn.smembers(key).await?;
+        Ok(members)
+    }
+
+    /// 批量写入有序集合成员
+    pub async fn zadd_multiple(&self, key: &str, items: &[(f64, String)]) -> Result<()> {
+        if items.is_empty() {
+            return Ok(());
+        }
+        let mut conn = self.get_conn().await?;
+        let _: usize = conn.zadd_multiple(key, items).await?;
+        Ok(())
+    }
+
+    /// 按分数从高到低读取区间内的成员和分数（闭区间，下标从 0 开始）
+    pub async fn zrevrange_with_scores(
+        &self,
+        key: &str,
+        start: isize,
+        stop: isize,
+    ) -> Result<Vec<(String, f64)>> {
+        let mut conn = self.get_conn().await?;
+        let members: Vec<(String, f64)> = conn.zrevrange_withscores(key, start, stop).await?;
+        Ok(members)
+    }
+
+    /// 成员按分数从高到低的排名（从 0 开始），不存在时返回 None
+    pub async fn zrevrank(&self, key: &str, member: &str) -> Result<Option<u64>> {
+        let mut conn = self.get_conn().await?;
+        let rank: Option<u64> = conn.zrevrank(key, member).await?;
+        Ok(rank)
+    }
+
+    /// 成员分数，不存在时返回 None
+    pub async fn zscore(&self, key: &str, member: &str) -> Result<Option<f64>> {
+        let mut conn = self.get_conn().await?;
+        let score: Option<f64> = conn.zscore(key, member).await?;
+        Ok(score)
+    }
+
+    /// 有序集合成员数
+    pub async fn zcard(&self, key: &str) -> Result<u64> {
+        let mut conn = self.get_conn().await?;
+        let count: u64 = conn.zcard(key).await?;
+        Ok(count)
+    }
+
+    /// 重命名键（覆盖目标键）
+    pub async fn rename(&self, from: &str, to: &str) -> Result<()> {
+        let mut conn = self.get_conn().await?;
+        let _: () = conn.rename(from, to).await?;
+        Ok(())
+    }
+
+    /// 执行 Lua 脚本并返回整数结果
+    ///
+    /// 脚本在 Redis 中原子执行，适用于需要多条命令同时生效的写入。
+    /// 优先以 EVALSHA 调用，脚本未缓存时自动回退到 EVAL。
+    pub async fn eval_script(&self, script: &str, keys: &[String], args: &[String]) -> Result<i64> {
+        let mut conn = self.get_conn().await?;
+        let script = redis::Script::new(script);
+        let mut invocation = script.prepare_invoke();
+        for key in keys {
+            invocation.key(key);
+        }
+        for arg in args {
+            invocation.arg(arg);
+        }
+        let result: i64 = invocation.invoke_async(&mut conn).await?;
+        Ok(result)
+    }
 }
 
 /// 缓存键生成器
@@ -177,9 +270,18 @@ impl CacheKey {
         format!("user:badge:count:{}", user_id)
     }
 
+    /// 用户未获得徽章的进度缓存，新事件或发放后失效
+    pub fn user_badge_progress(user_id: &str) -> String {
+        format!("user:badge:progress:{}", user_id)
+    }
+
     pub fn rule(rule_id: &str) -> String {
         format!("rule:{}", rule_id)
     }
+
+    pub fn rule_aggregate(state_key: &str) -> String {
+        format!("rule:agg:{}", state_key)
+    }
 }
 
 #[cfg(test)]
@@ -190,5 +292,9 @@ mod tests {
     fn test_cache_key_generation() {
         assert_eq!(CacheKey::user_badges("123"), "user:badge:123");
         assert_eq!(CacheKey::badge_detail("abc"), "badge:detail:abc");
+        assert_eq!(
+            CacheKey::user_badge_progress("123"),
+            "user:badge:progress:123"
+        );
     }
 }
@@ -65,6 +65,9 @@ pub struct KafkaTopicsConfig {
     #[serde(default = "default_transaction_events")]
     pub transaction_events: String,
 
+    #[serde(default = "default_lifecycle_events")]
+    pub lifecycle_events: String,
+
     #[serde(default = "default_notifications")]
     pub notifications: String,
 
@@ -81,6 +84,9 @@ fn default_engagement_events() -> String {
 fn default_transaction_events() -> String {
     "badge.transaction.events".into()
 }
+fn default_lifecycle_events() -> String {
+    "badge.lifecycle.events".into()
+}
 fn default_notifications() -> String {
     "badge.notifications".into()
 }
@@ -96,6 +102,7 @@ impl Default for KafkaTopicsConfig {
         Self {
             engagement_events: default_engagement_events(),
             transaction_events: default_transaction_events(),
+            lifecycle_events: default_lifecycle_events(),
             notifications: default_notifications(),
             dead_letter_queue: default_dead_letter_queue(),
             rule_reload: default_rule_reload(),
@@ -183,6 +190,21 @@ pub struct RulesConfig {
     /// 幂等窗口（小时），默认 24
     #[serde(default = "default_idempotency_ttl")]
     pub idempotency_ttl_hours: u64,
+
+    /// 规则评估方式，默认通过 gRPC 调用规则引擎服务
+    #[serde(default)]
+    pub evaluation_mode: RuleEvaluationMode,
+}
+
+/// 事件服务的规则评估方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleEvaluationMode {
+    /// 通过 gRPC 调用独立部署的规则引擎服务
+    #[default]
+    Remote,
+    /// 在事件服务进程内直接执行规则引擎，省去网络调用
+    Embedded,
 }
 
 fn default_refresh_interval() -> u64 {
@@ -201,6 +223,7 @@ impl Default for RulesConfig {
             refresh_interval_secs: default_refresh_interval(),
             initial_load_timeout_secs: default_initial_timeout(),
             idempotency_ttl_hours: default_idempotency_ttl(),
+            evaluation_mode: RuleEvaluationMode::default(),
         }
     }
 }
@@ -286,6 +309,303 @@ impl Default for EncryptionConfig {
     }
 }
 
+/// 徽章墙展示配置
+///
+/// 控制用户徽章墙的置顶展示位，由徽章管理服务使用。
+#[derive(Debug, Clone, Deserialize)]
+pub struct ShowcaseConfig {
+    /// 每个用户最多可置顶的徽章数量，默认 5
+    #[serde(default = "default_max_pinned")]
+    pub max_pinned: u32,
+}
+
+fn default_max_pinned() -> u32 {
+    5
+}
+
+impl Default for ShowcaseConfig {
+    fn default() -> Self {
+        Self {
+            max_pinned: default_max_pinned(),
+        }
+    }
+}
+
+/// 徽章兑换配置
+///
+/// 兑换频率限制按该时区的自然日、自然周（周一开始）、自然月统计，由徽章管理服务使用。
+#[derive(Debug, Clone, Deserialize)]
+pub struct RedemptionConfig {
+    /// IANA 时区，默认 Asia/Shanghai
+    #[serde(default = "default_redemption_timezone")]
+    pub timezone: String,
+    /// 用户可自助取消兑换订单的时限（分钟），管理员取消不受此限制
+    #[serde(default = "default_cancel_window_minutes")]
+    pub cancel_window_minutes: i64,
+}
+
+fn default_redemption_timezone() -> String {
+    "Asia/Shanghai".to_string()
+}
+
+fn default_cancel_window_minutes() -> i64 {
+    30
+}
+
+impl Default for RedemptionConfig {
+    fn default() -> Self {
+        Self {
+            timezone: default_redemption_timezone(),
+            cancel_window_minutes: default_cancel_window_minutes(),
+        }
+    }
+}
+
+impl RedemptionConfig {
+    /// 解析时区名称，名称无效时返回错误由调用方决定是否回退
+    pub fn tz(&self) -> Result<chrono_tz::Tz, chrono_tz::ParseError> {
+        self.timezone.parse()
+    }
+}
+
+/// SMTP 连接加密方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum SmtpTlsMode {
+    /// 明文连接，仅用于本地开发和测试（如 MailHog）
+    #[serde(rename = "none")]
+    None,
+    /// 先建立明文连接，再通过 STARTTLS 升级（通常为 587 端口）
+    #[serde(rename = "starttls")]
+    StartTls,
+    /// 连接建立即使用 TLS（通常为 465 端口）
+    #[serde(rename = "tls")]
+    Implicit,
+}
+
+/// SMTP 邮件配置
+///
+/// 邮件渠道通过 SMTP 投递通知，未启用时邮件渠道不可用。
+/// 密码通过环境变量 `BADGE_SMTP_PASSWORD` 注入，不在配置文件中存储。
+#[derive(Debug, Clone, Deserialize)]
+pub struct SmtpConfig {
+    /// 是否启用 SMTP 邮件发送
+    #[serde(default)]
+    pub enabled: bool,
+    /// SMTP 服务器地址
+    #[serde(default = "default_smtp_host")]
+    pub host: String,
+    /// SMTP 服务器端口
+    #[serde(default = "default_smtp_port")]
+    pub port: u16,
+    /// 加密方式：none / starttls / tls
+    #[serde(default = "default_smtp_tls")]
+    pub tls: SmtpTlsMode,
+    /// 认证用户名，为空时不进行 SMTP AUTH
+    pub username: Option<String>,
+    /// 认证密码
+    pub password: Option<String>,
+    /// 发件人地址
+    #[serde(default = "default_smtp_from_address")]
+    pub from_address: String,
+    /// 发件人名称，显示在邮件头 From 字段
+    #[serde(default = "default_smtp_from_name")]
+    pub from_name: String,
+    /// 单封邮件发送超时（毫秒）
+    #[serde(default = "default_smtp_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_smtp_host() -> String {
+    "localhost".into()
+}
+
+fn default_smtp_port() -> u16 {
+    587
+}
+
+fn default_smtp_tls() -> SmtpTlsMode {
+    SmtpTlsMode::StartTls
+}
+
+fn default_smtp_from_address() -> String {
+    "noreply@badge-system.com".into()
+}
+
+fn default_smtp_from_name() -> String {
+    "徽章系统".into()
+}
+
+fn default_smtp_timeout_ms() -> u64 {
+    10000
+}
+
+impl Default for SmtpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: default_smtp_host(),
+            port: default_smtp_port(),
+            tls: default_smtp_tls(),
+            username: None,
+            password: None,
+            from_address: default_smtp_from_address(),
+            from_name: default_smtp_from_name(),
+            timeout_ms: default_smtp_timeout_ms(),
+        }
+    }
+}
+
+/// 短信网关配置
+///
+/// 未启用时短信渠道不投递。API 密钥通过环境变量 `BADGE_SMS_TOKEN` 注入。
+#[derive(Debug, Clone, Deserialize)]
+pub struct SmsProviderConfig {
+    /// 是否启用短信发送
+    #[serde(default)]
+    pub enabled: bool,
+    /// 网关发送接口地址
+    #[serde(default)]
+    pub url: String,
+    /// API 密钥，通过 `Authorization: Bearer` 传递
+    #[serde(default)]
+    pub token: String,
+    /// 短信签名，由网关拼接到正文前
+    pub sign_name: Option<String>,
+    /// 单次请求超时（毫秒）
+    #[serde(default = "default_provider_timeout_ms")]
+    pub timeout_ms: u64,
+    /// 每秒请求上限，0 表示不限流
+    #[serde(default = "default_sms_rate_limit")]
+    pub rate_limit_per_sec: u32,
+}
+
+fn default_provider_timeout_ms() -> u64 {
+    5000
+}
+
+fn default_sms_rate_limit() -> u32 {
+    50
+}
+
+impl Default for SmsProviderConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: String::new(),
+            token: String::new(),
+            sign_name: None,
+            timeout_ms: default_provider_timeout_ms(),
+            rate_limit_per_sec: default_sms_rate_limit(),
+        }
+    }
+}
+
+/// 微信订阅消息配置
+///
+/// 未启用时微信渠道不投递。AppID 和 AppSecret 通过环境变量
+/// `BADGE_WECHAT_APPID`、`BADGE_WECHAT_SECRET` 注入。
+#[derive(Debug, Clone, Deserialize)]
+pub struct WeChatProviderConfig {
+    /// 是否启用微信订阅消息
+    #[serde(default)]
+    pub enabled: bool,
+    /// 接口地址
+    #[serde(default = "default_wechat_api_base")]
+    pub api_base: String,
+    #[serde(default)]
+    pub appid: String,
+    #[serde(default)]
+    pub secret: String,
+    /// 订阅消息模板 ID
+    #[serde(default)]
+    pub template_id: String,
+    /// 模板中承载通知标题的字段
+    #[serde(default = "default_wechat_title_field")]
+    pub title_field: String,
+    /// 模板中承载通知正文的字段
+    #[serde(default = "default_wechat_content_field")]
+    pub content_field: String,
+    /// 点击消息跳转的小程序页面
+    pub page: Option<String>,
+    /// 单次请求超时（毫秒）
+    #[serde(default = "default_provider_timeout_ms")]
+    pub timeout_ms: u64,
+    /// 每秒请求上限，0 表示不限流
+    #[serde(default = "default_wechat_rate_limit")]
+    pub rate_limit_per_sec: u32,
+}
+
+fn default_wechat_api_base() -> String {
+    "https://api.weixin.qq.com".into()
+}
+
+fn default_wechat_title_field() -> String {
+    "thing1".into()
+}
+
+fn default_wechat_content_field() -> String {
+    "thing2".into()
+}
+
+fn default_wechat_rate_limit() -> u32 {
+    100
+}
+
+impl Default for WeChatProviderConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            api_base: default_wechat_api_base(),
+            appid: String::new(),
+            secret: String::new(),
+            template_id: String::new(),
+            title_field: default_wechat_title_field(),
+            content_field: default_wechat_content_field(),
+            page: None,
+            timeout_ms: default_provider_timeout_ms(),
+            rate_limit_per_sec: default_wechat_rate_limit(),
+        }
+    }
+}
+
+/// APP 推送服务配置
+///
+/// 未启用时推送渠道不投递。服务端密钥通过环境变量 `BADGE_PUSH_TOKEN` 注入。
+#[derive(Debug, Clone, Deserialize)]
+pub struct PushProviderConfig {
+    /// 是否启用 APP 推送
+    #[serde(default)]
+    pub enabled: bool,
+    /// 推送发送接口地址
+    #[serde(default)]
+    pub url: String,
+    /// 服务端密钥，通过 `Authorization: Bearer` 传递
+    #[serde(default)]
+    pub token: String,
+    /// 单次请求超时（毫秒）
+    #[serde(default = "default_provider_timeout_ms")]
+    pub timeout_ms: u64,
+    /// 每秒请求上限，0 表示不限流
+    #[serde(default = "default_push_rate_limit")]
+    pub rate_limit_per_sec: u32,
+}
+
+fn default_push_rate_limit() -> u32 {
+    500
+}
+
+impl Default for PushProviderConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: String::new(),
+            token: String::new(),
+            timeout_ms: default_provider_timeout_ms(),
+            rate_limit_per_sec: default_push_rate_limit(),
+        }
+    }
+}
+
 /// 配置中心配置
 ///
 /// 控制配置热更新行为。方案 B（文件监听）是默认实现，
@@ -345,6 +665,18 @@ pub struct AppConfig {
     pub config_center: ConfigCenterConfig,
     #[serde(default)]
     pub encryption: EncryptionConfig,
+    #[serde(default)]
+    pub showcase: ShowcaseConfig,
+    #[serde(default)]
+    pub redemption: RedemptionConfig,
+    #[serde(default)]
+    pub smtp: SmtpConfig,
+    #[serde(default)]
+    pub sms: SmsProviderConfig,
+    #[serde(default)]
+    pub wechat: WeChatProviderConfig,
+    #[serde(default)]
+    pub push: PushProviderConfig,
 }
 
 impl AppConfig {
@@ -514,16 +846,83 @@ mod tests {
         assert_eq!(config.idempotency_ttl_hours, 24);
     }
 
+    #[test]
+    fn test_rule_evaluation_mode_deserialize() {
+        let config: RulesConfig =
+            serde_json::from_str(r#"{"evaluation_mode": "embedded"}"#).unwrap();
+        assert_eq!(config.evaluation_mode, RuleEvaluationMode::Embedded);
+
+        let config: RulesConfig = serde_json::from_str("{}").unwrap();
+        assert_eq!(config.evaluation_mode, RuleEvaluationMode::Remote);
+    }
+
     #[test]
     fn test_kafka_topics_config_defaults() {
         let config = KafkaTopicsConfig::default();
         assert_eq!(config.engagement_events, "badge.engagement.events");
         assert_eq!(config.transaction_events, "badge.transaction.events");
+        assert_eq!(config.lifecycle_events, "badge.lifecycle.events");
         assert_eq!(config.notifications, "badge.notifications");
         assert_eq!(config.dead_letter_queue, "badge.dlq");
         assert_eq!(config.rule_reload, "badge.rule.reload");
     }
 
+    #[test]
+    fn test_showcase_config_default() {
+        assert_eq!(ShowcaseConfig::default().max_pinned, 5);
+        assert_eq!(AppConfig::default().showcase.max_pinned, 5);
+    }
+
+    #[test]
+    fn test_redemption_config_default() {
+        assert_eq!(AppConfig::default().redemption.timezone, "Asia/Shanghai");
+        let config: RedemptionConfig = serde_json::from_str("{}").unwrap();
+        assert_eq!(config.timezone, "Asia/Shanghai");
+        assert_eq!(config.tz().unwrap(), chrono_tz::Asia::Shanghai);
+        assert_eq!(config.cancel_window_minutes, 30);
+
+        let config = RedemptionConfig {
+            timezone: "Mars/Olympus".to_string(),
+            cancel_window_minutes: 30,
+        };
+        assert!(config.tz().is_err());
+    }
+
+    #[test]
+    fn test_smtp_config_deserialize() {
+        let config: SmtpConfig = serde_json::from_str("{}").unwrap();
+        assert!(!config.enabled);
+        assert_eq!(config.port, 587);
+        assert_eq!(config.tls, SmtpTlsMode::StartTls);
+
+        let config: SmtpConfig =
+            serde_json::from_str(r#"{"enabled": true, "port": 465, "tls": "tls"}"#).unwrap();
+        assert!(config.enabled);
+        assert_eq!(config.tls, SmtpTlsMode::Implicit);
+
+        let config: SmtpConfig = serde_json::from_str(r#"{"tls": "none"}"#).unwrap();
+        assert_eq!(config.tls, SmtpTlsMode::None);
+    }
+
+    #[test]
+    fn test_provider_configs_disabled_by_default() {
+        let config = AppConfig::default();
+        assert!(!config.sms.enabled);
+        assert!(config.sms.url.is_empty());
+        assert!(config.sms.token.is_empty());
+        assert!(!config.wechat.enabled);
+        assert!(config.wechat.secret.is_empty());
+        assert!(!config.push.enabled);
+        assert!(config.push.url.is_empty());
+
+        let config: WeChatProviderConfig =
+            serde_json::from_str(r#"{"enabled": true, "appid": "wx-app"}"#).unwrap();
+        assert!(config.enabled);
+        assert_eq!(config.appid, "wx-app");
+        assert_eq!(config.api_base, "https://api.weixin.qq.com");
+        assert_eq!(config.rate_limit_per_sec, 100);
+    }
+
     #[test]
     fn test_service_port_env_var_names() {
         // 验证各服务对应的环境变量名
@@ -1,20 +1,41 @@
 //! 死信队列处理
 //!
 //! 当事件处理失败且重试耗尽后，消息会被发送到死信队列（DLQ）。
-//! DLQ 消费者会按退避策略尝试重新投递，超过上限后记录日志等待人工介入。
+//! DLQ 消费者把死信持久化到 `dead_letter_messages` 表，重放调度器按退避策略
+//! 把到期的消息发回原始 topic，超过上限的消息保留在表中等待人工重放或清理。
 //! 这一机制确保消息不会因瞬时故障而永久丢失。
+//!
+//! ## 状态流转
+//!
+//! - `pending`：等待自动重放，`next_retry_at` 到达后由调度器领取
+//! - `replaying`：调度器已领取，正在发回原始 topic
+//! - `replayed`：已发回原始 topic；业务再次失败时重新进入 DLQ，重试次数加一
+//! - `exhausted`：重试次数耗尽，需人工介入
 
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
 use tokio::sync::watch;
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
 
 use crate::config::AppConfig;
-use crate::error::BadgeError;
+use crate::error::{BadgeError, Result};
 use crate::events::EventPayload;
 use crate::kafka::{ConsumerMessage, KafkaConsumer, KafkaProducer, topics};
+use crate::observability::metrics;
 use crate::retry::RetryPolicy;
 
+/// 死信消息默认的最大自动重试次数
+pub const DEFAULT_DLQ_MAX_RETRIES: u32 = 3;
+
+/// `replaying` 状态超过该时长视为调度器异常退出，消息重新回到 `pending`
+const STALE_REPLAYING_SECS: i64 = 300;
+
 // ---------------------------------------------------------------------------
 // DeadLetterMessage — 死信消息信封
 // ---------------------------------------------------------------------------
@@ -83,6 +104,35 @@ impl DeadLetterMessage {
         self.retry_count < self.max_retries
     }
 
+    /// 从处理失败的事件构造死信消息，按事件类型推断原始 topic
+    pub fn from_event(
+        event: &EventPayload,
+        error: impl Into<String>,
+        max_retries: u32,
+        source_service: impl Into<String>,
+    ) -> Result<Self> {
+        let payload = serde_json::to_string(event)
+            .map_err(|e| BadgeError::Kafka(format!("序列化事件失败: {e}")))?;
+
+        Ok(Self::new(
+            &event.event_id,
+            source_topic_for(event),
+            payload,
+            error,
+            max_retries,
+            source_service,
+        ))
+    }
+
+    /// 持久化时的状态：仍有重试机会时等待自动重放，否则需人工介入
+    pub fn status(&self) -> DeadLetterStatus {
+        if self.should_retry() && self.next_retry_at.is_some() {
+            DeadLetterStatus::Pending
+        } else {
+            DeadLetterStatus::Exhausted
+        }
+    }
+
     /// 增加重试计数并更新元数据
     ///
     /// 每次重试失败后调用，更新错误信息和时间戳，
@@ -104,6 +154,32 @@ impl DeadLetterMessage {
     }
 }
 
+/// 根据事件类型推断事件的原始 topic
+pub fn source_topic_for(event: &EventPayload) -> &'static str {
+    if event.event_type.is_transaction() {
+        topics::TRANSACTION_EVENTS
+    } else if event.event_type.is_identity() || event.event_type.is_seasonal() {
+        topics::LIFECYCLE_EVENTS
+    } else {
+        topics::ENGAGEMENT_EVENTS
+    }
+}
+
+/// 将死信消息发送到死信队列
+pub async fn publish(producer: &KafkaProducer, message: &DeadLetterMessage) -> Result<()> {
+    producer
+        .send_json(topics::DEAD_LETTER_QUEUE, &message.message_id, message)
+        .await?;
+
+    warn!(
+        message_id = %message.message_id,
+        source_topic = %message.source_topic,
+        error = %message.error,
+        "消息已发送到死信队列"
+    );
+    Ok(())
+}
+
 // ---------------------------------------------------------------------------
 // DlqProducer — 将失败消息发送到死信队列
 // ---------------------------------------------------------------------------
@@ -134,7 +210,7 @@ impl DlqProducer {
         source_topic: &str,
         payload: &str,
         error: &str,
-    ) -> Result<(), BadgeError> {
+    ) -> Result<()> {
         let dlq_msg = DeadLetterMessage::new(
             message_id,
             source_topic,
@@ -144,39 +220,328 @@ impl DlqProducer {
             &self.source_service,
         );
 
-        self.producer
-            .send_json(topics::DEAD_LETTER_QUEUE, message_id, &dlq_msg)
-            .await?;
-
-        warn!(message_id, source_topic, error, "消息已发送到死信队列");
-
-        Ok(())
+        publish(&self.producer, &dlq_msg).await
     }
 
     /// 从 EventPayload 构造死信消息并发送
     ///
     /// 便捷方法：自动提取 event_id 作为 message_id，
     /// 根据事件类型推断 source_topic，并将整个事件序列化为 payload。
-    pub async fn send_event_to_dlq(
-        &self,
-        event: &EventPayload,
-        error: &str,
-    ) -> Result<(), BadgeError> {
-        let payload = serde_json::to_string(event)
-            .map_err(|e| BadgeError::Kafka(format!("序列化事件失败: {e}")))?;
+    pub async fn send_event_to_dlq(&self, event: &EventPayload, error: &str) -> Result<()> {
+        let dlq_msg = DeadLetterMessage::from_event(
+            event,
+            error,
+            self.retry_policy.max_retries,
+            &self.source_service,
+        )?;
 
-        // 根据事件类型推断原始 topic
-        let source_topic = if event.event_type.is_transaction() {
-            topics::TRANSACTION_EVENTS
-        } else if event.event_type.is_engagement() {
-            topics::ENGAGEMENT_EVENTS
-        } else {
-            // 身份类和季节类事件暂归入行为事件 topic
-            topics::ENGAGEMENT_EVENTS
-        };
+        publish(&self.producer, &dlq_msg).await
+    }
+}
 
-        self.send_to_dlq(&event.event_id, source_topic, &payload, error)
-            .await
+// ---------------------------------------------------------------------------
+// DeadLetterStore — 死信持久化
+// ---------------------------------------------------------------------------
+
+/// 死信消息状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeadLetterStatus {
+    /// 等待自动重放
+    Pending,
+    /// 调度器已领取，正在重放
+    Replaying,
+    /// 已发回原始 topic
+    Replayed,
+    /// 重试次数耗尽，需人工介入
+    Exhausted,
+}
+
+impl DeadLetterStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Replaying => "replaying",
+            Self::Replayed => "replayed",
+            Self::Exhausted => "exhausted",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pending" => Some(Self::Pending),
+            "replaying" => Some(Self::Replaying),
+            "replayed" => Some(Self::Replayed),
+            "exhausted" => Some(Self::Exhausted),
+            _ => None,
+        }
+    }
+}
+
+/// 到期待重放的死信
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct DueDeadLetter {
+    pub id: i64,
+    pub message_id: String,
+    pub source_topic: String,
+    pub source_service: String,
+    pub payload: String,
+}
+
+/// 按来源服务和状态统计的死信数量
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct DeadLetterDepth {
+    pub source_service: String,
+    pub status: String,
+    pub count: i64,
+}
+
+/// 死信存储
+#[async_trait]
+pub trait DeadLetterStore: Send + Sync {
+    /// 记录进入死信队列的消息
+    ///
+    /// 同一消息（`source_topic` + `message_id`）重放后再次失败时累加重试次数，
+    /// 尚未重放就重复到达（Kafka 重复投递）时只更新错误信息。
+    async fn record(&self, message: &DeadLetterMessage) -> Result<()>;
+
+    /// 领取一批到期的 `pending` 死信并标记为 `replaying`
+    async fn claim_due(&self, limit: i64) -> Result<Vec<DueDeadLetter>>;
+
+    /// 标记已发回原始 topic
+    async fn mark_replayed(&self, id: i64) -> Result<()>;
+
+    /// 重放发送失败，回到 `pending` 并在指定时间后再次尝试
+    async fn release(&self, id: i64, error: &str, next_retry_at: DateTime<Utc>) -> Result<()>;
+
+    /// 按来源服务和状态统计死信数量
+    async fn depth(&self) -> Result<Vec<DeadLetterDepth>>;
+}
+
+/// 已记录的同一条死信
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct ExistingDeadLetter {
+    id: i64,
+    status: String,
+    retry_count: i32,
+    max_retries: i32,
+    first_failed_at: DateTime<Utc>,
+}
+
+/// 记录死信时的处理方式
+#[derive(Debug)]
+enum RecordPlan {
+    /// 首次进入 DLQ
+    Insert(DeadLetterMessage),
+    /// 重放后再次失败，按退避策略安排下一次重试
+    Retry(i64, DeadLetterMessage),
+    /// 重复到达，只更新错误信息
+    Touch(i64, DeadLetterMessage),
+}
+
+fn plan_record(
+    existing: Option<ExistingDeadLetter>,
+    mut incoming: DeadLetterMessage,
+    retry_policy: &RetryPolicy,
+) -> RecordPlan {
+    let Some(existing) = existing else {
+        return RecordPlan::Insert(incoming);
+    };
+
+    match DeadLetterStatus::parse(&existing.status) {
+        Some(DeadLetterStatus::Replayed | DeadLetterStatus::Replaying) => {
+            let error = incoming.error.clone();
+            incoming.retry_count = existing.retry_count.max(0) as u32;
+            incoming.max_retries = existing.max_retries.max(0) as u32;
+            incoming.first_failed_at = existing.first_failed_at;
+            incoming.increment_retry(&error, retry_policy);
+            RecordPlan::Retry(existing.id, incoming)
+        }
+        _ => RecordPlan::Touch(existing.id, incoming),
+    }
+}
+
+/// 基于 PostgreSQL 的死信存储
+///
+/// 领取时使用 `FOR UPDATE SKIP LOCKED`，多实例部署时同一条死信不会被重复重放。
+pub struct PgDeadLetterStore {
+    pool: PgPool,
+    retry_policy: RetryPolicy,
+}
+
+impl PgDeadLetterStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            retry_policy: RetryPolicy::default(),
+        }
+    }
+
+    /// 设置重放后再次失败时的退避策略
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+}
+
+#[async_trait]
+impl DeadLetterStore for PgDeadLetterStore {
+    async fn record(&self, message: &DeadLetterMessage) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let existing = sqlx::query_as::<_, ExistingDeadLetter>(
+            r#"
+            SELECT id, status, retry_count, max_retries, first_failed_at
+            FROM dead_letter_messages
+            WHERE source_topic = $1 AND message_id = $2
+            FOR UPDATE
+            "#,
+        )
+        .bind(&message.source_topic)
+        .bind(&message.message_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        match plan_record(existing, message.clone(), &self.retry_policy) {
+            RecordPlan::Insert(m) => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO dead_letter_messages
+                        (message_id, source_topic, source_service, payload, error, retry_count,
+                         max_retries, status, first_failed_at, last_failed_at, next_retry_at)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                    ON CONFLICT (source_topic, message_id) DO NOTHING
+                    "#,
+                )
+                .bind(&m.message_id)
+                .bind(&m.source_topic)
+                .bind(&m.source_service)
+                .bind(&m.payload)
+                .bind(&m.error)
+                .bind(m.retry_count as i32)
+                .bind(m.max_retries as i32)
+                .bind(m.status().as_str())
+                .bind(m.first_failed_at)
+                .bind(m.last_failed_at)
+                .bind(m.next_retry_at)
+                .execute(&mut *tx)
+                .await?;
+            }
+            RecordPlan::Retry(id, m) => {
+                sqlx::query(
+                    r#"
+                    UPDATE dead_letter_messages
+                    SET payload = $2, error = $3, retry_count = $4, status = $5,
+                        last_failed_at = $6, next_retry_at = $7, updated_at = NOW()
+                    WHERE id = $1
+                    "#,
+                )
+                .bind(id)
+                .bind(&m.payload)
+                .bind(&m.error)
+                .bind(m.retry_count as i32)
+                .bind(m.status().as_str())
+                .bind(m.last_failed_at)
+                .bind(m.next_retry_at)
+                .execute(&mut *tx)
+                .await?;
+            }
+            RecordPlan::Touch(id, m) => {
+                sqlx::query(
+                    r#"
+                    UPDATE dead_letter_messages
+                    SET error = $2, last_failed_at = $3, updated_at = NOW()
+                    WHERE id = $1
+                    "#,
+                )
+                .bind(id)
+                .bind(&m.error)
+                .bind(m.last_failed_at)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn claim_due(&self, limit: i64) -> Result<Vec<DueDeadLetter>> {
+        // 领取前先回收长时间停留在 replaying 的消息（调度器重放过程中崩溃）
+        sqlx::query(
+            r#"
+            UPDATE dead_letter_messages
+            SET status = 'pending', next_retry_at = NOW(), updated_at = NOW()
+            WHERE status = 'replaying'
+              AND updated_at < NOW() - make_interval(secs => $1)
+            "#,
+        )
+        .bind(STALE_REPLAYING_SECS as f64)
+        .execute(&self.pool)
+        .await?;
+
+        let due = sqlx::query_as::<_, DueDeadLetter>(
+            r#"
+            UPDATE dead_letter_messages
+            SET status = 'replaying', updated_at = NOW()
+            WHERE id IN (
+                SELECT id FROM dead_letter_messages
+                WHERE status = 'pending' AND next_retry_at <= NOW()
+                ORDER BY next_retry_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, message_id, source_topic, source_service, payload
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(due)
+    }
+
+    async fn mark_replayed(&self, id: i64) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE dead_letter_messages
+            SET status = 'replayed', next_retry_at = NULL, replayed_at = NOW(), updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn release(&self, id: i64, error: &str, next_retry_at: DateTime<Utc>) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE dead_letter_messages
+            SET status = 'pending', error = $2, next_retry_at = $3, updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(error)
+        .bind(next_retry_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn depth(&self) -> Result<Vec<DeadLetterDepth>> {
+        let rows = sqlx::query_as::<_, DeadLetterDepth>(
+            r#"
+            SELECT source_service, status, COUNT(*) AS count
+            FROM dead_letter_messages
+            GROUP BY source_service, status
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
     }
 }
 
@@ -186,19 +551,18 @@ impl DlqProducer {
 
 /// DLQ 消费者
 ///
-/// 持续消费死信队列，对尚有重试机会且已到达重试时间的消息重新投递到原始 topic。
-/// 超过重试上限的消息记录日志以便人工介入。
+/// 持续消费死信队列，把每条死信写入死信存储。消费位点提交后消息仍保留在存储中，
+/// 由 `DlqReplayScheduler` 在重试时间到达后重放。
 pub struct DlqConsumer {
     consumer: KafkaConsumer,
-    /// 将待重试的消息发回原始 topic
-    retry_producer: KafkaProducer,
+    store: Arc<dyn DeadLetterStore>,
 }
 
 impl DlqConsumer {
     /// 创建 DLQ 消费者
     ///
     /// 使用 `.dlq` 后缀作为独立消费组，与业务消费者互不干扰
-    pub fn new(config: &AppConfig, retry_producer: KafkaProducer) -> Result<Self, BadgeError> {
+    pub fn new(config: &AppConfig, store: Arc<dyn DeadLetterStore>) -> Result<Self> {
         let consumer = KafkaConsumer::new(&config.kafka, Some("dlq"))?;
         consumer.subscribe(&[topics::DEAD_LETTER_QUEUE])?;
 
@@ -207,20 +571,17 @@ impl DlqConsumer {
             topics::DEAD_LETTER_QUEUE
         );
 
-        Ok(Self {
-            consumer,
-            retry_producer,
-        })
+        Ok(Self { consumer, store })
     }
 
     /// 启动 DLQ 消费循环
     pub async fn run(self, shutdown: watch::Receiver<bool>) {
-        let retry_producer = self.retry_producer.clone();
+        let store = self.store;
 
         self.consumer
             .start(shutdown, move |msg| {
-                let producer = retry_producer.clone();
-                async move { handle_dlq_message(&msg, &producer).await }
+                let store = store.clone();
+                async move { handle_dlq_message(&msg, store.as_ref()).await }
             })
             .await;
 
@@ -230,64 +591,203 @@ impl DlqConsumer {
 
 /// 处理单条死信消息
 ///
-/// 判断消息是否仍可重试且重试时间已到达：
-/// - 是 → 将原始 payload 发回 source_topic，由业务消费者重新处理
-/// - 否 → 记录错误日志，需要人工介入处理
-async fn handle_dlq_message(
-    msg: &ConsumerMessage,
-    retry_producer: &KafkaProducer,
-) -> Result<(), BadgeError> {
-    let dlq_msg: DeadLetterMessage = msg.deserialize_payload()?;
-
-    if dlq_msg.should_retry() {
-        // 检查是否已到达下次重试时间
-        let now = Utc::now();
-        if let Some(next_retry) = dlq_msg.next_retry_at
-            && now >= next_retry
-        {
-            info!(
-                message_id = %dlq_msg.message_id,
-                source_topic = %dlq_msg.source_topic,
-                retry_count = dlq_msg.retry_count,
-                max_retries = dlq_msg.max_retries,
-                "重试死信消息，发回原始 topic"
+/// 信封格式无法识别的消息（如旧版本直接写入的原始事件）同样入库，
+/// 标记为需人工介入，避免提交位点后丢失。
+async fn handle_dlq_message(msg: &ConsumerMessage, store: &dyn DeadLetterStore) -> Result<()> {
+    let dlq_msg = match msg.deserialize_payload::<DeadLetterMessage>() {
+        Ok(dlq_msg) => dlq_msg,
+        Err(e) => {
+            warn!(
+                partition = msg.partition,
+                offset = msg.offset,
+                error = %e,
+                "死信消息格式无法识别，记录为需人工介入"
             );
-
-            retry_producer
-                .send(
-                    &dlq_msg.source_topic,
-                    &dlq_msg.message_id,
-                    dlq_msg.payload.as_bytes(),
-                )
-                .await?;
-
-            return Ok(());
+            unrecognized_dead_letter(msg, &e.to_string())
         }
+    };
 
-        // 重试时间未到，记录调试信息（消息会在下次消费时再次检查）
-        info!(
-            message_id = %dlq_msg.message_id,
-            next_retry_at = ?dlq_msg.next_retry_at,
-            "死信消息重试时间未到，跳过"
-        );
-    } else {
-        // 已耗尽重试次数，需人工介入
-        error!(
+    store.record(&dlq_msg).await?;
+
+    match dlq_msg.status() {
+        DeadLetterStatus::Exhausted => error!(
             message_id = %dlq_msg.message_id,
             source_topic = %dlq_msg.source_topic,
             source_service = %dlq_msg.source_service,
             retry_count = dlq_msg.retry_count,
             max_retries = dlq_msg.max_retries,
-            first_failed_at = %dlq_msg.first_failed_at,
-            last_failed_at = %dlq_msg.last_failed_at,
             error = %dlq_msg.error,
             "死信消息已耗尽重试次数，需人工介入"
-        );
+        ),
+        _ => info!(
+            message_id = %dlq_msg.message_id,
+            source_topic = %dlq_msg.source_topic,
+            next_retry_at = ?dlq_msg.next_retry_at,
+            "死信消息已记录，等待重放"
+        ),
     }
 
     Ok(())
 }
 
+/// 将无法解析的死信包装为不可自动重放的信封
+fn unrecognized_dead_letter(msg: &ConsumerMessage, error: &str) -> DeadLetterMessage {
+    let message_id = msg
+        .key
+        .clone()
+        .unwrap_or_else(|| format!("{}-{}-{}", msg.topic, msg.partition, msg.offset));
+    let mut dlq_msg = DeadLetterMessage::new(
+        message_id,
+        "",
+        String::from_utf8_lossy(&msg.payload),
+        format!("死信消息格式无法识别: {}", error),
+        0,
+        "unknown",
+    );
+    dlq_msg.next_retry_at = None;
+    dlq_msg
+}
+
+// ---------------------------------------------------------------------------
+// DlqReplayScheduler — 重放到期的死信
+// ---------------------------------------------------------------------------
+
+/// 死信重放调度器
+///
+/// 定期领取到期的死信发回原始 topic，并更新死信积压指标。
+/// 管理后台的手动重放只是把死信置为立即到期，实际发送同样由调度器完成。
+pub struct DlqReplayScheduler {
+    store: Arc<dyn DeadLetterStore>,
+    producer: KafkaProducer,
+    interval: Duration,
+    batch_size: i64,
+    /// 已上报过的积压指标标签，数量归零后需显式置 0
+    reported: std::sync::Mutex<HashSet<(String, String)>>,
+}
+
+impl DlqReplayScheduler {
+    pub fn new(store: Arc<dyn DeadLetterStore>, producer: KafkaProducer) -> Self {
+        Self {
+            store,
+            producer,
+            interval: Duration::from_secs(10),
+            batch_size: 100,
+            reported: std::sync::Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// 设置轮询间隔
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// 设置每轮最多重放的死信数量
+    pub fn with_batch_size(mut self, batch_size: i64) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// 调度循环，收到停机信号后退出
+    pub async fn run(self, mut shutdown: watch::Receiver<bool>) {
+        let mut ticker = tokio::time::interval(self.interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        info!(
+            interval_secs = self.interval.as_secs(),
+            "死信重放调度器已启动"
+        );
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => self.tick().await,
+                _ = shutdown.changed() => break,
+            }
+        }
+
+        info!("死信重放调度器已停止");
+    }
+
+    /// 重放一批到期的死信并刷新积压指标
+    pub async fn tick(&self) {
+        match self.store.claim_due(self.batch_size).await {
+            Ok(due) => {
+                for dead_letter in due {
+                    self.replay(&dead_letter).await;
+                }
+            }
+            Err(e) => warn!(error = %e, "领取到期死信失败"),
+        }
+
+        self.report_depth().await;
+        metrics::set_worker_last_run("dlq_replay");
+    }
+
+    async fn replay(&self, dead_letter: &DueDeadLetter) {
+        let sent = self
+            .producer
+            .send(
+                &dead_letter.source_topic,
+                &dead_letter.message_id,
+                dead_letter.payload.as_bytes(),
+            )
+            .await;
+
+        let result = match sent {
+            Ok(_) => {
+                info!(
+                    message_id = %dead_letter.message_id,
+                    source_topic = %dead_letter.source_topic,
+                    "死信已重放到原始 topic"
+                );
+                metrics::record_dlq_replay(&dead_letter.source_service, "success");
+                self.store.mark_replayed(dead_letter.id).await
+            }
+            Err(e) => {
+                warn!(
+                    message_id = %dead_letter.message_id,
+                    error = %e,
+                    "死信重放失败，稍后重试"
+                );
+                metrics::record_dlq_replay(&dead_letter.source_service, "failed");
+                let next_retry_at =
+                    Utc::now() + chrono::Duration::from_std(self.interval).unwrap_or_default();
+                self.store
+                    .release(dead_letter.id, &format!("重放失败: {e}"), next_retry_at)
+                    .await
+            }
+        };
+
+        if let Err(e) = result {
+            error!(id = dead_letter.id, error = %e, "更新死信状态失败");
+        }
+    }
+
+    async fn report_depth(&self) {
+        let depth = match self.store.depth().await {
+            Ok(depth) => depth,
+            Err(e) => {
+                debug!(error = %e, "统计死信积压失败");
+                return;
+            }
+        };
+
+        let current: HashSet<(String, String)> = depth
+            .iter()
+            .map(|d| (d.source_service.clone(), d.status.clone()))
+            .collect();
+        for d in &depth {
+            metrics::set_dlq_depth(&d.source_service, &d.status, d.count as f64);
+        }
+
+        let mut reported = self.reported.lock().unwrap();
+        for (source_service, status) in reported.difference(&current) {
+            metrics::set_dlq_depth(source_service, status, 0.0);
+        }
+        *reported = current;
+    }
+}
+
 // ---------------------------------------------------------------------------
 // 单元测试
 // ---------------------------------------------------------------------------
@@ -405,4 +905,158 @@ mod tests {
         assert_eq!(deserialized.max_retries, 5);
         assert_eq!(deserialized.source_service, "transaction-service");
     }
+
+    fn existing(status: &str, retry_count: i32) -> ExistingDeadLetter {
+        ExistingDeadLetter {
+            id: 7,
+            status: status.to_string(),
+            retry_count,
+            max_retries: 3,
+            first_failed_at: Utc::now() - chrono::Duration::hours(1),
+        }
+    }
+
+    #[test]
+    fn test_status_of_new_and_exhausted_messages() {
+        let msg = DeadLetterMessage::new("evt-001", "topic", "payload", "error", 3, "svc");
+        assert_eq!(msg.status(), DeadLetterStatus::Pending);
+
+        let msg = DeadLetterMessage::new("evt-001", "topic", "payload", "error", 0, "svc");
+        assert_eq!(msg.status(), DeadLetterStatus::Exhausted);
+    }
+
+    #[test]
+    fn test_dead_letter_status_round_trip() {
+        for status in [
+            DeadLetterStatus::Pending,
+            DeadLetterStatus::Replaying,
+            DeadLetterStatus::Replayed,
+            DeadLetterStatus::Exhausted,
+        ] {
+            assert_eq!(DeadLetterStatus::parse(status.as_str()), Some(status));
+        }
+        assert_eq!(DeadLetterStatus::parse("unknown"), None);
+    }
+
+    #[test]
+    fn test_plan_record_inserts_new_message() {
+        let msg = DeadLetterMessage::new("evt-001", "topic", "payload", "error", 3, "svc");
+        let plan = plan_record(None, msg, &RetryPolicy::default());
+        assert!(matches!(plan, RecordPlan::Insert(m) if m.retry_count == 0));
+    }
+
+    #[test]
+    fn test_plan_record_increments_after_replay() {
+        let existing = existing("replayed", 1);
+        let first_failed_at = existing.first_failed_at;
+        let msg = DeadLetterMessage::new("evt-001", "topic", "payload", "再次失败", 3, "svc");
+
+        match plan_record(Some(existing), msg, &RetryPolicy::default()) {
+            RecordPlan::Retry(id, m) => {
+                assert_eq!(id, 7);
+                assert_eq!(m.retry_count, 2);
+                assert_eq!(m.error, "再次失败");
+                assert_eq!(m.first_failed_at, first_failed_at);
+                assert_eq!(m.status(), DeadLetterStatus::Pending);
+            }
+            other => panic!("unexpected plan: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_plan_record_exhausts_after_last_replay() {
+        let msg = DeadLetterMessage::new("evt-001", "topic", "payload", "error", 3, "svc");
+
+        match plan_record(Some(existing("replayed", 2)), msg, &RetryPolicy::default()) {
+            RecordPlan::Retry(_, m) => {
+                assert_eq!(m.retry_count, 3);
+                assert!(m.next_retry_at.is_none());
+                assert_eq!(m.status(), DeadLetterStatus::Exhausted);
+            }
+            other => panic!("unexpected plan: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_plan_record_touches_duplicate_delivery() {
+        let msg = DeadLetterMessage::new("evt-001", "topic", "payload", "error", 3, "svc");
+        let plan = plan_record(Some(existing("pending", 0)), msg, &RetryPolicy::default());
+        assert!(matches!(plan, RecordPlan::Touch(7, _)));
+    }
+
+    /// 仅记录写入内容的内存存储
+    #[derive(Default)]
+    struct RecordingStore {
+        recorded: std::sync::Mutex<Vec<DeadLetterMessage>>,
+    }
+
+    #[async_trait]
+    impl DeadLetterStore for RecordingStore {
+        async fn record(&self, message: &DeadLetterMessage) -> Result<()> {
+            self.recorded.lock().unwrap().push(message.clone());
+            Ok(())
+        }
+
+        async fn claim_due(&self, _limit: i64) -> Result<Vec<DueDeadLetter>> {
+            Ok(vec![])
+        }
+
+        async fn mark_replayed(&self, _id: i64) -> Result<()> {
+            Ok(())
+        }
+
+        async fn release(&self, _id: i64, _error: &str, _next: DateTime<Utc>) -> Result<()> {
+            Ok(())
+        }
+
+        async fn depth(&self) -> Result<Vec<DeadLetterDepth>> {
+            Ok(vec![])
+        }
+    }
+
+    fn consumer_message(key: Option<&str>, payload: Vec<u8>) -> ConsumerMessage {
+        ConsumerMessage {
+            topic: topics::DEAD_LETTER_QUEUE.to_string(),
+            partition: 0,
+            offset: 42,
+            key: key.map(String::from),
+            payload,
+            timestamp: None,
+            headers: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handle_dlq_message_persists_envelope() {
+        let store = RecordingStore::default();
+        let mut msg = DeadLetterMessage::new("evt-001", "topic", "payload", "error", 3, "svc");
+        // 未来才到期的消息同样需要入库，而不是被丢弃
+        msg.next_retry_at = Some(Utc::now() + chrono::Duration::hours(1));
+        let payload = serde_json::to_vec(&msg).unwrap();
+
+        handle_dlq_message(&consumer_message(Some("evt-001"), payload), &store)
+            .await
+            .unwrap();
+
+        let recorded = store.recorded.lock().unwrap();
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].message_id, "evt-001");
+        assert_eq!(recorded[0].status(), DeadLetterStatus::Pending);
+    }
+
+    #[tokio::test]
+    async fn test_handle_dlq_message_keeps_unrecognized_payload() {
+        let store = RecordingStore::default();
+
+        handle_dlq_message(&consumer_message(None, b"not-json".to_vec()), &store)
+            .await
+            .unwrap();
+
+        let recorded = store.recorded.lock().unwrap();
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].message_id, "badge.dlq-0-42");
+        assert_eq!(recorded[0].payload, "not-json");
+        assert_eq!(recorded[0].source_service, "unknown");
+        assert_eq!(recorded[0].status(), DeadLetterStatus::Exhausted);
+    }
 }
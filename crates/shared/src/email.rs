@@ -0,0 +1,331 @@
+//! SMTP 邮件发送
+//!
+//! 徽章管理服务的邮件渠道和通知工作者的邮件发送器共用同一个 SMTP 客户端，
+//! 连接参数来自 `AppConfig.smtp`。
+//!
+//! ## 设计说明
+//!
+//! - 支持明文（仅限开发测试）、STARTTLS 和隐式 TLS 三种连接方式
+//! - 邮件统一构建为 `multipart/alternative`（纯文本 + HTML），兼容不渲染 HTML 的客户端
+//! - 每封邮件生成独立的 `Message-ID` 并返回给调用方，用于投递追踪和退信关联
+
+use std::time::Duration;
+
+use lettre::message::{Mailbox, MultiPart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Address, AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use thiserror::Error;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::config::{SmtpConfig, SmtpTlsMode};
+
+/// 通知邮件页脚
+const FOOTER: &str = "此邮件由徽章系统自动发送，请勿回复。";
+
+/// 邮件发送错误
+#[derive(Debug, Error)]
+pub enum EmailError {
+    /// 发件人或收件人地址无效，重试无意义
+    #[error("邮件地址无效: {0}")]
+    InvalidAddress(String),
+
+    /// 邮件内容构建失败
+    #[error("邮件构建失败: {0}")]
+    Build(String),
+
+    /// SMTP 连接或投递失败
+    #[error("SMTP 发送失败: {0}")]
+    Transport(String),
+}
+
+impl EmailError {
+    /// 是否为瞬时故障，地址错误属于永久失败
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::Transport(_))
+    }
+}
+
+/// 待发送的邮件
+#[derive(Debug, Clone)]
+pub struct EmailMessage {
+    /// 收件人地址
+    pub to: String,
+    pub subject: String,
+    /// 纯文本正文
+    pub text_body: String,
+    /// HTML 正文
+    pub html_body: String,
+}
+
+impl EmailMessage {
+    /// 按统一模板构建通知邮件
+    pub fn notification(to: impl Into<String>, title: &str, body: &str) -> Self {
+        Self {
+            to: to.into(),
+            subject: title.to_string(),
+            text_body: render_text(title, body),
+            html_body: render_html(title, body),
+        }
+    }
+
+    /// 使用模板渲染的 HTML 片段作为邮件 HTML 正文
+    ///
+    /// 片段中的变量值已在模板渲染时转义，这里不再转义
+    pub fn with_html_content(mut self, content: &str) -> Self {
+        self.html_body = render_html_content(&self.subject, content);
+        self
+    }
+}
+
+/// 渲染通知邮件的纯文本正文
+pub fn render_text(title: &str, body: &str) -> String {
+    format!("{}\n\n{}\n\n{}", title, body, FOOTER)
+}
+
+/// 渲染通知邮件的 HTML 正文
+///
+/// 标题和正文会做 HTML 转义，徽章名称等内容可能来自运营配置
+pub fn render_html(title: &str, body: &str) -> String {
+    render_layout(
+        &escape_html(title),
+        &format!("<p>{}</p>", escape_html(body)),
+    )
+}
+
+/// 使用已渲染的 HTML 片段生成通知邮件的 HTML 正文，仅转义标题
+pub fn render_html_content(title: &str, content: &str) -> String {
+    render_layout(&escape_html(title), content)
+}
+
+/// 邮件 HTML 布局，参数均为已转义的 HTML
+fn render_layout(title: &str, content: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>{}</title>
+    <style>
+        body {{ font-family: Arial, sans-serif; line-height: 1.6; color: #333; }}
+        .container {{ max-width: 600px; margin: 0 auto; padding: 20px; }}
+        .header {{ background: linear-gradient(135deg, #667eea 0%, #764ba2 100%); color: white; padding: 20px; border-radius: 8px 8px 0 0; }}
+        .content {{ background: #f9f9f9; padding: 20px; border-radius: 0 0 8px 8px; }}
+        .footer {{ text-align: center; color: #888; font-size: 12px; margin-top: 20px; }}
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="header">
+            <h1>{}</h1>
+        </div>
+        <div class="content">
+            {}
+        </div>
+        <div class="footer">
+            <p>{}</p>
+        </div>
+    </div>
+</body>
+</html>"#,
+        title, title, content, FOOTER
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// SMTP 邮件发送器
+///
+/// 每次发送建立独立的 SMTP 会话，克隆后可在多个任务间共享。
+#[derive(Clone)]
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    /// Message-ID 的域名部分，取自发件人地址
+    message_id_domain: String,
+}
+
+impl SmtpMailer {
+    /// 根据配置创建发送器
+    ///
+    /// 仅构建传输层，不会立即连接 SMTP 服务器，首次发送时才建立连接。
+    pub fn from_config(config: &SmtpConfig) -> Result<Self, EmailError> {
+        let builder = match config.tls {
+            SmtpTlsMode::None => {
+                AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(config.host.as_str())
+            }
+            SmtpTlsMode::StartTls => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)
+                    .map_err(|e| EmailError::Transport(e.to_string()))?
+            }
+            SmtpTlsMode::Implicit => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host)
+                .map_err(|e| EmailError::Transport(e.to_string()))?,
+        };
+
+        let mut builder = builder
+            .port(config.port)
+            .timeout(Some(Duration::from_millis(config.timeout_ms)));
+
+        if let Some(username) = config.username.as_deref().filter(|u| !u.is_empty()) {
+            if config.tls == SmtpTlsMode::None {
+                warn!(host = %config.host, "SMTP 认证信息将通过明文连接发送");
+            }
+            builder = builder.credentials(Credentials::new(
+                username.to_string(),
+                config.password.clone().unwrap_or_default(),
+            ));
+        }
+
+        let address = config
+            .from_address
+            .parse::<Address>()
+            .map_err(|e| EmailError::InvalidAddress(format!("{}: {}", config.from_address, e)))?;
+        let message_id_domain = address.domain().to_string();
+        let name = Some(config.from_name.clone()).filter(|n| !n.is_empty());
+        let from = Mailbox::new(name, address);
+
+        Ok(Self {
+            transport: builder.build(),
+            from,
+            message_id_domain,
+        })
+    }
+
+    /// 发件人
+    pub fn from(&self) -> &Mailbox {
+        &self.from
+    }
+
+    /// 发送邮件，成功时返回本次邮件的 `Message-ID`
+    pub async fn send(&self, email: &EmailMessage) -> Result<String, EmailError> {
+        let to = email
+            .to
+            .parse::<Mailbox>()
+            .map_err(|e| EmailError::InvalidAddress(format!("{}: {}", email.to, e)))?;
+
+        let message_id = format!("<{}@{}>", Uuid::new_v4(), self.message_id_domain);
+
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(email.subject.as_str())
+            .message_id(Some(message_id.clone()))
+            .multipart(MultiPart::alternative_plain_html(
+                email.text_body.clone(),
+                email.html_body.clone(),
+            ))
+            .map_err(|e| EmailError::Build(e.to_string()))?;
+
+        let response = self
+            .transport
+            .send(message)
+            .await
+            .map_err(|e| EmailError::Transport(e.to_string()))?;
+
+        debug!(
+            message_id = %message_id,
+            response = ?response.first_line(),
+            "SMTP 邮件已投递"
+        );
+
+        Ok(message_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain_config() -> SmtpConfig {
+        SmtpConfig {
+            enabled: true,
+            tls: SmtpTlsMode::None,
+            port: 2525,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_from_mailbox_uses_name_and_address() {
+        let mailer = SmtpMailer::from_config(&plain_config()).unwrap();
+        assert_eq!(mailer.from().name.as_deref(), Some("徽章系统"));
+        assert_eq!(mailer.from().email.to_string(), "noreply@badge-system.com");
+        assert_eq!(mailer.message_id_domain, "badge-system.com");
+    }
+
+    #[test]
+    fn test_invalid_from_address() {
+        let config = SmtpConfig {
+            from_address: "not-an-address".into(),
+            ..plain_config()
+        };
+        let err = SmtpMailer::from_config(&config).err().unwrap();
+        assert!(matches!(err, EmailError::InvalidAddress(_)));
+        assert!(!err.is_retryable());
+    }
+
+    #[test]
+    fn test_notification_message_escapes_html() {
+        let email = EmailMessage::notification("user@example.com", "获得徽章", "<首单> & 复购");
+        assert_eq!(email.subject, "获得徽章");
+        assert!(email.text_body.contains("<首单> & 复购"));
+        assert!(email.html_body.contains("&lt;首单&gt; &amp; 复购"));
+        assert!(email.html_body.contains(FOOTER));
+    }
+
+    #[test]
+    fn test_notification_message_with_html_content() {
+        let email = EmailMessage::notification("user@example.com", "<获得>徽章", "首单")
+            .with_html_content("<ul><li>首单</li></ul>");
+        assert!(email.text_body.contains("首单"));
+        assert!(email.html_body.contains("<ul><li>首单</li></ul>"));
+        assert!(email.html_body.contains("&lt;获得&gt;徽章"));
+        assert!(email.html_body.contains(FOOTER));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_recipient_is_not_retryable() {
+        let mailer = SmtpMailer::from_config(&plain_config()).unwrap();
+        let email = EmailMessage {
+            to: "bad address".into(),
+            subject: "s".into(),
+            text_body: "t".into(),
+            html_body: "<p>t</p>".into(),
+        };
+
+        let err = mailer.send(&email).await.unwrap_err();
+        assert!(matches!(err, EmailError::InvalidAddress(_)));
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_server_is_retryable() {
+        let config = SmtpConfig {
+            host: "127.0.0.1".into(),
+            port: 1,
+            ..plain_config()
+        };
+        let mailer = SmtpMailer::from_config(&config).unwrap();
+        let email = EmailMessage {
+            to: "user@example.com".into(),
+            subject: "s".into(),
+            text_body: "t".into(),
+            html_body: "<p>t</p>".into(),
+        };
+
+        let err = mailer.send(&email).await.unwrap_err();
+        assert!(err.is_retryable());
+    }
+}
@@ -81,6 +81,10 @@ pub enum BadgeError {
     #[error("无效的参数: {field} - {message}")]
     InvalidArgument { field: String, message: String },
 
+    /// 事件负载不符合事件类型的 JSON Schema，属于永久性错误，重放也不会成功
+    #[error("事件负载校验失败: {0}")]
+    InvalidPayload(String),
+
     // ==================== 权限错误 ====================
     #[error("未授权访问")]
     Unauthorized,
@@ -131,6 +135,7 @@ impl BadgeError {
             Self::RuleNotFound { .. } => "RULE_NOT_FOUND",
             Self::Validation(_) => "VALIDATION_ERROR",
             Self::InvalidArgument { .. } => "INVALID_ARGUMENT",
+            Self::InvalidPayload(_) => "INVALID_PAYLOAD",
             Self::Unauthorized => "UNAUTHORIZED",
             Self::Forbidden { .. } => "FORBIDDEN",
             Self::ExternalService { .. } => "EXTERNAL_SERVICE_ERROR",
@@ -159,7 +164,7 @@ impl BadgeError {
         let (code, message) = match self {
             Self::NotFound { .. } => (Code::NotFound, self.to_string()),
             Self::AlreadyExists { .. } => (Code::AlreadyExists, self.to_string()),
-            Self::Validation(_) | Self::InvalidArgument { .. } => {
+            Self::Validation(_) | Self::InvalidArgument { .. } | Self::InvalidPayload(_) => {
                 (Code::InvalidArgument, self.to_string())
             }
             Self::Unauthorized => (Code::Unauthenticated, self.to_string()),
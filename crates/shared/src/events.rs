@@ -19,8 +19,11 @@ use crate::error::BadgeError;
 ///
 /// 按业务域划分为四大类：交易、行为、身份、季节。
 /// 分类信息用于路由事件到不同的处理管道，以及在规则引擎中按类别批量启用/禁用规则。
+///
+/// 内置类型之外的事件类型在管理后台的 `event_types` 表中注册，反序列化为 `Custom`，
+/// 携带数据库编码（小写下划线格式），新增业务事件无需修改本枚举。
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(from = "String", into = "String")]
 pub enum EventType {
     // 交易类事件 — 涉及金额流转，需要与订单系统核对
     Purchase,
@@ -42,8 +45,28 @@ pub enum EventType {
     // 季节类事件 — 运营活动驱动，有时间窗口限制
     SeasonalActivity,
     CampaignParticipation,
+
+    // 自定义事件 — 在 event_types 表中注册，按服务组路由到对应服务
+    Custom(String),
 }
 
+/// 内置事件类型，用于按名称解析
+const BUILTIN_EVENT_TYPES: [EventType; 13] = [
+    EventType::Purchase,
+    EventType::Refund,
+    EventType::OrderCancel,
+    EventType::CheckIn,
+    EventType::ProfileUpdate,
+    EventType::PageView,
+    EventType::Share,
+    EventType::Review,
+    EventType::Registration,
+    EventType::MembershipUpgrade,
+    EventType::Anniversary,
+    EventType::SeasonalActivity,
+    EventType::CampaignParticipation,
+];
+
 impl EventType {
     /// 交易类事件涉及资金流转，后续可能需要退款回滚等补偿逻辑
     pub fn is_transaction(&self) -> bool {
@@ -71,12 +94,29 @@ impl EventType {
         matches!(self, Self::SeasonalActivity | Self::CampaignParticipation)
     }
 
+    /// 在 event_types 表中注册的自定义事件
+    pub fn is_custom(&self) -> bool {
+        matches!(self, Self::Custom(_))
+    }
+
+    /// 按名称解析事件类型
+    ///
+    /// 同时接受 Kafka 消息中的 SCREAMING_SNAKE_CASE 名称和数据库编码，
+    /// 不是内置类型的名称统一转为小写作为自定义事件编码。
+    pub fn from_code(code: &str) -> Self {
+        BUILTIN_EVENT_TYPES
+            .iter()
+            .find(|t| t.to_string().eq_ignore_ascii_case(code) || t.to_db_key() == code)
+            .cloned()
+            .unwrap_or_else(|| Self::Custom(code.to_ascii_lowercase()))
+    }
+
     /// 返回数据库中使用的事件类型键名
     ///
     /// 数据库中的 event_types 表使用小写下划线格式（snake_case），
     /// 而 Display trait 使用 SCREAMING_SNAKE_CASE 用于日志和 Kafka。
     /// 此方法专用于规则查找时匹配数据库记录。
-    pub fn to_db_key(&self) -> &'static str {
+    pub fn to_db_key(&self) -> &str {
         match self {
             Self::Purchase => "purchase",
             Self::Refund => "refund",
@@ -91,6 +131,7 @@ impl EventType {
             Self::Anniversary => "anniversary",
             Self::SeasonalActivity => "seasonal_activity",
             Self::CampaignParticipation => "campaign_participation",
+            Self::Custom(code) => code,
         }
     }
 }
@@ -113,11 +154,24 @@ impl std::fmt::Display for EventType {
             Self::Anniversary => "ANNIVERSARY",
             Self::SeasonalActivity => "SEASONAL_ACTIVITY",
             Self::CampaignParticipation => "CAMPAIGN_PARTICIPATION",
+            Self::Custom(code) => return write!(f, "{}", code.to_ascii_uppercase()),
         };
         write!(f, "{s}")
     }
 }
 
+impl From<String> for EventType {
+    fn from(code: String) -> Self {
+        Self::from_code(&code)
+    }
+}
+
+impl From<EventType> for String {
+    fn from(event_type: EventType) -> Self {
+        event_type.to_string()
+    }
+}
+
 // ---------------------------------------------------------------------------
 // EventPayload — 通用事件信封
 // ---------------------------------------------------------------------------
@@ -282,6 +336,16 @@ pub enum NotificationType {
     BenefitGranted,
 }
 
+impl NotificationType {
+    /// 是否为紧急通知
+    ///
+    /// 兑换结果由用户主动发起并等待反馈，即使处于免打扰时段也立即投递；
+    /// 其余通知可延迟到免打扰时段结束
+    pub fn is_urgent(&self) -> bool {
+        matches!(self, Self::RedemptionSuccess | Self::RedemptionFailed)
+    }
+}
+
 /// 通知投递渠道
 ///
 /// 各渠道有不同的消息长度限制和格式要求，通知服务会按渠道适配内容
@@ -312,6 +376,14 @@ pub trait EventProcessor: Send + Sync {
     /// 该处理器支持的事件类型，用于事件路由
     fn supported_event_types(&self) -> Vec<EventType>;
 
+    /// 判断处理器能否处理该事件类型
+    ///
+    /// 默认只接受 `supported_event_types` 中的内置类型；
+    /// 支持自定义事件的处理器覆盖此方法，按已注册的事件类型判断。
+    fn supports(&self, event_type: &EventType) -> bool {
+        self.supported_event_types().contains(event_type)
+    }
+
     /// 检查事件是否已处理（基于 event_id 的幂等性校验）
     async fn is_processed(&self, event_id: &str) -> Result<bool, BadgeError>;
 
@@ -404,6 +476,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_custom_event_type_round_trip() {
+        let event_type: EventType = serde_json::from_str("\"VIP_LOGIN\"").unwrap();
+        assert_eq!(event_type, EventType::Custom("vip_login".to_string()));
+        assert!(event_type.is_custom());
+        assert_eq!(event_type.to_db_key(), "vip_login");
+        assert_eq!(event_type.to_string(), "VIP_LOGIN");
+        assert_eq!(serde_json::to_string(&event_type).unwrap(), "\"VIP_LOGIN\"");
+
+        // 内置类型按名称或数据库编码解析，不会落入自定义类型
+        assert_eq!(EventType::from_code("CHECK_IN"), EventType::CheckIn);
+        assert_eq!(EventType::from_code("checkin"), EventType::CheckIn);
+        assert_eq!(EventType::from_code("purchase"), EventType::Purchase);
+        assert!(!EventType::Custom("vip_login".to_string()).is_engagement());
+    }
+
     #[test]
     fn test_event_payload_to_context() {
         let event = EventPayload {
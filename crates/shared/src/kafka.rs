@@ -8,15 +8,16 @@ use std::time::Duration;
 
 use rdkafka::config::ClientConfig;
 use rdkafka::consumer::{Consumer, StreamConsumer};
-use rdkafka::message::{BorrowedMessage, Headers, Message};
+use rdkafka::message::{BorrowedMessage, Header, Headers, Message, OwnedHeaders};
 use rdkafka::producer::{FutureProducer, FutureRecord};
 use serde::Serialize;
 use serde::de::DeserializeOwned;
 use tokio::sync::watch;
-use tracing::{debug, error, info, warn};
+use tracing::{Instrument, debug, error, info, warn};
 
 use crate::config::KafkaConfig;
 use crate::error::BadgeError;
+use crate::observability::middleware::kafka::{consumer_span, inject_trace_context};
 
 // ---------------------------------------------------------------------------
 // Topic 常量
@@ -26,6 +27,7 @@ use crate::error::BadgeError;
 pub mod topics {
     pub const ENGAGEMENT_EVENTS: &str = "badge.engagement.events";
     pub const TRANSACTION_EVENTS: &str = "badge.transaction.events";
+    pub const LIFECYCLE_EVENTS: &str = "badge.lifecycle.events";
     pub const BADGE_NOTIFICATIONS: &str = "badge.notifications";
     pub const DEAD_LETTER_QUEUE: &str = "badge.dlq";
     pub const RULE_RELOAD: &str = "badge.rule.reload";
@@ -137,13 +139,19 @@ impl KafkaProducer {
     }
 
     /// 发送原始字节消息
+    ///
+    /// 当前 span 的追踪上下文以 W3C `traceparent` 消息头随消息发送，
+    /// 消费端据此把处理过程串联到同一条 trace。
     pub async fn send(
         &self,
         topic: &str,
         key: &str,
         payload: &[u8],
     ) -> Result<(i32, i64), BadgeError> {
-        let record = FutureRecord::to(topic).key(key).payload(payload);
+        let mut record = FutureRecord::to(topic).key(key).payload(payload);
+        if let Some(headers) = trace_headers() {
+            record = record.headers(headers);
+        }
 
         // rdkafka 0.39+ 返回 Delivery 结构体而非元组
         let delivery = self
@@ -178,6 +186,23 @@ impl KafkaProducer {
     }
 }
 
+/// 当前追踪上下文对应的消息头，没有有效上下文时返回 `None`
+fn trace_headers() -> Option<OwnedHeaders> {
+    let mut context = HashMap::new();
+    inject_trace_context(&mut context);
+    if context.is_empty() {
+        return None;
+    }
+
+    let headers = context.iter().fold(OwnedHeaders::new(), |headers, (key, value)| {
+        headers.insert(Header {
+            key,
+            value: Some(value),
+        })
+    });
+    Some(headers)
+}
+
 // ---------------------------------------------------------------------------
 // KafkaConsumer
 // ---------------------------------------------------------------------------
@@ -201,12 +226,45 @@ impl KafkaConsumer {
             None => config.consumer_group.clone(),
         };
 
+        Self::create(config, &group_id, &config.auto_offset_reset, true)
+    }
+
+    /// 创建广播消费者
+    ///
+    /// 每个实例使用独立的消费组，保证同一条消息被所有实例各自消费一次，
+    /// 适用于规则刷新这类需要通知到每个 Pod 的控制消息。
+    /// 消费组按实例标识（Pod 名或主机名）命名，进程重启沿用同一消费组，不会遗留孤儿组；
+    /// 只关心启动之后的新消息，从最新位点开始消费且不提交位点。
+    pub fn new_broadcast(config: &KafkaConfig, name: &str) -> Result<Self, BadgeError> {
+        let instance_id = broadcast_instance_id(
+            std::env::var("POD_NAME").ok(),
+            std::env::var("HOSTNAME").ok(),
+        )
+        .unwrap_or_else(|| {
+            let fallback = uuid::Uuid::new_v4().simple().to_string();
+            warn!(
+                instance_id = %fallback,
+                "未设置 POD_NAME/HOSTNAME，广播消费组使用随机标识"
+            );
+            fallback
+        });
+        let group_id = format!("{}.{}.{}", config.consumer_group, name, instance_id);
+
+        Self::create(config, &group_id, "latest", false)
+    }
+
+    fn create(
+        config: &KafkaConfig,
+        group_id: &str,
+        auto_offset_reset: &str,
+        enable_auto_commit: bool,
+    ) -> Result<Self, BadgeError> {
         let mut client_config = ClientConfig::new();
         client_config
             .set("bootstrap.servers", &config.brokers)
-            .set("group.id", &group_id)
-            .set("auto.offset.reset", &config.auto_offset_reset)
-            .set("enable.auto.commit", "true")
+            .set("group.id", group_id)
+            .set("auto.offset.reset", auto_offset_reset)
+            .set("enable.auto.commit", enable_auto_commit.to_string())
             // 消费者处理链路涉及 DB 事务 + 规则匹配 + 通知投递，
             // 高负载下单条消息可能耗时较长，需放宽 poll 间隔避免被踢出消费组
             .set("max.poll.interval.ms", &config.max_poll_interval_ms.to_string())
@@ -245,6 +303,7 @@ impl KafkaConsumer {
     /// 使用 `tokio::select!` 同时监听消息流和关闭信号：
     /// - 收到消息时调用 handler 处理；handler 返回错误只记录日志而不中断循环，
     ///   避免单条坏消息导致整个消费者停止。
+    /// - 每条消息在独立的 span 中处理，消息头携带上游追踪上下文时作为其子 span。
     /// - 关闭信号变为 `true` 时退出循环，确保正在执行的 handler 能自然完成。
     pub async fn start<F, Fut>(self, mut shutdown: watch::Receiver<bool>, handler: F)
     where
@@ -279,15 +338,22 @@ impl KafkaConsumer {
                     match msg_result {
                         Ok(borrowed_msg) => {
                             let msg = ConsumerMessage::from_borrowed(&borrowed_msg);
+                            let span = consumer_span(
+                                &msg.topic,
+                                msg.partition,
+                                msg.offset,
+                                &msg.headers,
+                            );
                             debug!(
+                                parent: &span,
                                 topic = %msg.topic,
                                 partition = msg.partition,
                                 offset = msg.offset,
                                 "收到 Kafka 消息"
                             );
 
-                            if let Err(e) = handler(msg).await {
-                                error!(error = %e, "处理 Kafka 消息失败");
+                            if let Err(e) = handler(msg).instrument(span.clone()).await {
+                                error!(parent: &span, error = %e, "处理 Kafka 消息失败");
                             }
                         }
                         Err(e) => {
@@ -338,6 +404,17 @@ fn apply_security_config(
     }
 }
 
+/// 广播消费组使用的实例标识
+///
+/// 优先取 Pod 名（Downward API 注入的 POD_NAME），其次取主机名；K8s 中 HOSTNAME 即 Pod 名。
+fn broadcast_instance_id(pod_name: Option<String>, hostname: Option<String>) -> Option<String> {
+    [pod_name, hostname]
+        .into_iter()
+        .flatten()
+        .map(|id| id.trim().to_string())
+        .find(|id| !id.is_empty())
+}
+
 // ---------------------------------------------------------------------------
 // 测试
 // ---------------------------------------------------------------------------
@@ -350,10 +427,25 @@ mod tests {
     fn test_topic_constants() {
         assert_eq!(topics::ENGAGEMENT_EVENTS, "badge.engagement.events");
         assert_eq!(topics::TRANSACTION_EVENTS, "badge.transaction.events");
+        assert_eq!(topics::LIFECYCLE_EVENTS, "badge.lifecycle.events");
         assert_eq!(topics::BADGE_NOTIFICATIONS, "badge.notifications");
         assert_eq!(topics::DEAD_LETTER_QUEUE, "badge.dlq");
     }
 
+    #[test]
+    fn test_broadcast_instance_id() {
+        assert_eq!(
+            broadcast_instance_id(Some("badge-svc-7d9f".into()), Some("host-1".into())),
+            Some("badge-svc-7d9f".to_string())
+        );
+        // 空白的 POD_NAME 回退到主机名
+        assert_eq!(
+            broadcast_instance_id(Some(" ".into()), Some("host-1".into())),
+            Some("host-1".to_string())
+        );
+        assert_eq!(broadcast_instance_id(None, None), None);
+    }
+
     #[test]
     fn test_consumer_message_creation() {
         let msg = ConsumerMessage {
@@ -0,0 +1,696 @@
+//! 用户徽章排行榜
+//!
+//! 排行榜基于 Redis 有序集合增量维护，徽章发放、取消、过期时调用
+//! [`LeaderboardStore::record`] 更新分数，查询时直接读取有序集合：
+//!
+//! - **徽章数榜**：用户持有的有效徽章种类数
+//! - **积分榜**：按徽章积分（`badges.score`）加权的持有数量
+//! - **系列完成榜**：用户在某系列中持有的徽章种类数，查询时换算为完成率
+//! - **分类榜**：用户在某分类下持有的徽章种类数
+//!
+//! 除系列完成榜只有总榜外，其余榜单同时维护总榜、周榜、月榜；
+//! 周榜和月榜记录窗口内的净变化，按 UTC 自然周（ISO 周）和自然月切分，
+//! 过期后由 Redis 自动清理。分数降到 0 及以下的用户从榜单移除。
+//!
+//! 积分榜按变化发生时的徽章积分累积，已有用户持有的徽章不允许再修改积分，
+//! 否则扣减时的增量与发放时不一致。
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Datelike, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use tracing::{info, warn};
+
+use crate::cache::Cache;
+use crate::error::{BadgeError, Result};
+
+/// 排行榜键前缀
+const KEY_PREFIX: &str = "leaderboard";
+
+/// 周榜保留时长，覆盖当前周并留出查询上周的余量
+const WEEKLY_TTL: Duration = Duration::from_secs(5 * 7 * 24 * 3600);
+
+/// 月榜保留时长
+const MONTHLY_TTL: Duration = Duration::from_secs(93 * 24 * 3600);
+
+/// 未指定条数时默认返回的条数
+pub const DEFAULT_LEADERBOARD_LIMIT: usize = 10;
+
+/// 单次查询的最大条数
+pub const MAX_LEADERBOARD_LIMIT: usize = 100;
+
+/// 排行榜类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type", content = "scopeId")]
+pub enum LeaderboardKind {
+    /// 有效徽章种类数
+    Badges,
+    /// 徽章积分
+    Score,
+    /// 指定系列的完成度
+    Series(i64),
+    /// 指定分类的徽章种类数
+    Category(i64),
+}
+
+impl LeaderboardKind {
+    /// 按榜单名称和范围 ID 解析，系列榜和分类榜必须指定范围
+    pub fn parse(board: &str, scope_id: Option<i64>) -> Result<Self> {
+        let scope = |name: &str| {
+            scope_id
+                .filter(|id| *id > 0)
+                .ok_or_else(|| BadgeError::Validation(format!("{}排行榜需要指定范围 ID", name)))
+        };
+        match board {
+            "badges" => Ok(Self::Badges),
+            "score" => Ok(Self::Score),
+            "series" => Ok(Self::Series(scope("系列")?)),
+            "category" => Ok(Self::Category(scope("分类")?)),
+            other => Err(BadgeError::Validation(format!(
+                "不支持的排行榜类型: {}",
+                other
+            ))),
+        }
+    }
+
+    /// 榜单名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Badges => "badges",
+            Self::Score => "score",
+            Self::Series(_) => "series",
+            Self::Category(_) => "category",
+        }
+    }
+
+    /// 范围 ID（系列或分类）
+    pub fn scope_id(&self) -> Option<i64> {
+        match self {
+            Self::Series(id) | Self::Category(id) => Some(*id),
+            _ => None,
+        }
+    }
+
+    /// 是否维护周榜和月榜
+    ///
+    /// 系列完成度是累计指标，按时间窗口统计没有意义。
+    pub fn supports_window(&self) -> bool {
+        !matches!(self, Self::Series(_))
+    }
+
+    fn key_base(&self) -> String {
+        match self.scope_id() {
+            Some(id) => format!("{}:{}:{}", KEY_PREFIX, self.name(), id),
+            None => format!("{}:{}", KEY_PREFIX, self.name()),
+        }
+    }
+}
+
+/// 排行榜时间窗口
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LeaderboardWindow {
+    #[default]
+    AllTime,
+    Weekly,
+    Monthly,
+}
+
+impl LeaderboardWindow {
+    pub const ALL: [LeaderboardWindow; 3] = [Self::AllTime, Self::Weekly, Self::Monthly];
+
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "" | "all" | "all_time" => Ok(Self::AllTime),
+            "weekly" | "week" => Ok(Self::Weekly),
+            "monthly" | "month" => Ok(Self::Monthly),
+            other => Err(BadgeError::Validation(format!(
+                "不支持的排行榜窗口: {}",
+                other
+            ))),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::AllTime => "all_time",
+            Self::Weekly => "weekly",
+            Self::Monthly => "monthly",
+        }
+    }
+
+    /// 窗口在键中的后缀，周榜使用 ISO 周，月榜使用自然月
+    fn key_suffix(&self, now: DateTime<Utc>) -> String {
+        match self {
+            Self::AllTime => "all".to_string(),
+            Self::Weekly => {
+                let week = now.iso_week();
+                format!("w:{}-{:02}", week.year(), week.week())
+            }
+            Self::Monthly => format!("m:{}-{:02}", now.year(), now.month()),
+        }
+    }
+
+    fn ttl(&self) -> Option<Duration> {
+        match self {
+            Self::AllTime => None,
+            Self::Weekly => Some(WEEKLY_TTL),
+            Self::Monthly => Some(MONTHLY_TTL),
+        }
+    }
+}
+
+impl fmt::Display for LeaderboardWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 排行榜 Redis 键
+pub fn leaderboard_key(
+    kind: LeaderboardKind,
+    window: LeaderboardWindow,
+    now: DateTime<Utc>,
+) -> String {
+    format!("{}:{}", kind.key_base(), window.key_suffix(now))
+}
+
+/// 一次徽章持有变化
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BadgeChange {
+    pub user_id: String,
+    pub badge_id: i64,
+    /// 持有数量变化，发放为正，取消/过期为负
+    pub quantity_delta: i32,
+    /// 持有种类变化：首次获得为 1，完全失去为 -1，其余为 0
+    pub distinct_delta: i32,
+}
+
+impl BadgeChange {
+    /// 按变化前后的有效持有数量构造
+    pub fn new(user_id: impl Into<String>, badge_id: i64, before: i32, after: i32) -> Self {
+        let before = before.max(0);
+        let after = after.max(0);
+        Self {
+            user_id: user_id.into(),
+            badge_id,
+            quantity_delta: after - before,
+            distinct_delta: i32::from(after > 0) - i32::from(before > 0),
+        }
+    }
+
+    /// 发放：`new_quantity` 为发放后的持有数量
+    pub fn granted(
+        user_id: impl Into<String>,
+        badge_id: i64,
+        quantity: i32,
+        new_quantity: i32,
+    ) -> Self {
+        Self::new(user_id, badge_id, new_quantity - quantity, new_quantity)
+    }
+
+    /// 取消或过期：`remaining` 为扣减后的剩余数量
+    pub fn removed(
+        user_id: impl Into<String>,
+        badge_id: i64,
+        quantity: i32,
+        remaining: i32,
+    ) -> Self {
+        Self::new(user_id, badge_id, remaining + quantity, remaining)
+    }
+
+    /// 各榜单的分数增量
+    fn deltas(&self, meta: &BadgeMeta) -> Vec<(LeaderboardKind, f64)> {
+        let distinct = f64::from(self.distinct_delta);
+        let mut deltas = vec![(
+            LeaderboardKind::Score,
+            f64::from(self.quantity_delta) * f64::from(meta.score),
+        )];
+        if self.distinct_delta != 0 {
+            deltas.push((LeaderboardKind::Badges, distinct));
+            deltas.push((LeaderboardKind::Series(meta.series_id), distinct));
+            deltas.push((LeaderboardKind::Category(meta.category_id), distinct));
+        }
+        deltas.retain(|(_, delta)| *delta != 0.0);
+        deltas
+    }
+}
+
+/// 排行榜条目
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaderboardEntry {
+    /// 名次，从 1 开始
+    pub rank: u64,
+    pub user_id: String,
+    pub score: f64,
+    /// 系列完成率（0-100），仅系列完成榜有值
+    pub completion_rate: Option<f64>,
+}
+
+/// 排行榜查询结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Leaderboard {
+    pub kind: LeaderboardKind,
+    pub window: LeaderboardWindow,
+    pub entries: Vec<LeaderboardEntry>,
+    /// 上榜用户总数
+    pub total: u64,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct BadgeMeta {
+    series_id: i64,
+    category_id: i64,
+    score: i32,
+}
+
+#[derive(sqlx::FromRow)]
+struct RebuildRow {
+    user_id: String,
+    series_id: i64,
+    category_id: i64,
+    distinct_count: i64,
+    score: i64,
+}
+
+/// 原子更新一次持有变化涉及的全部榜单
+///
+/// KEYS: 各榜单有序集合
+/// ARGV: 1 用户 ID，之后每个键依次为分数增量和 TTL 秒（0 表示不过期）
+///
+/// 增量、分数归零移除和续期在同一脚本内完成，并发变化不会在两步之间穿插，
+/// 也不会留下未设置过期时间的窗口榜。
+const RECORD_SCRIPT: &str = r#"
+for i, key in ipairs(KEYS) do
+  local score = tonumber(redis.call('ZINCRBY', key, ARGV[i * 2], ARGV[1]))
+  if score <= 0 then
+    redis.call('ZREM', key, ARGV[1])
+  end
+  local ttl = tonumber(ARGV[i * 2 + 1])
+  if ttl > 0 then
+    redis.call('EXPIRE', key, ttl)
+  end
+end
+return #KEYS
+"#;
+
+/// 一次持有变化需要写入的榜单键、分数增量和过期时间
+fn score_updates(
+    change: &BadgeChange,
+    meta: &BadgeMeta,
+    now: DateTime<Utc>,
+) -> Vec<(String, f64, Option<Duration>)> {
+    let mut updates = Vec::new();
+    for (kind, delta) in change.deltas(meta) {
+        for window in LeaderboardWindow::ALL {
+            if window != LeaderboardWindow::AllTime && !kind.supports_window() {
+                continue;
+            }
+            updates.push((leaderboard_key(kind, window, now), delta, window.ttl()));
+        }
+    }
+    updates
+}
+
+/// 排行榜存储
+pub struct LeaderboardStore {
+    cache: Arc<Cache>,
+    pool: PgPool,
+}
+
+impl LeaderboardStore {
+    pub fn new(cache: Arc<Cache>, pool: PgPool) -> Self {
+        Self { cache, pool }
+    }
+
+    /// 记录一次徽章持有变化，更新所有相关榜单
+    pub async fn record(&self, change: &BadgeChange) -> Result<()> {
+        self.record_at(change, Utc::now()).await
+    }
+
+    async fn record_at(&self, change: &BadgeChange, now: DateTime<Utc>) -> Result<()> {
+        let meta = self.badge_meta(change.badge_id).await?;
+        let updates = score_updates(change, &meta, now);
+        if updates.is_empty() {
+            return Ok(());
+        }
+
+        let mut keys = Vec::with_capacity(updates.len());
+        let mut args = Vec::with_capacity(updates.len() * 2 + 1);
+        args.push(change.user_id.clone());
+        for (key, delta, ttl) in updates {
+            keys.push(key);
+            args.push(delta.to_string());
+            args.push(ttl.map_or(0, |ttl| ttl.as_secs()).to_string());
+        }
+        self.cache.eval_script(RECORD_SCRIPT, &keys, &args).await?;
+        Ok(())
+    }
+
+    /// 查询榜单前 `limit` 名，`limit` 为 0 时取默认条数
+    pub async fn top(
+        &self,
+        kind: LeaderboardKind,
+        window: LeaderboardWindow,
+        limit: usize,
+    ) -> Result<Leaderboard> {
+        let window = effective_window(kind, window);
+        let key = leaderboard_key(kind, window, Utc::now());
+        let limit = match limit {
+            0 => DEFAULT_LEADERBOARD_LIMIT,
+            n => n.min(MAX_LEADERBOARD_LIMIT),
+        };
+
+        let members = self
+            .cache
+            .zrevrange_with_scores(&key, 0, limit as isize - 1)
+            .await?;
+        let total = self.cache.zcard(&key).await?;
+        let series_size = self.series_size(kind).await?;
+
+        let entries = members
+            .into_iter()
+            .enumerate()
+            .map(|(index, (user_id, score))| LeaderboardEntry {
+                rank: index as u64 + 1,
+                user_id,
+                score,
+                completion_rate: completion_rate(score, series_size),
+            })
+            .collect();
+
+        Ok(Leaderboard {
+            kind,
+            window,
+            entries,
+            total,
+        })
+    }
+
+    /// 查询用户在榜单中的名次，未上榜时返回 None
+    pub async fn rank(
+        &self,
+        kind: LeaderboardKind,
+        window: LeaderboardWindow,
+        user_id: &str,
+    ) -> Result<Option<LeaderboardEntry>> {
+        let window = effective_window(kind, window);
+        let key = leaderboard_key(kind, window, Utc::now());
+
+        let Some(rank) = self.cache.zrevrank(&key, user_id).await? else {
+            return Ok(None);
+        };
+        let score = self.cache.zscore(&key, user_id).await?.unwrap_or_default();
+        let series_size = self.series_size(kind).await?;
+
+        Ok(Some(LeaderboardEntry {
+            rank: rank + 1,
+            user_id: user_id.to_string(),
+            score,
+            completion_rate: completion_rate(score, series_size),
+        }))
+    }
+
+    /// 总榜是否已初始化
+    pub async fn is_initialized(&self) -> Result<bool> {
+        let key = leaderboard_key(
+            LeaderboardKind::Badges,
+            LeaderboardWindow::AllTime,
+            Utc::now(),
+        );
+        self.cache.exists(&key).await
+    }
+
+    /// 按 `user_badges` 全量重建总榜
+    ///
+    /// 先写入临时键再整体替换，重建期间查询仍能读到旧数据。
+    /// 周榜和月榜只能增量累积，不参与重建。
+    pub async fn rebuild_all_time(&self) -> Result<()> {
+        let rows = sqlx::query_as::<_, RebuildRow>(
+            r#"
+            SELECT ub.user_id, b.series_id, s.category_id,
+                   COUNT(*) AS distinct_count,
+                   SUM(ub.quantity::BIGINT * b.score)::BIGINT AS score
+            FROM user_badges ub
+            JOIN badges b ON b.id = ub.badge_id
+            JOIN badge_series s ON s.id = b.series_id
+            WHERE ub.status = 'active' AND ub.quantity > 0
+            GROUP BY ub.user_id, b.series_id, s.category_id
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut boards: HashMap<LeaderboardKind, HashMap<String, f64>> = HashMap::new();
+        for row in &rows {
+            let distinct = row.distinct_count as f64;
+            let mut add = |kind: LeaderboardKind, value: f64| {
+                *boards
+                    .entry(kind)
+                    .or_default()
+                    .entry(row.user_id.clone())
+                    .or_default() += value;
+            };
+            add(LeaderboardKind::Badges, distinct);
+            add(LeaderboardKind::Score, row.score as f64);
+            add(LeaderboardKind::Series(row.series_id), distinct);
+            add(LeaderboardKind::Category(row.category_id), distinct);
+        }
+
+        let now = Utc::now();
+        for (kind, scores) in &boards {
+            let key = leaderboard_key(*kind, LeaderboardWindow::AllTime, now);
+            let tmp_key = format!("{}:rebuild", key);
+            let items: Vec<(f64, String)> = scores
+                .iter()
+                .filter(|(_, score)| **score > 0.0)
+                .map(|(user_id, score)| (*score, user_id.clone()))
+                .collect();
+            if items.is_empty() {
+                self.cache.delete(&key).await?;
+                continue;
+            }
+            self.cache.delete(&tmp_key).await?;
+            for chunk in items.chunks(1000) {
+                self.cache.zadd_multiple(&tmp_key, chunk).await?;
+            }
+            self.cache.rename(&tmp_key, &key).await?;
+        }
+
+        info!(
+            boards = boards.len(),
+            rows = rows.len(),
+            "Leaderboards rebuilt from user_badges"
+        );
+        Ok(())
+    }
+
+    async fn badge_meta(&self, badge_id: i64) -> Result<BadgeMeta> {
+        sqlx::query_as::<_, BadgeMeta>(
+            r#"
+            SELECT b.series_id, s.category_id, b.score
+            FROM badges b
+            JOIN badge_series s ON s.id = b.series_id
+            WHERE b.id = $1
+            "#,
+        )
+        .bind(badge_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| BadgeError::NotFound {
+            entity: "badge".to_string(),
+            id: badge_id.to_string(),
+        })
+    }
+
+    /// 系列内有效徽章数，用于计算完成率；非系列榜返回 None
+    async fn series_size(&self, kind: LeaderboardKind) -> Result<Option<i64>> {
+        let LeaderboardKind::Series(series_id) = kind else {
+            return Ok(None);
+        };
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM badges WHERE series_id = $1 AND status = 'active'",
+        )
+        .bind(series_id)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(Some(count))
+    }
+}
+
+/// 系列完成榜只有总榜，请求其他窗口时按总榜查询
+fn effective_window(kind: LeaderboardKind, window: LeaderboardWindow) -> LeaderboardWindow {
+    if kind.supports_window() {
+        window
+    } else {
+        if window != LeaderboardWindow::AllTime {
+            warn!(board = kind.name(), window = %window, "排行榜不支持时间窗口，按总榜查询");
+        }
+        LeaderboardWindow::AllTime
+    }
+}
+
+fn completion_rate(owned: f64, series_size: Option<i64>) -> Option<f64> {
+    let size = series_size?;
+    if size <= 0 {
+        return Some(0.0);
+    }
+    Some((owned / size as f64 * 100.0).min(100.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(y: i32, m: u32, d: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_leaderboard_keys() {
+        let now = at(2025, 3, 5);
+        assert_eq!(
+            leaderboard_key(LeaderboardKind::Badges, LeaderboardWindow::AllTime, now),
+            "leaderboard:badges:all"
+        );
+        assert_eq!(
+            leaderboard_key(LeaderboardKind::Score, LeaderboardWindow::Weekly, now),
+            "leaderboard:score:w:2025-10"
+        );
+        assert_eq!(
+            leaderboard_key(
+                LeaderboardKind::Category(3),
+                LeaderboardWindow::Monthly,
+                now
+            ),
+            "leaderboard:category:3:m:2025-03"
+        );
+        assert_eq!(
+            leaderboard_key(LeaderboardKind::Series(7), LeaderboardWindow::AllTime, now),
+            "leaderboard:series:7:all"
+        );
+    }
+
+    #[test]
+    fn test_weekly_key_uses_iso_week_year() {
+        // 2024-12-30 属于 2025 年第 1 周
+        assert_eq!(
+            leaderboard_key(
+                LeaderboardKind::Badges,
+                LeaderboardWindow::Weekly,
+                at(2024, 12, 30)
+            ),
+            "leaderboard:badges:w:2025-01"
+        );
+    }
+
+    #[test]
+    fn test_parse_kind_and_window() {
+        assert_eq!(
+            LeaderboardKind::parse("badges", None).unwrap(),
+            LeaderboardKind::Badges
+        );
+        assert_eq!(
+            LeaderboardKind::parse("series", Some(2)).unwrap(),
+            LeaderboardKind::Series(2)
+        );
+        assert!(LeaderboardKind::parse("category", None).is_err());
+        assert!(LeaderboardKind::parse("unknown", None).is_err());
+
+        assert_eq!(
+            LeaderboardWindow::parse("weekly").unwrap(),
+            LeaderboardWindow::Weekly
+        );
+        assert_eq!(
+            LeaderboardWindow::parse("").unwrap(),
+            LeaderboardWindow::AllTime
+        );
+        assert!(LeaderboardWindow::parse("daily").is_err());
+    }
+
+    #[test]
+    fn test_change_deltas() {
+        let meta = BadgeMeta {
+            series_id: 7,
+            category_id: 3,
+            score: 10,
+        };
+
+        // 首次获得 2 个：种类 +1，积分 +20
+        let deltas = BadgeChange::granted("u1", 1, 2, 2).deltas(&meta);
+        assert!(deltas.contains(&(LeaderboardKind::Score, 20.0)));
+        assert!(deltas.contains(&(LeaderboardKind::Badges, 1.0)));
+        assert!(deltas.contains(&(LeaderboardKind::Series(7), 1.0)));
+        assert!(deltas.contains(&(LeaderboardKind::Category(3), 1.0)));
+
+        // 已持有时再获得：只影响积分
+        let deltas = BadgeChange::granted("u1", 1, 1, 3).deltas(&meta);
+        assert_eq!(deltas, vec![(LeaderboardKind::Score, 10.0)]);
+
+        // 全部扣除：种类 -1
+        let deltas = BadgeChange::removed("u1", 1, 3, 0).deltas(&meta);
+        assert!(deltas.contains(&(LeaderboardKind::Score, -30.0)));
+        assert!(deltas.contains(&(LeaderboardKind::Badges, -1.0)));
+
+        // 过期徽章重新激活：原有数量一并计入
+        let change = BadgeChange::new("u1", 1, 0, 4);
+        assert_eq!(change.quantity_delta, 4);
+        assert_eq!(change.distinct_delta, 1);
+    }
+
+    #[test]
+    fn test_score_updates() {
+        let meta = BadgeMeta {
+            series_id: 7,
+            category_id: 3,
+            score: 10,
+        };
+        let now = at(2025, 3, 5);
+
+        // 积分、徽章数、分类榜各 3 个窗口，系列榜只有总榜
+        let updates = score_updates(&BadgeChange::granted("u1", 1, 1, 1), &meta, now);
+        assert_eq!(updates.len(), 10);
+        assert!(updates.contains(&("leaderboard:series:7:all".to_string(), 1.0, None)));
+        assert!(updates.contains(&(
+            "leaderboard:score:w:2025-10".to_string(),
+            10.0,
+            Some(WEEKLY_TTL)
+        )));
+        assert!(
+            !updates
+                .iter()
+                .any(|(key, _, _)| key.starts_with("leaderboard:series:7:w"))
+        );
+
+        // 只影响积分
+        let updates = score_updates(&BadgeChange::granted("u1", 1, 1, 2), &meta, now);
+        assert_eq!(updates.len(), 3);
+    }
+
+    #[test]
+    fn test_series_board_is_all_time_only() {
+        assert_eq!(
+            effective_window(LeaderboardKind::Series(1), LeaderboardWindow::Weekly),
+            LeaderboardWindow::AllTime
+        );
+        assert_eq!(
+            effective_window(LeaderboardKind::Badges, LeaderboardWindow::Weekly),
+            LeaderboardWindow::Weekly
+        );
+    }
+
+    #[test]
+    fn test_completion_rate() {
+        assert_eq!(completion_rate(3.0, None), None);
+        assert_eq!(completion_rate(3.0, Some(4)), Some(75.0));
+        assert_eq!(completion_rate(5.0, Some(4)), Some(100.0));
+        assert_eq!(completion_rate(1.0, Some(0)), Some(0.0));
+    }
+}
@@ -9,11 +9,17 @@ pub mod config_watcher;
 pub mod crypto;
 pub mod database;
 pub mod dlq;
+pub mod email;
 pub mod error;
 pub mod events;
 pub mod grpc_tls;
 pub mod kafka;
+pub mod leaderboard;
+pub mod notification_contacts;
+pub mod notification_preferences;
+pub mod notification_templates;
 pub mod observability;
 pub mod retry;
 pub mod rules;
+pub mod signature;
 pub mod test_utils;
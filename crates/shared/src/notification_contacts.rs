@@ -0,0 +1,329 @@
+//! 用户通知联系方式
+//!
+//! 通知生产方只知道用户 ID，真实收件人（邮箱、手机号、微信 openid、推送设备 token）
+//! 在分发前按用户 ID 查询，
+//! 写入通知业务数据中对应的字段，各渠道从这些字段读取收件人。
+//! 通知自带的收件人字段优先，不会被覆盖。
+//!
+//! 联系方式存储在 `user_notification_contacts` 表，新用户由注册事件携带的字段写入，
+//! 存量用户通过管理后台 `/api/admin/users/notification-contacts/batch` 导入。
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use sqlx::PgPool;
+use tracing::warn;
+
+use crate::error::Result;
+use crate::events::NotificationEvent;
+
+/// 通知业务数据中存放收件人邮箱的字段
+pub const EMAIL_DATA_KEY: &str = "email";
+/// 通知业务数据中的手机号字段
+pub const PHONE_DATA_KEY: &str = "phone";
+/// 通知业务数据中的微信 openid 字段
+pub const OPENID_DATA_KEY: &str = "openid";
+/// 通知业务数据中的推送设备 token 字段
+pub const DEVICE_TOKEN_DATA_KEY: &str = "device_token";
+
+/// 用户联系方式
+#[derive(Debug, Clone, Default, PartialEq, Eq, sqlx::FromRow)]
+pub struct UserContacts {
+    pub user_id: String,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub wechat_openid: Option<String>,
+    pub device_token: Option<String>,
+}
+
+impl UserContacts {
+    pub fn new(user_id: impl Into<String>) -> Self {
+        Self {
+            user_id: user_id.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_email(mut self, email: impl Into<String>) -> Self {
+        self.email = Some(email.into());
+        self
+    }
+
+    pub fn with_phone(mut self, phone: impl Into<String>) -> Self {
+        self.phone = Some(phone.into());
+        self
+    }
+
+    pub fn with_wechat_openid(mut self, openid: impl Into<String>) -> Self {
+        self.wechat_openid = Some(openid.into());
+        self
+    }
+
+    pub fn with_device_token(mut self, device_token: impl Into<String>) -> Self {
+        self.device_token = Some(device_token.into());
+        self
+    }
+
+    /// 从业务数据（如注册事件）中读取联系方式，字段名与通知数据一致
+    ///
+    /// 不含任何联系方式时返回 None
+    pub fn from_data(user_id: impl Into<String>, data: &serde_json::Value) -> Option<Self> {
+        let field = |key: &str| {
+            data.get(key)
+                .and_then(|v| v.as_str())
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+        };
+        let contacts = Self {
+            user_id: user_id.into(),
+            email: field(EMAIL_DATA_KEY),
+            phone: field(PHONE_DATA_KEY),
+            wechat_openid: field(OPENID_DATA_KEY),
+            device_token: field(DEVICE_TOKEN_DATA_KEY),
+        };
+        (!contacts.is_empty()).then_some(contacts)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields().is_empty()
+    }
+
+    /// 已设置的联系方式及其在通知数据中的字段名
+    pub fn fields(&self) -> Vec<(&'static str, &str)> {
+        [
+            (EMAIL_DATA_KEY, self.email.as_deref()),
+            (PHONE_DATA_KEY, self.phone.as_deref()),
+            (OPENID_DATA_KEY, self.wechat_openid.as_deref()),
+            (DEVICE_TOKEN_DATA_KEY, self.device_token.as_deref()),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| value.map(|v| (key, v)))
+        .collect()
+    }
+
+    /// 将联系方式写入通知数据，通知已携带的字段保持不变
+    pub fn fill(&self, event: &mut NotificationEvent) {
+        if !event.data.is_object() {
+            event.data = serde_json::Value::Object(serde_json::Map::new());
+        }
+        if let Some(data) = event.data.as_object_mut() {
+            for (key, value) in self.fields() {
+                data.entry(key)
+                    .or_insert_with(|| serde_json::Value::String(value.to_string()));
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// 联系方式存储
+// ---------------------------------------------------------------------------
+
+/// 用户联系方式存储
+#[async_trait]
+pub trait ContactStore: Send + Sync {
+    /// 获取用户联系方式，未登记时返回 None
+    async fn get(&self, user_id: &str) -> Result<Option<UserContacts>>;
+
+    /// 登记用户联系方式，未提供的字段保留原值
+    async fn upsert(&self, contacts: &UserContacts) -> Result<()>;
+}
+
+/// 查询联系方式并写入通知数据
+///
+/// 查询失败时原样返回，由各渠道按收件人缺失处理，联系方式服务故障不应阻断其他渠道。
+pub async fn resolve_recipients(store: &dyn ContactStore, event: &mut NotificationEvent) {
+    match store.get(&event.user_id).await {
+        Ok(Some(contacts)) => contacts.fill(event),
+        Ok(None) => {}
+        Err(e) => warn!(
+            user_id = %event.user_id,
+            error = %e,
+            "读取用户联系方式失败，按通知自带的收件人发送"
+        ),
+    }
+}
+
+/// 基于 PostgreSQL 的联系方式存储
+pub struct PgContactStore {
+    pool: PgPool,
+}
+
+impl PgContactStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ContactStore for PgContactStore {
+    async fn get(&self, user_id: &str) -> Result<Option<UserContacts>> {
+        let contacts = sqlx::query_as::<_, UserContacts>(
+            r#"
+            SELECT user_id, email, phone, wechat_openid, device_token
+            FROM user_notification_contacts
+            WHERE user_id = $1
+            "#,
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(contacts)
+    }
+
+    async fn upsert(&self, contacts: &UserContacts) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO user_notification_contacts
+                (user_id, email, phone, wechat_openid, device_token)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (user_id) DO UPDATE SET
+                email = COALESCE(EXCLUDED.email, user_notification_contacts.email),
+                phone = COALESCE(EXCLUDED.phone, user_notification_contacts.phone),
+                wechat_openid = COALESCE(
+                    EXCLUDED.wechat_openid, user_notification_contacts.wechat_openid
+                ),
+                device_token = COALESCE(
+                    EXCLUDED.device_token, user_notification_contacts.device_token
+                )
+            "#,
+        )
+        .bind(&contacts.user_id)
+        .bind(&contacts.email)
+        .bind(&contacts.phone)
+        .bind(&contacts.wechat_openid)
+        .bind(&contacts.device_token)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// 内存联系方式存储，用于测试和未接入数据库的场景
+#[derive(Default)]
+pub struct InMemoryContactStore {
+    contacts: Mutex<HashMap<String, UserContacts>>,
+}
+
+impl InMemoryContactStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ContactStore for InMemoryContactStore {
+    async fn get(&self, user_id: &str) -> Result<Option<UserContacts>> {
+        Ok(self.contacts.lock().unwrap().get(user_id).cloned())
+    }
+
+    async fn upsert(&self, contacts: &UserContacts) -> Result<()> {
+        let mut all = self.contacts.lock().unwrap();
+        let entry = all
+            .entry(contacts.user_id.clone())
+            .or_insert_with(|| UserContacts::new(&contacts.user_id));
+        for (stored, provided) in [
+            (&mut entry.email, &contacts.email),
+            (&mut entry.phone, &contacts.phone),
+            (&mut entry.wechat_openid, &contacts.wechat_openid),
+            (&mut entry.device_token, &contacts.device_token),
+        ] {
+            if provided.is_some() {
+                stored.clone_from(provided);
+            }
+        }
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// 测试
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{NotificationChannel, NotificationType};
+    use chrono::Utc;
+
+    fn event(data: serde_json::Value) -> NotificationEvent {
+        NotificationEvent {
+            notification_id: "notif-1".to_string(),
+            user_id: "user-1".to_string(),
+            notification_type: NotificationType::BadgeGranted,
+            title: "title".to_string(),
+            body: "body".to_string(),
+            data,
+            channels: vec![NotificationChannel::Email],
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_from_data() {
+        let contacts = UserContacts::from_data(
+            "user-1",
+            &serde_json::json!({
+                "email": " a@example.com ",
+                "phone": "13800138000",
+                "openid": "o-user-1",
+                "device_token": "device-1"
+            }),
+        )
+        .unwrap();
+        assert_eq!(contacts.email.as_deref(), Some("a@example.com"));
+        assert_eq!(contacts.phone.as_deref(), Some("13800138000"));
+        assert_eq!(contacts.wechat_openid.as_deref(), Some("o-user-1"));
+        assert_eq!(contacts.device_token.as_deref(), Some("device-1"));
+
+        assert!(UserContacts::from_data("user-1", &serde_json::json!({"email": ""})).is_none());
+        assert!(UserContacts::from_data("user-1", &serde_json::json!({})).is_none());
+    }
+
+    #[test]
+    fn test_fill_keeps_existing_recipient() {
+        let contacts = UserContacts::new("user-1").with_email("stored@example.com");
+
+        let mut empty = event(serde_json::json!({"badge_id": 1}));
+        contacts.fill(&mut empty);
+        assert_eq!(empty.data[EMAIL_DATA_KEY], "stored@example.com");
+        assert_eq!(empty.data["badge_id"], 1);
+
+        let mut explicit = event(serde_json::json!({"email": "explicit@example.com"}));
+        contacts.fill(&mut explicit);
+        assert_eq!(explicit.data[EMAIL_DATA_KEY], "explicit@example.com");
+
+        let mut null_data = event(serde_json::Value::Null);
+        contacts.fill(&mut null_data);
+        assert_eq!(null_data.data[EMAIL_DATA_KEY], "stored@example.com");
+    }
+
+    #[tokio::test]
+    async fn test_resolve_recipients() {
+        let store = InMemoryContactStore::new();
+        store
+            .upsert(&UserContacts::new("user-1").with_email("a@example.com"))
+            .await
+            .unwrap();
+        // 未提供的字段保留原值
+        store
+            .upsert(&UserContacts::new("user-1").with_phone("13800138000"))
+            .await
+            .unwrap();
+
+        let mut notification = event(serde_json::json!({}));
+        resolve_recipients(&store, &mut notification).await;
+        assert_eq!(notification.data[EMAIL_DATA_KEY], "a@example.com");
+        assert_eq!(notification.data[PHONE_DATA_KEY], "13800138000");
+        assert!(notification.data.get(OPENID_DATA_KEY).is_none());
+
+        let mut unknown = event(serde_json::json!({}));
+        unknown.user_id = "user-2".to_string();
+        resolve_recipients(&store, &mut unknown).await;
+        assert!(unknown.data.get(EMAIL_DATA_KEY).is_none());
+    }
+}
@@ -0,0 +1,684 @@
+//! 用户通知偏好
+//!
+//! 通知分发前读取用户偏好，决定实际投递的渠道和投递时间：
+//!
+//! - **渠道偏好**：按通知类型配置启用的渠道，未配置的类型沿用通知自带的渠道
+//! - **免打扰时段**：按用户所在时区判断，时段内的非紧急通知延迟到时段结束后投递
+//! - **语言**：随通知透传给模板渲染
+//!
+//! 偏好存储在 `user_notification_preferences` 表，延迟投递的通知存储在
+//! `deferred_notifications` 表，由 notification-worker 到期后投递。
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Days, NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use crate::error::{BadgeError, Result};
+use crate::events::{NotificationChannel, NotificationEvent, NotificationType};
+
+/// 默认时区
+pub const DEFAULT_TIMEZONE: &str = "Asia/Shanghai";
+
+/// 默认通知语言
+pub const DEFAULT_LOCALE: &str = "zh-CN";
+
+/// 通知数据中透传用户语言的字段
+pub const LOCALE_DATA_KEY: &str = "locale";
+
+// ---------------------------------------------------------------------------
+// 偏好模型
+// ---------------------------------------------------------------------------
+
+/// 免打扰时段
+///
+/// 起止时间为用户本地时间，结束时间早于开始时间表示跨越午夜（如 22:00-08:00）。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    /// IANA 时区，如 `Asia/Shanghai`
+    pub timezone: String,
+}
+
+impl QuietHours {
+    /// 创建免打扰时段，校验时区和起止时间
+    pub fn new(start: NaiveTime, end: NaiveTime, timezone: impl Into<String>) -> Result<Self> {
+        let timezone = timezone.into();
+        Tz::from_str(&timezone)
+            .map_err(|_| BadgeError::Validation(format!("无效的时区: {}", timezone)))?;
+        if start == end {
+            return Err(BadgeError::Validation(
+                "免打扰开始时间与结束时间不能相同".to_string(),
+            ));
+        }
+        Ok(Self {
+            start,
+            end,
+            timezone,
+        })
+    }
+
+    /// 时区无法解析时按默认时区处理，避免脏数据导致通知无法投递
+    fn tz(&self) -> Tz {
+        Tz::from_str(&self.timezone).unwrap_or(chrono_tz::Asia::Shanghai)
+    }
+
+    fn contains(&self, time: NaiveTime) -> bool {
+        if self.start < self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+
+    /// 若 `now` 处于免打扰时段，返回时段结束的时刻
+    pub fn ends_at(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let tz = self.tz();
+        let local = now.with_timezone(&tz);
+        let time = local.time();
+        if !self.contains(time) {
+            return None;
+        }
+
+        // 当前时间晚于结束时间说明时段跨越午夜，结束于次日
+        let date = if time < self.end {
+            local.date_naive()
+        } else {
+            local.date_naive().checked_add_days(Days::new(1))?
+        };
+        Some(local_to_utc(&tz, date, self.end))
+    }
+}
+
+/// 本地时间转为 UTC；夏令时切换导致本地时间不存在时顺延一小时
+fn local_to_utc(tz: &Tz, date: NaiveDate, time: NaiveTime) -> DateTime<Utc> {
+    let naive = date.and_time(time);
+    tz.from_local_datetime(&naive)
+        .earliest()
+        .or_else(|| {
+            tz.from_local_datetime(&(naive + chrono::Duration::hours(1)))
+                .earliest()
+        })
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|| Utc.from_utc_datetime(&naive))
+}
+
+/// 用户通知偏好
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationPreferences {
+    pub user_id: String,
+    /// 各通知类型启用的渠道，未配置的类型不做限制
+    #[serde(default)]
+    pub channels: HashMap<NotificationType, Vec<NotificationChannel>>,
+    #[serde(default)]
+    pub quiet_hours: Option<QuietHours>,
+    #[serde(default = "default_locale")]
+    pub locale: String,
+}
+
+fn default_locale() -> String {
+    DEFAULT_LOCALE.to_string()
+}
+
+impl NotificationPreferences {
+    /// 创建不做任何限制的默认偏好
+    pub fn new(user_id: impl Into<String>) -> Self {
+        Self {
+            user_id: user_id.into(),
+            channels: HashMap::new(),
+            quiet_hours: None,
+            locale: default_locale(),
+        }
+    }
+
+    /// 设置某通知类型启用的渠道，空列表表示关闭该类型的所有通知
+    pub fn with_channels(
+        mut self,
+        notification_type: NotificationType,
+        channels: Vec<NotificationChannel>,
+    ) -> Self {
+        self.channels.insert(notification_type, channels);
+        self
+    }
+
+    pub fn with_quiet_hours(mut self, quiet_hours: QuietHours) -> Self {
+        self.quiet_hours = Some(quiet_hours);
+        self
+    }
+
+    pub fn with_locale(mut self, locale: impl Into<String>) -> Self {
+        self.locale = locale.into();
+        self
+    }
+
+    /// 从通知请求的渠道中筛选出用户启用的渠道，保持原有顺序
+    pub fn allowed_channels(
+        &self,
+        notification_type: &NotificationType,
+        requested: &[NotificationChannel],
+    ) -> Vec<NotificationChannel> {
+        match self.channels.get(notification_type) {
+            Some(enabled) => requested
+                .iter()
+                .filter(|c| enabled.contains(c))
+                .cloned()
+                .collect(),
+            None => requested.to_vec(),
+        }
+    }
+
+    /// 非紧急通知处于免打扰时段时，返回应延迟到的时刻
+    pub fn deferred_until(
+        &self,
+        notification_type: &NotificationType,
+        now: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        if notification_type.is_urgent() {
+            return None;
+        }
+        self.quiet_hours.as_ref()?.ends_at(now)
+    }
+}
+
+/// 偏好应用结果
+#[derive(Debug, Clone)]
+pub enum PreferenceDecision {
+    /// 立即投递到筛选后的渠道
+    Deliver(NotificationEvent),
+    /// 免打扰时段内，延迟到指定时刻投递
+    Defer(NotificationEvent, DateTime<Utc>),
+    /// 用户关闭了该通知的所有渠道
+    Suppress,
+}
+
+/// 按用户偏好处理通知：筛选渠道、判断免打扰并透传语言
+///
+/// 未设置偏好的用户按默认偏好处理，即原样投递。
+pub fn apply_preferences(
+    preferences: Option<&NotificationPreferences>,
+    mut event: NotificationEvent,
+    now: DateTime<Utc>,
+) -> PreferenceDecision {
+    let Some(preferences) = preferences else {
+        return PreferenceDecision::Deliver(event);
+    };
+
+    event.channels = preferences.allowed_channels(&event.notification_type, &event.channels);
+    if event.channels.is_empty() {
+        return PreferenceDecision::Suppress;
+    }
+
+    if let Some(data) = event.data.as_object_mut() {
+        data.entry(LOCALE_DATA_KEY)
+            .or_insert_with(|| serde_json::Value::String(preferences.locale.clone()));
+    }
+
+    match preferences.deferred_until(&event.notification_type, now) {
+        Some(until) => PreferenceDecision::Defer(event, until),
+        None => PreferenceDecision::Deliver(event),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// 偏好存储
+// ---------------------------------------------------------------------------
+
+/// 通知偏好存储
+#[async_trait]
+pub trait PreferenceStore: Send + Sync {
+    /// 获取用户偏好，未设置时返回 None
+    async fn get(&self, user_id: &str) -> Result<Option<NotificationPreferences>>;
+
+    /// 保存用户偏好（覆盖已有设置）
+    async fn save(&self, preferences: &NotificationPreferences) -> Result<()>;
+}
+
+/// 基于 PostgreSQL 的偏好存储
+pub struct PgPreferenceStore {
+    pool: PgPool,
+}
+
+impl PgPreferenceStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct PreferenceRow {
+    user_id: String,
+    channels: serde_json::Value,
+    quiet_start: Option<NaiveTime>,
+    quiet_end: Option<NaiveTime>,
+    timezone: String,
+    locale: String,
+}
+
+impl From<PreferenceRow> for NotificationPreferences {
+    fn from(row: PreferenceRow) -> Self {
+        let quiet_hours = match (row.quiet_start, row.quiet_end) {
+            (Some(start), Some(end)) if start != end => Some(QuietHours {
+                start,
+                end,
+                timezone: row.timezone,
+            }),
+            _ => None,
+        };
+        Self {
+            user_id: row.user_id,
+            // 渠道配置格式错误时视为未配置，不阻断通知
+            channels: serde_json::from_value(row.channels).unwrap_or_default(),
+            quiet_hours,
+            locale: row.locale,
+        }
+    }
+}
+
+#[async_trait]
+impl PreferenceStore for PgPreferenceStore {
+    async fn get(&self, user_id: &str) -> Result<Option<NotificationPreferences>> {
+        let row = sqlx::query_as::<_, PreferenceRow>(
+            r#"
+            SELECT user_id, channels, quiet_start, quiet_end, timezone, locale
+            FROM user_notification_preferences
+            WHERE user_id = $1
+            "#,
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(Into::into))
+    }
+
+    async fn save(&self, preferences: &NotificationPreferences) -> Result<()> {
+        let channels = serde_json::to_value(&preferences.channels)
+            .map_err(|e| BadgeError::Internal(format!("序列化渠道偏好失败: {}", e)))?;
+        let quiet = preferences.quiet_hours.as_ref();
+
+        sqlx::query(
+            r#"
+            INSERT INTO user_notification_preferences
+                (user_id, channels, quiet_start, quiet_end, timezone, locale)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (user_id) DO UPDATE SET
+                channels = EXCLUDED.channels,
+                quiet_start = EXCLUDED.quiet_start,
+                quiet_end = EXCLUDED.quiet_end,
+                timezone = EXCLUDED.timezone,
+                locale = EXCLUDED.locale
+            "#,
+        )
+        .bind(&preferences.user_id)
+        .bind(channels)
+        .bind(quiet.map(|q| q.start))
+        .bind(quiet.map(|q| q.end))
+        .bind(quiet.map_or(DEFAULT_TIMEZONE, |q| q.timezone.as_str()))
+        .bind(&preferences.locale)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// 内存偏好存储，用于测试和未接入数据库的场景
+#[derive(Default)]
+pub struct InMemoryPreferenceStore {
+    preferences: Mutex<HashMap<String, NotificationPreferences>>,
+}
+
+impl InMemoryPreferenceStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl PreferenceStore for InMemoryPreferenceStore {
+    async fn get(&self, user_id: &str) -> Result<Option<NotificationPreferences>> {
+        Ok(self.preferences.lock().unwrap().get(user_id).cloned())
+    }
+
+    async fn save(&self, preferences: &NotificationPreferences) -> Result<()> {
+        self.preferences
+            .lock()
+            .unwrap()
+            .insert(preferences.user_id.clone(), preferences.clone());
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// 延迟投递队列
+// ---------------------------------------------------------------------------
+
+/// 延迟投递通知存储
+#[async_trait]
+pub trait DeferredNotificationStore: Send + Sync {
+    /// 保存延迟通知，同一通知重复保存时保留首次记录
+    async fn defer(&self, event: &NotificationEvent, deliver_at: DateTime<Utc>) -> Result<()>;
+
+    /// 取出到期的通知（取出即删除），按计划投递时间排序
+    async fn take_due(&self, now: DateTime<Utc>, limit: i64) -> Result<Vec<NotificationEvent>>;
+
+    /// 删除指定通知（已通过其他途径投递）
+    async fn remove(&self, notification_ids: &[String]) -> Result<()>;
+}
+
+/// 基于 PostgreSQL 的延迟投递存储
+///
+/// 取出时使用 `FOR UPDATE SKIP LOCKED`，多个 worker 实例并发轮询不会重复投递。
+pub struct PgDeferredNotificationStore {
+    pool: PgPool,
+}
+
+impl PgDeferredNotificationStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl DeferredNotificationStore for PgDeferredNotificationStore {
+    async fn defer(&self, event: &NotificationEvent, deliver_at: DateTime<Utc>) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO deferred_notifications (notification_id, user_id, payload, deliver_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (notification_id) DO NOTHING
+            "#,
+        )
+        .bind(&event.notification_id)
+        .bind(&event.user_id)
+        .bind(
+            serde_json::to_value(event)
+                .map_err(|e| BadgeError::Internal(format!("序列化延迟通知失败: {}", e)))?,
+        )
+        .bind(deliver_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn take_due(&self, now: DateTime<Utc>, limit: i64) -> Result<Vec<NotificationEvent>> {
+        let payloads: Vec<serde_json::Value> = sqlx::query_scalar(
+            r#"
+            DELETE FROM deferred_notifications
+            WHERE notification_id IN (
+                SELECT notification_id FROM deferred_notifications
+                WHERE deliver_at <= $1
+                ORDER BY deliver_at
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING payload
+            "#,
+        )
+        .bind(now)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        payloads
+            .into_iter()
+            .map(|p| {
+                serde_json::from_value(p)
+                    .map_err(|e| BadgeError::Internal(format!("解析延迟通知失败: {}", e)))
+            })
+            .collect()
+    }
+
+    async fn remove(&self, notification_ids: &[String]) -> Result<()> {
+        sqlx::query("DELETE FROM deferred_notifications WHERE notification_id = ANY($1)")
+            .bind(notification_ids)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// 内存延迟投递存储，用于测试和未接入数据库的场景
+#[derive(Default)]
+pub struct InMemoryDeferredStore {
+    entries: Mutex<Vec<(DateTime<Utc>, NotificationEvent)>>,
+}
+
+impl InMemoryDeferredStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 当前等待投递的通知数量
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[async_trait]
+impl DeferredNotificationStore for InMemoryDeferredStore {
+    async fn defer(&self, event: &NotificationEvent, deliver_at: DateTime<Utc>) -> Result<()> {
+        let mut entries = self.entries.lock().unwrap();
+        if !entries
+            .iter()
+            .any(|(_, e)| e.notification_id == event.notification_id)
+        {
+            entries.push((deliver_at, event.clone()));
+        }
+        Ok(())
+    }
+
+    async fn take_due(&self, now: DateTime<Utc>, limit: i64) -> Result<Vec<NotificationEvent>> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.sort_by_key(|(at, _)| *at);
+        let due = entries
+            .iter()
+            .take_while(|(at, _)| *at <= now)
+            .count()
+            .min(limit.max(0) as usize);
+        Ok(entries.drain(..due).map(|(_, e)| e).collect())
+    }
+
+    async fn remove(&self, notification_ids: &[String]) -> Result<()> {
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|(_, e)| !notification_ids.contains(&e.notification_id));
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// 测试
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn make_event(notification_type: NotificationType) -> NotificationEvent {
+        NotificationEvent {
+            notification_id: "n-1".to_string(),
+            user_id: "user-1".to_string(),
+            notification_type,
+            title: "t".to_string(),
+            body: "b".to_string(),
+            data: serde_json::json!({"badge_id": 1}),
+            channels: vec![NotificationChannel::AppPush, NotificationChannel::Sms],
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_quiet_hours_validation() {
+        assert!(QuietHours::new(time(22, 0), time(8, 0), "Asia/Shanghai").is_ok());
+        assert!(QuietHours::new(time(22, 0), time(8, 0), "Mars/Olympus").is_err());
+        assert!(QuietHours::new(time(8, 0), time(8, 0), "UTC").is_err());
+    }
+
+    #[test]
+    fn test_quiet_hours_across_midnight() {
+        let quiet = QuietHours::new(time(22, 0), time(8, 0), "Asia/Shanghai").unwrap();
+
+        // 上海 23:30，结束于次日 08:00（UTC 00:00）
+        assert_eq!(
+            quiet.ends_at(utc("2025-03-01T15:30:00Z")),
+            Some(utc("2025-03-02T00:00:00Z"))
+        );
+        // 上海 02:00，结束于当日 08:00
+        assert_eq!(
+            quiet.ends_at(utc("2025-03-01T18:00:00Z")),
+            Some(utc("2025-03-02T00:00:00Z"))
+        );
+        // 上海 12:00 不在时段内
+        assert_eq!(quiet.ends_at(utc("2025-03-01T04:00:00Z")), None);
+    }
+
+    #[test]
+    fn test_quiet_hours_same_day() {
+        let quiet = QuietHours::new(time(12, 0), time(14, 0), "UTC").unwrap();
+        assert_eq!(
+            quiet.ends_at(utc("2025-03-01T13:00:00Z")),
+            Some(utc("2025-03-01T14:00:00Z"))
+        );
+        assert_eq!(quiet.ends_at(utc("2025-03-01T14:00:00Z")), None);
+    }
+
+    #[test]
+    fn test_allowed_channels() {
+        let prefs = NotificationPreferences::new("user-1")
+            .with_channels(
+                NotificationType::BadgeGranted,
+                vec![NotificationChannel::Sms],
+            )
+            .with_channels(NotificationType::BadgeRevoked, vec![]);
+        let requested = [NotificationChannel::AppPush, NotificationChannel::Sms];
+
+        assert_eq!(
+            prefs.allowed_channels(&NotificationType::BadgeGranted, &requested),
+            vec![NotificationChannel::Sms]
+        );
+        assert!(
+            prefs
+                .allowed_channels(&NotificationType::BadgeRevoked, &requested)
+                .is_empty()
+        );
+        assert_eq!(
+            prefs.allowed_channels(&NotificationType::BadgeExpiring, &requested),
+            requested.to_vec()
+        );
+    }
+
+    #[test]
+    fn test_apply_preferences() {
+        let now = utc("2025-03-01T15:30:00Z");
+        let prefs = NotificationPreferences::new("user-1")
+            .with_channels(NotificationType::BadgeRevoked, vec![])
+            .with_quiet_hours(QuietHours::new(time(22, 0), time(8, 0), "Asia/Shanghai").unwrap())
+            .with_locale("en-US");
+
+        // 未设置偏好原样投递
+        assert!(matches!(
+            apply_preferences(None, make_event(NotificationType::BadgeGranted), now),
+            PreferenceDecision::Deliver(e) if e.channels.len() == 2
+        ));
+
+        // 免打扰时段内非紧急通知延迟，并透传语言
+        match apply_preferences(
+            Some(&prefs),
+            make_event(NotificationType::BadgeGranted),
+            now,
+        ) {
+            PreferenceDecision::Defer(event, until) => {
+                assert_eq!(until, utc("2025-03-02T00:00:00Z"));
+                assert_eq!(event.data[LOCALE_DATA_KEY], "en-US");
+            }
+            other => panic!("应延迟投递: {:?}", other),
+        }
+
+        // 紧急通知不受免打扰限制
+        assert!(matches!(
+            apply_preferences(
+                Some(&prefs),
+                make_event(NotificationType::RedemptionFailed),
+                now
+            ),
+            PreferenceDecision::Deliver(_)
+        ));
+
+        // 关闭所有渠道的通知类型被抑制
+        assert!(matches!(
+            apply_preferences(
+                Some(&prefs),
+                make_event(NotificationType::BadgeRevoked),
+                now
+            ),
+            PreferenceDecision::Suppress
+        ));
+    }
+
+    #[test]
+    fn test_preferences_deserialize_defaults() {
+        let prefs: NotificationPreferences = serde_json::from_value(serde_json::json!({
+            "userId": "user-1",
+            "channels": {"BADGE_GRANTED": ["SMS"]}
+        }))
+        .unwrap();
+        assert_eq!(prefs.locale, DEFAULT_LOCALE);
+        assert!(prefs.quiet_hours.is_none());
+        assert_eq!(
+            prefs.channels[&NotificationType::BadgeGranted],
+            vec![NotificationChannel::Sms]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_deferred_store() {
+        let store = InMemoryDeferredStore::new();
+        let event = make_event(NotificationType::BadgeGranted);
+        let at = utc("2025-03-02T00:00:00Z");
+
+        store.defer(&event, at).await.unwrap();
+        store.defer(&event, at).await.unwrap();
+        assert_eq!(store.len(), 1);
+
+        assert!(
+            store
+                .take_due(utc("2025-03-01T23:59:59Z"), 10)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        let due = store.take_due(at, 10).await.unwrap();
+        assert_eq!(due.len(), 1);
+        assert!(store.is_empty());
+
+        store.defer(&event, at).await.unwrap();
+        store.remove(&["n-2".to_string()]).await.unwrap();
+        assert_eq!(store.len(), 1);
+        store.remove(&["n-1".to_string()]).await.unwrap();
+        assert!(store.is_empty());
+    }
+}
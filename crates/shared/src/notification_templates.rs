@@ -0,0 +1,1079 @@
+//! 通知模板
+//!
+//! badge-management-service 和 notification-worker 共用的模板模型与渲染引擎：
+//!
+//! - **模板变体**：按通知类型、语言、渠道区分，未配置渠道的模板作为该语言的默认模板
+//! - **模板语法**：`{{var}}` 变量（支持 `a.b` 路径）、`{{#if var}}…{{else}}…{{/if}}` 条件、
+//!   `{{#each list}}…{{/each}}` 循环（循环体内可用 `{{this}}`、`{{@index}}`）
+//! - **HTML 转义**：邮件 HTML 正文模板中的变量值会做 HTML 转义，标题和纯文本正文原样输出
+//!
+//! 模板存储在 `notification_templates` 表，由管理后台维护。数据库模板覆盖代码内置的
+//! 默认模板，数据库不可用时仍可用内置模板渲染。
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use arc_swap::ArcSwap;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::PgPool;
+use tracing::{debug, warn};
+
+use crate::error::{BadgeError, Result};
+use crate::events::{NotificationChannel, NotificationType};
+use crate::notification_preferences::DEFAULT_LOCALE;
+
+/// 通知数据中携带邮件 HTML 正文的字段
+pub const HTML_BODY_DATA_KEY: &str = "html_body";
+
+/// 默认的模板刷新间隔
+const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+// ---------------------------------------------------------------------------
+// 模板语法
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone)]
+enum Node {
+    Text(String),
+    Var(String),
+    If {
+        path: String,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    Each {
+        path: String,
+        body: Vec<Node>,
+    },
+}
+
+/// 解析中尚未闭合的块
+enum Block {
+    If {
+        path: String,
+        then: Vec<Node>,
+        otherwise: Option<Vec<Node>>,
+    },
+    Each {
+        path: String,
+        body: Vec<Node>,
+    },
+}
+
+impl Block {
+    fn nodes_mut(&mut self) -> &mut Vec<Node> {
+        match self {
+            Block::If {
+                otherwise: Some(otherwise),
+                ..
+            } => otherwise,
+            Block::If { then, .. } => then,
+            Block::Each { body, .. } => body,
+        }
+    }
+}
+
+/// 编译后的模板
+#[derive(Debug, Clone)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+impl Template {
+    /// 解析模板，语法错误（未闭合的块、非法变量名等）返回 `Validation`
+    pub fn parse(source: &str) -> Result<Self> {
+        let mut root = Vec::new();
+        let mut blocks: Vec<Block> = Vec::new();
+        let mut rest = source;
+
+        while let Some(start) = rest.find("{{") {
+            let (text, tail) = rest.split_at(start);
+            if !text.is_empty() {
+                push_node(&mut root, &mut blocks, Node::Text(text.to_string()));
+            }
+            let end = tail
+                .find("}}")
+                .ok_or_else(|| syntax_error(format!("标签未闭合: {}", truncate(tail))))?;
+            let tag = tail[2..end].trim();
+            rest = &tail[end + 2..];
+
+            if let Some(path) = tag.strip_prefix("#if ") {
+                blocks.push(Block::If {
+                    path: parse_path(path)?,
+                    then: Vec::new(),
+                    otherwise: None,
+                });
+            } else if let Some(path) = tag.strip_prefix("#each ") {
+                blocks.push(Block::Each {
+                    path: parse_path(path)?,
+                    body: Vec::new(),
+                });
+            } else if tag == "else" {
+                match blocks.last_mut() {
+                    Some(Block::If { otherwise, .. }) if otherwise.is_none() => {
+                        *otherwise = Some(Vec::new());
+                    }
+                    _ => return Err(syntax_error("{{else}} 不在 {{#if}} 块内".to_string())),
+                }
+            } else if tag == "/if" {
+                match blocks.pop() {
+                    Some(Block::If {
+                        path,
+                        then,
+                        otherwise,
+                    }) => push_node(
+                        &mut root,
+                        &mut blocks,
+                        Node::If {
+                            path,
+                            then,
+                            otherwise: otherwise.unwrap_or_default(),
+                        },
+                    ),
+                    _ => return Err(syntax_error("多余的 {{/if}}".to_string())),
+                }
+            } else if tag == "/each" {
+                match blocks.pop() {
+                    Some(Block::Each { path, body }) => {
+                        push_node(&mut root, &mut blocks, Node::Each { path, body })
+                    }
+                    _ => return Err(syntax_error("多余的 {{/each}}".to_string())),
+                }
+            } else {
+                let path = parse_path(tag)?;
+                push_node(&mut root, &mut blocks, Node::Var(path));
+            }
+        }
+        if !rest.is_empty() {
+            push_node(&mut root, &mut blocks, Node::Text(rest.to_string()));
+        }
+
+        match blocks.last() {
+            None => Ok(Self { nodes: root }),
+            Some(Block::If { path, .. }) => Err(syntax_error(format!(
+                "{{{{#if {}}}}} 缺少 {{{{/if}}}}",
+                path
+            ))),
+            Some(Block::Each { path, .. }) => Err(syntax_error(format!(
+                "{{{{#each {}}}}} 缺少 {{{{/each}}}}",
+                path
+            ))),
+        }
+    }
+
+    /// 渲染模板
+    ///
+    /// 未找到的变量保留原样并记录到 `missing`，`escape_html` 为 true 时对变量值做 HTML 转义
+    pub fn render(&self, context: &Value, escape_html: bool) -> RenderOutput {
+        let mut renderer = Renderer {
+            escape_html,
+            output: String::new(),
+            missing: Vec::new(),
+        };
+        let mut scopes = vec![Scope {
+            value: context.clone(),
+            index: None,
+        }];
+        renderer.render_nodes(&self.nodes, &mut scopes);
+        RenderOutput {
+            text: renderer.output,
+            missing: renderer.missing,
+        }
+    }
+
+    /// 模板引用的变量名（含条件和循环引用的变量）
+    pub fn variables(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        collect_variables(&self.nodes, &mut names);
+        names
+    }
+}
+
+/// 模板渲染结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderOutput {
+    pub text: String,
+    /// 上下文中缺失的变量
+    pub missing: Vec<String>,
+}
+
+fn push_node(root: &mut Vec<Node>, blocks: &mut [Block], node: Node) {
+    match blocks.last_mut() {
+        Some(block) => block.nodes_mut().push(node),
+        None => root.push(node),
+    }
+}
+
+fn parse_path(raw: &str) -> Result<String> {
+    let path = raw.trim();
+    let valid = !path.is_empty()
+        && path
+            .split('.')
+            .all(|seg| !seg.is_empty() && seg.chars().all(|c| c.is_alphanumeric() || c == '_'))
+        || path == "@index";
+    if valid {
+        Ok(path.to_string())
+    } else {
+        Err(syntax_error(format!("非法的变量名: {}", raw)))
+    }
+}
+
+fn syntax_error(message: String) -> BadgeError {
+    BadgeError::Validation(format!("模板语法错误: {}", message))
+}
+
+fn truncate(text: &str) -> String {
+    text.chars().take(20).collect()
+}
+
+fn collect_variables(nodes: &[Node], names: &mut Vec<String>) {
+    let push = |name: &str, names: &mut Vec<String>| {
+        if !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+    };
+    for node in nodes {
+        match node {
+            Node::Text(_) => {}
+            Node::Var(path) => push(path, names),
+            Node::If {
+                path,
+                then,
+                otherwise,
+            } => {
+                push(path, names);
+                collect_variables(then, names);
+                collect_variables(otherwise, names);
+            }
+            Node::Each { path, body } => {
+                push(path, names);
+                collect_variables(body, names);
+            }
+        }
+    }
+}
+
+struct Scope {
+    value: Value,
+    index: Option<usize>,
+}
+
+struct Renderer {
+    escape_html: bool,
+    output: String,
+    missing: Vec<String>,
+}
+
+impl Renderer {
+    fn render_nodes(&mut self, nodes: &[Node], scopes: &mut Vec<Scope>) {
+        for node in nodes {
+            match node {
+                Node::Text(text) => self.output.push_str(text),
+                Node::Var(path) => match lookup(scopes, path) {
+                    Some(value) => {
+                        let text = value_to_string(&value);
+                        if self.escape_html {
+                            self.output.push_str(&escape_html(&text));
+                        } else {
+                            self.output.push_str(&text);
+                        }
+                    }
+                    None => {
+                        if !self.missing.contains(path) {
+                            self.missing.push(path.clone());
+                        }
+                        self.output.push_str(&format!("{{{{{}}}}}", path));
+                    }
+                },
+                Node::If {
+                    path,
+                    then,
+                    otherwise,
+                } => {
+                    if lookup(scopes, path).is_some_and(|v| is_truthy(&v)) {
+                        self.render_nodes(then, scopes);
+                    } else {
+                        self.render_nodes(otherwise, scopes);
+                    }
+                }
+                Node::Each { path, body } => {
+                    let Some(Value::Array(items)) = lookup(scopes, path) else {
+                        continue;
+                    };
+                    for (index, item) in items.into_iter().enumerate() {
+                        scopes.push(Scope {
+                            value: item,
+                            index: Some(index),
+                        });
+                        self.render_nodes(body, scopes);
+                        scopes.pop();
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// 查找变量，从最内层作用域向外逐层查找
+fn lookup(scopes: &[Scope], path: &str) -> Option<Value> {
+    let current = scopes.last()?;
+    if path == "@index" {
+        return current.index.map(Value::from);
+    }
+    if path == "this" {
+        return Some(current.value.clone());
+    }
+    if let Some(rest) = path.strip_prefix("this.") {
+        return get_path(&current.value, rest).cloned();
+    }
+    scopes
+        .iter()
+        .rev()
+        .find_map(|scope| get_path(&scope.value, path))
+        .cloned()
+}
+
+fn get_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .try_fold(value, |current, segment| current.get(segment))
+        .filter(|v| !v.is_null())
+}
+
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().is_some_and(|f| f != 0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(a) => !a.is_empty(),
+        Value::Object(o) => !o.is_empty(),
+    }
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// ---------------------------------------------------------------------------
+// 通知模板
+// ---------------------------------------------------------------------------
+
+/// 通知模板定义
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationTemplate {
+    pub notification_type: NotificationType,
+    pub locale: String,
+    /// 适用渠道，None 表示该语言下所有渠道的默认模板
+    pub channel: Option<NotificationChannel>,
+    pub title: String,
+    pub body: String,
+    /// 邮件 HTML 正文模板，变量值会做 HTML 转义
+    pub html_body: Option<String>,
+}
+
+impl NotificationTemplate {
+    pub fn new(
+        notification_type: NotificationType,
+        locale: impl Into<String>,
+        title: impl Into<String>,
+        body: impl Into<String>,
+    ) -> Self {
+        Self {
+            notification_type,
+            locale: locale.into(),
+            channel: None,
+            title: title.into(),
+            body: body.into(),
+            html_body: None,
+        }
+    }
+
+    pub fn with_channel(mut self, channel: NotificationChannel) -> Self {
+        self.channel = Some(channel);
+        self
+    }
+
+    pub fn with_html_body(mut self, html_body: impl Into<String>) -> Self {
+        self.html_body = Some(html_body.into());
+        self
+    }
+
+    /// 编译模板，校验标题、正文和 HTML 正文的语法
+    pub fn compile(&self) -> Result<CompiledTemplate> {
+        Ok(CompiledTemplate {
+            title: Template::parse(&self.title)?,
+            body: Template::parse(&self.body)?,
+            html_body: self.html_body.as_deref().map(Template::parse).transpose()?,
+        })
+    }
+
+    fn key(&self) -> TemplateKey {
+        (
+            self.notification_type.clone(),
+            self.locale.clone(),
+            self.channel.clone(),
+        )
+    }
+}
+
+/// 编译后的通知模板
+#[derive(Debug, Clone)]
+pub struct CompiledTemplate {
+    title: Template,
+    body: Template,
+    html_body: Option<Template>,
+}
+
+impl CompiledTemplate {
+    /// 用通知数据渲染模板
+    pub fn render(&self, context: &Value) -> RenderedNotification {
+        let title = self.title.render(context, false);
+        let body = self.body.render(context, false);
+        let html_body = self.html_body.as_ref().map(|t| t.render(context, true));
+
+        let mut missing_variables = title.missing;
+        for name in body
+            .missing
+            .into_iter()
+            .chain(html_body.iter().flat_map(|h| h.missing.clone()))
+        {
+            if !missing_variables.contains(&name) {
+                missing_variables.push(name);
+            }
+        }
+
+        RenderedNotification {
+            title: title.text,
+            body: body.text,
+            html_body: html_body.map(|h| h.text),
+            missing_variables,
+        }
+    }
+}
+
+/// 渲染后的通知内容
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenderedNotification {
+    pub title: String,
+    pub body: String,
+    pub html_body: Option<String>,
+    /// 通知数据中缺失的变量
+    pub missing_variables: Vec<String>,
+}
+
+impl RenderedNotification {
+    /// 所有变量均已替换
+    pub fn is_complete(&self) -> bool {
+        self.missing_variables.is_empty()
+    }
+}
+
+type TemplateKey = (NotificationType, String, Option<NotificationChannel>);
+
+/// 模板集合
+///
+/// 按 `(类型, 语言, 渠道)` 索引，同一键的模板后加入的覆盖先加入的。
+#[derive(Debug, Default)]
+pub struct TemplateSet {
+    templates: HashMap<TemplateKey, CompiledTemplate>,
+}
+
+impl TemplateSet {
+    /// 编译并加入模板，语法错误的模板跳过
+    pub fn new(templates: impl IntoIterator<Item = NotificationTemplate>) -> Self {
+        let mut set = Self::default();
+        for template in templates {
+            match template.compile() {
+                Ok(compiled) => {
+                    set.templates.insert(template.key(), compiled);
+                }
+                Err(e) => warn!(
+                    notification_type = ?template.notification_type,
+                    locale = %template.locale,
+                    channel = ?template.channel,
+                    error = %e,
+                    "通知模板语法错误，已跳过"
+                ),
+            }
+        }
+        set
+    }
+
+    /// 内置默认模板
+    pub fn builtin() -> Self {
+        Self::new(builtin_templates())
+    }
+
+    pub fn len(&self) -> usize {
+        self.templates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.templates.is_empty()
+    }
+
+    /// 查找模板
+    ///
+    /// 依次尝试：指定语言的渠道模板、指定语言的默认模板、
+    /// 默认语言的渠道模板、默认语言的默认模板。
+    pub fn resolve(
+        &self,
+        notification_type: &NotificationType,
+        locale: &str,
+        channel: Option<&NotificationChannel>,
+    ) -> Option<&CompiledTemplate> {
+        let mut candidates = Vec::with_capacity(4);
+        for locale in [locale, DEFAULT_LOCALE] {
+            if let Some(channel) = channel {
+                candidates.push((
+                    notification_type.clone(),
+                    locale.to_string(),
+                    Some(channel.clone()),
+                ));
+            }
+            candidates.push((notification_type.clone(), locale.to_string(), None));
+        }
+        candidates.iter().find_map(|key| self.templates.get(key))
+    }
+
+    /// 查找并渲染模板，没有匹配的模板时返回 None
+    pub fn render(
+        &self,
+        notification_type: &NotificationType,
+        locale: &str,
+        channel: Option<&NotificationChannel>,
+        context: &Value,
+    ) -> Option<RenderedNotification> {
+        self.resolve(notification_type, locale, channel)
+            .map(|t| t.render(context))
+    }
+}
+
+/// 内置默认模板
+///
+/// 徽章发放模板兼容 notification-worker 合并后的摘要通知（`digest_count` 和 `badges`）
+pub fn builtin_templates() -> Vec<NotificationTemplate> {
+    use NotificationType::*;
+
+    vec![
+        NotificationTemplate::new(
+            BadgeGranted,
+            "zh-CN",
+            "{{#if digest_count}}恭喜获得 {{digest_count}} 枚新徽章{{else}}恭喜获得新徽章{{/if}}",
+            "您已获得{{#if badges}}{{#each badges}}「{{badge_name}}」{{/each}}{{else}}「{{badge_name}}」{{/if}}徽章！",
+        )
+        .with_html_body(
+            "<p>您已获得以下徽章：</p><ul>{{#if badges}}{{#each badges}}<li>{{badge_name}}</li>{{/each}}{{else}}<li>{{badge_name}}</li>{{/if}}</ul>",
+        ),
+        NotificationTemplate::new(
+            BadgeExpiring,
+            "zh-CN",
+            "徽章即将过期",
+            "您的「{{badge_name}}」徽章将在 {{days_left}} 天后过期，请及时使用！",
+        ),
+        NotificationTemplate::new(
+            BadgeRevoked,
+            "zh-CN",
+            "徽章已被回收",
+            "您的「{{badge_name}}」徽章已被回收{{#if reason}}，原因：{{reason}}{{/if}}",
+        ),
+        NotificationTemplate::new(
+            BadgeUnlocked,
+            "zh-CN",
+            "徽章已解锁",
+            "恭喜！您的「{{badge_name}}」徽章已解锁，快去查看吧！",
+        ),
+        NotificationTemplate::new(
+            RedemptionSuccess,
+            "zh-CN",
+            "兑换成功",
+            "您已成功{{#if badge_name}}使用「{{badge_name}}」徽章{{/if}}兑换「{{benefit_name}}」",
+        ),
+        NotificationTemplate::new(
+            RedemptionFailed,
+            "zh-CN",
+            "兑换失败",
+            "您的兑换请求未能成功{{#if reason}}，原因：{{reason}}{{/if}}",
+        ),
+        NotificationTemplate::new(
+            BenefitGranted,
+            "zh-CN",
+            "权益已发放",
+            "您已获得「{{benefit_name}}」权益，快去使用吧！",
+        ),
+        NotificationTemplate::new(
+            BadgeGranted,
+            "en-US",
+            "{{#if digest_count}}You earned {{digest_count}} new badges{{else}}You earned a new badge{{/if}}",
+            "You earned {{#if badges}}{{#each badges}}{{#if @index}}, {{/if}}\"{{badge_name}}\"{{/each}}{{else}}the \"{{badge_name}}\" badge{{/if}}!",
+        )
+        .with_html_body(
+            "<p>You earned:</p><ul>{{#if badges}}{{#each badges}}<li>{{badge_name}}</li>{{/each}}{{else}}<li>{{badge_name}}</li>{{/if}}</ul>",
+        ),
+        NotificationTemplate::new(
+            BadgeExpiring,
+            "en-US",
+            "Badge expiring soon",
+            "Your \"{{badge_name}}\" badge expires in {{days_left}} days.",
+        ),
+        NotificationTemplate::new(
+            BadgeRevoked,
+            "en-US",
+            "Badge revoked",
+            "Your \"{{badge_name}}\" badge was revoked{{#if reason}}: {{reason}}{{/if}}.",
+        ),
+        NotificationTemplate::new(
+            BadgeUnlocked,
+            "en-US",
+            "Badge unlocked",
+            "Your \"{{badge_name}}\" badge is now unlocked!",
+        ),
+        NotificationTemplate::new(
+            RedemptionSuccess,
+            "en-US",
+            "Redemption successful",
+            "You redeemed \"{{benefit_name}}\"{{#if badge_name}} with your \"{{badge_name}}\" badge{{/if}}.",
+        ),
+        NotificationTemplate::new(
+            RedemptionFailed,
+            "en-US",
+            "Redemption failed",
+            "Your redemption did not go through{{#if reason}}: {{reason}}{{/if}}.",
+        ),
+        NotificationTemplate::new(
+            BenefitGranted,
+            "en-US",
+            "Benefit granted",
+            "You received \"{{benefit_name}}\". Enjoy!",
+        ),
+    ]
+}
+
+// ---------------------------------------------------------------------------
+// 模板存储
+// ---------------------------------------------------------------------------
+
+/// 通知模板存储
+#[async_trait]
+pub trait TemplateStore: Send + Sync {
+    /// 加载所有启用的模板
+    async fn list_enabled(&self) -> Result<Vec<NotificationTemplate>>;
+}
+
+/// 基于 PostgreSQL 的模板存储
+pub struct PgTemplateStore {
+    pool: PgPool,
+}
+
+impl PgTemplateStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct TemplateRow {
+    id: i64,
+    notification_type: String,
+    locale: String,
+    channel: Option<String>,
+    title_template: String,
+    body_template: String,
+    html_template: Option<String>,
+}
+
+impl TemplateRow {
+    fn into_template(self) -> Option<NotificationTemplate> {
+        let notification_type = parse_notification_type(&self.notification_type)?;
+        let channel = match self.channel.as_deref() {
+            Some(channel) => Some(parse_channel(channel)?),
+            None => None,
+        };
+        Some(NotificationTemplate {
+            notification_type,
+            locale: self.locale,
+            channel,
+            title: self.title_template,
+            body: self.body_template,
+            html_body: self.html_template,
+        })
+    }
+}
+
+#[async_trait]
+impl TemplateStore for PgTemplateStore {
+    async fn list_enabled(&self) -> Result<Vec<NotificationTemplate>> {
+        let rows = sqlx::query_as::<_, TemplateRow>(
+            r#"
+            SELECT id, notification_type, locale, channel,
+                   title_template, body_template, html_template
+            FROM notification_templates
+            WHERE enabled = TRUE
+            ORDER BY id
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                let id = row.id;
+                let template = row.into_template();
+                if template.is_none() {
+                    warn!(template_id = id, "通知模板的类型或渠道无法识别，已跳过");
+                }
+                template
+            })
+            .collect())
+    }
+}
+
+/// 解析通知类型（与事件序列化格式一致，如 `BADGE_GRANTED`）
+pub fn parse_notification_type(value: &str) -> Option<NotificationType> {
+    serde_json::from_value(Value::String(value.to_string())).ok()
+}
+
+/// 解析通知渠道（与事件序列化格式一致，如 `APP_PUSH`）
+pub fn parse_channel(value: &str) -> Option<NotificationChannel> {
+    serde_json::from_value(Value::String(value.to_string())).ok()
+}
+
+// ---------------------------------------------------------------------------
+// 模板注册表
+// ---------------------------------------------------------------------------
+
+/// 模板注册表
+///
+/// 持有内置模板和数据库模板合并后的模板集合，按刷新间隔从存储重新加载，
+/// 管理后台修改模板后无需重启服务。加载失败时沿用上一次的模板集合。
+pub struct TemplateRegistry {
+    builtin: Vec<NotificationTemplate>,
+    store: Option<Arc<dyn TemplateStore>>,
+    refresh_interval: Duration,
+    current: ArcSwap<TemplateSet>,
+    loaded_at: Mutex<Option<Instant>>,
+}
+
+impl TemplateRegistry {
+    /// 以给定模板作为基础模板创建注册表
+    pub fn new(builtin: Vec<NotificationTemplate>) -> Self {
+        let current = ArcSwap::from_pointee(TemplateSet::new(builtin.clone()));
+        Self {
+            builtin,
+            store: None,
+            refresh_interval: DEFAULT_REFRESH_INTERVAL,
+            current,
+            loaded_at: Mutex::new(None),
+        }
+    }
+
+    /// 使用内置默认模板创建注册表
+    pub fn builtin() -> Self {
+        Self::new(builtin_templates())
+    }
+
+    /// 设置模板存储，存储中的模板覆盖基础模板
+    pub fn with_store(mut self, store: Arc<dyn TemplateStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    pub fn with_refresh_interval(mut self, interval: Duration) -> Self {
+        self.refresh_interval = interval;
+        self
+    }
+
+    /// 注册基础模板（应在首次加载存储前调用）
+    pub fn register(&mut self, template: NotificationTemplate) {
+        self.builtin.push(template);
+        self.current
+            .store(Arc::new(TemplateSet::new(self.builtin.clone())));
+    }
+
+    /// 当前模板集合（不触发刷新）
+    pub fn snapshot(&self) -> Arc<TemplateSet> {
+        self.current.load_full()
+    }
+
+    /// 当前模板集合，超过刷新间隔时先从存储重新加载
+    pub async fn current(&self) -> Arc<TemplateSet> {
+        if self.store.is_some()
+            && self.claim_refresh()
+            && let Err(e) = self.reload().await
+        {
+            warn!(error = %e, "加载通知模板失败，沿用当前模板");
+        }
+        self.snapshot()
+    }
+
+    /// 从存储重新加载模板
+    pub async fn reload(&self) -> Result<()> {
+        let Some(store) = &self.store else {
+            return Ok(());
+        };
+        let stored = store.list_enabled().await?;
+        let count = stored.len();
+        let set = TemplateSet::new(self.builtin.iter().cloned().chain(stored));
+        self.current.store(Arc::new(set));
+        *self.loaded_at.lock().unwrap() = Some(Instant::now());
+        debug!(stored_templates = count, "通知模板已加载");
+        Ok(())
+    }
+
+    /// 判断是否需要刷新，并发调用时只有一个调用方执行加载
+    fn claim_refresh(&self) -> bool {
+        let mut loaded_at = self.loaded_at.lock().unwrap();
+        match *loaded_at {
+            Some(at) if at.elapsed() < self.refresh_interval => false,
+            _ => {
+                *loaded_at = Some(Instant::now());
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn render(source: &str, context: Value) -> RenderOutput {
+        Template::parse(source).unwrap().render(&context, false)
+    }
+
+    #[test]
+    fn test_render_variables_and_paths() {
+        let output = render(
+            "{{user.name}} 获得「{{badge_name}}」x{{quantity}}",
+            json!({"user": {"name": "张三"}, "badge_name": "首单", "quantity": 2}),
+        );
+        assert_eq!(output.text, "张三 获得「首单」x2");
+        assert!(output.missing.is_empty());
+
+        let output = render("你好，{{name}}！", json!({}));
+        assert_eq!(output.text, "你好，{{name}}！");
+        assert_eq!(output.missing, vec!["name".to_string()]);
+    }
+
+    #[test]
+    fn test_render_conditionals() {
+        let template =
+            Template::parse("徽章已回收{{#if reason}}，原因：{{reason}}{{else}}。{{/if}}").unwrap();
+        assert_eq!(
+            template.render(&json!({"reason": "违规"}), false).text,
+            "徽章已回收，原因：违规"
+        );
+        assert_eq!(
+            template.render(&json!({"reason": ""}), false).text,
+            "徽章已回收。"
+        );
+        // 条件中引用的变量缺失不算缺失变量
+        assert!(template.render(&json!({}), false).missing.is_empty());
+    }
+
+    #[test]
+    fn test_render_loops() {
+        let output = render(
+            "{{#each badges}}{{#if @index}}、{{/if}}{{badge_name}}({{@index}}){{/each}} / {{#each tags}}{{this}}{{/each}} / {{user}}",
+            json!({
+                "badges": [{"badge_name": "首单"}, {"badge_name": "复购"}],
+                "tags": ["a", "b"],
+                "user": "u-1"
+            }),
+        );
+        assert_eq!(output.text, "首单(0)、复购(1) / ab / u-1");
+    }
+
+    #[test]
+    fn test_html_escaping() {
+        let template = Template::parse("<p>{{badge_name}}</p>").unwrap();
+        let context = json!({"badge_name": "<script>&\"'"});
+        assert_eq!(
+            template.render(&context, true).text,
+            "<p>&lt;script&gt;&amp;&quot;&#39;</p>"
+        );
+        assert_eq!(template.render(&context, false).text, "<p><script>&\"'</p>");
+    }
+
+    #[test]
+    fn test_syntax_errors() {
+        for source in [
+            "{{#if a}}未闭合",
+            "{{#each a}}{{/if}}",
+            "多余的{{/each}}",
+            "{{else}}",
+            "{{#if a}}{{else}}{{else}}{{/if}}",
+            "{{name",
+            "{{bad name}}",
+            "{{}}",
+        ] {
+            assert!(Template::parse(source).is_err(), "应拒绝: {}", source);
+        }
+        assert_eq!(
+            Template::parse("{{#if a}}{{b}}{{/if}}{{#each c}}{{this.d}}{{/each}}")
+                .unwrap()
+                .variables(),
+            vec!["a", "b", "c", "this.d"]
+        );
+    }
+
+    #[test]
+    fn test_resolve_fallback_order() {
+        let set = TemplateSet::new([
+            NotificationTemplate::new(NotificationType::BadgeGranted, "zh-CN", "中文默认", ""),
+            NotificationTemplate::new(NotificationType::BadgeGranted, "zh-CN", "中文短信", "")
+                .with_channel(NotificationChannel::Sms),
+            NotificationTemplate::new(NotificationType::BadgeGranted, "en-US", "English", ""),
+        ]);
+        let title = |locale: &str, channel: Option<NotificationChannel>| {
+            set.render(
+                &NotificationType::BadgeGranted,
+                locale,
+                channel.as_ref(),
+                &json!({}),
+            )
+            .map(|r| r.title)
+        };
+
+        assert_eq!(
+            title("zh-CN", Some(NotificationChannel::Sms)).unwrap(),
+            "中文短信"
+        );
+        assert_eq!(
+            title("zh-CN", Some(NotificationChannel::Email)).unwrap(),
+            "中文默认"
+        );
+        assert_eq!(
+            title("en-US", Some(NotificationChannel::Sms)).unwrap(),
+            "English"
+        );
+        assert_eq!(
+            title("ja-JP", Some(NotificationChannel::Sms)).unwrap(),
+            "中文短信"
+        );
+        assert_eq!(title("ja-JP", None).unwrap(), "中文默认");
+        assert!(
+            set.render(&NotificationType::BadgeRevoked, "zh-CN", None, &json!({}))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_builtin_templates() {
+        let set = TemplateSet::builtin();
+        assert_eq!(set.len(), builtin_templates().len());
+
+        let single = set
+            .render(
+                &NotificationType::BadgeGranted,
+                "zh-CN",
+                Some(&NotificationChannel::Email),
+                &json!({"badge_name": "首次购物"}),
+            )
+            .unwrap();
+        assert_eq!(single.title, "恭喜获得新徽章");
+        assert_eq!(single.body, "您已获得「首次购物」徽章！");
+        assert_eq!(
+            single.html_body.as_deref(),
+            Some("<p>您已获得以下徽章：</p><ul><li>首次购物</li></ul>")
+        );
+
+        let digest = set
+            .render(
+                &NotificationType::BadgeGranted,
+                "en-US",
+                None,
+                &json!({"digest_count": 2, "badges": [{"badge_name": "A"}, {"badge_name": "B"}]}),
+            )
+            .unwrap();
+        assert_eq!(digest.title, "You earned 2 new badges");
+        assert_eq!(digest.body, "You earned \"A\", \"B\"!");
+
+        let revoked = set
+            .render(&NotificationType::BadgeRevoked, "zh-CN", None, &json!({}))
+            .unwrap();
+        assert!(!revoked.is_complete());
+        assert_eq!(revoked.missing_variables, vec!["badge_name".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_type_and_channel() {
+        assert_eq!(
+            parse_notification_type("BADGE_GRANTED"),
+            Some(NotificationType::BadgeGranted)
+        );
+        assert_eq!(parse_channel("WE_CHAT"), Some(NotificationChannel::WeChat));
+        assert_eq!(parse_channel("wechat"), None);
+    }
+
+    struct StaticStore(Vec<NotificationTemplate>);
+
+    #[async_trait]
+    impl TemplateStore for StaticStore {
+        async fn list_enabled(&self) -> Result<Vec<NotificationTemplate>> {
+            Ok(self.0.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_registry_store_overrides_builtin() {
+        let registry = TemplateRegistry::builtin().with_store(Arc::new(StaticStore(vec![
+            NotificationTemplate::new(
+                NotificationType::BadgeGranted,
+                "zh-CN",
+                "运营标题",
+                "{{badge_name}}",
+            ),
+            // 语法错误的模板被跳过，不影响其他模板
+            NotificationTemplate::new(NotificationType::BadgeRevoked, "zh-CN", "{{#if x}}", ""),
+        ])));
+
+        let set = registry.current().await;
+        let granted = set
+            .render(
+                &NotificationType::BadgeGranted,
+                "zh-CN",
+                None,
+                &json!({"badge_name": "首单"}),
+            )
+            .unwrap();
+        assert_eq!(granted.title, "运营标题");
+        assert_eq!(granted.html_body, None);
+
+        let revoked = set
+            .render(
+                &NotificationType::BadgeRevoked,
+                "zh-CN",
+                None,
+                &json!({"badge_name": "首单"}),
+            )
+            .unwrap();
+        assert_eq!(revoked.title, "徽章已被回收");
+    }
+}
@@ -117,7 +117,17 @@ fn register_common_metrics(service_name: &str) {
     );
 
     // Worker 健康指标
-    metrics::describe_gauge!("worker_last_run_timestamp", "Last successful worker run timestamp");
+    metrics::describe_gauge!(
+        "worker_last_run_timestamp",
+        "Last successful worker run timestamp"
+    );
+
+    // 死信队列指标
+    metrics::describe_gauge!(
+        "dlq_messages",
+        "Number of dead-letter messages by source service and status"
+    );
+    metrics::describe_counter!("dlq_replays_total", "Total number of dead-letter replays");
 
     // 记录服务启动
     metrics::counter!("service_starts_total", "service" => service_name.to_string()).increment(1);
@@ -347,6 +357,28 @@ pub fn set_worker_last_run(worker_name: &str) {
     .set(timestamp);
 }
 
+/// 更新死信积压数量
+#[inline]
+pub fn set_dlq_depth(source_service: &str, status: &str, count: f64) {
+    metrics::gauge!(
+        "dlq_messages",
+        "source_service" => source_service.to_string(),
+        "status" => status.to_string()
+    )
+    .set(count);
+}
+
+/// 记录死信重放
+#[inline]
+pub fn record_dlq_replay(source_service: &str, status: &str) {
+    metrics::counter!(
+        "dlq_replays_total",
+        "source_service" => source_service.to_string(),
+        "status" => status.to_string()
+    )
+    .increment(1);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -362,5 +394,7 @@ mod tests {
         record_rule_evaluation(true, 0.01);
         record_benefit_grant("coupon", "success");
         set_benefit_stock(1, 100.0);
+        set_dlq_depth("event-engagement-service", "pending", 3.0);
+        record_dlq_replay("event-engagement-service", "success");
     }
 }
@@ -1,7 +1,6 @@
 //! HTTP 和 gRPC 中间件
 //!
-//! 提供请求追踪和指标收集的中间件。
-//! 具体实现将在 Task 3.4 中完善。
+//! 提供请求追踪和指标收集的中间件，以及 gRPC 和 Kafka 的追踪上下文传播。
 
 use std::time::Instant;
 
@@ -165,12 +164,63 @@ impl RequestId {
     }
 }
 
-/// gRPC 拦截器（占位）
+/// gRPC 追踪辅助
 ///
-/// 实际实现需要 tonic 的 Interceptor trait。
-/// 将在 Task 3.4 中完善。
+/// 客户端调用前把当前追踪上下文写入请求 metadata，服务端通过 `Server::trace_fn`
+/// 为每个请求创建以上游上下文为父节点的 span。
 pub mod grpc {
+    use std::collections::HashMap;
+
+    use tonic::codegen::http;
+    use tonic::metadata::{MetadataKey, MetadataValue};
     use tonic::{Request, Status};
+    use tracing::Span;
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+    use super::super::tracing::{extract_from_headers, inject_to_headers};
+
+    /// 构建携带当前追踪上下文的 gRPC 请求
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let response = client.batch_evaluate(traced_request(request)).await?;
+    /// ```
+    pub fn traced_request<T>(message: T) -> Request<T> {
+        let mut request = Request::new(message);
+        let mut headers = HashMap::new();
+        inject_to_headers(&mut headers);
+        for (key, value) in headers {
+            if let (Ok(key), Ok(value)) = (
+                MetadataKey::from_bytes(key.as_bytes()),
+                MetadataValue::try_from(value.as_str()),
+            ) {
+                request.metadata_mut().insert(key, value);
+            }
+        }
+        request
+    }
+
+    /// 服务端请求 span，父上下文取自请求头中的 W3C Trace Context
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// Server::builder()
+    ///     .trace_fn(server_span)
+    ///     .add_service(service)
+    /// ```
+    pub fn server_span(request: &http::Request<()>) -> Span {
+        let span = tracing::info_span!("grpc_request", path = %request.uri().path());
+
+        let headers: HashMap<String, String> = request
+            .headers()
+            .iter()
+            .filter_map(|(k, v)| Some((k.as_str().to_string(), v.to_str().ok()?.to_string())))
+            .collect();
+        let _ = span.set_parent(extract_from_headers(&headers));
+        span
+    }
 
     /// gRPC 请求追踪拦截器函数
     ///
@@ -202,26 +252,55 @@ pub mod grpc {
     }
 }
 
-/// Kafka 消息追踪辅助（占位）
+/// Kafka 消息追踪辅助
 ///
-/// 用于在 Kafka 消息中传播追踪上下文。
-/// 将在 Task 3.4 中完善。
+/// 生产消息时把当前 span 的 W3C Trace Context（`traceparent`/`tracestate`）写入消息头，
+/// 消费时据此恢复上游上下文，使一次请求经过 Kafka 后仍处于同一条 trace 中。
 pub mod kafka {
     use std::collections::HashMap;
 
+    use opentelemetry::trace::TraceContextExt;
+    use tracing::Span;
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+    use super::super::tracing::{extract_from_headers, inject_to_headers};
+
     /// 注入追踪上下文到 Kafka 消息头
+    ///
+    /// 当前没有有效的追踪上下文（如未配置 OTLP 端点）时不写入任何 header。
     pub fn inject_trace_context(headers: &mut HashMap<String, String>) {
-        // 占位实现：将当前 trace context 注入到 headers
-        if let Some(trace_id) = super::super::tracing::current_trace_id() {
-            headers.insert("traceparent".to_string(), trace_id);
-        }
+        inject_to_headers(headers);
     }
 
     /// 从 Kafka 消息头提取追踪上下文
-    pub fn extract_trace_context(_headers: &HashMap<String, String>) -> Option<String> {
-        // 占位实现：从 headers 提取 trace context
-        // 完整实现需要使用 opentelemetry-propagator
-        None
+    ///
+    /// 消息头不包含有效的 `traceparent` 时返回 `None`。
+    pub fn extract_trace_context(
+        headers: &HashMap<String, String>,
+    ) -> Option<opentelemetry::Context> {
+        let context = extract_from_headers(headers);
+        let valid = context.span().span_context().is_valid();
+        valid.then_some(context)
+    }
+
+    /// 创建处理单条 Kafka 消息的 span，父上下文取自消息头
+    pub fn consumer_span(
+        topic: &str,
+        partition: i32,
+        offset: i64,
+        headers: &HashMap<String, String>,
+    ) -> Span {
+        let span = tracing::info_span!(
+            "kafka_consume",
+            messaging.system = "kafka",
+            messaging.destination = %topic,
+            partition,
+            offset,
+        );
+        if let Some(context) = extract_trace_context(headers) {
+            let _ = span.set_parent(context);
+        }
+        span
     }
 }
 
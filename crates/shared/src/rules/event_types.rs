@@ -0,0 +1,204 @@
+//! 事件类型注册表
+//!
+//! 缓存 `event_types` 表中属于本服务组的事件类型及其负载 JSON Schema，
+//! 供事件处理器判断自定义事件是否已注册，并在规则评估前校验 `EventPayload.data`。
+//! 与规则映射一起由 `RuleLoader` 刷新。
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use jsonschema::Validator;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::error::BadgeError;
+use crate::events::EventPayload;
+
+/// 单条错误信息的最大数量，避免超大负载产生过长的错误描述
+const MAX_REPORTED_ERRORS: usize = 5;
+
+/// 事件类型定义
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventTypeDefinition {
+    pub code: String,
+    pub name: String,
+    pub service_group: String,
+    /// 事件负载（`EventPayload.data`）的 JSON Schema，为空表示不校验
+    pub payload_schema: Option<serde_json::Value>,
+}
+
+struct RegisteredEventType {
+    definition: EventTypeDefinition,
+    validator: Option<Arc<Validator>>,
+}
+
+/// 编译 JSON Schema，返回可读的错误描述
+pub fn compile_payload_schema(schema: &serde_json::Value) -> Result<Validator, String> {
+    jsonschema::validator_for(schema).map_err(|e| e.to_string())
+}
+
+/// 事件类型注册表
+#[derive(Default)]
+pub struct EventTypeRegistry {
+    types: RwLock<HashMap<String, RegisteredEventType>>,
+}
+
+impl EventTypeRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 全量替换注册表
+    ///
+    /// Schema 无法编译的事件类型不注册：自定义事件会被拒绝，内置事件按未注册处理，
+    /// 避免错误配置的 Schema 放行任意负载。
+    pub fn replace_all(&self, definitions: Vec<EventTypeDefinition>) -> usize {
+        let mut types = HashMap::with_capacity(definitions.len());
+
+        for definition in definitions {
+            let validator = match definition.payload_schema {
+                Some(ref schema) => match compile_payload_schema(schema) {
+                    Ok(v) => Some(Arc::new(v)),
+                    Err(e) => {
+                        warn!(code = %definition.code, error = %e, "事件负载 Schema 无效，跳过该事件类型");
+                        continue;
+                    }
+                },
+                None => None,
+            };
+            types.insert(
+                definition.code.clone(),
+                RegisteredEventType {
+                    definition,
+                    validator,
+                },
+            );
+        }
+
+        let count = types.len();
+        *self.types.write() = types;
+        count
+    }
+
+    /// 事件类型是否已注册（按数据库编码）
+    pub fn contains(&self, code: &str) -> bool {
+        self.types.read().contains_key(code)
+    }
+
+    pub fn get(&self, code: &str) -> Option<EventTypeDefinition> {
+        self.types.read().get(code).map(|t| t.definition.clone())
+    }
+
+    pub fn len(&self) -> usize {
+        self.types.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.types.read().is_empty()
+    }
+
+    /// 按事件类型校验事件的负载，供各事件服务在规则评估前调用
+    pub fn validate_event(&self, event: &EventPayload) -> Result<(), BadgeError> {
+        self.validate_payload(event.event_type.to_db_key(), &event.data)
+    }
+
+    /// 按注册的 Schema 校验事件负载
+    ///
+    /// 未注册或未配置 Schema 的事件类型直接通过。
+    pub fn validate_payload(&self, code: &str, data: &serde_json::Value) -> Result<(), BadgeError> {
+        let validator = match self.types.read().get(code) {
+            Some(t) => t.validator.clone(),
+            None => None,
+        };
+        let Some(validator) = validator else {
+            return Ok(());
+        };
+
+        let errors: Vec<String> = validator
+            .iter_errors(data)
+            .take(MAX_REPORTED_ERRORS)
+            .map(|e| {
+                let path = e.instance_path.to_string();
+                if path.is_empty() {
+                    e.to_string()
+                } else {
+                    format!("{}: {}", path, e)
+                }
+            })
+            .collect();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(BadgeError::InvalidPayload(format!(
+                "事件负载不符合 {} 的 Schema: {}",
+                code,
+                errors.join("; ")
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn definition(code: &str, schema: Option<serde_json::Value>) -> EventTypeDefinition {
+        EventTypeDefinition {
+            code: code.to_string(),
+            name: code.to_string(),
+            service_group: "engagement".to_string(),
+            payload_schema: schema,
+        }
+    }
+
+    #[test]
+    fn test_validate_payload_against_schema() {
+        let registry = EventTypeRegistry::new();
+        registry.replace_all(vec![definition(
+            "vip_login",
+            Some(json!({
+                "type": "object",
+                "required": ["level"],
+                "properties": {"level": {"type": "integer", "minimum": 1}}
+            })),
+        )]);
+
+        assert!(registry.contains("vip_login"));
+        assert!(registry.validate_payload("vip_login", &json!({"level": 3})).is_ok());
+
+        let err = registry
+            .validate_payload("vip_login", &json!({"level": 0}))
+            .unwrap_err();
+        assert!(err.to_string().contains("/level"));
+        assert!(matches!(
+            registry.validate_payload("vip_login", &json!({})),
+            Err(BadgeError::InvalidPayload(_))
+        ));
+    }
+
+    #[test]
+    fn test_types_without_schema_accept_any_payload() {
+        let registry = EventTypeRegistry::new();
+        registry.replace_all(vec![definition("checkin", None)]);
+
+        assert!(registry.validate_payload("checkin", &json!("anything")).is_ok());
+        // 未注册的事件类型不做校验，是否处理由处理器决定
+        assert!(registry.validate_payload("unknown", &json!(1)).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_schema_is_not_registered() {
+        let registry = EventTypeRegistry::new();
+        let count = registry.replace_all(vec![
+            definition("broken", Some(json!({"type": "not-a-type"}))),
+            definition("ok", None),
+        ]);
+
+        assert_eq!(count, 1);
+        assert!(!registry.contains("broken"));
+        assert!(registry.contains("ok"));
+    }
+}
@@ -12,8 +12,10 @@ use tracing::{error, info, warn};
 
 use crate::error::BadgeError;
 
+use super::event_types::{EventTypeDefinition, EventTypeRegistry};
 use super::mapping::RuleBadgeMapping;
 use super::models::BadgeGrant;
+use super::reload::RuleReloadListener;
 
 /// 规则加载器
 ///
@@ -23,6 +25,8 @@ pub struct RuleLoader {
     db_pool: PgPool,
     service_group: String,
     rule_mapping: Arc<RuleBadgeMapping>,
+    /// 本服务组的事件类型注册表，配置后随规则一起刷新
+    event_types: Option<Arc<EventTypeRegistry>>,
     refresh_interval: Duration,
     initial_timeout: Duration,
 }
@@ -39,11 +43,18 @@ impl RuleLoader {
             db_pool,
             service_group: service_group.into(),
             rule_mapping,
+            event_types: None,
             refresh_interval: Duration::from_secs(refresh_interval_secs),
             initial_timeout: Duration::from_secs(initial_timeout_secs),
         }
     }
 
+    /// 同时维护事件类型注册表（自定义事件路由与负载校验）
+    pub fn with_event_type_registry(mut self, registry: Arc<EventTypeRegistry>) -> Self {
+        self.event_types = Some(registry);
+        self
+    }
+
     /// 首次加载规则（阻塞，带超时）
     ///
     /// 服务启动时调用，确保规则加载完成后再处理事件。
@@ -124,8 +135,51 @@ impl RuleLoader {
         });
     }
 
+    /// 启动规则刷新事件监听任务
+    ///
+    /// 管理后台发布、停用、修改规则时会发送刷新事件，收到属于本服务组的事件后
+    /// 立即刷新，无需等待下一次定时刷新。
+    pub fn start_reload_listener(
+        self: Arc<Self>,
+        listener: RuleReloadListener,
+        shutdown: watch::Receiver<bool>,
+    ) {
+        let loader = self.clone();
+
+        tokio::spawn(async move {
+            let service_group = loader.service_group.clone();
+            listener
+                .run(shutdown, Some(service_group), |_event| {
+                    let loader = loader.clone();
+                    async move {
+                        if let Err(e) = loader.reload_now().await {
+                            warn!(
+                                service_group = %loader.service_group,
+                                error = %e,
+                                "Kafka 触发规则刷新失败"
+                            );
+                        }
+                    }
+                })
+                .await;
+
+            info!(service_group = %loader.service_group, "规则刷新事件监听已停止");
+        });
+    }
+
     /// 从数据库加载规则并更新内存映射
     async fn load_rules_from_db(&self) -> Result<usize, BadgeError> {
+        // 先刷新事件类型，保证新注册事件的规则生效时已能通过路由和校验
+        if let Some(ref registry) = self.event_types {
+            let definitions = self.query_event_types().await?;
+            let registered = registry.replace_all(definitions);
+            info!(
+                service_group = %self.service_group,
+                event_type_count = registered,
+                "事件类型刷新完成"
+            );
+        }
+
         let rules = self.query_active_rules().await?;
         let count = rules.len();
 
@@ -201,6 +255,39 @@ impl RuleLoader {
 
         Ok(rules)
     }
+
+    /// 查询本服务组已启用的事件类型
+    async fn query_event_types(&self) -> Result<Vec<EventTypeDefinition>, BadgeError> {
+        let rows = sqlx::query_as::<_, EventTypeRow>(
+            r#"
+            SELECT code, name, service_group, payload_schema
+            FROM event_types
+            WHERE service_group = $1 AND enabled = TRUE
+            "#,
+        )
+        .bind(&self.service_group)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| EventTypeDefinition {
+                code: row.code,
+                name: row.name,
+                service_group: row.service_group,
+                payload_schema: row.payload_schema,
+            })
+            .collect())
+    }
+}
+
+/// 事件类型查询结果行
+#[derive(sqlx::FromRow)]
+struct EventTypeRow {
+    code: String,
+    name: String,
+    service_group: String,
+    payload_schema: Option<serde_json::Value>,
 }
 
 /// 数据库查询结果行
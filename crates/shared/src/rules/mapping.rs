@@ -2,7 +2,7 @@
 //!
 //! 使用 DashMap 实现高并发读写，支持按事件类型索引规则。
 
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 
 use chrono::{DateTime, Utc};
 use crossbeam_utils::atomic::AtomicCell;
@@ -21,6 +21,8 @@ pub struct RuleBadgeMapping {
     last_loaded_at: AtomicCell<Option<DateTime<Utc>>>,
     /// 规则总数
     rule_count: AtomicUsize,
+    /// 每次全量替换后递增，供下游缓存（如内嵌规则引擎）判断是否需要重建
+    generation: AtomicU64,
 }
 
 impl RuleBadgeMapping {
@@ -29,6 +31,7 @@ impl RuleBadgeMapping {
             mappings: DashMap::new(),
             last_loaded_at: AtomicCell::new(None),
             rule_count: AtomicUsize::new(0),
+            generation: AtomicU64::new(0),
         }
     }
 
@@ -67,6 +70,20 @@ impl RuleBadgeMapping {
         // 更新统计
         self.rule_count.store(rules.len(), Ordering::SeqCst);
         self.last_loaded_at.store(Some(Utc::now()));
+        self.generation.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// 获取所有规则
+    pub fn all_rules(&self) -> Vec<BadgeGrant> {
+        self.mappings
+            .iter()
+            .flat_map(|entry| entry.value().clone())
+            .collect()
+    }
+
+    /// 当前映射的代数，每次 `replace_all` 后加一
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
     }
 
     /// 获取加载状态
@@ -177,6 +194,23 @@ mod tests {
         assert_eq!(event_types, vec!["login", "purchase"]);
     }
 
+    #[test]
+    fn test_all_rules_and_generation() {
+        let mapping = RuleBadgeMapping::new();
+        assert_eq!(mapping.generation(), 0);
+
+        mapping.replace_all(vec![create_test_rule(1, "login"), create_test_rule(2, "purchase")]);
+        assert_eq!(mapping.generation(), 1);
+
+        let mut ids: Vec<i64> = mapping.all_rules().iter().map(|r| r.rule_id).collect();
+        ids.sort();
+        assert_eq!(ids, vec![1, 2]);
+
+        mapping.replace_all(vec![]);
+        assert_eq!(mapping.generation(), 2);
+        assert!(mapping.all_rules().is_empty());
+    }
+
     #[test]
     fn test_replace_clears_old_rules() {
         let mapping = RuleBadgeMapping::new();
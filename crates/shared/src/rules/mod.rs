@@ -2,12 +2,16 @@
 //!
 //! 提供从数据库动态加载规则、内存缓存、校验等功能。
 
+pub mod event_types;
 pub mod loader;
 pub mod mapping;
 pub mod models;
+pub mod reload;
 pub mod validator;
 
+pub use event_types::{EventTypeDefinition, EventTypeRegistry};
 pub use loader::RuleLoader;
 pub use mapping::RuleBadgeMapping;
 pub use models::*;
+pub use reload::{RuleReloadListener, RuleReloadPublisher};
 pub use validator::RuleValidator;
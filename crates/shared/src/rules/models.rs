@@ -172,11 +172,51 @@ pub struct RuleReloadEvent {
     pub service_group: Option<String>,
     /// 目标事件类型，None 表示所有事件类型
     pub event_type: Option<String>,
+    /// 触发刷新的规则 ID，None 表示非单条规则变更（如全量刷新）
+    #[serde(default)]
+    pub rule_id: Option<i64>,
     /// 触发来源标识
     pub trigger_source: String,
     pub triggered_at: DateTime<Utc>,
 }
 
+impl RuleReloadEvent {
+    /// 创建面向所有服务的刷新事件
+    pub fn new(trigger_source: impl Into<String>) -> Self {
+        Self {
+            service_group: None,
+            event_type: None,
+            rule_id: None,
+            trigger_source: trigger_source.into(),
+            triggered_at: Utc::now(),
+        }
+    }
+
+    pub fn with_service_group(mut self, service_group: impl Into<String>) -> Self {
+        self.service_group = Some(service_group.into());
+        self
+    }
+
+    pub fn with_event_type(mut self, event_type: impl Into<String>) -> Self {
+        self.event_type = Some(event_type.into());
+        self
+    }
+
+    pub fn with_rule_id(mut self, rule_id: i64) -> Self {
+        self.rule_id = Some(rule_id);
+        self
+    }
+
+    /// 判断事件是否需要指定服务组响应
+    ///
+    /// service_group 为空表示全部服务刷新，否则只刷新匹配的服务组。
+    pub fn targets(&self, service_group: &str) -> bool {
+        self.service_group
+            .as_deref()
+            .is_none_or(|group| group == service_group)
+    }
+}
+
 /// 跳过的规则信息
 ///
 /// 记录因校验失败而被跳过的规则，用于批量处理时的结果汇总。
@@ -292,4 +332,29 @@ mod tests {
         let not_found = QuotaUpdateResult::RuleNotFound { rule_id: 999 };
         assert!(!not_found.is_success());
     }
+
+    #[test]
+    fn test_rule_reload_event_targets() {
+        let all = RuleReloadEvent::new("admin");
+        assert!(all.targets("engagement"));
+        assert!(all.targets("transaction"));
+
+        let engagement = RuleReloadEvent::new("admin").with_service_group("engagement");
+        assert!(engagement.targets("engagement"));
+        assert!(!engagement.targets("transaction"));
+    }
+
+    #[test]
+    fn test_rule_reload_event_without_rule_id() {
+        // 兼容未携带 rule_id 的旧格式消息
+        let json = r#"{
+            "service_group": null,
+            "event_type": null,
+            "trigger_source": "manual",
+            "triggered_at": "2024-01-01T00:00:00Z"
+        }"#;
+        let event: RuleReloadEvent = serde_json::from_str(json).unwrap();
+        assert_eq!(event.rule_id, None);
+        assert_eq!(event.trigger_source, "manual");
+    }
 }
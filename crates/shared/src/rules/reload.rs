@@ -0,0 +1,105 @@
+//! 规则刷新通知
+//!
+//! 管理后台变更规则后向 `badge.rule.reload` 发布刷新事件，
+//! 规则引擎和各事件处理服务订阅该 topic 并即时刷新内存中的规则。
+
+use std::future::Future;
+
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+use crate::config::KafkaConfig;
+use crate::error::BadgeError;
+use crate::kafka::{KafkaConsumer, KafkaProducer, topics};
+
+use super::models::RuleReloadEvent;
+
+/// 规则刷新事件发布器
+#[derive(Clone)]
+pub struct RuleReloadPublisher {
+    producer: KafkaProducer,
+}
+
+impl RuleReloadPublisher {
+    pub fn new(producer: KafkaProducer) -> Self {
+        Self { producer }
+    }
+
+    /// 发布刷新事件
+    ///
+    /// 以服务组作为消息 key，保证同一服务组的刷新事件有序。
+    pub async fn publish(&self, event: &RuleReloadEvent) -> Result<(), BadgeError> {
+        let key = event.service_group.as_deref().unwrap_or("all");
+        self.producer
+            .send_json(topics::RULE_RELOAD, key, event)
+            .await?;
+
+        info!(
+            service_group = ?event.service_group,
+            event_type = ?event.event_type,
+            rule_id = ?event.rule_id,
+            trigger_source = %event.trigger_source,
+            "规则刷新事件已发布"
+        );
+        Ok(())
+    }
+}
+
+/// 规则刷新事件监听器
+///
+/// 使用广播消费组订阅刷新 topic，同一服务的每个实例都会收到事件。
+pub struct RuleReloadListener {
+    consumer: KafkaConsumer,
+}
+
+impl RuleReloadListener {
+    /// 创建监听器，`name` 用于区分消费组名称
+    pub fn new(config: &KafkaConfig, name: &str) -> Result<Self, BadgeError> {
+        let consumer = KafkaConsumer::new_broadcast(config, &format!("{}.rule-reload", name))?;
+        consumer.subscribe(&[topics::RULE_RELOAD])?;
+        Ok(Self { consumer })
+    }
+
+    /// 启动监听循环，直到收到 shutdown 信号
+    ///
+    /// `service_group` 为 None 时响应所有刷新事件（如规则引擎），
+    /// 否则只响应目标服务组匹配的事件。
+    pub async fn run<F, Fut>(
+        self,
+        shutdown: watch::Receiver<bool>,
+        service_group: Option<String>,
+        on_reload: F,
+    ) where
+        F: Fn(RuleReloadEvent) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        self.consumer
+            .start(shutdown, |msg| {
+                let on_reload = &on_reload;
+                let service_group = service_group.as_deref();
+                async move {
+                    let event: RuleReloadEvent = match msg.deserialize_payload() {
+                        Ok(e) => e,
+                        Err(e) => {
+                            warn!(error = %e, "规则刷新事件反序列化失败，忽略");
+                            return Ok(());
+                        }
+                    };
+
+                    if service_group.is_some_and(|group| !event.targets(group)) {
+                        return Ok(());
+                    }
+
+                    info!(
+                        trigger_source = %event.trigger_source,
+                        triggered_at = %event.triggered_at,
+                        rule_id = ?event.rule_id,
+                        "收到 Kafka 规则刷新事件"
+                    );
+                    on_reload(event).await;
+                    Ok(())
+                }
+            })
+            .await;
+    }
+}
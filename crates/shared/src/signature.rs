@@ -0,0 +1,124 @@
+//! 回调请求签名
+//!
+//! 徽章系统主动调用外部系统（如外部回调权益）时，使用 HMAC-SHA256 对请求体签名，
+//! 接收方用共享密钥校验请求来源和完整性。
+//!
+//! ## 签名规则
+//!
+//! - 签名原文：`{timestamp}.{body}`，timestamp 为 Unix 秒
+//! - 签名值：`v1=` + hex(HMAC-SHA256(secret, 签名原文))
+//! - 时间戳与签名分别放在 `X-Badge-Timestamp`、`X-Badge-Signature` 请求头中
+//!
+//! 签名包含时间戳，接收方应拒绝超出容忍窗口的请求以防止重放。
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// 时间戳请求头
+pub const TIMESTAMP_HEADER: &str = "X-Badge-Timestamp";
+/// 签名请求头
+pub const SIGNATURE_HEADER: &str = "X-Badge-Signature";
+/// 签名版本前缀，签名算法变更时递增
+const SIGNATURE_VERSION: &str = "v1=";
+/// 默认时间戳容忍窗口（秒）
+pub const DEFAULT_TOLERANCE_SECS: i64 = 300;
+
+/// 计算请求体签名
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let digest: String = new_mac(secret, timestamp, body)
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("{}{}", SIGNATURE_VERSION, digest)
+}
+
+/// 校验请求体签名
+///
+/// 时间戳与 `now` 相差超过 `tolerance_secs` 时视为重放请求，直接拒绝。
+/// 签名比较使用常量时间算法，避免时序攻击。
+pub fn verify(
+    secret: &str,
+    timestamp: i64,
+    body: &[u8],
+    signature: &str,
+    now: i64,
+    tolerance_secs: i64,
+) -> bool {
+    if (now - timestamp).abs() > tolerance_secs {
+        return false;
+    }
+
+    let Some(hex_digest) = signature.strip_prefix(SIGNATURE_VERSION) else {
+        return false;
+    };
+    let Some(expected) = decode_hex(hex_digest) else {
+        return false;
+    };
+
+    new_mac(secret, timestamp, body)
+        .verify_slice(&expected)
+        .is_ok()
+}
+
+fn new_mac(secret: &str, timestamp: i64, body: &[u8]) -> HmacSha256 {
+    // HMAC 接受任意长度的密钥，new_from_slice 不会失败
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC 接受任意长度的密钥");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    mac
+}
+
+fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "test-secret";
+    const BODY: &[u8] = br#"{"action":"grant","grant_no":"BG-001"}"#;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let ts = 1_700_000_000;
+        let signature = sign(SECRET, ts, BODY);
+
+        assert!(signature.starts_with("v1="));
+        assert_eq!(signature.len(), 3 + 64);
+        assert!(verify(SECRET, ts, BODY, &signature, ts + 10, DEFAULT_TOLERANCE_SECS));
+    }
+
+    #[test]
+    fn test_verify_rejects_tampering() {
+        let ts = 1_700_000_000;
+        let signature = sign(SECRET, ts, BODY);
+
+        assert!(!verify("other-secret", ts, BODY, &signature, ts, DEFAULT_TOLERANCE_SECS));
+        assert!(!verify(SECRET, ts, b"{}", &signature, ts, DEFAULT_TOLERANCE_SECS));
+        assert!(!verify(SECRET, ts + 1, BODY, &signature, ts, DEFAULT_TOLERANCE_SECS));
+        assert!(!verify(SECRET, ts, BODY, "v1=zz", ts, DEFAULT_TOLERANCE_SECS));
+        assert!(!verify(SECRET, ts, BODY, &signature[3..], ts, DEFAULT_TOLERANCE_SECS));
+    }
+
+    #[test]
+    fn test_verify_rejects_stale_timestamp() {
+        let ts = 1_700_000_000;
+        let signature = sign(SECRET, ts, BODY);
+
+        assert!(!verify(SECRET, ts, BODY, &signature, ts + 301, DEFAULT_TOLERANCE_SECS));
+        assert!(!verify(SECRET, ts, BODY, &signature, ts - 301, DEFAULT_TOLERANCE_SECS));
+    }
+}
@@ -396,6 +396,143 @@ impl TestAssertions {
     }
 }
 
+// ==================== SMTP 测试服务 ====================
+
+/// SMTP 测试服务收到的邮件
+#[derive(Debug, Clone)]
+pub struct ReceivedMail {
+    /// MAIL FROM 地址
+    pub from: String,
+    /// RCPT TO 地址列表
+    pub recipients: Vec<String>,
+    /// DATA 阶段收到的原始邮件（含邮件头）
+    pub data: String,
+}
+
+impl ReceivedMail {
+    /// 按名称查找邮件头（不区分大小写）
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.data
+            .lines()
+            .take_while(|line| !line.is_empty())
+            .find_map(|line| {
+                let (key, value) = line.split_once(':')?;
+                key.eq_ignore_ascii_case(name).then(|| value.trim())
+            })
+    }
+}
+
+/// 进程内 SMTP 测试服务
+///
+/// 在本地随机端口上监听明文 SMTP，接受任意认证和收件人，
+/// 把收到的邮件保存在内存中供断言使用。只实现邮件客户端发送所需的最小命令集。
+pub struct SmtpSink {
+    port: u16,
+    messages: Arc<RwLock<Vec<ReceivedMail>>>,
+}
+
+impl SmtpSink {
+    /// 启动测试服务
+    pub async fn start() -> std::io::Result<Self> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let port = listener.local_addr()?.port();
+        let messages = Arc::new(RwLock::new(Vec::new()));
+
+        let store = messages.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let store = store.clone();
+                tokio::spawn(async move {
+                    let _ = Self::handle_connection(stream, store).await;
+                });
+            }
+        });
+
+        Ok(Self { port, messages })
+    }
+
+    /// 监听端口
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// 已收到的全部邮件
+    pub async fn messages(&self) -> Vec<ReceivedMail> {
+        self.messages.read().await.clone()
+    }
+
+    async fn handle_connection(
+        stream: tokio::net::TcpStream,
+        store: Arc<RwLock<Vec<ReceivedMail>>>,
+    ) -> std::io::Result<()> {
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        let mut from = String::new();
+        let mut recipients = Vec::new();
+
+        writer.write_all(b"220 smtp-sink ESMTP\r\n").await?;
+
+        while let Some(line) = lines.next_line().await? {
+            let command = line.to_ascii_uppercase();
+            let reply: &[u8] = if command.starts_with("EHLO") {
+                b"250-smtp-sink\r\n250 AUTH PLAIN LOGIN\r\n"
+            } else if command.starts_with("HELO") || command.starts_with("NOOP") {
+                b"250 OK\r\n"
+            } else if command.starts_with("AUTH") {
+                b"235 Authentication successful\r\n"
+            } else if command.starts_with("MAIL FROM:") {
+                from = Self::extract_address(&line);
+                recipients.clear();
+                b"250 OK\r\n"
+            } else if command.starts_with("RCPT TO:") {
+                recipients.push(Self::extract_address(&line));
+                b"250 OK\r\n"
+            } else if command.starts_with("DATA") {
+                writer
+                    .write_all(b"354 End data with <CR><LF>.<CR><LF>\r\n")
+                    .await?;
+                let mut data = Vec::new();
+                while let Some(data_line) = lines.next_line().await? {
+                    if data_line == "." {
+                        break;
+                    }
+                    // 去除 SMTP 点填充
+                    let data_line = data_line.strip_prefix('.').unwrap_or(&data_line);
+                    data.push(data_line.to_string());
+                }
+                store.write().await.push(ReceivedMail {
+                    from: std::mem::take(&mut from),
+                    recipients: std::mem::take(&mut recipients),
+                    data: data.join("\r\n"),
+                });
+                b"250 OK: queued\r\n"
+            } else if command.starts_with("RSET") {
+                from.clear();
+                recipients.clear();
+                b"250 OK\r\n"
+            } else if command.starts_with("QUIT") {
+                writer.write_all(b"221 Bye\r\n").await?;
+                return Ok(());
+            } else {
+                b"502 Command not implemented\r\n"
+            };
+            writer.write_all(reply).await?;
+        }
+
+        Ok(())
+    }
+
+    /// 从 `MAIL FROM:<addr>` / `RCPT TO:<addr>` 中提取地址
+    fn extract_address(line: &str) -> String {
+        line.split_once('<')
+            .and_then(|(_, rest)| rest.split_once('>'))
+            .map(|(addr, _)| addr.to_string())
+            .unwrap_or_default()
+    }
+}
+
 // ==================== 测试 Fixture ====================
 
 /// 测试 Fixture 构建器
@@ -311,10 +311,22 @@ mod grpc_middleware_tests {
 // ============================================================================
 
 mod kafka_tracing_tests {
+    use badge_shared::observability::middleware::grpc::traced_request;
     use badge_shared::observability::middleware::kafka::{
-        extract_trace_context, inject_trace_context,
+        consumer_span, extract_trace_context, inject_trace_context,
     };
+    use opentelemetry::trace::{TraceContextExt, TracerProvider as _};
+    use opentelemetry_sdk::trace::SdkTracerProvider;
     use std::collections::HashMap;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    const TRACEPARENT: &str = "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01";
+
+    /// 启用 OpenTelemetry 层的订阅者，span 才会携带可传播的追踪上下文
+    fn otel_subscriber() -> impl tracing::Subscriber + Send + Sync {
+        let tracer = SdkTracerProvider::builder().build().tracer("test");
+        tracing_subscriber::registry().with(tracing_opentelemetry::layer().with_tracer(tracer))
+    }
 
     #[test]
     fn test_inject_trace_context() {
@@ -334,12 +346,37 @@ mod kafka_tracing_tests {
     #[test]
     fn test_extract_trace_context_with_traceparent() {
         let mut headers = HashMap::new();
-        headers.insert(
-            "traceparent".to_string(),
-            "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01".to_string(),
+        headers.insert("traceparent".to_string(), TRACEPARENT.to_string());
+
+        let context = extract_trace_context(&headers).expect("应提取到追踪上下文");
+        assert_eq!(
+            context.span().span_context().trace_id().to_string(),
+            "0af7651916cd43dd8448eb211c80319c"
         );
-        // 当前占位实现返回 None，但不应 panic
-        let _ = extract_trace_context(&headers);
+    }
+
+    #[test]
+    fn test_consumer_span_continues_upstream_trace() {
+        let headers = HashMap::from([("traceparent".to_string(), TRACEPARENT.to_string())]);
+
+        tracing::subscriber::with_default(otel_subscriber(), || {
+            let span = consumer_span("badge.engagement.events", 0, 42, &headers);
+            let _guard = span.enter();
+
+            // 消费过程中再生产的消息沿用同一个 trace id，父 span 为消费 span
+            let mut downstream = HashMap::new();
+            inject_trace_context(&mut downstream);
+            let traceparent = downstream.get("traceparent").expect("应注入 traceparent");
+            assert!(traceparent.starts_with("00-0af7651916cd43dd8448eb211c80319c-"));
+            assert!(!traceparent.contains("b7ad6b7169203331"));
+
+            // gRPC 调用同样携带追踪上下文
+            let request = traced_request(());
+            assert_eq!(
+                request.metadata().get("traceparent").unwrap().to_str().unwrap(),
+                traceparent
+            );
+        });
     }
 }
 
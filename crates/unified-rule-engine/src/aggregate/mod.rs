@@ -0,0 +1,15 @@
+//! 有状态聚合条件
+//!
+//! 支持在滑动/滚动时间窗口上对同一用户的某类事件做计数、求和、去重计数和
+//! 连续性统计，状态按"用户 + 事件类型"划分，存储可插拔（Redis / 内存）。
+
+pub mod models;
+pub mod resolver;
+pub mod store;
+
+pub use models::{AggregateCondition, AggregateFunction, TimeUnit, TimeWindow, WindowKind};
+pub use resolver::{AggregateProgress, AggregateResolver, ResolveMode};
+pub use store::{
+    AggregateBucket, AggregateSample, AggregateStateStore, InMemoryAggregateStore,
+    RedisAggregateStore,
+};
@@ -0,0 +1,352 @@
+//! 聚合节点领域模型
+//!
+//! 聚合节点描述"某用户在某时间窗口内某类事件的统计值满足条件"，
+//! 例如"30 天内购买 5 次"或"本季度累计消费 ≥ 2000"。
+
+use crate::operators::Operator;
+use chrono::{DateTime, Datelike, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::time::Duration;
+
+/// 聚合函数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AggregateFunction {
+    /// 事件次数
+    Count,
+    /// 指定字段求和
+    Sum,
+    /// 指定字段去重计数
+    DistinctCount,
+    /// 截至当前时间单位的连续活跃单位数（如连续签到天数）
+    Streak,
+}
+
+impl AggregateFunction {
+    /// 该函数是否需要指定统计字段
+    pub fn requires_field(&self) -> bool {
+        matches!(self, Self::Sum | Self::DistinctCount)
+    }
+}
+
+impl fmt::Display for AggregateFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Count => "count",
+            Self::Sum => "sum",
+            Self::DistinctCount => "distinct_count",
+            Self::Streak => "streak",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// 窗口类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowKind {
+    /// 滑动窗口：以当前时间单位为终点向前回溯 size 个单位
+    #[default]
+    Sliding,
+    /// 滚动窗口：按 size 个单位对齐切分，只统计当前所在区间（如"本季度" = 3 个月滚动窗口）
+    Tumbling,
+}
+
+/// 窗口时间单位
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeUnit {
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+}
+
+impl TimeUnit {
+    /// 计算时间点所在的单位序号（UTC）
+    ///
+    /// 序号在同一单位下单调递增，相邻序号即相邻的时间单位，
+    /// 窗口范围与连续性判断都基于该序号完成。周以周一为起点。
+    pub fn index_of(&self, ts: DateTime<Utc>) -> i64 {
+        let secs = ts.timestamp();
+        match self {
+            Self::Minute => secs.div_euclid(60),
+            Self::Hour => secs.div_euclid(3_600),
+            Self::Day => secs.div_euclid(86_400),
+            // 1970-01-01 是周四，偏移 3 天使序号在周一切换
+            Self::Week => (secs.div_euclid(86_400) + 3).div_euclid(7),
+            Self::Month => ts.year() as i64 * 12 + ts.month0() as i64,
+        }
+    }
+
+    /// 单位的近似时长，用于计算状态的保留时间
+    pub fn approx_duration(&self) -> Duration {
+        let secs = match self {
+            Self::Minute => 60,
+            Self::Hour => 3_600,
+            Self::Day => 86_400,
+            Self::Week => 7 * 86_400,
+            Self::Month => 31 * 86_400,
+        };
+        Duration::from_secs(secs)
+    }
+}
+
+impl fmt::Display for TimeUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Minute => "minute",
+            Self::Hour => "hour",
+            Self::Day => "day",
+            Self::Week => "week",
+            Self::Month => "month",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// 时间窗口定义
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TimeWindow {
+    #[serde(rename = "type", default)]
+    pub kind: WindowKind,
+    pub size: u32,
+    pub unit: TimeUnit,
+}
+
+impl TimeWindow {
+    pub fn sliding(size: u32, unit: TimeUnit) -> Self {
+        Self {
+            kind: WindowKind::Sliding,
+            size,
+            unit,
+        }
+    }
+
+    pub fn tumbling(size: u32, unit: TimeUnit) -> Self {
+        Self {
+            kind: WindowKind::Tumbling,
+            size,
+            unit,
+        }
+    }
+
+    /// 以 `current` 为当前单位序号时，窗口覆盖的序号闭区间
+    pub fn range(&self, current: i64) -> (i64, i64) {
+        let size = self.size.max(1) as i64;
+        match self.kind {
+            WindowKind::Sliding => (current - size + 1, current),
+            WindowKind::Tumbling => (current.div_euclid(size) * size, current),
+        }
+    }
+
+    /// 窗口状态需要保留的时长（多保留一个单位，避免边界处数据提前过期）
+    pub fn retention(&self) -> Duration {
+        self.unit.approx_duration() * (self.size + 1)
+    }
+}
+
+impl fmt::Display for TimeWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            WindowKind::Sliding => "sliding",
+            WindowKind::Tumbling => "tumbling",
+        };
+        write!(f, "{}:{}{}", kind, self.size, self.unit)
+    }
+}
+
+/// 聚合条件节点
+///
+/// 统计对象按"用户 + 事件类型"划分；`event_type` 为空时取当前事件的类型。
+/// 聚合值在执行前由 `AggregateResolver` 计算并写入评估上下文，
+/// 执行器只负责用 `operator`/`value` 比较聚合值，保持求值过程同步且无副作用。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AggregateCondition {
+    pub function: AggregateFunction,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event_type: Option<String>,
+    /// sum / distinct_count 的统计字段（支持点号路径）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    pub window: TimeWindow,
+    pub operator: Operator,
+    pub value: Value,
+}
+
+impl AggregateCondition {
+    pub fn new(
+        function: AggregateFunction,
+        event_type: Option<&str>,
+        field: Option<&str>,
+        window: TimeWindow,
+        operator: Operator,
+        value: impl Into<Value>,
+    ) -> Self {
+        Self {
+            function,
+            event_type: event_type.map(String::from),
+            field: field.map(String::from),
+            window,
+            operator,
+            value: value.into(),
+        }
+    }
+
+    /// 聚合值在上下文中的标识
+    ///
+    /// 只由统计口径（函数、事件类型、字段、窗口）决定，与比较条件无关，
+    /// 因此多条规则中口径相同的聚合节点共享同一个计算结果。
+    pub fn signature(&self) -> String {
+        format!(
+            "{}({}.{})@{}",
+            self.function,
+            self.event_type.as_deref().unwrap_or("$current"),
+            self.field.as_deref().unwrap_or("*"),
+            self.window
+        )
+    }
+
+    /// 进度展示的目标值
+    ///
+    /// 只有"达到某个数值"类的条件（gte / gt / eq）才有进度的概念，上限类条件返回 None。
+    /// 计数类函数的 gt 条件换算为下一个整数，例如"次数 > 4"的目标是 5 次。
+    pub fn progress_target(&self) -> Option<f64> {
+        let value = match &self.value {
+            Value::Number(n) => n.as_f64()?,
+            Value::String(s) => s.parse().ok()?,
+            _ => return None,
+        };
+        match self.operator {
+            Operator::Gte | Operator::Eq => Some(value),
+            Operator::Gt if self.function == AggregateFunction::Sum => Some(value),
+            Operator::Gt => Some(value.floor() + 1.0),
+            _ => None,
+        }
+    }
+}
+
+/// 归一化事件类型名称
+///
+/// 规则中习惯写数据库键名（`checkin`、`order_cancel`），事件上下文中则是
+/// SCREAMING_SNAKE_CASE（`CHECK_IN`、`ORDER_CANCEL`），去掉下划线并转小写后两者一致。
+pub fn normalize_event_type(event_type: &str) -> String {
+    event_type
+        .chars()
+        .filter(|c| *c != '_')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_aggregate_condition_deserialization() {
+        let json = r#"
+        {
+            "function": "sum",
+            "event_type": "purchase",
+            "field": "amount",
+            "window": {"type": "tumbling", "size": 3, "unit": "month"},
+            "operator": "gte",
+            "value": 2000
+        }
+        "#;
+
+        let cond: AggregateCondition = serde_json::from_str(json).unwrap();
+        assert_eq!(cond.function, AggregateFunction::Sum);
+        assert_eq!(cond.field.as_deref(), Some("amount"));
+        assert_eq!(cond.window, TimeWindow::tumbling(3, TimeUnit::Month));
+        assert_eq!(cond.signature(), "sum(purchase.amount)@tumbling:3month");
+    }
+
+    #[test]
+    fn test_window_kind_defaults_to_sliding() {
+        let window: TimeWindow = serde_json::from_str(r#"{"size": 30, "unit": "day"}"#).unwrap();
+        assert_eq!(window.kind, WindowKind::Sliding);
+    }
+
+    #[test]
+    fn test_time_unit_index() {
+        let monday = Utc.with_ymd_and_hms(2024, 1, 15, 10, 0, 0).unwrap();
+        let sunday = Utc.with_ymd_and_hms(2024, 1, 21, 23, 59, 59).unwrap();
+        let next_monday = Utc.with_ymd_and_hms(2024, 1, 22, 0, 0, 0).unwrap();
+
+        assert_eq!(
+            TimeUnit::Week.index_of(monday),
+            TimeUnit::Week.index_of(sunday)
+        );
+        assert_eq!(
+            TimeUnit::Week.index_of(next_monday),
+            TimeUnit::Week.index_of(monday) + 1
+        );
+        assert_eq!(
+            TimeUnit::Day.index_of(next_monday),
+            TimeUnit::Day.index_of(sunday) + 1
+        );
+
+        let dec = Utc.with_ymd_and_hms(2023, 12, 31, 0, 0, 0).unwrap();
+        let jan = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(
+            TimeUnit::Month.index_of(jan),
+            TimeUnit::Month.index_of(dec) + 1
+        );
+    }
+
+    #[test]
+    fn test_window_range() {
+        assert_eq!(TimeWindow::sliding(30, TimeUnit::Day).range(100), (71, 100));
+        // 2024 年 5 月：季度（3 个月滚动窗口）从 4 月开始
+        let may = Utc.with_ymd_and_hms(2024, 5, 20, 0, 0, 0).unwrap();
+        let apr = Utc.with_ymd_and_hms(2024, 4, 1, 0, 0, 0).unwrap();
+        let current = TimeUnit::Month.index_of(may);
+        assert_eq!(
+            TimeWindow::tumbling(3, TimeUnit::Month).range(current),
+            (TimeUnit::Month.index_of(apr), current)
+        );
+    }
+
+    #[test]
+    fn test_progress_target() {
+        let window = TimeWindow::sliding(7, TimeUnit::Day);
+        let count = |operator: Operator, value: Value| {
+            AggregateCondition::new(
+                AggregateFunction::Count,
+                Some("checkin"),
+                None,
+                window,
+                operator,
+                value,
+            )
+        };
+
+        assert_eq!(count(Operator::Gte, 5.into()).progress_target(), Some(5.0));
+        assert_eq!(count(Operator::Gt, 4.into()).progress_target(), Some(5.0));
+        assert_eq!(count(Operator::Lt, 4.into()).progress_target(), None);
+        assert_eq!(count(Operator::Gte, "abc".into()).progress_target(), None);
+
+        let sum = AggregateCondition::new(
+            AggregateFunction::Sum,
+            Some("purchase"),
+            Some("amount"),
+            window,
+            Operator::Gt,
+            99.5,
+        );
+        assert_eq!(sum.progress_target(), Some(99.5));
+    }
+
+    #[test]
+    fn test_normalize_event_type() {
+        assert_eq!(normalize_event_type("CHECK_IN"), "checkin");
+        assert_eq!(normalize_event_type("checkin"), "checkin");
+        assert_eq!(normalize_event_type("ORDER_CANCEL"), "ordercancel");
+        assert_eq!(normalize_event_type("order_cancel"), "ordercancel");
+    }
+}
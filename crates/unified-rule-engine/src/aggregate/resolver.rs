@@ -0,0 +1,615 @@
+//! 聚合值计算
+//!
+//! 在规则执行前，根据当前事件更新各聚合节点的状态，并把计算出的聚合值写入
+//! 评估上下文。执行器本身保持同步、无副作用，只负责比较。
+
+use super::models::{AggregateCondition, AggregateFunction, normalize_event_type};
+use super::store::{AggregateBucket, AggregateSample, AggregateStateStore, InMemoryAggregateStore};
+use crate::compiler::CompiledRule;
+use crate::error::Result;
+use crate::models::EvaluationContext;
+use crate::operators::Operator;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// 聚合计算模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResolveMode {
+    /// 记录当前事件并计算聚合值（正式评估）
+    Record,
+    /// 只把当前事件临时计入结果，不写入状态（规则测试）
+    DryRun,
+}
+
+/// 当前事件中与聚合相关的信息
+struct EventInfo {
+    user_id: String,
+    event_type: Option<String>,
+    event_id: Option<String>,
+    timestamp: DateTime<Utc>,
+}
+
+impl EventInfo {
+    /// 从评估上下文提取，兼容 `EventPayload::to_evaluation_context` 的顶层字段
+    /// 和 `user.id` / `event.type` 嵌套写法
+    fn from_context(context: &EvaluationContext) -> Option<Self> {
+        let str_field = |paths: &[&str]| {
+            paths
+                .iter()
+                .find_map(|p| context.get_field(p).and_then(Value::as_str))
+                .filter(|s| !s.is_empty())
+                .map(String::from)
+        };
+
+        let user_id = str_field(&["user_id", "user.id"])?;
+        let timestamp = str_field(&["timestamp", "event.timestamp"])
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(Utc::now);
+
+        Some(Self {
+            user_id,
+            event_type: str_field(&["event_type", "event.type"]).map(|t| normalize_event_type(&t)),
+            event_id: str_field(&["event_id"]),
+            timestamp,
+        })
+    }
+}
+
+/// 同一状态键下需要计算的聚合节点
+///
+/// 函数不同但事件类型、字段、窗口相同的聚合节点共享一份状态，
+/// 分组后每个状态键只记录、读取一次。
+struct StateGroup<'a> {
+    event_type: Option<String>,
+    specs: Vec<&'a AggregateCondition>,
+}
+
+/// 聚合状态键：用户 + 事件类型 + 统计字段 + 窗口
+fn state_key(user_id: &str, event_type: Option<&str>, spec: &AggregateCondition) -> String {
+    format!(
+        "{}:{}:{}:{}",
+        user_id,
+        event_type.unwrap_or("*"),
+        spec.field.as_deref().unwrap_or("*"),
+        spec.window
+    )
+}
+
+/// 聚合条件的当前进度
+#[derive(Debug, Clone)]
+pub struct AggregateProgress {
+    pub condition: AggregateCondition,
+    pub current: f64,
+    pub target: f64,
+}
+
+impl AggregateProgress {
+    /// 当前值是否已满足条件
+    pub fn achieved(&self) -> bool {
+        match self.condition.operator {
+            Operator::Eq => self.current == self.target,
+            Operator::Gt if self.condition.function == AggregateFunction::Sum => {
+                self.current > self.target
+            }
+            _ => self.current >= self.target,
+        }
+    }
+
+    /// 完成百分比（0 ~ 100）
+    pub fn percentage(&self) -> f64 {
+        if self.achieved() {
+            return 100.0;
+        }
+        if self.target <= 0.0 {
+            return 0.0;
+        }
+        (self.current / self.target * 100.0).clamp(0.0, 100.0)
+    }
+}
+
+/// 聚合值解析器
+pub struct AggregateResolver {
+    store: Arc<dyn AggregateStateStore>,
+}
+
+impl AggregateResolver {
+    pub fn new(store: Arc<dyn AggregateStateStore>) -> Self {
+        Self { store }
+    }
+
+    /// 使用内存状态存储
+    pub fn in_memory() -> Self {
+        Self::new(Arc::new(InMemoryAggregateStore::new()))
+    }
+
+    /// 计算规则中所有聚合节点的值并写入上下文
+    pub async fn resolve(
+        &self,
+        rules: &[&CompiledRule],
+        context: &mut EvaluationContext,
+        mode: ResolveMode,
+    ) -> Result<()> {
+        if rules.iter().all(|r| !r.has_aggregates()) {
+            return Ok(());
+        }
+
+        let Some(event) = EventInfo::from_context(context) else {
+            // 没有用户标识无法定位状态，聚合值保持缺失，聚合条件按不满足处理
+            warn!("评估上下文缺少 user_id，跳过聚合计算");
+            return Ok(());
+        };
+
+        let mut groups: HashMap<String, StateGroup> = HashMap::new();
+        for spec in rules.iter().flat_map(|r| r.aggregates.iter()) {
+            let event_type = spec
+                .event_type
+                .as_deref()
+                .map(normalize_event_type)
+                .or_else(|| event.event_type.clone());
+            let key = state_key(&event.user_id, event_type.as_deref(), spec);
+            groups
+                .entry(key)
+                .or_insert_with(|| StateGroup {
+                    event_type,
+                    specs: Vec::new(),
+                })
+                .specs
+                .push(spec);
+        }
+
+        for (key, group) in groups {
+            self.resolve_group(&key, &group, &event, context, mode)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// 读取用户在规则各聚合节点上的当前进度，不记录任何事件
+    ///
+    /// `event_type` 为空的节点统计口径取决于触发事件，脱离事件无法定位状态；
+    /// 上限类条件没有进度的概念。两者都会被跳过。
+    pub async fn progress(
+        &self,
+        rule: &CompiledRule,
+        user_id: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<AggregateProgress>> {
+        let mut progress = Vec::new();
+
+        for spec in &rule.aggregates {
+            let (Some(event_type), Some(target)) =
+                (spec.event_type.as_deref(), spec.progress_target())
+            else {
+                continue;
+            };
+
+            let key = state_key(user_id, Some(&normalize_event_type(event_type)), spec);
+            let mut current = spec.window.unit.index_of(now);
+            let (from, to) = spec.window.range(current);
+            let buckets = self
+                .store
+                .load(
+                    &key,
+                    from,
+                    to,
+                    spec.function == AggregateFunction::DistinctCount,
+                )
+                .await?;
+
+            // 当前单位还没有活动时连续记录并未中断（如今天尚未签到），从上一个单位往回数
+            if spec.function == AggregateFunction::Streak
+                && !buckets.iter().any(|b| b.index == current && b.count > 0)
+            {
+                current -= 1;
+            }
+
+            let value = Self::compute(spec.function, &buckets, current, from);
+            progress.push(AggregateProgress {
+                condition: spec.clone(),
+                current: value.as_f64().unwrap_or_default(),
+                target,
+            });
+        }
+
+        Ok(progress)
+    }
+
+    async fn resolve_group(
+        &self,
+        key: &str,
+        group: &StateGroup<'_>,
+        event: &EventInfo,
+        context: &mut EvaluationContext,
+        mode: ResolveMode,
+    ) -> Result<()> {
+        // 同一组的字段与窗口一致，取第一个节点即可
+        let first = group.specs[0];
+        let window = first.window;
+        let current = window.unit.index_of(event.timestamp);
+        let (from, to) = window.range(current);
+
+        let sample = Self::sample_for(first, group, event, context);
+        let with_distinct = group
+            .specs
+            .iter()
+            .any(|s| s.function == AggregateFunction::DistinctCount);
+
+        if mode == ResolveMode::Record
+            && let Some(ref sample) = sample
+        {
+            let recorded = self
+                .store
+                .record(
+                    key,
+                    current,
+                    sample,
+                    event.event_id.as_deref(),
+                    window.retention(),
+                )
+                .await?;
+            if !recorded {
+                debug!(key = %key, event_id = ?event.event_id, "事件已计入聚合状态，跳过重复记录");
+            }
+        }
+
+        let mut buckets = self.store.load(key, from, to, with_distinct).await?;
+
+        if mode == ResolveMode::DryRun
+            && let Some(ref sample) = sample
+        {
+            match buckets.iter_mut().find(|b| b.index == current) {
+                Some(bucket) => bucket.apply(sample),
+                None => {
+                    let mut bucket = AggregateBucket {
+                        index: current,
+                        ..Default::default()
+                    };
+                    bucket.apply(sample);
+                    buckets.push(bucket);
+                }
+            }
+        }
+
+        for spec in &group.specs {
+            let value = Self::compute(spec.function, &buckets, current, from);
+            context.set_aggregate(spec.signature(), value);
+        }
+
+        Ok(())
+    }
+
+    /// 当前事件对该状态的贡献；事件类型不符或缺少统计字段时不计入
+    fn sample_for(
+        spec: &AggregateCondition,
+        group: &StateGroup<'_>,
+        event: &EventInfo,
+        context: &EvaluationContext,
+    ) -> Option<AggregateSample> {
+        if group.event_type.is_some() && group.event_type != event.event_type {
+            return None;
+        }
+
+        let Some(ref field) = spec.field else {
+            return Some(AggregateSample::default());
+        };
+
+        let value = context.get_field(field).filter(|v| !v.is_null())?;
+        Some(AggregateSample {
+            value: match value {
+                Value::Number(n) => n.as_f64(),
+                Value::String(s) => s.parse().ok(),
+                _ => None,
+            },
+            distinct: Some(match value {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            }),
+        })
+    }
+
+    fn compute(
+        function: AggregateFunction,
+        buckets: &[AggregateBucket],
+        current: i64,
+        from: i64,
+    ) -> Value {
+        match function {
+            AggregateFunction::Count => Value::from(buckets.iter().map(|b| b.count).sum::<i64>()),
+            AggregateFunction::Sum => Value::from(buckets.iter().map(|b| b.sum).sum::<f64>()),
+            AggregateFunction::DistinctCount => {
+                let mut distinct = std::collections::HashSet::new();
+                for bucket in buckets {
+                    distinct.extend(bucket.distinct.iter());
+                }
+                Value::from(distinct.len() as i64)
+            }
+            AggregateFunction::Streak => {
+                let active: std::collections::HashSet<i64> = buckets
+                    .iter()
+                    .filter(|b| b.count > 0)
+                    .map(|b| b.index)
+                    .collect();
+                let mut streak = 0i64;
+                let mut index = current;
+                while index >= from && active.contains(&index) {
+                    streak += 1;
+                    index -= 1;
+                }
+                Value::from(streak)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aggregate::models::{TimeUnit, TimeWindow};
+    use crate::compiler::RuleCompiler;
+    use crate::models::{Rule, RuleNode};
+    use crate::operators::Operator;
+    use serde_json::json;
+
+    fn compile(spec: AggregateCondition) -> CompiledRule {
+        let rule = Rule::new("agg", RuleNode::Aggregate(spec));
+        RuleCompiler::new().compile(rule).unwrap()
+    }
+
+    fn context(event_id: &str, event_type: &str, ts: &str, data: Value) -> EvaluationContext {
+        let mut value = json!({
+            "event_id": event_id,
+            "event_type": event_type,
+            "user_id": "user-1",
+            "timestamp": ts,
+        });
+        if let (Value::Object(base), Value::Object(extra)) = (&mut value, data) {
+            base.extend(extra);
+        }
+        EvaluationContext::new(value)
+    }
+
+    #[tokio::test]
+    async fn test_count_and_sum_within_window() {
+        let resolver = AggregateResolver::in_memory();
+        let count = AggregateCondition::new(
+            AggregateFunction::Count,
+            Some("purchase"),
+            None,
+            TimeWindow::sliding(30, TimeUnit::Day),
+            Operator::Gte,
+            3,
+        );
+        let sum = AggregateCondition::new(
+            AggregateFunction::Sum,
+            Some("purchase"),
+            Some("amount"),
+            TimeWindow::sliding(30, TimeUnit::Day),
+            Operator::Gte,
+            300,
+        );
+        let count_rule = compile(count.clone());
+        let sum_rule = compile(sum.clone());
+
+        // 第一笔在窗口之外
+        let events = [
+            ("e1", "2024-01-01T10:00:00Z", 500),
+            ("e2", "2024-02-10T10:00:00Z", 100),
+            ("e3", "2024-02-20T10:00:00Z", 150),
+        ];
+        let mut ctx = EvaluationContext::default();
+        for (id, ts, amount) in events {
+            ctx = context(id, "PURCHASE", ts, json!({ "amount": amount }));
+            resolver
+                .resolve(&[&count_rule, &sum_rule], &mut ctx, ResolveMode::Record)
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(ctx.get_aggregate(&count.signature()), Some(&json!(2)));
+        assert_eq!(ctx.get_aggregate(&sum.signature()), Some(&json!(250.0)));
+    }
+
+    #[tokio::test]
+    async fn test_streak_breaks_on_gap() {
+        let resolver = AggregateResolver::in_memory();
+        let streak = AggregateCondition::new(
+            AggregateFunction::Streak,
+            None,
+            None,
+            TimeWindow::sliding(7, TimeUnit::Day),
+            Operator::Gte,
+            3,
+        );
+        let rule = compile(streak.clone());
+
+        let days = ["2024-03-01", "2024-03-03", "2024-03-04", "2024-03-05"];
+        let mut ctx = EvaluationContext::default();
+        for (i, day) in days.iter().enumerate() {
+            ctx = context(
+                &format!("e{}", i),
+                "CHECK_IN",
+                &format!("{}T08:00:00Z", day),
+                json!({}),
+            );
+            resolver
+                .resolve(&[&rule], &mut ctx, ResolveMode::Record)
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(ctx.get_aggregate(&streak.signature()), Some(&json!(3)));
+    }
+
+    #[tokio::test]
+    async fn test_distinct_count_and_duplicate_event() {
+        let resolver = AggregateResolver::in_memory();
+        let distinct = AggregateCondition::new(
+            AggregateFunction::DistinctCount,
+            Some("page_view"),
+            Some("page_id"),
+            TimeWindow::tumbling(1, TimeUnit::Week),
+            Operator::Gte,
+            2,
+        );
+        let rule = compile(distinct.clone());
+
+        let mut ctx = EvaluationContext::default();
+        for (id, page) in [("e1", "a"), ("e2", "a"), ("e2", "b"), ("e3", "c")] {
+            ctx = context(
+                id,
+                "PAGE_VIEW",
+                "2024-03-05T08:00:00Z",
+                json!({ "page_id": page }),
+            );
+            resolver
+                .resolve(&[&rule], &mut ctx, ResolveMode::Record)
+                .await
+                .unwrap();
+        }
+
+        // e2 重投时携带的 b 不会被计入
+        assert_eq!(ctx.get_aggregate(&distinct.signature()), Some(&json!(2)));
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_does_not_persist() {
+        let resolver = AggregateResolver::in_memory();
+        let count = AggregateCondition::new(
+            AggregateFunction::Count,
+            Some("purchase"),
+            None,
+            TimeWindow::sliding(1, TimeUnit::Day),
+            Operator::Gte,
+            1,
+        );
+        let rule = compile(count.clone());
+
+        for id in ["e1", "e2"] {
+            let mut ctx = context(id, "PURCHASE", "2024-03-05T08:00:00Z", json!({}));
+            resolver
+                .resolve(&[&rule], &mut ctx, ResolveMode::DryRun)
+                .await
+                .unwrap();
+            assert_eq!(ctx.get_aggregate(&count.signature()), Some(&json!(1)));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_other_event_type_is_not_recorded() {
+        let resolver = AggregateResolver::in_memory();
+        let count = AggregateCondition::new(
+            AggregateFunction::Count,
+            Some("purchase"),
+            None,
+            TimeWindow::sliding(1, TimeUnit::Day),
+            Operator::Gte,
+            1,
+        );
+        let rule = compile(count.clone());
+
+        let mut ctx = context("e1", "CHECK_IN", "2024-03-05T08:00:00Z", json!({}));
+        resolver
+            .resolve(&[&rule], &mut ctx, ResolveMode::Record)
+            .await
+            .unwrap();
+
+        assert_eq!(ctx.get_aggregate(&count.signature()), Some(&json!(0)));
+    }
+
+    #[tokio::test]
+    async fn test_progress_reads_without_recording() {
+        let resolver = AggregateResolver::in_memory();
+        let count = AggregateCondition::new(
+            AggregateFunction::Count,
+            Some("check_in"),
+            None,
+            TimeWindow::sliding(30, TimeUnit::Day),
+            Operator::Gte,
+            5,
+        );
+        let rule = compile(count);
+
+        for (id, ts) in [
+            ("e1", "2024-03-01T08:00:00Z"),
+            ("e2", "2024-03-02T08:00:00Z"),
+        ] {
+            let mut ctx = context(id, "CHECK_IN", ts, json!({}));
+            resolver
+                .resolve(&[&rule], &mut ctx, ResolveMode::Record)
+                .await
+                .unwrap();
+        }
+
+        let now = DateTime::parse_from_rfc3339("2024-03-03T08:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        for _ in 0..2 {
+            let progress = resolver.progress(&rule, "user-1", now).await.unwrap();
+            assert_eq!(progress.len(), 1);
+            assert_eq!(progress[0].current, 2.0);
+            assert_eq!(progress[0].target, 5.0);
+            assert_eq!(progress[0].percentage(), 40.0);
+            assert!(!progress[0].achieved());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_streak_progress_counts_until_yesterday() {
+        let resolver = AggregateResolver::in_memory();
+        let streak = AggregateCondition::new(
+            AggregateFunction::Streak,
+            Some("check_in"),
+            None,
+            TimeWindow::sliding(7, TimeUnit::Day),
+            Operator::Gte,
+            7,
+        );
+        // 没有指定事件类型的节点无法脱离事件计算进度
+        let current_type = AggregateCondition::new(
+            AggregateFunction::Count,
+            None,
+            None,
+            TimeWindow::sliding(7, TimeUnit::Day),
+            Operator::Gte,
+            3,
+        );
+        let rule = RuleCompiler::new()
+            .compile(Rule::new(
+                "agg",
+                RuleNode::Group(crate::models::LogicalGroup {
+                    operator: crate::operators::LogicalOperator::And,
+                    children: vec![
+                        RuleNode::Aggregate(streak),
+                        RuleNode::Aggregate(current_type),
+                    ],
+                }),
+            ))
+            .unwrap();
+
+        for (i, day) in ["2024-03-03", "2024-03-04"].iter().enumerate() {
+            let mut ctx = context(
+                &format!("e{}", i),
+                "CHECK_IN",
+                &format!("{}T08:00:00Z", day),
+                json!({}),
+            );
+            resolver
+                .resolve(&[&rule], &mut ctx, ResolveMode::Record)
+                .await
+                .unwrap();
+        }
+
+        let now = DateTime::parse_from_rfc3339("2024-03-05T08:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let progress = resolver.progress(&rule, "user-1", now).await.unwrap();
+        assert_eq!(progress.len(), 1);
+        assert_eq!(progress[0].condition.function, AggregateFunction::Streak);
+        assert_eq!(progress[0].current, 2.0);
+    }
+}
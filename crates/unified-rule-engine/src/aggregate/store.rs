@@ -0,0 +1,443 @@
+//! 聚合状态存储
+//!
+//! 按"状态键 + 时间单位序号"维护统计桶。生产环境使用 Redis（经由
+//! `badge_shared::cache::Cache`），测试和单机场景使用内存实现。
+
+use crate::error::{Result, RuleError};
+use async_trait::async_trait;
+use badge_shared::cache::{Cache, CacheKey};
+use dashmap::{DashMap, DashSet};
+use std::collections::{BTreeMap, HashSet};
+use std::time::Duration;
+
+/// 单个时间单位内的统计桶
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AggregateBucket {
+    /// 时间单位序号
+    pub index: i64,
+    /// 事件次数
+    pub count: i64,
+    /// 统计字段累计值
+    pub sum: f64,
+    /// 统计字段的去重取值
+    pub distinct: HashSet<String>,
+}
+
+/// 一次事件对统计桶的贡献
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AggregateSample {
+    /// 参与求和的数值（字段非数值时为 None）
+    pub value: Option<f64>,
+    /// 参与去重计数的取值
+    pub distinct: Option<String>,
+}
+
+impl AggregateBucket {
+    /// 将样本累加到桶中
+    pub fn apply(&mut self, sample: &AggregateSample) {
+        self.count += 1;
+        if let Some(v) = sample.value {
+            self.sum += v;
+        }
+        if let Some(ref d) = sample.distinct {
+            self.distinct.insert(d.clone());
+        }
+    }
+}
+
+/// 聚合状态存储抽象
+///
+/// `record` 以 event_id 做幂等：Kafka 重投或同一事件被多次评估时不会重复累加。
+#[async_trait]
+pub trait AggregateStateStore: Send + Sync {
+    /// 将样本记录到指定桶，返回 false 表示该事件已记录过
+    async fn record(
+        &self,
+        key: &str,
+        bucket: i64,
+        sample: &AggregateSample,
+        event_id: Option<&str>,
+        retention: Duration,
+    ) -> Result<bool>;
+
+    /// 读取 `[from, to]` 区间内有数据的桶，并清理 `from` 之前已滑出窗口的桶
+    ///
+    /// `with_distinct` 为 false 时不加载去重取值，避免为计数类聚合读取集合数据。
+    async fn load(
+        &self,
+        key: &str,
+        from: i64,
+        to: i64,
+        with_distinct: bool,
+    ) -> Result<Vec<AggregateBucket>>;
+}
+
+// ---------------------------------------------------------------------------
+// 内存实现
+// ---------------------------------------------------------------------------
+
+/// 内存聚合状态存储
+///
+/// 状态仅存在于进程内，重启即丢失，适用于测试和规则试运行。
+#[derive(Default)]
+pub struct InMemoryAggregateStore {
+    buckets: DashMap<String, BTreeMap<i64, AggregateBucket>>,
+    seen_events: DashSet<String>,
+}
+
+impl InMemoryAggregateStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl AggregateStateStore for InMemoryAggregateStore {
+    async fn record(
+        &self,
+        key: &str,
+        bucket: i64,
+        sample: &AggregateSample,
+        event_id: Option<&str>,
+        _retention: Duration,
+    ) -> Result<bool> {
+        if let Some(event_id) = event_id
+            && !self.seen_events.insert(format!("{}:{}", key, event_id))
+        {
+            return Ok(false);
+        }
+
+        let mut entry = self.buckets.entry(key.to_string()).or_default();
+        entry
+            .entry(bucket)
+            .or_insert_with(|| AggregateBucket {
+                index: bucket,
+                ..Default::default()
+            })
+            .apply(sample);
+
+        Ok(true)
+    }
+
+    async fn load(
+        &self,
+        key: &str,
+        from: i64,
+        to: i64,
+        with_distinct: bool,
+    ) -> Result<Vec<AggregateBucket>> {
+        let Some(mut entry) = self.buckets.get_mut(key) else {
+            return Ok(Vec::new());
+        };
+
+        entry.retain(|index, _| *index >= from);
+
+        Ok(entry
+            .range(from..=to)
+            .map(|(_, b)| {
+                let mut bucket = b.clone();
+                if !with_distinct {
+                    bucket.distinct.clear();
+                }
+                bucket
+            })
+            .collect())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Redis 实现
+// ---------------------------------------------------------------------------
+
+/// Redis 聚合状态存储
+///
+/// 每个状态键对应：
+/// - 一个哈希 `rule:agg:{key}`，字段 `c:{index}` 为次数、`s:{index}` 为累计值
+/// - 每个桶一个集合 `rule:agg:{key}:d:{index}`，存放去重取值
+/// - 幂等标记 `rule:agg:{key}:evt:{event_id}`
+///
+/// 计数与求和使用 HINCRBY/HINCRBYFLOAT，同一用户的并发事件不会互相覆盖。
+/// 幂等检查、桶更新与幂等标记在同一 Lua 脚本内完成：脚本中途失败时标记尚未写入，
+/// 事件重投后仍会重新累加，不会出现"已标记但未计数"。
+pub struct RedisAggregateStore {
+    cache: Cache,
+}
+
+impl RedisAggregateStore {
+    pub fn new(cache: Cache) -> Self {
+        Self { cache }
+    }
+
+    fn distinct_key(hash_key: &str, bucket: i64) -> String {
+        format!("{}:d:{}", hash_key, bucket)
+    }
+
+    fn event_marker_key(hash_key: &str, event_id: &str) -> String {
+        format!("{}:evt:{}", hash_key, event_id)
+    }
+
+    /// 组装 `RECORD_SCRIPT` 的键
+    ///
+    /// 只有携带事件 ID 时才传入幂等标记键，脚本按键的数量判断是否做幂等
+    fn record_keys(hash_key: &str, bucket: i64, event_id: Option<&str>) -> Vec<String> {
+        let mut keys = vec![hash_key.to_string(), Self::distinct_key(hash_key, bucket)];
+        if let Some(id) = event_id {
+            keys.push(Self::event_marker_key(hash_key, id));
+        }
+        keys
+    }
+
+    /// 组装 `RECORD_SCRIPT` 的参数
+    ///
+    /// 非有限的数值不参与求和（HINCRBYFLOAT 会拒绝），只计次数
+    fn record_args(bucket: i64, sample: &AggregateSample, retention: Duration) -> Vec<String> {
+        let flag = |b: bool| if b { "1" } else { "0" }.to_string();
+        let value = sample.value.filter(|v| v.is_finite());
+        vec![
+            bucket.to_string(),
+            retention.as_secs().max(1).to_string(),
+            flag(value.is_some()),
+            value.map(|v| v.to_string()).unwrap_or_default(),
+            flag(sample.distinct.is_some()),
+            sample.distinct.clone().unwrap_or_default(),
+        ]
+    }
+
+    /// 解析哈希字段名 `c:{index}` / `s:{index}`
+    fn parse_field(field: &str) -> Option<(char, i64)> {
+        let (kind, index) = field.split_once(':')?;
+        let kind = kind.chars().next()?;
+        Some((kind, index.parse().ok()?))
+    }
+}
+
+/// 原子记录一次事件
+///
+/// KEYS: 1 哈希，2 去重集合，3 幂等标记（可选，不做幂等时只传 2 个键）
+/// ARGV: 1 桶序号，2 TTL 秒，3/4 是否求和及数值，5/6 是否去重及取值
+///
+/// 幂等标记最后写入，返回 0 表示事件已记录过
+const RECORD_SCRIPT: &str = r#"
+local dedup = #KEYS == 3
+if dedup and redis.call('EXISTS', KEYS[3]) == 1 then
+  return 0
+end
+redis.call('HINCRBY', KEYS[1], 'c:' .. ARGV[1], 1)
+if ARGV[3] == '1' then
+  redis.call('HINCRBYFLOAT', KEYS[1], 's:' .. ARGV[1], ARGV[4])
+end
+if ARGV[5] == '1' then
+  redis.call('SADD', KEYS[2], ARGV[6])
+  redis.call('EXPIRE', KEYS[2], ARGV[2])
+end
+redis.call('EXPIRE', KEYS[1], ARGV[2])
+if dedup then
+  redis.call('SET', KEYS[3], '1', 'EX', ARGV[2])
+end
+return 1
+"#;
+
+fn store_error(e: badge_shared::error::BadgeError) -> RuleError {
+    RuleError::StateStoreError(e.to_string())
+}
+
+#[async_trait]
+impl AggregateStateStore for RedisAggregateStore {
+    async fn record(
+        &self,
+        key: &str,
+        bucket: i64,
+        sample: &AggregateSample,
+        event_id: Option<&str>,
+        retention: Duration,
+    ) -> Result<bool> {
+        let hash_key = CacheKey::rule_aggregate(key);
+        let keys = Self::record_keys(&hash_key, bucket, event_id);
+        let args = Self::record_args(bucket, sample, retention);
+
+        let recorded = self
+            .cache
+            .eval_script(RECORD_SCRIPT, &keys, &args)
+            .await
+            .map_err(store_error)?;
+
+        Ok(recorded == 1)
+    }
+
+    async fn load(
+        &self,
+        key: &str,
+        from: i64,
+        to: i64,
+        with_distinct: bool,
+    ) -> Result<Vec<AggregateBucket>> {
+        let hash_key = CacheKey::rule_aggregate(key);
+        let fields = self.cache.hget_all(&hash_key).await.map_err(store_error)?;
+
+        let mut buckets: BTreeMap<i64, AggregateBucket> = BTreeMap::new();
+        let mut expired = Vec::new();
+
+        for (field, raw) in fields {
+            let Some((kind, index)) = Self::parse_field(&field) else {
+                continue;
+            };
+            if index < from {
+                expired.push(field);
+                continue;
+            }
+            if index > to {
+                continue;
+            }
+
+            let bucket = buckets.entry(index).or_insert_with(|| AggregateBucket {
+                index,
+                ..Default::default()
+            });
+            match kind {
+                'c' => bucket.count = raw.parse().unwrap_or(0),
+                's' => bucket.sum = raw.parse().unwrap_or(0.0),
+                _ => {}
+            }
+        }
+
+        // 窗口已滑过的桶不会再被读取，顺手清理以控制哈希大小
+        self.cache
+            .hdel(&hash_key, &expired)
+            .await
+            .map_err(store_error)?;
+
+        if with_distinct {
+            for (index, bucket) in buckets.iter_mut() {
+                let members = self
+                    .cache
+                    .smembers(&Self::distinct_key(&hash_key, *index))
+                    .await
+                    .map_err(store_error)?;
+                bucket.distinct = members.into_iter().collect();
+            }
+        }
+
+        Ok(buckets.into_values().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(value: f64, distinct: &str) -> AggregateSample {
+        AggregateSample {
+            value: Some(value),
+            distinct: Some(distinct.to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_record_and_load() {
+        let store = InMemoryAggregateStore::new();
+        let retention = Duration::from_secs(60);
+
+        store
+            .record("k", 10, &sample(100.0, "a"), Some("e1"), retention)
+            .await
+            .unwrap();
+        store
+            .record("k", 10, &sample(50.0, "a"), Some("e2"), retention)
+            .await
+            .unwrap();
+        store
+            .record("k", 12, &sample(30.0, "b"), Some("e3"), retention)
+            .await
+            .unwrap();
+
+        let buckets = store.load("k", 10, 12, true).await.unwrap();
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(buckets[0].index, 10);
+        assert_eq!(buckets[0].count, 2);
+        assert_eq!(buckets[0].sum, 150.0);
+        assert_eq!(buckets[0].distinct.len(), 1);
+        assert_eq!(buckets[1].count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_record_is_idempotent() {
+        let store = InMemoryAggregateStore::new();
+        let retention = Duration::from_secs(60);
+
+        assert!(
+            store
+                .record("k", 1, &AggregateSample::default(), Some("e1"), retention)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !store
+                .record("k", 1, &AggregateSample::default(), Some("e1"), retention)
+                .await
+                .unwrap()
+        );
+
+        let buckets = store.load("k", 1, 1, false).await.unwrap();
+        assert_eq!(buckets[0].count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_load_prunes_expired_buckets() {
+        let store = InMemoryAggregateStore::new();
+        let retention = Duration::from_secs(60);
+
+        store
+            .record("k", 1, &AggregateSample::default(), None, retention)
+            .await
+            .unwrap();
+        store
+            .record("k", 5, &AggregateSample::default(), None, retention)
+            .await
+            .unwrap();
+
+        assert_eq!(store.load("k", 3, 5, false).await.unwrap().len(), 1);
+        // 被清理的桶即使扩大区间也不会再出现
+        assert_eq!(store.load("k", 0, 5, false).await.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_redis_field_parsing() {
+        assert_eq!(
+            RedisAggregateStore::parse_field("c:19500"),
+            Some(('c', 19500))
+        );
+        assert_eq!(RedisAggregateStore::parse_field("s:-3"), Some(('s', -3)));
+        assert_eq!(RedisAggregateStore::parse_field("bogus"), None);
+    }
+
+    #[test]
+    fn test_redis_record_keys() {
+        assert_eq!(
+            RedisAggregateStore::record_keys("agg", 7, Some("evt-1")),
+            ["agg", "agg:d:7", "agg:evt:evt-1"]
+        );
+        // 没有事件 ID 时不传幂等标记键，避免向脚本传入空键名
+        assert_eq!(
+            RedisAggregateStore::record_keys("agg", 7, None),
+            ["agg", "agg:d:7"]
+        );
+    }
+
+    #[test]
+    fn test_redis_record_args() {
+        let args =
+            RedisAggregateStore::record_args(7, &sample(12.5, "sku-1"), Duration::from_secs(3600));
+        assert_eq!(args, ["7", "3600", "1", "12.5", "1", "sku-1"]);
+
+        // 非有限数值只计次数，不参与求和
+        let args = RedisAggregateStore::record_args(
+            7,
+            &AggregateSample {
+                value: Some(f64::NAN),
+                distinct: None,
+            },
+            Duration::from_secs(3600),
+        );
+        assert_eq!(args, ["7", "3600", "0", "", "0", ""]);
+    }
+}
@@ -2,6 +2,7 @@
 //!
 //! 将 JSON 规则解析并编译成内存中的执行树，支持字段索引预提取优化。
 
+use crate::aggregate::AggregateCondition;
 use crate::error::{Result, RuleError};
 use crate::models::{Condition, Rule, RuleNode};
 use crate::operators::Operator;
@@ -15,6 +16,8 @@ pub struct CompiledRule {
     pub rule: Rule,
     /// 规则中使用的所有字段路径（用于优化字段提取）
     pub required_fields: HashSet<String>,
+    /// 规则中的聚合节点（执行前需先计算聚合值）
+    pub aggregates: Vec<AggregateCondition>,
     /// 编译版本号（用于缓存失效）
     pub compile_version: u64,
 }
@@ -34,6 +37,11 @@ impl CompiledRule {
     pub fn root(&self) -> &RuleNode {
         &self.rule.root
     }
+
+    /// 是否包含聚合节点
+    pub fn has_aggregates(&self) -> bool {
+        !self.aggregates.is_empty()
+    }
 }
 
 /// 规则编译器
@@ -59,12 +67,15 @@ impl RuleCompiler {
 
         // 提取所有使用的字段
         let required_fields = self.extract_fields(&rule.root);
+        let mut aggregates = Vec::new();
+        Self::collect_aggregates(&rule.root, &mut aggregates);
 
         self.compile_version += 1;
 
         Ok(CompiledRule {
             rule,
             required_fields,
+            aggregates,
             compile_version: self.compile_version,
         })
     }
@@ -100,6 +111,9 @@ impl RuleCompiler {
                     self.validate_node(child, &child_path)?;
                 }
             }
+            RuleNode::Aggregate(agg) => {
+                self.validate_aggregate(agg, path)?;
+            }
         }
 
         Ok(())
@@ -115,16 +129,55 @@ impl RuleCompiler {
         }
 
         // 验证操作符和值的兼容性
-        self.validate_operator_value(cond, path)?;
+        self.validate_operator_value(cond.operator, &cond.value, path)?;
 
         Ok(())
     }
 
+    /// 验证聚合条件
+    fn validate_aggregate(&self, agg: &AggregateCondition, path: &str) -> Result<()> {
+        if agg.window.size == 0 {
+            return Err(RuleError::ParseError(format!(
+                "聚合条件 '{}' 的窗口大小必须大于 0",
+                path
+            )));
+        }
+
+        let has_field = agg.field.as_deref().is_some_and(|f| !f.is_empty());
+        if agg.function.requires_field() && !has_field {
+            return Err(RuleError::ParseError(format!(
+                "聚合条件 '{}' 的 {} 函数需要指定统计字段",
+                path, agg.function
+            )));
+        }
+
+        // 聚合值总是数值，只允许比较类操作符
+        if !matches!(
+            agg.operator,
+            Operator::Eq
+                | Operator::Neq
+                | Operator::Gt
+                | Operator::Gte
+                | Operator::Lt
+                | Operator::Lte
+                | Operator::Between
+                | Operator::In
+                | Operator::NotIn
+        ) {
+            return Err(RuleError::ParseError(format!(
+                "聚合条件 '{}' 不支持 {} 操作符",
+                path, agg.operator
+            )));
+        }
+
+        self.validate_operator_value(agg.operator, &agg.value, path)
+    }
+
     /// 验证操作符和值的兼容性
-    fn validate_operator_value(&self, cond: &Condition, path: &str) -> Result<()> {
-        match cond.operator {
+    fn validate_operator_value(&self, operator: Operator, value: &Value, path: &str) -> Result<()> {
+        match operator {
             Operator::Between => {
-                if let Value::Array(arr) = &cond.value {
+                if let Value::Array(arr) = value {
                     if arr.len() != 2 {
                         return Err(RuleError::ParseError(format!(
                             "条件 '{}' 的 between 操作符需要 [min, max] 数组，当前有 {} 个元素",
@@ -140,15 +193,15 @@ impl RuleCompiler {
                 }
             }
             Operator::In | Operator::NotIn | Operator::ContainsAny | Operator::ContainsAll => {
-                if !cond.value.is_array() {
+                if !value.is_array() {
                     return Err(RuleError::ParseError(format!(
                         "条件 '{}' 的 {} 操作符需要数组值",
-                        path, cond.operator
+                        path, operator
                     )));
                 }
             }
             Operator::Regex => {
-                if let Some(pattern) = cond.value.as_str() {
+                if let Some(pattern) = value.as_str() {
                     // 预验证正则表达式
                     regex::Regex::new(pattern).map_err(|e| {
                         RuleError::ParseError(format!("条件 '{}' 的正则表达式无效: {}", path, e))
@@ -189,6 +242,21 @@ impl RuleCompiler {
                     self.collect_fields(child, fields);
                 }
             }
+            // 聚合节点读取的是预计算的聚合值，不依赖事件字段
+            RuleNode::Aggregate(_) => {}
+        }
+    }
+
+    /// 递归收集聚合节点
+    fn collect_aggregates(node: &RuleNode, aggregates: &mut Vec<AggregateCondition>) {
+        match node {
+            RuleNode::Condition(_) => {}
+            RuleNode::Group(group) => {
+                for child in &group.children {
+                    Self::collect_aggregates(child, aggregates);
+                }
+            }
+            RuleNode::Aggregate(agg) => aggregates.push(agg.clone()),
         }
     }
 }
@@ -394,4 +462,99 @@ mod tests {
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("正则表达式无效"));
     }
+
+    #[test]
+    fn test_compile_collects_aggregates() {
+        let mut compiler = RuleCompiler::new();
+        let json = r#"
+        {
+            "id": "rule-agg",
+            "name": "test",
+            "version": "1.0",
+            "root": {
+                "type": "group",
+                "operator": "AND",
+                "children": [
+                    {
+                        "type": "condition",
+                        "field": "event_type",
+                        "operator": "eq",
+                        "value": "PURCHASE"
+                    },
+                    {
+                        "type": "aggregate",
+                        "function": "count",
+                        "event_type": "purchase",
+                        "window": {"type": "sliding", "size": 30, "unit": "day"},
+                        "operator": "gte",
+                        "value": 5
+                    }
+                ]
+            }
+        }
+        "#;
+
+        let compiled = compiler.compile_from_json(json).unwrap();
+        assert!(compiled.has_aggregates());
+        assert_eq!(compiled.aggregates.len(), 1);
+        assert_eq!(compiled.required_fields.len(), 1);
+    }
+
+    #[test]
+    fn test_validate_aggregate_requires_field() {
+        let mut compiler = RuleCompiler::new();
+        let json = r#"
+        {
+            "id": "rule-agg",
+            "name": "test",
+            "version": "1.0",
+            "root": {
+                "type": "aggregate",
+                "function": "sum",
+                "window": {"size": 3, "unit": "month"},
+                "operator": "gte",
+                "value": 2000
+            }
+        }
+        "#;
+
+        let result = compiler.compile_from_json(json);
+        assert!(result.unwrap_err().to_string().contains("统计字段"));
+    }
+
+    #[test]
+    fn test_validate_aggregate_operator() {
+        let mut compiler = RuleCompiler::new();
+        let json = r#"
+        {
+            "id": "rule-agg",
+            "name": "test",
+            "version": "1.0",
+            "root": {
+                "type": "aggregate",
+                "function": "count",
+                "window": {"size": 0, "unit": "day"},
+                "operator": "gte",
+                "value": 1
+            }
+        }
+        "#;
+        assert!(compiler.compile_from_json(json).is_err());
+
+        let json = r#"
+        {
+            "id": "rule-agg",
+            "name": "test",
+            "version": "1.0",
+            "root": {
+                "type": "aggregate",
+                "function": "count",
+                "window": {"size": 7, "unit": "day"},
+                "operator": "regex",
+                "value": "^1"
+            }
+        }
+        "#;
+        assert!(compiler.compile_from_json(json).is_err());
+    }
 }
@@ -0,0 +1,273 @@
+//! 规则结构差异
+//!
+//! 对比同一规则两个版本的 `RuleNode` 树，输出节点级别的变更列表，
+//! 供管理后台展示版本差异。逻辑组的子节点按最长公共子序列对齐，
+//! 中间插入或删除一个条件不会让后续条件全部显示为"已修改"。
+
+use crate::models::RuleNode;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// 节点变更类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// 单个节点的变更
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeChange {
+    /// 节点路径，如 `root.children[1]`；删除的节点使用旧版本中的位置
+    pub path: String,
+    pub kind: ChangeKind,
+    /// 发生变化的属性（仅 Modified），节点类型不同时为 `type`
+    pub fields: Vec<String>,
+    /// 变更前的节点（Added 时为 None）
+    pub before: Option<Value>,
+    /// 变更后的节点（Removed 时为 None）
+    pub after: Option<Value>,
+}
+
+impl NodeChange {
+    fn added(path: String, node: Value) -> Self {
+        Self {
+            path,
+            kind: ChangeKind::Added,
+            fields: Vec::new(),
+            before: None,
+            after: Some(node),
+        }
+    }
+
+    fn removed(path: String, node: Value) -> Self {
+        Self {
+            path,
+            kind: ChangeKind::Removed,
+            fields: Vec::new(),
+            before: Some(node),
+            after: None,
+        }
+    }
+
+    fn modified(path: String, fields: Vec<String>, before: Value, after: Value) -> Self {
+        Self {
+            path,
+            kind: ChangeKind::Modified,
+            fields,
+            before: Some(before),
+            after: Some(after),
+        }
+    }
+}
+
+/// 对比两棵规则树，返回按路径顺序排列的变更列表；完全相同时返回空列表
+pub fn diff_rule_nodes(old: &RuleNode, new: &RuleNode) -> Vec<NodeChange> {
+    let mut changes = Vec::new();
+    diff_node("root".to_string(), old, new, &mut changes);
+    changes
+}
+
+fn to_json(node: &RuleNode) -> Value {
+    serde_json::to_value(node).unwrap_or(Value::Null)
+}
+
+/// 列出两个 JSON 对象中取值不同的键（忽略节点类型标记）
+fn changed_keys(before: &Value, after: &Value) -> Vec<String> {
+    let (Some(a), Some(b)) = (before.as_object(), after.as_object()) else {
+        return Vec::new();
+    };
+
+    let mut keys: Vec<String> = a
+        .keys()
+        .chain(b.keys())
+        .filter(|k| k.as_str() != "type" && a.get(*k) != b.get(*k))
+        .cloned()
+        .collect();
+    keys.sort();
+    keys.dedup();
+    keys
+}
+
+fn diff_node(path: String, old: &RuleNode, new: &RuleNode, out: &mut Vec<NodeChange>) {
+    let before = to_json(old);
+    let after = to_json(new);
+    if before == after {
+        return;
+    }
+
+    match (old, new) {
+        (RuleNode::Group(a), RuleNode::Group(b)) => {
+            if a.operator != b.operator {
+                out.push(NodeChange::modified(
+                    path.clone(),
+                    vec!["operator".to_string()],
+                    serde_json::json!({ "operator": a.operator }),
+                    serde_json::json!({ "operator": b.operator }),
+                ));
+            }
+            diff_children(&path, &a.children, &b.children, out);
+        }
+        (RuleNode::Condition(_), RuleNode::Condition(_))
+        | (RuleNode::Aggregate(_), RuleNode::Aggregate(_)) => {
+            let fields = changed_keys(&before, &after);
+            out.push(NodeChange::modified(path, fields, before, after));
+        }
+        _ => {
+            out.push(NodeChange::modified(
+                path,
+                vec!["type".to_string()],
+                before,
+                after,
+            ));
+        }
+    }
+}
+
+/// 子节点对齐步骤
+enum Step {
+    Keep,
+    Remove(usize),
+    Insert(usize),
+}
+
+fn diff_children(path: &str, old: &[RuleNode], new: &[RuleNode], out: &mut Vec<NodeChange>) {
+    let old_json: Vec<Value> = old.iter().map(to_json).collect();
+    let new_json: Vec<Value> = new.iter().map(to_json).collect();
+    let child_path = |index: usize| format!("{}.children[{}]", path, index);
+
+    // lcs[i][j]：old[i..] 与 new[j..] 的最长公共子序列长度
+    let (n, m) = (old_json.len(), new_json.len());
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old_json[i] == new_json[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut steps = Vec::with_capacity(n + m);
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && old_json[i] == new_json[j] {
+            steps.push(Step::Keep);
+            i += 1;
+            j += 1;
+        } else if j < m && (i == n || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            steps.push(Step::Insert(j));
+            j += 1;
+        } else {
+            steps.push(Step::Remove(i));
+            i += 1;
+        }
+    }
+
+    // 两个未变化节点之间的删除和插入按顺序两两配对视为修改，
+    // 这样修改某个条件的值时能定位到具体属性，而不是"删一条、加一条"
+    let mut flush = |removed: &mut Vec<usize>, inserted: &mut Vec<usize>| {
+        let paired = removed.len().min(inserted.len());
+        for (&r, &a) in removed.iter().zip(inserted.iter()) {
+            diff_node(child_path(a), &old[r], &new[a], out);
+        }
+        for &r in &removed[paired..] {
+            out.push(NodeChange::removed(child_path(r), old_json[r].clone()));
+        }
+        for &a in &inserted[paired..] {
+            out.push(NodeChange::added(child_path(a), new_json[a].clone()));
+        }
+        removed.clear();
+        inserted.clear();
+    };
+
+    let (mut removed, mut inserted) = (Vec::new(), Vec::new());
+    for step in steps {
+        match step {
+            Step::Keep => flush(&mut removed, &mut inserted),
+            Step::Remove(r) => removed.push(r),
+            Step::Insert(a) => inserted.push(a),
+        }
+    }
+    flush(&mut removed, &mut inserted);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Condition, LogicalGroup};
+    use crate::operators::Operator;
+
+    fn cond(field: &str, operator: Operator, value: i64) -> RuleNode {
+        RuleNode::Condition(Condition::new(field, operator, value))
+    }
+
+    #[test]
+    fn test_identical_rules_have_no_changes() {
+        let rule = RuleNode::Group(LogicalGroup::and(vec![cond("amount", Operator::Gte, 100)]));
+        assert!(diff_rule_nodes(&rule, &rule.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_modified_condition_reports_changed_fields() {
+        let old = RuleNode::Group(LogicalGroup::and(vec![
+            cond("amount", Operator::Gte, 100),
+            cond("level", Operator::Eq, 1),
+        ]));
+        let new = RuleNode::Group(LogicalGroup::and(vec![
+            cond("amount", Operator::Gte, 500),
+            cond("level", Operator::Eq, 1),
+        ]));
+
+        let changes = diff_rule_nodes(&old, &new);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].path, "root.children[0]");
+        assert_eq!(changes[0].kind, ChangeKind::Modified);
+        assert_eq!(changes[0].fields, vec!["value"]);
+    }
+
+    #[test]
+    fn test_inserted_child_does_not_shift_siblings() {
+        let old = RuleNode::Group(LogicalGroup::and(vec![
+            cond("a", Operator::Eq, 1),
+            cond("c", Operator::Eq, 3),
+        ]));
+        let new = RuleNode::Group(LogicalGroup::and(vec![
+            cond("a", Operator::Eq, 1),
+            cond("b", Operator::Eq, 2),
+            cond("c", Operator::Eq, 3),
+        ]));
+
+        let changes = diff_rule_nodes(&old, &new);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].kind, ChangeKind::Added);
+        assert_eq!(changes[0].path, "root.children[1]");
+
+        let reverse = diff_rule_nodes(&new, &old);
+        assert_eq!(reverse.len(), 1);
+        assert_eq!(reverse[0].kind, ChangeKind::Removed);
+        assert_eq!(reverse[0].path, "root.children[1]");
+    }
+
+    #[test]
+    fn test_group_operator_and_type_changes() {
+        let old = RuleNode::Group(LogicalGroup::and(vec![
+            cond("a", Operator::Eq, 1),
+            cond("b", Operator::Eq, 2),
+        ]));
+        let new = RuleNode::Group(LogicalGroup::or(vec![
+            cond("a", Operator::Eq, 1),
+            RuleNode::Group(LogicalGroup::and(vec![cond("b", Operator::Eq, 2)])),
+        ]));
+
+        let changes = diff_rule_nodes(&old, &new);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].path, "root");
+        assert_eq!(changes[0].fields, vec!["operator"]);
+        assert_eq!(changes[1].path, "root.children[1]");
+        assert_eq!(changes[1].fields, vec!["type"]);
+    }
+}
@@ -0,0 +1,310 @@
+//! 内嵌规则评估
+//!
+//! 供事件服务在进程内直接执行规则，与 gRPC 服务共用同一套编译器、执行器和
+//! 聚合状态存储，评估语义与远程调用完全一致，同时省去热点规则的网络往返。
+//!
+//! 规则来源是事件服务已经维护的 `RuleBadgeMapping`：映射每次全量刷新后代数加一，
+//! 评估前发现代数变化即重新编译，无需额外的加载通道。
+
+use crate::aggregate::{AggregateResolver, ResolveMode};
+use crate::error::Result;
+use crate::executor::RuleExecutor;
+use crate::models::{EvaluationContext, EvaluationResult, Rule, RuleNode};
+use crate::store::RuleStore;
+use badge_shared::error::BadgeError;
+use badge_shared::rules::{BadgeGrant, RuleBadgeMapping};
+use parking_lot::{Mutex, RwLock};
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::{debug, info, warn};
+
+/// 尚未同步过的代数标记
+const NOT_SYNCED: u64 = u64::MAX;
+
+/// 内嵌规则评估器
+pub struct EmbeddedRuleEvaluator {
+    mapping: Arc<RuleBadgeMapping>,
+    store: RuleStore,
+    executor: RuleExecutor,
+    aggregates: Arc<AggregateResolver>,
+    /// 未配置 rule_json 的规则，仅按事件类型匹配
+    unconditional: RwLock<HashSet<String>>,
+    synced_generation: AtomicU64,
+    sync_lock: Mutex<()>,
+}
+
+impl EmbeddedRuleEvaluator {
+    pub fn new(mapping: Arc<RuleBadgeMapping>, aggregates: Arc<AggregateResolver>) -> Self {
+        Self {
+            mapping,
+            store: RuleStore::new(),
+            executor: RuleExecutor::new(),
+            aggregates,
+            unconditional: RwLock::new(HashSet::new()),
+            synced_generation: AtomicU64::new(NOT_SYNCED),
+            sync_lock: Mutex::new(()),
+        }
+    }
+
+    /// 当前已编译的规则数量
+    pub fn len(&self) -> usize {
+        self.store.len() + self.unconditional.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 规则映射刷新后重建编译缓存
+    ///
+    /// 代数未变化时直接返回；并发评估只会有一个线程执行重建，其余线程等待后复用结果。
+    pub fn sync(&self) {
+        let generation = self.mapping.generation();
+        if self.synced_generation.load(Ordering::Acquire) == generation {
+            return;
+        }
+
+        let _guard = self.sync_lock.lock();
+        if self.synced_generation.load(Ordering::Acquire) == generation {
+            return;
+        }
+
+        let mut rules = Vec::new();
+        let mut unconditional = HashSet::new();
+        for grant in self.mapping.all_rules() {
+            match grant.rule_json {
+                Some(ref rule_json) => rules.extend(Self::to_rule(&grant, rule_json)),
+                None => {
+                    unconditional.insert(grant.rule_id.to_string());
+                }
+            }
+        }
+
+        let loaded = self.store.replace_all(rules);
+        *self.unconditional.write() = unconditional;
+        self.synced_generation.store(generation, Ordering::Release);
+
+        info!(generation, loaded, "内嵌规则引擎已同步规则映射");
+    }
+
+    /// 将规则映射转为规则引擎模型
+    ///
+    /// 解析失败的规则不加载（因而不会命中），与规则引擎服务从数据库加载时的行为一致。
+    fn to_rule(grant: &BadgeGrant, rule_json: &serde_json::Value) -> Option<Rule> {
+        let rule_id = grant.rule_id.to_string();
+
+        let root = serde_json::from_value::<RuleNode>(rule_json.clone())
+            .inspect_err(|e| warn!(rule_id = %rule_id, error = %e, "rule_json 解析失败，跳过"))
+            .ok()?;
+
+        let name = if grant.rule_code.is_empty() {
+            format!("rule_{}", grant.rule_id)
+        } else {
+            grant.rule_code.clone()
+        };
+
+        Some(Rule {
+            id: rule_id,
+            name,
+            version: "embedded".to_string(),
+            root,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        })
+    }
+
+    /// 批量评估规则，返回命中的规则结果
+    ///
+    /// 与 gRPC BatchEvaluate 相同：先以 Record 模式统一计算聚合值，再逐条执行；
+    /// 单条规则执行失败只记录日志，不影响其他规则。
+    pub async fn evaluate(
+        &self,
+        rule_ids: &[String],
+        context: serde_json::Value,
+    ) -> Result<Vec<EvaluationResult>> {
+        self.sync();
+
+        let mut context = EvaluationContext::new(context);
+        let mut matched = Vec::new();
+
+        let unconditional = self.unconditional.read().clone();
+        let rules: Vec<_> = rule_ids
+            .iter()
+            .filter_map(|rule_id| {
+                if unconditional.contains(rule_id) {
+                    let mut result = EvaluationResult::new(rule_id.clone(), rule_id.clone());
+                    result.matched = true;
+                    matched.push(result);
+                    return None;
+                }
+                let rule = self.store.get(rule_id);
+                if rule.is_none() {
+                    debug!(rule_id = %rule_id, "内嵌规则引擎中不存在该规则");
+                }
+                rule
+            })
+            .collect();
+
+        let rule_refs: Vec<_> = rules.iter().collect();
+        self.aggregates
+            .resolve(&rule_refs, &mut context, ResolveMode::Record)
+            .await?;
+
+        for rule in &rules {
+            match self.executor.execute(rule, &context) {
+                Ok(result) if result.matched => matched.push(result),
+                Ok(_) => {}
+                Err(e) => warn!(rule_id = %rule.id(), error = %e, "规则执行失败"),
+            }
+        }
+
+        Ok(matched)
+    }
+}
+
+/// 事件服务的规则评估入口，返回命中的规则 ID
+///
+/// 配置了内嵌评估器时在进程内评估，否则调用 `remote` 请求规则引擎服务。
+/// 两种模式使用同一套规则引擎实现，匹配语义完全一致。
+pub async fn evaluate_rule_ids<F, Fut, E>(
+    embedded: Option<&EmbeddedRuleEvaluator>,
+    rule_ids: &[String],
+    context: serde_json::Value,
+    remote: F,
+) -> std::result::Result<Vec<String>, BadgeError>
+where
+    F: FnOnce(serde_json::Value) -> Fut,
+    Fut: Future<Output = std::result::Result<Vec<String>, E>>,
+    E: Display,
+{
+    match embedded {
+        Some(evaluator) => Ok(evaluator
+            .evaluate(rule_ids, context)
+            .await
+            .map_err(|e| BadgeError::Internal(format!("规则评估失败: {e}")))?
+            .into_iter()
+            .map(|r| r.rule_id)
+            .collect()),
+        None => remote(context)
+            .await
+            .map_err(|e| BadgeError::Internal(format!("规则评估失败: {e}"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn grant(rule_id: i64, rule_json: Option<serde_json::Value>) -> BadgeGrant {
+        BadgeGrant {
+            rule_id,
+            rule_code: format!("RULE_{}", rule_id),
+            badge_id: rule_id * 10,
+            badge_name: format!("Badge {}", rule_id),
+            quantity: 1,
+            event_type: "purchase".to_string(),
+            start_time: None,
+            end_time: None,
+            max_count_per_user: None,
+            global_quota: None,
+            global_granted: 0,
+            rule_json,
+        }
+    }
+
+    fn evaluator(rules: Vec<BadgeGrant>) -> (Arc<RuleBadgeMapping>, EmbeddedRuleEvaluator) {
+        let mapping = Arc::new(RuleBadgeMapping::new());
+        mapping.replace_all(rules);
+        let evaluator =
+            EmbeddedRuleEvaluator::new(mapping.clone(), Arc::new(AggregateResolver::in_memory()));
+        (mapping, evaluator)
+    }
+
+    fn matched_ids(results: &[EvaluationResult]) -> Vec<&str> {
+        results.iter().map(|r| r.rule_id.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_supports_full_operator_set() {
+        let (_, evaluator) = evaluator(vec![
+            grant(
+                1,
+                Some(json!({"type": "condition", "field": "tier", "operator": "in", "value": ["gold", "vip"]})),
+            ),
+            grant(
+                2,
+                Some(json!({"type": "condition", "field": "amount", "operator": "between", "value": [100, 500]})),
+            ),
+            grant(
+                3,
+                Some(json!({"type": "condition", "field": "sku", "operator": "regex", "value": "^A\\d+$"})),
+            ),
+        ]);
+
+        let ids = vec!["1".to_string(), "2".to_string(), "3".to_string()];
+        let results = evaluator
+            .evaluate(&ids, json!({"tier": "gold", "amount": 800, "sku": "A42"}))
+            .await
+            .unwrap();
+
+        assert_eq!(matched_ids(&results), vec!["1", "3"]);
+    }
+
+    #[tokio::test]
+    async fn test_rule_without_json_matches_unconditionally() {
+        let (_, evaluator) = evaluator(vec![grant(7, None)]);
+
+        let results = evaluator
+            .evaluate(&["7".to_string()], json!({}))
+            .await
+            .unwrap();
+
+        assert_eq!(matched_ids(&results), vec!["7"]);
+    }
+
+    #[tokio::test]
+    async fn test_resyncs_after_mapping_refresh() {
+        let rule = json!({"type": "condition", "field": "amount", "operator": "gte", "value": 100});
+        let (mapping, evaluator) = evaluator(vec![grant(1, Some(rule.clone()))]);
+
+        let ids = vec!["1".to_string(), "2".to_string()];
+        let results = evaluator.evaluate(&ids, json!({"amount": 150})).await.unwrap();
+        assert_eq!(matched_ids(&results), vec!["1"]);
+
+        mapping.replace_all(vec![grant(2, Some(rule))]);
+        let results = evaluator.evaluate(&ids, json!({"amount": 150})).await.unwrap();
+        assert_eq!(matched_ids(&results), vec!["2"]);
+        assert_eq!(evaluator.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_rule_ids_dispatch() {
+        let (_, embedded) = evaluator(vec![grant(1, None)]);
+        let ids = vec!["1".to_string()];
+
+        let matched = evaluate_rule_ids(Some(&embedded), &ids, json!({}), |_| async {
+            Err::<Vec<String>, _>("内嵌模式不应调用远程规则引擎")
+        })
+        .await
+        .unwrap();
+        assert_eq!(matched, vec!["1"]);
+
+        let matched = evaluate_rule_ids(None, &ids, json!({}), |_| async {
+            Ok::<_, String>(vec!["9".to_string()])
+        })
+        .await
+        .unwrap();
+        assert_eq!(matched, vec!["9"]);
+
+        let err = evaluate_rule_ids(None, &ids, json!({}), |_| async {
+            Err::<Vec<String>, _>("连接超时")
+        })
+        .await
+        .unwrap_err();
+        assert_eq!(err.to_string(), "内部错误: 规则评估失败: 连接超时");
+    }
+}
@@ -28,6 +28,9 @@ pub enum RuleError {
     #[error("规则未找到: {0}")]
     RuleNotFound(String),
 
+    #[error("聚合状态存储错误: {0}")]
+    StateStoreError(String),
+
     #[error("JSON 序列化错误: {0}")]
     JsonError(#[from] serde_json::Error),
 }
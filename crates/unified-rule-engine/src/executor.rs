@@ -2,6 +2,7 @@
 //!
 //! 实现规则的短路求值执行，返回匹配结果和评估追踪信息。
 
+use crate::aggregate::AggregateCondition;
 use crate::compiler::CompiledRule;
 use crate::error::Result;
 use crate::evaluator::ConditionEvaluator;
@@ -58,9 +59,48 @@ impl RuleExecutor {
         match node {
             RuleNode::Condition(cond) => self.evaluate_condition(cond, context, result, path),
             RuleNode::Group(group) => self.evaluate_group(group, context, result, path),
+            RuleNode::Aggregate(agg) => self.evaluate_aggregate(agg, context, result, path),
         }
     }
 
+    /// 评估聚合节点
+    ///
+    /// 聚合值由 `AggregateResolver` 预先写入上下文；未计算时按字段缺失处理，
+    /// 与普通条件的缺失字段语义一致。
+    fn evaluate_aggregate(
+        &self,
+        agg: &AggregateCondition,
+        context: &EvaluationContext,
+        result: &mut EvaluationResult,
+        path: &str,
+    ) -> Result<bool> {
+        let signature = agg.signature();
+        let aggregate_value = context.get_aggregate(&signature);
+
+        let matched = ConditionEvaluator::evaluate(aggregate_value, agg.operator, &agg.value)?;
+
+        if self.trace_enabled {
+            result.evaluation_trace.push(format!(
+                "{}: {} = {} {} {} => {}",
+                path,
+                signature,
+                aggregate_value.map_or("null".to_string(), |v| v.to_string()),
+                agg.operator,
+                agg.value,
+                if matched { "MATCHED" } else { "NOT_MATCHED" }
+            ));
+        }
+
+        if matched {
+            result.matched_conditions.push(format!(
+                "{}.{} {} {}",
+                path, signature, agg.operator, agg.value
+            ));
+        }
+
+        Ok(matched)
+    }
+
     /// 评估条件节点
     fn evaluate_condition(
         &self,
@@ -4,16 +4,23 @@
 
 #![allow(clippy::result_large_err)]
 
+use crate::aggregate::{
+    AggregateCondition, AggregateFunction, AggregateProgress, AggregateResolver,
+    AggregateStateStore, ResolveMode, TimeUnit, TimeWindow, WindowKind,
+};
 use crate::executor::RuleExecutor;
 use crate::models::{Condition, EvaluationContext, LogicalGroup, Rule, RuleNode};
 use crate::operators::{LogicalOperator, Operator};
 use crate::store::RuleStore;
 use badge_proto::rule_engine::rule_engine_service_server::RuleEngineService;
 use badge_proto::rule_engine::{
-    BatchEvaluateRequest, BatchEvaluateResponse, ConditionNode, DeleteRuleRequest,
-    DeleteRuleResponse, EvaluateRequest, EvaluateResponse, GroupNode, LoadRuleRequest,
-    LoadRuleResponse, LogicalOperator as ProtoLogicalOperator, Operator as ProtoOperator,
+    AggregateFunction as ProtoAggregateFunction, AggregateNode,
+    AggregateProgress as ProtoAggregateProgress, BatchEvaluateRequest, BatchEvaluateResponse,
+    ConditionNode, DeleteRuleRequest, DeleteRuleResponse, EvaluateRequest, EvaluateResponse,
+    GetRuleProgressRequest, GetRuleProgressResponse, GroupNode, LoadRuleRequest,
+    LoadRuleResponse, RuleProgress as ProtoRuleProgress, LogicalOperator as ProtoLogicalOperator, Operator as ProtoOperator,
     Rule as ProtoRule, RuleNode as ProtoRuleNode, TestRuleRequest, TestRuleResponse,
+    TimeUnit as ProtoTimeUnit, WindowType as ProtoWindowType,
 };
 use std::sync::Arc;
 use tonic::{Request, Response, Status};
@@ -23,16 +30,25 @@ use tracing::{info, instrument, warn};
 pub struct RuleEngineServiceImpl {
     store: RuleStore,
     executor: Arc<RuleExecutor>,
+    aggregates: Arc<AggregateResolver>,
 }
 
 impl RuleEngineServiceImpl {
+    /// 创建服务，聚合状态默认保存在内存中
     pub fn new(store: RuleStore) -> Self {
         Self {
             store,
             executor: Arc::new(RuleExecutor::new().with_trace()),
+            aggregates: Arc::new(AggregateResolver::in_memory()),
         }
     }
 
+    /// 指定聚合状态存储（多实例部署需使用 Redis 等共享存储）
+    pub fn with_aggregate_store(mut self, store: Arc<dyn AggregateStateStore>) -> Self {
+        self.aggregates = Arc::new(AggregateResolver::new(store));
+        self
+    }
+
     /// Proto Rule 转换为内部 Rule
     fn convert_rule(proto: &ProtoRule) -> Result<Rule, Status> {
         let root = Self::convert_rule_node(
@@ -75,10 +91,87 @@ impl RuleEngineServiceImpl {
             Some(badge_proto::rule_engine::rule_node::Node::Group(group)) => {
                 Ok(RuleNode::Group(Self::convert_group(group)?))
             }
+            Some(badge_proto::rule_engine::rule_node::Node::Aggregate(agg)) => {
+                Ok(RuleNode::Aggregate(Self::convert_aggregate(agg)?))
+            }
             None => Err(Status::invalid_argument("规则节点不能为空")),
         }
     }
 
+    /// 转换聚合节点
+    fn convert_aggregate(proto: &AggregateNode) -> Result<AggregateCondition, Status> {
+        let function = match proto.function() {
+            ProtoAggregateFunction::Count => AggregateFunction::Count,
+            ProtoAggregateFunction::Sum => AggregateFunction::Sum,
+            ProtoAggregateFunction::DistinctCount => AggregateFunction::DistinctCount,
+            ProtoAggregateFunction::Streak => AggregateFunction::Streak,
+            ProtoAggregateFunction::Unspecified => {
+                return Err(Status::invalid_argument("未指定聚合函数"));
+            }
+        };
+
+        let window = proto
+            .window
+            .as_ref()
+            .ok_or_else(|| Status::invalid_argument("聚合节点的时间窗口不能为空"))?;
+        let unit = match window.unit() {
+            ProtoTimeUnit::Minute => TimeUnit::Minute,
+            ProtoTimeUnit::Hour => TimeUnit::Hour,
+            ProtoTimeUnit::Day => TimeUnit::Day,
+            ProtoTimeUnit::Week => TimeUnit::Week,
+            ProtoTimeUnit::Month => TimeUnit::Month,
+            ProtoTimeUnit::Unspecified => {
+                return Err(Status::invalid_argument("未指定窗口时间单位"));
+            }
+        };
+        let kind = match window.r#type() {
+            ProtoWindowType::Tumbling => WindowKind::Tumbling,
+            // 与 JSON 规则保持一致，未指定时按滑动窗口处理
+            ProtoWindowType::Sliding | ProtoWindowType::Unspecified => WindowKind::Sliding,
+        };
+
+        let non_empty = |s: &str| (!s.is_empty()).then(|| s.to_string());
+
+        Ok(AggregateCondition {
+            function,
+            event_type: non_empty(&proto.event_type),
+            field: non_empty(&proto.field),
+            window: TimeWindow {
+                kind,
+                size: window.size,
+                unit,
+            },
+            operator: Self::convert_operator(proto.operator())?,
+            value: proto
+                .value
+                .as_ref()
+                .map(Self::convert_value)
+                .unwrap_or(serde_json::Value::Null),
+        })
+    }
+
+    /// 聚合进度转换为 Proto
+    fn aggregate_progress_to_proto(progress: &AggregateProgress) -> ProtoAggregateProgress {
+        let condition = &progress.condition;
+        let function = match condition.function {
+            AggregateFunction::Count => ProtoAggregateFunction::Count,
+            AggregateFunction::Sum => ProtoAggregateFunction::Sum,
+            AggregateFunction::DistinctCount => ProtoAggregateFunction::DistinctCount,
+            AggregateFunction::Streak => ProtoAggregateFunction::Streak,
+        };
+
+        ProtoAggregateProgress {
+            signature: condition.signature(),
+            function: function as i32,
+            event_type: condition.event_type.clone().unwrap_or_default(),
+            field: condition.field.clone().unwrap_or_default(),
+            current: progress.current,
+            target: progress.target,
+            percentage: progress.percentage(),
+            achieved: progress.achieved(),
+        }
+    }
+
     /// 转换条件节点
     fn convert_condition(proto: &ConditionNode) -> Result<Condition, Status> {
         let operator = Self::convert_operator(proto.operator())?;
@@ -197,7 +290,12 @@ impl RuleEngineService for RuleEngineServiceImpl {
             .get(&req.rule_id)
             .ok_or_else(|| Status::not_found(format!("规则不存在: {}", req.rule_id)))?;
 
-        let context = Self::convert_context(req.context.as_ref());
+        let mut context = Self::convert_context(req.context.as_ref());
+
+        self.aggregates
+            .resolve(&[&rule], &mut context, ResolveMode::Record)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
 
         let result = self
             .executor
@@ -222,19 +320,31 @@ impl RuleEngineService for RuleEngineServiceImpl {
         let req = request.into_inner();
         let start = std::time::Instant::now();
 
-        let context = Self::convert_context(req.context.as_ref());
+        let mut context = Self::convert_context(req.context.as_ref());
         let mut results = Vec::with_capacity(req.rule_ids.len());
 
-        for rule_id in &req.rule_ids {
-            let rule = match self.store.get(rule_id) {
-                Some(r) => r,
-                None => {
+        let rules: Vec<_> = req
+            .rule_ids
+            .iter()
+            .filter_map(|rule_id| {
+                let rule = self.store.get(rule_id);
+                if rule.is_none() {
                     warn!("规则不存在: {}", rule_id);
-                    continue;
                 }
-            };
+                rule
+            })
+            .collect();
+
+        // 同一事件只记录一次聚合状态，所有规则共享计算结果
+        let rule_refs: Vec<_> = rules.iter().collect();
+        self.aggregates
+            .resolve(&rule_refs, &mut context, ResolveMode::Record)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
 
-            match self.executor.execute(&rule, &context) {
+        for rule in &rules {
+            let rule_id = rule.id();
+            match self.executor.execute(rule, &context) {
                 Ok(result) => {
                     results.push(EvaluateResponse {
                         matched: result.matched,
@@ -323,7 +433,13 @@ impl RuleEngineService for RuleEngineServiceImpl {
             .compile(rule)
             .map_err(|e| Status::invalid_argument(e.to_string()))?;
 
-        let context = Self::convert_context(req.context.as_ref());
+        let mut context = Self::convert_context(req.context.as_ref());
+
+        // 试运行只读取已有聚合状态，不写入当前事件
+        self.aggregates
+            .resolve(&[&compiled], &mut context, ResolveMode::DryRun)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
 
         // 使用带追踪的执行器
         let executor = RuleExecutor::new().with_trace();
@@ -338,6 +454,47 @@ impl RuleEngineService for RuleEngineServiceImpl {
             evaluation_time_ms: result.evaluation_time_ms,
         }))
     }
+
+    /// 查询规则聚合条件的当前进度（只读，不记录事件）
+    #[instrument(skip(self, request), fields(user_id = %request.get_ref().user_id))]
+    async fn get_rule_progress(
+        &self,
+        request: Request<GetRuleProgressRequest>,
+    ) -> Result<Response<GetRuleProgressResponse>, Status> {
+        let req = request.into_inner();
+
+        if req.user_id.is_empty() {
+            return Err(Status::invalid_argument("user_id 不能为空"));
+        }
+
+        let now = chrono::Utc::now();
+        let mut rules = Vec::with_capacity(req.rule_ids.len());
+        for rule_id in &req.rule_ids {
+            // 已停用或尚未加载的规则没有进度可言，直接跳过
+            let Some(rule) = self.store.get(rule_id) else {
+                continue;
+            };
+            if !rule.has_aggregates() {
+                continue;
+            }
+
+            let progress = self
+                .aggregates
+                .progress(&rule, &req.user_id, now)
+                .await
+                .map_err(|e| Status::internal(e.to_string()))?;
+
+            rules.push(ProtoRuleProgress {
+                rule_id: rule_id.clone(),
+                aggregates: progress
+                    .iter()
+                    .map(Self::aggregate_progress_to_proto)
+                    .collect(),
+            });
+        }
+
+        Ok(Response::new(GetRuleProgressResponse { rules }))
+    }
 }
 
 #[cfg(test)]
@@ -395,6 +552,28 @@ mod tests {
         assert_eq!(value, serde_json::json!("test"));
     }
 
+    #[test]
+    fn test_aggregate_progress_to_proto() {
+        let progress = AggregateProgress {
+            condition: AggregateCondition::new(
+                AggregateFunction::Count,
+                Some("check_in"),
+                None,
+                TimeWindow::sliding(30, TimeUnit::Day),
+                Operator::Gte,
+                5,
+            ),
+            current: 3.0,
+            target: 5.0,
+        };
+
+        let proto = RuleEngineServiceImpl::aggregate_progress_to_proto(&progress);
+        assert_eq!(proto.function, ProtoAggregateFunction::Count as i32);
+        assert_eq!(proto.event_type, "check_in");
+        assert_eq!(proto.percentage, 60.0);
+        assert!(!proto.achieved);
+    }
+
     #[test]
     fn test_convert_context() {
         let context = create_test_context();
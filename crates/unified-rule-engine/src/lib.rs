@@ -4,9 +4,15 @@
 //! - JSON 规则定义和解析
 //! - 规则编译和缓存
 //! - 短路求值执行
+//! - 时间窗口聚合条件
+//! - 规则版本差异对比
+//! - 进程内嵌评估（供事件服务直接链接）
 //! - gRPC 服务接口
 
+pub mod aggregate;
 pub mod compiler;
+pub mod diff;
+pub mod embedded;
 pub mod error;
 pub mod evaluator;
 pub mod executor;
@@ -16,7 +22,13 @@ pub mod operators;
 pub mod store;
 pub mod template;
 
+pub use aggregate::{
+    AggregateCondition, AggregateProgress, AggregateResolver, AggregateStateStore,
+    InMemoryAggregateStore, RedisAggregateStore, ResolveMode,
+};
 pub use compiler::{CompiledRule, RuleCompiler};
+pub use diff::{ChangeKind, NodeChange, diff_rule_nodes};
+pub use embedded::{EmbeddedRuleEvaluator, evaluate_rule_ids};
 pub use error::{Result, RuleError};
 pub use evaluator::ConditionEvaluator;
 pub use executor::RuleExecutor;
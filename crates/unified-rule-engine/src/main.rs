@@ -4,13 +4,19 @@
 
 use anyhow::Result;
 use badge_proto::rule_engine::rule_engine_service_server::RuleEngineServiceServer;
+use badge_shared::cache::Cache;
 use badge_shared::config::AppConfig;
 use badge_shared::observability;
-use rule_engine::{Rule, RuleEngineServiceImpl, RuleNode, RuleStore};
+use badge_shared::observability::middleware::grpc as grpc_middleware;
+use badge_shared::rules::RuleReloadListener;
+use rule_engine::{RedisAggregateStore, Rule, RuleEngineServiceImpl, RuleNode, RuleStore};
+use sqlx::PgPool;
 use sqlx::postgres::PgPoolOptions;
 use std::net::SocketAddr;
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::signal;
+use tokio::sync::watch;
 use tonic::transport::Server;
 use tracing::{info, warn};
 
@@ -51,14 +57,33 @@ async fn main() -> Result<()> {
     let store = RuleStore::new();
     info!("Rule store initialized");
 
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+
     // 连接数据库并加载规则
-    match load_rules_from_database(&config, &store).await {
-        Ok(count) => info!("Loaded {} rules from database", count),
-        Err(e) => warn!("Failed to load rules from database: {}, starting with empty store", e),
+    match connect_database(&config).await {
+        Ok(pool) => {
+            match load_rules_from_database(&pool, &store).await {
+                Ok(count) => info!("Loaded {} rules from database", count),
+                Err(e) => {
+                    warn!("Failed to load rules from database: {}, starting with empty store", e)
+                }
+            }
+            start_rule_reload_listener(&config, pool, store.clone(), shutdown_rx.clone());
+        }
+        Err(e) => warn!("Failed to connect database: {}, starting with empty store", e),
     }
 
     // 创建 gRPC 服务
-    let rule_service = RuleEngineServiceImpl::new(store);
+    // 聚合状态需要在多个实例间共享，Redis 不可用时退化为进程内存储
+    let mut rule_service = RuleEngineServiceImpl::new(store);
+    match Cache::new(&config.redis) {
+        Ok(cache) => {
+            rule_service =
+                rule_service.with_aggregate_store(Arc::new(RedisAggregateStore::new(cache)));
+            info!("Aggregate state store: redis");
+        }
+        Err(e) => warn!("Failed to create Redis client: {}, using in-memory aggregate state", e),
+    }
 
     // 启动 gRPC 服务
     // 健康检查端点已由 observability 模块在 metrics_port 上提供
@@ -77,38 +102,82 @@ async fn main() -> Result<()> {
     }
 
     server_builder
+        .trace_fn(grpc_middleware::server_span)
         .add_service(RuleEngineServiceServer::new(rule_service))
         .serve_with_shutdown(service_config.grpc_addr, shutdown_signal())
         .await?;
 
+    let _ = shutdown_tx.send(true);
+
     info!("Service shutdown complete");
     Ok(())
 }
 
-/// 从数据库加载所有启用的规则
+/// 连接数据库
 ///
-/// 查询 badge_rules 表，将 rule_json 转换为规则引擎的 Rule 结构并加载。
-async fn load_rules_from_database(config: &AppConfig, store: &RuleStore) -> Result<usize> {
+/// 仅用于加载规则，连接数保持较小。
+async fn connect_database(config: &AppConfig) -> Result<PgPool> {
     let pool = PgPoolOptions::new()
         .max_connections(2)
         .acquire_timeout(Duration::from_secs(config.database.connect_timeout_seconds))
         .connect(&config.database.url)
         .await?;
+    Ok(pool)
+}
+
+/// 启动规则刷新监听
+///
+/// 管理后台发布、停用、修改、删除规则后会发送刷新事件，
+/// 规则引擎服务于所有服务组，收到任意刷新事件都从数据库全量重建规则并热替换。
+fn start_rule_reload_listener(
+    config: &AppConfig,
+    pool: PgPool,
+    store: RuleStore,
+    shutdown: watch::Receiver<bool>,
+) {
+    let listener = match RuleReloadListener::new(&config.kafka, &config.service_name) {
+        Ok(listener) => listener,
+        Err(e) => {
+            warn!("Failed to create rule reload listener: {}, rules will not hot-reload", e);
+            return;
+        }
+    };
 
+    tokio::spawn(async move {
+        listener
+            .run(shutdown, None, |_event| {
+                let pool = pool.clone();
+                let store = store.clone();
+                async move {
+                    match load_rules_from_database(&pool, &store).await {
+                        Ok(count) => info!("Reloaded {} rules from database", count),
+                        Err(e) => warn!("Failed to reload rules from database: {}", e),
+                    }
+                }
+            })
+            .await;
+    });
+}
+
+/// 从数据库加载所有启用的规则
+///
+/// 查询 badge_rules 表，将 rule_json 转换为规则引擎的 Rule 结构，
+/// 并全量替换规则存储（已停用或删除的规则随之移除）。
+async fn load_rules_from_database(pool: &PgPool, store: &RuleStore) -> Result<usize> {
     // 查询所有启用的规则
     let rows = sqlx::query_as::<_, RuleRow>(
         r#"
-        SELECT r.id, r.rule_code, r.rule_json
+        SELECT r.id, r.rule_code, r.rule_json, r.current_version
         FROM badge_rules r
         WHERE r.enabled = TRUE
           AND (r.start_time IS NULL OR r.start_time <= NOW())
           AND (r.end_time IS NULL OR r.end_time > NOW())
         "#,
     )
-    .fetch_all(&pool)
+    .fetch_all(pool)
     .await?;
 
-    let mut loaded_count = 0;
+    let mut rules = Vec::with_capacity(rows.len());
     for row in rows {
         // 将 rule_json 包装成完整的 Rule 结构
         let rule_id = row.id.to_string();
@@ -117,20 +186,14 @@ async fn load_rules_from_database(config: &AppConfig, store: &RuleStore) -> Resu
         // 尝试解析 rule_json 为 RuleNode
         match serde_json::from_value::<RuleNode>(row.rule_json.clone()) {
             Ok(root) => {
-                let rule = Rule {
-                    id: rule_id.clone(),
+                rules.push(Rule {
+                    id: rule_id,
                     name: rule_name,
-                    version: "1.0".to_string(),
+                    version: row.current_version.to_string(),
                     root,
                     created_at: chrono::Utc::now(),
                     updated_at: chrono::Utc::now(),
-                };
-
-                if let Err(e) = store.load(rule) {
-                    warn!(rule_id = %rule_id, error = %e, "Failed to load rule");
-                } else {
-                    loaded_count += 1;
-                }
+                });
             }
             Err(e) => {
                 warn!(
@@ -143,8 +206,7 @@ async fn load_rules_from_database(config: &AppConfig, store: &RuleStore) -> Resu
         }
     }
 
-    pool.close().await;
-    Ok(loaded_count)
+    Ok(store.replace_all(rules))
 }
 
 /// 数据库规则行
@@ -153,6 +215,7 @@ struct RuleRow {
     id: i64,
     rule_code: Option<String>,
     rule_json: serde_json::Value,
+    current_version: i32,
 }
 
 /// 优雅关闭信号处理
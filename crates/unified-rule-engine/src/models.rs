@@ -1,9 +1,11 @@
 //! 规则引擎领域模型
 
+use crate::aggregate::AggregateCondition;
 use crate::operators::{LogicalOperator, Operator};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
 use uuid::Uuid;
 
 /// 规则定义
@@ -32,12 +34,13 @@ impl Rule {
     }
 }
 
-/// 规则节点（条件或逻辑组）
+/// 规则节点（条件、逻辑组或聚合条件）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum RuleNode {
     Condition(Condition),
     Group(LogicalGroup),
+    Aggregate(AggregateCondition),
 }
 
 /// 条件节点
@@ -83,17 +86,22 @@ impl LogicalGroup {
 #[derive(Debug, Clone, Default)]
 pub struct EvaluationContext {
     data: Value,
+    /// 预先计算好的聚合值，键为 `AggregateCondition::signature()`
+    aggregates: HashMap<String, Value>,
 }
 
 impl EvaluationContext {
     pub fn new(data: Value) -> Self {
-        Self { data }
+        Self {
+            data,
+            aggregates: HashMap::new(),
+        }
     }
 
     /// 从 JSON 对象创建
     pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
         let data: Value = serde_json::from_str(json)?;
-        Ok(Self { data })
+        Ok(Self::new(data))
     }
 
     /// 获取字段值（支持点号分隔的路径，如 "event.type" 或 "user.profile.age"）
@@ -122,6 +130,16 @@ impl EvaluationContext {
     pub fn data(&self) -> &Value {
         &self.data
     }
+
+    /// 写入聚合值
+    pub fn set_aggregate(&mut self, signature: impl Into<String>, value: Value) {
+        self.aggregates.insert(signature.into(), value);
+    }
+
+    /// 获取聚合值
+    pub fn get_aggregate(&self, signature: &str) -> Option<&Value> {
+        self.aggregates.get(signature)
+    }
 }
 
 /// 评估结果
@@ -6,6 +6,7 @@ use crate::compiler::{CompiledRule, RuleCompiler};
 use crate::error::{Result, RuleError};
 use crate::models::Rule;
 use dashmap::DashMap;
+use std::collections::HashMap;
 use std::sync::Arc;
 use tracing::{info, instrument, warn};
 
@@ -102,6 +103,19 @@ impl RuleStore {
         self.rules.contains_key(rule_id)
     }
 
+    /// 获取规则当前生效的版本号
+    pub fn live_version(&self, rule_id: &str) -> Option<String> {
+        self.rules.get(rule_id).map(|r| r.rule.version.clone())
+    }
+
+    /// 获取所有规则当前生效的版本号（规则 ID → 版本号）
+    pub fn live_versions(&self) -> HashMap<String, String> {
+        self.rules
+            .iter()
+            .map(|r| (r.key().clone(), r.rule.version.clone()))
+            .collect()
+    }
+
     /// 获取所有规则 ID
     pub fn list_ids(&self) -> Vec<String> {
         self.rules.iter().map(|r| r.key().clone()).collect()
@@ -138,6 +152,46 @@ impl RuleStore {
         Ok(loaded_ids)
     }
 
+    /// 全量替换规则
+    ///
+    /// 先编译全部新规则，再逐条覆盖并移除不再存在的规则。
+    /// 替换过程中每条规则都是原子切换的，评估请求不会读到半编译状态。
+    /// 编译失败的规则会被跳过（与启动加载行为一致），返回成功加载的数量。
+    #[instrument(skip(self, rules))]
+    pub fn replace_all(&self, rules: Vec<Rule>) -> usize {
+        let compiled: Vec<CompiledRule> = {
+            let mut compiler = self.compiler.lock();
+            rules
+                .into_iter()
+                .filter_map(|rule| {
+                    let rule_id = rule.id.clone();
+                    compiler
+                        .compile(rule)
+                        .inspect_err(|e| warn!(rule_id = %rule_id, error = %e, "规则编译失败，跳过"))
+                        .ok()
+                })
+                .collect()
+        };
+
+        let new_ids: std::collections::HashSet<String> =
+            compiled.iter().map(|r| r.id().to_string()).collect();
+        let loaded = compiled.len();
+
+        for rule in compiled {
+            let rule_id = rule.id().to_string();
+            if let Some(previous) = self.live_version(&rule_id)
+                && previous != rule.rule.version
+            {
+                info!(rule_id = %rule_id, from = %previous, to = %rule.rule.version, "规则版本切换");
+            }
+            self.rules.insert(rule_id, rule);
+        }
+        self.rules.retain(|id, _| new_ids.contains(id));
+
+        info!("规则全量替换完成: {} 条", loaded);
+        loaded
+    }
+
     /// 清空所有规则
     #[instrument(skip(self))]
     pub fn clear(&self) {
@@ -384,4 +438,41 @@ mod tests {
 
         assert_eq!(store.len(), 200);
     }
+
+    #[test]
+    fn test_replace_all() {
+        let store = RuleStore::new();
+        store.load(sample_rule("rule-001", "old")).unwrap();
+        store.load(sample_rule("rule-002", "removed")).unwrap();
+
+        let mut invalid = sample_rule("rule-004", "invalid");
+        invalid.name = String::new();
+
+        let loaded = store.replace_all(vec![
+            sample_rule("rule-001", "new"),
+            sample_rule("rule-003", "added"),
+            invalid,
+        ]);
+
+        assert_eq!(loaded, 2);
+        assert_eq!(store.get("rule-001").unwrap().name(), "new");
+        assert!(!store.contains("rule-002"));
+        assert!(store.contains("rule-003"));
+        assert!(!store.contains("rule-004"));
+    }
+
+    #[test]
+    fn test_live_version_follows_replacement() {
+        let store = RuleStore::new();
+        let mut rule = sample_rule("rule-001", "v1");
+        rule.version = "1".to_string();
+        store.replace_all(vec![rule.clone()]);
+        assert_eq!(store.live_version("rule-001").as_deref(), Some("1"));
+
+        rule.version = "2".to_string();
+        store.replace_all(vec![rule]);
+        assert_eq!(store.live_version("rule-001").as_deref(), Some("2"));
+        assert_eq!(store.live_versions().get("rule-001").map(String::as_str), Some("2"));
+        assert_eq!(store.live_version("missing"), None);
+    }
 }
@@ -3,8 +3,8 @@
 //! 测试完整的规则加载、编译、执行工作流。
 
 use rule_engine::{
-    Condition, EvaluationContext, LogicalGroup, Operator, Rule, RuleCompiler, RuleExecutor,
-    RuleNode, RuleStore,
+    AggregateResolver, Condition, EvaluationContext, LogicalGroup, Operator, ResolveMode, Rule,
+    RuleCompiler, RuleExecutor, RuleNode, RuleStore,
 };
 use serde_json::json;
 
@@ -1018,3 +1018,63 @@ fn test_rule_store_stats() {
     assert_eq!(stats.rules_count, 2);
     assert_eq!(stats.total_fields, 3); // r1: 1 field, r2: 2 fields
 }
+
+// ==================== 聚合条件测试 ====================
+
+#[tokio::test]
+async fn test_aggregate_rule_workflow() {
+    let store = RuleStore::new();
+    store
+        .load_from_json(
+            r#"
+    {
+        "id": "frequent-buyer",
+        "name": "30天内购买3次",
+        "version": "1.0",
+        "root": {
+            "type": "group",
+            "operator": "AND",
+            "children": [
+                {
+                    "type": "condition",
+                    "field": "event_type",
+                    "operator": "eq",
+                    "value": "PURCHASE"
+                },
+                {
+                    "type": "aggregate",
+                    "function": "count",
+                    "event_type": "purchase",
+                    "window": {"type": "sliding", "size": 30, "unit": "day"},
+                    "operator": "gte",
+                    "value": 3
+                }
+            ]
+        }
+    }
+    "#,
+        )
+        .unwrap();
+
+    let compiled = store.get("frequent-buyer").unwrap();
+    let resolver = AggregateResolver::in_memory();
+    let executor = RuleExecutor::new();
+
+    let mut outcomes = Vec::new();
+    for (i, day) in ["2024-05-01", "2024-05-10", "2024-05-20"].iter().enumerate() {
+        let mut context = EvaluationContext::new(json!({
+            "event_id": format!("evt-{}", i),
+            "event_type": "PURCHASE",
+            "user_id": "user-agg",
+            "timestamp": format!("{}T12:00:00Z", day),
+        }));
+        resolver
+            .resolve(&[&compiled], &mut context, ResolveMode::Record)
+            .await
+            .unwrap();
+        outcomes.push(executor.execute(&compiled, &context).unwrap().matched);
+    }
+
+    // 第三次购买时才满足条件
+    assert_eq!(outcomes, vec![false, false, true]);
+}
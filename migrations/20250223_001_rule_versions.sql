@@ -0,0 +1,49 @@
+-- 规则版本历史
+-- 每次编辑 badge_rules 都生成一条不可变的版本快照，支持历史查询、差异对比和回滚
+
+-- 规则当前生效的版本号
+ALTER TABLE badge_rules
+ADD COLUMN IF NOT EXISTS current_version INT NOT NULL DEFAULT 1;
+
+COMMENT ON COLUMN badge_rules.current_version IS '当前生效的版本号，对应 badge_rule_versions.version';
+
+-- 规则版本快照表
+CREATE TABLE IF NOT EXISTS badge_rule_versions (
+    id BIGSERIAL PRIMARY KEY,
+    rule_id BIGINT NOT NULL REFERENCES badge_rules(id) ON DELETE CASCADE,
+    version INT NOT NULL,
+    event_type VARCHAR(50),
+    rule_code VARCHAR(100),
+    name VARCHAR(200),
+    description TEXT,
+    rule_json JSONB NOT NULL,
+    start_time TIMESTAMPTZ,
+    end_time TIMESTAMPTZ,
+    max_count_per_user INT,
+    global_quota INT,
+    change_type VARCHAR(20) NOT NULL,
+    source_version INT,
+    created_by VARCHAR(100),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT uq_badge_rule_versions_rule_version UNIQUE (rule_id, version),
+    CONSTRAINT chk_badge_rule_versions_change_type CHECK (change_type IN ('create', 'update', 'rollback'))
+);
+
+COMMENT ON TABLE badge_rule_versions IS '规则版本快照，记录写入后不再修改';
+COMMENT ON COLUMN badge_rule_versions.version IS '规则内递增的版本号，从 1 开始';
+COMMENT ON COLUMN badge_rule_versions.change_type IS '变更类型：create-创建，update-编辑，rollback-回滚';
+COMMENT ON COLUMN badge_rule_versions.source_version IS '回滚来源版本号，仅 rollback 类型有值';
+COMMENT ON COLUMN badge_rule_versions.created_by IS '变更操作人';
+
+CREATE INDEX IF NOT EXISTS idx_badge_rule_versions_rule ON badge_rule_versions(rule_id, version DESC);
+
+-- 为已有规则补齐初始版本
+INSERT INTO badge_rule_versions (
+    rule_id, version, event_type, rule_code, name, description, rule_json,
+    start_time, end_time, max_count_per_user, global_quota, change_type, created_at
+)
+SELECT
+    id, current_version, event_type, rule_code, name, description, rule_json,
+    start_time, end_time, max_count_per_user, global_quota, 'create', updated_at
+FROM badge_rules
+ON CONFLICT (rule_id, version) DO NOTHING;
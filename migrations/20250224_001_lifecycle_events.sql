@@ -0,0 +1,40 @@
+-- 身份类与季节类事件
+-- 注册、会员升级、周年、季节活动、营销活动事件经 badge.lifecycle.events 投递，
+-- 由 event-engagement-service 处理，规则与互动类事件共用 engagement 服务组
+
+INSERT INTO event_types (code, name, service_group, description) VALUES
+    -- 身份类事件
+    ('registration', '注册', 'engagement', '用户完成注册'),
+    ('membership_upgrade', '会员升级', 'engagement', '用户会员等级提升'),
+    ('anniversary', '注册周年', 'engagement', '用户注册周年纪念日，由周年调度器生成'),
+    -- 季节类事件
+    ('seasonal_activity', '季节活动', 'engagement', '用户参与季节性活动，受徽章系列活动时间约束'),
+    ('campaign_participation', '营销活动参与', 'engagement', '用户参与营销活动，受徽章系列活动时间约束')
+ON CONFLICT (code) DO NOTHING;
+
+-- 用户注册日期：处理注册事件时写入，周年调度器据此生成周年事件
+CREATE TABLE IF NOT EXISTS user_registrations (
+    user_id VARCHAR(100) PRIMARY KEY,
+    registered_at TIMESTAMPTZ NOT NULL,
+    source_event_id VARCHAR(100),
+    last_anniversary_year INT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE user_registrations IS '用户注册日期，用于生成注册周年事件';
+COMMENT ON COLUMN user_registrations.registered_at IS '注册时间（取注册事件的发生时间）';
+COMMENT ON COLUMN user_registrations.source_event_id IS '写入该记录的注册事件 ID';
+COMMENT ON COLUMN user_registrations.last_anniversary_year IS '最近一次已生成周年事件的年份，防止同一年重复生成';
+
+-- 周年调度器按注册日期的月、日扫描（UTC）
+CREATE INDEX IF NOT EXISTS idx_user_registrations_month_day
+    ON user_registrations (
+        (EXTRACT(MONTH FROM registered_at AT TIME ZONE 'UTC')::int),
+        (EXTRACT(DAY FROM registered_at AT TIME ZONE 'UTC')::int)
+    );
+
+DROP TRIGGER IF EXISTS update_user_registrations_updated_at ON user_registrations;
+CREATE TRIGGER update_user_registrations_updated_at
+    BEFORE UPDATE ON user_registrations
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
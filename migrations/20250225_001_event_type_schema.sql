@@ -0,0 +1,7 @@
+-- 自定义事件类型
+-- 事件类型由管理后台注册，可为事件负载配置 JSON Schema，事件服务在规则评估前据此校验
+
+ALTER TABLE event_types
+ADD COLUMN IF NOT EXISTS payload_schema JSONB;
+
+COMMENT ON COLUMN event_types.payload_schema IS '事件负载（EventPayload.data）的 JSON Schema，为空表示不校验';
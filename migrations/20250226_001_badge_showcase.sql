@@ -0,0 +1,13 @@
+-- 徽章墙展示位（置顶徽章）
+-- 用户可将持有的徽章置顶到徽章墙展示位，pin_order 决定展示顺序，为空表示未置顶
+
+ALTER TABLE user_badges
+ADD COLUMN IF NOT EXISTS pin_order INT,
+ADD COLUMN IF NOT EXISTS pinned_at TIMESTAMPTZ;
+
+COMMENT ON COLUMN user_badges.pin_order IS '置顶展示顺序（从 1 开始，越小越靠前），为空表示未置顶';
+COMMENT ON COLUMN user_badges.pinned_at IS '置顶时间';
+
+CREATE INDEX IF NOT EXISTS idx_user_badges_pinned
+ON user_badges(user_id, pin_order)
+WHERE pin_order IS NOT NULL;
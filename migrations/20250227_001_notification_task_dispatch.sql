@@ -0,0 +1,16 @@
+-- 通知任务分发
+-- NotificationTaskWorker 领取 pending 任务，逐渠道投递到 badge.notifications，
+-- 记录各渠道投递结果；存在失败渠道时按重试间隔指数退避，仅重投失败渠道
+
+ALTER TABLE notification_tasks
+ADD COLUMN IF NOT EXISTS channel_results JSONB NOT NULL DEFAULT '{}'::jsonb,
+ADD COLUMN IF NOT EXISTS retry_interval_seconds INT NOT NULL DEFAULT 60,
+ADD COLUMN IF NOT EXISTS next_retry_at TIMESTAMPTZ;
+
+COMMENT ON COLUMN notification_tasks.channel_results IS '各渠道投递结果，键为渠道名，值包含 status(sent/failed/unsupported)、attempts、error、sent_at';
+COMMENT ON COLUMN notification_tasks.retry_interval_seconds IS '重试基础间隔（秒），实际间隔 = 基础间隔 * 2^retry_count';
+COMMENT ON COLUMN notification_tasks.next_retry_at IS '下次重试时间，为空表示可立即处理';
+
+CREATE INDEX IF NOT EXISTS idx_notification_tasks_dispatch
+ON notification_tasks(next_retry_at, created_at)
+WHERE status = 'pending';
@@ -0,0 +1,41 @@
+-- 用户通知偏好与免打扰延迟投递
+-- 通知分发前读取用户偏好：按通知类型过滤渠道，免打扰时段内的非紧急通知
+-- 写入 deferred_notifications，时段结束后由 notification-worker 投递
+
+CREATE TABLE IF NOT EXISTS user_notification_preferences (
+    user_id VARCHAR(100) PRIMARY KEY,
+    channels JSONB NOT NULL DEFAULT '{}'::jsonb,
+    quiet_start TIME,
+    quiet_end TIME,
+    timezone VARCHAR(64) NOT NULL DEFAULT 'Asia/Shanghai',
+    locale VARCHAR(20) NOT NULL DEFAULT 'zh-CN',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE user_notification_preferences IS '用户通知偏好';
+COMMENT ON COLUMN user_notification_preferences.channels IS '各通知类型启用的渠道，键为通知类型（如 BADGE_GRANTED），值为渠道数组；未配置的类型使用通知自带的渠道';
+COMMENT ON COLUMN user_notification_preferences.quiet_start IS '免打扰开始时间（用户本地时间），与 quiet_end 同时为空表示未设置';
+COMMENT ON COLUMN user_notification_preferences.quiet_end IS '免打扰结束时间（用户本地时间），早于开始时间表示跨越午夜';
+COMMENT ON COLUMN user_notification_preferences.timezone IS 'IANA 时区，如 Asia/Shanghai';
+COMMENT ON COLUMN user_notification_preferences.locale IS '通知语言，如 zh-CN、en-US';
+
+DROP TRIGGER IF EXISTS update_user_notification_preferences_updated_at ON user_notification_preferences;
+CREATE TRIGGER update_user_notification_preferences_updated_at
+    BEFORE UPDATE ON user_notification_preferences
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+CREATE TABLE IF NOT EXISTS deferred_notifications (
+    notification_id VARCHAR(100) PRIMARY KEY,
+    user_id VARCHAR(100) NOT NULL,
+    payload JSONB NOT NULL,
+    deliver_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE deferred_notifications IS '免打扰时段内延迟投递的通知';
+COMMENT ON COLUMN deferred_notifications.payload IS '完整的 NotificationEvent，渠道已按用户偏好过滤';
+COMMENT ON COLUMN deferred_notifications.deliver_at IS '计划投递时间，即免打扰时段结束时间';
+
+CREATE INDEX IF NOT EXISTS idx_deferred_notifications_due
+ON deferred_notifications(deliver_at);
@@ -0,0 +1,35 @@
+-- 通知模板
+-- 运营在管理后台维护的本地化通知模板，badge-management-service 和 notification-worker
+-- 定期加载并覆盖内置默认模板。模板按通知类型、语言和渠道区分，渠道为空表示该语言的通用模板
+
+CREATE TABLE IF NOT EXISTS notification_templates (
+    id BIGSERIAL PRIMARY KEY,
+    notification_type VARCHAR(30) NOT NULL,
+    locale VARCHAR(20) NOT NULL DEFAULT 'zh-CN',
+    channel VARCHAR(20),
+    title_template TEXT NOT NULL,
+    body_template TEXT NOT NULL,
+    html_template TEXT,
+    sample_data JSONB NOT NULL DEFAULT '{}'::jsonb,
+    description TEXT,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE notification_templates IS '本地化通知模板';
+COMMENT ON COLUMN notification_templates.notification_type IS '通知类型，如 BADGE_GRANTED';
+COMMENT ON COLUMN notification_templates.locale IS '模板语言，如 zh-CN、en-US';
+COMMENT ON COLUMN notification_templates.channel IS '渠道（APP_PUSH/SMS/WE_CHAT/EMAIL），为空表示该语言的通用模板';
+COMMENT ON COLUMN notification_templates.title_template IS '标题模板，支持 {{变量}}、{{#if}} 和 {{#each}}';
+COMMENT ON COLUMN notification_templates.body_template IS '纯文本正文模板';
+COMMENT ON COLUMN notification_templates.html_template IS 'HTML 正文模板，仅邮件渠道使用，变量值渲染时转义';
+COMMENT ON COLUMN notification_templates.sample_data IS '预览使用的示例数据';
+
+CREATE UNIQUE INDEX IF NOT EXISTS uk_notification_templates_type_locale_channel
+ON notification_templates(notification_type, locale, COALESCE(channel, ''));
+
+DROP TRIGGER IF EXISTS update_notification_templates_updated_at ON notification_templates;
+CREATE TRIGGER update_notification_templates_updated_at
+    BEFORE UPDATE ON notification_templates
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,68 @@
+-- 死信消息
+-- 管理后台的 DLQ 消费者把死信队列中的消息持久化到此表，重放调度器按 next_retry_at
+-- 把到期消息发回原始 topic；耗尽重试次数的消息保留在表中，由运营在管理后台查看、重放或清理
+
+CREATE TABLE IF NOT EXISTS dead_letter_messages (
+    id BIGSERIAL PRIMARY KEY,
+    message_id VARCHAR(100) NOT NULL,
+    source_topic VARCHAR(100) NOT NULL,
+    source_service VARCHAR(100) NOT NULL,
+    payload TEXT NOT NULL,
+    error TEXT NOT NULL,
+    retry_count INT NOT NULL DEFAULT 0,
+    max_retries INT NOT NULL DEFAULT 3,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending',
+    first_failed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_failed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    next_retry_at TIMESTAMPTZ,
+    replayed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT uk_dead_letter_messages_topic_message UNIQUE (source_topic, message_id),
+    CONSTRAINT chk_dead_letter_messages_status
+        CHECK (status IN ('pending', 'replaying', 'replayed', 'exhausted'))
+);
+
+COMMENT ON TABLE dead_letter_messages IS '死信消息';
+COMMENT ON COLUMN dead_letter_messages.message_id IS '原始消息标识，通常为事件 ID 或通知 ID';
+COMMENT ON COLUMN dead_letter_messages.source_topic IS '重放时发回的原始 topic，为空表示无法自动重放';
+COMMENT ON COLUMN dead_letter_messages.source_service IS '写入死信的服务';
+COMMENT ON COLUMN dead_letter_messages.payload IS '原始消息内容';
+COMMENT ON COLUMN dead_letter_messages.error IS '最近一次失败原因';
+COMMENT ON COLUMN dead_letter_messages.status IS '状态：pending 等待重放 / replaying 重放中 / replayed 已重放 / exhausted 需人工介入';
+COMMENT ON COLUMN dead_letter_messages.next_retry_at IS '下一次自动重放时间';
+
+CREATE INDEX IF NOT EXISTS idx_dead_letter_messages_due
+ON dead_letter_messages(next_retry_at) WHERE status = 'pending';
+
+CREATE INDEX IF NOT EXISTS idx_dead_letter_messages_service_status
+ON dead_letter_messages(source_service, status);
+
+DROP TRIGGER IF EXISTS update_dead_letter_messages_updated_at ON dead_letter_messages;
+CREATE TRIGGER update_dead_letter_messages_updated_at
+    BEFORE UPDATE ON dead_letter_messages
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- 死信管理权限
+INSERT INTO permission (code, name, module, action, resource_pattern, description, sort_order) VALUES
+('system:dlq:read', '查看死信', 'system', 'read', '/system/dlq/*', '查看死信消息列表、详情和积压统计', 130),
+('system:dlq:write', '管理死信', 'system', 'write', '/system/dlq/*', '重放、清理死信消息', 131)
+ON CONFLICT (code) DO UPDATE SET
+    name = EXCLUDED.name,
+    module = EXCLUDED.module,
+    action = EXCLUDED.action,
+    resource_pattern = EXCLUDED.resource_pattern,
+    description = EXCLUDED.description,
+    sort_order = EXCLUDED.sort_order;
+
+INSERT INTO role_permission (role_id, permission_id)
+SELECT r.id, p.id
+FROM role r, permission p
+WHERE r.code = 'admin' AND p.code IN ('system:dlq:read', 'system:dlq:write')
+ON CONFLICT DO NOTHING;
+
+INSERT INTO role_permission (role_id, permission_id)
+SELECT r.id, p.id
+FROM role r, permission p
+WHERE r.code = 'viewer' AND p.code = 'system:dlq:read'
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,13 @@
+-- 分级徽章（铜 → 银 → 金）
+-- 徽章配置有序等级及门槛，用户持有数量达到门槛时自动升级；
+-- 同一徽章只保留一条持有记录，level 记录当前等级，高等级替换低等级展示
+
+ALTER TABLE badges
+ADD COLUMN IF NOT EXISTS levels JSONB NOT NULL DEFAULT '[]'::jsonb;
+
+COMMENT ON COLUMN badges.levels IS '等级定义 [{"level", "name", "threshold"}]，为空表示非分级徽章；各等级资源在 assets.levels 中配置';
+
+ALTER TABLE user_badges
+ADD COLUMN IF NOT EXISTS level INT;
+
+COMMENT ON COLUMN user_badges.level IS '分级徽章当前等级（只升不降），非分级徽章为空';
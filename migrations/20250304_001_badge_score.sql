@@ -0,0 +1,10 @@
+-- 徽章积分
+-- 用户积分榜按徽章积分加权统计持有数量，默认每个徽章 1 分
+
+ALTER TABLE badges
+ADD COLUMN IF NOT EXISTS score INT NOT NULL DEFAULT 1;
+
+COMMENT ON COLUMN badges.score IS '徽章积分，用于积分排行榜加权统计';
+
+ALTER TABLE badges
+ADD CONSTRAINT badges_score_non_negative CHECK (score >= 0);
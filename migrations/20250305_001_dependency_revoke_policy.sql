@@ -0,0 +1,27 @@
+-- 反向级联：前置徽章被撤销或过期时，按依赖关系上的策略处理已获得的目标徽章
+
+-- ==================== 依赖撤销策略 ====================
+
+ALTER TABLE badge_dependencies
+ADD COLUMN IF NOT EXISTS revoke_policy VARCHAR(20) NOT NULL DEFAULT 'keep';
+
+COMMENT ON COLUMN badge_dependencies.revoke_policy IS '前置徽章失效时目标徽章的处理策略：keep-保留，revoke-一并撤销，suspend-暂停直至重新满足前置条件（仅 prerequisite 类型生效）';
+
+ALTER TABLE badge_dependencies
+ADD CONSTRAINT badge_dependencies_revoke_policy_check
+    CHECK (revoke_policy IN ('keep', 'revoke', 'suspend'));
+
+-- 反向级联：前置徽章失效时，快速找到需要处理的目标徽章
+CREATE INDEX IF NOT EXISTS idx_badge_deps_revoke_policy ON badge_dependencies(depends_on_badge_id)
+    WHERE revoke_policy != 'keep' AND enabled = TRUE;
+
+-- ==================== 级联评估日志 ====================
+
+ALTER TABLE cascade_evaluation_logs
+ADD COLUMN IF NOT EXISTS direction VARCHAR(10) NOT NULL DEFAULT 'grant';
+
+ALTER TABLE cascade_evaluation_logs
+ADD COLUMN IF NOT EXISTS affected_badges JSONB;
+
+COMMENT ON COLUMN cascade_evaluation_logs.direction IS '评估方向：grant-徽章发放后的正向级联，revoke-徽章撤销或过期后的反向级联';
+COMMENT ON COLUMN cascade_evaluation_logs.affected_badges IS '反向级联撤销/暂停的徽章，或正向级联中恢复的暂停徽章';
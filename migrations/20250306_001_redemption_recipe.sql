@@ -0,0 +1,12 @@
+-- 兑换配方：在固定徽章列表之外支持备选、系列/分类通配，以及消耗徽章的选择策略
+
+ALTER TABLE badge_redemption_rules
+ADD COLUMN IF NOT EXISTS recipe JSONB;
+
+COMMENT ON COLUMN badge_redemption_rules.recipe IS '兑换配方（JSON）：{"terms": [...], "selection": "soonest_expiring"}，条目类型 badge/any_of/series/category；为空时按 required_badges 固定列表兑换';
+
+-- 记录每笔消耗满足的是配方中的哪一项
+ALTER TABLE redemption_details
+ADD COLUMN IF NOT EXISTS recipe_term INT;
+
+COMMENT ON COLUMN redemption_details.recipe_term IS '满足的兑换配方条目序号（从 0 开始），配方上线前的明细为空';
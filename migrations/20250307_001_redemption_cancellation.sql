@@ -0,0 +1,16 @@
+-- 兑换订单取消：记录取消信息，并标记已退回的兑换明细，保证退回徽章只执行一次
+
+ALTER TABLE redemption_orders
+ADD COLUMN IF NOT EXISTS cancelled_at TIMESTAMPTZ,
+ADD COLUMN IF NOT EXISTS cancel_reason TEXT,
+ADD COLUMN IF NOT EXISTS cancelled_by VARCHAR(100);
+
+COMMENT ON COLUMN redemption_orders.cancelled_at IS '订单取消时间';
+COMMENT ON COLUMN redemption_orders.cancel_reason IS '取消原因';
+COMMENT ON COLUMN redemption_orders.cancelled_by IS '取消发起人：用户取消为 user:<user_id>，管理员取消为操作人账号';
+
+-- 明细退回后写入时间，重试取消时跳过已退回的明细
+ALTER TABLE redemption_details
+ADD COLUMN IF NOT EXISTS reversed_at TIMESTAMPTZ;
+
+COMMENT ON COLUMN redemption_details.reversed_at IS '消耗的徽章退回时间，为空表示未退回';
@@ -0,0 +1,21 @@
+-- 待处理反向级联：撤销或过期事务内登记，级联执行成功后在同一事务内删除
+-- 级联失败时记录保留，由过期 Worker 按退避时间重试，避免依赖徽章永久保持有效
+
+CREATE TABLE IF NOT EXISTS pending_revoke_cascades (
+    id BIGSERIAL PRIMARY KEY,
+    user_id VARCHAR(64) NOT NULL,
+    revoked_badge_id BIGINT NOT NULL REFERENCES badges(id) ON DELETE CASCADE,
+    attempts INT NOT NULL DEFAULT 0,
+    last_error TEXT,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE pending_revoke_cascades IS '待处理的反向级联，与撤销/过期在同一事务内写入，保证级联失败后可重试';
+COMMENT ON COLUMN pending_revoke_cascades.revoked_badge_id IS '被撤销或过期的前置徽章';
+COMMENT ON COLUMN pending_revoke_cascades.attempts IS '已失败的重试次数，用于计算退避间隔';
+COMMENT ON COLUMN pending_revoke_cascades.last_error IS '最近一次级联失败的错误信息';
+COMMENT ON COLUMN pending_revoke_cascades.next_attempt_at IS '下次允许重试的时间';
+
+-- 重试扫描按到期时间取数
+CREATE INDEX IF NOT EXISTS idx_pending_revoke_cascades_due ON pending_revoke_cascades(next_attempt_at);
@@ -0,0 +1,31 @@
+-- 注册周年事件可靠投递：认领与投递分离，并记录调度器已完成扫描的日期
+-- 认领写入 last_anniversary_year，投递成功后写入 anniversary_published_year，
+-- 两者不一致的记录由调度器重试投递；停机期间漏扫的日期从检查点续扫
+
+ALTER TABLE user_registrations
+ADD COLUMN IF NOT EXISTS anniversary_published_year INT;
+
+COMMENT ON COLUMN user_registrations.last_anniversary_year IS '最近一次已认领周年事件的年份，防止同一年重复生成';
+COMMENT ON COLUMN user_registrations.anniversary_published_year IS '最近一次周年事件已成功投递的年份，小于 last_anniversary_year 表示待投递';
+
+-- 此前的认领在投递失败时会撤销，已认领的记录视为已投递
+UPDATE user_registrations
+SET anniversary_published_year = last_anniversary_year
+WHERE last_anniversary_year IS NOT NULL;
+
+-- 待投递记录扫描
+CREATE INDEX IF NOT EXISTS idx_user_registrations_unpublished
+    ON user_registrations (user_id)
+    WHERE last_anniversary_year IS NOT NULL
+      AND (anniversary_published_year IS NULL OR anniversary_published_year < last_anniversary_year);
+
+-- ==================== 调度器检查点 ====================
+
+CREATE TABLE IF NOT EXISTS scheduler_checkpoints (
+    name VARCHAR(100) PRIMARY KEY,
+    last_completed_date DATE NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE scheduler_checkpoints IS '按日扫描的调度器检查点，重启后从最后完成日期的次日续扫';
+COMMENT ON COLUMN scheduler_checkpoints.last_completed_date IS '已完整扫描的最后日期（UTC）';
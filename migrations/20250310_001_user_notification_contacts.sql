@@ -0,0 +1,24 @@
+-- 用户通知联系方式
+-- 通知生产方只携带用户 ID，notification-worker 和 badge-management-service
+-- 分发前按用户 ID 查询收件人，写入通知数据中对应的字段
+
+CREATE TABLE IF NOT EXISTS user_notification_contacts (
+    user_id VARCHAR(100) PRIMARY KEY,
+    email VARCHAR(255),
+    phone VARCHAR(32),
+    wechat_openid VARCHAR(128),
+    device_token VARCHAR(512),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE user_notification_contacts IS '用户通知联系方式，由注册事件携带的字段或管理后台导入写入';
+COMMENT ON COLUMN user_notification_contacts.email IS '邮件通知收件人地址';
+COMMENT ON COLUMN user_notification_contacts.phone IS '短信通知手机号';
+COMMENT ON COLUMN user_notification_contacts.wechat_openid IS '微信订阅消息接收人 openid';
+COMMENT ON COLUMN user_notification_contacts.device_token IS 'APP 推送设备 token';
+
+DROP TRIGGER IF EXISTS update_user_notification_contacts_updated_at ON user_notification_contacts;
+CREATE TRIGGER update_user_notification_contacts_updated_at
+    BEFORE UPDATE ON user_notification_contacts
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,16 @@
+-- 权益发放时的配置快照
+-- payload 保存 Handler 的发放结果（券码、积分数量等），发放时使用的权益配置
+-- 单独存入 benefit_config，供外部回调等 Handler 查询和撤销时恢复 callback_url
+
+ALTER TABLE benefit_grants
+ADD COLUMN IF NOT EXISTS benefit_config JSONB;
+
+COMMENT ON COLUMN benefit_grants.benefit_config IS '发放时使用的权益配置快照，如外部回调的 callback_url 和透传参数';
+
+-- 此前的版本把权益配置写入 payload，迁移到新列并清空 payload
+UPDATE benefit_grants
+SET benefit_config = payload,
+    payload = NULL
+WHERE benefit_config IS NULL
+  AND payload IS NOT NULL
+  AND benefit_id IN (SELECT id FROM benefits WHERE UPPER(benefit_type) = 'EXTERNAL_CALLBACK');
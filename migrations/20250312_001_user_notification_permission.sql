@@ -0,0 +1,18 @@
+-- 用户通知设置管理权限
+-- 查看沿用 user:view:read，修改用户通知偏好和联系方式需要 user:notification:write
+
+INSERT INTO permission (code, name, module, action, resource_pattern, description, sort_order) VALUES
+('user:notification:write', '管理用户通知设置', 'user', 'write', '/users/*/notification-*', '修改用户的通知渠道、免打扰时段、通知语言和通知联系方式', 620)
+ON CONFLICT (code) DO UPDATE SET
+    name = EXCLUDED.name,
+    module = EXCLUDED.module,
+    action = EXCLUDED.action,
+    resource_pattern = EXCLUDED.resource_pattern,
+    description = EXCLUDED.description,
+    sort_order = EXCLUDED.sort_order;
+
+INSERT INTO role_permission (role_id, permission_id)
+SELECT r.id, p.id
+FROM role r, permission p
+WHERE r.code IN ('admin', 'operator') AND p.code = 'user:notification:write'
+ON CONFLICT DO NOTHING;
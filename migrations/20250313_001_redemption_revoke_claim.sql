@@ -0,0 +1,8 @@
+-- 兑换订单取消：记录权益撤销开始时间
+-- 撤销外部权益期间不持有订单行锁，取消请求先在短事务内写入该时间占用撤销，
+-- 并发的取消请求在租约内直接返回冲突；撤销结束后清空，进程中断时租约过期后可重新占用
+
+ALTER TABLE redemption_orders
+ADD COLUMN IF NOT EXISTS revoke_started_at TIMESTAMPTZ;
+
+COMMENT ON COLUMN redemption_orders.revoke_started_at IS '权益撤销开始时间，为空表示当前没有进行中的撤销';
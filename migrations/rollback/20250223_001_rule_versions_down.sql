@@ -0,0 +1,3 @@
+-- 回滚 20250223_001_rule_versions
+DROP TABLE IF EXISTS badge_rule_versions CASCADE;
+ALTER TABLE badge_rules DROP COLUMN IF EXISTS current_version;
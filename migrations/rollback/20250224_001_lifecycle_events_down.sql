@@ -0,0 +1,5 @@
+-- 回滚 20250224_001_lifecycle_events
+DROP TABLE IF EXISTS user_registrations CASCADE;
+DELETE FROM event_types
+WHERE code IN ('registration', 'membership_upgrade', 'anniversary', 'seasonal_activity', 'campaign_participation')
+  AND NOT EXISTS (SELECT 1 FROM badge_rules r WHERE r.event_type = event_types.code);
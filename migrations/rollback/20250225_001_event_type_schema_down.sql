@@ -0,0 +1,2 @@
+-- 回滚 20250225_001_event_type_schema
+ALTER TABLE event_types DROP COLUMN IF EXISTS payload_schema;
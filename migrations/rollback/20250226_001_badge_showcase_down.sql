@@ -0,0 +1,4 @@
+-- 回滚 20250226_001_badge_showcase
+DROP INDEX IF EXISTS idx_user_badges_pinned;
+ALTER TABLE user_badges DROP COLUMN IF EXISTS pinned_at;
+ALTER TABLE user_badges DROP COLUMN IF EXISTS pin_order;
@@ -0,0 +1,5 @@
+-- 回滚 20250227_001_notification_task_dispatch
+DROP INDEX IF EXISTS idx_notification_tasks_dispatch;
+ALTER TABLE notification_tasks DROP COLUMN IF EXISTS next_retry_at;
+ALTER TABLE notification_tasks DROP COLUMN IF EXISTS retry_interval_seconds;
+ALTER TABLE notification_tasks DROP COLUMN IF EXISTS channel_results;
@@ -0,0 +1,4 @@
+-- 回滚 20250228_001_notification_preferences
+DROP TABLE IF EXISTS deferred_notifications;
+DROP TRIGGER IF EXISTS update_user_notification_preferences_updated_at ON user_notification_preferences;
+DROP TABLE IF EXISTS user_notification_preferences;
@@ -0,0 +1,3 @@
+-- 回滚 20250301_001_notification_templates
+DROP TRIGGER IF EXISTS update_notification_templates_updated_at ON notification_templates;
+DROP TABLE IF EXISTS notification_templates;
@@ -0,0 +1,4 @@
+-- 回滚 20250302_001_dead_letter_messages
+DELETE FROM permission WHERE code IN ('system:dlq:read', 'system:dlq:write');
+DROP TRIGGER IF EXISTS update_dead_letter_messages_updated_at ON dead_letter_messages;
+DROP TABLE IF EXISTS dead_letter_messages;
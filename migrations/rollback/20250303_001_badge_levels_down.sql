@@ -0,0 +1,3 @@
+-- 回滚 20250303_001_badge_levels
+ALTER TABLE user_badges DROP COLUMN IF EXISTS level;
+ALTER TABLE badges DROP COLUMN IF EXISTS levels;
@@ -0,0 +1,3 @@
+-- 回滚 20250304_001_badge_score
+ALTER TABLE badges DROP CONSTRAINT IF EXISTS badges_score_non_negative;
+ALTER TABLE badges DROP COLUMN IF EXISTS score;
@@ -0,0 +1,10 @@
+-- 回滚 20250305_001_dependency_revoke_policy
+-- 暂停状态的用户徽章恢复为有效，避免回滚后无法处理
+UPDATE user_badges SET status = 'active', updated_at = NOW() WHERE status = 'suspended';
+
+ALTER TABLE cascade_evaluation_logs DROP COLUMN IF EXISTS affected_badges;
+ALTER TABLE cascade_evaluation_logs DROP COLUMN IF EXISTS direction;
+
+DROP INDEX IF EXISTS idx_badge_deps_revoke_policy;
+ALTER TABLE badge_dependencies DROP CONSTRAINT IF EXISTS badge_dependencies_revoke_policy_check;
+ALTER TABLE badge_dependencies DROP COLUMN IF EXISTS revoke_policy;
@@ -0,0 +1,4 @@
+-- 回滚 20250306_001_redemption_recipe
+-- 配置了配方的规则回滚后只按 required_badges 兑换，需人工核对
+ALTER TABLE redemption_details DROP COLUMN IF EXISTS recipe_term;
+ALTER TABLE badge_redemption_rules DROP COLUMN IF EXISTS recipe;
@@ -0,0 +1,7 @@
+-- 回滚 20250307_001_redemption_cancellation
+-- 已取消订单的取消信息会丢失，订单状态仍为 cancelled
+ALTER TABLE redemption_details DROP COLUMN IF EXISTS reversed_at;
+ALTER TABLE redemption_orders
+DROP COLUMN IF EXISTS cancelled_by,
+DROP COLUMN IF EXISTS cancel_reason,
+DROP COLUMN IF EXISTS cancelled_at;
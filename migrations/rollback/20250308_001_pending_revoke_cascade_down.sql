@@ -0,0 +1,3 @@
+-- 回滚 20250308_001_pending_revoke_cascade
+DROP INDEX IF EXISTS idx_pending_revoke_cascades_due;
+DROP TABLE IF EXISTS pending_revoke_cascades;
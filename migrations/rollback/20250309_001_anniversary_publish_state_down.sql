@@ -0,0 +1,4 @@
+-- 回滚 20250309_001_anniversary_publish_state
+DROP TABLE IF EXISTS scheduler_checkpoints;
+DROP INDEX IF EXISTS idx_user_registrations_unpublished;
+ALTER TABLE user_registrations DROP COLUMN IF EXISTS anniversary_published_year;
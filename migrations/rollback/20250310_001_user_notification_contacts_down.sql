@@ -0,0 +1,3 @@
+-- 回滚 20250310_001_user_notification_contacts
+DROP TRIGGER IF EXISTS update_user_notification_contacts_updated_at ON user_notification_contacts;
+DROP TABLE IF EXISTS user_notification_contacts;
@@ -0,0 +1,2 @@
+-- 回滚 20250311_001_benefit_grant_config
+ALTER TABLE benefit_grants DROP COLUMN IF EXISTS benefit_config;
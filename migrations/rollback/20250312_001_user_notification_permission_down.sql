@@ -0,0 +1,2 @@
+-- 回滚 20250312_001_user_notification_permission
+DELETE FROM permission WHERE code = 'user:notification:write';
@@ -0,0 +1,2 @@
+-- 回滚 20250313_001_redemption_revoke_claim
+ALTER TABLE redemption_orders DROP COLUMN IF EXISTS revoke_started_at;
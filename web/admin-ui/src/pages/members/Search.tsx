@@ -178,6 +178,7 @@ const BadgeListItem: React.FC<{
           </div>
         </Space>
         <Space>
+          {badge.pinned && <Tag color="gold">置顶 #{badge.pinOrder}</Tag>}
           <Text type="secondary">x{badge.quantity}</Text>
           <BadgeStatusTag status={badge.status} />
         </Space>
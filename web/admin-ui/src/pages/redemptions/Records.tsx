@@ -33,6 +33,7 @@ const ORDER_STATUS_MAP: Record<RedemptionOrderStatus, { text: string; color: str
   PROCESSING: { text: '处理中', color: 'warning' },
   COMPLETED: { text: '已完成', color: 'success' },
   FAILED: { text: '失败', color: 'error' },
+  CANCELLING: { text: '取消中', color: 'warning' },
   CANCELLED: { text: '已取消', color: 'default' },
 };
 
@@ -110,6 +110,7 @@ export type RedemptionOrderStatus =
   | 'PROCESSING'
   | 'COMPLETED'
   | 'FAILED'
+  | 'CANCELLING'
   | 'CANCELLED';
 
 /**
@@ -87,6 +87,10 @@ export interface UserBadgeDetail {
   revokedReason?: string;
   /** 操作人 */
   operatorName?: string;
+  /** 是否置顶在用户徽章墙上 */
+  pinned?: boolean;
+  /** 置顶展示顺序（从 1 开始） */
+  pinOrder?: number | null;
 }
 
 /**